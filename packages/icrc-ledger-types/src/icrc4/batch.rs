@@ -0,0 +1,107 @@
+use candid::{CandidType, Deserialize, Nat};
+use serde::Serialize;
+use std::fmt;
+
+use super::super::icrc1::account::Account;
+use super::super::icrc1::transfer::{BlockIndex, NumTokens, TransferArg, TransferError};
+
+/// The arguments for the `icrc4_transfer_batch` endpoint.
+pub type TransferBatchArgs = Vec<TransferArg>;
+
+/// The result of a single transfer of an `icrc4_transfer_batch` call.
+pub type TransferBatchResult = Result<BlockIndex, TransferBatchError>;
+
+/// The results returned by the `icrc4_transfer_batch` endpoint, one for each
+/// transfer of the batch and in the same order. A result is `None` if the
+/// ledger did not process the corresponding transfer.
+pub type TransferBatchResults = Vec<Option<TransferBatchResult>>;
+
+/// Errors defined for a single transfer of the `icrc4_transfer_batch` endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum TransferBatchError {
+    BadFee { expected_fee: NumTokens },
+    BadBurn { min_burn_amount: NumTokens },
+    InsufficientFunds { balance: NumTokens },
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    TooManyRequests { limit: Nat },
+    Duplicate { duplicate_of: BlockIndex },
+    TemporarilyUnavailable,
+    GenericError { error_code: Nat, message: String },
+}
+
+impl From<TransferError> for TransferBatchError {
+    fn from(err: TransferError) -> Self {
+        match err {
+            TransferError::BadFee { expected_fee } => Self::BadFee { expected_fee },
+            TransferError::BadBurn { min_burn_amount } => Self::BadBurn { min_burn_amount },
+            TransferError::InsufficientFunds { balance } => Self::InsufficientFunds { balance },
+            TransferError::TooOld => Self::TooOld,
+            TransferError::CreatedInFuture { ledger_time } => Self::CreatedInFuture { ledger_time },
+            TransferError::TemporarilyUnavailable => Self::TemporarilyUnavailable,
+            TransferError::Duplicate { duplicate_of } => Self::Duplicate { duplicate_of },
+            TransferError::GenericError {
+                error_code,
+                message,
+            } => Self::GenericError {
+                error_code,
+                message,
+            },
+        }
+    }
+}
+
+impl fmt::Display for TransferBatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyRequests { limit } => write!(
+                f,
+                "the batch contains too many transfers, the maximum is {}",
+                limit
+            ),
+            Self::BadFee { expected_fee } => TransferError::BadFee {
+                expected_fee: expected_fee.clone(),
+            }
+            .fmt(f),
+            Self::BadBurn { min_burn_amount } => TransferError::BadBurn {
+                min_burn_amount: min_burn_amount.clone(),
+            }
+            .fmt(f),
+            Self::InsufficientFunds { balance } => TransferError::InsufficientFunds {
+                balance: balance.clone(),
+            }
+            .fmt(f),
+            Self::TooOld => TransferError::TooOld.fmt(f),
+            Self::CreatedInFuture { ledger_time } => TransferError::CreatedInFuture {
+                ledger_time: *ledger_time,
+            }
+            .fmt(f),
+            Self::Duplicate { duplicate_of } => TransferError::Duplicate {
+                duplicate_of: duplicate_of.clone(),
+            }
+            .fmt(f),
+            Self::TemporarilyUnavailable => TransferError::TemporarilyUnavailable.fmt(f),
+            Self::GenericError {
+                error_code,
+                message,
+            } => write!(f, "{} {}", error_code, message),
+        }
+    }
+}
+
+/// The arguments for the `icrc4_balance_of_batch` endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BalanceQueryArgs {
+    pub accounts: Vec<Account>,
+}
+
+/// The balance of an account returned by the `icrc4_balance_of_batch` endpoint.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AccountBalance {
+    pub account: Account,
+    pub balance: NumTokens,
+}
+
+/// The balances returned by the `icrc4_balance_of_batch` endpoint, in the
+/// order of the queried accounts.
+pub type BalanceQueryResult = Vec<AccountBalance>;
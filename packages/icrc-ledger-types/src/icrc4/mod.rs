@@ -0,0 +1,4 @@
+//! The [ICRC-4](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-4/ICRC-4.md)
+//! batch transfer standard.
+
+pub mod batch;
@@ -6,3 +6,4 @@ pub mod icrc103;
 pub mod icrc2;
 pub mod icrc21;
 pub mod icrc3;
+pub mod icrc4;
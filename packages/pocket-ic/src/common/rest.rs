@@ -2,7 +2,7 @@
 //! The types in this module are used to serialize and deserialize data
 //! from and to JSON, and are used by both crates.
 
-use crate::RejectResponse;
+use crate::{RejectCode, RejectResponse};
 use candid::Principal;
 use hex;
 use reqwest::Response;
@@ -163,6 +163,170 @@ impl From<Result<Vec<u8>, RejectResponse>> for RawCanisterResult {
     }
 }
 
+/// Whether the result of a traced query was served from the query cache.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+pub enum QueryCacheOutcome {
+    /// The query cache is disabled or not applicable to the query.
+    Disabled,
+    /// The result was served from the query cache and the query was not executed.
+    Hit,
+    /// The query was executed and its result was added to the query cache.
+    Miss,
+}
+
+/// The outcome of a query call in the call tree of a traced query.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+pub enum QueryCallOutcome {
+    Reply,
+    Reject {
+        reject_code: RejectCode,
+        reject_message: String,
+    },
+    /// The query failed, e.g., because it exceeded a limit of the query call graph.
+    /// Only the root of the call tree can fail.
+    Error(RejectResponse),
+    /// The evaluation of the call graph was aborted before the callee responded.
+    Unfinished,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawQueryCallTraceNode {
+    pub canister_id: RawCanisterId,
+    pub method: String,
+    pub instructions: u64,
+    pub response_size: u64,
+    pub outcome: QueryCallOutcome,
+    pub calls: Vec<RawQueryCallTraceNode>,
+}
+
+/// A query call in the call tree of a traced query.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct QueryCallTraceNode {
+    pub canister_id: Principal,
+    pub method: String,
+    /// The instructions used by the query method and its response callbacks,
+    /// excluding the instructions of the nested calls.
+    pub instructions: u64,
+    /// The size of the reply payload or of the reject message in bytes.
+    pub response_size: u64,
+    pub outcome: QueryCallOutcome,
+    /// The query calls made by the callee in the order they were executed.
+    pub calls: Vec<QueryCallTraceNode>,
+}
+
+impl From<RawQueryCallTraceNode> for QueryCallTraceNode {
+    fn from(raw_node: RawQueryCallTraceNode) -> Self {
+        Self {
+            canister_id: raw_node.canister_id.into(),
+            method: raw_node.method,
+            instructions: raw_node.instructions,
+            response_size: raw_node.response_size,
+            outcome: raw_node.outcome,
+            calls: raw_node.calls.into_iter().map(|call| call.into()).collect(),
+        }
+    }
+}
+
+impl From<QueryCallTraceNode> for RawQueryCallTraceNode {
+    fn from(node: QueryCallTraceNode) -> Self {
+        Self {
+            canister_id: node.canister_id.into(),
+            method: node.method,
+            instructions: node.instructions,
+            response_size: node.response_size,
+            outcome: node.outcome,
+            calls: node.calls.into_iter().map(|call| call.into()).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawQueryCallTrace {
+    pub cache: QueryCacheOutcome,
+    pub root: Option<RawQueryCallTraceNode>,
+    pub call_graph_depth: u64,
+    pub instructions_used: u64,
+    pub max_call_graph_depth: u64,
+    pub max_call_graph_instructions: u64,
+    pub max_instructions_per_query: u64,
+}
+
+/// The trace of a query: its call tree and how close it came to the limits of the query call graph.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct QueryCallTrace {
+    pub cache: QueryCacheOutcome,
+    /// The root of the call tree, i.e., the query call made by the test driver.
+    /// It is `None` if the query was not executed, e.g., on a query cache hit.
+    pub root: Option<QueryCallTraceNode>,
+    /// The maximum number of nested query calls.
+    pub call_graph_depth: u64,
+    /// The instructions used by the whole call tree including the overhead charged per query call.
+    pub instructions_used: u64,
+    pub max_call_graph_depth: u64,
+    pub max_call_graph_instructions: u64,
+    pub max_instructions_per_query: u64,
+}
+
+impl From<RawQueryCallTrace> for QueryCallTrace {
+    fn from(raw_trace: RawQueryCallTrace) -> Self {
+        Self {
+            cache: raw_trace.cache,
+            root: raw_trace.root.map(|root| root.into()),
+            call_graph_depth: raw_trace.call_graph_depth,
+            instructions_used: raw_trace.instructions_used,
+            max_call_graph_depth: raw_trace.max_call_graph_depth,
+            max_call_graph_instructions: raw_trace.max_call_graph_instructions,
+            max_instructions_per_query: raw_trace.max_instructions_per_query,
+        }
+    }
+}
+
+impl From<QueryCallTrace> for RawQueryCallTrace {
+    fn from(trace: QueryCallTrace) -> Self {
+        Self {
+            cache: trace.cache,
+            root: trace.root.map(|root| root.into()),
+            call_graph_depth: trace.call_graph_depth,
+            instructions_used: trace.instructions_used,
+            max_call_graph_depth: trace.max_call_graph_depth,
+            max_call_graph_instructions: trace.max_call_graph_instructions,
+            max_instructions_per_query: trace.max_instructions_per_query,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawTracedCanisterResult {
+    pub result: RawCanisterResult,
+    pub trace: RawQueryCallTrace,
+}
+
+/// Coverage of a single basic block of a canister's Wasm module.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CoverageBlock {
+    /// Index of the function in the function index space of the module.
+    pub function_index: u32,
+    /// Name of the function from the `name` section (if any).
+    pub function_name: Option<String>,
+    /// Offset of the first instruction of the block relative to the start of the code section.
+    pub code_offset: u64,
+    /// Source file of the first instruction of the block from the DWARF debug information (if any).
+    pub file: Option<String>,
+    /// Source line of the first instruction of the block from the DWARF debug information (if any).
+    pub line: Option<u64>,
+    /// Number of times the block was executed.
+    pub hits: u64,
+}
+
+/// Wasm-level code coverage of a canister collected since its code was last installed,
+/// reinstalled, or upgraded.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CoverageReport {
+    pub blocks: Vec<CoverageBlock>,
+    /// The report in the LCOV tracefile format.
+    pub lcov: String,
+}
+
 impl From<RawCanisterResult> for Result<Vec<u8>, RejectResponse> {
     fn from(result: RawCanisterResult) -> Self {
         match result {
@@ -544,6 +708,21 @@ pub struct InstanceConfig {
     pub nonmainnet_features: bool,
     pub log_level: Option<String>,
     pub bitcoind_addr: Option<Vec<SocketAddr>>,
+    /// If set, every state-changing operation applied to the instance is appended to the operation log
+    /// at this path (on the machine running the PocketIC server).
+    pub operation_log: Option<PathBuf>,
+    /// If set, canisters are instrumented to collect Wasm-level code coverage
+    /// when their code is installed.
+    #[serde(default)]
+    pub wasm_coverage: bool,
+}
+
+/// Configuration for creating a new instance by replaying an operation log
+/// recorded via [`InstanceConfig::operation_log`].
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayInstanceConfig {
+    /// Path to the operation log (on the machine running the PocketIC server).
+    pub operation_log: PathBuf,
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, Default, JsonSchema)]
@@ -792,6 +971,9 @@ pub enum CanisterHttpMethod {
     GET,
     POST,
     HEAD,
+    PUT,
+    PATCH,
+    DELETE,
 }
 
 #[derive(
@@ -54,16 +54,20 @@
 ///
 use crate::{
     common::rest::{
-        BlobCompression, BlobId, CanisterHttpRequest, ExtendedSubnetConfigSet, HttpsConfig,
-        InstanceId, MockCanisterHttpResponse, RawEffectivePrincipal, RawMessageId, SubnetId,
-        SubnetKind, SubnetSpec, Topology,
+        BlobCompression, BlobId, CanisterHttpRequest, CoverageReport, ExtendedSubnetConfigSet,
+        HttpsConfig, InstanceId, MockCanisterHttpResponse, QueryCallTrace, RawEffectivePrincipal,
+        RawMessageId, SubnetId, SubnetKind, SubnetSpec, Topology,
     },
     nonblocking::PocketIc as PocketIcAsync,
+    snapshot_archive::{
+        CanisterSnapshotDataKind, CanisterSnapshotDataOffset, CanisterSnapshotMetadata,
+        SnapshotArchiveError, UploadCanisterSnapshotMetadataArgs,
+    },
 };
 use candid::{
     decode_args, encode_args,
     utils::{ArgumentDecoder, ArgumentEncoder},
-    Principal,
+    CandidType, Principal,
 };
 use flate2::read::GzDecoder;
 use ic_management_canister_types::{
@@ -80,7 +84,7 @@ use std::os::unix::fs::OpenOptionsExt;
 use std::{
     fs::OpenOptions,
     net::{IpAddr, SocketAddr},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::Command,
     sync::{mpsc::channel, Arc},
     thread,
@@ -97,6 +101,7 @@ use wslpath::windows_to_wsl;
 
 pub mod common;
 pub mod nonblocking;
+pub mod snapshot_archive;
 
 pub const EXPECTED_SERVER_VERSION: &str = "9.0.2";
 
@@ -160,6 +165,9 @@ pub struct PocketIcBuilder {
     nonmainnet_features: bool,
     log_level: Option<Level>,
     bitcoind_addr: Option<Vec<SocketAddr>>,
+    operation_log: Option<PathBuf>,
+    replayed_operation_log: Option<PathBuf>,
+    wasm_coverage: bool,
 }
 
 #[allow(clippy::new_without_default)]
@@ -175,6 +183,9 @@ impl PocketIcBuilder {
             nonmainnet_features: false,
             log_level: None,
             bitcoind_addr: None,
+            operation_log: None,
+            replayed_operation_log: None,
+            wasm_coverage: false,
         }
     }
 
@@ -195,6 +206,9 @@ impl PocketIcBuilder {
             self.nonmainnet_features,
             self.log_level,
             self.bitcoind_addr,
+            self.operation_log,
+            self.replayed_operation_log,
+            self.wasm_coverage,
         )
     }
 
@@ -209,6 +223,9 @@ impl PocketIcBuilder {
             self.nonmainnet_features,
             self.log_level,
             self.bitcoind_addr,
+            self.operation_log,
+            self.replayed_operation_log,
+            self.wasm_coverage,
         )
         .await
     }
@@ -266,6 +283,30 @@ impl PocketIcBuilder {
         }
     }
 
+    /// Record all state-changing operations applied to the PocketIC instance (e.g., ingress messages,
+    /// ticks, time changes) to an operation log at the given path.
+    /// Note that the provided path must be accessible for the PocketIC server process.
+    pub fn with_operation_log(mut self, operation_log: PathBuf) -> Self {
+        self.operation_log = Some(operation_log);
+        self
+    }
+
+    /// Create the PocketIC instance by replaying an operation log recorded via [`Self::with_operation_log`].
+    /// The instance is created with the configuration of the recorded instance
+    /// and thus the subnet configuration of this builder is ignored.
+    /// Note that the provided path must be accessible for the PocketIC server process.
+    pub fn with_replayed_operation_log(mut self, operation_log: PathBuf) -> Self {
+        self.replayed_operation_log = Some(operation_log);
+        self
+    }
+
+    /// Instrument canisters to collect Wasm-level code coverage when their code is installed,
+    /// see [`PocketIc::coverage_report`].
+    pub fn with_wasm_coverage(mut self) -> Self {
+        self.wasm_coverage = true;
+        self
+    }
+
     /// Add an empty NNS subnet unless an NNS subnet has already been added.
     pub fn with_nns_subnet(mut self) -> Self {
         let mut config = self.config.unwrap_or_default();
@@ -514,6 +555,9 @@ impl PocketIc {
         nonmainnet_features: bool,
         log_level: Option<Level>,
         bitcoind_addr: Option<Vec<SocketAddr>>,
+        operation_log: Option<PathBuf>,
+        replayed_operation_log: Option<PathBuf>,
+        wasm_coverage: bool,
     ) -> Self {
         let (tx, rx) = channel();
         let thread = thread::spawn(move || {
@@ -536,6 +580,9 @@ impl PocketIc {
                 nonmainnet_features,
                 log_level,
                 bitcoind_addr,
+                operation_log,
+                replayed_operation_log,
+                wasm_coverage,
             )
             .await
         });
@@ -780,6 +827,16 @@ impl PocketIc {
         runtime.block_on(async { self.pocket_ic.get_controllers(canister_id).await })
     }
 
+    /// Get the Wasm-level code coverage of a canister collected since its code was last installed,
+    /// reinstalled, or upgraded.
+    /// Panics if the canister does not exist or if the instance was not created
+    /// with [`PocketIcBuilder::with_wasm_coverage`] when the canister code was installed.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string()))]
+    pub fn coverage_report(&self, canister_id: CanisterId) -> CoverageReport {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.coverage_report(canister_id).await })
+    }
+
     /// Get the current cycles balance of a canister.
     #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string()))]
     pub fn cycle_balance(&self, canister_id: CanisterId) -> u128 {
@@ -905,6 +962,26 @@ impl PocketIc {
         })
     }
 
+    /// Execute a query call on a canister and additionally return the trace of its query call tree:
+    /// the callee, method, instructions, and response size of every (composite) query call,
+    /// whether the result was served from the query cache, and how close the query came to the limits
+    /// of the query call graph. This is useful to debug composite queries failing with a limit error.
+    #[instrument(skip(self, payload), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.to_string(), method = %method, payload_len = %payload.len()))]
+    pub fn query_call_with_trace(
+        &self,
+        canister_id: CanisterId,
+        sender: Principal,
+        method: &str,
+        payload: Vec<u8>,
+    ) -> (Result<Vec<u8>, RejectResponse>, QueryCallTrace) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .query_call_with_trace(canister_id, sender, method, payload)
+                .await
+        })
+    }
+
     /// Fetch canister logs via a query call to the management canister.
     pub fn fetch_canister_logs(
         &self,
@@ -919,6 +996,23 @@ impl PocketIc {
         })
     }
 
+    /// Fetch canister logs matching the given filter via a query call to the management canister.
+    /// At most `max_records` log records are returned if specified.
+    pub fn fetch_canister_logs_with_filter(
+        &self,
+        canister_id: CanisterId,
+        sender: Principal,
+        filter: CanisterLogFilter,
+        max_records: Option<u64>,
+    ) -> Result<CanisterLogsPage, RejectResponse> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .fetch_canister_logs_with_filter(canister_id, sender, filter, max_records)
+                .await
+        })
+    }
+
     /// Request a canister's status.
     #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
     pub fn canister_status(
@@ -1182,6 +1276,109 @@ impl PocketIc {
         })
     }
 
+    /// Read the metadata of a canister snapshot.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub fn read_canister_snapshot_metadata(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        snapshot_id: Vec<u8>,
+    ) -> Result<CanisterSnapshotMetadata, RejectResponse> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .read_canister_snapshot_metadata(canister_id, sender, snapshot_id)
+                .await
+        })
+    }
+
+    /// Read a chunk of the data of a canister snapshot.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub fn read_canister_snapshot_data(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        snapshot_id: Vec<u8>,
+        kind: CanisterSnapshotDataKind,
+    ) -> Result<Vec<u8>, RejectResponse> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .read_canister_snapshot_data(canister_id, sender, snapshot_id, kind)
+                .await
+        })
+    }
+
+    /// Upload the metadata of a canister snapshot and return the ID of the new snapshot.
+    #[instrument(skip(self, args), fields(instance_id=self.pocket_ic.instance_id, canister_id = %args.canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub fn upload_canister_snapshot_metadata(
+        &self,
+        sender: Option<Principal>,
+        args: UploadCanisterSnapshotMetadataArgs,
+    ) -> Result<Vec<u8>, RejectResponse> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .upload_canister_snapshot_metadata(sender, args)
+                .await
+        })
+    }
+
+    /// Upload a chunk of the data of a canister snapshot.
+    #[instrument(skip(self, chunk), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub fn upload_canister_snapshot_data(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        snapshot_id: Vec<u8>,
+        kind: CanisterSnapshotDataOffset,
+        chunk: Vec<u8>,
+    ) -> Result<(), RejectResponse> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .upload_canister_snapshot_data(canister_id, sender, snapshot_id, kind, chunk)
+                .await
+        })
+    }
+
+    /// Export a canister snapshot into a snapshot archive file
+    /// (see [`snapshot_archive`] for the archive format).
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub fn export_canister_snapshot(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        snapshot_id: Vec<u8>,
+        archive: &Path,
+    ) -> Result<(), SnapshotArchiveError> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .export_canister_snapshot(canister_id, sender, snapshot_id, archive)
+                .await
+        })
+    }
+
+    /// Import a canister snapshot from a snapshot archive file
+    /// (see [`snapshot_archive`] for the archive format)
+    /// and return the ID of the new snapshot.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub fn import_canister_snapshot(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        replace_snapshot: Option<Vec<u8>>,
+        archive: &Path,
+    ) -> Result<Vec<u8>, SnapshotArchiveError> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .import_canister_snapshot(canister_id, sender, replace_snapshot, archive)
+                .await
+        })
+    }
+
     /// Update canister settings.
     #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
     pub fn update_canister_settings(
@@ -1743,6 +1940,51 @@ pub enum IngressStatusResult {
     Success(Result<Vec<u8>, RejectResponse>),
 }
 
+/// A half-open range `[start, end)` of canister log record indices or timestamps.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, CandidType, Deserialize)]
+pub struct CanisterLogRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl CanisterLogRange {
+    /// A range including all values starting from `start`.
+    pub fn starting_at(start: u64) -> Self {
+        Self {
+            start,
+            end: u64::MAX,
+        }
+    }
+}
+
+/// A filter on the content of canister log records.
+#[derive(Clone, Debug, PartialEq, Eq, CandidType, Deserialize)]
+pub enum CanisterLogContentFilter {
+    #[serde(rename = "substring")]
+    Substring(String),
+    #[serde(rename = "regex")]
+    Regex(String),
+}
+
+/// A filter on canister log records used in [`PocketIc::fetch_canister_logs_with_filter`].
+/// A log record is returned only if it matches all the specified conditions.
+#[derive(Clone, Debug, Default, PartialEq, Eq, CandidType, Deserialize)]
+pub struct CanisterLogFilter {
+    pub idx_range: Option<CanisterLogRange>,
+    pub timestamp_nanos_range: Option<CanisterLogRange>,
+    pub content: Option<CanisterLogContentFilter>,
+}
+
+/// A page of canister log records returned by [`PocketIc::fetch_canister_logs_with_filter`].
+/// The index `next_idx` can be used as the start of the index range in a subsequent call
+/// to only fetch log records that have not been returned yet.
+/// It is `None` if the PocketIC server does not support filtering canister logs.
+#[derive(Clone, Debug, PartialEq, Eq, CandidType, Deserialize)]
+pub struct CanisterLogsPage {
+    pub canister_log_records: Vec<CanisterLogRecord>,
+    pub next_idx: Option<u64>,
+}
+
 #[cfg(windows)]
 fn wsl_path(path: &PathBuf, desc: &str) -> String {
     windows_to_wsl(
@@ -1,25 +1,34 @@
 use crate::common::rest::{
-    ApiResponse, AutoProgressConfig, BlobCompression, BlobId, CanisterHttpRequest,
+    ApiResponse, AutoProgressConfig, BlobCompression, BlobId, CanisterHttpRequest, CoverageReport,
     CreateHttpGatewayResponse, CreateInstanceResponse, ExtendedSubnetConfigSet, HttpGatewayBackend,
     HttpGatewayConfig, HttpGatewayInfo, HttpsConfig, InstanceConfig, InstanceId,
-    MockCanisterHttpResponse, RawAddCycles, RawCanisterCall, RawCanisterHttpRequest, RawCanisterId,
-    RawCanisterResult, RawCycles, RawEffectivePrincipal, RawIngressStatusArgs, RawMessageId,
-    RawMockCanisterHttpResponse, RawPrincipalId, RawSetStableMemory, RawStableMemory, RawSubnetId,
-    RawTime, RawVerifyCanisterSigArg, SubnetId, TickConfigs, Topology,
+    MockCanisterHttpResponse, QueryCallTrace, RawAddCycles, RawCanisterCall,
+    RawCanisterHttpRequest, RawCanisterId, RawCanisterResult, RawCycles, RawEffectivePrincipal,
+    RawIngressStatusArgs, RawMessageId, RawMockCanisterHttpResponse, RawPrincipalId,
+    RawSetStableMemory, RawStableMemory, RawSubnetId, RawTime, RawTracedCanisterResult,
+    RawVerifyCanisterSigArg, ReplayInstanceConfig, SubnetId, TickConfigs, Topology,
+};
+use crate::snapshot_archive::{
+    CanisterSnapshotDataKind, CanisterSnapshotDataOffset, CanisterSnapshotMetadata,
+    ReadCanisterSnapshotDataArgs, ReadCanisterSnapshotDataResponse,
+    ReadCanisterSnapshotMetadataArgs, SnapshotArchiveError, SnapshotArchiveReader,
+    SnapshotArchiveSection, SnapshotArchiveSectionKind, SnapshotArchiveWriter,
+    UploadCanisterSnapshotDataArgs, UploadCanisterSnapshotMetadataArgs,
+    UploadCanisterSnapshotMetadataResponse, MAX_WASM_CHUNK_SIZE, SNAPSHOT_DATA_CHUNK_SIZE,
 };
 #[cfg(windows)]
 use crate::wsl_path;
 pub use crate::DefaultEffectiveCanisterIdError;
 use crate::{
-    copy_dir, start_or_reuse_server, IngressStatusResult, PocketIcBuilder, PocketIcState,
-    RejectResponse, Time,
+    copy_dir, start_or_reuse_server, CanisterLogFilter, CanisterLogsPage, IngressStatusResult,
+    PocketIcBuilder, PocketIcState, RejectResponse, Time,
 };
 use backoff::backoff::Backoff;
 use backoff::{ExponentialBackoff, ExponentialBackoffBuilder};
 use candid::{
     decode_args, encode_args,
     utils::{ArgumentDecoder, ArgumentEncoder},
-    Principal,
+    CandidType, Principal,
 };
 use ic_certification::{Certificate, Label, LookupResult};
 use ic_management_canister_types::{
@@ -40,8 +49,9 @@ use sha2::{Digest, Sha256};
 use slog::Level;
 use std::fs::{read_dir, File};
 use std::future::Future;
+use std::io::{BufReader, BufWriter, Read};
 use std::net::{IpAddr, SocketAddr};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tracing::{debug, instrument, warn};
 use tracing_appender::non_blocking::WorkerGuard;
@@ -77,6 +87,15 @@ enum HttpMethod {
     Post,
 }
 
+// The argument of the management canister method `fetch_canister_logs`
+// including the optional filter and limit on the number of log records.
+#[derive(CandidType)]
+struct FetchCanisterLogsWithFilterArgs {
+    canister_id: CanisterId,
+    filter: Option<CanisterLogFilter>,
+    max_records: Option<u64>,
+}
+
 /// Main entry point for interacting with PocketIC.
 pub struct PocketIc {
     /// The unique ID of this PocketIC instance.
@@ -139,6 +158,9 @@ impl PocketIc {
         nonmainnet_features: bool,
         log_level: Option<Level>,
         bitcoind_addr: Option<Vec<SocketAddr>>,
+        operation_log: Option<PathBuf>,
+        replayed_operation_log: Option<PathBuf>,
+        wasm_coverage: bool,
     ) -> Self {
         let server_url = if let Some(server_url) = server_url {
             server_url
@@ -179,8 +201,8 @@ impl PocketIc {
 
         // if there is no topology to fetch from the state dir,
         // the topology will be derived from the provided subnet config set
-        // that we need to validate
-        if !has_topology {
+        // that we need to validate (unless the instance is created by replaying an operation log)
+        if !has_topology && replayed_operation_log.is_none() {
             subnet_config_set.validate().unwrap();
         }
 
@@ -195,15 +217,35 @@ impl PocketIc {
             nonmainnet_features,
             log_level: log_level.map(|l| l.to_string()),
             bitcoind_addr,
+            #[cfg(not(windows))]
+            operation_log,
+            #[cfg(windows)]
+            operation_log: operation_log
+                .as_ref()
+                .map(|operation_log| wsl_path(operation_log, "operation log").into()),
+            wasm_coverage,
         };
 
         let test_driver_pid = std::process::id();
         let log_guard = setup_tracing(test_driver_pid);
 
         let reqwest_client = reqwest::Client::new();
-        let instance_id = match reqwest_client
-            .post(server_url.join("instances").unwrap())
-            .json(&instance_config)
+        let request = if let Some(replayed_operation_log) = replayed_operation_log {
+            let replay_config = ReplayInstanceConfig {
+                #[cfg(not(windows))]
+                operation_log: replayed_operation_log,
+                #[cfg(windows)]
+                operation_log: wsl_path(&replayed_operation_log, "operation log").into(),
+            };
+            reqwest_client
+                .post(server_url.join("instances/replay").unwrap())
+                .json(&replay_config)
+        } else {
+            reqwest_client
+                .post(server_url.join("instances").unwrap())
+                .json(&instance_config)
+        };
+        let instance_id = match request
             .send()
             .await
             .expect("Failed to get result")
@@ -604,6 +646,22 @@ impl PocketIc {
         result.into_iter().map(|p| p.into()).collect()
     }
 
+    /// Get the Wasm-level code coverage of a canister collected since its code was last installed,
+    /// reinstalled, or upgraded.
+    /// Panics if the canister does not exist or if the instance was not created
+    /// with [`crate::PocketIcBuilder::with_wasm_coverage`] when the canister code was installed.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string()))]
+    pub async fn coverage_report(&self, canister_id: CanisterId) -> CoverageReport {
+        let endpoint = "read/get_coverage_report";
+        self.post(
+            endpoint,
+            RawCanisterId {
+                canister_id: canister_id.as_slice().to_vec(),
+            },
+        )
+        .await
+    }
+
     /// Get the current cycles balance of a canister.
     #[instrument(ret, skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string()))]
     pub async fn cycle_balance(&self, canister_id: CanisterId) -> u128 {
@@ -791,6 +849,30 @@ impl PocketIc {
         .await
     }
 
+    /// Execute a query call on a canister and additionally return the trace of its query call tree:
+    /// the callee, method, instructions, and response size of every (composite) query call,
+    /// whether the result was served from the query cache, and how close the query came to the limits
+    /// of the query call graph. This is useful to debug composite queries failing with a limit error.
+    #[instrument(skip(self, payload), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.to_string(), method = %method, payload_len = %payload.len()))]
+    pub async fn query_call_with_trace(
+        &self,
+        canister_id: CanisterId,
+        sender: Principal,
+        method: &str,
+        payload: Vec<u8>,
+    ) -> (Result<Vec<u8>, RejectResponse>, QueryCallTrace) {
+        let endpoint = "read/query_with_trace";
+        let raw_canister_call = RawCanisterCall {
+            sender: sender.as_slice().to_vec(),
+            canister_id: canister_id.as_slice().to_vec(),
+            method: method.to_string(),
+            payload,
+            effective_principal: RawEffectivePrincipal::CanisterId(canister_id.as_slice().to_vec()),
+        };
+        let traced_result: RawTracedCanisterResult = self.post(endpoint, raw_canister_call).await;
+        (traced_result.result.into(), traced_result.trace.into())
+    }
+
     /// Execute a query call on a canister explicitly specifying an effective principal to route the request:
     /// this API is useful for making generic query calls (including management canister query calls) without using dedicated functions from this library
     /// (e.g., making generic query calls in dfx to a PocketIC instance).
@@ -838,6 +920,36 @@ impl PocketIc {
         .map(|responses| responses.0.canister_log_records)
     }
 
+    /// Fetch canister logs matching the given filter via a query call to the management canister.
+    /// At most `max_records` log records are returned if specified.
+    pub async fn fetch_canister_logs_with_filter(
+        &self,
+        canister_id: CanisterId,
+        sender: Principal,
+        filter: CanisterLogFilter,
+        max_records: Option<u64>,
+    ) -> Result<CanisterLogsPage, RejectResponse> {
+        with_candid::<_, (CanisterLogsPage,), _>(
+            (FetchCanisterLogsWithFilterArgs {
+                canister_id,
+                filter: Some(filter),
+                max_records,
+            },),
+            |payload| async {
+                self.query_call_with_effective_principal(
+                    Principal::management_canister(),
+                    RawEffectivePrincipal::CanisterId(canister_id.as_slice().to_vec()),
+                    sender,
+                    "fetch_canister_logs",
+                    payload,
+                )
+                .await
+            },
+        )
+        .await
+        .map(|responses| responses.0)
+    }
+
     /// Request a canister's status.
     #[instrument(skip(self), fields(instance_id=self.instance_id, sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
     pub async fn canister_status(
@@ -1280,6 +1392,285 @@ impl PocketIc {
         .await
     }
 
+    /// Read the metadata of a canister snapshot.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub async fn read_canister_snapshot_metadata(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        snapshot_id: Vec<u8>,
+    ) -> Result<CanisterSnapshotMetadata, RejectResponse> {
+        call_candid_as::<_, (CanisterSnapshotMetadata,)>(
+            self,
+            Principal::management_canister(),
+            RawEffectivePrincipal::CanisterId(canister_id.as_slice().to_vec()),
+            sender.unwrap_or(Principal::anonymous()),
+            "read_canister_snapshot_metadata",
+            (ReadCanisterSnapshotMetadataArgs {
+                canister_id,
+                snapshot_id,
+            },),
+        )
+        .await
+        .map(|responses| responses.0)
+    }
+
+    /// Read a chunk of the data of a canister snapshot.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub async fn read_canister_snapshot_data(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        snapshot_id: Vec<u8>,
+        kind: CanisterSnapshotDataKind,
+    ) -> Result<Vec<u8>, RejectResponse> {
+        call_candid_as::<_, (ReadCanisterSnapshotDataResponse,)>(
+            self,
+            Principal::management_canister(),
+            RawEffectivePrincipal::CanisterId(canister_id.as_slice().to_vec()),
+            sender.unwrap_or(Principal::anonymous()),
+            "read_canister_snapshot_data",
+            (ReadCanisterSnapshotDataArgs {
+                canister_id,
+                snapshot_id,
+                kind,
+            },),
+        )
+        .await
+        .map(|responses| responses.0.chunk)
+    }
+
+    /// Upload the metadata of a canister snapshot and return the ID of the new snapshot.
+    #[instrument(skip(self, args), fields(instance_id=self.instance_id, canister_id = %args.canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub async fn upload_canister_snapshot_metadata(
+        &self,
+        sender: Option<Principal>,
+        args: UploadCanisterSnapshotMetadataArgs,
+    ) -> Result<Vec<u8>, RejectResponse> {
+        call_candid_as::<_, (UploadCanisterSnapshotMetadataResponse,)>(
+            self,
+            Principal::management_canister(),
+            RawEffectivePrincipal::CanisterId(args.canister_id.as_slice().to_vec()),
+            sender.unwrap_or(Principal::anonymous()),
+            "upload_canister_snapshot_metadata",
+            (args,),
+        )
+        .await
+        .map(|responses| responses.0.snapshot_id)
+    }
+
+    /// Upload a chunk of the data of a canister snapshot.
+    #[instrument(skip(self, chunk), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub async fn upload_canister_snapshot_data(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        snapshot_id: Vec<u8>,
+        kind: CanisterSnapshotDataOffset,
+        chunk: Vec<u8>,
+    ) -> Result<(), RejectResponse> {
+        call_candid_as(
+            self,
+            Principal::management_canister(),
+            RawEffectivePrincipal::CanisterId(canister_id.as_slice().to_vec()),
+            sender.unwrap_or(Principal::anonymous()),
+            "upload_canister_snapshot_data",
+            (UploadCanisterSnapshotDataArgs {
+                canister_id,
+                snapshot_id,
+                kind,
+                chunk,
+            },),
+        )
+        .await
+    }
+
+    /// Export a canister snapshot into a snapshot archive file
+    /// (see [`crate::snapshot_archive`] for the archive format).
+    /// The snapshot data are downloaded in chunks and streamed into the file.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub async fn export_canister_snapshot(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        snapshot_id: Vec<u8>,
+        archive: &Path,
+    ) -> Result<(), SnapshotArchiveError> {
+        let metadata = self
+            .read_canister_snapshot_metadata(canister_id, sender, snapshot_id.clone())
+            .await?;
+        let file = BufWriter::new(File::create(archive)?);
+        let mut writer = SnapshotArchiveWriter::new(file, &metadata)?;
+        let sections = [
+            (
+                SnapshotArchiveSectionKind::WasmModule,
+                metadata.wasm_module_size,
+            ),
+            (
+                SnapshotArchiveSectionKind::MainMemory,
+                metadata.wasm_memory_size,
+            ),
+            (
+                SnapshotArchiveSectionKind::StableMemory,
+                metadata.stable_memory_size,
+            ),
+        ];
+        for (kind, len) in sections {
+            writer.begin_section(SnapshotArchiveSection { kind, len })?;
+            let mut offset = 0;
+            while offset < len {
+                let size = std::cmp::min(SNAPSHOT_DATA_CHUNK_SIZE, len - offset);
+                let data_kind = match kind {
+                    SnapshotArchiveSectionKind::WasmModule => {
+                        CanisterSnapshotDataKind::WasmModule { offset, size }
+                    }
+                    SnapshotArchiveSectionKind::MainMemory => {
+                        CanisterSnapshotDataKind::MainMemory { offset, size }
+                    }
+                    SnapshotArchiveSectionKind::StableMemory => {
+                        CanisterSnapshotDataKind::StableMemory { offset, size }
+                    }
+                    SnapshotArchiveSectionKind::WasmChunk => unreachable!(),
+                };
+                let chunk = self
+                    .read_canister_snapshot_data(
+                        canister_id,
+                        sender,
+                        snapshot_id.clone(),
+                        data_kind,
+                    )
+                    .await?;
+                if chunk.len() as u64 != size {
+                    return Err(SnapshotArchiveError::MetadataMismatch(format!(
+                        "read {} bytes of {:?} at offset {}, but expected {} bytes",
+                        chunk.len(),
+                        kind,
+                        offset,
+                        size
+                    )));
+                }
+                writer.write_chunk(&chunk)?;
+                offset += size;
+            }
+        }
+        for chunk_hash in &metadata.wasm_chunk_store {
+            let chunk = self
+                .read_canister_snapshot_data(
+                    canister_id,
+                    sender,
+                    snapshot_id.clone(),
+                    CanisterSnapshotDataKind::WasmChunk {
+                        hash: chunk_hash.hash.clone(),
+                    },
+                )
+                .await?;
+            writer.begin_section(SnapshotArchiveSection {
+                kind: SnapshotArchiveSectionKind::WasmChunk,
+                len: chunk.len() as u64,
+            })?;
+            writer.write_chunk(&chunk)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Import a canister snapshot from a snapshot archive file
+    /// (see [`crate::snapshot_archive`] for the archive format)
+    /// and return the ID of the new snapshot.
+    /// The canister does not have to be the canister from which the snapshot was exported.
+    /// If the archive turns out to be corrupted, the partially uploaded snapshot is deleted.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub async fn import_canister_snapshot(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        replace_snapshot: Option<Vec<u8>>,
+        archive: &Path,
+    ) -> Result<Vec<u8>, SnapshotArchiveError> {
+        let mut reader = SnapshotArchiveReader::new(BufReader::new(File::open(archive)?))?;
+        let metadata = reader.metadata().clone();
+        let snapshot_id = self
+            .upload_canister_snapshot_metadata(
+                sender,
+                UploadCanisterSnapshotMetadataArgs::new(canister_id, replace_snapshot, &metadata),
+            )
+            .await?;
+        let res = self
+            .upload_snapshot_archive_data(canister_id, sender, snapshot_id.clone(), &mut reader)
+            .await;
+        if res.is_err() {
+            if let Err(err) = self
+                .delete_canister_snapshot(canister_id, sender, snapshot_id.clone())
+                .await
+            {
+                warn!("Failed to delete partially imported snapshot: {}", err);
+            }
+        }
+        res.map(|()| snapshot_id)
+    }
+
+    async fn upload_snapshot_archive_data<R: Read>(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        snapshot_id: Vec<u8>,
+        reader: &mut SnapshotArchiveReader<R>,
+    ) -> Result<(), SnapshotArchiveError> {
+        // The reader validates the sections against the metadata.
+        let metadata = reader.metadata().clone();
+        while let Some(section) = reader.next_section()? {
+            if section.kind == SnapshotArchiveSectionKind::WasmChunk {
+                let chunk = reader.read_chunk(MAX_WASM_CHUNK_SIZE)?;
+                let hash = Sha256::digest(&chunk).to_vec();
+                if !metadata
+                    .wasm_chunk_store
+                    .iter()
+                    .any(|chunk_hash| chunk_hash.hash == hash)
+                {
+                    return Err(SnapshotArchiveError::MetadataMismatch(format!(
+                        "Wasm chunk with hash {} is not in the Wasm chunk store",
+                        hex::encode(hash)
+                    )));
+                }
+                self.upload_canister_snapshot_data(
+                    canister_id,
+                    sender,
+                    snapshot_id.clone(),
+                    CanisterSnapshotDataOffset::WasmChunk,
+                    chunk,
+                )
+                .await?;
+                continue;
+            }
+            let mut offset = 0;
+            while offset < section.len {
+                let chunk = reader.read_chunk(SNAPSHOT_DATA_CHUNK_SIZE)?;
+                let kind = match section.kind {
+                    SnapshotArchiveSectionKind::WasmModule => {
+                        CanisterSnapshotDataOffset::WasmModule { offset }
+                    }
+                    SnapshotArchiveSectionKind::MainMemory => {
+                        CanisterSnapshotDataOffset::MainMemory { offset }
+                    }
+                    SnapshotArchiveSectionKind::StableMemory => {
+                        CanisterSnapshotDataOffset::StableMemory { offset }
+                    }
+                    SnapshotArchiveSectionKind::WasmChunk => unreachable!(),
+                };
+                offset += chunk.len() as u64;
+                self.upload_canister_snapshot_data(
+                    canister_id,
+                    sender,
+                    snapshot_id.clone(),
+                    kind,
+                    chunk,
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
     /// Update canister settings.
     #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
     pub async fn update_canister_settings(
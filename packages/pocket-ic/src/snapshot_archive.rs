@@ -0,0 +1,882 @@
+//! A portable archive format for canister snapshots.
+//!
+//! A snapshot archive stores the metadata and the data (Wasm module, main memory,
+//! stable memory, and Wasm chunk store) of a canister snapshot in a single file
+//! so that the snapshot can be downloaded from a canister (e.g., on the IC mainnet)
+//! and uploaded into another canister (e.g., on a PocketIC instance).
+//!
+//! An archive consists of a header followed by a sequence of sections
+//! (all integers are encoded in little-endian byte order):
+//! ```text
+//! archive := magic (8 bytes) | version (u32) | metadata section | data section* | end section
+//! section := kind (u8) | length (u64) | payload (length bytes) | SHA-256 of the payload (32 bytes)
+//! ```
+//! The payload of the metadata section is the Candid encoding of [`CanisterSnapshotMetadata`].
+//! The payload of a data section is the raw content of the Wasm module, main memory,
+//! stable memory, or a single Wasm chunk. The end section has an empty payload and its checksum
+//! is replaced by the SHA-256 of the checksums of all preceding sections so that truncated
+//! or reordered archives are detected.
+//!
+//! An archive must contain exactly one section for each of the Wasm module, main memory, and
+//! stable memory whose length matches the metadata, and one section per Wasm chunk in the metadata.
+//! Since archives are untrusted input, [`SnapshotArchiveReader`] rejects archives violating these
+//! constraints before reading the affected payload and never allocates more than
+//! [`SNAPSHOT_DATA_CHUNK_SIZE`] bytes for a single chunk of payload.
+
+use crate::RejectResponse;
+use candid::{CandidType, Decode, Encode, Principal};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use thiserror::Error;
+
+/// The magic bytes at the beginning of every snapshot archive.
+pub const SNAPSHOT_ARCHIVE_MAGIC: &[u8; 8] = b"ICSNPARC";
+
+/// The version of the snapshot archive format produced by [`SnapshotArchiveWriter`].
+pub const SNAPSHOT_ARCHIVE_VERSION: u32 = 1;
+
+/// The maximum size of a chunk of snapshot data read from or uploaded to the management canister.
+pub const SNAPSHOT_DATA_CHUNK_SIZE: u64 = 2_000_000;
+
+/// The maximum size of a chunk in the Wasm chunk store of a canister.
+pub const MAX_WASM_CHUNK_SIZE: u64 = 1 << 20;
+
+/// The maximum size of the metadata section of a snapshot archive.
+const MAX_METADATA_SECTION_SIZE: u64 = 1 << 20;
+
+const METADATA_SECTION_TAG: u8 = 0;
+const END_SECTION_TAG: u8 = 0xFF;
+const CHECKSUM_LEN: usize = 32;
+
+/// A global exported by the Wasm module of a canister.
+#[derive(Copy, Clone, Debug, PartialEq, CandidType, Deserialize)]
+pub enum Global {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    V128(u128),
+}
+
+/// The source of a canister snapshot.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, CandidType, Deserialize)]
+pub enum SnapshotSource {
+    #[default]
+    TakenFromCanister,
+    UploadedManually,
+}
+
+/// The state of the global timer of a canister.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, CandidType, Deserialize)]
+pub enum GlobalTimer {
+    Inactive,
+    Active(u64),
+}
+
+/// The status of the `on_low_wasm_memory` hook of a canister.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, CandidType, Deserialize)]
+pub enum OnLowWasmMemoryHookStatus {
+    #[default]
+    ConditionNotSatisfied,
+    Ready,
+    Executed,
+}
+
+/// The hash of a chunk in the Wasm chunk store of a canister.
+#[derive(Clone, Debug, PartialEq, Eq, CandidType, Deserialize)]
+pub struct SnapshotChunkHash {
+    #[serde(with = "serde_bytes")]
+    pub hash: Vec<u8>,
+}
+
+/// Metadata of a canister snapshot returned by `read_canister_snapshot_metadata`.
+#[derive(Clone, Debug, Default, PartialEq, CandidType, Deserialize)]
+pub struct CanisterSnapshotMetadata {
+    pub source: SnapshotSource,
+    pub taken_at_timestamp: u64,
+    pub wasm_module_size: u64,
+    pub exported_globals: Vec<Global>,
+    pub wasm_memory_size: u64,
+    pub stable_memory_size: u64,
+    pub wasm_chunk_store: Vec<SnapshotChunkHash>,
+    pub canister_version: u64,
+    #[serde(with = "serde_bytes")]
+    pub certified_data: Vec<u8>,
+    pub global_timer: Option<GlobalTimer>,
+    pub on_low_wasm_memory_hook_status: Option<OnLowWasmMemoryHookStatus>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub(crate) struct ReadCanisterSnapshotMetadataArgs {
+    pub canister_id: Principal,
+    #[serde(with = "serde_bytes")]
+    pub snapshot_id: Vec<u8>,
+}
+
+/// The kind of snapshot data to be read by `read_canister_snapshot_data`.
+#[derive(Clone, Debug, PartialEq, Eq, CandidType, Deserialize)]
+pub enum CanisterSnapshotDataKind {
+    WasmModule {
+        offset: u64,
+        size: u64,
+    },
+    MainMemory {
+        offset: u64,
+        size: u64,
+    },
+    StableMemory {
+        offset: u64,
+        size: u64,
+    },
+    WasmChunk {
+        #[serde(with = "serde_bytes")]
+        hash: Vec<u8>,
+    },
+}
+
+#[derive(CandidType, Deserialize)]
+pub(crate) struct ReadCanisterSnapshotDataArgs {
+    pub canister_id: Principal,
+    #[serde(with = "serde_bytes")]
+    pub snapshot_id: Vec<u8>,
+    pub kind: CanisterSnapshotDataKind,
+}
+
+#[derive(CandidType, Deserialize)]
+pub(crate) struct ReadCanisterSnapshotDataResponse {
+    #[serde(with = "serde_bytes")]
+    pub chunk: Vec<u8>,
+}
+
+/// Arguments of `upload_canister_snapshot_metadata`.
+#[derive(Clone, Debug, PartialEq, CandidType, Deserialize)]
+pub struct UploadCanisterSnapshotMetadataArgs {
+    pub canister_id: Principal,
+    pub replace_snapshot: Option<serde_bytes::ByteBuf>,
+    pub wasm_module_size: u64,
+    pub exported_globals: Vec<Global>,
+    pub wasm_memory_size: u64,
+    pub stable_memory_size: u64,
+    #[serde(with = "serde_bytes")]
+    pub certified_data: Vec<u8>,
+    pub global_timer: GlobalTimer,
+    pub on_low_wasm_memory_hook_status: OnLowWasmMemoryHookStatus,
+}
+
+impl UploadCanisterSnapshotMetadataArgs {
+    /// Creates the arguments to upload the given snapshot metadata into a canister,
+    /// optionally replacing an existing snapshot of that canister.
+    pub fn new(
+        canister_id: Principal,
+        replace_snapshot: Option<Vec<u8>>,
+        metadata: &CanisterSnapshotMetadata,
+    ) -> Self {
+        Self {
+            canister_id,
+            replace_snapshot: replace_snapshot.map(serde_bytes::ByteBuf::from),
+            wasm_module_size: metadata.wasm_module_size,
+            exported_globals: metadata.exported_globals.clone(),
+            wasm_memory_size: metadata.wasm_memory_size,
+            stable_memory_size: metadata.stable_memory_size,
+            certified_data: metadata.certified_data.clone(),
+            global_timer: metadata.global_timer.unwrap_or(GlobalTimer::Inactive),
+            on_low_wasm_memory_hook_status: metadata
+                .on_low_wasm_memory_hook_status
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize)]
+pub(crate) struct UploadCanisterSnapshotMetadataResponse {
+    #[serde(with = "serde_bytes")]
+    pub snapshot_id: Vec<u8>,
+}
+
+/// The kind and offset of snapshot data to be uploaded by `upload_canister_snapshot_data`.
+#[derive(Clone, Debug, PartialEq, Eq, CandidType, Deserialize)]
+pub enum CanisterSnapshotDataOffset {
+    WasmModule { offset: u64 },
+    MainMemory { offset: u64 },
+    StableMemory { offset: u64 },
+    WasmChunk,
+}
+
+#[derive(CandidType, Deserialize)]
+pub(crate) struct UploadCanisterSnapshotDataArgs {
+    pub canister_id: Principal,
+    #[serde(with = "serde_bytes")]
+    pub snapshot_id: Vec<u8>,
+    pub kind: CanisterSnapshotDataOffset,
+    #[serde(with = "serde_bytes")]
+    pub chunk: Vec<u8>,
+}
+
+/// The kind of a data section in a snapshot archive.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotArchiveSectionKind {
+    WasmModule,
+    MainMemory,
+    StableMemory,
+    WasmChunk,
+}
+
+impl SnapshotArchiveSectionKind {
+    fn tag(&self) -> u8 {
+        match self {
+            SnapshotArchiveSectionKind::WasmModule => 1,
+            SnapshotArchiveSectionKind::MainMemory => 2,
+            SnapshotArchiveSectionKind::StableMemory => 3,
+            SnapshotArchiveSectionKind::WasmChunk => 4,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(SnapshotArchiveSectionKind::WasmModule),
+            2 => Some(SnapshotArchiveSectionKind::MainMemory),
+            3 => Some(SnapshotArchiveSectionKind::StableMemory),
+            4 => Some(SnapshotArchiveSectionKind::WasmChunk),
+            _ => None,
+        }
+    }
+}
+
+/// A data section in a snapshot archive.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotArchiveSection {
+    pub kind: SnapshotArchiveSectionKind,
+    pub len: u64,
+}
+
+#[derive(Debug, Error)]
+pub enum SnapshotArchiveError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("not a canister snapshot archive")]
+    InvalidMagic,
+    #[error("unsupported snapshot archive version {0}")]
+    UnsupportedVersion(u32),
+    #[error("checksum mismatch in snapshot archive section {0}")]
+    ChecksumMismatch(String),
+    #[error("malformed snapshot archive: {0}")]
+    Malformed(String),
+    #[error("snapshot archive does not match the snapshot metadata: {0}")]
+    MetadataMismatch(String),
+    #[error("failed to encode or decode snapshot metadata: {0}")]
+    Candid(String),
+    #[error("{0}")]
+    Reject(RejectResponse),
+}
+
+impl From<RejectResponse> for SnapshotArchiveError {
+    fn from(reject_response: RejectResponse) -> Self {
+        SnapshotArchiveError::Reject(reject_response)
+    }
+}
+
+struct OpenSection {
+    name: String,
+    remaining: u64,
+    hasher: Sha256,
+}
+
+impl OpenSection {
+    fn new(name: String, len: u64) -> Self {
+        Self {
+            name,
+            remaining: len,
+            hasher: Sha256::new(),
+        }
+    }
+}
+
+/// Writes a snapshot archive section by section.
+///
+/// Every data section must be started by [`SnapshotArchiveWriter::begin_section`]
+/// and its payload must then be written in full by (repeated calls to)
+/// [`SnapshotArchiveWriter::write_chunk`].
+pub struct SnapshotArchiveWriter<W: Write> {
+    writer: W,
+    checksums: Sha256,
+    section: Option<OpenSection>,
+}
+
+impl<W: Write> SnapshotArchiveWriter<W> {
+    /// Writes the archive header and the metadata section.
+    pub fn new(
+        mut writer: W,
+        metadata: &CanisterSnapshotMetadata,
+    ) -> Result<Self, SnapshotArchiveError> {
+        let metadata =
+            Encode!(metadata).map_err(|err| SnapshotArchiveError::Candid(err.to_string()))?;
+        writer.write_all(SNAPSHOT_ARCHIVE_MAGIC)?;
+        writer.write_all(&SNAPSHOT_ARCHIVE_VERSION.to_le_bytes())?;
+        let mut archive_writer = Self {
+            writer,
+            checksums: Sha256::new(),
+            section: None,
+        };
+        archive_writer.write_section_header(
+            METADATA_SECTION_TAG,
+            "metadata",
+            metadata.len() as u64,
+        )?;
+        archive_writer.write_chunk(&metadata)?;
+        Ok(archive_writer)
+    }
+
+    /// Starts a new data section of the given length.
+    pub fn begin_section(
+        &mut self,
+        section: SnapshotArchiveSection,
+    ) -> Result<(), SnapshotArchiveError> {
+        self.write_section_header(
+            section.kind.tag(),
+            &format!("{:?}", section.kind),
+            section.len,
+        )
+    }
+
+    /// Appends a chunk to the payload of the current section.
+    pub fn write_chunk(&mut self, chunk: &[u8]) -> Result<(), SnapshotArchiveError> {
+        let section = self.section.as_mut().ok_or_else(|| {
+            SnapshotArchiveError::Malformed("no open section to write to".to_string())
+        })?;
+        if chunk.len() as u64 > section.remaining {
+            return Err(SnapshotArchiveError::Malformed(format!(
+                "chunk of {} bytes exceeds the remaining {} bytes of section {}",
+                chunk.len(),
+                section.remaining,
+                section.name
+            )));
+        }
+        self.writer.write_all(chunk)?;
+        section.hasher.update(chunk);
+        section.remaining -= chunk.len() as u64;
+        if section.remaining == 0 {
+            self.close_section()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the end section and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, SnapshotArchiveError> {
+        self.ensure_no_open_section()?;
+        self.writer.write_all(&[END_SECTION_TAG])?;
+        self.writer.write_all(&0_u64.to_le_bytes())?;
+        self.writer.write_all(&self.checksums.finalize())?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_section_header(
+        &mut self,
+        tag: u8,
+        name: &str,
+        len: u64,
+    ) -> Result<(), SnapshotArchiveError> {
+        self.ensure_no_open_section()?;
+        self.writer.write_all(&[tag])?;
+        self.writer.write_all(&len.to_le_bytes())?;
+        self.section = Some(OpenSection::new(name.to_string(), len));
+        if len == 0 {
+            self.close_section()?;
+        }
+        Ok(())
+    }
+
+    fn close_section(&mut self) -> Result<(), SnapshotArchiveError> {
+        let section = self.section.take().unwrap();
+        let checksum = section.hasher.finalize();
+        self.writer.write_all(&checksum)?;
+        self.checksums.update(checksum);
+        Ok(())
+    }
+
+    fn ensure_no_open_section(&self) -> Result<(), SnapshotArchiveError> {
+        match &self.section {
+            Some(section) => Err(SnapshotArchiveError::Malformed(format!(
+                "{} bytes of section {} have not been written",
+                section.remaining, section.name
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Reads a snapshot archive section by section, verifying the checksum
+/// of every section once its payload has been read in full.
+pub struct SnapshotArchiveReader<R: Read> {
+    reader: R,
+    metadata: CanisterSnapshotMetadata,
+    checksums: Sha256,
+    section: Option<OpenSection>,
+    read_sections: Vec<SnapshotArchiveSectionKind>,
+    finished: bool,
+}
+
+impl<R: Read> SnapshotArchiveReader<R> {
+    /// Reads the archive header and the metadata section.
+    pub fn new(mut reader: R) -> Result<Self, SnapshotArchiveError> {
+        let mut magic = [0; SNAPSHOT_ARCHIVE_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != SNAPSHOT_ARCHIVE_MAGIC {
+            return Err(SnapshotArchiveError::InvalidMagic);
+        }
+        let mut version = [0; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != SNAPSHOT_ARCHIVE_VERSION {
+            return Err(SnapshotArchiveError::UnsupportedVersion(version));
+        }
+        let mut archive_reader = Self {
+            reader,
+            metadata: CanisterSnapshotMetadata::default(),
+            checksums: Sha256::new(),
+            section: None,
+            read_sections: vec![],
+            finished: false,
+        };
+        let (tag, len) = archive_reader.read_section_header()?;
+        if tag != METADATA_SECTION_TAG {
+            return Err(SnapshotArchiveError::Malformed(format!(
+                "expected the metadata section, found section with tag {}",
+                tag
+            )));
+        }
+        if len > MAX_METADATA_SECTION_SIZE {
+            return Err(SnapshotArchiveError::Malformed(format!(
+                "metadata section of {} bytes exceeds the maximum of {} bytes",
+                len, MAX_METADATA_SECTION_SIZE
+            )));
+        }
+        archive_reader.open_section("metadata".to_string(), len)?;
+        let mut metadata = vec![];
+        while archive_reader.section.is_some() {
+            metadata.extend(archive_reader.read_chunk(SNAPSHOT_DATA_CHUNK_SIZE)?);
+        }
+        archive_reader.metadata = Decode!(&metadata, CanisterSnapshotMetadata)
+            .map_err(|err| SnapshotArchiveError::Candid(err.to_string()))?;
+        Ok(archive_reader)
+    }
+
+    /// Returns the snapshot metadata stored in the archive.
+    pub fn metadata(&self) -> &CanisterSnapshotMetadata {
+        &self.metadata
+    }
+
+    /// Starts reading the next data section.
+    /// Returns `None` once the end section has been read and verified
+    /// and all sections specified by the metadata have been read.
+    pub fn next_section(&mut self) -> Result<Option<SnapshotArchiveSection>, SnapshotArchiveError> {
+        if let Some(section) = &self.section {
+            return Err(SnapshotArchiveError::Malformed(format!(
+                "{} bytes of section {} have not been read",
+                section.remaining, section.name
+            )));
+        }
+        if self.finished {
+            return Ok(None);
+        }
+        let (tag, len) = self.read_section_header()?;
+        if tag == END_SECTION_TAG {
+            if len != 0 {
+                return Err(SnapshotArchiveError::Malformed(
+                    "end section must be empty".to_string(),
+                ));
+            }
+            let mut checksum = [0; CHECKSUM_LEN];
+            self.reader.read_exact(&mut checksum)?;
+            if checksum[..] != self.checksums.clone().finalize()[..] {
+                return Err(SnapshotArchiveError::ChecksumMismatch("end".to_string()));
+            }
+            self.ensure_all_sections_read()?;
+            self.finished = true;
+            return Ok(None);
+        }
+        let kind = SnapshotArchiveSectionKind::from_tag(tag).ok_or_else(|| {
+            SnapshotArchiveError::Malformed(format!("unknown section tag {}", tag))
+        })?;
+        self.validate_section(kind, len)?;
+        self.read_sections.push(kind);
+        self.open_section(format!("{:?}", kind), len)?;
+        Ok(Some(SnapshotArchiveSection { kind, len }))
+    }
+
+    /// Reads at most `max_len` bytes (but never more than [`SNAPSHOT_DATA_CHUNK_SIZE`] bytes)
+    /// of the payload of the current section.
+    pub fn read_chunk(&mut self, max_len: u64) -> Result<Vec<u8>, SnapshotArchiveError> {
+        let section = self.section.as_mut().ok_or_else(|| {
+            SnapshotArchiveError::Malformed("no open section to read from".to_string())
+        })?;
+        let len = max_len.min(section.remaining).min(SNAPSHOT_DATA_CHUNK_SIZE);
+        let mut chunk = vec![0; len as usize];
+        self.reader.read_exact(&mut chunk)?;
+        section.hasher.update(&chunk);
+        section.remaining -= len;
+        if section.remaining == 0 {
+            self.close_section()?;
+        }
+        Ok(chunk)
+    }
+
+    // Checks that a data section of the given kind and length is expected
+    // according to the metadata and the sections that have already been read.
+    fn validate_section(
+        &self,
+        kind: SnapshotArchiveSectionKind,
+        len: u64,
+    ) -> Result<(), SnapshotArchiveError> {
+        let expected_len = match kind {
+            SnapshotArchiveSectionKind::WasmModule => self.metadata.wasm_module_size,
+            SnapshotArchiveSectionKind::MainMemory => self.metadata.wasm_memory_size,
+            SnapshotArchiveSectionKind::StableMemory => self.metadata.stable_memory_size,
+            SnapshotArchiveSectionKind::WasmChunk => {
+                if len > MAX_WASM_CHUNK_SIZE {
+                    return Err(SnapshotArchiveError::Malformed(format!(
+                        "Wasm chunk of {} bytes exceeds the maximum of {} bytes",
+                        len, MAX_WASM_CHUNK_SIZE
+                    )));
+                }
+                if self.num_read_sections(kind) >= self.metadata.wasm_chunk_store.len() {
+                    return Err(SnapshotArchiveError::MetadataMismatch(format!(
+                        "archive contains more than the {} Wasm chunks specified by the metadata",
+                        self.metadata.wasm_chunk_store.len()
+                    )));
+                }
+                return Ok(());
+            }
+        };
+        if self.num_read_sections(kind) > 0 {
+            return Err(SnapshotArchiveError::Malformed(format!(
+                "duplicate section {:?}",
+                kind
+            )));
+        }
+        if len != expected_len {
+            return Err(SnapshotArchiveError::MetadataMismatch(format!(
+                "section {:?} has {} bytes, but the metadata specify {} bytes",
+                kind, len, expected_len
+            )));
+        }
+        Ok(())
+    }
+
+    fn ensure_all_sections_read(&self) -> Result<(), SnapshotArchiveError> {
+        for kind in [
+            SnapshotArchiveSectionKind::WasmModule,
+            SnapshotArchiveSectionKind::MainMemory,
+            SnapshotArchiveSectionKind::StableMemory,
+        ] {
+            if self.num_read_sections(kind) == 0 {
+                return Err(SnapshotArchiveError::Malformed(format!(
+                    "missing section {:?}",
+                    kind
+                )));
+            }
+        }
+        let num_wasm_chunks = self.num_read_sections(SnapshotArchiveSectionKind::WasmChunk);
+        if num_wasm_chunks != self.metadata.wasm_chunk_store.len() {
+            return Err(SnapshotArchiveError::MetadataMismatch(format!(
+                "archive contains {} Wasm chunks, but the metadata specify {} Wasm chunks",
+                num_wasm_chunks,
+                self.metadata.wasm_chunk_store.len()
+            )));
+        }
+        Ok(())
+    }
+
+    fn num_read_sections(&self, kind: SnapshotArchiveSectionKind) -> usize {
+        self.read_sections
+            .iter()
+            .filter(|read_kind| **read_kind == kind)
+            .count()
+    }
+
+    fn read_section_header(&mut self) -> Result<(u8, u64), SnapshotArchiveError> {
+        let mut tag = [0; 1];
+        self.reader.read_exact(&mut tag)?;
+        let mut len = [0; 8];
+        self.reader.read_exact(&mut len)?;
+        Ok((tag[0], u64::from_le_bytes(len)))
+    }
+
+    fn open_section(&mut self, name: String, len: u64) -> Result<(), SnapshotArchiveError> {
+        self.section = Some(OpenSection::new(name, len));
+        if len == 0 {
+            self.close_section()?;
+        }
+        Ok(())
+    }
+
+    fn close_section(&mut self) -> Result<(), SnapshotArchiveError> {
+        let section = self.section.take().unwrap();
+        let mut checksum = [0; CHECKSUM_LEN];
+        self.reader.read_exact(&mut checksum)?;
+        let expected = section.hasher.finalize();
+        if checksum[..] != expected[..] {
+            return Err(SnapshotArchiveError::ChecksumMismatch(section.name));
+        }
+        self.checksums.update(expected);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> CanisterSnapshotMetadata {
+        CanisterSnapshotMetadata {
+            source: SnapshotSource::TakenFromCanister,
+            taken_at_timestamp: 42,
+            wasm_module_size: 5,
+            exported_globals: vec![Global::I32(1), Global::V128(u128::MAX)],
+            wasm_memory_size: 3,
+            stable_memory_size: 0,
+            wasm_chunk_store: vec![SnapshotChunkHash {
+                hash: Sha256::digest(b"chunk").to_vec(),
+            }],
+            canister_version: 7,
+            certified_data: vec![1, 2, 3],
+            global_timer: Some(GlobalTimer::Active(123)),
+            on_low_wasm_memory_hook_status: Some(OnLowWasmMemoryHookStatus::Ready),
+        }
+    }
+
+    fn archive() -> Vec<u8> {
+        let mut writer = SnapshotArchiveWriter::new(vec![], &metadata()).unwrap();
+        writer
+            .begin_section(SnapshotArchiveSection {
+                kind: SnapshotArchiveSectionKind::WasmModule,
+                len: 5,
+            })
+            .unwrap();
+        writer.write_chunk(b"\0asm").unwrap();
+        writer.write_chunk(b"!").unwrap();
+        writer
+            .begin_section(SnapshotArchiveSection {
+                kind: SnapshotArchiveSectionKind::MainMemory,
+                len: 3,
+            })
+            .unwrap();
+        writer.write_chunk(&[7, 8, 9]).unwrap();
+        writer
+            .begin_section(SnapshotArchiveSection {
+                kind: SnapshotArchiveSectionKind::StableMemory,
+                len: 0,
+            })
+            .unwrap();
+        writer
+            .begin_section(SnapshotArchiveSection {
+                kind: SnapshotArchiveSectionKind::WasmChunk,
+                len: 5,
+            })
+            .unwrap();
+        writer.write_chunk(b"chunk").unwrap();
+        writer.finish().unwrap()
+    }
+
+    fn read_all(
+        archive: &[u8],
+    ) -> Result<Vec<(SnapshotArchiveSection, Vec<u8>)>, SnapshotArchiveError> {
+        let mut reader = SnapshotArchiveReader::new(archive)?;
+        let mut sections = vec![];
+        while let Some(section) = reader.next_section()? {
+            let mut data = vec![];
+            for _ in 0..section.len.div_ceil(2) {
+                data.extend(reader.read_chunk(2)?);
+            }
+            sections.push((section, data));
+        }
+        Ok(sections)
+    }
+
+    #[test]
+    fn snapshot_archive_round_trip() {
+        let archive = archive();
+        let reader = SnapshotArchiveReader::new(&archive[..]).unwrap();
+        assert_eq!(reader.metadata(), &metadata());
+
+        let sections = read_all(&archive).unwrap();
+        let kinds_and_data: Vec<_> = sections
+            .into_iter()
+            .map(|(section, data)| (section.kind, data))
+            .collect();
+        assert_eq!(
+            kinds_and_data,
+            vec![
+                (SnapshotArchiveSectionKind::WasmModule, b"\0asm!".to_vec()),
+                (SnapshotArchiveSectionKind::MainMemory, vec![7, 8, 9]),
+                (SnapshotArchiveSectionKind::StableMemory, vec![]),
+                (SnapshotArchiveSectionKind::WasmChunk, b"chunk".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn snapshot_archive_detects_corruption() {
+        let archive = archive();
+        // Flip a byte of the main memory payload.
+        let pos = archive
+            .windows(3)
+            .position(|window| window == [7, 8, 9])
+            .unwrap();
+        let mut corrupted = archive.clone();
+        corrupted[pos + 1] ^= 0xFF;
+        assert!(matches!(
+            read_all(&corrupted),
+            Err(SnapshotArchiveError::ChecksumMismatch(section)) if section == "MainMemory"
+        ));
+
+        let truncated = &archive[..archive.len() - 1];
+        assert!(matches!(
+            read_all(truncated),
+            Err(SnapshotArchiveError::Io(_))
+        ));
+
+        let mut wrong_magic = archive.clone();
+        wrong_magic[0] = b'X';
+        assert!(matches!(
+            read_all(&wrong_magic),
+            Err(SnapshotArchiveError::InvalidMagic)
+        ));
+
+        let mut wrong_version = archive.clone();
+        wrong_version[SNAPSHOT_ARCHIVE_MAGIC.len()] = 2;
+        assert!(matches!(
+            read_all(&wrong_version),
+            Err(SnapshotArchiveError::UnsupportedVersion(2))
+        ));
+    }
+
+    fn archive_with_sections(
+        metadata: &CanisterSnapshotMetadata,
+        sections: &[(SnapshotArchiveSectionKind, &[u8])],
+    ) -> Vec<u8> {
+        let mut writer = SnapshotArchiveWriter::new(vec![], metadata).unwrap();
+        for (kind, data) in sections {
+            writer
+                .begin_section(SnapshotArchiveSection {
+                    kind: *kind,
+                    len: data.len() as u64,
+                })
+                .unwrap();
+            if !data.is_empty() {
+                writer.write_chunk(data).unwrap();
+            }
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn snapshot_archive_rejects_missing_and_unexpected_sections() {
+        // The Wasm chunk is missing.
+        let archive = archive_with_sections(
+            &metadata(),
+            &[
+                (SnapshotArchiveSectionKind::WasmModule, b"\0asm!"),
+                (SnapshotArchiveSectionKind::MainMemory, &[7, 8, 9]),
+                (SnapshotArchiveSectionKind::StableMemory, &[]),
+            ],
+        );
+        assert!(matches!(
+            read_all(&archive),
+            Err(SnapshotArchiveError::MetadataMismatch(_))
+        ));
+
+        // The stable memory is missing.
+        let archive = archive_with_sections(
+            &metadata(),
+            &[
+                (SnapshotArchiveSectionKind::WasmModule, b"\0asm!"),
+                (SnapshotArchiveSectionKind::MainMemory, &[7, 8, 9]),
+                (SnapshotArchiveSectionKind::WasmChunk, b"chunk"),
+            ],
+        );
+        assert!(matches!(
+            read_all(&archive),
+            Err(SnapshotArchiveError::Malformed(msg)) if msg.contains("StableMemory")
+        ));
+
+        // The main memory is duplicated.
+        let archive = archive_with_sections(
+            &metadata(),
+            &[
+                (SnapshotArchiveSectionKind::MainMemory, &[7, 8, 9]),
+                (SnapshotArchiveSectionKind::MainMemory, &[7, 8, 9]),
+            ],
+        );
+        assert!(matches!(
+            read_all(&archive),
+            Err(SnapshotArchiveError::Malformed(msg)) if msg.contains("duplicate")
+        ));
+
+        // The main memory does not match the metadata.
+        let archive = archive_with_sections(
+            &metadata(),
+            &[(SnapshotArchiveSectionKind::MainMemory, &[7, 8])],
+        );
+        assert!(matches!(
+            read_all(&archive),
+            Err(SnapshotArchiveError::MetadataMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn snapshot_archive_rejects_oversized_sections_before_reading_them() {
+        let mut archive = SNAPSHOT_ARCHIVE_MAGIC.to_vec();
+        archive.extend(SNAPSHOT_ARCHIVE_VERSION.to_le_bytes());
+        archive.push(METADATA_SECTION_TAG);
+        archive.extend(u64::MAX.to_le_bytes());
+        assert!(matches!(
+            SnapshotArchiveReader::new(&archive[..]),
+            Err(SnapshotArchiveError::Malformed(_))
+        ));
+
+        // A Wasm chunk section declaring a huge length is rejected
+        // without allocating memory for its payload.
+        let mut archive = archive_with_sections(
+            &metadata(),
+            &[
+                (SnapshotArchiveSectionKind::WasmModule, b"\0asm!"),
+                (SnapshotArchiveSectionKind::MainMemory, &[7, 8, 9]),
+                (SnapshotArchiveSectionKind::StableMemory, &[]),
+            ],
+        );
+        // Replace the end section by a Wasm chunk section header.
+        archive.truncate(archive.len() - 1 - 8 - CHECKSUM_LEN);
+        archive.push(SnapshotArchiveSectionKind::WasmChunk.tag());
+        archive.extend(u64::MAX.to_le_bytes());
+        let mut reader = SnapshotArchiveReader::new(&archive[..]).unwrap();
+        for _ in 0..3 {
+            let section = reader.next_section().unwrap().unwrap();
+            if section.len > 0 {
+                reader.read_chunk(section.len).unwrap();
+            }
+        }
+        assert!(matches!(
+            reader.next_section(),
+            Err(SnapshotArchiveError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn snapshot_archive_writer_rejects_incomplete_sections() {
+        let mut writer = SnapshotArchiveWriter::new(vec![], &metadata()).unwrap();
+        writer
+            .begin_section(SnapshotArchiveSection {
+                kind: SnapshotArchiveSectionKind::WasmModule,
+                len: 5,
+            })
+            .unwrap();
+        assert!(writer.write_chunk(&[0; 6]).is_err());
+        writer.write_chunk(&[0; 4]).unwrap();
+        assert!(matches!(
+            writer.finish(),
+            Err(SnapshotArchiveError::Malformed(_))
+        ));
+    }
+}
@@ -13,10 +13,11 @@ use pocket_ic::common::rest::{BlockmakerConfigs, RawSubnetBlockmaker, TickConfig
 use pocket_ic::{
     common::rest::{
         BlobCompression, CanisterHttpReply, CanisterHttpResponse, MockCanisterHttpResponse,
-        RawEffectivePrincipal, RawMessageId, SubnetKind,
+        QueryCallOutcome, RawEffectivePrincipal, RawMessageId, SubnetKind,
     },
-    query_candid, update_candid, DefaultEffectiveCanisterIdError, ErrorCode, IngressStatusResult,
-    PocketIc, PocketIcBuilder, PocketIcState, RejectCode, Time,
+    query_candid, update_candid, CanisterLogContentFilter, CanisterLogFilter, CanisterLogRange,
+    DefaultEffectiveCanisterIdError, ErrorCode, IngressStatusResult, PocketIc, PocketIcBuilder,
+    PocketIcState, RejectCode, Time,
 };
 use reqwest::blocking::Client;
 use reqwest::header::CONTENT_LENGTH;
@@ -76,6 +77,84 @@ fn test_counter_canister() {
     assert_eq!(reply, vec![2, 0, 0, 0]);
 }
 
+#[test]
+fn test_query_call_with_trace() {
+    let pic = PocketIc::new();
+    let canister_id = deploy_counter_canister(&pic);
+    call_counter_canister(&pic, canister_id, "write");
+
+    let (result, trace) =
+        pic.query_call_with_trace(canister_id, Principal::anonymous(), "read", vec![]);
+    assert_eq!(result.unwrap(), vec![1, 0, 0, 0]);
+    assert_eq!(trace.call_graph_depth, 0);
+    assert!(trace.instructions_used > 0);
+    assert!(trace.max_call_graph_depth > 0);
+    let root = trace.root.unwrap();
+    assert_eq!(root.canister_id, canister_id);
+    assert_eq!(root.method, "read");
+    assert_eq!(root.outcome, QueryCallOutcome::Reply);
+    assert_eq!(root.response_size, 4);
+    assert!(root.calls.is_empty());
+
+    let (result, trace) =
+        pic.query_call_with_trace(canister_id, Principal::anonymous(), "unknown", vec![]);
+    let reject_response = result.unwrap_err();
+    match trace.root.unwrap().outcome {
+        QueryCallOutcome::Error(err) => assert_eq!(err, reject_response),
+        outcome => panic!("Unexpected outcome: {:?}", outcome),
+    }
+}
+
+#[test]
+fn test_coverage_report() {
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_wasm_coverage()
+        .build();
+    let canister_id = deploy_counter_canister(&pic);
+    call_counter_canister(&pic, canister_id, "write");
+    call_counter_canister(&pic, canister_id, "write");
+
+    let report = pic.coverage_report(canister_id);
+    let hits = |name: &str| {
+        report
+            .blocks
+            .iter()
+            .find(|block| block.function_name.as_deref() == Some(name))
+            .unwrap()
+            .hits
+    };
+    assert_eq!(hits("write"), 2);
+    // `write` calls `read`.
+    assert_eq!(hits("read"), 2);
+    assert!(report.lcov.contains("FNDA:2,write\n"));
+}
+
+#[test]
+fn test_operation_log_replay() {
+    let operation_log = tempfile::NamedTempFile::new().unwrap();
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_operation_log(operation_log.path().to_path_buf())
+        .build();
+    let canister_id = deploy_counter_canister(&pic);
+    call_counter_canister(&pic, canister_id, "write");
+    call_counter_canister(&pic, canister_id, "write");
+    pic.advance_time(std::time::Duration::from_secs(42));
+    pic.tick();
+    let time = pic.get_time();
+    let cycles = pic.cycle_balance(canister_id);
+
+    // The replayed instance ends up in the same state as the recorded one.
+    let replayed_pic = PocketIcBuilder::new()
+        .with_replayed_operation_log(operation_log.path().to_path_buf())
+        .build();
+    assert_eq!(replayed_pic.get_time(), time);
+    assert_eq!(replayed_pic.cycle_balance(canister_id), cycles);
+    let reply = call_counter_canister(&replayed_pic, canister_id, "read");
+    assert_eq!(reply, vec![2, 0, 0, 0]);
+}
+
 fn counter_wasm() -> Vec<u8> {
     const COUNTER_WAT: &str = r#"
     (module
@@ -1302,6 +1381,40 @@ fn test_canister_http_in_live_mode() {
     http_response.unwrap();
 }
 
+#[test]
+fn test_operation_log_replay_canister_http_in_live_mode() {
+    let operation_log = tempfile::NamedTempFile::new().unwrap();
+    let mut pic = PocketIcBuilder::new()
+        .with_nns_subnet()
+        .with_application_subnet()
+        .with_operation_log(operation_log.path().to_path_buf())
+        .build();
+
+    // The canister http outcall is made by the PocketIC server in the "live" mode.
+    let _ = pic.make_live(None);
+
+    let canister_id = pic.create_canister();
+    pic.add_cycles(canister_id, INIT_CYCLES);
+    pic.install_canister(canister_id, test_canister_wasm(), vec![], None);
+    let call_id = pic
+        .submit_call(
+            canister_id,
+            Principal::anonymous(),
+            "canister_http",
+            encode_one(()).unwrap(),
+        )
+        .unwrap();
+    let reply = pic.await_call_no_ticks(call_id.clone()).unwrap();
+    pic.stop_live();
+
+    // The replayed instance receives the recorded canister http outcall response
+    // without making the canister http outcall again.
+    let replayed_pic = PocketIcBuilder::new()
+        .with_replayed_operation_log(operation_log.path().to_path_buf())
+        .build();
+    assert_eq!(replayed_pic.ingress_status(call_id), Some(Ok(reply)));
+}
+
 #[test]
 fn test_canister_http_with_transform() {
     let pic = PocketIc::new();
@@ -1932,6 +2045,57 @@ fn test_canister_snapshots() {
     assert_eq!(snapshots[0].id, third_snapshot.id);
 }
 
+#[test]
+fn test_canister_snapshot_archive_round_trip() {
+    let pic = PocketIc::new();
+    let canister_id = deploy_counter_canister(&pic);
+    call_counter_canister(&pic, canister_id, "write");
+    call_counter_canister(&pic, canister_id, "write");
+
+    pic.stop_canister(canister_id, None).unwrap();
+    let snapshot = pic.take_canister_snapshot(canister_id, None, None).unwrap();
+    let archive = tempfile::NamedTempFile::new().unwrap();
+    pic.export_canister_snapshot(canister_id, None, snapshot.id, archive.path())
+        .unwrap();
+
+    // We restore the snapshot into a fresh canister on a different PocketIC instance.
+    let other_pic = PocketIc::new();
+    let other_canister_id = other_pic.create_canister();
+    other_pic.add_cycles(other_canister_id, INIT_CYCLES);
+    let snapshot_id = other_pic
+        .import_canister_snapshot(other_canister_id, None, None, archive.path())
+        .unwrap();
+    other_pic.stop_canister(other_canister_id, None).unwrap();
+    other_pic
+        .load_canister_snapshot(other_canister_id, None, snapshot_id)
+        .unwrap();
+    other_pic.start_canister(other_canister_id, None).unwrap();
+
+    // The restored canister has the state of the exported canister.
+    let reply = call_counter_canister(&other_pic, other_canister_id, "read");
+    assert_eq!(reply, 2_u32.to_le_bytes().to_vec());
+    let reply = call_counter_canister(&other_pic, other_canister_id, "write");
+    assert_eq!(reply, 3_u32.to_le_bytes().to_vec());
+
+    // A truncated archive is rejected and no partial snapshot is left behind.
+    let archive_bytes = std::fs::read(archive.path()).unwrap();
+    let truncated_archive = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        truncated_archive.path(),
+        &archive_bytes[..archive_bytes.len() / 2],
+    )
+    .unwrap();
+    let third_canister_id = other_pic.create_canister();
+    other_pic.add_cycles(third_canister_id, INIT_CYCLES);
+    other_pic
+        .import_canister_snapshot(third_canister_id, None, None, truncated_archive.path())
+        .unwrap_err();
+    assert!(other_pic
+        .list_canister_snapshots(third_canister_id, None)
+        .unwrap()
+        .is_empty());
+}
+
 #[test]
 fn test_wasm_chunk_store() {
     let pic = PocketIc::new();
@@ -2044,6 +2208,75 @@ fn canister_logs() {
     );
 }
 
+#[test]
+fn canister_logs_with_filter() {
+    let pic = PocketIc::new();
+
+    // We deploy the test canister.
+    let canister = pic.create_canister();
+    pic.add_cycles(canister, INIT_CYCLES);
+    pic.install_canister(canister, test_canister_wasm(), vec![], None);
+
+    for log_msg in ["info: first", "error: second", "info: third"] {
+        pic.update_call(
+            canister,
+            Principal::anonymous(),
+            "canister_log",
+            encode_one(log_msg).unwrap(),
+        )
+        .unwrap();
+    }
+
+    // Only log records matching the content filter are returned.
+    let page = pic
+        .fetch_canister_logs_with_filter(
+            canister,
+            Principal::anonymous(),
+            CanisterLogFilter {
+                content: Some(CanisterLogContentFilter::Substring("info".to_string())),
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
+    let contents: Vec<_> = page
+        .canister_log_records
+        .iter()
+        .map(|log| String::from_utf8(log.content.clone()).unwrap())
+        .collect();
+    assert_eq!(contents, vec!["info: first", "info: third"]);
+    assert_eq!(page.next_idx, Some(3));
+
+    // Log records are paginated using the returned cursor.
+    let page = pic
+        .fetch_canister_logs_with_filter(
+            canister,
+            Principal::anonymous(),
+            CanisterLogFilter::default(),
+            Some(2),
+        )
+        .unwrap();
+    assert_eq!(page.canister_log_records.len(), 2);
+    assert_eq!(page.next_idx, Some(2));
+    let page = pic
+        .fetch_canister_logs_with_filter(
+            canister,
+            Principal::anonymous(),
+            CanisterLogFilter {
+                idx_range: Some(CanisterLogRange::starting_at(page.next_idx.unwrap())),
+                ..Default::default()
+            },
+            Some(2),
+        )
+        .unwrap();
+    assert_eq!(page.canister_log_records.len(), 1);
+    assert_eq!(
+        String::from_utf8(page.canister_log_records[0].content.clone()).unwrap(),
+        "info: third"
+    );
+    assert_eq!(page.next_idx, Some(3));
+}
+
 #[test]
 fn get_subnet() {
     let pic = PocketIcBuilder::new()
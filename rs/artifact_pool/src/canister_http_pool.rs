@@ -15,7 +15,9 @@ use ic_logger::{warn, ReplicaLogger};
 use ic_metrics::MetricsRegistry;
 use ic_types::{
     artifact::CanisterHttpResponseId,
-    canister_http::{CanisterHttpResponse, CanisterHttpResponseShare},
+    canister_http::{
+        CanisterHttpResponse, CanisterHttpResponseArtifact, CanisterHttpResponseShare,
+    },
     crypto::CryptoHashOf,
 };
 use prometheus::IntCounter;
@@ -23,9 +25,13 @@ use prometheus::IntCounter;
 const POOL_CANISTER_HTTP: &str = "canister_http";
 const POOL_CANISTER_HTTP_CONTENT: &str = "canister_http_content";
 
-type ValidatedCanisterHttpPoolSection = PoolSection<CanisterHttpResponseShare, ()>;
+/// Shares are stored together with the response that is gossiped along with them,
+/// which is only the case for shares of non-replicated requests.
+type ValidatedCanisterHttpPoolSection =
+    PoolSection<CanisterHttpResponseShare, Option<CanisterHttpResponse>>;
 
-type UnvalidatedCanisterHttpPoolSection = PoolSection<CanisterHttpResponseShare, ()>;
+type UnvalidatedCanisterHttpPoolSection =
+    PoolSection<CanisterHttpResponseShare, Option<CanisterHttpResponse>>;
 
 type ContentCanisterHttpPoolSection =
     PoolSection<CryptoHashOf<CanisterHttpResponse>, CanisterHttpResponse>;
@@ -70,6 +76,13 @@ impl CanisterHttpPool for CanisterHttpPoolImpl {
         Box::new(self.unvalidated.keys())
     }
 
+    fn get_unvalidated_response(
+        &self,
+        share: &CanisterHttpResponseShare,
+    ) -> Option<&CanisterHttpResponse> {
+        self.unvalidated.get(share)?.as_ref()
+    }
+
     fn get_response_content_items(
         &self,
     ) -> Box<dyn Iterator<Item = (&CryptoHashOf<CanisterHttpResponse>, &CanisterHttpResponse)> + '_>
@@ -88,22 +101,23 @@ impl CanisterHttpPool for CanisterHttpPoolImpl {
         &self,
         msg_id: &CanisterHttpResponseId,
     ) -> Option<CanisterHttpResponseShare> {
-        self.validated.get(msg_id).map(|()| msg_id.clone())
+        self.validated.get(msg_id).map(|_| msg_id.clone())
     }
 
     fn lookup_unvalidated(
         &self,
         msg_id: &CanisterHttpResponseId,
     ) -> Option<CanisterHttpResponseShare> {
-        self.unvalidated.get(msg_id).map(|()| msg_id.clone())
+        self.unvalidated.get(msg_id).map(|_| msg_id.clone())
     }
 }
 
-impl MutablePool<CanisterHttpResponseShare> for CanisterHttpPoolImpl {
+impl MutablePool<CanisterHttpResponseArtifact> for CanisterHttpPoolImpl {
     type Mutations = CanisterHttpChangeSet;
 
-    fn insert(&mut self, artifact: UnvalidatedArtifact<CanisterHttpResponseShare>) {
-        self.unvalidated.insert(artifact.message, ());
+    fn insert(&mut self, artifact: UnvalidatedArtifact<CanisterHttpResponseArtifact>) {
+        let CanisterHttpResponseArtifact { share, response } = artifact.message;
+        self.unvalidated.insert(share, response);
     }
 
     fn remove(&mut self, id: &CanisterHttpResponseId) {
@@ -113,23 +127,44 @@ impl MutablePool<CanisterHttpResponseShare> for CanisterHttpPoolImpl {
     fn apply(
         &mut self,
         change_set: CanisterHttpChangeSet,
-    ) -> ArtifactTransmits<CanisterHttpResponseShare> {
+    ) -> ArtifactTransmits<CanisterHttpResponseArtifact> {
         let changed = !change_set.is_empty();
         let mut transmits = vec![];
         for action in change_set {
             match action {
                 CanisterHttpChangeAction::AddToValidated(share, content) => {
                     transmits.push(ArtifactTransmit::Deliver(ArtifactWithOpt {
-                        artifact: share.clone(),
+                        artifact: CanisterHttpResponseArtifact {
+                            share: share.clone(),
+                            response: None,
+                        },
                         is_latency_sensitive: true,
                     }));
-                    self.validated.insert(share, ());
+                    self.validated.insert(share, None);
+                    self.content
+                        .insert(ic_types::crypto::crypto_hash(&content), content);
+                }
+                CanisterHttpChangeAction::AddToValidatedWithResponse(share, content) => {
+                    transmits.push(ArtifactTransmit::Deliver(ArtifactWithOpt {
+                        artifact: CanisterHttpResponseArtifact {
+                            share: share.clone(),
+                            response: Some(content.clone()),
+                        },
+                        is_latency_sensitive: true,
+                    }));
+                    self.validated.insert(share, Some(content.clone()));
                     self.content
                         .insert(ic_types::crypto::crypto_hash(&content), content);
                 }
                 CanisterHttpChangeAction::MoveToValidated(share) => {
-                    if self.unvalidated.remove(&share).is_some() {
-                        self.validated.insert(share, ());
+                    if let Some(response) = self.unvalidated.remove(&share) {
+                        // Shares received from peers are not relayed, so the
+                        // response only needs to be kept as content.
+                        if let Some(content) = response {
+                            self.content
+                                .insert(ic_types::crypto::crypto_hash(&content), content);
+                        }
+                        self.validated.insert(share, None);
                     }
                 }
                 CanisterHttpChangeAction::RemoveValidated(id) => {
@@ -160,9 +195,14 @@ impl MutablePool<CanisterHttpResponseShare> for CanisterHttpPoolImpl {
     }
 }
 
-impl ValidatedPoolReader<CanisterHttpResponseShare> for CanisterHttpPoolImpl {
-    fn get(&self, id: &CanisterHttpResponseId) -> Option<CanisterHttpResponseShare> {
-        self.validated.get(id).map(|()| id.clone())
+impl ValidatedPoolReader<CanisterHttpResponseArtifact> for CanisterHttpPoolImpl {
+    fn get(&self, id: &CanisterHttpResponseId) -> Option<CanisterHttpResponseArtifact> {
+        self.validated
+            .get(id)
+            .map(|response| CanisterHttpResponseArtifact {
+                share: id.clone(),
+                response: response.clone(),
+            })
     }
 }
 
@@ -172,6 +212,12 @@ impl HasLabel for CanisterHttpResponse {
     }
 }
 
+impl HasLabel for Option<CanisterHttpResponse> {
+    fn label(&self) -> &str {
+        ""
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use ic_logger::replica_logger::no_op_logger;
@@ -190,10 +236,17 @@ mod tests {
     use super::*;
 
     fn to_unvalidated(
-        message: CanisterHttpResponseShare,
-    ) -> UnvalidatedArtifact<CanisterHttpResponseShare> {
-        UnvalidatedArtifact::<CanisterHttpResponseShare> {
-            message,
+        share: CanisterHttpResponseShare,
+    ) -> UnvalidatedArtifact<CanisterHttpResponseArtifact> {
+        to_unvalidated_with_response(share, None)
+    }
+
+    fn to_unvalidated_with_response(
+        share: CanisterHttpResponseShare,
+        response: Option<CanisterHttpResponse>,
+    ) -> UnvalidatedArtifact<CanisterHttpResponseArtifact> {
+        UnvalidatedArtifact::<CanisterHttpResponseArtifact> {
+            message: CanisterHttpResponseArtifact { share, response },
             peer_id: node_test_id(0),
             timestamp: UNIX_EPOCH,
         }
@@ -255,7 +308,13 @@ mod tests {
         assert!(result.poll_immediately);
         assert_eq!(result.transmits.len(), 2);
         assert_eq!(share, pool.lookup_validated(&id).unwrap());
-        assert_eq!(share, pool.get(&id).unwrap());
+        assert_eq!(
+            CanisterHttpResponseArtifact {
+                share: share.clone(),
+                response: None,
+            },
+            pool.get(&id).unwrap()
+        );
         assert_eq!(
             response,
             pool.get_response_content_by_hash(&content_hash).unwrap()
@@ -299,6 +358,62 @@ mod tests {
         assert_eq!(share1, pool.lookup_validated(&id1).unwrap());
     }
 
+    #[test]
+    fn test_canister_http_pool_gossips_response_of_non_replicated_share() {
+        let mut pool = CanisterHttpPoolImpl::new(MetricsRegistry::new(), no_op_logger());
+        let share = fake_share(123);
+        let id = share.clone();
+        let response = fake_response(123);
+        let content_hash = ic_types::crypto::crypto_hash(&response);
+
+        let result = pool.apply(vec![CanisterHttpChangeAction::AddToValidatedWithResponse(
+            share.clone(),
+            response.clone(),
+        )]);
+
+        let expected = CanisterHttpResponseArtifact {
+            share,
+            response: Some(response.clone()),
+        };
+        assert_eq!(result.transmits.len(), 1);
+        assert!(
+            matches!(&result.transmits[0], ArtifactTransmit::Deliver(x) if x.artifact == expected)
+        );
+        assert_eq!(expected, pool.get(&id).unwrap());
+        assert_eq!(
+            response,
+            pool.get_response_content_by_hash(&content_hash).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_canister_http_pool_move_to_validated_keeps_gossiped_response() {
+        let mut pool = CanisterHttpPoolImpl::new(MetricsRegistry::new(), no_op_logger());
+        let share = fake_share(123);
+        let id = share.clone();
+        let response = fake_response(123);
+        let content_hash = ic_types::crypto::crypto_hash(&response);
+
+        pool.insert(to_unvalidated_with_response(
+            share.clone(),
+            Some(response.clone()),
+        ));
+        assert_eq!(Some(&response), pool.get_unvalidated_response(&share));
+        assert!(pool.get_response_content_by_hash(&content_hash).is_none());
+
+        let result = pool.apply(vec![CanisterHttpChangeAction::MoveToValidated(
+            share.clone(),
+        )]);
+
+        assert!(result.transmits.is_empty());
+        assert!(pool.lookup_unvalidated(&id).is_none());
+        assert_eq!(share, pool.lookup_validated(&id).unwrap());
+        assert_eq!(
+            response,
+            pool.get_response_content_by_hash(&content_hash).unwrap()
+        );
+    }
+
     #[test]
     fn test_canister_http_pool_remove_unvalidated() {
         let mut pool = CanisterHttpPoolImpl::new(MetricsRegistry::new(), no_op_logger());
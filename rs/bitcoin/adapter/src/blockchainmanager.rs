@@ -1,17 +1,21 @@
 use crate::{
     blockchainstate::{AddHeaderError, BlockchainState},
     common::{BlockHeight, MINIMUM_VERSION_NUMBER},
+    config::Config,
     metrics::RouterMetrics,
     Channel, Command, ProcessBitcoinNetworkMessageError,
 };
 use bitcoin::{
+    bip158::{BlockFilter, FilterHeader},
     block::Header as BlockHeader,
     hashes::Hash as _,
     p2p::{
         message::{NetworkMessage, MAX_INV_SIZE},
         message_blockdata::{GetHeadersMessage, Inventory},
+        message_filter::{CFHeaders, CFilter, GetCFHeaders, GetCFilters},
+        ServiceFlags,
     },
-    Block, BlockHash,
+    Block, BlockHash, ScriptBuf,
 };
 use hashlink::{LinkedHashMap, LinkedHashSet};
 use ic_btc_validation::ValidateHeaderError;
@@ -42,6 +46,25 @@ const MAX_UNSOLICITED_HEADERS: usize = 20;
 /// to a peer at a time.
 const INV_PER_GET_DATA_REQUEST: u32 = 8;
 
+/// This constant is the maximum time to wait until we get a response to a getcfheaders or
+/// getcfilters request sent by us.
+const GETCFILTERS_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Max number of "getcfheaders" and "getcfilters" requests that can be in flight to a peer at a time.
+const MAX_IN_FLIGHT_GETCFILTERS_PER_PEER: usize = 8;
+
+/// Max number of filter headers of validated filters that are kept to validate the filter
+/// headers of their children. When the limit is exceeded, the oldest filter headers are dropped.
+const MAX_BLOCK_FILTER_HEADERS: usize = 10_000;
+
+/// Max number of blocks waiting for their compact block filter. When the limit is
+/// exceeded, the oldest blocks are dropped and their filters are never requested.
+const MAX_PENDING_BLOCK_FILTERS: usize = 100;
+
+/// The BIP-158 filter type of basic block filters.
+/// https://github.com/bitcoin/bips/blob/master/bip-0158.mediawiki#block-filters
+const BASIC_FILTER_TYPE: u8 = 0;
+
 /// Block locators. Consists of starting hashes and a stop hash.
 type Locators = (Vec<BlockHash>, BlockHash);
 
@@ -84,6 +107,21 @@ pub enum ReceivedBlockMessageError {
     BlockNotAdded,
 }
 
+/// The possible errors the `BlockchainManager::received_cfheaders_message(...)` and
+/// `BlockchainManager::received_cfilter_message(...)` may produce.
+#[derive(Debug, Error)]
+enum ReceivedCFilterMessageError {
+    /// This variant represents when a message from an unknown peer.
+    #[error("Unknown peer")]
+    UnknownPeer,
+    #[error("Received a filter of unsupported type {0}")]
+    UnsupportedFilterType(u8),
+    #[error("Received filter headers for block {0} that do not extend the filter header chain")]
+    InvalidFilterHeaders(BlockHash),
+    #[error("Received a filter for block {0} that does not match the block or its filter header")]
+    InvalidFilter(BlockHash),
+}
+
 /// This struct stores the information regarding a peer with respect to synchronizing the blockchain.
 /// This information is useful to keep track of the commands that have been sent to the peer,
 /// and how much blockchain state has already been synced with the peer.
@@ -131,6 +169,39 @@ struct GetDataRequestInfo {
     sent_at: Option<Instant>,
 }
 
+/// The BIP-158 filter headers of a block as advertised in a "cfheaders" message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FilterHeaders {
+    /// The filter header of the block's parent.
+    previous: FilterHeader,
+    /// The filter header of the block, which commits to the block's filter and to `previous`.
+    current: FilterHeader,
+}
+
+/// This struct stores the information related to the "getcfheaders" and "getcfilters"
+/// requests for the BIP-158 basic filter of a single block.
+///
+/// The adapter does not know the scripts spent by the block's inputs, which basic filters
+/// also contain. Hence, the filter headers of the block are requested first, and the filter
+/// is accepted only if it matches the block's filter header, which in turn must extend the
+/// filter header of the block's parent.
+#[derive(Debug)]
+struct GetCFiltersRequestInfo {
+    /// This field stores the height of the block.
+    height: BlockHeight,
+    /// This field stores the hash of the block's parent.
+    prev_block_hash: BlockHash,
+    /// This field stores the output scripts of the block, which a valid basic filter must match.
+    /// OP_RETURN and empty scripts are excluded, as they are not part of basic filters.
+    output_scripts: Vec<ScriptBuf>,
+    /// This field stores the filter headers of the block. When assigned `None`, the
+    /// "getcfheaders" request has yet to be answered and the filter is not requested.
+    filter_headers: Option<FilterHeaders>,
+    /// This field stores the socket address of the Bitcoin node to which the pending request was
+    /// sent and the time at which it was sent. When assigned `None`, the request has yet to be sent.
+    sent: Option<(SocketAddr, Instant)>,
+}
+
 /// The BlockChainManager struct handles interactions that involve the headers.
 pub struct BlockchainManager {
     /// This field contains the BlockchainState, which stores and manages
@@ -167,6 +238,20 @@ pub struct BlockchainManager {
     /// A block hash is removed when it is determined a peer can receive another `getdata` message.
     block_sync_queue: LinkedHashSet<BlockHash>,
 
+    /// When this field is set to `true`, the BlockChainManager downloads the BIP-158 basic filter
+    /// of every block that is added to the block cache.
+    compact_block_filters: bool,
+
+    /// This map stores the blocks whose filter has yet to be received, in the order the blocks
+    /// were received. An entry is added when a block is added to the block cache and removed
+    /// when the corresponding valid "cfilter" message is received.
+    getcfilters_request_info: LinkedHashMap<BlockHash, GetCFiltersRequestInfo>,
+
+    /// This map stores the filter headers of the blocks whose filter was validated, in the
+    /// order the filters were received. The filter headers advertised for a block must extend
+    /// the filter header of its parent if the parent is in this map.
+    block_filter_headers: LinkedHashMap<BlockHash, FilterHeader>,
+
     /// This field contains a logger for the blockchain manager's use.
     logger: ReplicaLogger,
     metrics: RouterMetrics,
@@ -177,6 +262,7 @@ impl BlockchainManager {
     /// in order to get its client so the manager can send messages to the
     /// BTC network.
     pub fn new(
+        config: &Config,
         blockchain: Arc<Mutex<BlockchainState>>,
         logger: ReplicaLogger,
         metrics: RouterMetrics,
@@ -192,6 +278,9 @@ impl BlockchainManager {
             getheaders_requests: HashMap::new(),
             catchup_headers: HashSet::new(),
             block_sync_queue: LinkedHashSet::new(),
+            compact_block_filters: config.compact_block_filters,
+            getcfilters_request_info: LinkedHashMap::new(),
+            block_filter_headers: LinkedHashMap::new(),
             logger,
             metrics,
         }
@@ -205,6 +294,7 @@ impl BlockchainManager {
 
         self.block_sync_queue.clear();
         self.getdata_request_info.clear();
+        self.getcfilters_request_info.clear();
         self.peer_info.clear();
         self.blockchain.lock().unwrap().clear_blocks();
     }
@@ -433,8 +523,22 @@ impl BlockchainManager {
             block_hash
         );
 
-        match self.blockchain.lock().unwrap().add_block(block.clone()) {
-            Ok(()) => Ok(()),
+        let maybe_height = {
+            let mut blockchain = self.blockchain.lock().unwrap();
+            blockchain
+                .add_block(block.clone())
+                .map(|()| blockchain.get_cached_header(&block_hash).map(|c| c.height))
+        };
+
+        match maybe_height {
+            Ok(maybe_height) => {
+                if let Some(height) = maybe_height {
+                    if self.compact_block_filters {
+                        self.enqueue_block_filter_to_download(block, height);
+                    }
+                }
+                Ok(())
+            }
             Err(err) => {
                 warn!(
                     self.logger,
@@ -445,6 +549,192 @@ impl BlockchainManager {
         }
     }
 
+    /// This function adds the given block to the blocks whose BIP-158 basic filter has yet to
+    /// be downloaded.
+    fn enqueue_block_filter_to_download(&mut self, block: &Block, height: BlockHeight) {
+        let output_scripts = block
+            .txdata
+            .iter()
+            .flat_map(|tx| tx.output.iter())
+            .map(|output| output.script_pubkey.clone())
+            .filter(|script| !script.is_empty() && !script.is_op_return())
+            .collect();
+
+        self.getcfilters_request_info.replace(
+            block.block_hash(),
+            GetCFiltersRequestInfo {
+                height,
+                prev_block_hash: block.header.prev_blockhash,
+                output_scripts,
+                filter_headers: None,
+                sent: None,
+            },
+        );
+
+        while self.getcfilters_request_info.len() > MAX_PENDING_BLOCK_FILTERS {
+            self.getcfilters_request_info.pop_front();
+        }
+    }
+
+    /// This function returns the pending filter request for the given block if it was sent to
+    /// the given peer. Responses to requests that were not sent to the peer, e.g., responses to
+    /// requests that timed out and were sent to another peer, are ignored.
+    fn get_filter_request_sent_to(
+        &self,
+        addr: &SocketAddr,
+        block_hash: &BlockHash,
+    ) -> Option<&GetCFiltersRequestInfo> {
+        let request = self.getcfilters_request_info.get(block_hash);
+        let request =
+            request.filter(|request| request.sent.is_some_and(|(socket, _)| socket == *addr));
+        if request.is_none() {
+            trace!(
+                self.logger,
+                "Received an unsolicited filter message from {} for block {}",
+                addr,
+                block_hash
+            );
+        }
+        request
+    }
+
+    /// This function processes "cfheaders" messages received from Bitcoin nodes.
+    fn received_cfheaders_message(
+        &mut self,
+        addr: &SocketAddr,
+        cfheaders: &CFHeaders,
+    ) -> Result<(), ReceivedCFilterMessageError> {
+        if !self.peer_info.contains_key(addr) {
+            return Err(ReceivedCFilterMessageError::UnknownPeer);
+        }
+
+        if cfheaders.filter_type != BASIC_FILTER_TYPE {
+            return Err(ReceivedCFilterMessageError::UnsupportedFilterType(
+                cfheaders.filter_type,
+            ));
+        }
+
+        let block_hash = cfheaders.stop_hash;
+        let prev_block_hash = match self.get_filter_request_sent_to(addr, &block_hash) {
+            Some(request) if request.filter_headers.is_none() => request.prev_block_hash,
+            _ => return Ok(()),
+        };
+        let parent_filter_header = self.block_filter_headers.get(&prev_block_hash).copied();
+        let request = self
+            .getcfilters_request_info
+            .get_mut(&block_hash)
+            .expect("bug: the request must exist");
+        // Request the filter headers again, most likely from another peer as this one is discarded.
+        request.sent = None;
+
+        // A single block was requested.
+        let filter_hash = match cfheaders.filter_hashes.as_slice() {
+            [filter_hash] => filter_hash,
+            _ => {
+                return Err(ReceivedCFilterMessageError::InvalidFilterHeaders(
+                    block_hash,
+                ))
+            }
+        };
+        if parent_filter_header.is_some_and(|parent_filter_header| {
+            parent_filter_header != cfheaders.previous_filter_header
+        }) {
+            return Err(ReceivedCFilterMessageError::InvalidFilterHeaders(
+                block_hash,
+            ));
+        }
+
+        trace!(
+            self.logger,
+            "Received filter headers from {} for block {}",
+            addr,
+            block_hash
+        );
+        request.filter_headers = Some(FilterHeaders {
+            previous: cfheaders.previous_filter_header,
+            current: filter_hash.filter_header(&cfheaders.previous_filter_header),
+        });
+        Ok(())
+    }
+
+    /// This function processes "cfilter" messages received from Bitcoin nodes.
+    fn received_cfilter_message(
+        &mut self,
+        addr: &SocketAddr,
+        cfilter: &CFilter,
+    ) -> Result<(), ReceivedCFilterMessageError> {
+        if !self.peer_info.contains_key(addr) {
+            return Err(ReceivedCFilterMessageError::UnknownPeer);
+        }
+
+        if cfilter.filter_type != BASIC_FILTER_TYPE {
+            return Err(ReceivedCFilterMessageError::UnsupportedFilterType(
+                cfilter.filter_type,
+            ));
+        }
+
+        let filter_headers = match self.get_filter_request_sent_to(addr, &cfilter.block_hash) {
+            Some(GetCFiltersRequestInfo {
+                filter_headers: Some(filter_headers),
+                ..
+            }) => *filter_headers,
+            _ => return Ok(()),
+        };
+
+        let request = self
+            .getcfilters_request_info
+            .remove(&cfilter.block_hash)
+            .expect("bug: the request must exist");
+
+        // The filter must commit to the filter header of the block. Additionally, a basic
+        // filter contains all output scripts of the block. Any missing script means that the
+        // filter would wrongly report the block as irrelevant to that script.
+        let filter = BlockFilter::new(&cfilter.filter);
+        let is_valid = filter.filter_header(&filter_headers.previous) == filter_headers.current
+            && (request.output_scripts.is_empty()
+                || filter
+                    .match_all(
+                        cfilter.block_hash,
+                        request
+                            .output_scripts
+                            .iter()
+                            .map(|script| script.as_bytes()),
+                    )
+                    .unwrap_or(false));
+        if !is_valid {
+            // Request the filter headers and the filter again, most likely from another peer as
+            // this one is discarded. The filter headers may have come from this peer as well.
+            self.getcfilters_request_info.insert(
+                cfilter.block_hash,
+                GetCFiltersRequestInfo {
+                    filter_headers: None,
+                    sent: None,
+                    ..request
+                },
+            );
+            return Err(ReceivedCFilterMessageError::InvalidFilter(
+                cfilter.block_hash,
+            ));
+        }
+
+        trace!(
+            self.logger,
+            "Received filter from {} for block {}",
+            addr,
+            cfilter.block_hash
+        );
+        self.block_filter_headers
+            .replace(cfilter.block_hash, filter_headers.current);
+        while self.block_filter_headers.len() > MAX_BLOCK_FILTER_HEADERS {
+            self.block_filter_headers.pop_front();
+        }
+        self.blockchain
+            .lock()
+            .unwrap()
+            .add_block_filter(cfilter.block_hash, cfilter.filter.clone());
+        Ok(())
+    }
+
     /// This function adds a new peer to `peer_info`
     /// and initiates sync with the peer by sending `getheaders` message.
     fn add_peer(&mut self, channel: &mut impl Channel, addr: &SocketAddr) {
@@ -486,6 +776,13 @@ impl BlockchainManager {
             }
         }
 
+        // Setting to `None` to ensure the `getcfheaders` and `getcfilters` requests sent to the peer are retried in `sync_block_filters`.
+        for request in self.getcfilters_request_info.values_mut() {
+            if request.sent.is_some_and(|(socket, _)| socket == *addr) {
+                request.sent = None;
+            }
+        }
+
         // Remove getheaders request sent to peer.
         self.getheaders_requests.remove(addr);
         // Unset catch-up flag
@@ -620,6 +917,113 @@ impl BlockchainManager {
         }
     }
 
+    /// Sends "getcfheaders" and "getcfilters" requests for the blocks whose filter has yet to be
+    /// received to the peers that advertise `NODE_COMPACT_FILTERS`. The filter headers of a block
+    /// are requested only after the filter of its parent was received, if the parent's filter is
+    /// pending, so that they can be validated against the parent's filter header.
+    fn sync_block_filters(&mut self, channel: &mut impl Channel) {
+        if !self.compact_block_filters {
+            return;
+        }
+
+        // Timeout requests so they may be retried again.
+        for request in self.getcfilters_request_info.values_mut() {
+            if request
+                .sent
+                .is_some_and(|(_, sent_at)| sent_at.elapsed() > GETCFILTERS_REQUEST_TIMEOUT)
+            {
+                request.sent = None;
+            }
+        }
+
+        // Count the number of requests per peer that is able to serve filters.
+        let mut requests_per_peer: HashMap<SocketAddr, usize> = self
+            .peer_info
+            .keys()
+            .filter(|addr| {
+                channel
+                    .peer_services(addr)
+                    .has(ServiceFlags::COMPACT_FILTERS)
+            })
+            .map(|addr| (*addr, 0))
+            .collect();
+        if requests_per_peer.is_empty() {
+            return;
+        }
+        for request in self.getcfilters_request_info.values() {
+            if let Some((socket, _)) = request.sent {
+                if let Some(counter) = requests_per_peer.get_mut(&socket) {
+                    *counter = counter.saturating_add(1);
+                }
+            }
+        }
+
+        let waiting_for_parent: HashSet<BlockHash> = self
+            .getcfilters_request_info
+            .iter()
+            .filter(|(_, request)| {
+                request.filter_headers.is_none()
+                    && self
+                        .getcfilters_request_info
+                        .contains_key(&request.prev_block_hash)
+            })
+            .map(|(block_hash, _)| *block_hash)
+            .collect();
+
+        for (block_hash, request) in self.getcfilters_request_info.iter_mut() {
+            if request.sent.is_some() || waiting_for_parent.contains(block_hash) {
+                continue;
+            }
+
+            let peer = match requests_per_peer
+                .iter_mut()
+                .find(|(_, counter)| **counter < MAX_IN_FLIGHT_GETCFILTERS_PER_PEER)
+            {
+                Some((peer, counter)) => {
+                    *counter = counter.saturating_add(1);
+                    *peer
+                }
+                // All peers are busy.
+                None => break,
+            };
+
+            let message = if request.filter_headers.is_none() {
+                trace!(
+                    self.logger,
+                    "Sending getcfheaders to {} : Block {}",
+                    peer,
+                    block_hash
+                );
+                NetworkMessage::GetCFHeaders(GetCFHeaders {
+                    filter_type: BASIC_FILTER_TYPE,
+                    start_height: request.height,
+                    stop_hash: *block_hash,
+                })
+            } else {
+                trace!(
+                    self.logger,
+                    "Sending getcfilters to {} : Block {}",
+                    peer,
+                    block_hash
+                );
+                NetworkMessage::GetCFilters(GetCFilters {
+                    filter_type: BASIC_FILTER_TYPE,
+                    start_height: request.height,
+                    stop_hash: *block_hash,
+                })
+            };
+
+            channel
+                .send(Command {
+                    address: Some(peer),
+                    message,
+                })
+                .ok();
+
+            request.sent = Some((peer, Instant::now()));
+        }
+    }
+
     /// This function is called by the adapter when a new event takes place.
     /// The event could be receiving "getheaders", "getdata", "inv" messages from bitcoin peers.
     /// The event could be change in connection status with a bitcoin peer.
@@ -654,6 +1058,21 @@ impl BlockchainManager {
                     return Err(ProcessBitcoinNetworkMessageError::InvalidMessage);
                 }
             }
+            NetworkMessage::CFHeaders(cfheaders) => {
+                if let Err(err) = self.received_cfheaders_message(&addr, cfheaders) {
+                    warn!(
+                        self.logger,
+                        "Received invalid filter headers {}: {}", addr, err
+                    );
+                    return Err(ProcessBitcoinNetworkMessageError::InvalidMessage);
+                }
+            }
+            NetworkMessage::CFilter(cfilter) => {
+                if let Err(err) = self.received_cfilter_message(&addr, cfilter) {
+                    warn!(self.logger, "Received an invalid filter {}: {}", addr, err);
+                    return Err(ProcessBitcoinNetworkMessageError::InvalidMessage);
+                }
+            }
             _ => {}
         };
         Ok(())
@@ -690,6 +1109,7 @@ impl BlockchainManager {
         }
 
         self.sync_blocks(channel);
+        self.sync_block_filters(channel);
         self.handle_getheaders_timeouts(channel);
     }
 
@@ -783,6 +1203,7 @@ pub mod test {
         (
             *blockchain_state.genesis(),
             BlockchainManager::new(
+                config,
                 Arc::new(Mutex::new(blockchain_state)),
                 no_op_logger(),
                 RouterMetrics::new(&MetricsRegistry::default()),
@@ -1640,4 +2061,341 @@ pub mod test {
         assert!(channel.has_discarded_address(&addr));
         assert!(!channel.has_discarded_address(&addr2));
     }
+
+    /// Adds the given block to the blockchain manager as if it was requested from and sent by `peer`.
+    fn receive_block(blockchain_manager: &mut BlockchainManager, peer: &SocketAddr, block: &Block) {
+        blockchain_manager.getdata_request_info.insert(
+            block.block_hash(),
+            GetDataRequestInfo {
+                socket: *peer,
+                sent_at: Some(Instant::now()),
+            },
+        );
+        blockchain_manager
+            .received_block_message(peer, block)
+            .expect("should be able to receive the block");
+    }
+
+    /// Creates a blockchain manager that downloads block filters and has received block 1
+    /// from the peer `block_peer`.
+    fn create_blockchain_manager_with_block_1(
+        channel: &mut TestChannel,
+        block_peer: &SocketAddr,
+        filter_peer: &SocketAddr,
+    ) -> (Block, BlockchainManager) {
+        let config = ConfigBuilder::new()
+            .with_compact_block_filters(true)
+            .build();
+        let (_, mut blockchain_manager) = create_blockchain_manager(&config);
+        let block_1 = TestState::setup().block_1;
+
+        blockchain_manager.add_peer(channel, block_peer);
+        blockchain_manager.add_peer(channel, filter_peer);
+        receive_block(&mut blockchain_manager, block_peer, &block_1);
+        while channel.pop_front().is_some() {}
+
+        (block_1, blockchain_manager)
+    }
+
+    fn basic_filter(block: &Block) -> Vec<u8> {
+        BlockFilter::new_script_filter(block, |outpoint| {
+            Err::<ScriptBuf, _>(bitcoin::bip158::Error::UtxoMissing(*outpoint))
+        })
+        .expect("should be able to compute the filter of a block with only a coinbase")
+        .content
+    }
+
+    /// Returns the "cfheaders" message for the block with the given hash and filter.
+    fn basic_filter_headers(
+        block_hash: BlockHash,
+        filter: &[u8],
+        previous_filter_header: FilterHeader,
+    ) -> CFHeaders {
+        CFHeaders {
+            filter_type: BASIC_FILTER_TYPE,
+            stop_hash: block_hash,
+            previous_filter_header,
+            filter_hashes: vec![bitcoin::bip158::FilterHash::hash(filter)],
+        }
+    }
+
+    /// Sends the pending filter requests and checks that the only request is the given message
+    /// sent to `filter_peer`.
+    fn assert_sent_filter_request(
+        blockchain_manager: &mut BlockchainManager,
+        channel: &mut TestChannel,
+        filter_peer: &SocketAddr,
+        message: NetworkMessage,
+    ) {
+        blockchain_manager.sync_block_filters(channel);
+        assert_eq!(channel.command_count(), 1);
+        let command = channel.pop_front().unwrap();
+        assert_eq!(command.address, Some(*filter_peer));
+        assert_eq!(command.message, message);
+    }
+
+    /// Tests that the filter headers and then the filter of a received block are requested only
+    /// from peers that advertise `NODE_COMPACT_FILTERS` and that the filter is stored once it
+    /// matches the block's outputs and filter header.
+    #[test]
+    fn test_sync_block_filters_from_compact_filters_peer() {
+        let block_peer = SocketAddr::from_str("127.0.0.1:8333").expect("bad address format");
+        let filter_peer = SocketAddr::from_str("127.0.0.1:8444").expect("bad address format");
+        let mut channel = TestChannel::new(vec![block_peer, filter_peer]);
+        channel.set_services(block_peer, ServiceFlags::NETWORK);
+        channel.set_services(
+            filter_peer,
+            ServiceFlags::NETWORK | ServiceFlags::COMPACT_FILTERS,
+        );
+        let (block_1, mut blockchain_manager) =
+            create_blockchain_manager_with_block_1(&mut channel, &block_peer, &filter_peer);
+        let block_hash = block_1.block_hash();
+        let filter = basic_filter(&block_1);
+        let cfheaders = basic_filter_headers(block_hash, &filter, FilterHeader::all_zeros());
+
+        assert_sent_filter_request(
+            &mut blockchain_manager,
+            &mut channel,
+            &filter_peer,
+            NetworkMessage::GetCFHeaders(GetCFHeaders {
+                filter_type: BASIC_FILTER_TYPE,
+                start_height: 1,
+                stop_hash: block_hash,
+            }),
+        );
+
+        // Filter headers sent by a peer they were not requested from are ignored.
+        blockchain_manager
+            .received_cfheaders_message(&block_peer, &cfheaders)
+            .expect("unsolicited filter headers should be ignored");
+        blockchain_manager
+            .received_cfheaders_message(&filter_peer, &cfheaders)
+            .expect("the filter headers should be valid");
+
+        assert_sent_filter_request(
+            &mut blockchain_manager,
+            &mut channel,
+            &filter_peer,
+            NetworkMessage::GetCFilters(GetCFilters {
+                filter_type: BASIC_FILTER_TYPE,
+                start_height: 1,
+                stop_hash: block_hash,
+            }),
+        );
+
+        // A filter sent by a peer it was not requested from is ignored.
+        let cfilter = CFilter {
+            filter_type: BASIC_FILTER_TYPE,
+            block_hash,
+            filter,
+        };
+        assert!(blockchain_manager
+            .received_cfilter_message(&block_peer, &cfilter)
+            .is_ok());
+        assert!(blockchain_manager
+            .blockchain
+            .lock()
+            .unwrap()
+            .get_block_filter(&block_hash)
+            .is_none());
+
+        blockchain_manager
+            .received_cfilter_message(&filter_peer, &cfilter)
+            .expect("the filter should be valid");
+        assert_eq!(
+            blockchain_manager
+                .blockchain
+                .lock()
+                .unwrap()
+                .get_block_filter(&block_hash)
+                .as_deref(),
+            Some(&cfilter.filter)
+        );
+        assert_eq!(
+            blockchain_manager.block_filter_headers.get(&block_hash),
+            Some(&cfheaders.filter_hashes[0].filter_header(&FilterHeader::all_zeros()))
+        );
+        assert!(blockchain_manager.getcfilters_request_info.is_empty());
+    }
+
+    /// Tests that a filter that does not match the block's outputs or the block's filter header
+    /// is rejected and that its filter headers and the filter are requested again.
+    #[test]
+    fn test_received_cfilter_message_rejects_invalid_filter() {
+        let block_peer = SocketAddr::from_str("127.0.0.1:8333").expect("bad address format");
+        let filter_peer = SocketAddr::from_str("127.0.0.1:8444").expect("bad address format");
+        let mut channel = TestChannel::new(vec![block_peer, filter_peer]);
+        channel.set_services(
+            filter_peer,
+            ServiceFlags::NETWORK | ServiceFlags::COMPACT_FILTERS,
+        );
+        let (block_1, mut blockchain_manager) =
+            create_blockchain_manager_with_block_1(&mut channel, &block_peer, &filter_peer);
+        let block_hash = block_1.block_hash();
+        // The filter of block 2 does not contain the output script of block 1.
+        let invalid_filter = basic_filter(&TestState::setup().block_2);
+
+        for filter_headers in [
+            // The filter matches the filter header but not the block's outputs.
+            basic_filter_headers(block_hash, &invalid_filter, FilterHeader::all_zeros()),
+            // The filter does not match the filter header.
+            basic_filter_headers(
+                block_hash,
+                &basic_filter(&block_1),
+                FilterHeader::all_zeros(),
+            ),
+        ] {
+            blockchain_manager.sync_block_filters(&mut channel);
+            assert_eq!(channel.command_count(), 1);
+            channel.pop_front();
+            blockchain_manager
+                .received_cfheaders_message(&filter_peer, &filter_headers)
+                .expect("the filter headers should be valid");
+            blockchain_manager.sync_block_filters(&mut channel);
+            assert_eq!(channel.command_count(), 1);
+            channel.pop_front();
+
+            let cfilter = CFilter {
+                filter_type: BASIC_FILTER_TYPE,
+                block_hash,
+                filter: invalid_filter.clone(),
+            };
+            let result = blockchain_manager.received_cfilter_message(&filter_peer, &cfilter);
+            assert!(matches!(
+                result,
+                Err(ReceivedCFilterMessageError::InvalidFilter(hash)) if hash == block_hash
+            ));
+            assert!(blockchain_manager
+                .blockchain
+                .lock()
+                .unwrap()
+                .get_block_filter(&block_hash)
+                .is_none());
+
+            let request = blockchain_manager
+                .getcfilters_request_info
+                .get(&block_hash)
+                .expect("the filter should be requested again");
+            assert!(request.sent.is_none());
+            assert!(request.filter_headers.is_none());
+        }
+    }
+
+    /// Tests that the filter headers of a block are requested only once the filter of its parent
+    /// was received and that they must extend the filter header of the parent.
+    #[test]
+    fn test_received_cfheaders_message_validates_filter_header_chain() {
+        let block_peer = SocketAddr::from_str("127.0.0.1:8333").expect("bad address format");
+        let filter_peer = SocketAddr::from_str("127.0.0.1:8444").expect("bad address format");
+        let mut channel = TestChannel::new(vec![block_peer, filter_peer]);
+        channel.set_services(
+            filter_peer,
+            ServiceFlags::NETWORK | ServiceFlags::COMPACT_FILTERS,
+        );
+        let (block_1, mut blockchain_manager) =
+            create_blockchain_manager_with_block_1(&mut channel, &block_peer, &filter_peer);
+        let block_2 = TestState::setup().block_2;
+        receive_block(&mut blockchain_manager, &block_peer, &block_2);
+
+        // Only the filter headers of block 1 are requested as the filter of block 2's
+        // parent is pending.
+        let filter_1 = basic_filter(&block_1);
+        let cfheaders_1 =
+            basic_filter_headers(block_1.block_hash(), &filter_1, FilterHeader::all_zeros());
+        assert_sent_filter_request(
+            &mut blockchain_manager,
+            &mut channel,
+            &filter_peer,
+            NetworkMessage::GetCFHeaders(GetCFHeaders {
+                filter_type: BASIC_FILTER_TYPE,
+                start_height: 1,
+                stop_hash: block_1.block_hash(),
+            }),
+        );
+        blockchain_manager
+            .received_cfheaders_message(&filter_peer, &cfheaders_1)
+            .expect("the filter headers should be valid");
+        blockchain_manager.sync_block_filters(&mut channel);
+        while channel.pop_front().is_some() {}
+        blockchain_manager
+            .received_cfilter_message(
+                &filter_peer,
+                &CFilter {
+                    filter_type: BASIC_FILTER_TYPE,
+                    block_hash: block_1.block_hash(),
+                    filter: filter_1,
+                },
+            )
+            .expect("the filter should be valid");
+        let filter_header_1 = blockchain_manager.block_filter_headers[&block_1.block_hash()];
+
+        assert_sent_filter_request(
+            &mut blockchain_manager,
+            &mut channel,
+            &filter_peer,
+            NetworkMessage::GetCFHeaders(GetCFHeaders {
+                filter_type: BASIC_FILTER_TYPE,
+                start_height: 2,
+                stop_hash: block_2.block_hash(),
+            }),
+        );
+
+        // The filter headers of block 2 do not extend the filter header of block 1.
+        let filter_2 = basic_filter(&block_2);
+        let result = blockchain_manager.received_cfheaders_message(
+            &filter_peer,
+            &basic_filter_headers(block_2.block_hash(), &filter_2, FilterHeader::all_zeros()),
+        );
+        assert!(matches!(
+            result,
+            Err(ReceivedCFilterMessageError::InvalidFilterHeaders(hash)) if hash == block_2.block_hash()
+        ));
+        let request = &blockchain_manager.getcfilters_request_info[&block_2.block_hash()];
+        assert!(request.sent.is_none());
+        assert!(request.filter_headers.is_none());
+
+        assert_sent_filter_request(
+            &mut blockchain_manager,
+            &mut channel,
+            &filter_peer,
+            NetworkMessage::GetCFHeaders(GetCFHeaders {
+                filter_type: BASIC_FILTER_TYPE,
+                start_height: 2,
+                stop_hash: block_2.block_hash(),
+            }),
+        );
+        blockchain_manager
+            .received_cfheaders_message(
+                &filter_peer,
+                &basic_filter_headers(block_2.block_hash(), &filter_2, filter_header_1),
+            )
+            .expect("the filter headers should extend the filter header chain");
+        assert!(
+            blockchain_manager.getcfilters_request_info[&block_2.block_hash()]
+                .filter_headers
+                .is_some()
+        );
+    }
+
+    /// Tests that no filters are requested when the feature is disabled or no peer
+    /// advertises `NODE_COMPACT_FILTERS`.
+    #[test]
+    fn test_sync_block_filters_without_compact_filters_peers() {
+        let block_peer = SocketAddr::from_str("127.0.0.1:8333").expect("bad address format");
+        let other_peer = SocketAddr::from_str("127.0.0.1:8444").expect("bad address format");
+        let mut channel = TestChannel::new(vec![block_peer, other_peer]);
+        let (_, mut blockchain_manager) =
+            create_blockchain_manager_with_block_1(&mut channel, &block_peer, &other_peer);
+
+        blockchain_manager.sync_block_filters(&mut channel);
+        assert_eq!(channel.command_count(), 0);
+        assert_eq!(blockchain_manager.getcfilters_request_info.len(), 1);
+
+        let config = ConfigBuilder::new().build();
+        let (_, mut blockchain_manager) = create_blockchain_manager(&config);
+        let block_1 = TestState::setup().block_1;
+        blockchain_manager.add_peer(&mut channel, &block_peer);
+        receive_block(&mut blockchain_manager, &block_peer, &block_1);
+        assert!(blockchain_manager.getcfilters_request_info.is_empty());
+    }
 }
@@ -7,6 +7,7 @@ use bitcoin::{
 };
 
 use bitcoin::Work;
+use hashlink::LinkedHashMap;
 use ic_btc_validation::{validate_header, HeaderStore, ValidateHeaderError};
 use ic_metrics::MetricsRegistry;
 use std::{collections::HashMap, sync::Arc};
@@ -16,6 +17,9 @@ use thiserror::Error;
 /// becomes too large. Inflight `getdata` messages will remain active, but new `getdata` messages will
 /// not be created.
 const BLOCK_CACHE_THRESHOLD_BYTES: usize = 10 * ONE_MB;
+/// The maximum size of the block filter cache. When the cache grows beyond this size, the
+/// filters that were added first are evicted.
+const BLOCK_FILTER_CACHE_THRESHOLD_BYTES: usize = 50 * ONE_MB;
 const ONE_MB: usize = 1_024 * 1_024;
 
 /// Contains the necessary information about a tip.
@@ -93,6 +97,9 @@ pub enum AddBlockError {
 
 pub type SerializedBlock = Vec<u8>;
 
+/// A BIP-158 basic block filter as received from Bitcoin nodes.
+pub type SerializedBlockFilter = Vec<u8>;
+
 /// This struct is a cache of Bitcoin blockchain.
 /// The BlockChainState caches all the Bitcoin headers, some of the Bitcoin blocks.
 /// The BlockChainState also maintains the child relationhips between the headers.
@@ -107,6 +114,14 @@ pub struct BlockchainState {
     /// This field stores a hashmap containing BlockHash and the corresponding SerializedBlock.
     block_cache: HashMap<BlockHash, Arc<SerializedBlock>>,
 
+    /// This field stores the BIP-158 basic filters of blocks in insertion order.
+    /// Unlike blocks, filters are not pruned once they are served but only when the
+    /// cache exceeds [BLOCK_FILTER_CACHE_THRESHOLD_BYTES].
+    block_filter_cache: LinkedHashMap<BlockHash, Arc<SerializedBlockFilter>>,
+
+    /// The total size of the filters in `block_filter_cache`.
+    block_filter_cache_size: usize,
+
     /// This field contains the known tips of the header cache.
     tips: Vec<Tip>,
 
@@ -132,6 +147,8 @@ impl BlockchainState {
             genesis_block_header,
             header_cache,
             block_cache,
+            block_filter_cache: LinkedHashMap::new(),
+            block_filter_cache_size: 0,
             tips,
             network: config.network,
             metrics: BlockchainStateMetrics::new(metrics_registry),
@@ -347,6 +364,38 @@ impl BlockchainState {
         self.block_cache.get(block_hash).cloned()
     }
 
+    /// This method adds the BIP-158 basic filter of the given block to the `block_filter_cache`,
+    /// evicting the oldest filters if the cache becomes too large.
+    pub fn add_block_filter(&mut self, block_hash: BlockHash, filter: SerializedBlockFilter) {
+        self.block_filter_cache_size += filter.len();
+        if let Some(replaced) = self
+            .block_filter_cache
+            .replace(block_hash, Arc::new(filter))
+        {
+            self.block_filter_cache_size -= replaced.len();
+        }
+
+        while self.block_filter_cache_size > BLOCK_FILTER_CACHE_THRESHOLD_BYTES {
+            match self.block_filter_cache.pop_front() {
+                Some((_, evicted)) => self.block_filter_cache_size -= evicted.len(),
+                None => break,
+            }
+        }
+
+        self.metrics
+            .block_filter_cache_size
+            .set(self.block_filter_cache_size as i64);
+        self.metrics
+            .block_filter_cache_elements
+            .set(self.block_filter_cache.len() as i64);
+    }
+
+    /// This method takes a block hash
+    /// If the corresponding filter is stored in the `block_filter_cache`, the cached filter is returned.
+    pub fn get_block_filter(&self, block_hash: &BlockHash) -> Option<Arc<SerializedBlockFilter>> {
+        self.block_filter_cache.get(block_hash).cloned()
+    }
+
     /// Used when the adapter is shutdown and no longer requires holding on to blocks.
     pub fn clear_blocks(&mut self) {
         self.block_cache = HashMap::new();
@@ -716,6 +765,29 @@ mod test {
         assert_eq!(state.get_active_chain_tip().header, h4);
     }
 
+    /// Tests that the block filter cache evicts the oldest filters once it exceeds its size limit.
+    #[test]
+    fn test_block_filter_cache_evicts_oldest_filters() {
+        let config = ConfigBuilder::new().with_network(Network::Regtest).build();
+        let mut state = BlockchainState::new(&config, &MetricsRegistry::default());
+
+        let initial_header = state.genesis();
+        let chain = generate_headers(initial_header.block_hash(), initial_header.time, 3, &[]);
+        let filter_size = BLOCK_FILTER_CACHE_THRESHOLD_BYTES / 2;
+        for header in chain.iter() {
+            state.add_block_filter(header.block_hash(), vec![0; filter_size]);
+        }
+
+        assert!(state.get_block_filter(&chain[0].block_hash()).is_none());
+        assert!(state.get_block_filter(&chain[1].block_hash()).is_some());
+        assert!(state.get_block_filter(&chain[2].block_hash()).is_some());
+        assert_eq!(state.block_filter_cache_size, 2 * filter_size);
+
+        // Replacing a filter does not count its previous size.
+        state.add_block_filter(chain[2].block_hash(), vec![0; 1]);
+        assert_eq!(state.block_filter_cache_size, filter_size + 1);
+    }
+
     /// Test header store `get_header` function.
     #[test]
     fn test_headerstore_get_cached_header() {
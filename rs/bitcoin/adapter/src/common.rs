@@ -20,11 +20,11 @@ pub type BlockHeight = u32;
 pub mod test_common {
 
     use std::{
-        collections::{HashSet, VecDeque},
+        collections::{HashMap, HashSet, VecDeque},
         net::SocketAddr,
     };
 
-    use bitcoin::{consensus::deserialize, Block};
+    use bitcoin::{consensus::deserialize, p2p::ServiceFlags, Block};
     use hex::FromHex;
 
     use crate::{Channel, ChannelError, Command};
@@ -43,6 +43,8 @@ pub mod test_common {
         available_connections: Vec<SocketAddr>,
         /// The addresses that disconnect was called on.
         disconnected_addresses: HashSet<SocketAddr>,
+        /// The services advertised by the connections.
+        services: HashMap<SocketAddr, ServiceFlags>,
     }
 
     impl TestChannel {
@@ -51,6 +53,7 @@ pub mod test_common {
                 received_commands: VecDeque::new(),
                 available_connections,
                 disconnected_addresses: HashSet::new(),
+                services: HashMap::new(),
             }
         }
     }
@@ -74,6 +77,10 @@ pub mod test_common {
         pub fn add_address(&mut self, addr: SocketAddr) {
             self.available_connections.push(addr);
         }
+
+        pub fn set_services(&mut self, addr: SocketAddr, services: ServiceFlags) {
+            self.services.insert(addr, services);
+        }
     }
 
     impl Channel for TestChannel {
@@ -93,6 +100,13 @@ pub mod test_common {
         fn discard(&mut self, addr: &SocketAddr) {
             self.disconnected_addresses.insert(*addr);
         }
+
+        fn peer_services(&self, addr: &SocketAddr) -> ServiceFlags {
+            self.services
+                .get(addr)
+                .copied()
+                .unwrap_or(ServiceFlags::NONE)
+        }
     }
 
     pub struct TestState {
@@ -49,6 +49,10 @@ pub struct Config {
     /// Specifies the address limits used by the `AddressBook`.
     #[serde(default)]
     pub address_limits: (usize, usize),
+    /// When this field is set to `true`, the adapter downloads the BIP-158 compact block filters
+    /// of the blocks it syncs from peers that advertise `NODE_COMPACT_FILTERS`.
+    #[serde(default)]
+    pub compact_block_filters: bool,
 }
 
 /// Set the default idle seconds to one hour.
@@ -94,6 +98,7 @@ impl Default for Config {
             logger: LoggerConfig::default(),
             incoming_source: Default::default(),
             address_limits: address_limits(Network::Bitcoin), // Address limits used for Bitcoin mainnet
+            compact_block_filters: false,
         }
     }
 }
@@ -136,6 +141,11 @@ pub mod test {
             self
         }
 
+        pub fn with_compact_block_filters(mut self, compact_block_filters: bool) -> Self {
+            self.config.compact_block_filters = compact_block_filters;
+            self
+        }
+
         pub fn build(self) -> Config {
             self.config
         }
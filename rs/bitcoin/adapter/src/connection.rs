@@ -1,5 +1,5 @@
 use crate::addressbook::AddressEntry;
-use bitcoin::p2p::message::NetworkMessage;
+use bitcoin::p2p::{message::NetworkMessage, ServiceFlags};
 use std::time::{Duration, SystemTime};
 use thiserror::Error;
 use tokio::{sync::mpsc::UnboundedSender, task::JoinHandle};
@@ -92,6 +92,8 @@ pub struct Connection {
     writer: UnboundedSender<NetworkMessage>,
     /// This field is used to track the current ping status.
     ping_state: PingState,
+    /// This field stores the services advertised by the BTC node in its `version` message.
+    services: ServiceFlags,
 }
 
 impl Connection {
@@ -114,6 +116,7 @@ impl Connection {
             ping_state: PingState::Idle {
                 last_pong_at: timestamp,
             },
+            services: ServiceFlags::NONE,
         }
     }
 
@@ -129,6 +132,16 @@ impl Connection {
         &self.address_entry
     }
 
+    /// This function retrieves the services advertised by the BTC node.
+    pub fn services(&self) -> ServiceFlags {
+        self.services
+    }
+
+    /// This function stores the services advertised by the BTC node.
+    pub fn set_services(&mut self, services: ServiceFlags) {
+        self.services = services;
+    }
+
     /// This function used to determine if a connection is a seed address. This is
     /// used to determine if the the service field needs to be validated when looking
     /// at the version.
@@ -276,6 +289,7 @@ mod test {
                 state,
                 writer,
                 ping_state: PingState::Idle { last_pong_at },
+                services: ServiceFlags::NONE,
             }
         }
     }
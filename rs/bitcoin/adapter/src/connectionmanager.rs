@@ -444,6 +444,9 @@ impl ConnectionManager {
             );
             return Err(ProcessBitcoinNetworkMessageError::InvalidMessage);
         }
+        if let Ok(conn) = self.get_connection(address) {
+            conn.set_services(message.services);
+        }
         self.send_verack(address).ok();
 
         if !self.address_book.has_max_address() {
@@ -620,6 +623,12 @@ impl Channel for ConnectionManager {
     fn discard(&mut self, addr: &SocketAddr) {
         self.internal_discard(addr);
     }
+
+    fn peer_services(&self, addr: &SocketAddr) -> ServiceFlags {
+        self.connections
+            .get(addr)
+            .map_or(ServiceFlags::NONE, |conn| conn.services())
+    }
 }
 
 impl ProcessEvent for ConnectionManager {
@@ -4,7 +4,7 @@
 //! and publish transactions. Moreover, it interacts with the Bitcoin system
 //! component to provide blocks and collect outgoing transactions.
 
-use bitcoin::p2p::message::NetworkMessage;
+use bitcoin::p2p::{message::NetworkMessage, ServiceFlags};
 use bitcoin::{block::Header as BlockHeader, BlockHash};
 use ic_logger::ReplicaLogger;
 use ic_metrics::MetricsRegistry;
@@ -94,6 +94,10 @@ trait Channel {
 
     /// Used to disconnect from nodes that are misbehaving.
     fn discard(&mut self, addr: &SocketAddr);
+
+    /// This method is used to retrieve the services advertised by a connected node.
+    /// Returns [ServiceFlags::NONE] if the node is unknown.
+    fn peer_services(&self, addr: &SocketAddr) -> ServiceFlags;
 }
 
 /// This trait provides an interface to anything that may need to react to a
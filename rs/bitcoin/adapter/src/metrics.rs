@@ -4,6 +4,7 @@ use ic_metrics::{
 };
 use prometheus::{Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge};
 
+pub(crate) const LABEL_GET_BLOCK_FILTERS: &str = "get_block_filters";
 pub(crate) const LABEL_GET_SUCCESSOR: &str = "get_successor";
 pub(crate) const LABEL_REQUEST_TYPE: &str = "type";
 pub(crate) const LABEL_SEND_TRANSACTION: &str = "send_transaction";
@@ -94,6 +95,8 @@ pub struct BlockchainStateMetrics {
     pub tip_height: IntGauge,
     pub block_cache_size: IntGauge,
     pub block_cache_elements: IntGauge,
+    pub block_filter_cache_size: IntGauge,
+    pub block_filter_cache_elements: IntGauge,
     pub header_cache_size: IntGauge,
     pub tips: IntGauge,
 }
@@ -108,6 +111,14 @@ impl BlockchainStateMetrics {
                 "block_cache_elements",
                 "Number of blocks currently stored in the block cache.",
             ),
+            block_filter_cache_size: metrics_registry.int_gauge(
+                "block_filter_cache_size_bytes",
+                "Current size of block filter cache.",
+            ),
+            block_filter_cache_elements: metrics_registry.int_gauge(
+                "block_filter_cache_elements",
+                "Number of block filters currently stored in the block filter cache.",
+            ),
             header_cache_size: metrics_registry.int_gauge(
                 "header_cache_size",
                 "Number of headers stored in the adapter.",
@@ -37,8 +37,12 @@ pub fn start_main_event_loop(
 
     let router_metrics = RouterMetrics::new(metrics_registry);
 
-    let mut blockchain_manager =
-        BlockchainManager::new(blockchain_state, logger.clone(), router_metrics.clone());
+    let mut blockchain_manager = BlockchainManager::new(
+        config,
+        blockchain_state,
+        logger.clone(),
+        router_metrics.clone(),
+    );
     let mut transaction_manager = TransactionStore::new(logger.clone(), metrics_registry);
     let mut connection_manager = ConnectionManager::new(
         config,
@@ -1,13 +1,16 @@
 use crate::{
     blockchainstate::BlockchainState,
     get_successors_handler::{GetSuccessorsRequest, GetSuccessorsResponse},
-    metrics::{ServiceMetrics, LABEL_GET_SUCCESSOR, LABEL_SEND_TRANSACTION},
+    metrics::{
+        ServiceMetrics, LABEL_GET_BLOCK_FILTERS, LABEL_GET_SUCCESSOR, LABEL_SEND_TRANSACTION,
+    },
     BlockchainManagerRequest, Config, GetSuccessorsHandler, IncomingSource,
     TransactionManagerRequest,
 };
 use bitcoin::{consensus::Encodable, hashes::Hash, BlockHash};
 use ic_btc_service::{
     btc_service_server::{BtcService, BtcServiceServer},
+    BtcServiceBlockFilter, BtcServiceGetBlockFiltersRequest, BtcServiceGetBlockFiltersResponse,
     BtcServiceGetSuccessorsRequest, BtcServiceGetSuccessorsResponse,
     BtcServiceSendTransactionRequest, BtcServiceSendTransactionResponse,
 };
@@ -21,9 +24,13 @@ use tokio::sync::mpsc;
 use tokio::sync::watch;
 use tonic::{transport::Server, Request, Response, Status};
 
+/// Max number of block filters that can be requested in a single `GetBlockFiltersRequest`.
+const MAX_BLOCK_FILTERS_PER_REQUEST: usize = 100;
+
 struct BtcServiceImpl {
     last_received_tx: watch::Sender<Option<Instant>>,
     get_successors_handler: GetSuccessorsHandler,
+    blockchain_state: Arc<Mutex<BlockchainState>>,
     transaction_manager_tx: mpsc::Sender<TransactionManagerRequest>,
     logger: ReplicaLogger,
     metrics: ServiceMetrics,
@@ -119,6 +126,49 @@ impl BtcService for BtcServiceImpl {
             );
         Ok(Response::new(BtcServiceSendTransactionResponse {}))
     }
+
+    async fn get_block_filters(
+        &self,
+        request: Request<BtcServiceGetBlockFiltersRequest>,
+    ) -> Result<Response<BtcServiceGetBlockFiltersResponse>, Status> {
+        let _timer = self
+            .metrics
+            .request_duration
+            .with_label_values(&[LABEL_GET_BLOCK_FILTERS])
+            .start_timer();
+        let _ = self.last_received_tx.send(Some(Instant::now()));
+        let inner = request.into_inner();
+        debug!(self.logger, "Received GetBlockFiltersRequest: {:?}", inner);
+        if inner.block_hashes.len() > MAX_BLOCK_FILTERS_PER_REQUEST {
+            return Err(Status::invalid_argument(format!(
+                "Too many block hashes, at most {} are allowed!",
+                MAX_BLOCK_FILTERS_PER_REQUEST
+            )));
+        }
+
+        let block_hashes = inner
+            .block_hashes
+            .iter()
+            .map(|hash| {
+                BlockHash::from_slice(hash.as_slice())
+                    .map_err(|_| Status::unknown("Failed to read block_hashes!"))
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+
+        let state = self.blockchain_state.lock().unwrap();
+        let filters = block_hashes
+            .into_iter()
+            .filter_map(|block_hash| {
+                state
+                    .get_block_filter(&block_hash)
+                    .map(|filter| BtcServiceBlockFilter {
+                        block_hash: block_hash.to_byte_array().to_vec(),
+                        filter: filter.to_vec(),
+                    })
+            })
+            .collect();
+        Ok(Response::new(BtcServiceGetBlockFiltersResponse { filters }))
+    }
 }
 
 /// Blocks until the server binds to the socket
@@ -135,7 +185,7 @@ pub fn start_grpc_server(
         &config,
         // The get successor handler should be low latency, and instead of not sharing state and
         // offloading the computation to an event loop here we directly access the shared state.
-        blockchain_state,
+        blockchain_state.clone(),
         blockchain_manager_tx,
         metrics_registry,
     );
@@ -143,6 +193,7 @@ pub fn start_grpc_server(
     let btc_adapter_impl = BtcServiceImpl {
         last_received_tx,
         get_successors_handler,
+        blockchain_state,
         transaction_manager_tx,
         logger,
         metrics: ServiceMetrics::new(metrics_registry),
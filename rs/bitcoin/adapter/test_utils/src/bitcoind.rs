@@ -8,14 +8,17 @@ use std::{
 use bitcoin::p2p::{Magic, ServiceFlags};
 
 use bitcoin::{
+    bip158::{BlockFilter, FilterHash, FilterHeader},
     block::Header as BlockHeader,
     consensus::{deserialize_partial, encode, serialize},
+    hashes::Hash,
     p2p::{
         message::{NetworkMessage, RawNetworkMessage},
         message_blockdata::{GetHeadersMessage, Inventory},
+        message_filter::{CFHeaders, CFilter, GetCFHeaders, GetCFilters},
         message_network::VersionMessage,
     },
-    Block, BlockHash,
+    Block, BlockHash, ScriptBuf,
 };
 
 use bitcoin::io as bitcoin_io;
@@ -77,6 +80,7 @@ async fn handle_version(
     }
     let mut version = v.clone();
     version.services.add(ServiceFlags::NETWORK);
+    version.services.add(ServiceFlags::COMPACT_FILTERS);
     write_network_message(socket, magic, NetworkMessage::Version(version)).await?;
     write_network_message(socket, magic, NetworkMessage::Verack).await?;
     Ok(())
@@ -124,6 +128,88 @@ async fn handle_getheaders(
     write_network_message(socket, magic, NetworkMessage::Headers(block_headers)).await
 }
 
+/// Computes the BIP-158 basic filter of the given block. The scripts spent by the block's
+/// inputs are looked up in the given blocks; unknown ones are left out of the filter.
+fn basic_filter(block: &Block, blocks: &HashMap<BlockHash, Block>) -> BlockFilter {
+    let outputs: HashMap<_, _> = blocks
+        .values()
+        .flat_map(|block| block.txdata.iter())
+        .flat_map(|tx| {
+            let txid = tx.compute_txid();
+            tx.output
+                .iter()
+                .enumerate()
+                .map(move |(vout, output)| ((txid, vout as u32), output.script_pubkey.clone()))
+        })
+        .collect();
+    BlockFilter::new_script_filter(block, |outpoint| {
+        Ok::<_, bitcoin::bip158::Error>(
+            outputs
+                .get(&(outpoint.txid, outpoint.vout))
+                .cloned()
+                .unwrap_or_else(ScriptBuf::new),
+        )
+    })
+    .expect("failed to compute the basic filter")
+}
+
+/// Computes the BIP-158 basic filter header of the given block. The filter header chain
+/// starts at the first ancestor of the block that is not in the given blocks.
+fn basic_filter_header(block_hash: &BlockHash, blocks: &HashMap<BlockHash, Block>) -> FilterHeader {
+    let mut chain = vec![];
+    let mut next = blocks.get(block_hash);
+    while let Some(block) = next {
+        chain.push(block);
+        next = blocks.get(&block.header.prev_blockhash);
+    }
+    chain
+        .into_iter()
+        .rev()
+        .fold(FilterHeader::all_zeros(), |previous, block| {
+            basic_filter(block, blocks).filter_header(&previous)
+        })
+}
+
+async fn handle_getcfheaders(
+    socket: &mut TcpStream,
+    msg: &GetCFHeaders,
+    magic: Magic,
+    blocks: Arc<HashMap<BlockHash, Block>>,
+) -> io::Result<()> {
+    // Only requests for the filter header of a single block are supported.
+    let block = match blocks.get(&msg.stop_hash) {
+        Some(block) => block,
+        None => return Ok(()),
+    };
+    let filter = basic_filter(block, &blocks);
+    let message = CFHeaders {
+        filter_type: msg.filter_type,
+        stop_hash: msg.stop_hash,
+        previous_filter_header: basic_filter_header(&block.header.prev_blockhash, &blocks),
+        filter_hashes: vec![FilterHash::hash(&filter.content)],
+    };
+    write_network_message(socket, magic, NetworkMessage::CFHeaders(message)).await
+}
+
+async fn handle_getcfilters(
+    socket: &mut TcpStream,
+    msg: &GetCFilters,
+    magic: Magic,
+    blocks: Arc<HashMap<BlockHash, Block>>,
+) -> io::Result<()> {
+    // Only requests for the filter of a single block are supported.
+    let block = match blocks.get(&msg.stop_hash) {
+        Some(block) => block,
+        None => return Ok(()),
+    };
+    let message = CFilter {
+        filter_type: msg.filter_type,
+        block_hash: msg.stop_hash,
+        filter: basic_filter(block, &blocks).content,
+    };
+    write_network_message(socket, magic, NetworkMessage::CFilter(message)).await
+}
+
 fn decompress(location: String) -> Vec<u8> {
     let bytes = std::fs::read(location).unwrap();
     let mut dec = flate2::read::GzDecoder::new(bytes.as_slice());
@@ -206,6 +292,12 @@ impl FakeBitcoind {
                                     NetworkMessage::GetData(msg) => {
                                         handle_getdata(&mut socket, msg, *raw.magic(), blocks.clone()).await
                                     }
+                                    NetworkMessage::GetCFHeaders(msg) => {
+                                        handle_getcfheaders(&mut socket, msg, *raw.magic(), blocks.clone()).await
+                                    }
+                                    NetworkMessage::GetCFilters(msg) => {
+                                        handle_getcfilters(&mut socket, msg, *raw.magic(), blocks.clone()).await
+                                    }
                                     NetworkMessage::Ping(val) => {
                                         handle_ping(&mut socket, *val, *raw.magic()).await
                                     }
@@ -1,4 +1,7 @@
-use bitcoin::{consensus::encode::deserialize, Address, Amount, Block, BlockHash};
+use bitcoin::{
+    bip158::BlockFilter, consensus::encode::deserialize, hashes::Hash, Address, Amount, Block,
+    BlockHash,
+};
 use bitcoincore_rpc::{bitcoincore_rpc_json::CreateRawTransactionInput, Auth, Client, RpcApi};
 use bitcoind::{BitcoinD, Conf, P2P};
 use ic_btc_adapter::{start_server, Config, IncomingSource};
@@ -8,6 +11,7 @@ use ic_btc_replica_types::{
     BitcoinAdapterRequestWrapper, BitcoinAdapterResponseWrapper, GetSuccessorsRequestInitial,
     SendTransactionRequest,
 };
+use ic_btc_service::{btc_service_client::BtcServiceClient, BtcServiceGetBlockFiltersRequest};
 use ic_config::adapters::AdaptersConfig;
 use ic_config::bitcoin_payload_builder_config::Config as BitcoinPayloadBuilderConfig;
 use ic_interfaces_adapter_client::{Options, RpcAdapterClient, RpcError};
@@ -20,7 +24,9 @@ use std::{
     str::FromStr,
 };
 use tempfile::{Builder, TempPath};
-use tokio::runtime::Runtime;
+use tokio::{net::UnixStream, runtime::Runtime};
+use tonic::transport::{Endpoint, Uri};
+use tower::service_fn;
 
 type BitcoinAdapterClient = Box<
     dyn RpcAdapterClient<BitcoinAdapterRequestWrapper, Response = BitcoinAdapterResponseWrapper>,
@@ -95,6 +101,7 @@ fn start_adapter(
     nodes: Vec<SocketAddr>,
     uds_path: &Path,
     network: bitcoin::Network,
+    compact_block_filters: bool,
 ) {
     let config = Config {
         network,
@@ -103,6 +110,7 @@ fn start_adapter(
         ipv6_only: true,
         address_limits: (1, 1),
         idle_seconds: 6, // it can take at most 5 seconds for tcp connections etc to be established.
+        compact_block_filters,
         ..Default::default()
     };
 
@@ -162,6 +170,7 @@ fn start_adapter_and_client(
     logger: ReplicaLogger,
     network: bitcoin::Network,
     adapter_state: AdapterState,
+    compact_block_filters: bool,
 ) -> (BitcoinAdapterClient, TempPath) {
     let metrics_registry = MetricsRegistry::new();
     let res = Builder::new()
@@ -173,6 +182,7 @@ fn start_adapter_and_client(
                 urls.clone(),
                 uds_path,
                 network,
+                compact_block_filters,
             );
             Ok(start_client(
                 &logger,
@@ -201,7 +211,7 @@ fn start_idle_adapter_and_client(
     logger: ReplicaLogger,
     network: bitcoin::Network,
 ) -> (BitcoinAdapterClient, TempPath) {
-    start_adapter_and_client(rt, urls, logger, network, AdapterState::Idle)
+    start_adapter_and_client(rt, urls, logger, network, AdapterState::Idle, false)
 }
 
 fn start_active_adapter_and_client(
@@ -210,7 +220,48 @@ fn start_active_adapter_and_client(
     logger: ReplicaLogger,
     network: bitcoin::Network,
 ) -> (BitcoinAdapterClient, TempPath) {
-    start_adapter_and_client(rt, urls, logger, network, AdapterState::Active)
+    start_adapter_and_client(rt, urls, logger, network, AdapterState::Active, false)
+}
+
+/// Requests the BIP-158 basic filters of the given blocks from the adapter listening on the
+/// given socket. Returns the filters the adapter has knowledge of.
+fn get_block_filters(
+    rt: &Runtime,
+    uds_path: &Path,
+    block_hashes: &[BlockHash],
+) -> HashMap<BlockHash, Vec<u8>> {
+    let uds_path = uds_path.to_path_buf();
+    rt.block_on(async move {
+        // The uri is ignored because uds do not use it.
+        let channel = Endpoint::try_from("http://[::]:50051")
+            .unwrap()
+            .connect_with_connector(service_fn(move |_: Uri| {
+                let uds_path = uds_path.clone();
+                async move {
+                    Ok::<_, std::io::Error>(hyper_util::rt::TokioIo::new(
+                        UnixStream::connect(uds_path).await?,
+                    ))
+                }
+            }))
+            .await
+            .unwrap();
+        BtcServiceClient::new(channel)
+            .get_block_filters(BtcServiceGetBlockFiltersRequest {
+                block_hashes: block_hashes.iter().map(|hash| hash[..].to_vec()).collect(),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .filters
+            .into_iter()
+            .map(|filter| {
+                (
+                    BlockHash::from_slice(&filter.block_hash).unwrap(),
+                    filter.filter,
+                )
+            })
+            .collect()
+    })
 }
 
 fn wait_for_blocks(client: &Client, blocks: u64) {
@@ -1026,6 +1077,82 @@ fn test_mainnet_data() {
     assert_eq!(blocks.len(), 10);
 }
 
+// This test makes use of mainnet data. It syncs 10 blocks, from 350,990 to 350,999, with
+// compact block filters enabled and checks that the adapter serves the filters of the blocks,
+// which it downloads from the mock node and validates against the filter header chain.
+#[test]
+fn test_mainnet_data_block_filters() {
+    let logger = no_op_logger();
+    let headers_data_path =
+        std::env::var("HEADERS_DATA_PATH").expect("Failed to get test data path env variable");
+    let blocks_data_path =
+        std::env::var("BLOCKS_DATA_PATH").expect("Failed to get test data path env variable");
+
+    let genesis: BlockHash = "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+        .parse()
+        .unwrap();
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let bitcoind_addr = ic_btc_adapter_test_utils::bitcoind::mock_bitcoin(
+        rt.handle(),
+        headers_data_path,
+        blocks_data_path,
+    );
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let (adapter_client, path) = start_adapter_and_client(
+        &rt,
+        vec![bitcoind_addr],
+        logger,
+        bitcoin::Network::Bitcoin,
+        AdapterState::Active,
+        true,
+    );
+    sync_headers_until_checkpoint(&adapter_client, genesis[..].to_vec());
+
+    // Block 350,989's block hash.
+    let anchor: BlockHash = "0000000000000000035908aacac4c97fb4e172a1758bbbba2ee2b188765780eb"
+        .parse()
+        .unwrap();
+
+    let blocks = sync_blocks(&adapter_client, &mut vec![], anchor[..].to_vec(), 10, 250);
+    assert_eq!(blocks.len(), 10);
+    let block_hashes: Vec<BlockHash> = blocks.iter().map(|block| block.block_hash()).collect();
+
+    let mut tries = 0;
+    let filters = loop {
+        let filters = get_block_filters(&rt, &path, &block_hashes);
+        if filters.len() == block_hashes.len() {
+            break filters;
+        }
+        tries += 1;
+        if tries > 60 {
+            panic!(
+                "Timeout in waiting for block filters, received {} of {}",
+                filters.len(),
+                block_hashes.len()
+            );
+        }
+        std::thread::sleep(std::time::Duration::from_secs(1));
+    };
+
+    for block in blocks.iter() {
+        let filter = BlockFilter::new(&filters[&block.block_hash()]);
+        let output_scripts = block
+            .txdata
+            .iter()
+            .flat_map(|tx| tx.output.iter())
+            .map(|output| output.script_pubkey.as_script())
+            .filter(|script| !script.is_empty() && !script.is_op_return());
+        assert!(filter
+            .match_all(
+                block.block_hash(),
+                output_scripts.map(|script| script.as_bytes())
+            )
+            .unwrap());
+    }
+}
+
 // This test makes use of testnet data. It first syncs the headerchain until the adapter
 // checkpoint is passed and then requests 9 blocks.
 #[test]
@@ -0,0 +1,155 @@
+//! Custom Bitcoin address lists that complement the built-in blocklist.
+//!
+//! The lists are managed by the controllers of the canister. Every update bumps
+//! the version of the lists and records an audit event.
+
+use crate::logs::WARN;
+use crate::state;
+use bitcoin::Address;
+use candid::Principal;
+use ic_btc_checker::{
+    blocklist, AddressListEvent, AddressListKind, AddressListPage, BtcNetwork,
+    UpdateAddressListArgs, UpdateAddressListError,
+};
+use ic_canister_log::log;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests;
+
+/// Max number of addresses that can be added or removed in a single update.
+pub const MAX_ADDRESSES_PER_UPDATE: usize = 1_000;
+
+/// Max number of addresses returned by `get_address_list`.
+pub const MAX_ADDRESSES_PER_RESPONSE: u64 = 1_000;
+
+/// Max number of events returned by `get_address_list_events`.
+pub const MAX_EVENTS_PER_RESPONSE: u64 = 100;
+
+/// Return `true` if the given address is on the built-in blocklist, or if it is on
+/// the custom blocklist and not on the custom allowlist.
+/// The custom allowlist never overrides the built-in blocklist.
+pub fn is_blocked(address: &Address) -> bool {
+    if blocklist::is_blocked(address) {
+        return true;
+    }
+    let address = address.to_string();
+    state::address_list_contains(AddressListKind::Blocklist, &address)
+        && !state::address_list_contains(AddressListKind::Allowlist, &address)
+}
+
+/// Return at most `length` addresses of the given list, starting from the address `start`.
+pub fn get_address_list(
+    list: AddressListKind,
+    start: Option<String>,
+    length: u64,
+) -> AddressListPage {
+    let length = length.min(MAX_ADDRESSES_PER_RESPONSE) as usize;
+    // Fetch one more address to know where the next page starts.
+    let mut addresses = state::get_address_list(list, start, length.saturating_add(1));
+    let next = if addresses.len() > length {
+        addresses.pop()
+    } else {
+        None
+    };
+    AddressListPage {
+        version: state::address_lists_version(),
+        addresses,
+        next,
+    }
+}
+
+/// Apply the given update to the address lists on behalf of `caller` and return
+/// the new version of the lists. Only the addresses that were actually added or
+/// removed are recorded in the audit event, and an update that changes nothing
+/// neither bumps the version nor records an event.
+///
+/// The caller must be authorized by the canister before calling this function.
+pub fn update_address_list(
+    caller: Principal,
+    now: u64,
+    btc_network: &BtcNetwork,
+    args: UpdateAddressListArgs,
+) -> Result<u64, UpdateAddressListError> {
+    let UpdateAddressListArgs {
+        list,
+        add,
+        remove,
+        expected_version,
+        reason,
+    } = args;
+    if add.len().saturating_add(remove.len()) > MAX_ADDRESSES_PER_UPDATE {
+        return Err(UpdateAddressListError::TooManyAddresses {
+            max: MAX_ADDRESSES_PER_UPDATE as u64,
+        });
+    }
+    let add = parse_addresses(btc_network, add)?;
+    let remove = parse_addresses(btc_network, remove)?;
+
+    let current_version = state::address_lists_version();
+    if let Some(expected_version) = expected_version {
+        if expected_version != current_version {
+            return Err(UpdateAddressListError::VersionMismatch { current_version });
+        }
+    }
+    let removed: Vec<String> = remove
+        .into_iter()
+        .filter(|address| state::remove_from_address_list(list, address.clone()))
+        .collect();
+    let added: Vec<String> = add
+        .into_iter()
+        .filter(|address| state::insert_into_address_list(list, address.clone()))
+        .collect();
+    if added.is_empty() && removed.is_empty() {
+        return Ok(current_version);
+    }
+
+    let version = current_version + 1;
+    log!(
+        WARN,
+        "[update_address_list]: {} updated the {} to version {}: added {:?}, removed {:?}, reason {:?}",
+        caller,
+        list,
+        version,
+        added,
+        removed,
+        reason
+    );
+    let event = AddressListEvent {
+        version,
+        timestamp: now,
+        caller,
+        list,
+        added,
+        removed,
+        reason,
+    };
+    state::record_address_list_update(&event);
+    Ok(version)
+}
+
+pub fn get_address_list_events(start: u64, length: u64) -> Vec<AddressListEvent> {
+    state::get_address_list_events(start, length.min(MAX_EVENTS_PER_RESPONSE))
+}
+
+/// Parse the given addresses and return their canonical representations.
+fn parse_addresses(
+    btc_network: &BtcNetwork,
+    addresses: Vec<String>,
+) -> Result<BTreeSet<String>, UpdateAddressListError> {
+    addresses
+        .into_iter()
+        .map(|address| {
+            Address::from_str(address.trim())
+                .map_err(|err| err.to_string())
+                .and_then(|parsed| {
+                    parsed
+                        .require_network(btc_network.clone().into())
+                        .map_err(|err| err.to_string())
+                })
+                .map(|parsed| parsed.to_string())
+                .map_err(|reason| UpdateAddressListError::InvalidAddress { address, reason })
+        })
+        .collect()
+}
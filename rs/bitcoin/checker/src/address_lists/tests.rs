@@ -0,0 +1,254 @@
+use super::*;
+use ic_btc_checker::blocklist::BTC_ADDRESS_BLOCKLIST;
+
+const ADDRESS_1: &str = "12cbQLTFMXRnSzktFkuoG3eHoMeFtpTu3S";
+const ADDRESS_2: &str = "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa";
+const TESTNET_ADDRESS: &str = "n47QBape2PcisN2mkHR2YnhqoBr56iPhJh";
+
+fn caller() -> Principal {
+    Principal::from_slice(&[1, 2, 3])
+}
+
+fn address(address: &str) -> Address {
+    Address::from_str(address)
+        .unwrap()
+        .require_network(bitcoin::Network::Bitcoin)
+        .unwrap()
+}
+
+fn update_args(list: AddressListKind, add: &[&str], remove: &[&str]) -> UpdateAddressListArgs {
+    UpdateAddressListArgs {
+        list,
+        add: add.iter().map(|a| a.to_string()).collect(),
+        remove: remove.iter().map(|a| a.to_string()).collect(),
+        expected_version: None,
+        reason: None,
+    }
+}
+
+#[test]
+fn should_block_addresses_on_custom_blocklist() {
+    assert!(!is_blocked(&address(ADDRESS_1)));
+
+    let version = update_address_list(
+        caller(),
+        1_000,
+        &BtcNetwork::Mainnet,
+        UpdateAddressListArgs {
+            reason: Some("case 42".to_string()),
+            ..update_args(AddressListKind::Blocklist, &[ADDRESS_1], &[])
+        },
+    )
+    .unwrap();
+    assert_eq!(version, 1);
+    assert!(is_blocked(&address(ADDRESS_1)));
+    assert!(!is_blocked(&address(ADDRESS_2)));
+
+    let version = update_address_list(
+        caller(),
+        2_000,
+        &BtcNetwork::Mainnet,
+        update_args(AddressListKind::Blocklist, &[], &[ADDRESS_1]),
+    )
+    .unwrap();
+    assert_eq!(version, 2);
+    assert!(!is_blocked(&address(ADDRESS_1)));
+
+    assert_eq!(
+        get_address_list_events(0, u64::MAX),
+        vec![
+            AddressListEvent {
+                version: 1,
+                timestamp: 1_000,
+                caller: caller(),
+                list: AddressListKind::Blocklist,
+                added: vec![ADDRESS_1.to_string()],
+                removed: vec![],
+                reason: Some("case 42".to_string()),
+            },
+            AddressListEvent {
+                version: 2,
+                timestamp: 2_000,
+                caller: caller(),
+                list: AddressListKind::Blocklist,
+                added: vec![],
+                removed: vec![ADDRESS_1.to_string()],
+                reason: None,
+            },
+        ]
+    );
+    assert_eq!(get_address_list_events(1, 1).len(), 1);
+    assert_eq!(get_address_list_events(2, 1), vec![]);
+}
+
+#[test]
+fn should_allow_addresses_on_allowlist() {
+    update_address_list(
+        caller(),
+        0,
+        &BtcNetwork::Mainnet,
+        update_args(AddressListKind::Blocklist, &[ADDRESS_1, ADDRESS_2], &[]),
+    )
+    .unwrap();
+    update_address_list(
+        caller(),
+        0,
+        &BtcNetwork::Mainnet,
+        update_args(AddressListKind::Allowlist, &[ADDRESS_1], &[]),
+    )
+    .unwrap();
+
+    assert!(!is_blocked(&address(ADDRESS_1)));
+    assert!(is_blocked(&address(ADDRESS_2)));
+}
+
+#[test]
+fn should_not_let_allowlist_override_built_in_blocklist() {
+    let blocked_address = address(BTC_ADDRESS_BLOCKLIST[0]);
+    assert!(is_blocked(&blocked_address));
+
+    update_address_list(
+        caller(),
+        0,
+        &BtcNetwork::Mainnet,
+        update_args(AddressListKind::Allowlist, &[BTC_ADDRESS_BLOCKLIST[0]], &[]),
+    )
+    .unwrap();
+
+    assert!(is_blocked(&blocked_address));
+}
+
+#[test]
+fn should_return_address_list_in_pages() {
+    let mut addresses: Vec<&str> = BTC_ADDRESS_BLOCKLIST.iter().take(5).copied().collect();
+    update_address_list(
+        caller(),
+        0,
+        &BtcNetwork::Mainnet,
+        update_args(AddressListKind::Blocklist, &addresses, &[]),
+    )
+    .unwrap();
+    update_address_list(
+        caller(),
+        0,
+        &BtcNetwork::Mainnet,
+        update_args(AddressListKind::Allowlist, &[ADDRESS_1], &[]),
+    )
+    .unwrap();
+    addresses.sort();
+
+    let first_page = get_address_list(AddressListKind::Blocklist, None, 2);
+    assert_eq!(
+        first_page,
+        AddressListPage {
+            version: 2,
+            addresses: vec![addresses[0].to_string(), addresses[1].to_string()],
+            next: Some(addresses[2].to_string()),
+        }
+    );
+    let last_page = get_address_list(AddressListKind::Blocklist, first_page.next, 10);
+    assert_eq!(
+        last_page,
+        AddressListPage {
+            version: 2,
+            addresses: addresses[2..].iter().map(|a| a.to_string()).collect(),
+            next: None,
+        }
+    );
+    assert_eq!(
+        get_address_list(AddressListKind::Allowlist, None, u64::MAX),
+        AddressListPage {
+            version: 2,
+            addresses: vec![ADDRESS_1.to_string()],
+            next: None,
+        }
+    );
+}
+
+#[test]
+fn should_not_record_updates_without_changes() {
+    update_address_list(
+        caller(),
+        0,
+        &BtcNetwork::Mainnet,
+        update_args(AddressListKind::Blocklist, &[ADDRESS_1], &[]),
+    )
+    .unwrap();
+
+    for args in [
+        update_args(AddressListKind::Blocklist, &[ADDRESS_1], &[]),
+        update_args(AddressListKind::Blocklist, &[], &[ADDRESS_2]),
+        update_args(AddressListKind::Allowlist, &[], &[ADDRESS_1]),
+    ] {
+        assert_eq!(
+            update_address_list(caller(), 0, &BtcNetwork::Mainnet, args),
+            Ok(1)
+        );
+    }
+    assert_eq!(get_address_list_events(0, u64::MAX).len(), 1);
+}
+
+#[test]
+fn should_reject_version_mismatch() {
+    let args = UpdateAddressListArgs {
+        expected_version: Some(1),
+        ..update_args(AddressListKind::Blocklist, &[ADDRESS_1], &[])
+    };
+    assert_eq!(
+        update_address_list(caller(), 0, &BtcNetwork::Mainnet, args.clone()),
+        Err(UpdateAddressListError::VersionMismatch { current_version: 0 })
+    );
+
+    let version = update_address_list(
+        caller(),
+        0,
+        &BtcNetwork::Mainnet,
+        UpdateAddressListArgs {
+            expected_version: Some(0),
+            ..update_args(AddressListKind::Blocklist, &[ADDRESS_2], &[])
+        },
+    );
+    assert_eq!(version, Ok(1));
+    assert_eq!(
+        update_address_list(caller(), 0, &BtcNetwork::Mainnet, args),
+        Ok(2)
+    );
+}
+
+#[test]
+fn should_reject_invalid_addresses() {
+    for (btc_network, invalid_address) in [
+        (BtcNetwork::Mainnet, "not an address"),
+        (BtcNetwork::Mainnet, TESTNET_ADDRESS),
+        (BtcNetwork::Testnet, ADDRESS_1),
+    ] {
+        let result = update_address_list(
+            caller(),
+            0,
+            &btc_network,
+            update_args(AddressListKind::Blocklist, &[invalid_address], &[]),
+        );
+        assert!(
+            matches!(
+                &result,
+                Err(UpdateAddressListError::InvalidAddress { address, .. }) if address == invalid_address
+            ),
+            "unexpected result {:?}",
+            result
+        );
+    }
+
+    let too_many: Vec<&str> = vec![ADDRESS_1; MAX_ADDRESSES_PER_UPDATE + 1];
+    assert_eq!(
+        update_address_list(
+            caller(),
+            0,
+            &BtcNetwork::Mainnet,
+            update_args(AddressListKind::Allowlist, &too_many, &[]),
+        ),
+        Err(UpdateAddressListError::TooManyAddresses {
+            max: MAX_ADDRESSES_PER_UPDATE as u64
+        })
+    );
+    assert_eq!(state::address_lists_version(), 0);
+}
@@ -1,23 +1,30 @@
-use crate::logs::WARN;
+use crate::logs::{DEBUG, WARN};
 use crate::state::{
     FetchGuardError, FetchTxStatus, FetchTxStatusError, FetchedTx, HttpGetTxError,
     TransactionCheckData,
 };
-use crate::{providers, state, Config};
+use crate::{address_lists::is_blocked, providers, state, BtcNetwork, Config};
 use bitcoin::Transaction;
 use futures::future::try_join_all;
 use ic_btc_checker::{
-    blocklist::is_blocked, get_tx_cycle_cost, CheckTransactionIrrecoverableError,
-    CheckTransactionQueryResponse, CheckTransactionResponse, CheckTransactionRetriable,
-    CheckTransactionStatus, INITIAL_MAX_RESPONSE_BYTES, RETRY_MAX_RESPONSE_BYTES,
+    get_tx_cycle_cost, CheckTransactionIrrecoverableError, CheckTransactionQueryResponse,
+    CheckTransactionResponse, CheckTransactionRetriable, CheckTransactionStatus,
+    INITIAL_MAX_RESPONSE_BYTES, RETRY_MAX_RESPONSE_BYTES,
 };
 use ic_btc_interface::Txid;
 use ic_canister_log::log;
+use ic_cdk::api::management_canister::bitcoin::{
+    BitcoinNetwork, GetUtxosRequest, GetUtxosResponse, UtxoFilter,
+};
 use std::convert::Infallible;
 
 #[cfg(test)]
 mod tests;
 
+/// Max number of `bitcoin_get_utxos` calls made to resolve the input addresses of
+/// a transaction with the Bitcoin canister.
+const MAX_GET_UTXOS_CALLS: usize = 10;
+
 impl HttpGetTxError {
     pub(crate) fn into_response(self, txid: Txid) -> CheckTransactionResponse {
         let txid = txid.as_ref().to_vec();
@@ -61,6 +68,10 @@ pub trait FetchEnv {
     ) -> Result<Transaction, HttpGetTxError>;
     fn cycles_accept(&self, cycles: u128) -> u128;
 
+    /// Return the UTXOs of an address from the Bitcoin canister.
+    async fn bitcoin_get_utxos(&self, request: GetUtxosRequest)
+        -> Result<GetUtxosResponse, String>;
+
     /// Try to fetch a transaction given its txid:
     /// - If it is already available, return `Fetched`.
     /// - If it is already pending, return `Pending`.
@@ -170,6 +181,106 @@ pub trait FetchEnv {
         }
     }
 
+    /// Try to fill in the missing input addresses of the given fetched transaction with
+    /// the UTXO data of the Bitcoin canister, instead of fetching the transactions of the
+    /// inputs from the providers.
+    ///
+    /// A candidate address of an input (see `PreviousOutput::address_candidates`) is the
+    /// address of the output spent by the input if the Bitcoin canister lists this output
+    /// among the UTXOs of the candidate. Since the output is spent by the transaction, the
+    /// UTXOs are requested with enough confirmations to get the UTXO set right before the
+    /// block of the transaction, whose height is that of an unspent output of the transaction.
+    ///
+    /// This is best effort: the inputs that are not resolved, e.g. because all outputs of
+    /// the transaction are spent or its block is too deep in the chain for the Bitcoin
+    /// canister, are left to be fetched from the providers.
+    async fn resolve_input_addresses_with_bitcoin_canister(&self, txid: Txid, fetched: &FetchedTx) {
+        let mut calls_left = MAX_GET_UTXOS_CALLS;
+        let mut block = None;
+        for (vout, output) in fetched.tx.outputs.iter().enumerate() {
+            if let Some(address) = output {
+                block = self
+                    .find_utxo(address, None, txid, vout as u32, &mut calls_left)
+                    .await;
+                if block.is_some() || calls_left == 0 {
+                    break;
+                }
+            }
+        }
+        let Some((height, tip_height)) = block else {
+            return;
+        };
+        // The UTXO set of the block right before the block of the transaction.
+        let min_confirmations = tip_height.saturating_sub(height).saturating_add(2);
+        for (index, input) in fetched.tx.inputs.iter().enumerate() {
+            if fetched.input_addresses[index].is_some() {
+                continue;
+            }
+            for candidate in input.address_candidates.iter() {
+                if self
+                    .find_utxo(
+                        candidate,
+                        Some(min_confirmations),
+                        input.txid,
+                        input.vout,
+                        &mut calls_left,
+                    )
+                    .await
+                    .is_some()
+                {
+                    state::set_fetched_address(txid, index, candidate.clone());
+                    break;
+                }
+                if calls_left == 0 {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Return the height of the output `vout` of the transaction `txid` and the height
+    /// of the tip of the chain if the output is a UTXO of the given address with at least
+    /// `min_confirmations` confirmations.
+    /// Stop, and stop any further lookup, when `calls_left` runs out or a call fails.
+    async fn find_utxo(
+        &self,
+        address: &bitcoin::Address,
+        min_confirmations: Option<u32>,
+        txid: Txid,
+        vout: u32,
+        calls_left: &mut usize,
+    ) -> Option<(u32, u32)> {
+        let network = match self.config().btc_network() {
+            BtcNetwork::Mainnet => BitcoinNetwork::Mainnet,
+            BtcNetwork::Testnet => BitcoinNetwork::Testnet,
+            BtcNetwork::Regtest { .. } => BitcoinNetwork::Regtest,
+        };
+        let mut filter = min_confirmations.map(UtxoFilter::MinConfirmations);
+        while *calls_left > 0 {
+            *calls_left -= 1;
+            let request = GetUtxosRequest {
+                address: address.to_string(),
+                network,
+                filter: filter.take(),
+            };
+            let response = match self.bitcoin_get_utxos(request).await {
+                Ok(response) => response,
+                Err(err) => {
+                    log!(DEBUG, "Failed to get the UTXOs of {}: {}", address, err);
+                    *calls_left = 0;
+                    return None;
+                }
+            };
+            if let Some(utxo) = response.utxos.iter().find(|utxo| {
+                utxo.outpoint.txid.as_slice() == txid.as_ref() && utxo.outpoint.vout == vout
+            }) {
+                return Some((utxo.height, response.tip_height));
+            }
+            filter = Some(UtxoFilter::Page(response.next_page?));
+        }
+        None
+    }
+
     /// After a transaction is successfully fetched, we still need to fetch
     /// all of its inputs in order to calculate input addresses. The steps
     /// are described as follows:
@@ -189,6 +300,23 @@ pub trait FetchEnv {
             result => return result.into(),
         }
 
+        let resolved;
+        let fetched = if self.config().use_bitcoin_canister {
+            self.resolve_input_addresses_with_bitcoin_canister(txid, fetched)
+                .await;
+            resolved = match state::get_fetch_status(txid) {
+                Some(FetchTxStatus::Fetched(fetched)) => fetched,
+                _ => return CheckTransactionRetriable::Pending.into(),
+            };
+            match check_for_blocked_input_addresses(&resolved) {
+                Err(CheckTxInputsError::MissingInputAddresses) => (),
+                result => return result.into(),
+            }
+            &resolved
+        } else {
+            fetched
+        };
+
         let mut futures = vec![];
         let mut jobs = vec![];
         let mut high_load = false;
@@ -9,6 +9,7 @@ use ic_btc_checker::{
     CHECK_TRANSACTION_CYCLES_SERVICE_FEE,
 };
 use ic_cdk::api::call::RejectionCode;
+use ic_cdk::api::management_canister::bitcoin::{Outpoint, Utxo};
 use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::str::FromStr;
@@ -22,6 +23,9 @@ struct MockEnv {
     available_cycles: RefCell<u128>,
     accepted_cycles: RefCell<u128>,
     called_provider: RefCell<Option<Provider>>,
+    use_bitcoin_canister: bool,
+    get_utxos_calls: RefCell<VecDeque<GetUtxosRequest>>,
+    get_utxos_replies: RefCell<VecDeque<GetUtxosResponse>>,
 }
 
 const TEST_SUBNET_NODES: u16 = 13;
@@ -38,7 +42,11 @@ impl FetchEnv for MockEnv {
     }
 
     fn config(&self) -> Config {
-        Config::new_and_validate(BtcNetwork::Mainnet, CheckMode::Normal, TEST_SUBNET_NODES).unwrap()
+        let mut config =
+            Config::new_and_validate(BtcNetwork::Mainnet, CheckMode::Normal, TEST_SUBNET_NODES)
+                .unwrap();
+        config.use_bitcoin_canister = self.use_bitcoin_canister;
+        config
     }
 
     async fn http_get_tx(
@@ -67,6 +75,16 @@ impl FetchEnv for MockEnv {
         *available -= cycles;
         cycles
     }
+    async fn bitcoin_get_utxos(
+        &self,
+        request: GetUtxosRequest,
+    ) -> Result<GetUtxosResponse, String> {
+        self.get_utxos_calls.borrow_mut().push_back(request);
+        self.get_utxos_replies
+            .borrow_mut()
+            .pop_front()
+            .ok_or("no more reply".to_string())
+    }
 }
 
 impl MockEnv {
@@ -78,6 +96,9 @@ impl MockEnv {
             available_cycles: RefCell::new(available_cycles),
             accepted_cycles: RefCell::new(0),
             called_provider: RefCell::new(None),
+            use_bitcoin_canister: false,
+            get_utxos_calls: RefCell::new(VecDeque::new()),
+            get_utxos_replies: RefCell::new(VecDeque::new()),
         }
     }
     fn assert_get_tx_call(&self, txid: Txid, max_response_bytes: u32) {
@@ -92,6 +113,9 @@ impl MockEnv {
     fn expect_get_tx_with_reply(&self, reply: Result<Transaction, HttpGetTxError>) {
         self.replies.borrow_mut().push_back(reply)
     }
+    fn expect_get_utxos_with_reply(&self, reply: GetUtxosResponse) {
+        self.get_utxos_replies.borrow_mut().push_back(reply)
+    }
     fn refill_cycles(&self, cycles: u128) {
         *self.available_cycles.borrow_mut() = cycles;
     }
@@ -567,3 +591,114 @@ async fn test_check_fetched() {
         )) if err.contains("has no address")
     ));
 }
+
+#[tokio::test]
+async fn test_check_fetched_with_bitcoin_canister() {
+    let mut env = MockEnv::new(CHECK_TRANSACTION_CYCLES_REQUIRED);
+    env.use_bitcoin_canister = true;
+    let network = env.config().btc_network();
+
+    let txid_0 = mock_txid(0);
+    let txid_1 = mock_txid(1);
+    let txid_2 = mock_txid(2);
+    // The first input spends a P2WPKH output, whose address is resolved with the
+    // Bitcoin canister. The second input reveals no public key, and the transaction
+    // of its output is fetched from a provider.
+    let public_key = bitcoin::CompressedPublicKey::from_slice(
+        &hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798").unwrap(),
+    )
+    .unwrap();
+    let p2wpkh_address = Address::p2wpkh(&public_key, bitcoin::Network::Bitcoin);
+    let mut tx_0 = mock_transaction_with_inputs(vec![(txid_1, 0), (txid_2, 1)]);
+    tx_0.input[0].script_sig = ScriptBuf::new();
+    tx_0.input[0].witness = Witness::from_slice(&[vec![1; 71], public_key.to_bytes().to_vec()]);
+    tx_0.input[1].script_sig = ScriptBuf::new();
+    tx_0.input[1].witness = Witness::from_slice(&[vec![1; 64]]);
+    tx_0.output = mock_transaction_with_outputs(1).output;
+    let output_address =
+        Address::from_script(&tx_0.output[0].script_pubkey, bitcoin::Network::Bitcoin).unwrap();
+    let tx_2 = mock_transaction_with_outputs(2);
+
+    let fetched = FetchedTx {
+        tx: TransactionCheckData::from_transaction(&network, tx_0.clone()).unwrap(),
+        input_addresses: vec![None, None],
+    };
+    assert_eq!(
+        fetched.tx.inputs[0].address_candidates,
+        vec![
+            p2wpkh_address.clone(),
+            Address::p2wsh(
+                bitcoin::Script::from_bytes(&public_key.to_bytes()),
+                bitcoin::Network::Bitcoin
+            )
+        ]
+    );
+    state::set_fetch_status(txid_0, FetchTxStatus::Fetched(fetched.clone()));
+    state::clear_fetch_status(txid_2);
+
+    let utxo = |txid: Txid, vout: u32, height: u32| Utxo {
+        outpoint: Outpoint {
+            txid: txid.as_ref().to_vec(),
+            vout,
+        },
+        value: 1,
+        height,
+    };
+    let utxos_response = |utxos: Vec<Utxo>| GetUtxosResponse {
+        utxos,
+        tip_block_hash: vec![0; 32],
+        tip_height: 105,
+        next_page: None,
+    };
+    // The output of the transaction is in the block at height 100.
+    env.expect_get_utxos_with_reply(utxos_response(vec![utxo(txid_0, 0, 100)]));
+    // The output spent by the first input is a UTXO of the P2WPKH address before that block.
+    env.expect_get_utxos_with_reply(utxos_response(vec![
+        utxo(txid_2, 0, 90),
+        utxo(txid_1, 0, 90),
+    ]));
+    // The candidate of the second input is wrong.
+    env.expect_get_utxos_with_reply(utxos_response(vec![]));
+    env.expect_get_tx_with_reply(Ok(tx_2.clone()));
+
+    assert!(matches!(
+        env.check_fetched(txid_0, &fetched).await,
+        CheckTransactionResponse::Passed
+    ));
+    let requests: Vec<_> = env
+        .get_utxos_calls
+        .borrow_mut()
+        .drain(..)
+        .map(|request| (request.address, request.filter))
+        .collect();
+    assert_eq!(
+        requests,
+        vec![
+            (output_address.to_string(), None),
+            (
+                p2wpkh_address.to_string(),
+                Some(UtxoFilter::MinConfirmations(7))
+            ),
+            (
+                Address::p2wsh(
+                    bitcoin::Script::from_bytes(&[1; 64]),
+                    bitcoin::Network::Bitcoin
+                )
+                .to_string(),
+                Some(UtxoFilter::MinConfirmations(7))
+            ),
+        ]
+    );
+    env.assert_get_tx_call(txid_2, INITIAL_MAX_RESPONSE_BYTES);
+    env.assert_no_more_get_tx_call();
+    match state::get_fetch_status(txid_0) {
+        Some(FetchTxStatus::Fetched(fetched)) => assert_eq!(
+            fetched.input_addresses,
+            vec![
+                Some(p2wpkh_address),
+                Address::from_script(&tx_2.output[1].script_pubkey, bitcoin::Network::Bitcoin).ok()
+            ]
+        ),
+        status => panic!("unexpected status {:?}", status),
+    }
+}
@@ -1,16 +1,18 @@
 use bitcoin::{consensus::Decodable, Address, Transaction};
 use candid::Nat;
 use ic_btc_checker::{
-    blocklist::is_blocked, get_tx_cycle_cost, BtcNetwork, CheckAddressArgs, CheckAddressResponse,
-    CheckArg, CheckMode, CheckTransactionArgs, CheckTransactionIrrecoverableError,
-    CheckTransactionQueryArgs, CheckTransactionQueryResponse, CheckTransactionResponse,
-    CheckTransactionRetriable, CheckTransactionStatus, CheckTransactionStrArgs,
-    CHECK_TRANSACTION_CYCLES_REQUIRED, CHECK_TRANSACTION_CYCLES_SERVICE_FEE,
-    RETRY_MAX_RESPONSE_BYTES,
+    get_tx_cycle_cost, AddressListEvent, AddressListKind, AddressListPage, BtcNetwork,
+    CheckAddressArgs, CheckAddressResponse, CheckArg, CheckMode, CheckTransactionArgs,
+    CheckTransactionIrrecoverableError, CheckTransactionQueryArgs, CheckTransactionQueryResponse,
+    CheckTransactionResponse, CheckTransactionRetriable, CheckTransactionStatus,
+    CheckTransactionStrArgs, GetAddressListArgs, GetAddressListEventsArgs, UpdateAddressListArgs,
+    UpdateAddressListError, CHECK_TRANSACTION_CYCLES_REQUIRED,
+    CHECK_TRANSACTION_CYCLES_SERVICE_FEE, RETRY_MAX_RESPONSE_BYTES,
 };
 use ic_btc_interface::Txid;
 use ic_canister_log::{export as export_logs, log};
 use ic_cdk::api::call::RejectionCode;
+use ic_cdk::api::management_canister::bitcoin::{GetUtxosRequest, GetUtxosResponse};
 use ic_cdk::api::management_canister::http_request::{HttpResponse, TransformArgs};
 use ic_http_types as http;
 use std::cell::RefCell;
@@ -18,16 +20,22 @@ use std::collections::BTreeMap;
 use std::fmt;
 use std::str::FromStr;
 
+mod address_lists;
 mod dashboard;
 mod fetch;
 mod logs;
 mod providers;
 mod state;
 
+use address_lists::is_blocked;
 use fetch::{check_for_blocked_input_addresses, FetchEnv, FetchResult, TryFetchResult};
 use logs::{Log, LogEntry, Priority, DEBUG, WARN};
 use state::{get_config, set_config, Config, FetchGuardError, FetchTxStatus, HttpGetTxError};
 
+/// Cycles attached to `bitcoin_get_utxos` calls, which is enough for the Bitcoin mainnet.
+/// The cycles that are not spent are refunded.
+const GET_UTXOS_CYCLES: u128 = 10_000_000_000;
+
 #[derive(PartialOrd, Ord, PartialEq, Eq)]
 enum HttpsOutcallStatus {
     ResponseTooLarge,
@@ -151,6 +159,35 @@ async fn check_transaction_with<F: FnOnce() -> Result<Txid, String>>(
     }
 }
 
+/// Add addresses to or remove addresses from the custom blocklist or allowlist,
+/// and return the new version of the address lists.
+/// Only controllers of the canister are authorized to update the address lists.
+#[ic_cdk::update]
+fn update_address_list(args: UpdateAddressListArgs) -> Result<u64, UpdateAddressListError> {
+    let caller = ic_cdk::caller();
+    if !ic_cdk::api::is_controller(&caller) {
+        return Err(UpdateAddressListError::Unauthorized);
+    }
+    address_lists::update_address_list(
+        caller,
+        ic_cdk::api::time(),
+        &get_config().btc_network(),
+        args,
+    )
+}
+
+/// Return a page of the given custom address list together with the version of the lists.
+#[ic_cdk::query]
+fn get_address_list(args: GetAddressListArgs) -> AddressListPage {
+    address_lists::get_address_list(args.list, args.start, args.length)
+}
+
+/// Return the audit events of the updates of the custom address lists.
+#[ic_cdk::query]
+fn get_address_list_events(args: GetAddressListEventsArgs) -> Vec<AddressListEvent> {
+    address_lists::get_address_list_events(args.start, args.length)
+}
+
 #[ic_cdk::query(hidden = true)]
 fn transform(raw: TransformArgs) -> HttpResponse {
     HttpResponse {
@@ -163,14 +200,16 @@ fn transform(raw: TransformArgs) -> HttpResponse {
 #[ic_cdk::init]
 fn init(arg: Option<CheckArg>) {
     match arg {
-        Some(CheckArg::InitArg(init_arg)) => set_config(
-            Config::new_and_validate(
+        Some(CheckArg::InitArg(init_arg)) => {
+            let mut config = Config::new_and_validate(
                 init_arg.btc_network,
                 init_arg.check_mode,
                 init_arg.num_subnet_nodes,
             )
-            .unwrap_or_else(|err| ic_cdk::trap(&format!("error creating config: {}", err))),
-        ),
+            .unwrap_or_else(|err| ic_cdk::trap(&format!("error creating config: {}", err)));
+            config.use_bitcoin_canister = init_arg.use_bitcoin_canister.unwrap_or_default();
+            set_config(config);
+        }
         _ => {
             ic_cdk::trap("cannot init canister state without init args");
         }
@@ -190,9 +229,13 @@ fn post_upgrade(arg: Option<CheckArg>) {
                 .as_ref()
                 .and_then(|arg| arg.check_mode)
                 .unwrap_or(old_config.check_mode);
-            let config =
+            let mut config =
                 Config::new_and_validate(old_config.btc_network(), check_mode, num_subnet_nodes)
                     .unwrap_or_else(|err| ic_cdk::trap(&format!("error creating config: {}", err)));
+            config.use_bitcoin_canister = arg
+                .as_ref()
+                .and_then(|arg| arg.use_bitcoin_canister)
+                .unwrap_or(old_config.use_bitcoin_canister);
             set_config(config);
         }
         Some(CheckArg::InitArg(_)) => {
@@ -277,6 +320,29 @@ fn http_request(req: http::HttpRequest) -> http::HttpResponse {
                     .value(&[("size", size.to_string().as_str())], *count as f64)
                     .unwrap();
             }
+            writer
+                .gauge_vec(
+                    "btc_checker_address_list_size",
+                    "The number of addresses in the custom address lists.",
+                )
+                .unwrap()
+                .value(
+                    &[("list", "blocklist")],
+                    state::address_list_len(AddressListKind::Blocklist) as f64,
+                )
+                .unwrap()
+                .value(
+                    &[("list", "allowlist")],
+                    state::address_list_len(AddressListKind::Allowlist) as f64,
+                )
+                .unwrap();
+            writer
+                .encode_gauge(
+                    "btc_checker_address_lists_version",
+                    state::address_lists_version() as f64,
+                    "The version of the custom address lists.",
+                )
+                .unwrap();
             writer
                 .counter_vec(
                     "btc_check_requests_total",
@@ -483,6 +549,30 @@ impl FetchEnv for BtcCheckerCanisterEnv {
     fn cycles_accept(&self, cycles: u128) -> u128 {
         ic_cdk::api::call::msg_cycles_accept128(cycles)
     }
+
+    async fn bitcoin_get_utxos(
+        &self,
+        request: GetUtxosRequest,
+    ) -> Result<GetUtxosResponse, String> {
+        use ic_cdk::api::call::{
+            call_with_payment128, msg_cycles_available128, msg_cycles_refunded128,
+        };
+        if msg_cycles_available128() < GET_UTXOS_CYCLES {
+            return Err("not enough cycles to call bitcoin_get_utxos".to_string());
+        }
+        let result: Result<(GetUtxosResponse,), _> = call_with_payment128(
+            candid::Principal::management_canister(),
+            "bitcoin_get_utxos",
+            (request,),
+            GET_UTXOS_CYCLES,
+        )
+        .await;
+        // Unspent cycles are refunded, and the caller only pays for the spent ones.
+        self.cycles_accept(GET_UTXOS_CYCLES.saturating_sub(msg_cycles_refunded128()));
+        result
+            .map(|(response,)| response)
+            .map_err(|(code, message)| format!("rejected with code {:?}: {}", code, message))
+    }
 }
 
 /// Check the input addresses of a transaction given its txid.
@@ -2,11 +2,17 @@ use crate::{
     providers::{parse_authorization_header_from_url, Provider},
     BtcNetwork, CheckMode,
 };
-use bitcoin::{Address, Transaction};
+use bitcoin::{
+    script::Instruction, Address, CompressedPublicKey, PublicKey, Script, Transaction, TxIn,
+};
+use ic_btc_checker::{AddressListEvent, AddressListKind};
 use ic_btc_interface::Txid;
 use ic_cdk::api::call::RejectionCode;
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
-use ic_stable_structures::{storable::Bound, Cell, DefaultMemoryImpl, Storable};
+use ic_stable_structures::{
+    storable::Bound, BTreeMap as StableBTreeMap, Cell, DefaultMemoryImpl, Log as StableLog,
+    Storable,
+};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -89,6 +95,8 @@ pub struct TransactionCheckData {
 pub struct PreviousOutput {
     pub txid: Txid,
     pub vout: u32,
+    /// Candidates for the address of this output, derived from the input spending it.
+    pub address_candidates: Vec<Address>,
 }
 
 impl TransactionCheckData {
@@ -96,30 +104,70 @@ impl TransactionCheckData {
         btc_network: &BtcNetwork,
         tx: Transaction,
     ) -> Result<Self, bitcoin::address::FromScriptError> {
+        let network = bitcoin::Network::from(btc_network.clone());
         let inputs = tx
             .input
             .iter()
             .map(|input| PreviousOutput {
                 txid: Txid::from(*(input.previous_output.txid.as_ref() as &[u8; 32])),
                 vout: input.previous_output.vout,
+                address_candidates: address_candidates(network, input),
             })
             .collect();
         let mut outputs = Vec::new();
         for output in tx.output.iter() {
             // Some outputs do not have addresses. These outputs will never be
             // inputs of other transactions, so it is okay to treat them as `None`.
-            outputs.push(
-                Address::from_script(
-                    &output.script_pubkey,
-                    bitcoin::Network::from(btc_network.clone()),
-                )
-                .ok(),
-            )
+            outputs.push(Address::from_script(&output.script_pubkey, network).ok())
         }
         Ok(Self { inputs, outputs })
     }
 }
 
+/// Return the candidate addresses of the output spent by the given input, derived
+/// from the public key or the script that the input reveals.
+///
+/// The derivation is ambiguous, e.g. the last push of a script signature is either a
+/// public key or a redeem script, so a candidate must be checked against the UTXO set
+/// before it is used. Inputs spending taproot outputs reveal neither and have no candidates.
+fn address_candidates(network: bitcoin::Network, input: &TxIn) -> Vec<Address> {
+    if input.previous_output.is_null() {
+        return vec![];
+    }
+    let pushes: Vec<&[u8]> = input
+        .script_sig
+        .instructions()
+        .map(|instruction| match instruction {
+            Ok(Instruction::PushBytes(bytes)) => Some(bytes.as_bytes()),
+            _ => None,
+        })
+        .collect::<Option<_>>()
+        .unwrap_or_default();
+    let mut candidates = vec![];
+    if let Some(last) = pushes.last() {
+        // Spending a P2PKH output reveals the public key, and spending a P2SH output,
+        // including a P2SH-wrapped segwit output, reveals the redeem script.
+        if pushes.len() >= 2 {
+            if let Ok(public_key) = PublicKey::from_slice(last) {
+                candidates.push(Address::p2pkh(public_key.pubkey_hash(), network));
+            }
+        }
+        if let Ok(address) = Address::p2sh(Script::from_bytes(last), network) {
+            candidates.push(address);
+        }
+    } else if let Some(last) = input.witness.last() {
+        // Spending a P2WPKH output reveals the public key, and spending a P2WSH output
+        // reveals the witness script.
+        if input.witness.len() == 2 {
+            if let Ok(public_key) = CompressedPublicKey::from_slice(last) {
+                candidates.push(Address::p2wpkh(&public_key, network));
+            }
+        }
+        candidates.push(Address::p2wsh(Script::from_bytes(last), network));
+    }
+    candidates
+}
+
 // Max number of concurrent http outcalls.
 const MAX_CONCURRENT: u32 = 50;
 
@@ -284,6 +332,10 @@ pub struct Config {
     pub check_mode: CheckMode,
     #[serde(default = "default_num_subnet_nodes")]
     pub num_subnet_nodes: u16,
+    /// Whether to resolve input addresses with the UTXO set of the Bitcoin canister
+    /// before fetching the transactions of the inputs from the providers.
+    #[serde(default)]
+    pub use_bitcoin_canister: bool,
 }
 
 fn default_num_subnet_nodes() -> u16 {
@@ -303,6 +355,7 @@ impl Config {
             btc_network,
             check_mode,
             num_subnet_nodes,
+            use_bitcoin_canister: false,
         })
     }
 
@@ -340,8 +393,13 @@ impl Storable for ConfigState {
 type StableMemory = VirtualMemory<DefaultMemoryImpl>;
 
 const CONFIG_MEMORY_ID: MemoryId = MemoryId::new(0);
+const ADDRESS_LISTS_VERSION_MEMORY_ID: MemoryId = MemoryId::new(1);
+const ADDRESS_LIST_EVENTS_INDEX_MEMORY_ID: MemoryId = MemoryId::new(2);
+const ADDRESS_LIST_EVENTS_DATA_MEMORY_ID: MemoryId = MemoryId::new(3);
+const ADDRESS_BLOCKLIST_MEMORY_ID: MemoryId = MemoryId::new(4);
+const ADDRESS_ALLOWLIST_MEMORY_ID: MemoryId = MemoryId::new(5);
 
-// Configuration is stored in stable memory.
+// Configuration, address lists and their audit events are stored in stable memory.
 thread_local! {
     static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(
             MemoryManager::init(DefaultMemoryImpl::default())
@@ -349,10 +407,32 @@ thread_local! {
     static CONFIG: RefCell<Cell<ConfigState, StableMemory>> = RefCell::new(
         Cell::init(config_memory(), ConfigState::Uninitialized).expect("failed to initialize stable cell for config")
     );
+    static ADDRESS_LISTS_VERSION: RefCell<Cell<u64, StableMemory>> = RefCell::new(
+        Cell::init(memory(ADDRESS_LISTS_VERSION_MEMORY_ID), 0)
+            .expect("failed to initialize stable cell for address lists version")
+    );
+    // The custom address lists, keyed by the canonical string representation of the addresses.
+    static ADDRESS_BLOCKLIST: RefCell<StableBTreeMap<String, (), StableMemory>> = RefCell::new(
+        StableBTreeMap::init(memory(ADDRESS_BLOCKLIST_MEMORY_ID))
+    );
+    static ADDRESS_ALLOWLIST: RefCell<StableBTreeMap<String, (), StableMemory>> = RefCell::new(
+        StableBTreeMap::init(memory(ADDRESS_ALLOWLIST_MEMORY_ID))
+    );
+    static ADDRESS_LIST_EVENTS: RefCell<StableLog<Vec<u8>, StableMemory, StableMemory>> = RefCell::new(
+        StableLog::init(
+            memory(ADDRESS_LIST_EVENTS_INDEX_MEMORY_ID),
+            memory(ADDRESS_LIST_EVENTS_DATA_MEMORY_ID),
+        )
+        .expect("failed to initialize stable log for address list events")
+    );
+}
+
+fn memory(id: MemoryId) -> StableMemory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(id))
 }
 
 fn config_memory() -> StableMemory {
-    MEMORY_MANAGER.with(|m| m.borrow().get(CONFIG_MEMORY_ID))
+    memory(CONFIG_MEMORY_ID)
 }
 
 pub fn set_config(config: Config) {
@@ -367,3 +447,80 @@ pub fn get_config() -> Config {
         ConfigState::Initialized(config) => config,
     }
 }
+
+/// Return the version of the custom address lists, i.e. the number of updates applied to them.
+pub fn address_lists_version() -> u64 {
+    ADDRESS_LISTS_VERSION.with(|c| *c.borrow().get())
+}
+
+fn with_address_list<R>(
+    list: AddressListKind,
+    f: impl FnOnce(&mut StableBTreeMap<String, (), StableMemory>) -> R,
+) -> R {
+    match list {
+        AddressListKind::Blocklist => ADDRESS_BLOCKLIST.with(|l| f(&mut l.borrow_mut())),
+        AddressListKind::Allowlist => ADDRESS_ALLOWLIST.with(|l| f(&mut l.borrow_mut())),
+    }
+}
+
+pub fn address_list_contains(list: AddressListKind, address: &str) -> bool {
+    with_address_list(list, |l| l.contains_key(&address.to_string()))
+}
+
+pub fn address_list_len(list: AddressListKind) -> u64 {
+    with_address_list(list, |l| l.len())
+}
+
+/// Return at most `length` addresses of the given list in lexicographic order,
+/// starting from the address `start` if it is given.
+pub fn get_address_list(
+    list: AddressListKind,
+    start: Option<String>,
+    length: usize,
+) -> Vec<String> {
+    with_address_list(list, |l| match start {
+        Some(start) => l
+            .range(start..)
+            .take(length)
+            .map(|(address, ())| address)
+            .collect(),
+        None => l.iter().take(length).map(|(address, ())| address).collect(),
+    })
+}
+
+/// Insert the given address into the given list and return `true` if it was not already there.
+pub fn insert_into_address_list(list: AddressListKind, address: String) -> bool {
+    with_address_list(list, |l| l.insert(address, ()).is_none())
+}
+
+/// Remove the given address from the given list and return `true` if it was there.
+pub fn remove_from_address_list(list: AddressListKind, address: String) -> bool {
+    with_address_list(list, |l| l.remove(&address).is_some())
+}
+
+/// Record the audit event of an update of the address lists and set the version
+/// of the lists to the version of the event.
+pub fn record_address_list_update(event: &AddressListEvent) {
+    let mut buf = vec![];
+    ciborium::ser::into_writer(event, &mut buf).expect("failed to encode AddressListEvent");
+    ADDRESS_LIST_EVENTS
+        .with(|l| l.borrow().append(&buf))
+        .expect("failed to append address list event");
+    ADDRESS_LISTS_VERSION
+        .with(|c| c.borrow_mut().set(event.version))
+        .expect("failed to set address lists version");
+}
+
+/// Return at most `length` address list events starting from the event at index `start`.
+pub fn get_address_list_events(start: u64, length: u64) -> Vec<AddressListEvent> {
+    ADDRESS_LIST_EVENTS.with(|l| {
+        let log = l.borrow();
+        (start..log.len().min(start.saturating_add(length)))
+            .map(|index| {
+                let bytes = log.get(index).expect("BUG: missing address list event");
+                ciborium::de::from_reader(bytes.as_slice())
+                    .unwrap_or_else(|e| panic!("failed to decode AddressListEvent: {}", e))
+            })
+            .collect()
+    })
+}
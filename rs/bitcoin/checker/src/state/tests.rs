@@ -233,3 +233,49 @@ mod schema_upgrades {
         ]
     }
 }
+
+#[test]
+fn test_address_candidates() {
+    use bitcoin::{hashes::Hash, OutPoint, ScriptBuf, Sequence, Witness};
+    let public_key = PublicKey::from_slice(
+        &hex::decode("02c4c69e4d364b3edf84b520a018d57e71face197ec8f94643607e4aca70dc82c1").unwrap(),
+    )
+    .unwrap();
+    let network = bitcoin::Network::Bitcoin;
+    let input = |script_sig: ScriptBuf, witness: Witness| TxIn {
+        previous_output: OutPoint {
+            txid: bitcoin::Txid::from_byte_array([1; 32]),
+            vout: 0,
+        },
+        script_sig,
+        sequence: Sequence::ZERO,
+        witness,
+    };
+    let p2pkh_script_sig = ScriptBuf::builder()
+        .push_slice(bitcoin::script::PushBytesBuf::try_from(vec![1; 71]).unwrap())
+        .push_key(&public_key)
+        .into_script();
+
+    // Spending a P2PKH output.
+    assert_eq!(
+        address_candidates(network, &input(p2pkh_script_sig.clone(), Witness::new())),
+        vec![
+            Address::p2pkh(public_key.pubkey_hash(), network),
+            Address::p2sh(Script::from_bytes(&public_key.to_bytes()), network).unwrap(),
+        ]
+    );
+    // Spending a P2WPKH output.
+    let compressed_public_key = CompressedPublicKey(public_key.inner);
+    let witness = Witness::from_slice(&[vec![1; 71], public_key.to_bytes()]);
+    assert_eq!(
+        address_candidates(network, &input(ScriptBuf::new(), witness)),
+        vec![
+            Address::p2wpkh(&compressed_public_key, network),
+            Address::p2wsh(Script::from_bytes(&public_key.to_bytes()), network),
+        ]
+    );
+    // Coinbase inputs spend no output.
+    let mut coinbase = input(p2pkh_script_sig, Witness::new());
+    coinbase.previous_output = OutPoint::null();
+    assert_eq!(address_candidates(network, &coinbase), vec![]);
+}
@@ -1,4 +1,4 @@
-use candid::{CandidType, Deserialize};
+use candid::{CandidType, Deserialize, Principal};
 use ic_btc_interface::Txid;
 use serde::Serialize;
 use std::{fmt, str::FromStr};
@@ -9,7 +9,7 @@ pub struct CheckAddressArgs {
     pub address: String,
 }
 
-#[derive(CandidType, Debug, Deserialize, Serialize)]
+#[derive(CandidType, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum CheckAddressResponse {
     Passed,
     Failed,
@@ -163,6 +163,9 @@ pub struct InitArg {
     pub btc_network: BtcNetwork,
     pub check_mode: CheckMode,
     pub num_subnet_nodes: u16,
+    /// Whether to resolve input addresses with the UTXO set of the Bitcoin canister
+    /// before fetching the transactions of the inputs with HTTPS outcalls. Defaults to `false`.
+    pub use_bitcoin_canister: Option<bool>,
 }
 
 #[derive(CandidType, Clone, Deserialize, Debug, Eq, PartialEq, Serialize, Hash)]
@@ -216,6 +219,7 @@ impl fmt::Display for BtcNetwork {
 pub struct UpgradeArg {
     pub check_mode: Option<CheckMode>,
     pub num_subnet_nodes: Option<u16>,
+    pub use_bitcoin_canister: Option<bool>,
 }
 
 #[derive(CandidType, Debug, Deserialize, Serialize)]
@@ -223,3 +227,90 @@ pub enum CheckArg {
     InitArg(InitArg),
     UpgradeArg(Option<UpgradeArg>),
 }
+
+#[derive(CandidType, Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, Hash)]
+pub enum AddressListKind {
+    /// Addresses that fail the check, in addition to the built-in blocklist.
+    Blocklist,
+    /// Addresses that pass the check, even if they are on the custom blocklist.
+    /// The allowlist does not override the built-in blocklist.
+    Allowlist,
+}
+
+impl fmt::Display for AddressListKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Blocklist => write!(f, "blocklist"),
+            Self::Allowlist => write!(f, "allowlist"),
+        }
+    }
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, Serialize)]
+pub struct UpdateAddressListArgs {
+    /// The list to update.
+    pub list: AddressListKind,
+    /// Bitcoin addresses to add to the list.
+    pub add: Vec<String>,
+    /// Bitcoin addresses to remove from the list.
+    pub remove: Vec<String>,
+    /// If set, the update is only applied if the address lists are at this version.
+    pub expected_version: Option<u64>,
+    /// Free-form justification of the update, recorded in the audit event.
+    pub reason: Option<String>,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum UpdateAddressListError {
+    /// The caller is not a controller of the canister.
+    Unauthorized,
+    /// The address lists are not at the expected version.
+    VersionMismatch { current_version: u64 },
+    /// The address is malformed or not an address of the Bitcoin network of the canister.
+    InvalidAddress { address: String, reason: String },
+    /// The update adds or removes more than `max` addresses.
+    TooManyAddresses { max: u64 },
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, Serialize)]
+pub struct GetAddressListArgs {
+    /// The list to return.
+    pub list: AddressListKind,
+    /// The first address to return. Addresses are returned in lexicographic order.
+    pub start: Option<String>,
+    /// The maximum number of addresses to return.
+    pub length: u64,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AddressListPage {
+    /// The number of updates applied to the address lists.
+    pub version: u64,
+    pub addresses: Vec<String>,
+    /// The address to pass as `start` to get the next page, if any.
+    pub next: Option<String>,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, Serialize)]
+pub struct GetAddressListEventsArgs {
+    /// The index of the first event to return.
+    pub start: u64,
+    /// The maximum number of events to return.
+    pub length: u64,
+}
+
+/// Audit event recorded for every update of the address lists.
+#[derive(CandidType, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AddressListEvent {
+    /// The version of the address lists after the update.
+    pub version: u64,
+    /// Time of the update in nanoseconds since the epoch (1970-01-01).
+    pub timestamp: u64,
+    pub caller: Principal,
+    pub list: AddressListKind,
+    /// Addresses that were added to the list.
+    pub added: Vec<String>,
+    /// Addresses that were removed from the list.
+    pub removed: Vec<String>,
+    pub reason: Option<String>,
+}
@@ -1,12 +1,13 @@
 use candid::{decode_one, Encode, Principal};
 use ic_base_types::PrincipalId;
 use ic_btc_checker::{
-    blocklist, get_tx_cycle_cost, BtcNetwork, CheckAddressArgs, CheckAddressResponse, CheckArg,
-    CheckMode, CheckTransactionArgs, CheckTransactionIrrecoverableError, CheckTransactionQueryArgs,
-    CheckTransactionQueryResponse, CheckTransactionResponse, CheckTransactionRetriable,
-    CheckTransactionStatus, CheckTransactionStrArgs, InitArg, UpgradeArg,
-    CHECK_TRANSACTION_CYCLES_REQUIRED, CHECK_TRANSACTION_CYCLES_SERVICE_FEE,
-    INITIAL_MAX_RESPONSE_BYTES,
+    blocklist, get_tx_cycle_cost, AddressListEvent, AddressListKind, AddressListPage, BtcNetwork,
+    CheckAddressArgs, CheckAddressResponse, CheckArg, CheckMode, CheckTransactionArgs,
+    CheckTransactionIrrecoverableError, CheckTransactionQueryArgs, CheckTransactionQueryResponse,
+    CheckTransactionResponse, CheckTransactionRetriable, CheckTransactionStatus,
+    CheckTransactionStrArgs, GetAddressListArgs, GetAddressListEventsArgs, InitArg,
+    UpdateAddressListArgs, UpdateAddressListError, UpgradeArg, CHECK_TRANSACTION_CYCLES_REQUIRED,
+    CHECK_TRANSACTION_CYCLES_SERVICE_FEE, INITIAL_MAX_RESPONSE_BYTES,
 };
 use ic_btc_interface::Txid;
 use ic_cdk::api::call::RejectionCode;
@@ -21,7 +22,7 @@ use pocket_ic::{
         CanisterHttpHeader, CanisterHttpReject, CanisterHttpReply, CanisterHttpRequest,
         CanisterHttpResponse, MockCanisterHttpResponse, RawMessageId,
     },
-    query_candid, PocketIc, PocketIcBuilder, RejectCode, RejectResponse,
+    query_candid, update_candid_as, PocketIc, PocketIcBuilder, RejectCode, RejectResponse,
 };
 use std::str::FromStr;
 
@@ -71,6 +72,7 @@ impl Setup {
             btc_network,
             check_mode: CheckMode::Normal,
             num_subnet_nodes: TEST_SUBNET_NODES,
+            use_bitcoin_canister: None,
         };
         let caller = env.create_canister_with_settings(Some(controller), None);
         env.add_cycles(caller, 100_000_000_000_000);
@@ -276,6 +278,184 @@ fn test_check_address() {
     );
 }
 
+#[test]
+fn test_update_address_lists() {
+    let blocked_address = blocklist::BTC_ADDRESS_BLOCKLIST[0].to_string();
+    let satoshi_address = "12cbQLTFMXRnSzktFkuoG3eHoMeFtpTu3S".to_string();
+    let other_address = "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".to_string();
+
+    let setup = Setup::new(BtcNetwork::Mainnet);
+    let check_address = |address: &String| {
+        setup
+            .query_btc_checker::<_, (CheckAddressResponse,)>(
+                "check_address",
+                CheckAddressArgs {
+                    address: address.clone(),
+                },
+            )
+            .unwrap()
+            .0
+    };
+    let update_address_list = |caller: Principal, args: UpdateAddressListArgs| {
+        update_candid_as::<_, (Result<u64, UpdateAddressListError>,)>(
+            &setup.env,
+            setup.btc_checker_canister,
+            caller,
+            "update_address_list",
+            (args,),
+        )
+        .unwrap()
+        .0
+    };
+
+    let block_satoshi = UpdateAddressListArgs {
+        list: AddressListKind::Blocklist,
+        add: vec![satoshi_address.clone()],
+        remove: vec![],
+        expected_version: Some(0),
+        reason: Some("test".to_string()),
+    };
+    assert_eq!(
+        update_address_list(PrincipalId::new_user_test_id(2).0, block_satoshi.clone()),
+        Err(UpdateAddressListError::Unauthorized)
+    );
+    assert_eq!(
+        check_address(&satoshi_address),
+        CheckAddressResponse::Passed
+    );
+
+    assert_eq!(update_address_list(setup.controller, block_satoshi), Ok(1));
+    assert_eq!(
+        check_address(&satoshi_address),
+        CheckAddressResponse::Failed
+    );
+
+    assert_eq!(
+        update_address_list(
+            setup.controller,
+            UpdateAddressListArgs {
+                list: AddressListKind::Blocklist,
+                add: vec![other_address.clone()],
+                remove: vec![],
+                expected_version: None,
+                reason: None,
+            }
+        ),
+        Ok(2)
+    );
+    assert_eq!(check_address(&other_address), CheckAddressResponse::Failed);
+
+    // The allowlist overrides the custom blocklist, but not the built-in blocklist.
+    assert_eq!(
+        update_address_list(
+            setup.controller,
+            UpdateAddressListArgs {
+                list: AddressListKind::Allowlist,
+                add: vec![other_address.clone(), blocked_address.clone()],
+                remove: vec![],
+                expected_version: None,
+                reason: None,
+            }
+        ),
+        Ok(3)
+    );
+    assert_eq!(check_address(&other_address), CheckAddressResponse::Passed);
+    assert_eq!(
+        check_address(&blocked_address),
+        CheckAddressResponse::Failed
+    );
+
+    // The address lists and their audit events are preserved across upgrades.
+    setup
+        .env
+        .upgrade_canister(
+            setup.btc_checker_canister,
+            btc_checker_wasm(),
+            Encode!(&CheckArg::UpgradeArg(None)).unwrap(),
+            Some(setup.controller),
+        )
+        .unwrap();
+    assert_eq!(
+        check_address(&satoshi_address),
+        CheckAddressResponse::Failed
+    );
+    assert_eq!(check_address(&other_address), CheckAddressResponse::Passed);
+
+    let get_address_list = |list: AddressListKind, start: Option<String>| {
+        setup
+            .query_btc_checker::<_, (AddressListPage,)>(
+                "get_address_list",
+                GetAddressListArgs {
+                    list,
+                    start,
+                    length: 1,
+                },
+            )
+            .unwrap()
+            .0
+    };
+    assert_eq!(
+        get_address_list(AddressListKind::Blocklist, None),
+        AddressListPage {
+            version: 3,
+            addresses: vec![satoshi_address.clone()],
+            next: Some(other_address.clone()),
+        }
+    );
+    assert_eq!(
+        get_address_list(AddressListKind::Blocklist, Some(other_address.clone())),
+        AddressListPage {
+            version: 3,
+            addresses: vec![other_address.clone()],
+            next: None,
+        }
+    );
+    assert_eq!(
+        get_address_list(AddressListKind::Allowlist, None),
+        AddressListPage {
+            version: 3,
+            addresses: vec![blocked_address.clone()],
+            next: Some(other_address.clone()),
+        }
+    );
+
+    let (events,) = setup
+        .query_btc_checker::<_, (Vec<AddressListEvent>,)>(
+            "get_address_list_events",
+            GetAddressListEventsArgs {
+                start: 0,
+                length: 10,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|event| (event.version, event.caller, event.list, event.added.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                1,
+                setup.controller,
+                AddressListKind::Blocklist,
+                vec![satoshi_address]
+            ),
+            (
+                2,
+                setup.controller,
+                AddressListKind::Blocklist,
+                vec![other_address.clone()]
+            ),
+            (
+                3,
+                setup.controller,
+                AddressListKind::Allowlist,
+                vec![blocked_address, other_address]
+            ),
+        ]
+    );
+}
+
 #[test]
 fn test_check_transaction_passed() {
     let setup = Setup::new(BtcNetwork::Mainnet);
@@ -106,6 +106,8 @@ pub fn build_dashboard(account_to_utxos_start: u64) -> Vec<u8> {
                     </thead>
                     <tbody>{}</tbody>
                 </table>
+                <h3>UTXO consolidation</h3>
+                {}
                 <h3>Finalized retrieve BTC requests</h3>
                 <table>
                     <thead>
@@ -195,6 +197,7 @@ pub fn build_dashboard(account_to_utxos_start: u64) -> Vec<u8> {
         build_pending_request_tx(s),
         build_requests_in_flight_tx(s),
         build_submitted_transactions(s),
+        build_utxo_consolidation(s),
         build_finalized_requests(s),
         build_unconfirmed_change(s),
         build_mint_unknown_utxos(s),
@@ -319,6 +322,10 @@ pub fn build_metadata(s: &CkBtcMinterState) -> String {
                         <th>Total BTC managed</th>
                         <td>{}</td>
                     </tr>
+                    <tr>
+                        <th>UTXO selection strategy</th>
+                        <td>{:?}</td>
+                    </tr>
                 </tbody>
             </table>",
         s.btc_network,
@@ -336,7 +343,8 @@ pub fn build_metadata(s: &CkBtcMinterState) -> String {
         DisplayAmount(s.check_fee),
         DisplayAmount(s.retrieve_btc_min_amount),
         DisplayAmount(s.fee_based_retrieve_btc_min_amount),
-        DisplayAmount(s.get_total_btc_managed())
+        DisplayAmount(s.get_total_btc_managed()),
+        s.utxo_selection_strategy,
     )
 }
 
@@ -393,6 +401,9 @@ pub fn build_submitted_transactions(s: &CkBtcMinterState) -> String {
                     .unwrap();
 
                     write!(buf, "<td rowspan='{}'>", rowspan).unwrap();
+                    if tx.is_consolidation() {
+                        write!(buf, "UTXO consolidation").unwrap();
+                    }
                     for req in &tx.requests {
                         write!(
                             buf,
@@ -425,6 +436,68 @@ pub fn build_submitted_transactions(s: &CkBtcMinterState) -> String {
     })
 }
 
+pub fn build_utxo_consolidation(s: &CkBtcMinterState) -> String {
+    let pending_consolidation = s
+        .submitted_transactions
+        .iter()
+        .find(|tx| tx.is_consolidation());
+    format!(
+        "<table>
+                <tbody>
+                    <tr>
+                        <th>Available UTXOs</th>
+                        <td>{}</td>
+                    </tr>
+                    <tr>
+                        <th>Consolidation threshold</th>
+                        <td>{}</td>
+                    </tr>
+                    <tr>
+                        <th>Max fee per vbyte (millisatoshi)</th>
+                        <td>{}</td>
+                    </tr>
+                    <tr>
+                        <th>Max fee per consolidation</th>
+                        <td>{}</td>
+                    </tr>
+                    <tr>
+                        <th>Confirmed consolidations</th>
+                        <td>{}</td>
+                    </tr>
+                    <tr>
+                        <th>Total consolidation fees</th>
+                        <td>{}</td>
+                    </tr>
+                    <tr>
+                        <th>Pending consolidation</th>
+                        <td>{}</td>
+                    </tr>
+                </tbody>
+            </table>",
+        s.available_utxos.len(),
+        s.utxo_consolidation_threshold
+            .map(|threshold| threshold.to_string())
+            .unwrap_or_else(|| "Disabled".to_string()),
+        s.utxo_consolidation_max_fee_per_vbyte,
+        DisplayAmount(s.utxo_consolidation_max_fee),
+        s.utxo_consolidations_count,
+        DisplayAmount(s.utxo_consolidation_fees_paid),
+        pending_consolidation
+            .map(|tx| {
+                let inputs_value = tx.used_utxos.iter().map(|u| u.value).sum::<u64>();
+                let output_value = tx.change_output.as_ref().map_or(0, |out| out.value);
+                format!(
+                    "{} ({} UTXOs, {} consolidated, {} fee)",
+                    txid_link(s, &tx.txid),
+                    tx.used_utxos.len(),
+                    DisplayAmount(output_value),
+                    DisplayAmount(inputs_value.saturating_sub(output_value)),
+                )
+            })
+            .unwrap_or_else(|| "N/A".to_string()),
+    )
+}
+
 pub fn build_finalized_requests(s: &CkBtcMinterState) -> String {
     with_utf8_buffer(|buf| {
         for req in &s.finalized_requests {
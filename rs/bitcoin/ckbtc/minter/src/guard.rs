@@ -136,6 +136,10 @@ mod tests {
             kyt_principal: None,
             kyt_fee: None,
             get_utxos_cache_expiration_seconds: None,
+            utxo_consolidation_threshold: None,
+            utxo_consolidation_max_fee_per_vbyte: None,
+            utxo_consolidation_max_fee: None,
+            utxo_selection_strategy: None,
         }
     }
 
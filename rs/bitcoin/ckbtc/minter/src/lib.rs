@@ -74,6 +74,25 @@ pub const CKBTC_LEDGER_MEMO_SIZE: u16 = 80;
 /// when building transactions.
 pub const UTXOS_COUNT_THRESHOLD: usize = 1_000;
 
+/// The fee rate (in millisatoshi per vbyte) at which the minter expects to
+/// spend its UTXOs in the long run. The branch-and-bound UTXO selection
+/// prefers fewer inputs when the fees are above this rate and more inputs
+/// when they are below.
+pub const LONG_TERM_FEE_PER_VBYTE: MillisatoshiPerByte = 10_000;
+
+/// The maximum number of steps of the branch-and-bound UTXO selection.
+const BNB_MAX_TRIES: usize = 100_000;
+
+/// The maximum number of UTXOs that the minter consolidates in a single
+/// transaction.
+pub const MAX_UTXOS_PER_CONSOLIDATION: usize = 100;
+
+/// Having a sequence number lower than (0xffffffff - 1) signals the use of replacement by fee.
+/// It allows us to increase the fee of a transaction already sent to the mempool.
+/// The rbf option is used in `resubmit_retrieve_btc`.
+/// https://github.com/bitcoin/bips/blob/master/bip-0125.mediawiki
+const SEQUENCE_RBF_ENABLED: u32 = 0xfffffffd;
+
 pub const IC_CANISTER_RUNTIME: IcCanisterRuntime = IcCanisterRuntime {};
 
 #[derive(Clone, Debug, Deserialize, serde::Serialize)]
@@ -345,6 +364,7 @@ async fn submit_pending_requests() {
             outputs,
             main_address,
             fee_millisatoshi_per_vbyte,
+            s.utxo_selection_strategy,
         ) {
             Ok((unsigned_tx, change_output, utxos)) => {
                 for req in batch.iter() {
@@ -496,6 +516,179 @@ async fn submit_pending_requests() {
     }
 }
 
+/// Consolidates the smallest UTXOs of the minter into a single output if the
+/// minter manages too many UTXOs and the Bitcoin network fees are low.
+async fn consolidate_utxos() {
+    if !state::read_state(|s| s.should_consolidate_utxos()) {
+        return;
+    }
+
+    let fee_millisatoshi_per_vbyte = match estimate_fee_per_vbyte().await {
+        Some(fee) => fee,
+        None => return,
+    };
+
+    let max_fee_per_vbyte = state::read_state(|s| s.utxo_consolidation_max_fee_per_vbyte);
+    if fee_millisatoshi_per_vbyte > max_fee_per_vbyte {
+        log!(
+            P1,
+            "[consolidate_utxos]: postponing UTXO consolidation, the median fee {} msat/vbyte is above {} msat/vbyte",
+            fee_millisatoshi_per_vbyte,
+            max_fee_per_vbyte
+        );
+        return;
+    }
+
+    let main_account = Account {
+        owner: ic_cdk::id(),
+        subaccount: None,
+    };
+
+    let ecdsa_public_key = updates::get_btc_address::init_ecdsa_public_key().await;
+    let main_address = address::account_to_bitcoin_address(&ecdsa_public_key, &main_account);
+
+    let maybe_sign_request = state::mutate_state(|s| {
+        // The state might have changed while we were waiting for the fee estimate.
+        if !s.should_consolidate_utxos() {
+            return None;
+        }
+
+        let utxos = utxos_to_consolidate(
+            &mut s.available_utxos,
+            MAX_UTXOS_PER_CONSOLIDATION,
+            fee_millisatoshi_per_vbyte,
+        );
+        if utxos.is_empty() {
+            log!(
+                P1,
+                "[consolidate_utxos]: not enough UTXOs worth consolidating at {} msat/vbyte",
+                fee_millisatoshi_per_vbyte
+            );
+            return None;
+        }
+
+        match build_consolidation_transaction(
+            utxos.clone(),
+            main_address,
+            fee_millisatoshi_per_vbyte,
+        ) {
+            Ok((_, change_output, utxos))
+                if consolidation_fee(&utxos, &change_output) > s.utxo_consolidation_max_fee =>
+            {
+                log!(
+                    P1,
+                    "[consolidate_utxos]: postponing UTXO consolidation, the fee {} is above the limit {}",
+                    consolidation_fee(&utxos, &change_output),
+                    s.utxo_consolidation_max_fee
+                );
+                for utxo in utxos {
+                    assert!(s.available_utxos.insert(utxo));
+                }
+                None
+            }
+            Ok((unsigned_tx, change_output, utxos)) => Some(SignTxRequest {
+                key_name: s.ecdsa_key_name.clone(),
+                ecdsa_public_key,
+                change_output,
+                outpoint_account: filter_output_accounts(s, &unsigned_tx),
+                network: s.btc_network,
+                unsigned_tx,
+                requests: vec![],
+                utxos,
+            }),
+            Err(err) => {
+                log!(
+                    P0,
+                    "[consolidate_utxos]: failed to build a consolidation transaction: {:?}",
+                    err
+                );
+                for utxo in utxos {
+                    assert!(s.available_utxos.insert(utxo));
+                }
+                None
+            }
+        }
+    });
+
+    let req = match maybe_sign_request {
+        Some(req) => req,
+        None => return,
+    };
+
+    log!(
+        P1,
+        "[consolidate_utxos]: signing a new consolidation transaction: {}",
+        hex::encode(tx::encode_into(&req.unsigned_tx, Vec::new()))
+    );
+
+    // This guard ensures that we return the UTXOs back to the state if the
+    // signing or sending a transaction fails or panics.
+    let utxos_guard = guard(req.utxos, |utxos| {
+        undo_sign_request(vec![], utxos);
+    });
+
+    let txid = req.unsigned_tx.txid();
+
+    let signed_tx = match sign_transaction(
+        req.key_name,
+        &req.ecdsa_public_key,
+        &req.outpoint_account,
+        req.unsigned_tx,
+    )
+    .await
+    {
+        Ok(signed_tx) => signed_tx,
+        Err(err) => {
+            log!(
+                P0,
+                "[consolidate_utxos]: failed to sign a Bitcoin transaction: {}",
+                err
+            );
+            return;
+        }
+    };
+
+    log!(
+        P0,
+        "[consolidate_utxos]: sending a signed consolidation transaction {}",
+        hex::encode(tx::encode_into(&signed_tx, Vec::new()))
+    );
+    match management::send_transaction(&signed_tx, req.network).await {
+        Ok(()) => {
+            log!(
+                P1,
+                "[consolidate_utxos]: successfully sent consolidation transaction {}",
+                &txid,
+            );
+
+            // Defuse the guard because we sent the transaction successfully.
+            let used_utxos = ScopeGuard::into_inner(utxos_guard);
+
+            state::mutate_state(|s| {
+                state::audit::sent_consolidation_transaction(
+                    s,
+                    state::SubmittedBtcTransaction {
+                        requests: vec![],
+                        txid,
+                        used_utxos,
+                        change_output: Some(req.change_output),
+                        submitted_at: ic_cdk::api::time(),
+                        fee_per_vbyte: Some(fee_millisatoshi_per_vbyte),
+                    },
+                    &IC_CANISTER_RUNTIME,
+                );
+            });
+        }
+        Err(err) => {
+            log!(
+                P0,
+                "[consolidate_utxos]: failed to send a Bitcoin transaction: {}",
+                err
+            );
+        }
+    }
+}
+
 fn finalization_time_estimate(min_confirmations: u32, network: Network) -> Duration {
     Duration::from_nanos(
         min_confirmations as u64
@@ -667,8 +860,6 @@ async fn finalize_requests() {
     let key_name = state::read_state(|s| s.ecdsa_key_name.clone());
 
     for (old_txid, submitted_tx) in maybe_finalized_transactions {
-        let mut utxos: BTreeSet<_> = submitted_tx.used_utxos.iter().cloned().collect();
-
         let tx_fee_per_vbyte = match submitted_tx.fee_per_vbyte {
             Some(prev_fee) => {
                 // Ensure that the fee is at least min relay fee higher than the previous
@@ -678,18 +869,47 @@ async fn finalize_requests() {
             None => fee_per_vbyte,
         };
 
-        let outputs = submitted_tx
-            .requests
-            .iter()
-            .map(|req| (req.address.clone(), req.amount))
-            .collect();
+        let rebuilt_tx = if submitted_tx.is_consolidation() {
+            build_consolidation_transaction(
+                submitted_tx.used_utxos.clone(),
+                main_address.clone(),
+                tx_fee_per_vbyte,
+            )
+        } else {
+            let outputs = submitted_tx
+                .requests
+                .iter()
+                .map(|req| (req.address.clone(), req.amount))
+                .collect();
+
+            // The replacement transaction must spend the same inputs as the
+            // stuck transaction.
+            build_unsigned_transaction_from_inputs(
+                &submitted_tx.used_utxos,
+                outputs,
+                main_address.clone(),
+                tx_fee_per_vbyte,
+            )
+            .map(|(unsigned_tx, change_output)| {
+                (unsigned_tx, change_output, submitted_tx.used_utxos.clone())
+            })
+        };
 
-        let (unsigned_tx, change_output, used_utxos) = match build_unsigned_transaction(
-            &mut utxos,
-            outputs,
-            main_address.clone(),
-            tx_fee_per_vbyte,
-        ) {
+        let max_consolidation_fee = state::read_state(|s| s.utxo_consolidation_max_fee);
+        let (unsigned_tx, change_output, used_utxos) = match rebuilt_tx {
+            Ok((_, change_output, used_utxos))
+                if submitted_tx.is_consolidation()
+                    && consolidation_fee(&used_utxos, &change_output) > max_consolidation_fee =>
+            {
+                log!(
+                    P1,
+                    "[finalize_requests]: not resubmitting consolidation transaction {}, the fee {} is above the limit {}",
+                    &submitted_tx.txid,
+                    consolidation_fee(&used_utxos, &change_output),
+                    max_consolidation_fee
+                );
+                continue;
+            }
             Ok(tx) => tx,
             // If it's impossible to build a new transaction, the fees probably became too high.
             // Let's ignore this transaction and wait for fees to go down.
@@ -706,10 +926,6 @@ async fn finalize_requests() {
 
         let outpoint_account = state::read_state(|s| filter_output_accounts(s, &unsigned_tx));
 
-        assert!(
-            utxos.is_empty(),
-            "build_unsigned_transaction didn't use all inputs"
-        );
         assert_eq!(used_utxos.len(), submitted_tx.used_utxos.len());
 
         let new_txid = unsigned_tx.txid();
@@ -804,7 +1020,11 @@ fn filter_output_accounts(
         .collect()
 }
 
-/// The algorithm greedily selects the smallest UTXO(s) with a value that is at least the given `target` in a first step.
+/// The algorithm selects UTXO(s) with a value that is at least the given `target` in a first step, using the given
+/// `strategy`:
+/// * [state::UtxoSelectionStrategy::Greedy] greedily selects the smallest UTXO(s), see [greedy].
+/// * [state::UtxoSelectionStrategy::BranchAndBound] selects the UTXOs that waste the least at the given fee rate, see
+///   [least_waste_selection].
 ///
 /// If the minter manages more than [UTXOS_COUNT_THRESHOLD], it will then try to match the number of inputs with the
 /// number of outputs + 1 (where the additional output corresponds to the change output).
@@ -818,8 +1038,15 @@ fn utxos_selection(
     target: u64,
     available_utxos: &mut BTreeSet<Utxo>,
     output_count: usize,
+    fee_per_vbyte: u64,
+    strategy: state::UtxoSelectionStrategy,
 ) -> Vec<Utxo> {
-    let mut input_utxos = greedy(target, available_utxos);
+    let mut input_utxos = match strategy {
+        state::UtxoSelectionStrategy::Greedy => greedy(target, available_utxos),
+        state::UtxoSelectionStrategy::BranchAndBound => {
+            least_waste_selection(target, available_utxos, fee_per_vbyte)
+        }
+    };
 
     if input_utxos.is_empty() {
         return vec![];
@@ -877,6 +1104,190 @@ fn greedy(target: u64, available_utxos: &mut BTreeSet<Utxo>) -> Vec<Utxo> {
     solution
 }
 
+/// Returns the fee (in satoshi) for spending a transaction input at the given
+/// fee rate (in millisatoshi per vbyte).
+fn input_fee(fee_per_vbyte: u64) -> Satoshi {
+    (tx_vsize_estimate(1, 0) - tx_vsize_estimate(0, 0)) * fee_per_vbyte / 1000
+}
+
+/// Returns the cost (in satoshi) of creating a change output at the given fee
+/// rate and spending it later at [LONG_TERM_FEE_PER_VBYTE].
+fn cost_of_change(fee_per_vbyte: u64) -> Satoshi {
+    (tx_vsize_estimate(0, 1) - tx_vsize_estimate(0, 0)) * fee_per_vbyte / 1000
+        + input_fee(LONG_TERM_FEE_PER_VBYTE)
+}
+
+/// Computes the waste (in satoshi) of spending the given UTXOs at the given
+/// fee rate, following the waste metric of Bitcoin Core:
+///
+/// ```text
+/// waste = inputs * (input_fee(fee_per_vbyte) - input_fee(LONG_TERM_FEE_PER_VBYTE))
+///       + cost_of_change(fee_per_vbyte)
+/// ```
+///
+/// Spending inputs when the fees are above the long-term fee rate is wasteful,
+/// spending them when the fees are below it saves fees in the future.
+///
+/// Unlike Bitcoin Core, the minter always creates a change output because
+/// that output also collects the minter's fee (see [build_unsigned_transaction]).
+/// Hence every selection pays the cost of change, and the excess over the
+/// target is not wasted: it returns to the minter with the change.
+fn selection_waste(utxos: &[Utxo], fee_per_vbyte: u64) -> i64 {
+    let input_waste = input_fee(fee_per_vbyte) as i64 - input_fee(LONG_TERM_FEE_PER_VBYTE) as i64;
+    utxos.len() as i64 * input_waste + cost_of_change(fee_per_vbyte) as i64
+}
+
+/// Returns the value (in satoshi) by which the given UTXOs exceed the `target`.
+/// The excess stays locked in the minter's change output until the
+/// transaction is finalized.
+fn selection_excess(utxos: &[Utxo], target: u64) -> u64 {
+    utxos
+        .iter()
+        .map(|u| u.value)
+        .sum::<u64>()
+        .saturating_sub(target)
+}
+
+/// Selects the UTXOs with the least waste (see [selection_waste]) among the
+/// [branch_and_bound] and the [greedy] selections and removes the selected
+/// UTXOs from the available set. If both selections waste the same, prefers
+/// the one with the smaller excess (see [selection_excess]).
+///
+/// If there are no UTXOs matching the criteria, returns an empty vector.
+///
+/// PROPERTY: sum(u.value for u in available_set) ≥ target ⇒ !solution.is_empty()
+/// POSTCONDITION: !solution.is_empty() ⇒ sum(u.value for u in solution) ≥ target
+/// POSTCONDITION:  solution.is_empty() ⇒ available_utxos did not change.
+fn least_waste_selection(
+    target: u64,
+    available_utxos: &mut BTreeSet<Utxo>,
+    fee_per_vbyte: u64,
+) -> Vec<Utxo> {
+    let bnb_solution = branch_and_bound(target, available_utxos, fee_per_vbyte);
+    let greedy_solution = greedy(target, available_utxos);
+
+    match bnb_solution {
+        Some(bnb_solution)
+            if (
+                selection_waste(&bnb_solution, fee_per_vbyte),
+                selection_excess(&bnb_solution, target),
+            ) <= (
+                selection_waste(&greedy_solution, fee_per_vbyte),
+                selection_excess(&greedy_solution, target),
+            ) =>
+        {
+            for utxo in greedy_solution {
+                available_utxos.insert(utxo);
+            }
+            for utxo in bnb_solution.iter() {
+                assert!(available_utxos.remove(utxo));
+            }
+            bnb_solution
+        }
+        _ => greedy_solution,
+    }
+}
+
+/// Searches for a subset of UTXOs whose total value is at least the given
+/// `target` but exceeds it by at most [cost_of_change], i.e., a selection that
+/// keeps the minter's change output small. Among such subsets, returns the one
+/// with the least waste (see [selection_waste]), breaking ties by the smallest
+/// excess (see [selection_excess]).
+///
+/// This is the branch-and-bound algorithm used by Bitcoin Core: the search
+/// explores a binary tree of inclusion/exclusion decisions over the UTXOs
+/// sorted by decreasing value, prunes the branches that cannot lead to a
+/// better solution, and gives up after [BNB_MAX_TRIES] steps.
+///
+/// Returns `None` if no such subset was found. Does not modify the
+/// available UTXOs.
+///
+/// POSTCONDITION: solution = Some(s) ⇒ target ≤ sum(u.value for u in s) ≤ target + cost_of_change
+fn branch_and_bound(
+    target: u64,
+    available_utxos: &BTreeSet<Utxo>,
+    fee_per_vbyte: u64,
+) -> Option<Vec<Utxo>> {
+    let mut utxos: Vec<&Utxo> = available_utxos.iter().collect();
+    utxos.sort_by(|a, b| b.value.cmp(&a.value));
+
+    let max_value = target.saturating_add(cost_of_change(fee_per_vbyte));
+    let input_waste = input_fee(fee_per_vbyte) as i64 - input_fee(LONG_TERM_FEE_PER_VBYTE) as i64;
+    // If spending inputs is wasteful, adding more inputs to a selection can
+    // only increase its waste.
+    let is_fee_rate_high = input_waste > 0;
+
+    // The total value of the UTXOs that were not considered yet.
+    let mut curr_available: u64 = utxos.iter().map(|u| u.value).sum();
+    let mut curr_value: u64 = 0;
+    let mut curr_waste: i64 = 0;
+    // Indices of the included UTXOs in increasing order.
+    let mut curr_selection: Vec<usize> = vec![];
+    let mut best_selection: Option<Vec<usize>> = None;
+    let mut best_waste = i64::MAX;
+    let mut best_excess = u64::MAX;
+
+    // The index of the next UTXO to consider.
+    let mut index = 0;
+    for _ in 0..BNB_MAX_TRIES {
+        let backtrack = if curr_value + curr_available < target
+            || curr_value > max_value
+            || (is_fee_rate_high && curr_waste > best_waste)
+        {
+            true
+        } else if curr_value >= target {
+            // All selections pay the same cost of change, so we compare only
+            // the input waste.
+            let excess = curr_value - target;
+            if (curr_waste, excess) <= (best_waste, best_excess) {
+                best_waste = curr_waste;
+                best_excess = excess;
+                best_selection = Some(curr_selection.clone());
+            }
+            true
+        } else {
+            false
+        };
+
+        if backtrack {
+            let last_included = match curr_selection.last() {
+                Some(last_included) => *last_included,
+                // The whole tree has been explored.
+                None => break,
+            };
+            // Reconsider the UTXOs skipped after the last included UTXO and
+            // explore the branch excluding the last included UTXO.
+            index -= 1;
+            while index > last_included {
+                curr_available += utxos[index].value;
+                index -= 1;
+            }
+            curr_value -= utxos[index].value;
+            curr_waste -= input_waste;
+            curr_selection.pop();
+        } else {
+            let utxo = utxos[index];
+            curr_available -= utxo.value;
+            // Skip the inclusion branch if the previous UTXO has the same value
+            // and was excluded: that branch was already explored.
+            let include = match curr_selection.last() {
+                None => true,
+                Some(last_included) => {
+                    *last_included == index - 1 || utxo.value != utxos[index - 1].value
+                }
+            };
+            if include {
+                curr_selection.push(index);
+                curr_value += utxo.value;
+                curr_waste += input_waste;
+            }
+        }
+        index += 1;
+    }
+
+    best_selection.map(|selection| selection.into_iter().map(|i| utxos[i].clone()).collect())
+}
+
 /// Gathers ECDSA signatures for all the inputs in the specified unsigned
 /// transaction.
 ///
@@ -973,6 +1384,7 @@ pub enum BuildTxError {
 /// * `outputs` - The destination BTC addresses and respective amounts.
 /// * `main_address` - The BTC address of the minter's main account do absorb the change.
 /// * `fee_per_vbyte` - The current 50th percentile of BTC fees, in millisatoshi/byte
+/// * `strategy` - The strategy for selecting the transaction inputs
 ///
 /// # Panics
 ///
@@ -1013,18 +1425,13 @@ pub fn build_unsigned_transaction(
     outputs: Vec<(BitcoinAddress, Satoshi)>,
     main_address: BitcoinAddress,
     fee_per_vbyte: u64,
+    strategy: state::UtxoSelectionStrategy,
 ) -> Result<(tx::UnsignedTransaction, state::ChangeOutput, Vec<Utxo>), BuildTxError> {
     assert!(!outputs.is_empty());
 
-    /// Having a sequence number lower than (0xffffffff - 1) signals the use of replacement by fee.
-    /// It allows us to increase the fee of a transaction already sent to the mempool.
-    /// The rbf option is used in `resubmit_retrieve_btc`.
-    /// https://github.com/bitcoin/bips/blob/master/bip-0125.mediawiki
-    const SEQUENCE_RBF_ENABLED: u32 = 0xfffffffd;
-
     let amount = outputs.iter().map(|(_, amount)| amount).sum::<u64>();
 
-    let input_utxos = utxos_selection(amount, minter_utxos, outputs.len());
+    let input_utxos = utxos_selection(amount, minter_utxos, outputs.len(), fee_per_vbyte, strategy);
 
     if input_utxos.is_empty() {
         return Err(BuildTxError::NotEnoughFunds);
@@ -1038,11 +1445,34 @@ pub fn build_unsigned_transaction(
         }
     });
 
-    let inputs_value = utxos_guard.iter().map(|u| u.value).sum::<u64>();
+    let (unsigned_tx, change_output) =
+        build_unsigned_transaction_from_inputs(&utxos_guard, outputs, main_address, fee_per_vbyte)?;
+
+    Ok((
+        unsigned_tx,
+        change_output,
+        ScopeGuard::into_inner(utxos_guard),
+    ))
+}
+
+/// Builds a transaction that moves BTC to the specified destination accounts
+/// using exactly the given minter UTXOs as inputs. See
+/// [build_unsigned_transaction] for the properties of the transaction.
+fn build_unsigned_transaction_from_inputs(
+    input_utxos: &[Utxo],
+    outputs: Vec<(BitcoinAddress, Satoshi)>,
+    main_address: BitcoinAddress,
+    fee_per_vbyte: u64,
+) -> Result<(tx::UnsignedTransaction, state::ChangeOutput), BuildTxError> {
+    let amount = outputs.iter().map(|(_, amount)| amount).sum::<u64>();
+
+    let inputs_value = input_utxos.iter().map(|u| u.value).sum::<u64>();
 
-    debug_assert!(inputs_value >= amount);
+    if inputs_value < amount {
+        return Err(BuildTxError::NotEnoughFunds);
+    }
 
-    let minter_fee = evaluate_minter_fee(utxos_guard.len() as u64, (outputs.len() + 1) as u64);
+    let minter_fee = evaluate_minter_fee(input_utxos.len() as u64, (outputs.len() + 1) as u64);
 
     let change = inputs_value - amount;
     let change_output = state::ChangeOutput {
@@ -1068,7 +1498,7 @@ pub fn build_unsigned_transaction(
     );
 
     let mut unsigned_tx = tx::UnsignedTransaction {
-        inputs: utxos_guard
+        inputs: input_utxos
             .iter()
             .map(|utxo| tx::UnsignedInput {
                 previous_output: utxo.outpoint.clone(),
@@ -1111,11 +1541,115 @@ pub fn build_unsigned_transaction(
         fee + unsigned_tx.outputs.iter().map(|u| u.value).sum::<u64>()
     );
 
-    Ok((
-        unsigned_tx,
-        change_output,
-        ScopeGuard::into_inner(utxos_guard),
-    ))
+    Ok((unsigned_tx, change_output))
+}
+
+/// Selects the smallest UTXOs that are worth consolidating at the given fee
+/// rate and removes the selected UTXOs from the available set. A UTXO is worth
+/// consolidating if its value exceeds the fee of spending it.
+///
+/// Returns an empty vector if there are fewer than two UTXOs matching the
+/// criteria because consolidating a single UTXO does not reduce the number of
+/// UTXOs.
+///
+/// POSTCONDITION: solution.len() <= max_count
+/// POSTCONDITION: solution.is_empty() ⇒ available_utxos did not change.
+fn utxos_to_consolidate(
+    available_utxos: &mut BTreeSet<Utxo>,
+    max_count: usize,
+    fee_per_vbyte: u64,
+) -> Vec<Utxo> {
+    let input_fee = input_fee(fee_per_vbyte);
+
+    let mut candidates: Vec<_> = available_utxos
+        .iter()
+        .filter(|u| u.value > input_fee)
+        .cloned()
+        .collect();
+    candidates.sort_by_key(|u| u.value);
+    candidates.truncate(max_count);
+
+    if candidates.len() < 2 {
+        return vec![];
+    }
+
+    for utxo in candidates.iter() {
+        assert!(available_utxos.remove(utxo));
+    }
+    candidates
+}
+
+/// Builds a transaction that consolidates the specified minter UTXOs into a
+/// single output to the minter's main address. The minter pays the fee.
+///
+/// # Arguments
+///
+/// * `utxos` - The minter UTXOs to consolidate.
+/// * `main_address` - The BTC address of the minter's main account.
+/// * `fee_per_vbyte` - The fee rate of the transaction, in millisatoshi/byte.
+///
+/// # Success case properties
+///
+/// * The transaction spends all the given UTXOs and has a single output.
+/// ```text
+/// value(tx.outputs[0]) == sum([u.value | u ∈ utxos]) - fee(tx)
+/// ```
+///
+/// # Error case properties
+///
+/// * The function returns `AmountTooLow` if the fee leaves less than the dust
+///   limit for the output.
+pub fn build_consolidation_transaction(
+    utxos: Vec<Utxo>,
+    main_address: BitcoinAddress,
+    fee_per_vbyte: u64,
+) -> Result<(tx::UnsignedTransaction, state::ChangeOutput, Vec<Utxo>), BuildTxError> {
+    if utxos.is_empty() {
+        return Err(BuildTxError::NotEnoughFunds);
+    }
+
+    let inputs_value = utxos.iter().map(|u| u.value).sum::<u64>();
+
+    let mut unsigned_tx = tx::UnsignedTransaction {
+        inputs: utxos
+            .iter()
+            .map(|utxo| tx::UnsignedInput {
+                previous_output: utxo.outpoint.clone(),
+                value: utxo.value,
+                sequence: SEQUENCE_RBF_ENABLED,
+            })
+            .collect(),
+        outputs: vec![tx::TxOut {
+            address: main_address,
+            value: inputs_value,
+        }],
+        lock_time: 0,
+    };
+
+    let tx_vsize = fake_sign(&unsigned_tx).vsize();
+    let fee = (tx_vsize as u64 * fee_per_vbyte) / 1000;
+
+    if inputs_value < fee + MINTER_ADDRESS_DUST_LIMIT {
+        return Err(BuildTxError::AmountTooLow);
+    }
+
+    unsigned_tx.outputs[0].value = inputs_value - fee;
+    let change_output = state::ChangeOutput {
+        vout: 0,
+        value: unsigned_tx.outputs[0].value,
+    };
+
+    Ok((unsigned_tx, change_output, utxos))
+}
+
+/// Returns the fee (in satoshi) that a consolidation transaction spending
+/// `utxos` into `change_output` pays to the Bitcoin network.
+pub fn consolidation_fee(utxos: &[Utxo], change_output: &state::ChangeOutput) -> u64 {
+    utxos
+        .iter()
+        .map(|u| u.value)
+        .sum::<u64>()
+        .saturating_sub(change_output.value)
 }
 
 pub fn evaluate_minter_fee(num_inputs: u64, num_outputs: u64) -> Satoshi {
@@ -1176,10 +1710,12 @@ pub fn tx_vsize_estimate(input_count: u64, output_count: u64) -> u64 {
 ///   * `available_utxos` - the list of UTXOs available to the minter.
 ///   * `maybe_amount` - the withdrawal amount.
 ///   * `median_fee_millisatoshi_per_vbyte` - the median network fee, in millisatoshi per vbyte.
+///   * `strategy` - the UTXO selection strategy of the minter.
 pub fn estimate_retrieve_btc_fee(
     available_utxos: &BTreeSet<Utxo>,
     maybe_amount: Option<u64>,
     median_fee_millisatoshi_per_vbyte: u64,
+    strategy: state::UtxoSelectionStrategy,
 ) -> WithdrawalFee {
     const DEFAULT_INPUT_COUNT: u64 = 2;
     // One output for the caller and one for the change.
@@ -1191,8 +1727,13 @@ pub fn estimate_retrieve_btc_fee(
             // should get the exact number of inputs that the minter
             // will use.
             let mut utxos = available_utxos.clone();
-            let selected_utxos =
-                utxos_selection(amount, &mut utxos, DEFAULT_OUTPUT_COUNT as usize - 1);
+            let selected_utxos = utxos_selection(
+                amount,
+                &mut utxos,
+                DEFAULT_OUTPUT_COUNT as usize - 1,
+                median_fee_millisatoshi_per_vbyte,
+                strategy,
+            );
 
             if !selected_utxos.is_empty() {
                 selected_utxos.len() as u64
@@ -1,6 +1,6 @@
 use crate::lifecycle::upgrade::UpgradeArgs;
-pub use crate::state::Mode;
 use crate::state::{replace_state, CkBtcMinterState};
+pub use crate::state::{Mode, UtxoSelectionStrategy};
 use crate::Network;
 use candid::{CandidType, Deserialize};
 use ic_base_types::CanisterId;
@@ -8,6 +8,8 @@ use serde::Serialize;
 
 pub const DEFAULT_MIN_CONFIRMATIONS: u32 = 6;
 pub const DEFAULT_CHECK_FEE: u64 = 1000;
+pub const DEFAULT_UTXO_CONSOLIDATION_MAX_FEE_PER_VBYTE: u64 = 5_000;
+pub const DEFAULT_UTXO_CONSOLIDATION_MAX_FEE: u64 = 100_000;
 
 #[derive(CandidType, serde::Deserialize)]
 pub enum MinterArg {
@@ -68,6 +70,27 @@ pub struct InitArgs {
     /// the get_utxos cache.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub get_utxos_cache_expiration_seconds: Option<u64>,
+
+    /// The number of available UTXOs above which the minter consolidates its
+    /// smallest UTXOs into a single output. Consolidation is disabled if this
+    /// field is not set or zero.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utxo_consolidation_threshold: Option<u64>,
+
+    /// The maximum median fee (in millisatoshi per vbyte) at which the minter
+    /// consolidates UTXOs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utxo_consolidation_max_fee_per_vbyte: Option<u64>,
+
+    /// The maximum total fee (in satoshi) the minter pays for a single
+    /// consolidation transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utxo_consolidation_max_fee: Option<u64>,
+
+    /// The strategy for selecting UTXOs in retrieve_btc transactions.
+    /// NOTE: this field is optional for backward compatibility.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utxo_selection_strategy: Option<UtxoSelectionStrategy>,
 }
 
 pub fn init(args: InitArgs) {
@@ -1,7 +1,7 @@
 use crate::logs::P0;
 use crate::state::eventlog::{replay, EventType};
 use crate::state::invariants::CheckInvariantsImpl;
-use crate::state::{replace_state, Mode};
+use crate::state::{replace_state, Mode, UtxoSelectionStrategy};
 use crate::storage::{count_events, events, migrate_old_events_if_not_empty, record_event};
 use crate::IC_CANISTER_RUNTIME;
 use candid::{CandidType, Deserialize};
@@ -49,6 +49,26 @@ pub struct UpgradeArgs {
     /// the get_utxos cache.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub get_utxos_cache_expiration_seconds: Option<u64>,
+
+    /// The number of available UTXOs above which the minter consolidates its
+    /// smallest UTXOs into a single output. Setting this field to zero
+    /// disables consolidation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utxo_consolidation_threshold: Option<u64>,
+
+    /// The maximum median fee (in millisatoshi per vbyte) at which the minter
+    /// consolidates UTXOs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utxo_consolidation_max_fee_per_vbyte: Option<u64>,
+
+    /// The maximum total fee (in satoshi) the minter pays for a single
+    /// consolidation transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utxo_consolidation_max_fee: Option<u64>,
+
+    /// The strategy for selecting UTXOs in retrieve_btc transactions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utxo_selection_strategy: Option<UtxoSelectionStrategy>,
 }
 
 pub fn post_upgrade(upgrade_args: Option<UpgradeArgs>) {
@@ -261,6 +261,19 @@ pub fn encode_metrics(
         "Total number of finalized retrieve_btc requests.",
     )?;
 
+    metrics.encode_counter(
+        "ckbtc_minter_utxo_consolidations",
+        state::read_state(|s| s.utxo_consolidations_count) as f64,
+        "Total number of confirmed UTXO consolidation transactions.",
+    )?;
+
+    metrics.encode_counter(
+        "ckbtc_minter_utxo_consolidation_fees",
+        state::read_state(|s| s.utxo_consolidation_fees_paid) as f64,
+        "Total fees (in satoshi) the minter paid for confirmed UTXO consolidation transactions. \
+         These fees are paid from the minter's BTC without burning ckBTC.",
+    )?;
+
     metrics.encode_counter(
         "ckbtc_minter_minted_tokens",
         state::read_state(|s| s.tokens_minted) as f64,
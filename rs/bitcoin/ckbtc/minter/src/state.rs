@@ -79,6 +79,7 @@ pub struct ChangeOutput {
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub struct SubmittedBtcTransaction {
     /// The original retrieve_btc requests that initiated the transaction.
+    /// Empty for UTXO consolidation transactions.
     pub requests: Vec<RetrieveBtcRequest>,
     /// The identifier of the unconfirmed transaction.
     pub txid: Txid,
@@ -94,6 +95,14 @@ pub struct SubmittedBtcTransaction {
     pub fee_per_vbyte: Option<u64>,
 }
 
+impl SubmittedBtcTransaction {
+    /// Returns true if the minter sent this transaction to consolidate its
+    /// own UTXOs rather than to serve retrieve_btc requests.
+    pub fn is_consolidation(&self) -> bool {
+        self.requests.is_empty()
+    }
+}
+
 /// Pairs a retrieve_btc request with its outcome.
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub struct FinalizedBtcRetrieval {
@@ -200,6 +209,22 @@ pub enum Mode {
     GeneralAvailability,
 }
 
+/// Controls how the minter selects the UTXOs it spends in retrieve_btc
+/// transactions.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, candid::CandidType, serde::Deserialize,
+)]
+pub enum UtxoSelectionStrategy {
+    /// Select the largest UTXOs first until the target amount is reached.
+    #[default]
+    Greedy,
+    /// Search for a selection that spends the inputs with the least waste
+    /// in fees and closely matches the target amount, keeping the change
+    /// output small, and fall back to the greedy selection if the greedy
+    /// selection wastes less in fees.
+    BranchAndBound,
+}
+
 impl Mode {
     /// Returns Ok if the specified principal can convert BTC to ckBTC.
     pub fn is_deposit_available_for(&self, p: &Principal) -> Result<(), String> {
@@ -394,6 +419,29 @@ pub struct CkBtcMinterState {
 
     /// Cache of get_utxos call results
     pub get_utxos_cache: GetUtxosCache,
+
+    /// The number of available UTXOs above which the minter consolidates
+    /// UTXOs, or None if consolidation is disabled (never zero).
+    pub utxo_consolidation_threshold: Option<u64>,
+
+    /// The maximum median fee (in millisatoshi per vbyte) at which the minter
+    /// consolidates UTXOs.
+    pub utxo_consolidation_max_fee_per_vbyte: u64,
+
+    /// The maximum total fee (in satoshi) the minter pays for a single
+    /// consolidation transaction.
+    pub utxo_consolidation_max_fee: u64,
+
+    /// The total number of confirmed consolidation transactions.
+    pub utxo_consolidations_count: u64,
+
+    /// The total fees (in satoshi) paid by confirmed consolidation transactions.
+    /// These fees are paid from the BTC held by the minter without burning any
+    /// ckBTC, so they reduce the BTC backing of the ckBTC supply by this amount.
+    pub utxo_consolidation_fees_paid: u64,
+
+    /// The strategy for selecting UTXOs in retrieve_btc transactions.
+    pub utxo_selection_strategy: UtxoSelectionStrategy,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, CandidType, Serialize, serde::Deserialize)]
@@ -437,6 +485,10 @@ impl CkBtcMinterState {
             kyt_principal: _,
             kyt_fee,
             get_utxos_cache_expiration_seconds,
+            utxo_consolidation_threshold,
+            utxo_consolidation_max_fee_per_vbyte,
+            utxo_consolidation_max_fee,
+            utxo_selection_strategy,
         }: InitArgs,
     ) {
         self.btc_network = btc_network;
@@ -459,6 +511,15 @@ impl CkBtcMinterState {
             self.get_utxos_cache
                 .set_expiration(Duration::from_secs(expiration));
         }
+        self.utxo_consolidation_threshold =
+            utxo_consolidation_threshold.filter(|threshold| *threshold > 0);
+        if let Some(max_fee) = utxo_consolidation_max_fee_per_vbyte {
+            self.utxo_consolidation_max_fee_per_vbyte = max_fee;
+        }
+        if let Some(max_fee) = utxo_consolidation_max_fee {
+            self.utxo_consolidation_max_fee = max_fee;
+        }
+        self.utxo_selection_strategy = utxo_selection_strategy.unwrap_or_default();
     }
 
     #[allow(deprecated)]
@@ -474,6 +535,10 @@ impl CkBtcMinterState {
             kyt_principal: _,
             kyt_fee,
             get_utxos_cache_expiration_seconds,
+            utxo_consolidation_threshold,
+            utxo_consolidation_max_fee_per_vbyte,
+            utxo_consolidation_max_fee,
+            utxo_selection_strategy,
         }: UpgradeArgs,
     ) {
         if let Some(retrieve_btc_min_amount) = retrieve_btc_min_amount {
@@ -510,6 +575,18 @@ impl CkBtcMinterState {
             self.get_utxos_cache
                 .set_expiration(Duration::from_secs(expiration));
         }
+        if let Some(threshold) = utxo_consolidation_threshold {
+            self.utxo_consolidation_threshold = (threshold > 0).then_some(threshold);
+        }
+        if let Some(max_fee) = utxo_consolidation_max_fee_per_vbyte {
+            self.utxo_consolidation_max_fee_per_vbyte = max_fee;
+        }
+        if let Some(max_fee) = utxo_consolidation_max_fee {
+            self.utxo_consolidation_max_fee = max_fee;
+        }
+        if let Some(strategy) = utxo_selection_strategy {
+            self.utxo_selection_strategy = strategy;
+        }
     }
 
     pub fn validate_config(&self) {
@@ -736,6 +813,13 @@ impl CkBtcMinterState {
             ));
         };
 
+        if finalized_tx.is_consolidation() {
+            if let Some(change_output) = &finalized_tx.change_output {
+                self.utxo_consolidations_count += 1;
+                self.utxo_consolidation_fees_paid +=
+                    crate::consolidation_fee(&finalized_tx.used_utxos, change_output);
+            }
+        }
         for utxo in finalized_tx.used_utxos.iter() {
             self.forget_utxo(utxo);
         }
@@ -914,6 +998,26 @@ impl CkBtcMinterState {
         self.submitted_transactions.push(tx);
     }
 
+    /// Returns true if the minter sent a UTXO consolidation transaction that
+    /// is not finalized yet.
+    pub fn has_pending_consolidation(&self) -> bool {
+        self.submitted_transactions
+            .iter()
+            .any(|tx| tx.is_consolidation())
+    }
+
+    /// Returns true if the minter should try to consolidate its UTXOs, i.e.,
+    /// if consolidation is enabled, the number of available UTXOs exceeds
+    /// the threshold, and there is no pending consolidation transaction.
+    pub fn should_consolidate_utxos(&self) -> bool {
+        match self.utxo_consolidation_threshold {
+            Some(threshold) => {
+                self.available_utxos.len() as u64 > threshold && !self.has_pending_consolidation()
+            }
+            None => false,
+        }
+    }
+
     /// Marks the specified retrieve_btc request as finalized.
     ///
     /// # Panics
@@ -1228,6 +1332,42 @@ impl CkBtcMinterState {
             "btc_checker_principal does not match"
         );
 
+        ensure_eq!(
+            self.utxo_consolidation_threshold,
+            other.utxo_consolidation_threshold,
+            "utxo_consolidation_threshold does not match"
+        );
+
+        ensure_eq!(
+            self.utxo_consolidation_max_fee_per_vbyte,
+            other.utxo_consolidation_max_fee_per_vbyte,
+            "utxo_consolidation_max_fee_per_vbyte does not match"
+        );
+
+        ensure_eq!(
+            self.utxo_consolidation_max_fee,
+            other.utxo_consolidation_max_fee,
+            "utxo_consolidation_max_fee does not match"
+        );
+
+        ensure_eq!(
+            self.utxo_consolidations_count,
+            other.utxo_consolidations_count,
+            "utxo_consolidations_count does not match"
+        );
+
+        ensure_eq!(
+            self.utxo_consolidation_fees_paid,
+            other.utxo_consolidation_fees_paid,
+            "utxo_consolidation_fees_paid does not match"
+        );
+
+        ensure_eq!(
+            self.utxo_selection_strategy,
+            other.utxo_selection_strategy,
+            "utxo_selection_strategy does not match"
+        );
+
         ensure_eq!(
             self.retrieve_btc_account_to_block_indices,
             other.retrieve_btc_account_to_block_indices,
@@ -1517,6 +1657,16 @@ impl From<InitArgs> for CkBtcMinterState {
             get_utxos_cache: GetUtxosCache::new(Duration::from_secs(
                 args.get_utxos_cache_expiration_seconds.unwrap_or_default(),
             )),
+            utxo_consolidation_threshold: args.utxo_consolidation_threshold,
+            utxo_consolidation_max_fee_per_vbyte: args
+                .utxo_consolidation_max_fee_per_vbyte
+                .unwrap_or(crate::lifecycle::init::DEFAULT_UTXO_CONSOLIDATION_MAX_FEE_PER_VBYTE),
+            utxo_consolidation_max_fee: args
+                .utxo_consolidation_max_fee
+                .unwrap_or(crate::lifecycle::init::DEFAULT_UTXO_CONSOLIDATION_MAX_FEE),
+            utxo_consolidations_count: 0,
+            utxo_consolidation_fees_paid: 0,
+            utxo_selection_strategy: args.utxo_selection_strategy.unwrap_or_default(),
         }
     }
 }
@@ -90,6 +90,33 @@ pub fn sent_transaction<R: CanisterRuntime>(
     state.push_submitted_transaction(tx);
 }
 
+pub fn sent_consolidation_transaction<R: CanisterRuntime>(
+    state: &mut CkBtcMinterState,
+    tx: SubmittedBtcTransaction,
+    runtime: &R,
+) {
+    assert!(tx.is_consolidation());
+    let change_output = tx
+        .change_output
+        .clone()
+        .expect("bug: all consolidation transactions must have the change output");
+    record_event(
+        EventType::SentConsolidationTransaction {
+            txid: tx.txid,
+            utxos: tx.used_utxos.clone(),
+            total_fee: crate::consolidation_fee(&tx.used_utxos, &change_output),
+            change_output,
+            submitted_at: tx.submitted_at,
+            fee_per_vbyte: tx
+                .fee_per_vbyte
+                .expect("bug: all consolidation transactions must have the fee"),
+        },
+        runtime,
+    );
+
+    state.push_submitted_transaction(tx);
+}
+
 pub fn confirm_transaction<R: CanisterRuntime>(
     state: &mut CkBtcMinterState,
     txid: &Txid,
@@ -95,6 +95,31 @@ mod event {
             fee_per_vbyte: Option<u64>,
         },
 
+        /// Indicates that the minter sent out a new transaction that consolidates
+        /// some of its UTXOs into a single output.
+        #[serde(rename = "sent_consolidation_transaction")]
+        SentConsolidationTransaction {
+            /// The Txid of the Bitcoin transaction.
+            #[serde(rename = "txid")]
+            txid: Txid,
+            /// UTXOs consolidated by the transaction.
+            #[serde(rename = "utxos")]
+            utxos: Vec<Utxo>,
+            /// The output holding the consolidated value.
+            #[serde(rename = "change_output")]
+            change_output: ChangeOutput,
+            /// The IC time at which the minter submitted the transaction.
+            #[serde(rename = "submitted_at")]
+            submitted_at: u64,
+            /// The fee per vbyte (in millisatoshi) that we used for the transaction.
+            #[serde(rename = "fee")]
+            fee_per_vbyte: u64,
+            /// The total fee (in satoshi) that the transaction pays out of the
+            /// minter's reserves.
+            #[serde(rename = "total_fee")]
+            total_fee: u64,
+        },
+
         /// Indicates that the minter sent out a new transaction to replace an older transaction
         /// because the old transaction did not appear on the Bitcoin blockchain.
         #[serde(rename = "replaced_transaction")]
@@ -328,6 +353,37 @@ pub fn replay<I: CheckInvariants>(
                     submitted_at,
                 });
             }
+            EventType::SentConsolidationTransaction {
+                txid,
+                utxos,
+                change_output,
+                submitted_at,
+                fee_per_vbyte,
+                total_fee,
+            } => {
+                if total_fee != crate::consolidation_fee(&utxos, &change_output) {
+                    return Err(ReplayLogError::InconsistentLog(format!(
+                        "Consolidation transaction {} reports fee {} that does not match its inputs and outputs",
+                        txid, total_fee
+                    )));
+                }
+                for utxo in utxos.iter() {
+                    if !state.available_utxos.remove(utxo) {
+                        return Err(ReplayLogError::InconsistentLog(format!(
+                            "Attempted to consolidate an unavailable UTXO {:?}",
+                            utxo
+                        )));
+                    }
+                }
+                state.push_submitted_transaction(SubmittedBtcTransaction {
+                    requests: vec![],
+                    txid,
+                    used_utxos: utxos,
+                    fee_per_vbyte: Some(fee_per_vbyte),
+                    change_output: Some(change_output),
+                    submitted_at,
+                });
+            }
             EventType::ReplacedBtcTransaction {
                 old_txid,
                 new_txid,
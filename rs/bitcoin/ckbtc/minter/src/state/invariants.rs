@@ -77,6 +77,52 @@ impl CheckInvariants for CheckInvariantsImpl {
             );
         }
 
+        let mut pending_consolidations = 0;
+        for tx in state
+            .submitted_transactions
+            .iter()
+            .filter(|tx| tx.is_consolidation())
+        {
+            pending_consolidations += 1;
+
+            for utxo in tx.used_utxos.iter() {
+                ensure!(
+                    !state.available_utxos.contains(utxo),
+                    "utxo {:?} consolidated by transaction {} is still available",
+                    utxo,
+                    &tx.txid,
+                );
+            }
+
+            let inputs_value = tx.used_utxos.iter().map(|u| u.value).sum::<u64>();
+            match &tx.change_output {
+                Some(change_output) => {
+                    ensure_eq!(
+                        change_output.vout,
+                        0,
+                        "consolidation transaction {} has more than one output",
+                        &tx.txid,
+                    );
+                    ensure!(
+                        change_output.value <= inputs_value,
+                        "consolidation transaction {} outputs more than its inputs",
+                        &tx.txid,
+                    );
+                }
+                None => {
+                    return Err(format!(
+                        "consolidation transaction {} does not have the change output",
+                        &tx.txid
+                    ))
+                }
+            }
+        }
+        ensure!(
+            pending_consolidations <= 1,
+            "there are {} pending consolidation transactions",
+            pending_consolidations,
+        );
+
         ensure_eq!(
             state.replacement_txid.len(),
             state.rev_replacement_txid.len(),
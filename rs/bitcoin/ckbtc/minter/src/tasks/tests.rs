@@ -48,6 +48,16 @@ async fn should_reschedule_refresh_fees() {
     .await;
 }
 
+#[tokio::test]
+async fn should_reschedule_consolidate_utxos() {
+    test_reschedule(
+        TaskType::ConsolidateUtxos,
+        || crate::guard::TimerLogicGuard::new().unwrap(),
+        Duration::from_secs(60 * 60),
+    )
+    .await;
+}
+
 async fn test_reschedule<T, G: FnOnce() -> T>(
     task_type: TaskType,
     guard: G,
@@ -31,6 +31,10 @@ pub fn init_args() -> InitArgs {
         kyt_principal: None,
         kyt_fee: None,
         get_utxos_cache_expiration_seconds: None,
+        utxo_consolidation_threshold: None,
+        utxo_consolidation_max_fee_per_vbyte: None,
+        utxo_consolidation_max_fee: None,
+        utxo_selection_strategy: None,
     }
 }
 
@@ -148,6 +152,7 @@ pub mod arbitrary {
         state::{
             eventlog::{Event, EventType},
             ChangeOutput, Mode, ReimbursementReason, RetrieveBtcRequest, SuspendedReason,
+            UtxoSelectionStrategy,
         },
         tx,
         tx::{SignedInput, TxOut, UnsignedInput},
@@ -261,6 +266,13 @@ pub mod arbitrary {
         ]
     }
 
+    fn utxo_selection_strategy() -> impl Strategy<Value = UtxoSelectionStrategy> {
+        prop_oneof![
+            Just(UtxoSelectionStrategy::Greedy),
+            Just(UtxoSelectionStrategy::BranchAndBound),
+        ]
+    }
+
     fn encoded_signature() -> impl Strategy<Value = EncodedSignature> {
         pvec(1u8..0xff, 64).prop_map(|bytes| EncodedSignature::from_sec1(bytes.as_slice()))
     }
@@ -352,6 +364,10 @@ pub mod arbitrary {
                 btc_checker_principal: option::of(canister_id()),
                 kyt_principal: option::of(canister_id()),
                 get_utxos_cache_expiration_seconds: option::of(any::<u64>()),
+                utxo_consolidation_threshold: option::of(any::<u64>()),
+                utxo_consolidation_max_fee_per_vbyte: option::of(any::<u64>()),
+                utxo_consolidation_max_fee: option::of(any::<u64>()),
+                utxo_selection_strategy: option::of(utxo_selection_strategy()),
             })
         }
 
@@ -366,6 +382,10 @@ pub mod arbitrary {
                 btc_checker_principal: option::of(canister_id()),
                 kyt_principal: option::of(canister_id()),
                 get_utxos_cache_expiration_seconds: option::of(any::<u64>()),
+                utxo_consolidation_threshold: option::of(any::<u64>()),
+                utxo_consolidation_max_fee_per_vbyte: option::of(any::<u64>()),
+                utxo_consolidation_max_fee: option::of(any::<u64>()),
+                utxo_selection_strategy: option::of(utxo_selection_strategy()),
             })
         }
 
@@ -390,6 +410,14 @@ pub mod arbitrary {
                     submitted_at: any::<u64>(),
                     fee_per_vbyte: option::of(any::<u64>()),
                 }),
+                prop_struct!(EventType::SentConsolidationTransaction {
+                    txid: txid(),
+                    utxos: pvec(utxo(amount()), 0..10_000),
+                    change_output: change_output(),
+                    submitted_at: any::<u64>(),
+                    fee_per_vbyte: any::<u64>(),
+                    total_fee: any::<u64>(),
+                }),
                 prop_struct!(EventType::ReplacedBtcTransaction {
                     old_txid: txid(),
                     new_txid: txid(),
@@ -1,14 +1,18 @@
 use crate::{
     address::BitcoinAddress,
-    build_unsigned_transaction, estimate_retrieve_btc_fee, evaluate_minter_fee, fake_sign, greedy,
-    lifecycle::init::InitArgs,
+    branch_and_bound, build_consolidation_transaction, build_unsigned_transaction,
+    consolidation_fee, cost_of_change, estimate_retrieve_btc_fee, evaluate_minter_fee, fake_sign,
+    greedy, least_waste_selection,
+    lifecycle::{init::InitArgs, upgrade::UpgradeArgs},
+    state::eventlog::{replay, Event, EventType},
     state::invariants::CheckInvariantsImpl,
     state::{
         ChangeOutput, CkBtcMinterState, Mode, RetrieveBtcRequest, RetrieveBtcStatus,
-        SubmittedBtcTransaction,
+        SubmittedBtcTransaction, UtxoSelectionStrategy,
     },
     test_fixtures::arbitrary,
-    tx, BuildTxError, CacheWithExpiration, Network, MINTER_ADDRESS_DUST_LIMIT,
+    tx, utxos_to_consolidate, BuildTxError, CacheWithExpiration, Network,
+    MINTER_ADDRESS_DUST_LIMIT,
 };
 use bitcoin::network::constants::Network as BtcNetwork;
 use bitcoin::util::psbt::serialize::{Deserialize, Serialize};
@@ -43,6 +47,10 @@ fn default_init_args() -> InitArgs {
         kyt_principal: None,
         kyt_fee: None,
         get_utxos_cache_expiration_seconds: None,
+        utxo_consolidation_threshold: None,
+        utxo_consolidation_max_fee_per_vbyte: None,
+        utxo_consolidation_max_fee: None,
+        utxo_selection_strategy: None,
     }
 }
 
@@ -198,6 +206,76 @@ fn greedy_smoke_test() {
     assert_eq!(res[1].value, 6_u64);
 }
 
+#[test]
+fn least_waste_selection_should_minimize_excess_at_long_term_fees() {
+    let values = [10_000_000, 6_000_000, 5_000_000, 4_000_000];
+    let mut utxos: BTreeSet<Utxo> = values.into_iter().map(dummy_utxo_from_value).collect();
+
+    // At the long-term fee rate, inputs cost nothing extra, so both selections
+    // waste the same. Greedy selects the 10M UTXO, leaving 1M of excess in the
+    // change output, whereas 5M + 4M matches the target exactly.
+    let res = least_waste_selection(9_000_000, &mut utxos, crate::LONG_TERM_FEE_PER_VBYTE);
+
+    let mut selected: Vec<u64> = res.iter().map(|u| u.value).collect();
+    selected.sort();
+    assert_eq!(selected, vec![4_000_000, 5_000_000]);
+    assert_eq!(
+        utxos,
+        btreeset! {dummy_utxo_from_value(10_000_000), dummy_utxo_from_value(6_000_000)}
+    );
+}
+
+#[test]
+fn least_waste_selection_should_prefer_fewer_inputs_at_high_fees() {
+    let values = [10_000_000, 6_000_000, 5_000_000, 4_000_000];
+    let mut utxos: BTreeSet<Utxo> = values.into_iter().map(dummy_utxo_from_value).collect();
+
+    // At 10x the long-term fee rate, the cost of the second input exceeds the
+    // cost of the change output.
+    let res = least_waste_selection(9_000_000, &mut utxos, 10 * crate::LONG_TERM_FEE_PER_VBYTE);
+
+    assert_eq!(res, vec![dummy_utxo_from_value(10_000_000)]);
+    assert_eq!(utxos.len(), 3);
+}
+
+#[test]
+fn branch_and_bound_transaction_should_keep_the_minter_fee_in_change() {
+    let values = [10_000_000, 6_000_000, 5_000_000, 4_000_000];
+    let mut available_utxos: BTreeSet<Utxo> =
+        values.into_iter().map(dummy_utxo_from_value).collect();
+    let minter_addr = BitcoinAddress::P2wpkhV0([0; 20]);
+    let out_addr = BitcoinAddress::P2wpkhV0([1; 20]);
+
+    // The selection matches the amount exactly, but the transaction still
+    // needs the change output to collect the minter's fee.
+    let (tx, change_output, used_utxos) = build_unsigned_transaction(
+        &mut available_utxos,
+        vec![(out_addr, 9_000_000)],
+        minter_addr.clone(),
+        crate::LONG_TERM_FEE_PER_VBYTE,
+        UtxoSelectionStrategy::BranchAndBound,
+    )
+    .expect("failed to build a transaction");
+
+    assert_eq!(used_utxos.iter().map(|u| u.value).sum::<u64>(), 9_000_000);
+    let minter_fee = evaluate_minter_fee(tx.inputs.len() as u64, tx.outputs.len() as u64);
+    assert_eq!(tx.outputs.len(), 2);
+    assert_eq!(
+        tx.outputs[1],
+        tx::TxOut {
+            address: minter_addr,
+            value: minter_fee,
+        }
+    );
+    assert_eq!(
+        change_output,
+        ChangeOutput {
+            vout: 1,
+            value: minter_fee,
+        }
+    );
+}
+
 #[test]
 fn should_have_same_input_and_output_count() {
     let mut available_utxos = BTreeSet::new();
@@ -257,6 +335,7 @@ fn should_have_same_input_and_output_count() {
         vec![(out1_addr.clone(), 100_000), (out2_addr.clone(), 99_999)],
         minter_addr.clone(),
         fee_per_vbyte,
+        UtxoSelectionStrategy::Greedy,
     )
     .expect("failed to build a transaction");
 
@@ -304,6 +383,7 @@ fn test_min_change_amount() {
         ],
         minter_addr.clone(),
         fee_per_vbyte,
+        UtxoSelectionStrategy::Greedy,
     )
     .expect("failed to build a transaction");
     let change_value = 1;
@@ -374,6 +454,7 @@ fn test_no_dust_outputs() {
                 vec![(out1_addr.clone(), 99_000), (out2_addr.clone(), dust)],
                 minter_addr.clone(),
                 fee_per_vbyte,
+                UtxoSelectionStrategy::Greedy,
             ),
             Err(BuildTxError::DustOutput {
                 address: out2_addr.clone(),
@@ -389,6 +470,7 @@ fn test_no_dust_outputs() {
                 vec![(out1_addr.clone(), 99_000), (out2_addr.clone(), dust)],
                 minter_addr.clone(),
                 fee_per_vbyte,
+                UtxoSelectionStrategy::Greedy,
             ),
             Err(BuildTxError::DustOutput {
                 address: out2_addr.clone(),
@@ -421,6 +503,7 @@ fn test_no_dust_in_change_output() {
             vec![(out1_addr.clone(), utxo.value - change)],
             minter_addr.clone(),
             fee_per_vbyte,
+            UtxoSelectionStrategy::Greedy,
         )
         .expect("failed to build a transaction");
         let fee = evaluate_minter_fee(tx.inputs.len() as u64, tx.outputs.len() as u64);
@@ -442,6 +525,297 @@ fn test_no_dust_in_change_output() {
     }
 }
 
+#[test]
+fn should_consolidate_smallest_utxos_worth_spending() {
+    // Spending an input costs 68 vbytes * 10 sat/vbyte = 680 sat.
+    let fee_per_vbyte = 10_000;
+    let mut available_utxos: BTreeSet<Utxo> = [500, 1_000, 2_000, 3_000, 100_000]
+        .into_iter()
+        .map(dummy_utxo_from_value)
+        .collect();
+
+    let utxos = utxos_to_consolidate(&mut available_utxos, 3, fee_per_vbyte);
+
+    assert_eq!(
+        utxos.iter().map(|u| u.value).collect::<Vec<_>>(),
+        vec![1_000, 2_000, 3_000]
+    );
+    assert_eq!(
+        available_utxos
+            .iter()
+            .map(|u| u.value)
+            .collect::<BTreeSet<_>>(),
+        btreeset! {500, 100_000}
+    );
+}
+
+#[test]
+fn should_not_consolidate_a_single_utxo() {
+    let fee_per_vbyte = 10_000;
+    let mut available_utxos: BTreeSet<Utxo> = [100, 200, 100_000]
+        .into_iter()
+        .map(dummy_utxo_from_value)
+        .collect();
+    let before = available_utxos.clone();
+
+    assert_eq!(
+        utxos_to_consolidate(&mut available_utxos, 10, fee_per_vbyte),
+        vec![]
+    );
+    assert_eq!(available_utxos, before);
+}
+
+#[test]
+fn should_build_consolidation_transaction() {
+    let minter_addr = BitcoinAddress::P2wpkhV0([0; 20]);
+    let fee_per_vbyte = 2_000;
+    let utxos: Vec<Utxo> = [10_000, 20_000, 30_000]
+        .into_iter()
+        .map(dummy_utxo_from_value)
+        .collect();
+
+    let (tx, change_output, used_utxos) =
+        build_consolidation_transaction(utxos.clone(), minter_addr.clone(), fee_per_vbyte)
+            .expect("failed to build a consolidation transaction");
+
+    assert_eq!(used_utxos, utxos);
+    assert_eq!(
+        tx.inputs
+            .iter()
+            .map(|input| input.previous_output.clone())
+            .collect::<Vec<_>>(),
+        utxos.iter().map(|u| u.outpoint.clone()).collect::<Vec<_>>()
+    );
+    let fee = fake_sign(&tx).vsize() as u64 * fee_per_vbyte / 1000;
+    assert_eq!(
+        tx.outputs,
+        vec![tx::TxOut {
+            address: minter_addr,
+            value: 60_000 - fee,
+        }]
+    );
+    assert_eq!(
+        change_output,
+        ChangeOutput {
+            vout: 0,
+            value: 60_000 - fee,
+        }
+    );
+    assert_eq!(consolidation_fee(&used_utxos, &change_output), fee);
+}
+
+#[test]
+fn should_not_build_consolidation_transaction_with_dust_output() {
+    let minter_addr = BitcoinAddress::P2wpkhV0([0; 20]);
+    let utxos: Vec<Utxo> = [1_000, 1_000]
+        .into_iter()
+        .map(dummy_utxo_from_value)
+        .collect();
+
+    assert_eq!(
+        build_consolidation_transaction(utxos, minter_addr, 20_000),
+        Err(BuildTxError::AmountTooLow)
+    );
+}
+
+#[test]
+fn zero_utxo_consolidation_threshold_disables_consolidation() {
+    let state = CkBtcMinterState::from(InitArgs {
+        utxo_consolidation_threshold: Some(0),
+        ..default_init_args()
+    });
+    assert_eq!(state.utxo_consolidation_threshold, None);
+
+    let mut state = CkBtcMinterState::from(InitArgs {
+        utxo_consolidation_threshold: Some(3),
+        ..default_init_args()
+    });
+    assert_eq!(state.utxo_consolidation_threshold, Some(3));
+    state.upgrade(UpgradeArgs {
+        utxo_consolidation_threshold: Some(0),
+        ..Default::default()
+    });
+    assert_eq!(state.utxo_consolidation_threshold, None);
+}
+
+#[test]
+fn should_consolidate_utxos_conditions() {
+    let mut state = CkBtcMinterState::from(default_init_args());
+    let account = Account {
+        owner: Principal::anonymous(),
+        subaccount: None,
+    };
+    let utxos: Vec<Utxo> = (1..=5).map(|i| dummy_utxo_from_value(i * 10_000)).collect();
+    state.add_utxos::<CheckInvariantsImpl>(account, utxos.clone());
+
+    // Consolidation is disabled by default.
+    assert!(!state.should_consolidate_utxos());
+
+    state.utxo_consolidation_threshold = Some(5);
+    // Five UTXOs, not above the threshold.
+    assert!(!state.should_consolidate_utxos());
+
+    state.utxo_consolidation_threshold = Some(4);
+    assert!(state.should_consolidate_utxos());
+
+    for utxo in &utxos[0..2] {
+        state.available_utxos.remove(utxo);
+    }
+    state.push_submitted_transaction(SubmittedBtcTransaction {
+        requests: vec![],
+        txid: [1; 32].into(),
+        used_utxos: utxos[0..2].to_vec(),
+        submitted_at: 0,
+        change_output: Some(ChangeOutput {
+            vout: 0,
+            value: 29_000,
+        }),
+        fee_per_vbyte: Some(1_000),
+    });
+    assert!(state.has_pending_consolidation());
+
+    state.utxo_consolidation_threshold = Some(1);
+    // There is a pending consolidation transaction.
+    assert!(!state.should_consolidate_utxos());
+}
+
+#[test]
+fn should_replay_consolidation_transaction() {
+    let account = Account {
+        owner: Principal::anonymous(),
+        subaccount: None,
+    };
+    let utxos: Vec<Utxo> = (1..=3).map(|i| dummy_utxo_from_value(i * 10_000)).collect();
+    let txid = [1; 32].into();
+    let change_output = ChangeOutput {
+        vout: 0,
+        value: 29_000,
+    };
+    let events = vec![
+        EventType::Init(InitArgs {
+            utxo_consolidation_threshold: Some(2),
+            ..default_init_args()
+        }),
+        EventType::ReceivedUtxos {
+            mint_txid: None,
+            to_account: account,
+            utxos: utxos.clone(),
+        },
+        EventType::SentConsolidationTransaction {
+            txid,
+            utxos: utxos[0..2].to_vec(),
+            change_output: change_output.clone(),
+            submitted_at: 1,
+            fee_per_vbyte: 2_000,
+            total_fee: 1_000,
+        },
+    ];
+
+    let state = replay::<CheckInvariantsImpl>(events.into_iter().map(Event::from))
+        .expect("failed to replay events");
+
+    assert_eq!(state.utxo_consolidation_threshold, Some(2));
+    assert_eq!(
+        state.available_utxos,
+        utxos[2..].iter().cloned().collect::<BTreeSet<_>>()
+    );
+    assert_eq!(
+        state.submitted_transactions,
+        vec![SubmittedBtcTransaction {
+            requests: vec![],
+            txid,
+            used_utxos: utxos[0..2].to_vec(),
+            submitted_at: 1,
+            change_output: Some(change_output),
+            fee_per_vbyte: Some(2_000),
+        }]
+    );
+    assert_eq!(state.check_invariants(), Ok(()));
+
+    let events = vec![
+        EventType::Init(default_init_args()),
+        EventType::SentConsolidationTransaction {
+            txid,
+            utxos: utxos[0..2].to_vec(),
+            change_output: ChangeOutput {
+                vout: 0,
+                value: 29_000,
+            },
+            submitted_at: 1,
+            fee_per_vbyte: 2_000,
+            total_fee: 1_000,
+        },
+    ];
+    assert!(replay::<CheckInvariantsImpl>(events.into_iter().map(Event::from)).is_err());
+
+    let events = vec![
+        EventType::Init(default_init_args()),
+        EventType::ReceivedUtxos {
+            mint_txid: None,
+            to_account: account,
+            utxos: utxos.clone(),
+        },
+        EventType::SentConsolidationTransaction {
+            txid,
+            utxos: utxos[0..2].to_vec(),
+            change_output: ChangeOutput {
+                vout: 0,
+                value: 29_000,
+            },
+            submitted_at: 1,
+            fee_per_vbyte: 2_000,
+            total_fee: 2_000,
+        },
+    ];
+    // The recorded fee does not match the transaction inputs and outputs.
+    assert!(replay::<CheckInvariantsImpl>(events.into_iter().map(Event::from)).is_err());
+}
+
+#[test]
+fn should_record_fees_of_confirmed_consolidations() {
+    let account = Account {
+        owner: Principal::anonymous(),
+        subaccount: None,
+    };
+    let utxos: Vec<Utxo> = (1..=3).map(|i| dummy_utxo_from_value(i * 10_000)).collect();
+    let txid = [1; 32].into();
+    let events = vec![
+        EventType::Init(InitArgs {
+            utxo_consolidation_threshold: Some(2),
+            utxo_consolidation_max_fee: Some(5_000),
+            ..default_init_args()
+        }),
+        EventType::ReceivedUtxos {
+            mint_txid: None,
+            to_account: account,
+            utxos: utxos.clone(),
+        },
+        EventType::SentConsolidationTransaction {
+            txid,
+            utxos: utxos[0..2].to_vec(),
+            change_output: ChangeOutput {
+                vout: 0,
+                value: 29_000,
+            },
+            submitted_at: 1,
+            fee_per_vbyte: 2_000,
+            total_fee: 1_000,
+        },
+    ];
+
+    let mut state = replay::<CheckInvariantsImpl>(events.into_iter().map(Event::from))
+        .expect("failed to replay events");
+    assert_eq!(state.utxo_consolidation_max_fee, 5_000);
+    assert_eq!(state.utxo_consolidations_count, 0);
+    assert_eq!(state.utxo_consolidation_fees_paid, 0);
+
+    state.finalize_transaction(&txid);
+
+    assert_eq!(state.utxo_consolidations_count, 1);
+    assert_eq!(state.utxo_consolidation_fees_paid, 1_000);
+    assert_eq!(state.check_invariants(), Ok(()));
+}
+
 proptest! {
     #[test]
     fn greedy_solution_properties(
@@ -502,6 +876,87 @@ proptest! {
         prop_assert_eq!(utxos, original_utxos);
     }
 
+    #[test]
+    fn least_waste_selection_solution_properties(
+        values in pvec(1u64..1_000_000_000, 1..10),
+        target in 1u64..1_000_000_000,
+        fee_per_vbyte in 1_000u64..100_000,
+    ) {
+        let mut utxos: BTreeSet<Utxo> = values
+            .into_iter()
+            .map(dummy_utxo_from_value)
+            .collect();
+
+        let total = utxos.iter().map(|u| u.value).sum::<u64>();
+
+        if total < target {
+            utxos.insert(dummy_utxo_from_value(target - total));
+        }
+
+        let original_utxos = utxos.clone();
+
+        let solution = least_waste_selection(target, &mut utxos, fee_per_vbyte);
+
+        prop_assert!(
+            !solution.is_empty(),
+            "least_waste_selection() must always find a solution given enough available UTXOs"
+        );
+
+        prop_assert!(
+            solution.iter().map(|u| u.value).sum::<u64>() >= target,
+            "least_waste_selection() must reach the specified target amount"
+        );
+
+        prop_assert!(
+            solution.iter().all(|u| original_utxos.contains(u)),
+            "least_waste_selection() must select utxos from the available set"
+        );
+
+        prop_assert_eq!(
+            utxos.len() + solution.len(),
+            original_utxos.len(),
+            "least_waste_selection() must remove exactly the found UTXOs from the available set"
+        );
+    }
+
+    #[test]
+    fn least_waste_selection_does_not_modify_input_when_fails(
+        values in pvec(1u64..1_000_000_000, 1..10),
+        fee_per_vbyte in 1_000u64..100_000,
+    ) {
+        let mut utxos: BTreeSet<Utxo> = values
+            .into_iter()
+            .map(dummy_utxo_from_value)
+            .collect();
+
+        let total = utxos.iter().map(|u| u.value).sum::<u64>();
+
+        let original_utxos = utxos.clone();
+        let solution = least_waste_selection(total + 1, &mut utxos, fee_per_vbyte);
+
+        prop_assert!(solution.is_empty());
+        prop_assert_eq!(utxos, original_utxos);
+    }
+
+    #[test]
+    fn branch_and_bound_solution_has_small_excess(
+        values in pvec(1u64..1_000_000, 1..15),
+        target in 1u64..5_000_000,
+        fee_per_vbyte in 1_000u64..100_000,
+    ) {
+        let utxos: BTreeSet<Utxo> = values
+            .into_iter()
+            .map(dummy_utxo_from_value)
+            .collect();
+
+        if let Some(solution) = branch_and_bound(target, &utxos, fee_per_vbyte) {
+            let selected = solution.iter().map(|u| u.value).sum::<u64>();
+            prop_assert!(selected >= target);
+            prop_assert!(selected <= target + cost_of_change(fee_per_vbyte));
+            prop_assert!(solution.iter().all(|u| utxos.contains(u)));
+        }
+    }
+
     #[test]
     fn unsigned_tx_encoding_model(
         inputs in pvec(arbitrary::unsigned_input(5_000u64..1_000_000_000), 1..20),
@@ -603,14 +1058,14 @@ proptest! {
         let target = total_value / 2;
 
         let minter_address= BitcoinAddress::P2wpkhV0(main_pkhash);
-        let fee_estimate = estimate_retrieve_btc_fee(&utxos, Some(target), fee_per_vbyte);
+        let fee_estimate = estimate_retrieve_btc_fee(&utxos, Some(target), fee_per_vbyte, UtxoSelectionStrategy::Greedy);
         let fee_estimate = fee_estimate.minter_fee + fee_estimate.bitcoin_fee;
 
         let (unsigned_tx, _, _) = build_unsigned_transaction(
             &mut utxos,
             vec![(BitcoinAddress::P2wpkhV0(dst_pkhash), target)],
             minter_address,
-            fee_per_vbyte
+            fee_per_vbyte, UtxoSelectionStrategy::Greedy
         )
         .expect("failed to build transaction");
 
@@ -649,7 +1104,7 @@ proptest! {
             &mut utxos,
             vec![(BitcoinAddress::P2wpkhV0(dst_pkhash), target)],
             BitcoinAddress::P2wpkhV0(main_pkhash),
-            fee_per_vbyte
+            fee_per_vbyte, UtxoSelectionStrategy::Greedy
         )
         .expect("failed to build transaction");
 
@@ -676,7 +1131,7 @@ proptest! {
             &mut utxos,
             vec![(BitcoinAddress::P2wpkhV0(dst_pkhash), target)],
             minter_address.clone(),
-            fee_per_vbyte
+            fee_per_vbyte, UtxoSelectionStrategy::Greedy
         )
         .expect("failed to build transaction");
 
@@ -721,7 +1176,7 @@ proptest! {
                 &mut utxos,
                 vec![(BitcoinAddress::P2wpkhV0(dst_pkhash), total_value * 2)],
                 BitcoinAddress::P2wpkhV0(main_pkhash),
-                fee_per_vbyte
+                fee_per_vbyte, UtxoSelectionStrategy::Greedy
             ).expect_err("build transaction should fail because the amount is too high"),
             BuildTxError::NotEnoughFunds
         );
@@ -732,7 +1187,7 @@ proptest! {
                 &mut utxos,
                 vec![(BitcoinAddress::P2wpkhV0(dst_pkhash), 1)],
                 BitcoinAddress::P2wpkhV0(main_pkhash),
-                fee_per_vbyte
+                fee_per_vbyte, UtxoSelectionStrategy::Greedy
             ).expect_err("build transaction should fail because the amount is too low to pay the fee"),
             BuildTxError::AmountTooLow
         );
@@ -809,7 +1264,7 @@ proptest! {
             &mut state.available_utxos,
             requests.iter().map(|r| (r.address.clone(), r.amount)).collect(),
             BitcoinAddress::P2wpkhV0(main_pkhash),
-            fee_per_vbyte
+            fee_per_vbyte, UtxoSelectionStrategy::Greedy
         )
         .expect("failed to build transaction");
         let mut txids = vec![tx.txid()];
@@ -834,6 +1289,7 @@ proptest! {
                 requests.iter().map(|r| (r.address.clone(), r.amount)).collect(),
                 BitcoinAddress::P2wpkhV0(main_pkhash),
                 fee_per_vbyte + 1000 * i as u64,
+                UtxoSelectionStrategy::Greedy,
             )
             .expect("failed to build transaction");
 
@@ -1000,7 +1456,7 @@ proptest! {
         const SMALLEST_TX_SIZE_VBYTES: u64 = 140; // one input, two outputs
         const MIN_MINTER_FEE: u64 = 312;
 
-        let estimate = estimate_retrieve_btc_fee(&utxos, amount, fee_per_vbyte);
+        let estimate = estimate_retrieve_btc_fee(&utxos, amount, fee_per_vbyte, UtxoSelectionStrategy::Greedy);
         let lower_bound = MIN_MINTER_FEE + SMALLEST_TX_SIZE_VBYTES * fee_per_vbyte / 1000;
         let estimate_amount = estimate.minter_fee + estimate.bitcoin_fee;
         prop_assert!(
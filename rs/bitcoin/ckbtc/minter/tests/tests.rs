@@ -62,6 +62,10 @@ fn default_init_args() -> CkbtcMinterInitArgs {
         kyt_principal: None,
         kyt_fee: None,
         get_utxos_cache_expiration_seconds: None,
+        utxo_consolidation_threshold: None,
+        utxo_consolidation_max_fee_per_vbyte: None,
+        utxo_consolidation_max_fee: None,
+        utxo_selection_strategy: None,
     }
 }
 
@@ -738,6 +742,7 @@ impl CkBtcSetup {
                 btc_network: CheckerBtcNetwork::Mainnet,
                 check_mode: CheckMode::AcceptAll,
                 num_subnet_nodes: 1,
+                use_bitcoin_canister: None,
             }))
             .unwrap(),
         )
@@ -127,6 +127,10 @@ impl NodeActor {
                 let current_avg = self.avg_mov_latency.get_average();
                 self.avg_mov_latency.add_sample(latency);
                 let latency_change = (latency - current_avg).abs() / current_avg;
+                // Routing uses the live value, not the conditionally updated one from NodeState
+                self.node
+                    .stats
+                    .set_avg_latency_secs(self.avg_mov_latency.get_average());
                 (true, res.height, latency_change)
             }
             // Note: we don't add latency to the moving average in case of an error.
@@ -749,6 +753,7 @@ pub(crate) mod test {
                         .0
                         .certificate_der,
                     avg_latency_secs: f64::MAX,
+                    stats: Arc::default(),
                 };
                 let node = Arc::new(node);
 
@@ -15,7 +15,10 @@ use std::time::Duration;
 use std::{net::SocketAddr, path::PathBuf};
 use url::Url;
 
-use crate::core::{AUTHOR_NAME, SERVICE_NAME};
+use crate::{
+    core::{AUTHOR_NAME, SERVICE_NAME},
+    strategy::RoutingStrategyType,
+};
 
 #[derive(Parser)]
 #[clap(name = SERVICE_NAME)]
@@ -52,6 +55,9 @@ pub struct Cli {
     #[command(flatten, next_help_heading = "Caching")]
     pub cache: Cache,
 
+    #[command(flatten, next_help_heading = "Routing")]
+    pub routing: Routing,
+
     #[command(flatten, next_help_heading = "Retries")]
     pub retry: Retry,
 
@@ -328,6 +334,21 @@ pub struct Cache {
     pub cache_non_anonymous: bool,
 }
 
+#[derive(Args)]
+pub struct Routing {
+    /// Strategy to select the nodes of a subnet that a request is sent to.
+    /// Retries and hedged queries go to the next nodes selected by the same strategy.
+    /// With "p2c" the cost of a node is its health check latency multiplied by the number of requests in flight to it.
+    #[clap(env, long, value_enum, default_value = "closest")]
+    pub routing_strategy: RoutingStrategyType,
+
+    /// If set, a query that is not answered within this delay is also sent to the next node,
+    /// and the first successful response is used.
+    /// The extra node counts towards --retry-count, but at least one is always used for hedging.
+    #[clap(env, long, value_parser = parse_duration)]
+    pub routing_hedge_query_after: Option<Duration>,
+}
+
 #[derive(Args)]
 pub struct Retry {
     /// How many times to retry a failed request.
@@ -340,7 +361,8 @@ pub struct Retry {
     #[clap(env, long, default_value = "false")]
     pub retry_update_call: bool,
 
-    /// Whether to use latency-based routing for /call
+    /// Whether to use latency-based routing for /call.
+    /// Deprecated: if set, overrides --routing-strategy with "random".
     #[clap(env, long, default_value = "false")]
     pub retry_disable_latency_routing: bool,
 }
@@ -66,16 +66,16 @@ use crate::{
             cache::{cache_middleware, CacheState},
             geoip::{self},
             process::{self},
-            retry::{retry_request, RetryParams},
+            retry::{retry_request, HedgeParams, RetryParams},
             validate::{self, UUID_REGEX},
         },
         PATH_CALL, PATH_CALL_V3, PATH_HEALTH, PATH_QUERY, PATH_READ_STATE, PATH_STATUS,
         PATH_SUBNET_READ_STATE,
     },
     metrics::{
-        self, HttpMetricParams, HttpMetricParamsStatus, MetricParamsCheck, MetricParamsPersist,
-        MetricParamsSnapshot, MetricsCache, MetricsRunner, WithMetricsCheck, WithMetricsPersist,
-        WithMetricsSnapshot,
+        self, HttpMetricParams, HttpMetricParamsStatus, MetricParamsCheck, MetricParamsHedge,
+        MetricParamsPersist, MetricParamsSnapshot, MetricsCache, MetricsRunner, WithMetricsCheck,
+        WithMetricsPersist, WithMetricsSnapshot,
     },
     persist::{Persist, Persister, Routes},
     rate_limiting::{generic, RateLimit},
@@ -85,6 +85,7 @@ use crate::{
         generate_stub_snapshot, generate_stub_subnet, RegistryReplicatorRunner, RegistrySnapshot,
         SnapshotPersister, Snapshotter,
     },
+    strategy::{self, RoutingStrategyType},
     tls_verify::TlsVerifier,
 };
 
@@ -873,7 +874,22 @@ pub fn setup_router(
         RetryParams {
             retry_count: cli.retry.retry_count as usize,
             retry_update_call: cli.retry.retry_update_call,
-            disable_latency_routing: cli.retry.retry_disable_latency_routing,
+            routing_strategy: strategy::setup(
+                // The deprecated flag still takes precedence
+                if cli.retry.retry_disable_latency_routing {
+                    RoutingStrategyType::Random
+                } else {
+                    cli.routing.routing_strategy
+                },
+                metrics_registry,
+            ),
+            hedge: cli
+                .routing
+                .routing_hedge_query_after
+                .map(|delay| HedgeParams {
+                    delay,
+                    metrics: MetricParamsHedge::new(metrics_registry),
+                }),
         },
         retry_request,
     );
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use axum::{
     body::{to_bytes, Body},
@@ -7,20 +7,33 @@ use axum::{
     response::{IntoResponse, Response},
     Extension,
 };
-use http::StatusCode;
+use bytes::Bytes;
+use futures::future::{select, Either};
+use http::{request::Parts, StatusCode};
+use tokio::time::timeout;
 
 use crate::{
     errors::{ApiError, ErrorCause},
+    metrics::MetricParamsHedge,
     persist::RouteSubnet,
-    routes::RequestContext,
+    routes::{RequestContext, RequestType},
     snapshot::Node,
+    strategy::RoutingStrategy,
 };
 
 #[derive(Clone)]
 pub struct RetryParams {
     pub retry_count: usize,
     pub retry_update_call: bool,
-    pub disable_latency_routing: bool,
+    pub routing_strategy: Arc<dyn RoutingStrategy>,
+    pub hedge: Option<HedgeParams>,
+}
+
+#[derive(Clone)]
+pub struct HedgeParams {
+    // How long to wait for the response to a query before sending it to the next node too
+    pub delay: Duration,
+    pub metrics: MetricParamsHedge,
 }
 
 #[derive(Clone)]
@@ -52,29 +65,98 @@ fn request_needs_retrying(response: &Response) -> bool {
     }
 }
 
+// Passes the request down the stack to the given node, counting it as in-flight until the response arrives
+async fn send_request(next: Next, mut request: Request, node: Arc<Node>) -> (Response, Arc<Node>) {
+    request.extensions_mut().insert(node.clone());
+    let _guard = node.stats.track_in_flight();
+    let response = next.run(request).await;
+    (response, node)
+}
+
+// Sends the query to the primary node and, if it does not respond in time, to the secondary node as well.
+// Returns the first response that does not need retrying, or the last one if both need it,
+// along with whether the secondary node was used.
+// The slower request is cancelled, which is safe since queries do not change the state.
+async fn send_hedged_request(
+    next: Next,
+    parts: &Parts,
+    body: &Bytes,
+    primary: Arc<Node>,
+    secondary: Arc<Node>,
+    hedge: &HedgeParams,
+) -> (Response, Arc<Node>, bool) {
+    let make_request = || Request::from_parts(parts.clone(), Body::from(body.clone()));
+
+    let primary_fut = send_request(next.clone(), make_request(), primary);
+    tokio::pin!(primary_fut);
+
+    if let Ok(out) = timeout(hedge.delay, primary_fut.as_mut()).await {
+        hedge
+            .metrics
+            .counter
+            .with_label_values(&["not_hedged"])
+            .inc();
+        return (out.0, out.1, false);
+    }
+
+    let secondary_fut = send_request(next, make_request(), secondary);
+    tokio::pin!(secondary_fut);
+
+    let (first, rest, first_is_primary) = match select(primary_fut, secondary_fut).await {
+        Either::Left((out, rest)) => (out, rest, true),
+        Either::Right((out, rest)) => (out, rest, false),
+    };
+
+    let (out, is_primary) = if request_needs_retrying(&first.0) {
+        (rest.await, !first_is_primary)
+    } else {
+        (first, first_is_primary)
+    };
+
+    let outcome = if request_needs_retrying(&out.0) {
+        "failed"
+    } else if is_primary {
+        "primary"
+    } else {
+        "hedge"
+    };
+    hedge.metrics.counter.with_label_values(&[outcome]).inc();
+
+    (out.0, out.1, true)
+}
+
 // Middleware that optionally retries the request according to the predefined conditions
 pub async fn retry_request(
     State(params): State<RetryParams>,
     Extension(ctx): Extension<Arc<RequestContext>>,
     Extension(subnet): Extension<Arc<RouteSubnet>>,
-    mut request: Request,
+    request: Request,
     next: Next,
 ) -> Result<impl IntoResponse, ApiError> {
-    // Select up to 1+retry_count nodes from the subnet if there are any
-    let nodes = if !params.disable_latency_routing && (ctx.request_type.is_call()) {
-        let factor = subnet.fault_tolerance_factor() + 1;
-        subnet.pick_n_out_of_m_closest(1 + params.retry_count, factor)?
+    let hedge = params
+        .hedge
+        .as_ref()
+        .filter(|_| ctx.request_type == RequestType::Query);
+
+    // Select up to 1+retry_count nodes from the subnet if there are any.
+    // Hedging needs at least two, the second one counts towards the retries.
+    let node_count = if hedge.is_some() {
+        std::cmp::max(2, 1 + params.retry_count)
     } else {
-        subnet.pick_random_nodes(1 + params.retry_count)?
+        1 + params.retry_count
     };
+    let nodes = params
+        .routing_strategy
+        .pick_nodes(&subnet, &ctx.request_type, node_count)?
+        .nodes;
 
     // Skip retrying in certain cases
-    if params.retry_count == 0 || (ctx.request_type.is_call() && !params.retry_update_call) {
+    if hedge.is_none()
+        && (params.retry_count == 0 || (ctx.request_type.is_call() && !params.retry_update_call))
+    {
         // Pick one node and pass the request down the stack
         // At this point there would be at least one node in the vector
-        let node = nodes[0].clone();
-        request.extensions_mut().insert(node.clone());
-        let mut response = next.run(request).await;
+        let (mut response, node) = send_request(next, request, nodes[0].clone()).await;
         response.extensions_mut().insert(node);
         return Ok(response);
     }
@@ -91,10 +173,33 @@ pub async fn retry_request(
     // And it cannot fail since it's already in-memory.
     let body = to_bytes(body, usize::MAX).await.unwrap();
 
-    for node in nodes.into_iter() {
-        let mut request = Request::from_parts(parts.clone(), Body::from(body.clone()));
-        request.extensions_mut().insert(node.clone());
-        let mut response = next.clone().run(request).await;
+    let mut nodes = nodes.into_iter().peekable();
+    while let Some(node) = nodes.next() {
+        // Only the first attempt is hedged, if there's a node left to hedge it with
+        let hedge_node = match hedge {
+            Some(_) if node_last.is_none() => nodes.peek().cloned(),
+            _ => None,
+        };
+
+        let (mut response, node) = match (hedge, hedge_node) {
+            (Some(hedge), Some(hedge_node)) => {
+                let (response, node, hedged) =
+                    send_hedged_request(next.clone(), &parts, &body, node, hedge_node, hedge).await;
+
+                // The hedge node counts towards the retries if the request was sent to it,
+                // otherwise it stays available for them
+                if hedged {
+                    nodes.next();
+                    retry_result.retries += 1;
+                }
+
+                (response, node)
+            }
+            _ => {
+                let request = Request::from_parts(parts.clone(), Body::from(body.clone()));
+                send_request(next.clone(), request, node).await
+            }
+        };
 
         // Stop if the request does not need retrying
         if !request_needs_retrying(&response) {
@@ -126,7 +231,11 @@ mod test {
 
     use std::{
         str::FromStr,
-        sync::{Arc, RwLock},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, RwLock,
+        },
+        time::Instant,
     };
 
     use anyhow::Error;
@@ -135,11 +244,14 @@ mod test {
         routing::method_routing::post, Router,
     };
     use http::StatusCode;
-    use ic_bn_lib::principal;
+    use ic_bn_lib::{principal, prometheus::Registry};
     use ic_types::CanisterId;
     use tower::Service;
 
-    use crate::routes::{test::test_route_subnet, RequestType};
+    use crate::{
+        routes::{test::test_route_subnet, RequestType},
+        strategy::Random,
+    };
 
     struct TestState {
         failures: u8,
@@ -201,7 +313,8 @@ mod test {
                 RetryParams {
                     retry_count: 3,
                     retry_update_call: false,
-                    disable_latency_routing: true,
+                    routing_strategy: Arc::new(Random),
+                    hedge: None,
                 },
                 retry_request,
             ));
@@ -267,7 +380,8 @@ mod test {
                 RetryParams {
                     retry_count: 3,
                     retry_update_call: true,
-                    disable_latency_routing: true,
+                    routing_strategy: Arc::new(Random),
+                    hedge: None,
                 },
                 retry_request,
             ));
@@ -283,4 +397,141 @@ mod test {
 
         Ok(())
     }
+
+    // Responds slowly to the first request and immediately to the others
+    async fn slow_first_handler(State(calls): State<Arc<AtomicUsize>>) -> impl IntoResponse {
+        if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+
+        "foobar".into_response()
+    }
+
+    // Responds slowly to the first request and fails the others
+    async fn slow_first_failing_handler(
+        State(calls): State<Arc<AtomicUsize>>,
+    ) -> impl IntoResponse {
+        if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+
+        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    }
+
+    // Fails the first request and responds successfully to the others
+    async fn failing_first_handler(State(calls): State<Arc<AtomicUsize>>) -> impl IntoResponse {
+        if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+
+        "foobar".into_response()
+    }
+
+    #[tokio::test]
+    async fn test_hedging() -> Result<(), Error> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let hedge = HedgeParams {
+            delay: Duration::from_millis(10),
+            metrics: MetricParamsHedge::new(&Registry::new()),
+        };
+
+        let mut app = Router::new()
+            .route("/", post(slow_first_handler).with_state(Arc::clone(&calls)))
+            .layer(middleware::from_fn_with_state(
+                RetryParams {
+                    retry_count: 0,
+                    retry_update_call: false,
+                    routing_strategy: Arc::new(Random),
+                    hedge: Some(hedge.clone()),
+                },
+                retry_request,
+            ));
+
+        // Check that a slow query is hedged and the faster response is used
+        let start = Instant::now();
+        let req = gen_request(RequestType::Query);
+        let res = app.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(start.elapsed() < Duration::from_millis(500));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(hedge.metrics.counter.with_label_values(&["hedge"]).get(), 1);
+        let retry_result = res.extensions().get::<RetryResult>().unwrap();
+        assert_eq!(retry_result.retries, 1);
+        assert!(retry_result.success);
+
+        // Check that a fast query is not hedged
+        let req = gen_request(RequestType::Query);
+        let res = app.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            hedge
+                .metrics
+                .counter
+                .with_label_values(&["not_hedged"])
+                .get(),
+            1
+        );
+
+        // Check that update calls are not hedged
+        calls.store(0, Ordering::SeqCst);
+        let req = gen_request(RequestType::Call);
+        let res = app.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Check that a query is counted as failed if both the primary and the hedge need retrying
+        calls.store(0, Ordering::SeqCst);
+        let mut app = Router::new()
+            .route(
+                "/",
+                post(slow_first_failing_handler).with_state(Arc::clone(&calls)),
+            )
+            .layer(middleware::from_fn_with_state(
+                RetryParams {
+                    retry_count: 0,
+                    retry_update_call: false,
+                    routing_strategy: Arc::new(Random),
+                    hedge: Some(hedge.clone()),
+                },
+                retry_request,
+            ));
+
+        let req = gen_request(RequestType::Query);
+        let res = app.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            hedge.metrics.counter.with_label_values(&["failed"]).get(),
+            1
+        );
+        assert!(!res.extensions().get::<RetryResult>().unwrap().success);
+
+        // Check that the hedge node is used for retrying if the query was not hedged
+        calls.store(0, Ordering::SeqCst);
+        let mut app = Router::new()
+            .route(
+                "/",
+                post(failing_first_handler).with_state(Arc::clone(&calls)),
+            )
+            .layer(middleware::from_fn_with_state(
+                RetryParams {
+                    retry_count: 1,
+                    retry_update_call: false,
+                    routing_strategy: Arc::new(Random),
+                    hedge: Some(hedge.clone()),
+                },
+                retry_request,
+            ));
+
+        let req = gen_request(RequestType::Query);
+        let res = app.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        let retry_result = res.extensions().get::<RetryResult>().unwrap();
+        assert_eq!(retry_result.retries, 1);
+        assert!(retry_result.success);
+
+        Ok(())
+    }
 }
@@ -12,6 +12,7 @@ mod rate_limiting;
 mod routes;
 mod salt_fetcher;
 mod snapshot;
+mod strategy;
 #[cfg(any(test, feature = "bench"))]
 pub mod test_utils;
 mod tls_verify;
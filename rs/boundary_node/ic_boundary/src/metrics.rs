@@ -45,6 +45,7 @@ const KB: f64 = 1024.0;
 pub const HTTP_DURATION_BUCKETS: &[f64] = &[0.05, 0.2, 0.5, 1.0, 2.0, 4.0, 7.0, 11.0];
 pub const HTTP_REQUEST_SIZE_BUCKETS: &[f64] = &[128.0, KB, 2.0 * KB, 4.0 * KB, 8.0 * KB];
 pub const HTTP_RESPONSE_SIZE_BUCKETS: &[f64] = &[1.0 * KB, 8.0 * KB, 64.0 * KB, 256.0 * KB];
+pub const ROUTING_LATENCY_BUCKETS: &[f64] = &[0.01, 0.025, 0.05, 0.1, 0.2, 0.4, 0.8, 1.6];
 
 // https://prometheus.io/docs/instrumenting/exposition_formats/#basic-info
 const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
@@ -233,6 +234,61 @@ impl MetricParamsPersist {
     }
 }
 
+#[derive(Clone)]
+pub struct WithMetricsRouting<T>(pub T, pub MetricParamsRouting);
+
+#[derive(Clone)]
+pub struct MetricParamsRouting {
+    pub counter: IntCounterVec,
+    pub latency: HistogramVec,
+}
+
+impl MetricParamsRouting {
+    pub fn new(registry: &Registry) -> Self {
+        let mut opts = HistogramOpts::new(
+            "routing_node_latency_sec",
+            "Records the average health check latency of the nodes picked by the routing strategy",
+        );
+        opts.buckets = ROUTING_LATENCY_BUCKETS.to_vec();
+
+        Self {
+            counter: register_int_counter_vec_with_registry!(
+                "routing_decision_total",
+                "Counts the reasons why the routing strategy picked a node",
+                &["strategy", "request_type", "decision"],
+                registry
+            )
+            .unwrap(),
+
+            latency: register_histogram_vec_with_registry!(
+                opts,
+                &["strategy", "request_type"],
+                registry
+            )
+            .unwrap(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MetricParamsHedge {
+    pub counter: IntCounterVec,
+}
+
+impl MetricParamsHedge {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            counter: register_int_counter_vec_with_registry!(
+                "hedge_total",
+                "Counts queries by the outcome of hedging: not_hedged, primary, hedge or failed",
+                &["outcome"],
+                registry
+            )
+            .unwrap(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct WithMetricsCheck<T>(pub T, pub MetricParamsCheck);
 
@@ -328,6 +328,7 @@ pub(crate) mod test {
                 .0
                 .certificate_der,
             avg_latency_secs: f64::MAX,
+            stats: Arc::default(),
         })
     }
 
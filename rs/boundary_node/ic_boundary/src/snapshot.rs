@@ -3,7 +3,10 @@ use std::{
     fmt,
     net::{IpAddr, SocketAddr},
     str::FromStr,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
     time::{Duration, Instant},
 };
 
@@ -47,6 +50,52 @@ pub struct Node {
     pub port: u16,
     pub tls_certificate: Vec<u8>,
     pub avg_latency_secs: f64,
+    pub stats: Arc<NodeStats>,
+}
+
+// Live statistics of a node used for routing decisions.
+// They're shared between all clones of the node (e.g. the ones published by the health checker)
+// so that they're always up-to-date, unlike `avg_latency_secs` which is only updated when
+// the set of healthy nodes changes.
+#[derive(Debug, Default)]
+pub struct NodeStats {
+    // Moving average of health check latency in microseconds, 0 if not yet known
+    avg_latency_us: AtomicU64,
+    // Number of requests currently being sent to the node
+    in_flight: AtomicU64,
+}
+
+impl NodeStats {
+    pub fn avg_latency_secs(&self) -> Option<f64> {
+        match self.avg_latency_us.load(Ordering::Relaxed) {
+            0 => None,
+            v => Some(v as f64 / 1_000_000.0),
+        }
+    }
+
+    pub fn set_avg_latency_secs(&self, latency: f64) {
+        // Clamp to 1us so that a known latency is never confused with an unknown one
+        let latency_us = ((latency * 1_000_000.0) as u64).max(1);
+        self.avg_latency_us.store(latency_us, Ordering::Relaxed);
+    }
+
+    pub fn in_flight(&self) -> u64 {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    // Counts the request as in-flight until the returned guard is dropped
+    pub fn track_in_flight(self: &Arc<Self>) -> InFlightGuard {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard(self.clone())
+    }
+}
+
+pub struct InFlightGuard(Arc<NodeStats>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 // Lightweight Eq, just compare principals
@@ -334,6 +383,7 @@ impl Snapshotter {
                                 .context("unable to parse IP address")?,
                             port: http_endpoint.port as u16, // Port is u16 anyway
                             tls_certificate: cert.certificate_der,
+                            stats: Arc::default(),
                         };
                         let node = Arc::new(node);
 
@@ -545,6 +595,7 @@ pub fn generate_stub_subnet(nodes: Vec<SocketAddr>) -> Subnet {
                 addr: x.ip(),
                 port: x.port(),
                 tls_certificate: vec![],
+                stats: Arc::default(),
             })
         })
         .collect::<Vec<_>>();
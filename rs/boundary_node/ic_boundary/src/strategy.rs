@@ -0,0 +1,293 @@
+use std::sync::Arc;
+
+use clap::ValueEnum;
+use ic_bn_lib::prometheus::Registry;
+use rand::Rng;
+use strum::IntoStaticStr;
+
+use crate::{
+    errors::ErrorCause,
+    metrics::{MetricParamsRouting, WithMetricsRouting},
+    persist::RouteSubnet,
+    routes::RequestType,
+    snapshot::Node,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum RoutingStrategyType {
+    /// Pick nodes uniformly at random
+    Random,
+    /// Pick update calls' nodes at random out of f+1 nodes with the lowest latency, other requests' at random
+    Closest,
+    /// Pick the better one out of two random nodes, based on latency and in-flight requests
+    #[value(name = "p2c")]
+    PowerOfTwoChoices,
+}
+
+// Why the routing strategy picked the first node of a selection
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum Decision {
+    // Picked at random
+    Random,
+    // Picked at random out of the closest nodes
+    Closest,
+    // Out of two candidates it had the lower cost
+    LowerCost,
+    // Both candidates had the same cost
+    Tie,
+    // There was only one candidate
+    SingleNode,
+}
+
+pub struct Selection {
+    // Nodes in the order in which they should be tried.
+    // The first one gets the request, others are used for retries & hedging.
+    pub nodes: Vec<Arc<Node>>,
+    pub decision: Decision,
+}
+
+// Selects the nodes of a subnet that a request is sent to
+pub trait RoutingStrategy: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // Picks up to `n` distinct nodes, fails if the subnet has no nodes
+    fn pick_nodes(
+        &self,
+        subnet: &RouteSubnet,
+        request_type: &RequestType,
+        n: usize,
+    ) -> Result<Selection, ErrorCause>;
+}
+
+pub struct Random;
+
+impl RoutingStrategy for Random {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn pick_nodes(
+        &self,
+        subnet: &RouteSubnet,
+        _request_type: &RequestType,
+        n: usize,
+    ) -> Result<Selection, ErrorCause> {
+        Ok(Selection {
+            nodes: subnet.pick_random_nodes(n)?,
+            decision: Decision::Random,
+        })
+    }
+}
+
+pub struct Closest;
+
+impl RoutingStrategy for Closest {
+    fn name(&self) -> &'static str {
+        "closest"
+    }
+
+    fn pick_nodes(
+        &self,
+        subnet: &RouteSubnet,
+        request_type: &RequestType,
+        n: usize,
+    ) -> Result<Selection, ErrorCause> {
+        if !request_type.is_call() {
+            return Random.pick_nodes(subnet, request_type, n);
+        }
+
+        let factor = subnet.fault_tolerance_factor() + 1;
+        Ok(Selection {
+            nodes: subnet.pick_n_out_of_m_closest(n, factor)?,
+            decision: Decision::Closest,
+        })
+    }
+}
+
+// Power of two choices: out of two random nodes picks the one with the lower cost.
+// Unlike always picking the best node it doesn't herd all the requests onto it,
+// while still steering clear of slow & overloaded nodes most of the time.
+pub struct PowerOfTwoChoices;
+
+impl PowerOfTwoChoices {
+    // Expected time for a new request to complete: the node's latency multiplied by
+    // the number of requests that are already in flight to it.
+    // Nodes with unknown latency (not yet health checked) are the most expensive,
+    // among them the in-flight requests decide.
+    fn cost(node: &Node) -> (f64, u64) {
+        let in_flight = node.stats.in_flight();
+        let latency = node.stats.avg_latency_secs().unwrap_or(f64::INFINITY);
+        (latency * (in_flight + 1) as f64, in_flight)
+    }
+
+    // Returns the index of the picked candidate
+    fn pick_one(
+        nodes: &[Arc<Node>],
+        candidates: &[usize],
+        rng: &mut impl Rng,
+    ) -> (usize, Decision) {
+        if candidates.len() == 1 {
+            return (0, Decision::SingleNode);
+        }
+
+        // Two distinct random candidates
+        let a = rng.gen_range(0..candidates.len());
+        let mut b = rng.gen_range(0..candidates.len() - 1);
+        if b >= a {
+            b += 1;
+        }
+
+        let cost_a = Self::cost(&nodes[candidates[a]]);
+        let cost_b = Self::cost(&nodes[candidates[b]]);
+
+        if cost_a < cost_b {
+            (a, Decision::LowerCost)
+        } else if cost_b < cost_a {
+            (b, Decision::LowerCost)
+        } else {
+            (a, Decision::Tie)
+        }
+    }
+}
+
+impl RoutingStrategy for PowerOfTwoChoices {
+    fn name(&self) -> &'static str {
+        "p2c"
+    }
+
+    fn pick_nodes(
+        &self,
+        subnet: &RouteSubnet,
+        _request_type: &RequestType,
+        n: usize,
+    ) -> Result<Selection, ErrorCause> {
+        if subnet.nodes.is_empty() || n == 0 {
+            return Err(ErrorCause::NoHealthyNodes);
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut candidates = (0..subnet.nodes.len()).collect::<Vec<_>>();
+        let mut nodes = Vec::with_capacity(n.min(candidates.len()));
+        let mut decision = None;
+
+        // Each next node is picked in the same way out of the remaining ones
+        while nodes.len() < n && !candidates.is_empty() {
+            let (idx, d) = Self::pick_one(&subnet.nodes, &candidates, &mut rng);
+            decision.get_or_insert(d);
+            nodes.push(subnet.nodes[candidates.swap_remove(idx)].clone());
+        }
+
+        Ok(Selection {
+            nodes,
+            // At least one node is always picked
+            decision: decision.unwrap(),
+        })
+    }
+}
+
+impl<T: RoutingStrategy> RoutingStrategy for WithMetricsRouting<T> {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    fn pick_nodes(
+        &self,
+        subnet: &RouteSubnet,
+        request_type: &RequestType,
+        n: usize,
+    ) -> Result<Selection, ErrorCause> {
+        let out = self.0.pick_nodes(subnet, request_type, n)?;
+        let MetricParamsRouting { counter, latency } = &self.1;
+
+        let request_type: &'static str = request_type.into();
+        let decision: &'static str = out.decision.into();
+        counter
+            .with_label_values(&[self.name(), request_type, decision])
+            .inc();
+
+        if let Some(v) = out.nodes[0].stats.avg_latency_secs() {
+            latency
+                .with_label_values(&[self.name(), request_type])
+                .observe(v);
+        }
+
+        Ok(out)
+    }
+}
+
+pub fn setup(strategy: RoutingStrategyType, registry: &Registry) -> Arc<dyn RoutingStrategy> {
+    let metrics = MetricParamsRouting::new(registry);
+
+    match strategy {
+        RoutingStrategyType::Random => Arc::new(WithMetricsRouting(Random, metrics)),
+        RoutingStrategyType::Closest => Arc::new(WithMetricsRouting(Closest, metrics)),
+        RoutingStrategyType::PowerOfTwoChoices => {
+            Arc::new(WithMetricsRouting(PowerOfTwoChoices, metrics))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    use crate::routes::test::test_route_subnet;
+
+    #[test]
+    fn test_p2c_picks_distinct_nodes() {
+        let subnet = test_route_subnet(5);
+
+        for n in 1..=7 {
+            let sel = PowerOfTwoChoices
+                .pick_nodes(&subnet, &RequestType::Query, n)
+                .unwrap();
+
+            assert_eq!(sel.nodes.len(), n.min(5));
+            let ids = sel.nodes.iter().map(|x| x.id).collect::<HashSet<_>>();
+            assert_eq!(ids.len(), sel.nodes.len());
+        }
+
+        assert!(PowerOfTwoChoices
+            .pick_nodes(&test_route_subnet(0), &RequestType::Query, 1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_p2c_avoids_slow_and_loaded_nodes() {
+        let subnet = test_route_subnet(2);
+        subnet.nodes[0].stats.set_avg_latency_secs(0.5);
+        subnet.nodes[1].stats.set_avg_latency_secs(0.1);
+
+        // With two nodes both are always the candidates, so the faster one wins
+        for _ in 0..20 {
+            let sel = PowerOfTwoChoices
+                .pick_nodes(&subnet, &RequestType::Query, 2)
+                .unwrap();
+            assert_eq!(sel.nodes[0].id, subnet.nodes[1].id);
+            assert_eq!(sel.nodes[1].id, subnet.nodes[0].id);
+            assert_eq!(sel.decision, Decision::LowerCost);
+        }
+
+        // Load the faster node so that it becomes more expensive: 0.1 * 6 > 0.5 * 1
+        let guards = (0..5)
+            .map(|_| subnet.nodes[1].stats.track_in_flight())
+            .collect::<Vec<_>>();
+
+        let sel = PowerOfTwoChoices
+            .pick_nodes(&subnet, &RequestType::Query, 1)
+            .unwrap();
+        assert_eq!(sel.nodes[0].id, subnet.nodes[0].id);
+
+        // Once the requests are done it's picked again
+        drop(guards);
+        assert_eq!(subnet.nodes[1].stats.in_flight(), 0);
+
+        let sel = PowerOfTwoChoices
+            .pick_nodes(&subnet, &RequestType::Query, 1)
+            .unwrap();
+        assert_eq!(sel.nodes[0].id, subnet.nodes[1].id);
+    }
+}
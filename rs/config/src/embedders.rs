@@ -112,6 +112,9 @@ pub struct FeatureFlags {
     pub wasm64: FlagStatus,
     /// Collect a backtrace from the canister when it panics.
     pub canister_backtrace: FlagStatus,
+    /// Count how many times every basic block of canister code is executed
+    /// (see `ic_embedders::wasm_utils::coverage`). Only meant for testing.
+    pub wasm_coverage: FlagStatus,
 }
 
 impl FeatureFlags {
@@ -121,6 +124,7 @@ impl FeatureFlags {
             write_barrier: FlagStatus::Disabled,
             wasm64: FlagStatus::Enabled,
             canister_backtrace: FlagStatus::Enabled,
+            wasm_coverage: FlagStatus::Disabled,
         }
     }
 }
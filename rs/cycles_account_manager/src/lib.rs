@@ -1186,6 +1186,17 @@ impl CyclesAccountManager {
             * (subnet_size as u64)
     }
 
+    /// Returns the fee for a non-replicated http request, which is made by a
+    /// single node and whose response is not agreed upon by the other nodes
+    /// of the subnet. It is charged as if the subnet consisted of one node.
+    pub fn non_replicated_http_request_fee(
+        &self,
+        request_size: NumBytes,
+        response_size_limit: Option<NumBytes>,
+    ) -> Cycles {
+        self.http_request_fee(request_size, response_size_limit, 1)
+    }
+
     /// Returns the default value of the reserved balance limit for the case
     /// when the canister doesn't have it set in the settings.
     pub fn default_reserved_balance_limit(&self) -> Cycles {
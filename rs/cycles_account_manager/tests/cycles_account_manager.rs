@@ -1143,3 +1143,26 @@ fn test_storage_reservation_cycles() {
         cam.storage_reservation_cycles(NumBytes::new(1000 * GB), &rs0, 13)
     )
 }
+
+#[test]
+fn non_replicated_http_request_is_cheaper_than_replicated() {
+    let cycles_account_manager = CyclesAccountManagerBuilder::new()
+        .with_subnet_type(SubnetType::Application)
+        .build();
+    let request_size = NumBytes::from(1_000);
+    let response_size_limit = Some(NumBytes::from(10_000));
+
+    let replicated_fee = cycles_account_manager.http_request_fee(
+        request_size,
+        response_size_limit,
+        SMALL_APP_SUBNET_MAX_SIZE,
+    );
+    let non_replicated_fee =
+        cycles_account_manager.non_replicated_http_request_fee(request_size, response_size_limit);
+
+    assert!(non_replicated_fee < replicated_fee);
+    assert_eq!(
+        non_replicated_fee,
+        cycles_account_manager.http_request_fee(request_size, response_size_limit, 1)
+    );
+}
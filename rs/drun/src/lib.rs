@@ -1,6 +1,6 @@
 //! Standalone interface for testing application canisters.
 
-use crate::message::{msg_stream_from_file, Message};
+use crate::message::{msg_stream_from_file, top_up_ingress, Message};
 use hex::encode;
 use ic_config::{subnet_config::SubnetConfig, Config};
 use ic_crypto_test_utils_ni_dkg::dummy_initial_dkg_transcript_with_master_key;
@@ -12,6 +12,7 @@ use ic_interfaces::{
     execution_environment::{IngressHistoryReader, QueryExecutionError},
     messaging::MessageRouting,
 };
+use ic_interfaces_state_manager::StateReader;
 use ic_messaging::MessageRoutingImpl;
 use ic_metrics::MetricsRegistry;
 use ic_protobuf::registry::{
@@ -74,20 +75,74 @@ pub struct DrunOptions {
     pub subnet_type: SubnetType,
 }
 
-/// Deliver a single message to the Message Routing layer
+/// Deliver a single message to the Message Routing layer and return its result.
 fn deliver_message(
     msg: SignedIngress,
     message_routing: &dyn MessageRouting,
     ingress_hist_reader: &dyn IngressHistoryReader,
     extra_batches: u64,
-) {
+    time_offset: Duration,
+) -> Result<WasmResult, UserError> {
     let message_id = msg.id();
 
-    let _ = execute_ingress_message(message_routing, msg, &message_id, ingress_hist_reader);
+    let result = execute_ingress_message(
+        message_routing,
+        msg,
+        &message_id,
+        ingress_hist_reader,
+        time_offset,
+    );
     // print result after waiting, to not interleave the result
     // with debug.print messages from subsequent calls. revise after DFN-1269.
-    wait_extra_batches(message_routing, extra_batches);
+    wait_extra_batches(message_routing, extra_batches, time_offset);
     print_ingress_result(&message_id, ingress_hist_reader);
+    result
+}
+
+/// Checks an `expect_reply` directive against the result of the previous message.
+fn check_expect_reply(
+    expected: Option<Vec<u8>>,
+    last_result: Option<&Result<WasmResult, UserError>>,
+) -> Result<(), String> {
+    match last_result {
+        Some(Ok(WasmResult::Reply(payload))) => match expected {
+            Some(expected) if expected != *payload => Err(format!(
+                "expect_reply failed: expected reply 0x{}, got reply 0x{}",
+                encode(expected),
+                encode(payload)
+            )),
+            _ => Ok(()),
+        },
+        Some(Ok(WasmResult::Reject(e))) => Err(format!("expect_reply failed: got reject: {}", e)),
+        Some(Err(e)) => Err(format!("expect_reply failed: got error: {}", e)),
+        None => Err("expect_reply failed: no previous message".to_string()),
+    }
+}
+
+/// Checks an `expect_reject` directive against the result of the previous
+/// message. Both rejects by the canister and system errors count as rejects.
+fn check_expect_reject(
+    expected: Option<String>,
+    last_result: Option<&Result<WasmResult, UserError>>,
+) -> Result<(), String> {
+    let message = match last_result {
+        Some(Ok(WasmResult::Reject(e))) => e.clone(),
+        Some(Err(e)) => e.to_string(),
+        Some(Ok(WasmResult::Reply(payload))) => {
+            return Err(format!(
+                "expect_reject failed: got reply 0x{}",
+                encode(payload)
+            ))
+        }
+        None => return Err("expect_reject failed: no previous message".to_string()),
+    };
+    match expected {
+        Some(expected) if !message.contains(&expected) => Err(format!(
+            "expect_reject failed: expected a reject containing {:?}, got: {}",
+            expected, message
+        )),
+        _ => Ok(()),
+    }
 }
 
 fn setup_logger(log_file: PathBuf) -> Logger {
@@ -272,15 +327,28 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
         MaliciousFlags::default(),
     );
 
-    for parse_result in msg_stream {
-        match parse_result? {
-            Message::Install(msg) => {
-                deliver_message(
+    // Offset added to the wall-clock time of every batch, moved forward by
+    // `advance_time` directives.
+    let mut time_offset = Duration::ZERO;
+    // Result of the last ingress message or query, checked by `expect_reply`
+    // and `expect_reject` directives.
+    let mut last_result: Option<Result<WasmResult, UserError>> = None;
+
+    for line in msg_stream {
+        match line?.parse(time_offset)? {
+            Message::Install(msg)
+            | Message::Ingress(msg)
+            | Message::Create(msg)
+            | Message::UpdateSettings(msg)
+            | Message::TakeSnapshot(msg)
+            | Message::LoadSnapshot(msg) => {
+                last_result = Some(deliver_message(
                     msg,
                     &message_routing,
                     ingress_hist_reader.as_ref(),
                     extra_batches,
-                );
+                    time_offset,
+                ));
             }
 
             Message::Query(q) => {
@@ -297,25 +365,50 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
                         panic!("Certified state unavailable for query call.")
                     }
                 };
-                print_query_result(query_result);
+                print_query_result(query_result.clone());
+                last_result = Some(query_result);
             }
 
-            Message::Ingress(msg) => {
-                deliver_message(
-                    msg,
+            Message::SetCycles {
+                canister_id,
+                amount,
+                nonce,
+            } => {
+                let balance = state_manager
+                    .get_latest_state()
+                    .get_ref()
+                    .canister_state(&canister_id)
+                    .map(|canister| canister.system_state.balance().get())
+                    .ok_or_else(|| format!("set_cycles: canister {} not found", canister_id))?;
+                if amount < balance {
+                    return Err(format!(
+                        "set_cycles: cannot decrease the balance of canister {} from {} to {} cycles",
+                        canister_id, balance, amount
+                    ));
+                }
+                last_result = Some(deliver_message(
+                    top_up_ingress(nonce, time_offset, canister_id, amount - balance),
                     &message_routing,
                     ingress_hist_reader.as_ref(),
                     extra_batches,
-                );
+                    time_offset,
+                ));
             }
 
-            Message::Create(msg) => {
-                deliver_message(
-                    msg,
-                    &message_routing,
-                    ingress_hist_reader.as_ref(),
-                    extra_batches,
-                );
+            Message::AdvanceTime(duration) => {
+                time_offset += duration;
+            }
+
+            Message::Tick(num_batches) => {
+                wait_extra_batches(&message_routing, num_batches, time_offset);
+            }
+
+            Message::ExpectReply(expected) => {
+                check_expect_reply(expected, last_result.as_ref())?;
+            }
+
+            Message::ExpectReject(expected) => {
+                check_expect_reject(expected, last_result.as_ref())?;
             }
         }
     }
@@ -374,7 +467,11 @@ fn get_random_seed() -> [u8; 32] {
     seed.try_into().unwrap()
 }
 
-fn build_batch(message_routing: &dyn MessageRouting, msgs: Vec<SignedIngress>) -> Batch {
+fn build_batch(
+    message_routing: &dyn MessageRouting,
+    msgs: Vec<SignedIngress>,
+    time_offset: Duration,
+) -> Batch {
     Batch {
         batch_number: message_routing.expected_batch_height(),
         batch_summary: None,
@@ -388,7 +485,7 @@ fn build_batch(message_routing: &dyn MessageRouting, msgs: Vec<SignedIngress>) -
         idkg_pre_signature_ids: BTreeMap::new(),
         ni_dkg_ids: BTreeMap::new(),
         registry_version: RegistryVersion::from(1),
-        time: time::current_time(),
+        time: time::current_time() + time_offset,
         consensus_responses: vec![],
         blockmaker_metrics: BlockmakerMetrics::new_for_test(),
         replica_version: ReplicaVersion::default(),
@@ -403,8 +500,9 @@ fn execute_ingress_message(
     msg: SignedIngress,
     msg_id: &MessageId,
     ingress_history: &dyn IngressHistoryReader,
+    time_offset: Duration,
 ) -> Result<WasmResult, UserError> {
-    let mut batch = build_batch(message_routing, vec![msg]);
+    let mut batch = build_batch(message_routing, vec![msg], time_offset);
     for _ in 0..MAX_BATCHES_UNTIL_RESPONSE {
         // In the first batch we try to send the ingress message itself. If it fails, we
         // repeat with the same batch.
@@ -415,7 +513,7 @@ fn execute_ingress_message(
         // potential inter-canister messages that the ingress message may have
         // triggered.
         if message_routing.deliver_batch(batch.clone()).is_ok() {
-            batch = build_batch(message_routing, vec![])
+            batch = build_batch(message_routing, vec![], time_offset)
         }
         sleep(WAIT_PER_BATCH);
 
@@ -461,10 +559,14 @@ fn execute_ingress_message(
 ///
 /// This is a temporary measure until DFN-1269 is resolved. In that ticket, we
 /// will actually try to wait until all messages have been executed.
-fn wait_extra_batches(message_routing: &dyn MessageRouting, extra_batches: u64) {
+fn wait_extra_batches(
+    message_routing: &dyn MessageRouting,
+    extra_batches: u64,
+    time_offset: Duration,
+) {
     for _ in 0..extra_batches {
         loop {
-            let batch = build_batch(message_routing, vec![]);
+            let batch = build_batch(message_routing, vec![], time_offset);
             let ok = message_routing.deliver_batch(batch).is_ok();
             sleep(WAIT_PER_BATCH);
             if ok {
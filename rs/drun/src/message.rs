@@ -3,20 +3,22 @@ use super::CanisterId;
 use hex::decode;
 use ic_execution_environment::execution::upgrade::ENHANCED_ORTHOGONAL_PERSISTENCE_SECTION;
 use ic_management_canister_types_private::{
-    self as ic00, CanisterInstallModeV2, CanisterUpgradeOptions, Payload, WasmMemoryPersistence,
+    self as ic00, CanisterInstallModeV2, CanisterSettingsArgsBuilder, CanisterUpgradeOptions,
+    LogVisibilityV2, Payload, WasmMemoryPersistence,
 };
 use ic_types::{
     messages::{Query, QuerySource, SignedIngress},
     time::expiry_time_from_now,
-    PrincipalId, UserId,
+    PrincipalId, SnapshotId, Time, UserId,
 };
 
 use std::{
     fmt,
     fs::File,
     io::{self, Read},
-    str::Chars,
+    str::{Chars, FromStr},
     string::FromUtf8Error,
+    time::Duration,
 };
 
 #[derive(PartialEq, Debug)]
@@ -25,6 +27,24 @@ pub(crate) enum Message {
     Query(Query),
     Install(SignedIngress),
     Create(SignedIngress),
+    UpdateSettings(SignedIngress),
+    TakeSnapshot(SignedIngress),
+    LoadSnapshot(SignedIngress),
+    /// Tops up the canister so that its balance reaches `amount` cycles.
+    /// The ingress message is built when the directive is executed since
+    /// the amount to top up depends on the current balance.
+    SetCycles {
+        canister_id: CanisterId,
+        amount: u128,
+        nonce: u64,
+    },
+    AdvanceTime(Duration),
+    Tick(u64),
+    /// Asserts that the previous message was replied, optionally with the given payload.
+    ExpectReply(Option<Vec<u8>>),
+    /// Asserts that the previous message was rejected, optionally with an
+    /// error message containing the given string.
+    ExpectReject(Option<String>),
 }
 
 #[derive(Debug)]
@@ -124,9 +144,25 @@ impl<R: Read> Iterator for LineIterator<R> {
     }
 }
 
+/// A non-empty and non-commented line of a message file.
+pub(crate) struct MessageLine {
+    index: usize,
+    line: String,
+}
+
+impl MessageLine {
+    /// Parses the line into a message executed at the given offset from the
+    /// wall-clock time. The line is only parsed right before the message is
+    /// executed since the ingress expiry depends on the time offset at that point.
+    pub(crate) fn parse(&self, time_offset: Duration) -> Result<Message, String> {
+        parse_message(&self.line, self.index as u64, time_offset)
+            .map_err(|e| format!("Line {}: {}", self.index + 1, e))
+    }
+}
+
 pub(crate) fn msg_stream_from_file(
     filename: &str,
-) -> Result<impl Iterator<Item = Result<Message, String>>, String> {
+) -> Result<impl Iterator<Item = Result<MessageLine, String>>, String> {
     let f = File::open(filename).map_err(|e| e.to_string())?;
     let line_iterator = LineIterator::new(f);
 
@@ -138,16 +174,21 @@ pub(crate) fn msg_stream_from_file(
             _ => true,
         })
         .map(|(i, line)| match line {
-            Ok(line) => {
-                parse_message(&line, i as u64).map_err(|e| format!("Line {}: {}", i + 1, e))
-            }
+            Ok(line) => Ok(MessageLine { index: i, line }),
             Err(e) => Err(format!("Error while reading line {}: {}", i, e)),
         }))
 }
 
-fn parse_message(s: &str, nonce: u64) -> Result<Message, String> {
+/// Returns the expiry time of an ingress message executed at the given offset
+/// from the wall-clock time, i.e., relative to the time of the executing batch.
+fn ingress_expiry(time_offset: Duration) -> Time {
+    expiry_time_from_now() + time_offset
+}
+
+fn parse_message(s: &str, nonce: u64, time_offset: Duration) -> Result<Message, String> {
     let s = s.trim_end();
     let tokens: Vec<&str> = s.splitn(4, char::is_whitespace).collect();
+    let expiry_time = ingress_expiry(time_offset);
 
     match &tokens[..] {
         [] => Err("Too few arguments.".to_string()),
@@ -165,29 +206,73 @@ fn parse_message(s: &str, nonce: u64) -> Result<Message, String> {
                 .method_name(method_name)
                 .method_payload(method_payload)
                 .nonce(nonce)
+                .expiry_time(expiry_time)
                 .build();
             Ok(Message::Ingress(signed_ingress))
         }
         ["query", canister_id, method_name, payload] => Ok(Message::Query(Query {
             source: QuerySource::User {
                 user_id: UserId::from(PrincipalId::new_anonymous()),
-                ingress_expiry: expiry_time_from_now().as_nanos_since_unix_epoch(),
+                ingress_expiry: expiry_time.as_nanos_since_unix_epoch(),
                 nonce: Some(nonce.to_le_bytes().to_vec()),
             },
             receiver: parse_canister_id(canister_id)?,
             method_name: validate_method_name(method_name)?,
             method_payload: parse_octet_string(payload)?,
         })),
-        ["create"] => parse_create(nonce),
-        ["install", canister_id, wasm_file, payload] => {
-            parse_install(nonce, canister_id, payload, wasm_file, "install")
-        }
-        ["reinstall", canister_id, wasm_file, payload] => {
-            parse_install(nonce, canister_id, payload, wasm_file, "reinstall")
+        ["create"] => parse_create(nonce, expiry_time),
+        ["update_settings", canister_id, ..] => parse_update_settings(
+            nonce,
+            expiry_time,
+            canister_id,
+            s.split_whitespace().skip(2),
+        ),
+        ["take_snapshot", canister_id] => parse_take_snapshot(nonce, expiry_time, canister_id),
+        ["load_snapshot", canister_id, local_snapshot_id] => {
+            parse_load_snapshot(nonce, expiry_time, canister_id, local_snapshot_id)
         }
-        ["upgrade", canister_id, wasm_file, payload] => {
-            parse_install(nonce, canister_id, payload, wasm_file, "upgrade")
+        ["set_cycles", canister_id, amount] => Ok(Message::SetCycles {
+            canister_id: parse_canister_id(canister_id)?,
+            amount: parse_number(amount)?,
+            nonce,
+        }),
+        ["advance_time", duration] => Ok(Message::AdvanceTime(parse_duration(duration)?)),
+        ["tick"] => Ok(Message::Tick(1)),
+        ["tick", num_batches] => Ok(Message::Tick(parse_number(num_batches)?)),
+        ["expect_reply"] => Ok(Message::ExpectReply(None)),
+        ["expect_reply", ..] => Ok(Message::ExpectReply(Some(parse_octet_string(arguments(
+            s,
+        ))?))),
+        ["expect_reject"] => Ok(Message::ExpectReject(None)),
+        ["expect_reject", ..] => {
+            let message =
+                String::from_utf8(parse_octet_string(arguments(s))?).map_err(|e| e.to_string())?;
+            Ok(Message::ExpectReject(Some(message)))
         }
+        ["install", canister_id, wasm_file, payload] => parse_install(
+            nonce,
+            expiry_time,
+            canister_id,
+            payload,
+            wasm_file,
+            "install",
+        ),
+        ["reinstall", canister_id, wasm_file, payload] => parse_install(
+            nonce,
+            expiry_time,
+            canister_id,
+            payload,
+            wasm_file,
+            "reinstall",
+        ),
+        ["upgrade", canister_id, wasm_file, payload] => parse_install(
+            nonce,
+            expiry_time,
+            canister_id,
+            payload,
+            wasm_file,
+            "upgrade",
+        ),
         _ => Err(format!(
             "Failed to parse line {}, don't have a pattern to match this with",
             s
@@ -206,7 +291,7 @@ fn parse_canister_id(canister_id: &str) -> Result<CanisterId, String> {
     }
 }
 
-fn parse_create(nonce: u64) -> Result<Message, String> {
+fn parse_create(nonce: u64, expiry_time: Time) -> Result<Message, String> {
     use ic_test_utilities_types::messages::SignedIngressBuilder;
 
     let signed_ingress = SignedIngressBuilder::new()
@@ -214,11 +299,120 @@ fn parse_create(nonce: u64) -> Result<Message, String> {
         .canister_id(ic00::IC_00)
         .method_payload(ic00::ProvisionalCreateCanisterWithCyclesArgs::new(None, None).encode())
         .nonce(nonce)
+        .expiry_time(expiry_time)
         .build();
 
     Ok(Message::Create(signed_ingress))
 }
 
+fn build_ic00_ingress(
+    nonce: u64,
+    expiry_time: Time,
+    method: ic00::Method,
+    payload: Vec<u8>,
+) -> SignedIngress {
+    use ic_test_utilities_types::messages::SignedIngressBuilder;
+
+    SignedIngressBuilder::new()
+        .canister_id(ic00::IC_00)
+        .method_name(method)
+        .method_payload(payload)
+        .nonce(nonce)
+        .expiry_time(expiry_time)
+        .build()
+}
+
+fn parse_update_settings<'a>(
+    nonce: u64,
+    expiry_time: Time,
+    canister_id: &str,
+    settings: impl Iterator<Item = &'a str>,
+) -> Result<Message, String> {
+    let canister_id = parse_canister_id(canister_id)?;
+    let mut builder = CanisterSettingsArgsBuilder::new();
+    for setting in settings {
+        let (name, value) = setting
+            .split_once('=')
+            .ok_or_else(|| format!("Expected a <setting>=<value> pair, got {}", setting))?;
+        builder = match name {
+            "controllers" => builder.with_controllers(
+                value
+                    .split(',')
+                    .filter(|controller| !controller.is_empty())
+                    .map(|controller| parse_canister_id(controller).map(|id| id.get()))
+                    .collect::<Result<_, _>>()?,
+            ),
+            "compute_allocation" => builder.with_compute_allocation(parse_number(value)?),
+            "memory_allocation" => builder.with_memory_allocation(parse_number(value)?),
+            "freezing_threshold" => builder.with_freezing_threshold(parse_number(value)?),
+            "reserved_cycles_limit" => builder.with_reserved_cycles_limit(parse_number(value)?),
+            "wasm_memory_limit" => builder.with_wasm_memory_limit(parse_number(value)?),
+            "wasm_memory_threshold" => builder.with_wasm_memory_threshold(parse_number(value)?),
+            "log_visibility" => builder.with_log_visibility(match value {
+                "controllers" => LogVisibilityV2::Controllers,
+                "public" => LogVisibilityV2::Public,
+                _ => return Err(format!("Unsupported log visibility: {}", value)),
+            }),
+            _ => return Err(format!("Unsupported canister setting: {}", name)),
+        };
+    }
+
+    Ok(Message::UpdateSettings(build_ic00_ingress(
+        nonce,
+        expiry_time,
+        ic00::Method::UpdateSettings,
+        ic00::UpdateSettingsArgs::new(canister_id, builder.build()).encode(),
+    )))
+}
+
+fn parse_take_snapshot(
+    nonce: u64,
+    expiry_time: Time,
+    canister_id: &str,
+) -> Result<Message, String> {
+    let canister_id = parse_canister_id(canister_id)?;
+    Ok(Message::TakeSnapshot(build_ic00_ingress(
+        nonce,
+        expiry_time,
+        ic00::Method::TakeCanisterSnapshot,
+        ic00::TakeCanisterSnapshotArgs::new(canister_id, None).encode(),
+    )))
+}
+
+/// Snapshots are referred to by their local ID, i.e., the sequence number of
+/// the snapshot taken for the canister (starting from 0).
+fn parse_load_snapshot(
+    nonce: u64,
+    expiry_time: Time,
+    canister_id: &str,
+    local_snapshot_id: &str,
+) -> Result<Message, String> {
+    let canister_id = parse_canister_id(canister_id)?;
+    let snapshot_id = SnapshotId::from((canister_id, parse_number(local_snapshot_id)?));
+    Ok(Message::LoadSnapshot(build_ic00_ingress(
+        nonce,
+        expiry_time,
+        ic00::Method::LoadCanisterSnapshot,
+        ic00::LoadCanisterSnapshotArgs::new(canister_id, snapshot_id, None).encode(),
+    )))
+}
+
+/// Builds the ingress message topping up `canister_id` by `amount` cycles,
+/// executed at the given offset from the wall-clock time.
+pub(crate) fn top_up_ingress(
+    nonce: u64,
+    time_offset: Duration,
+    canister_id: CanisterId,
+    amount: u128,
+) -> SignedIngress {
+    build_ic00_ingress(
+        nonce,
+        ingress_expiry(time_offset),
+        ic00::Method::ProvisionalTopUpCanister,
+        ic00::ProvisionalTopUpCanisterArgs::new(canister_id, amount).encode(),
+    )
+}
+
 fn contains_icp_private_custom_section(wasm_binary: &[u8], name: &str) -> Result<bool, String> {
     use wasmparser::{Parser, Payload::CustomSection};
 
@@ -236,6 +430,7 @@ fn contains_icp_private_custom_section(wasm_binary: &[u8], name: &str) -> Result
 
 fn parse_install(
     nonce: u64,
+    expiry_time: Time,
     canister_id: &str,
     payload: &str,
     wasm_file: &str,
@@ -284,6 +479,7 @@ fn parse_install(
             ic00::InstallCodeArgsV2::new(install_mode, canister_id, wasm_data, payload).encode(),
         )
         .nonce(nonce)
+        .expiry_time(expiry_time)
         .build();
     Ok(Message::Install(signed_ingress))
 }
@@ -308,6 +504,43 @@ fn validate_method_name(method_name: &str) -> Result<String, String> {
     }
 }
 
+/// Returns the arguments of a directive, i.e., everything after the first token.
+fn arguments(s: &str) -> &str {
+    s.split_once(char::is_whitespace)
+        .map_or("", |(_, arguments)| arguments.trim_start())
+}
+
+fn parse_number<T: FromStr>(s: &str) -> Result<T, String>
+where
+    T::Err: fmt::Display,
+{
+    s.replace('_', "")
+        .parse()
+        .map_err(|e| format!("Failed to parse number {}: {}", s, e))
+}
+
+/// Parses a duration such as `500ms`, `10s` or `2h`. A number without unit is
+/// interpreted as seconds.
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let split_at = s.find(|c: char| c.is_alphabetic()).unwrap_or(s.len());
+    let (amount, unit) = s.split_at(split_at);
+    let amount: u64 = parse_number(amount)?;
+    let nanos_per_unit: u64 = match unit {
+        "ns" => 1,
+        "us" => 1_000,
+        "ms" => 1_000_000,
+        "" | "s" => 1_000_000_000,
+        "m" => 60 * 1_000_000_000,
+        "h" => 60 * 60 * 1_000_000_000,
+        "d" => 24 * 60 * 60 * 1_000_000_000,
+        _ => return Err(format!("Unsupported duration unit in {}", s)),
+    };
+    amount
+        .checked_mul(nanos_per_unit)
+        .map(Duration::from_nanos)
+        .ok_or_else(|| format!("Duration {} is too large", s))
+}
+
 fn parse_octet_string(input_str: &str) -> Result<Vec<u8>, String> {
     if input_str.starts_with('"') {
         parse_quoted(input_str)
@@ -408,7 +641,7 @@ mod tests {
             "ingress {} write \"payload \\x0a\\b00010001\"",
             APP_CANISTER_URL
         );
-        let parsed_message = parse_message(s, 0).unwrap();
+        let parsed_message = parse_message(s, 0, Duration::ZERO).unwrap();
         let expiry_time = match &parsed_message {
             Message::Ingress(signed_ingress) => signed_ingress.expiry_time(),
             _ => panic!(
@@ -431,7 +664,7 @@ mod tests {
     #[test]
     fn test_parse_message_hex_payload_succeeds() {
         let s = &format!("ingress {} write 0x010203", APP_CANISTER_URL);
-        let parsed_message = parse_message(s, 0).unwrap();
+        let parsed_message = parse_message(s, 0, Duration::ZERO).unwrap();
         let expiry_time = match &parsed_message {
             Message::Ingress(signed_ingress) => signed_ingress.expiry_time(),
             _ => panic!(
@@ -452,7 +685,7 @@ mod tests {
 
         let s = &format!("query {} read 0x010203", APP_CANISTER_URL);
         let nonce: u64 = 0;
-        let parsed_message = parse_message(s, 0).unwrap();
+        let parsed_message = parse_message(s, 0, Duration::ZERO).unwrap();
         let ingress_expiry = match &parsed_message {
             Message::Query(query) => match query.source {
                 QuerySource::User { ingress_expiry, .. } => ingress_expiry,
@@ -479,25 +712,150 @@ mod tests {
     #[test]
     fn test_parse_message_invalid_escapes_fails() {
         let s = &format!("query {} read \"\\xzz\"", APP_CANISTER_URL);
-        assert!(parse_message(s, 0).is_err());
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
 
         let s = &format!("query {} read \"\\b01\"", APP_CANISTER_URL);
-        assert!(parse_message(s, 0).is_err());
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
 
         let s = &format!("query {} read \"\\x1\"", APP_CANISTER_URL);
-        assert!(parse_message(s, 0).is_err());
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
 
         let s = &format!("query {} read \"\\b2\"", APP_CANISTER_URL);
-        assert!(parse_message(s, 0).is_err());
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
     }
 
     #[test]
     fn test_illegal_method_name_must_fail() {
         let s = &format!("query {} 0read \"\\xzz\"", APP_CANISTER_URL);
-        assert!(parse_message(s, 0).is_err());
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
 
         let s = &format!("query {} üread \"\\xzz\"", APP_CANISTER_URL);
-        assert!(parse_message(s, 0).is_err());
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_ingress_expiry_includes_time_offset() {
+        let expiry_time = |time_offset: Duration| {
+            let s = &format!("ingress {} write 0x010203", APP_CANISTER_URL);
+            match parse_message(s, 0, time_offset).unwrap() {
+                Message::Ingress(signed_ingress) => signed_ingress.expiry_time(),
+                message => panic!("Unexpected message: {:?}", message),
+            }
+        };
+
+        let offset = Duration::from_secs(3600);
+        let before = expiry_time_from_now() + offset;
+        let expiry = expiry_time(offset);
+        assert!(before <= expiry && expiry <= expiry_time_from_now() + offset);
+        assert!(expiry_time(Duration::ZERO) < before);
+    }
+
+    #[test]
+    fn test_parse_time_directives() {
+        assert_eq!(
+            parse_message("advance_time 1500ms", 0, Duration::ZERO).unwrap(),
+            Message::AdvanceTime(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            parse_message("advance_time 2h", 0, Duration::ZERO).unwrap(),
+            Message::AdvanceTime(Duration::from_secs(7200))
+        );
+        assert_eq!(
+            parse_message("advance_time 10", 0, Duration::ZERO).unwrap(),
+            Message::AdvanceTime(Duration::from_secs(10))
+        );
+        assert!(parse_message("advance_time 10y", 0, Duration::ZERO).is_err());
+        assert_eq!(
+            parse_message("tick", 0, Duration::ZERO).unwrap(),
+            Message::Tick(1)
+        );
+        assert_eq!(
+            parse_message("tick 5", 0, Duration::ZERO).unwrap(),
+            Message::Tick(5)
+        );
+        assert!(parse_message("tick -1", 0, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_parse_set_cycles() {
+        let s = &format!("set_cycles {} 1_000_000", APP_CANISTER_URL);
+        assert_eq!(
+            parse_message(s, 7, Duration::ZERO).unwrap(),
+            Message::SetCycles {
+                canister_id: canister_test_id(APP_CANISTER_ID),
+                amount: 1_000_000,
+                nonce: 7,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_update_settings() {
+        let s = &format!(
+            "update_settings {} controllers={} freezing_threshold=100 log_visibility=public",
+            APP_CANISTER_URL, APP_CANISTER_URL
+        );
+        let payload = match parse_message(s, 0, Duration::ZERO).unwrap() {
+            Message::UpdateSettings(signed_ingress) => {
+                assert_eq!(signed_ingress.canister_id(), ic00::IC_00);
+                signed_ingress.content().arg().to_vec()
+            }
+            parsed_message => panic!(
+                "parse_message() returned an unexpected message type: {:?}",
+                parsed_message
+            ),
+        };
+        let args = ic00::UpdateSettingsArgs::decode(&payload).unwrap();
+        assert_eq!(args.get_canister_id(), canister_test_id(APP_CANISTER_ID));
+        assert_eq!(
+            args.settings,
+            CanisterSettingsArgsBuilder::new()
+                .with_controllers(vec![canister_test_id(APP_CANISTER_ID).get()])
+                .with_freezing_threshold(100)
+                .with_log_visibility(LogVisibilityV2::Public)
+                .build()
+        );
+
+        let s = &format!("update_settings {} unknown=1", APP_CANISTER_URL);
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
+        let s = &format!("update_settings {} freezing_threshold", APP_CANISTER_URL);
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_parse_snapshot_directives() {
+        let s = &format!("take_snapshot {}", APP_CANISTER_URL);
+        assert!(matches!(
+            parse_message(s, 0, Duration::ZERO).unwrap(),
+            Message::TakeSnapshot(_)
+        ));
+        let s = &format!("load_snapshot {} 0", APP_CANISTER_URL);
+        assert!(matches!(
+            parse_message(s, 0, Duration::ZERO).unwrap(),
+            Message::LoadSnapshot(_)
+        ));
+        let s = &format!("load_snapshot {} abc", APP_CANISTER_URL);
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_parse_expectations() {
+        assert_eq!(
+            parse_message("expect_reply", 0, Duration::ZERO).unwrap(),
+            Message::ExpectReply(None)
+        );
+        assert_eq!(
+            parse_message("expect_reply 0x0102", 0, Duration::ZERO).unwrap(),
+            Message::ExpectReply(Some(vec![1, 2]))
+        );
+        assert_eq!(
+            parse_message("expect_reject", 0, Duration::ZERO).unwrap(),
+            Message::ExpectReject(None)
+        );
+        assert_eq!(
+            parse_message("expect_reject \"trapped explicitly\"", 0, Duration::ZERO).unwrap(),
+            Message::ExpectReject(Some("trapped explicitly".to_string()))
+        );
     }
 
     #[test]
@@ -0,0 +1,56 @@
+use ic_config::{flag_status::FlagStatus, Config};
+use ic_drun::{run_drun, DrunOptions};
+use ic_registry_subnet_type::SubnetType;
+use ic_universal_canister::wasm;
+use std::io::Write;
+
+// The ID of the first canister created by `drun` on a system subnet.
+const CANISTER_ID: &str = "rwlgt-iiaaa-aaaaa-aaaaa-cai";
+
+async fn run_messages(messages: &str) -> Result<(), String> {
+    let mut msg_file = tempfile::NamedTempFile::new().unwrap();
+    msg_file.write_all(messages.as_bytes()).unwrap();
+
+    Config::run_with_temp_config(|mut cfg| async {
+        // The test binary cannot be launched as a canister sandbox.
+        cfg.hypervisor.canister_sandboxing_flag = FlagStatus::Disabled;
+        run_drun(DrunOptions {
+            msg_filename: msg_file.path().to_str().unwrap().to_string(),
+            cfg,
+            extra_batches: 0,
+            log_file: None,
+            instruction_limit: None,
+            subnet_type: SubnetType::System,
+        })
+        .await
+    })
+    .await
+}
+
+#[tokio::test]
+async fn messages_are_executed_after_advancing_time_past_the_ingress_expiry() {
+    let uc_wasm_path = std::env::var("UNIVERSAL_CANISTER_WASM_PATH")
+        .expect("UNIVERSAL_CANISTER_WASM_PATH not set");
+    let reply = wasm().reply_data(b"hello").build();
+
+    // Advance the time by more than the maximum ingress expiry of 5 minutes.
+    let messages = format!(
+        "create\n\
+         install {CANISTER_ID} {uc_wasm_path} \"\"\n\
+         advance_time 10m\n\
+         ingress {CANISTER_ID} update 0x{reply}\n\
+         expect_reply \"hello\"\n\
+         update_settings {CANISTER_ID} freezing_threshold=1000\n\
+         expect_reply\n\
+         set_cycles {CANISTER_ID} 1_000_000_000_000_000\n\
+         expect_reply\n\
+         take_snapshot {CANISTER_ID}\n\
+         expect_reply\n\
+         advance_time 1h\n\
+         ingress {CANISTER_ID} update 0x{reply}\n\
+         expect_reply \"hello\"\n",
+        reply = hex::encode(&reply),
+    );
+
+    run_messages(&messages).await.unwrap();
+}
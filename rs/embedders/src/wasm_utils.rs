@@ -21,6 +21,7 @@ use crate::wasmtime_embedder::StoreData;
 use crate::{serialized_module::SerializedModule, CompilationResult, WasmtimeEmbedder};
 use wasmtime::InstancePre;
 
+pub mod coverage;
 pub mod decoding;
 pub mod instrumentation;
 mod system_api_replacements;
@@ -222,6 +223,7 @@ fn validate_and_instrument(
         config.dirty_page_overhead,
         max_wasm_memory_size,
         config.max_stable_memory_size,
+        config.feature_flags.wasm_coverage,
     )?;
     Ok((wasm_validation_details, instrumentation_output))
 }
@@ -0,0 +1,172 @@
+//! Wasm-level code coverage.
+//!
+//! If the `wasm_coverage` feature flag is enabled, the instrumentation injects
+//! a hit counter at the beginning of every basic block of every function
+//! defined in the module (see [`super::instrumentation`]). The counters are
+//! mutable `i64` globals exported under the names
+//! `canister coverage_counter_<i>` after all other exports of the instrumented
+//! module. Since exported globals are persisted in the execution state of the
+//! canister, the counts accumulate across messages until the canister is
+//! (re)installed or upgraded.
+//!
+//! A [`CoverageMap`] relates the `i`-th counter to the function and the offset
+//! of the first instruction of the corresponding basic block in the original
+//! (non-instrumented) module so that the counts can be mapped back to source
+//! code, e.g., using the DWARF debug information of the module.
+//! The values of the counters are obtained from the exported globals of the
+//! canister by [`coverage_counters`].
+
+use super::instrumentation::coverage_block_positions;
+use crate::wasmtime_embedder::NON_PERSISTED_EXPORTED_GLOBALS;
+use ic_management_canister_types_private::Global;
+use ic_wasm_types::{BinaryEncodedWasm, WasmValidationError};
+use std::collections::BTreeMap;
+use wasmparser::{ExternalKind, KnownCustom, Name, Parser, Payload, TypeRef};
+
+/// The prefix of the names of the exported coverage counter globals.
+pub const COVERAGE_COUNTER_GLOBAL_PREFIX: &str = "canister coverage_counter_";
+
+/// A basic block of a function defined in a Wasm module.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CoverageBlock {
+    /// Index of the function in the function index space of the module
+    /// (i.e., including imported functions).
+    pub function_index: u32,
+    /// Name of the function from the `name` custom section (if any).
+    pub function_name: Option<String>,
+    /// Offset of the first instruction of the basic block relative to the
+    /// start of the code section payload. This is the address used by DWARF
+    /// debug information of Wasm modules.
+    pub code_offset: u64,
+}
+
+/// The basic blocks of a Wasm module in the order of their coverage counters.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct CoverageMap {
+    pub blocks: Vec<CoverageBlock>,
+}
+
+impl CoverageMap {
+    /// Computes the coverage map of the given (decoded) Wasm module.
+    pub fn new(wasm: &BinaryEncodedWasm) -> Result<Self, WasmValidationError> {
+        let decoding_error = |err: wasmparser::BinaryReaderError| {
+            WasmValidationError::DecodingError(format!("failed to compute coverage map: {}", err))
+        };
+        let mut num_imported_functions = 0;
+        let mut code_section_start = 0;
+        let mut function_index = 0;
+        let mut blocks = vec![];
+        let mut function_names = BTreeMap::new();
+        for payload in Parser::new(0).parse_all(wasm.as_slice()) {
+            match payload.map_err(decoding_error)? {
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        if let TypeRef::Func(_) = import.map_err(decoding_error)?.ty {
+                            num_imported_functions += 1;
+                        }
+                    }
+                    function_index = num_imported_functions;
+                }
+                Payload::CodeSectionStart { range, .. } => {
+                    code_section_start = range.start;
+                }
+                Payload::CodeSectionEntry(body) => {
+                    let mut code = vec![];
+                    let mut offsets = vec![];
+                    for op in body
+                        .get_operators_reader()
+                        .map_err(decoding_error)?
+                        .into_iter_with_offsets()
+                    {
+                        let (op, offset) = op.map_err(decoding_error)?;
+                        code.push(op);
+                        offsets.push(offset);
+                    }
+                    for position in coverage_block_positions(&code) {
+                        blocks.push(CoverageBlock {
+                            function_index,
+                            function_name: None,
+                            code_offset: (offsets[position] - code_section_start) as u64,
+                        });
+                    }
+                    function_index += 1;
+                }
+                Payload::CustomSection(reader) => {
+                    if let KnownCustom::Name(reader) = reader.as_known() {
+                        for name in reader {
+                            // The name section is only informative, so we ignore malformed ones.
+                            if let Ok(Name::Function(names)) = name {
+                                for naming in names.into_iter().flatten() {
+                                    function_names.insert(naming.index, naming.name.to_string());
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        for block in blocks.iter_mut() {
+            block.function_name = function_names.get(&block.function_index).cloned();
+        }
+        Ok(Self { blocks })
+    }
+}
+
+/// Returns the values of the coverage counters of a canister ordered by their
+/// number, i.e., in the order of the blocks of the [`CoverageMap`] of its module.
+///
+/// The exported globals of the execution state of a canister are the exported
+/// globals of its instrumented module (except for the ones that are not persisted)
+/// in the order of their exports. The counters are identified by their export names
+/// in the given instrumented module so that other exported globals (of the original
+/// module or added by the instrumentation) are skipped no matter their position.
+pub fn coverage_counters(
+    instrumented_wasm: &BinaryEncodedWasm,
+    exported_globals: &[Global],
+) -> Result<Vec<u64>, String> {
+    let mut exported_global_names = vec![];
+    for payload in Parser::new(0).parse_all(instrumented_wasm.as_slice()) {
+        if let Payload::ExportSection(reader) = payload.map_err(|err| err.to_string())? {
+            for export in reader {
+                let export = export.map_err(|err| err.to_string())?;
+                if export.kind == ExternalKind::Global
+                    && !NON_PERSISTED_EXPORTED_GLOBALS.contains(&export.name)
+                {
+                    exported_global_names.push(export.name);
+                }
+            }
+        }
+    }
+    if exported_global_names.len() != exported_globals.len() {
+        return Err(format!(
+            "the instrumented module exports {} globals, but the canister has {} exported globals",
+            exported_global_names.len(),
+            exported_globals.len()
+        ));
+    }
+    let mut counters = BTreeMap::new();
+    for (name, global) in exported_global_names.into_iter().zip(exported_globals) {
+        let Some(number) = name.strip_prefix(COVERAGE_COUNTER_GLOBAL_PREFIX) else {
+            continue;
+        };
+        let number: usize = number
+            .parse()
+            .map_err(|_| format!("invalid coverage counter name {}", name))?;
+        let hits = match global {
+            Global::I64(hits) => *hits as u64,
+            global => {
+                return Err(format!(
+                    "expected an i64 coverage counter {}, found {}",
+                    name,
+                    global.type_name()
+                ))
+            }
+        };
+        counters.insert(number, hits);
+    }
+    if counters.keys().copied().ne(0..counters.len()) {
+        return Err("the coverage counters are not numbered consecutively".to_string());
+    }
+    Ok(counters.into_values().collect())
+}
@@ -109,7 +109,29 @@
 //! (memory (export "stable_memory_bytemap") i32 (i64.const STABLE_BYTEMAP_SIZE) (i64.const STABLE_BYTEMAP_SIZE))
 //! ```
 //!
+//! # Coverage
+//!
+//! If Wasm coverage is enabled, a mutable `i64` global is inserted and exported
+//! for every basic block of every function defined in the module:
+//! ```wasm
+//! (global (;N;) (mut i64) (i64.const 0))
+//! (export "canister coverage_counter_0" (global N)))
+//! ```
+//! and the counter is incremented at the beginning of the basic block (after
+//! the instructions counter decrementation):
+//!
+//! ```wasm
+//! global.get N
+//! i64.const 1
+//! i64.add
+//! global.set N
+//! ```
+//!
+//! The exports of the coverage counters are the last exports of the module.
+//! See [`super::coverage`] for how to map the counters back to the module.
+//!
 
+use super::coverage::COVERAGE_COUNTER_GLOBAL_PREFIX;
 use super::system_api_replacements::replacement_functions;
 use super::validation::API_VERSION_IC0;
 use super::{InstrumentationOutput, Segments, SystemApiFunc};
@@ -933,6 +955,7 @@ pub(super) fn instrument(
     dirty_page_overhead: NumInstructions,
     max_wasm_memory_size: NumBytes,
     max_stable_memory_size: NumBytes,
+    wasm_coverage: FlagStatus,
 ) -> Result<InstrumentationOutput, WasmInstrumentationError> {
     let main_memory_type = main_memory_type(&module);
     let stable_memory_index;
@@ -983,13 +1006,32 @@ pub(super) fn instrument(
         module.start = None;
     }
 
-    // inject instructions counter decrementation
+    // The coverage counters are inserted after the three counters
+    // inserted by `export_additional_symbols`.
+    let first_coverage_counter_ix = num_globals + 3;
+    let mut num_coverage_counters = 0;
+
+    // inject instructions counter decrementation and coverage counters
     for func_body in &mut module.code_sections {
+        let coverage_points: Vec<(usize, u32)> = match wasm_coverage {
+            FlagStatus::Enabled => coverage_block_positions(&func_body.instructions)
+                .into_iter()
+                .map(|position| {
+                    num_coverage_counters += 1;
+                    (
+                        position,
+                        first_coverage_counter_ix + num_coverage_counters - 1,
+                    )
+                })
+                .collect(),
+            FlagStatus::Disabled => vec![],
+        };
         inject_metering(
             &mut func_body.instructions,
             &special_indices,
             metering_type,
             main_memory_type,
+            &coverage_points,
         );
     }
 
@@ -1035,6 +1077,29 @@ pub(super) fn instrument(
         max_wasm_memory_size,
     );
 
+    let coverage_counter_names: Vec<String> = (0..num_coverage_counters)
+        .map(|i| format!("{}{}", COVERAGE_COUNTER_GLOBAL_PREFIX, i))
+        .collect();
+    debug_assert!(
+        num_coverage_counters == 0
+            || (module.globals.len() + num_imported_globals) as u32 == first_coverage_counter_ix
+    );
+    for (i, name) in coverage_counter_names.iter().enumerate() {
+        module.globals.push(Global {
+            ty: GlobalType {
+                content_type: ValType::I64,
+                mutable: true,
+                shared: false,
+            },
+            init_expr: Operator::I64Const { value: 0 },
+        });
+        module.exports.push(Export {
+            name: name.as_str(),
+            kind: ExternalKind::Global,
+            index: first_coverage_counter_ix + i as u32,
+        });
+    }
+
     let exported_functions = module
         .exports
         .iter()
@@ -1438,11 +1503,15 @@ impl InjectionPoint {
 // - we insert a function call before each dynamic cost instruction which
 //   performs an overflow check and then decrements the counter by the value at
 //   the top of the stack.
+//
+// Moreover, it increments the given coverage counters (pairs of a position
+// and a global index) at their positions after the metering code.
 fn inject_metering(
     code: &mut Vec<Operator>,
     export_data_module: &SpecialIndices,
     metering_type: MeteringType,
     mem_type: WasmMemoryType,
+    coverage_points: &[(usize, u32)],
 ) {
     let points = match metering_type {
         MeteringType::None => Vec::new(),
@@ -1459,10 +1528,27 @@ fn inject_metering(
     let orig_elems = code;
     let mut elems: Vec<Operator> = Vec::new();
     let mut last_injection_position = 0;
+    let mut coverage_points = coverage_points.iter().peekable();
 
     use Operator::*;
 
+    let increment_coverage_counter = |global_index: u32| {
+        [
+            GlobalGet { global_index },
+            I64Const { value: 1 },
+            I64Add,
+            GlobalSet { global_index },
+        ]
+    };
+
     for point in points {
+        while let Some((position, global_index)) =
+            coverage_points.next_if(|(position, _)| *position < point.position)
+        {
+            elems.extend_from_slice(&orig_elems[last_injection_position..*position]);
+            elems.extend_from_slice(&increment_coverage_counter(*global_index));
+            last_injection_position = *position;
+        }
         elems.extend_from_slice(&orig_elems[last_injection_position..point.position]);
         match point.cost_detail {
             InjectionPointCostDetail::StaticCost { scope, cost } => {
@@ -1517,6 +1603,11 @@ fn inject_metering(
         }
         last_injection_position = point.position;
     }
+    for (position, global_index) in coverage_points {
+        elems.extend_from_slice(&orig_elems[last_injection_position..*position]);
+        elems.extend_from_slice(&increment_coverage_counter(*global_index));
+        last_injection_position = *position;
+    }
     elems.extend_from_slice(&orig_elems[last_injection_position..]);
     *orig_elems = elems;
 }
@@ -1786,6 +1877,23 @@ fn inject_try_grow_wasm_memory(
     }
 }
 
+/// Returns the positions of the first instructions of the basic blocks of the
+/// given function body (in increasing order). These are the positions of the
+/// static cost injection points of the instructions metering except for the
+/// position past the final `end` instruction.
+pub(super) fn coverage_block_positions(code: &[Operator]) -> Vec<usize> {
+    // The memory type only affects the costs and dynamic cost injection points.
+    injections(code, WasmMemoryType::Wasm32)
+        .into_iter()
+        .filter_map(|point| match point.cost_detail {
+            InjectionPointCostDetail::StaticCost { .. } if point.position < code.len() => {
+                Some(point.position)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 // This function scans through the Wasm code and creates an injection point
 // at the beginning of every basic block (straight-line sequence of instructions
 // with no branches) and before each bulk memory instruction. An injection point
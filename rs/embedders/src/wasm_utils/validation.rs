@@ -1,6 +1,7 @@
 //! This module is responsible for validating the wasm binaries that are
 //! installed on the Internet Computer.
 
+use super::coverage::COVERAGE_COUNTER_GLOBAL_PREFIX;
 use super::{Complexity, WasmImportsDetails, WasmValidationDetails};
 
 use ic_config::{embedders::Config as EmbeddersConfig, flag_status::FlagStatus};
@@ -1001,6 +1002,7 @@ fn validate_export_section(
     module: &Module,
     max_number_exported_functions: usize,
     max_sum_exported_function_name_lengths: usize,
+    wasm_coverage: FlagStatus,
 ) -> Result<(), WasmValidationError> {
     if !module.exports.is_empty() {
         let imported_function_types: Vec<_> = module
@@ -1018,7 +1020,12 @@ fn validate_export_section(
         let mut sum_exported_function_name_lengths = 0;
         for export in &module.exports {
             // Verify that the exported symbol's name isn't reserved.
-            if RESERVED_SYMBOLS.contains(&export.name) {
+            // The names of the coverage counters are only reserved
+            // if they are inserted by the instrumentation.
+            if RESERVED_SYMBOLS.contains(&export.name)
+                || (wasm_coverage == FlagStatus::Enabled
+                    && export.name.starts_with(COVERAGE_COUNTER_GLOBAL_PREFIX))
+            {
                 return Err(WasmValidationError::InvalidExportSection(format!(
                     "Exporting reserved symbol {} not allowed.",
                     export.name
@@ -1669,6 +1676,7 @@ pub(super) fn validate_wasm_binary<'a>(
         &module,
         config.max_number_exported_functions,
         config.max_sum_exported_function_name_lengths,
+        config.feature_flags.wasm_coverage,
     )?;
     validate_data_section(&module)?;
     validate_global_section(&module, config.max_globals)?;
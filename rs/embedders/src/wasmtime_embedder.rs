@@ -158,16 +158,18 @@ fn trap_code_to_hypervisor_error(
     }
 }
 
-fn get_exported_globals<T>(instance: &Instance, store: &mut Store<T>) -> Vec<wasmtime::Global> {
-    const TO_IGNORE: &[&str] = &[
-        DIRTY_PAGES_COUNTER_GLOBAL_NAME,
-        ACCESSED_PAGES_COUNTER_GLOBAL_NAME,
-    ];
+/// Exported globals of the instrumented module that are not persisted
+/// in the execution state of a canister.
+pub(crate) const NON_PERSISTED_EXPORTED_GLOBALS: &[&str] = &[
+    DIRTY_PAGES_COUNTER_GLOBAL_NAME,
+    ACCESSED_PAGES_COUNTER_GLOBAL_NAME,
+];
 
+fn get_exported_globals<T>(instance: &Instance, store: &mut Store<T>) -> Vec<wasmtime::Global> {
     instance
         .exports(store)
         .filter_map(|e| {
-            if TO_IGNORE.contains(&e.name()) {
+            if NON_PERSISTED_EXPORTED_GLOBALS.contains(&e.name()) {
                 None
             } else {
                 e.into_global()
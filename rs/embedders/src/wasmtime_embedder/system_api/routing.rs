@@ -5,12 +5,12 @@ use ic_base_types::{CanisterId, PrincipalId, SubnetId};
 use ic_btc_interface::NetworkInRequest as BitcoinNetwork;
 use ic_error_types::UserError;
 use ic_management_canister_types_private::{
-    BitcoinGetBalanceArgs, BitcoinGetBlockHeadersArgs, BitcoinGetCurrentFeePercentilesArgs,
-    BitcoinGetUtxosArgs, BitcoinSendTransactionArgs, CanisterIdRecord, CanisterInfoRequest,
-    ClearChunkStoreArgs, ComputeInitialIDkgDealingsArgs, DeleteCanisterSnapshotArgs,
-    ECDSAPublicKeyArgs, InstallChunkedCodeArgs, InstallCodeArgsV2, ListCanisterSnapshotArgs,
-    LoadCanisterSnapshotArgs, MasterPublicKeyId, Method as Ic00Method, NodeMetricsHistoryArgs,
-    Payload, ProvisionalTopUpCanisterArgs, ReadCanisterSnapshotDataArgs,
+    BitcoinGetBalanceArgs, BitcoinGetBlockFilterArgs, BitcoinGetBlockHeadersArgs,
+    BitcoinGetCurrentFeePercentilesArgs, BitcoinGetUtxosArgs, BitcoinSendTransactionArgs,
+    CanisterIdRecord, CanisterInfoRequest, ClearChunkStoreArgs, ComputeInitialIDkgDealingsArgs,
+    DeleteCanisterSnapshotArgs, ECDSAPublicKeyArgs, InstallChunkedCodeArgs, InstallCodeArgsV2,
+    ListCanisterSnapshotArgs, LoadCanisterSnapshotArgs, MasterPublicKeyId, Method as Ic00Method,
+    NodeMetricsHistoryArgs, Payload, ProvisionalTopUpCanisterArgs, ReadCanisterSnapshotDataArgs,
     ReadCanisterSnapshotMetadataArgs, ReshareChainKeyArgs, SchnorrPublicKeyArgs, SignWithECDSAArgs,
     SignWithSchnorrArgs, StoredChunksArgs, SubnetInfoArgs, TakeCanisterSnapshotArgs,
     UninstallCodeArgs, UpdateSettingsArgs, UploadCanisterSnapshotDataArgs,
@@ -166,6 +166,14 @@ pub(super) fn resolve_destination(
                 own_subnet,
             ))
         }
+        Ok(Ic00Method::BitcoinGetBlockFilter) => {
+            let args = BitcoinGetBlockFilterArgs::decode(payload)?;
+            Ok(route_bitcoin_message(
+                args.network,
+                network_topology,
+                own_subnet,
+            ))
+        }
         Ok(Ic00Method::NodeMetricsHistory) => {
             Ok(NodeMetricsHistoryArgs::decode(payload)?.subnet_id)
         }
@@ -272,6 +272,7 @@ impl SystemStateModifications {
             | Ok(Ic00Method::BitcoinGetBlockHeaders)
             | Ok(Ic00Method::BitcoinSendTransaction)
             | Ok(Ic00Method::BitcoinGetCurrentFeePercentiles)
+            | Ok(Ic00Method::BitcoinGetBlockFilter)
             | Ok(Ic00Method::NodeMetricsHistory)
             | Ok(Ic00Method::SubnetInfo)
             | Ok(Ic00Method::FetchCanisterLogs)
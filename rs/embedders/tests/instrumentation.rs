@@ -14,6 +14,7 @@ use ic_wasm_types::BinaryEncodedWasm;
 use insta::assert_snapshot;
 use pretty_assertions::assert_eq;
 
+use ic_embedders::wasm_utils::coverage::CoverageMap;
 use ic_embedders::wasm_utils::instrumentation::instruction_to_cost;
 use ic_embedders::wasm_utils::instrumentation::WasmMemoryType;
 use ic_embedders::wasmtime_embedder::{system_api_complexity, WasmtimeInstance};
@@ -1362,3 +1363,90 @@ fn test_wasm64_costs_similar_to_wasm32_for_arithmetic_instructions() {
     // Check that the cost in Wasm64 mode is similar to Wasm32 mode.
     assert_eq!(total_cost, total_cost_wasm32);
 }
+
+#[test]
+fn wasm_coverage_counts_basic_block_hits() {
+    let wat = r#"
+        (module
+            (func $f (param i32) (result i32)
+                (if (result i32) (local.get 0)
+                    (then (i32.const 1))
+                    (else (i32.const 2))
+                )
+            )
+            (func $test (export "canister_update test")
+                (drop (call $f (i32.const 0)))
+                (drop (call $f (i32.const 1)))
+                (drop (call $f (i32.const 1)))
+            )
+        )"#;
+    let run = |wasm_coverage| {
+        let mut embedder_config = EmbeddersConfig::default();
+        embedder_config.feature_flags.wasm_coverage = wasm_coverage;
+        let mut instance = WasmtimeInstanceBuilder::new()
+            .with_config(embedder_config)
+            .with_wat(wat)
+            .with_num_instructions(NumInstructions::new(10000))
+            .build();
+        instance.run(func_ref("test")).unwrap();
+        (
+            instr_used(&mut instance),
+            instance.get_exported_globals().unwrap(),
+        )
+    };
+
+    let (instructions, globals) = run(FlagStatus::Disabled);
+    let (instructions_with_coverage, globals_with_coverage) = run(FlagStatus::Enabled);
+    // Coverage counters are not metered.
+    assert_eq!(instructions, instructions_with_coverage);
+
+    let wasm = BinaryEncodedWasm::new(wat::parse_str(wat).unwrap());
+    let coverage_map = CoverageMap::new(&wasm).unwrap();
+    let functions: Vec<_> = coverage_map
+        .blocks
+        .iter()
+        .map(|block| (block.function_index, block.function_name.as_deref()))
+        .collect();
+    assert_eq!(
+        functions,
+        vec![
+            (0, Some("f")),
+            (0, Some("f")),
+            (0, Some("f")),
+            (0, Some("f")),
+            (1, Some("test")),
+        ]
+    );
+    assert_eq!(
+        globals_with_coverage.len(),
+        globals.len() + coverage_map.blocks.len()
+    );
+    let hits: Vec<_> = globals_with_coverage[globals.len()..]
+        .iter()
+        .map(|global| match global {
+            Global::I64(hits) => *hits,
+            _ => panic!("unexpected coverage counter {:?}", global),
+        })
+        .collect();
+    // Entry, `then`, `else`, and the end of `$f` followed by the entry of `$test`.
+    assert_eq!(hits, vec![3, 2, 1, 3, 1]);
+}
+
+#[test]
+fn exporting_coverage_counter_prefix_is_only_reserved_with_wasm_coverage() {
+    let wat = r#"
+        (module
+            (global (export "canister coverage_counter_0") (mut i64) (i64.const 0))
+        )"#;
+    let compile = |wasm_coverage| {
+        let mut embedder_config = EmbeddersConfig::default();
+        embedder_config.feature_flags.wasm_coverage = wasm_coverage;
+        let embedder = WasmtimeEmbedder::new(embedder_config, no_op_logger());
+        let wasm = wat::parse_str(wat).unwrap();
+        wasm_utils::compile(&embedder, &BinaryEncodedWasm::new(wasm)).1
+    };
+
+    compile(FlagStatus::Disabled).unwrap();
+    let err = compile(FlagStatus::Enabled).unwrap_err();
+    assert!(err.to_string().contains("reserved symbol"));
+}
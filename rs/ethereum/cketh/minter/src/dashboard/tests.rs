@@ -19,8 +19,8 @@ use ic_cketh_minter::state::transactions::{
 };
 use ic_cketh_minter::state::{MintedEvent, State};
 use ic_cketh_minter::tx::{
-    Eip1559Signature, Eip1559TransactionRequest, GasFeeEstimate, SignedEip1559TransactionRequest,
-    TransactionPrice,
+    Eip1559Signature, Eip1559TransactionRequest, FeeModel, GasFeeEstimate,
+    SignedEip1559TransactionRequest, TransactionPrice,
 };
 use ic_ethereum_types::Address;
 use maplit::{btreemap, btreeset};
@@ -438,6 +438,9 @@ fn should_display_correct_cketh_token_symbol_based_on_network() {
     }
     test(EthereumNetwork::Sepolia, "ckSepoliaETH");
     test(EthereumNetwork::Mainnet, "ckETH");
+    test(EthereumNetwork::ArbitrumOne, "ckArbETH");
+    test(EthereumNetwork::Base, "ckBaseETH");
+    test(EthereumNetwork::Optimism, "ckOpETH");
 }
 
 #[test]
@@ -756,6 +759,15 @@ fn should_display_etherscan_links_according_to_chosen_network() {
         |link| link.contains("etherscan.io/"),
         |link| link.starts_with("https://etherscan.io"),
     );
+
+    let base_dashboard = DashboardTemplate {
+        ethereum_network: EthereumNetwork::Base,
+        ..initial_dashboard()
+    };
+    DashboardAssert::assert_that(base_dashboard).has_links_satisfying(
+        |link| link.contains("/address/") || link.contains("/block/") || link.contains("/tx/"),
+        |link| link.starts_with("https://basescan.org"),
+    );
 }
 
 #[test]
@@ -1442,6 +1454,7 @@ fn cketh_withdrawal_flow(
         gas_used: signed_tx.transaction().gas_limit,
         status: tx_status,
         transaction_hash: signed_tx.hash(),
+        l1_fee: None,
     };
     (
         withdrawal_request.into(),
@@ -1474,6 +1487,7 @@ fn ckerc20_withdrawal_flow(
         gas_fee,
         GasAmount::from(65_000_u32),
         EthereumNetwork::Sepolia,
+        &FeeModel::Eip1559,
     )
     .unwrap();
     let dummy_signature = Eip1559Signature {
@@ -1495,6 +1509,7 @@ fn ckerc20_withdrawal_flow(
         gas_used: signed_tx.transaction().gas_limit,
         status: tx_status,
         transaction_hash: signed_tx.hash(),
+        l1_fee: None,
     };
     (
         withdrawal_request.into(),
@@ -147,9 +147,18 @@ pub async fn scrape_logs() {
         }
     };
     let max_block_spread = read_state(|s| s.max_block_spread_for_logs_scraping());
-    scrape_until_block::<ReceivedEthLogScraping>(last_block_number, max_block_spread).await;
-    scrape_until_block::<ReceivedErc20LogScraping>(last_block_number, max_block_spread).await;
-    scrape_until_block::<ReceivedEthOrErc20LogScraping>(last_block_number, max_block_spread).await;
+    let max_calls = read_state(|s| s.ethereum_network().max_logs_scraping_calls_per_run())
+        .unwrap_or(usize::MAX);
+    scrape_until_block::<ReceivedEthLogScraping>(last_block_number, max_block_spread, max_calls)
+        .await;
+    scrape_until_block::<ReceivedErc20LogScraping>(last_block_number, max_block_spread, max_calls)
+        .await;
+    scrape_until_block::<ReceivedEthOrErc20LogScraping>(
+        last_block_number,
+        max_block_spread,
+        max_calls,
+    )
+    .await;
 }
 
 pub async fn update_last_observed_block_number() -> Option<BlockNumber> {
@@ -173,8 +182,11 @@ pub async fn update_last_observed_block_number() -> Option<BlockNumber> {
     }
 }
 
-async fn scrape_until_block<S>(last_block_number: BlockNumber, max_block_spread: u16)
-where
+async fn scrape_until_block<S>(
+    last_block_number: BlockNumber,
+    max_block_spread: u16,
+    max_calls: usize,
+) where
     S: LogScraping,
 {
     let scrape = match read_state(S::next_scrape) {
@@ -201,7 +213,8 @@ where
         S::ID
     );
     let rpc_client = read_state(EthRpcClient::from_state);
-    for block_range in block_range.into_chunks(max_block_spread) {
+    // Remaining blocks are scraped in the next run, starting from the last scraped block.
+    for block_range in block_range.into_chunks(max_block_spread).take(max_calls) {
         match scrape_block_range::<S>(
             &rpc_client,
             scrape.contract_address,
@@ -243,6 +243,37 @@ impl From<LedgerBurnError> for WithdrawalError {
     }
 }
 
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct WithdrawalReplacementArg {
+    pub withdrawal_id: u64,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub enum WithdrawalReplacementError {
+    WithdrawalNotFound,
+    CallerNotAuthorized,
+    TransactionNotSent,
+    AlreadyFinalized,
+    AlreadyCancelled,
+    InsufficientTransactionFee {
+        allowed_max_transaction_fee: Nat,
+        max_transaction_fee: Nat,
+    },
+    TemporarilyUnavailable(String),
+}
+
+impl From<transactions::ReplacementRequestError> for WithdrawalReplacementError {
+    fn from(error: transactions::ReplacementRequestError) -> Self {
+        use transactions::ReplacementRequestError;
+        match error {
+            ReplacementRequestError::WithdrawalNotFound => Self::WithdrawalNotFound,
+            ReplacementRequestError::TransactionNotSent => Self::TransactionNotSent,
+            ReplacementRequestError::AlreadyFinalized => Self::AlreadyFinalized,
+            ReplacementRequestError::AlreadyCancelled => Self::AlreadyCancelled,
+        }
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
 pub enum WithdrawalSearchParameter {
     ByWithdrawalId(u64),
@@ -335,6 +366,12 @@ pub mod events {
         },
     }
 
+    #[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+    pub enum ReplacementRequest {
+        BumpFee,
+        Cancel,
+    }
+
     #[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
     pub struct AccessListItem {
         pub address: String,
@@ -368,6 +405,7 @@ pub mod events {
         pub gas_used: Nat,
         pub status: TransactionStatus,
         pub transaction_hash: String,
+        pub l1_fee: Option<Nat>,
     }
 
     #[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
@@ -488,5 +526,19 @@ pub mod events {
         QuarantinedReimbursement {
             index: ReimbursementIndex,
         },
+        RequestedWithdrawalReplacement {
+            withdrawal_id: Nat,
+            request: ReplacementRequest,
+            requested_by: Principal,
+        },
+        CreatedCancellationTransaction {
+            withdrawal_id: Nat,
+            transaction: UnsignedTransaction,
+        },
+        FailedWithdrawalReplacement {
+            withdrawal_id: Nat,
+            allowed_max_transaction_fee: Nat,
+            max_transaction_fee: Nat,
+        },
     }
 }
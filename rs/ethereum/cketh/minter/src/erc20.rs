@@ -42,6 +42,9 @@ impl CkTokenSymbol {
         match state.ethereum_network {
             EthereumNetwork::Mainnet => Self::from_str("ckETH").unwrap(),
             EthereumNetwork::Sepolia => Self::from_str("ckSepoliaETH").unwrap(),
+            EthereumNetwork::ArbitrumOne => Self::from_str("ckArbETH").unwrap(),
+            EthereumNetwork::Base => Self::from_str("ckBaseETH").unwrap(),
+            EthereumNetwork::Optimism => Self::from_str("ckOpETH").unwrap(),
         }
     }
 }
@@ -231,6 +231,15 @@ pub enum JsonRpcResult<T> {
     Error { code: i64, message: String },
 }
 
+/// A JSON-RPC reply, as returned by raw requests to the EVM RPC canister.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct JsonRpcReply<T> {
+    pub id: u64,
+    pub jsonrpc: String,
+    #[serde(flatten)]
+    pub result: JsonRpcResult<T>,
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub enum HttpOutcallError {
     /// Error from the IC system API.
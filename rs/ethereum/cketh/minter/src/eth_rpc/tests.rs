@@ -12,3 +12,22 @@ fn should_deserialize_event_data_from_str() {
 
     assert_eq!(parsed_data, Ok(expected_data));
 }
+
+#[test]
+fn should_deserialize_json_rpc_reply() {
+    let reply: JsonRpcReply<String> =
+        serde_json::from_str(r#"{"jsonrpc":"2.0","id":1,"result":"0x1"}"#).unwrap();
+    assert_eq!(reply.result, JsonRpcResult::Result("0x1".to_string()));
+
+    let reply: JsonRpcReply<String> = serde_json::from_str(
+        r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"header not found"}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        reply.result,
+        JsonRpcResult::Error {
+            code: -32000,
+            message: "header not found".to_string()
+        }
+    );
+}
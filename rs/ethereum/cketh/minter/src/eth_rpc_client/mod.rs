@@ -1,23 +1,26 @@
 use crate::eth_rpc::{
-    Data, FixedSizeData, Hash, HttpOutcallError, LogEntry, Quantity, HEADER_SIZE_LIMIT,
+    Data, FixedSizeData, Hash, HttpOutcallError, JsonRpcReply, JsonRpcResult, LogEntry, Quantity,
+    HEADER_SIZE_LIMIT,
 };
 use crate::eth_rpc_client::responses::{TransactionReceipt, TransactionStatus};
 use crate::lifecycle::EthereumNetwork;
 use crate::logs::{PrintProxySink, INFO, TRACE_HTTP};
-use crate::numeric::{BlockNumber, GasAmount, LogIndex, TransactionCount, WeiPerGas};
+use crate::numeric::{BlockNumber, GasAmount, LogIndex, TransactionCount, Wei, WeiPerGas};
 use crate::state::State;
 use candid::Nat;
 use evm_rpc_client::{
     Block, BlockTag, ConsensusStrategy, EthSepoliaService, EvmRpcClient, FeeHistory,
     FeeHistoryArgs, GetLogsArgs, GetTransactionCountArgs as EvmGetTransactionCountArgs, Hex20,
-    IcRuntime, LogEntry as EvmLogEntry, MultiRpcResult as EvmMultiRpcResult, Nat256,
-    OverrideRpcConfig, RpcConfig as EvmRpcConfig, RpcError as EvmRpcError,
+    IcRuntime, L2MainnetService, LogEntry as EvmLogEntry, MultiRpcResult as EvmMultiRpcResult,
+    Nat256, OverrideRpcConfig, RpcConfig as EvmRpcConfig, RpcError as EvmRpcError,
     RpcResult as EvmRpcResult, RpcService as EvmRpcService, RpcServices as EvmRpcServices,
     SendRawTransactionStatus, TransactionReceipt as EvmTransactionReceipt,
 };
+use futures::future::join_all;
 use ic_canister_log::log;
 use ic_ethereum_types::Address;
 use num_traits::ToPrimitive;
+use serde::Deserialize;
 use std::collections::{BTreeMap, BTreeSet};
 use std::convert::Infallible;
 use std::fmt::{Debug, Display};
@@ -29,10 +32,13 @@ mod tests;
 
 // We expect most of the calls to contain zero events.
 const ETH_GET_LOGS_INITIAL_RESPONSE_SIZE_ESTIMATE: u64 = 100;
+// Receipts of withdrawal transactions contain at most one event.
+const ETH_GET_TRANSACTION_RECEIPT_MAX_RESPONSE_SIZE: u64 = 10_000;
 const TOTAL_NUMBER_OF_PROVIDERS: u8 = 4;
 
 #[derive(Debug)]
 pub struct EthRpcClient {
+    chain: EthereumNetwork,
     evm_rpc_client: EvmRpcClient<IcRuntime, PrintProxySink>,
 }
 
@@ -50,9 +56,15 @@ impl EthRpcClient {
                 EthSepoliaService::Alchemy,
                 EthSepoliaService::Ankr,
             ])),
+            EthereumNetwork::ArbitrumOne => EvmRpcServices::ArbitrumOne(Some(l2_providers())),
+            EthereumNetwork::Base => EvmRpcServices::BaseMainnet(Some(l2_providers())),
+            EthereumNetwork::Optimism => EvmRpcServices::OptimismMainnet(Some(l2_providers())),
         };
         let min_threshold = match chain {
-            EthereumNetwork::Mainnet => 3_u8,
+            EthereumNetwork::Mainnet
+            | EthereumNetwork::ArbitrumOne
+            | EthereumNetwork::Base
+            | EthereumNetwork::Optimism => 3_u8,
             EthereumNetwork::Sepolia => 2_u8,
         };
         assert!(
@@ -85,7 +97,10 @@ impl EthRpcClient {
             })
             .build();
 
-        Self { evm_rpc_client }
+        Self {
+            chain,
+            evm_rpc_client,
+        }
     }
 
     pub async fn eth_get_logs(
@@ -121,6 +136,58 @@ impl EthRpcClient {
             .into()
     }
 
+    /// Retrieves the L1 fee charged for posting the given transaction to Ethereum,
+    /// as reported in the transaction receipt by OP Stack networks.
+    ///
+    /// The typed receipt returned by the EVM RPC canister does not contain that field,
+    /// so the receipt is queried from each provider with a raw JSON-RPC request.
+    pub async fn eth_get_transaction_l1_fee(
+        &self,
+        tx_hash: Hash,
+    ) -> Result<Option<Wei>, MultiCallError<Option<Wei>>> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct L1FeeReceipt {
+            l1_fee: Option<Wei>,
+        }
+
+        fn parse_l1_fee(reply: String) -> Result<Option<Wei>, String> {
+            let reply: JsonRpcReply<Option<L1FeeReceipt>> = serde_json::from_str(&reply)
+                .map_err(|e| format!("failed to parse transaction receipt: {e}"))?;
+            match reply.result {
+                JsonRpcResult::Result(receipt) => Ok(receipt.and_then(|r| r.l1_fee)),
+                JsonRpcResult::Error { code, message } => {
+                    Err(format!("JSON-RPC error {code}: {message}"))
+                }
+            }
+        }
+
+        let providers = l2_services(self.chain);
+        if providers.is_empty() {
+            return Ok(None);
+        }
+        let payload = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_getTransactionReceipt",
+            "params": [tx_hash.to_string()],
+            "id": 1,
+        })
+        .to_string();
+        let results = join_all(providers.iter().map(|provider| {
+            self.evm_rpc_client.request(
+                provider.clone(),
+                payload.clone(),
+                ETH_GET_TRANSACTION_RECEIPT_MAX_RESPONSE_SIZE + HEADER_SIZE_LIMIT,
+            )
+        }))
+        .await;
+        ReducedResult::from_internal(EvmMultiRpcResult::Inconsistent(
+            providers.into_iter().zip(results).collect(),
+        ))
+        .map_reduce(&parse_l1_fee, MultiCallResults::reduce_with_equality)
+        .into()
+    }
+
     pub async fn eth_fee_history(
         &self,
         params: FeeHistoryArgs,
@@ -172,6 +239,27 @@ impl EthRpcClient {
     }
 }
 
+/// Providers queried with raw JSON-RPC requests on L2 networks.
+fn l2_services(chain: EthereumNetwork) -> Vec<EvmRpcService> {
+    let service: fn(L2MainnetService) -> EvmRpcService = match chain {
+        EthereumNetwork::Mainnet | EthereumNetwork::Sepolia => return vec![],
+        EthereumNetwork::ArbitrumOne => EvmRpcService::ArbitrumOne,
+        EthereumNetwork::Base => EvmRpcService::BaseMainnet,
+        EthereumNetwork::Optimism => EvmRpcService::OptimismMainnet,
+    };
+    l2_providers().into_iter().map(service).collect()
+}
+
+/// Providers queried on L2 networks, whose number must match `TOTAL_NUMBER_OF_PROVIDERS`.
+fn l2_providers() -> Vec<L2MainnetService> {
+    vec![
+        L2MainnetService::Alchemy,
+        L2MainnetService::Ankr,
+        L2MainnetService::BlockPi,
+        L2MainnetService::PublicNode,
+    ]
+}
+
 /// Aggregates responses of different providers to the same query.
 /// Guaranteed to be non-empty.
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -514,6 +602,7 @@ impl Reduce for EvmMultiRpcResult<Option<EvmTransactionReceipt>> {
                                 .ok_or("invalid transaction status")?,
                         )?,
                         transaction_hash: Hash(evm_receipt.transaction_hash.into()),
+                        l1_fee: None,
                     })
                 })
                 .transpose()
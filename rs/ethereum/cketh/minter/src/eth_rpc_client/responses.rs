@@ -30,12 +30,23 @@ pub struct TransactionReceipt {
     /// The hash of the transaction
     #[n(5)]
     pub transaction_hash: Hash,
+
+    /// The fee paid for posting the transaction data to Ethereum.
+    /// Only reported by rollups and not part of `effective_gas_price * gas_used`.
+    #[n(6)]
+    #[serde(default)]
+    pub l1_fee: Option<Wei>,
 }
 
 impl TransactionReceipt {
+    /// The total fee paid for the transaction, including the L1 fee on rollups.
     pub fn effective_transaction_fee(&self) -> Wei {
-        self.effective_gas_price
+        let l2_fee = self
+            .effective_gas_price
             .transaction_cost(self.gas_used)
+            .expect("ERROR: overflow during transaction fee calculation");
+        l2_fee
+            .checked_add(self.l1_fee.unwrap_or(Wei::ZERO))
             .expect("ERROR: overflow during transaction fee calculation")
     }
 }
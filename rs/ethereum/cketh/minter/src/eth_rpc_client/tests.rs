@@ -530,6 +530,7 @@ mod eth_get_transaction_receipt {
                     "0x0e59bd032b9b22aca5e2784e4cf114783512db00988c716cf17a1cc755a0a93d"
                 )
                 .unwrap(),
+                l1_fee: None,
             }
         )
     }
@@ -1,6 +1,9 @@
 //! Module dealing with the lifecycle methods of the ckETH Minter.
+use crate::endpoints::CandidBlockTag;
 use crate::lifecycle::init::InitArg;
 use crate::lifecycle::upgrade::UpgradeArg;
+use crate::numeric::{GasAmount, Wei};
+use crate::tx::FeeModel;
 use candid::{CandidType, Deserialize};
 use minicbor::{Decode, Encode};
 use std::fmt::{Display, Formatter};
@@ -29,6 +32,12 @@ pub enum EthereumNetwork {
     #[n(11155111)]
     #[default]
     Sepolia,
+    #[n(42161)]
+    ArbitrumOne,
+    #[n(8453)]
+    Base,
+    #[n(10)]
+    Optimism,
 }
 
 impl EthereumNetwork {
@@ -36,6 +45,73 @@ impl EthereumNetwork {
         match self {
             EthereumNetwork::Mainnet => 1,
             EthereumNetwork::Sepolia => 11155111,
+            EthereumNetwork::ArbitrumOne => 42161,
+            EthereumNetwork::Base => 8453,
+            EthereumNetwork::Optimism => 10,
+        }
+    }
+
+    /// How the network charges for a transaction on top of the gas used by its execution,
+    /// unless configured otherwise by an upgrade.
+    ///
+    /// Rollups also charge for posting the transaction data to Ethereum,
+    /// which is not captured by the EIP-1559 fee estimate.
+    pub fn default_fee_model(&self) -> FeeModel {
+        match self {
+            EthereumNetwork::Mainnet | EthereumNetwork::Sepolia => FeeModel::Eip1559,
+            EthereumNetwork::ArbitrumOne => FeeModel::ArbitrumL1Gas {
+                l1_gas_buffer: GasAmount::new(50_000),
+            },
+            EthereumNetwork::Base | EthereumNetwork::Optimism => FeeModel::OpStackL1DataFee {
+                max_l1_data_fee: Wei::new(50_000_000_000_000),
+            },
+        }
+    }
+
+    /// Whether the given block tag is final enough to scrape deposits up to that block.
+    ///
+    /// Rollup blocks that are not yet finalized only carry the promise of the sequencer
+    /// and are reorged if the corresponding batch is not posted to Ethereum,
+    /// so only the `Finalized` block tag is accepted there.
+    pub fn is_block_tag_final_enough(&self, block_tag: &CandidBlockTag) -> bool {
+        match self {
+            EthereumNetwork::Mainnet | EthereumNetwork::Sepolia => true,
+            EthereumNetwork::ArbitrumOne | EthereumNetwork::Base | EthereumNetwork::Optimism => {
+                block_tag == &CandidBlockTag::Finalized
+            }
+        }
+    }
+
+    /// Maximum number of blocks whose logs can be requested in a single `eth_getLogs` call.
+    pub fn max_block_spread_for_logs_scraping(&self) -> u16 {
+        // Limit set by the EVM-RPC canister itself for all networks, see
+        // https://github.com/internet-computer-protocol/evm-rpc-canister/blob/3cce151d4c1338d83e6741afa354ccf11dff41e8/src/candid_rpc.rs#L192
+        500_u16
+    }
+
+    /// Maximum number of `eth_getLogs` calls made for a single contract in one run
+    /// of the log scraping task, if any.
+    ///
+    /// Rollups produce blocks much faster than Ethereum (e.g., every 250ms on Arbitrum One),
+    /// so that catching up on a large range of blocks is spread over several runs
+    /// instead of issuing thousands of calls at once.
+    pub fn max_logs_scraping_calls_per_run(&self) -> Option<usize> {
+        match self {
+            EthereumNetwork::Mainnet | EthereumNetwork::Sepolia => None,
+            EthereumNetwork::ArbitrumOne | EthereumNetwork::Base | EthereumNetwork::Optimism => {
+                Some(20)
+            }
+        }
+    }
+
+    /// Base URL of the block explorer used to link addresses, blocks and transactions.
+    pub fn block_explorer_url(&self) -> &'static str {
+        match self {
+            EthereumNetwork::Mainnet => "https://etherscan.io",
+            EthereumNetwork::Sepolia => "https://sepolia.etherscan.io",
+            EthereumNetwork::ArbitrumOne => "https://arbiscan.io",
+            EthereumNetwork::Base => "https://basescan.org",
+            EthereumNetwork::Optimism => "https://optimistic.etherscan.io",
         }
     }
 }
@@ -47,6 +123,9 @@ impl TryFrom<u64> for EthereumNetwork {
         match value {
             1 => Ok(EthereumNetwork::Mainnet),
             11155111 => Ok(EthereumNetwork::Sepolia),
+            42161 => Ok(EthereumNetwork::ArbitrumOne),
+            8453 => Ok(EthereumNetwork::Base),
+            10 => Ok(EthereumNetwork::Optimism),
             _ => Err("Unknown Ethereum Network".to_string()),
         }
     }
@@ -57,6 +136,9 @@ impl Display for EthereumNetwork {
         match self {
             EthereumNetwork::Mainnet => write!(f, "Ethereum Mainnet"),
             EthereumNetwork::Sepolia => write!(f, "Ethereum Testnet Sepolia"),
+            EthereumNetwork::ArbitrumOne => write!(f, "Arbitrum One"),
+            EthereumNetwork::Base => write!(f, "Base"),
+            EthereumNetwork::Optimism => write!(f, "OP Mainnet"),
         }
     }
 }
@@ -74,7 +74,10 @@ impl TryFrom<InitArg> for State {
                     )
                 })?;
         let evm_rpc_id = evm_rpc_id.unwrap_or(match ethereum_network {
-            EthereumNetwork::Mainnet => EVM_RPC_ID_PRODUCTION,
+            EthereumNetwork::Mainnet
+            | EthereumNetwork::ArbitrumOne
+            | EthereumNetwork::Base
+            | EthereumNetwork::Optimism => EVM_RPC_ID_PRODUCTION,
             EthereumNetwork::Sepolia => EVM_RPC_ID_STAGING,
         });
         let mut log_scrapings = LogScrapings::new(last_scraped_block_number);
@@ -89,7 +92,8 @@ impl TryFrom<InitArg> for State {
             ethereum_network,
             ecdsa_key_name,
             pending_withdrawal_principals: Default::default(),
-            eth_transactions: EthTransactions::new(initial_nonce),
+            eth_transactions: EthTransactions::new(initial_nonce)
+                .with_fee_model(ethereum_network.default_fee_model()),
             cketh_ledger_id: ledger_id,
             cketh_minimum_withdrawal_amount: minimum_withdrawal_amount,
             ethereum_block_height,
@@ -1,9 +1,12 @@
 mod init {
+    use crate::endpoints::CandidBlockTag;
     use crate::lifecycle::init::InitArg;
+    use crate::lifecycle::EthereumNetwork;
     use crate::numeric::{TransactionNonce, Wei};
     use crate::state::eth_logs_scraping::LogScrapingId;
     use crate::state::{InvalidStateError, State};
     use crate::test_fixtures::valid_init_arg;
+    use crate::EVM_RPC_ID_PRODUCTION;
     use assert_matches::assert_matches;
     use candid::{Nat, Principal};
     use num_bigint::BigUint;
@@ -71,6 +74,43 @@ mod init {
             }),
             Err(InvalidStateError::InvalidLastScrapedBlockNumber(_))
         );
+
+        for network in [
+            EthereumNetwork::ArbitrumOne,
+            EthereumNetwork::Base,
+            EthereumNetwork::Optimism,
+        ] {
+            for block_tag in [CandidBlockTag::Latest, CandidBlockTag::Safe] {
+                assert_matches!(
+                    State::try_from(InitArg {
+                        ethereum_network: network,
+                        ethereum_block_height: block_tag,
+                        ..valid_init_arg()
+                    }),
+                    Err(InvalidStateError::InvalidEthereumBlockHeight(_))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn should_succeed_on_l2_network_with_finalized_block_height() {
+        for network in [
+            EthereumNetwork::ArbitrumOne,
+            EthereumNetwork::Base,
+            EthereumNetwork::Optimism,
+        ] {
+            let state = State::try_from(InitArg {
+                ethereum_network: network,
+                ethereum_block_height: CandidBlockTag::Finalized,
+                evm_rpc_id: None,
+                ..valid_init_arg()
+            })
+            .expect("valid init args");
+
+            assert_eq!(state.ethereum_network, network);
+            assert_eq!(state.evm_rpc_id, EVM_RPC_ID_PRODUCTION);
+        }
     }
 
     #[test]
@@ -98,3 +138,86 @@ mod init {
         );
     }
 }
+
+mod ethereum_network {
+    use crate::lifecycle::EthereumNetwork;
+    use crate::numeric::{GasAmount, Wei};
+    use crate::tx::FeeModel;
+
+    const ALL_NETWORKS: [EthereumNetwork; 5] = [
+        EthereumNetwork::Mainnet,
+        EthereumNetwork::Sepolia,
+        EthereumNetwork::ArbitrumOne,
+        EthereumNetwork::Base,
+        EthereumNetwork::Optimism,
+    ];
+
+    #[test]
+    fn should_convert_from_and_to_chain_id() {
+        for network in ALL_NETWORKS {
+            assert_eq!(EthereumNetwork::try_from(network.chain_id()), Ok(network));
+        }
+        assert_eq!(
+            ALL_NETWORKS.map(|network| network.chain_id()),
+            [1, 11155111, 42161, 8453, 10]
+        );
+        assert!(EthereumNetwork::try_from(5_u64).is_err());
+    }
+
+    #[test]
+    fn should_encode_decode_with_chain_id_as_cbor_index() {
+        for network in ALL_NETWORKS {
+            let mut buf = vec![];
+            minicbor::encode(network, &mut buf).expect("encoding should succeed");
+            assert_eq!(
+                minicbor::decode::<u64>(&buf).expect("decoding as u64 should succeed"),
+                network.chain_id()
+            );
+            assert_eq!(
+                minicbor::decode::<EthereumNetwork>(&buf).expect("decoding should succeed"),
+                network
+            );
+        }
+    }
+
+    #[test]
+    fn should_have_l1_fee_model_only_on_ethereum() {
+        assert_eq!(
+            EthereumNetwork::Mainnet.default_fee_model(),
+            FeeModel::Eip1559
+        );
+        assert_eq!(
+            EthereumNetwork::Sepolia.default_fee_model(),
+            FeeModel::Eip1559
+        );
+        assert_eq!(
+            EthereumNetwork::ArbitrumOne.default_fee_model(),
+            FeeModel::ArbitrumL1Gas {
+                l1_gas_buffer: GasAmount::new(50_000)
+            }
+        );
+        for network in [EthereumNetwork::Base, EthereumNetwork::Optimism] {
+            assert_eq!(
+                network.default_fee_model(),
+                FeeModel::OpStackL1DataFee {
+                    max_l1_data_fee: Wei::new(50_000_000_000_000)
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn should_bound_logs_scraping_calls_per_run_only_on_rollups() {
+        for network in [EthereumNetwork::Mainnet, EthereumNetwork::Sepolia] {
+            assert_eq!(network.max_logs_scraping_calls_per_run(), None);
+        }
+        for network in [
+            EthereumNetwork::ArbitrumOne,
+            EthereumNetwork::Base,
+            EthereumNetwork::Optimism,
+        ] {
+            assert_eq!(network.max_logs_scraping_calls_per_run(), Some(20));
+            assert_eq!(network.max_block_spread_for_logs_scraping(), 500);
+        }
+    }
+}
@@ -30,6 +30,12 @@ pub struct UpgradeArg {
     pub deposit_with_subaccount_helper_contract_address: Option<String>,
     #[cbor(n(9), with = "icrc_cbor::nat::option")]
     pub last_deposit_with_subaccount_scraped_block_number: Option<Nat>,
+    /// Additional L2 gas reserved for posting a transaction to Ethereum (Arbitrum only).
+    #[cbor(n(10), with = "icrc_cbor::nat::option")]
+    pub l1_gas_buffer: Option<Nat>,
+    /// Maximum L1 data fee set aside for a transaction, in wei (OP Stack networks only).
+    #[cbor(n(11), with = "icrc_cbor::nat::option")]
+    pub max_l1_data_fee: Option<Nat>,
 }
 
 pub fn post_upgrade(upgrade_args: Option<UpgradeArg>) {
@@ -14,7 +14,8 @@ use ic_cketh_minter::endpoints::events::{
 use ic_cketh_minter::endpoints::{
     AddCkErc20Token, Eip1559TransactionPrice, Eip1559TransactionPriceArg, Erc20Balance,
     GasFeeEstimate, MinterInfo, RetrieveEthRequest, RetrieveEthStatus, WithdrawalArg,
-    WithdrawalDetail, WithdrawalError, WithdrawalSearchParameter,
+    WithdrawalDetail, WithdrawalError, WithdrawalReplacementArg, WithdrawalReplacementError,
+    WithdrawalSearchParameter,
 };
 use ic_cketh_minter::erc20::CkTokenSymbol;
 use ic_cketh_minter::eth_logs::{
@@ -30,7 +31,7 @@ use ic_cketh_minter::state::audit::{process_event, Event, EventType};
 use ic_cketh_minter::state::eth_logs_scraping::{LogScrapingId, LogScrapingInfo};
 use ic_cketh_minter::state::transactions::{
     Erc20WithdrawalRequest, EthWithdrawalRequest, Reimbursed, ReimbursementIndex,
-    ReimbursementRequest,
+    ReimbursementRequest, ReplacementRequest, ResubmitTransactionError,
 };
 use ic_cketh_minter::state::{
     lazy_call_ecdsa_public_key, mutate_state, read_state, transactions, State, STATE,
@@ -186,9 +187,13 @@ async fn eip_1559_transaction_price(
             }
         }
     };
+    let fee_model = read_state(|s| s.eth_transactions.fee_model());
     match read_state(|s| s.last_transaction_price_estimate.clone()) {
         Some((ts, estimate)) => {
-            let mut result = Eip1559TransactionPrice::from(estimate.to_price(gas_limit));
+            let transaction_price = estimate.to_price(fee_model.gas_limit(gas_limit));
+            let max_transaction_fee = fee_model.max_transaction_fee(&transaction_price);
+            let mut result = Eip1559TransactionPrice::from(transaction_price);
+            result.max_transaction_fee = max_transaction_fee.into();
             result.timestamp = Some(ts);
             result
         }
@@ -370,6 +375,10 @@ async fn withdrawal_status(parameter: WithdrawalSearchParameter) -> Vec<Withdraw
                 },
                 max_transaction_fee: match (request, tx) {
                     (CkEth(_), None) => None,
+                    (CkEth(_), Some(tx)) if tx.is_cancellation() => Some(
+                        tx.max_transaction_fee(&s.eth_transactions.fee_model())
+                            .into(),
+                    ),
                     (CkEth(r), Some(tx)) => {
                         r.withdrawal_amount.checked_sub(tx.amount).map(|x| x.into())
                     }
@@ -386,6 +395,83 @@ async fn withdrawal_status(parameter: WithdrawalSearchParameter) -> Vec<Withdraw
     })
 }
 
+/// Replace the sent transaction of a stuck withdrawal by the same transaction with a higher fee.
+#[update]
+async fn bump_withdrawal_fee(
+    WithdrawalReplacementArg { withdrawal_id }: WithdrawalReplacementArg,
+) -> Result<(), WithdrawalReplacementError> {
+    request_withdrawal_replacement(withdrawal_id, ReplacementRequest::BumpFee).await
+}
+
+/// Cancel a stuck withdrawal by replacing its sent transaction by a zero-value self-transfer.
+/// Once the cancellation is finalized, the withdrawn amount minus the transaction fee is reimbursed.
+#[update]
+async fn cancel_withdrawal(
+    WithdrawalReplacementArg { withdrawal_id }: WithdrawalReplacementArg,
+) -> Result<(), WithdrawalReplacementError> {
+    request_withdrawal_replacement(withdrawal_id, ReplacementRequest::Cancel).await
+}
+
+async fn request_withdrawal_replacement(
+    withdrawal_id: u64,
+    request: ReplacementRequest,
+) -> Result<(), WithdrawalReplacementError> {
+    let caller = validate_caller_not_anonymous();
+    let withdrawal_id = LedgerBurnIndex::new(withdrawal_id);
+    let check_request = |s: &State| -> Result<(), WithdrawalReplacementError> {
+        let withdrawal_request = s
+            .eth_transactions
+            .check_replacement_request(&withdrawal_id, request)?;
+        if withdrawal_request.from() != caller && !ic_cdk::api::is_controller(&caller) {
+            return Err(WithdrawalReplacementError::CallerNotAuthorized);
+        }
+        Ok(())
+    };
+    read_state(check_request)?;
+
+    let gas_fee_estimate = lazy_refresh_gas_fee_estimate().await.ok_or_else(|| {
+        WithdrawalReplacementError::TemporarilyUnavailable(
+            "Failed to retrieve current gas fee".to_string(),
+        )
+    })?;
+    let minter_address = state::minter_address().await;
+
+    mutate_state(|s| {
+        // The state may have changed while awaiting the gas fee estimate.
+        check_request(s)?;
+        s.eth_transactions
+            .check_replacement_transaction_fee(
+                &withdrawal_id,
+                request,
+                gas_fee_estimate,
+                minter_address,
+            )
+            .map_err(
+                |ResubmitTransactionError::InsufficientTransactionFee {
+                     allowed_max_transaction_fee,
+                     max_transaction_fee,
+                     ..
+                 }| WithdrawalReplacementError::InsufficientTransactionFee {
+                    allowed_max_transaction_fee: allowed_max_transaction_fee.into(),
+                    max_transaction_fee: max_transaction_fee.into(),
+                },
+            )?;
+        log!(
+            INFO,
+            "[request_withdrawal_replacement]: {caller} requested {request:?} for withdrawal {withdrawal_id}"
+        );
+        process_event(
+            s,
+            EventType::RequestedWithdrawalReplacement {
+                withdrawal_id,
+                request,
+                requested_by: caller,
+            },
+        );
+        Ok(())
+    })
+}
+
 #[update]
 async fn withdraw_erc20(
     WithdrawErc20Arg {
@@ -544,12 +630,14 @@ async fn withdraw_erc20(
 }
 
 async fn estimate_erc20_transaction_fee() -> Option<Wei> {
+    let fee_model = read_state(|s| s.eth_transactions.fee_model());
     lazy_refresh_gas_fee_estimate()
         .await
         .map(|gas_fee_estimate| {
-            gas_fee_estimate
-                .to_price(CKERC20_WITHDRAWAL_TRANSACTION_GAS_LIMIT)
-                .max_transaction_fee()
+            fee_model.max_transaction_fee(
+                &gas_fee_estimate
+                    .to_price(fee_model.gas_limit(CKERC20_WITHDRAWAL_TRANSACTION_GAS_LIMIT)),
+            )
         })
 }
 
@@ -591,6 +679,7 @@ async fn get_canister_status() -> ic_cdk::api::management_canister::main::Canist
 fn get_events(arg: GetEventsArg) -> GetEventsResult {
     use ic_cketh_minter::endpoints::events::{
         AccessListItem, ReimbursementIndex as CandidReimbursementIndex,
+        ReplacementRequest as CandidReplacementRequest,
         TransactionReceipt as CandidTransactionReceipt,
         TransactionStatus as CandidTransactionStatus, UnsignedTransaction,
     };
@@ -667,6 +756,7 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                 TransactionStatus::Failure => CandidTransactionStatus::Failure,
             },
             transaction_hash: receipt.transaction_hash.to_string(),
+            l1_fee: receipt.l1_fee.map(|fee| fee.into()),
         }
     }
 
@@ -869,6 +959,34 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                 EventType::QuarantinedReimbursement { index } => EP::QuarantinedReimbursement {
                     index: map_reimbursement_index(index),
                 },
+                EventType::RequestedWithdrawalReplacement {
+                    withdrawal_id,
+                    request,
+                    requested_by,
+                } => EP::RequestedWithdrawalReplacement {
+                    withdrawal_id: withdrawal_id.get().into(),
+                    request: match request {
+                        ReplacementRequest::BumpFee => CandidReplacementRequest::BumpFee,
+                        ReplacementRequest::Cancel => CandidReplacementRequest::Cancel,
+                    },
+                    requested_by,
+                },
+                EventType::CreatedCancellationTransaction {
+                    withdrawal_id,
+                    transaction,
+                } => EP::CreatedCancellationTransaction {
+                    withdrawal_id: withdrawal_id.get().into(),
+                    transaction: map_unsigned_transaction(transaction),
+                },
+                EventType::FailedWithdrawalReplacement {
+                    withdrawal_id,
+                    allowed_max_transaction_fee,
+                    max_transaction_fee,
+                } => EP::FailedWithdrawalReplacement {
+                    withdrawal_id: withdrawal_id.get().into(),
+                    allowed_max_transaction_fee: allowed_max_transaction_fee.into(),
+                    max_transaction_fee: max_transaction_fee.into(),
+                },
             },
         }
     }
@@ -8,11 +8,11 @@ use crate::lifecycle::EthereumNetwork;
 use crate::logs::DEBUG;
 use crate::map::DedupMultiKeyMap;
 use crate::numeric::{
-    BlockNumber, Erc20Value, LedgerBurnIndex, LedgerMintIndex, TransactionNonce, Wei,
+    BlockNumber, Erc20Value, GasAmount, LedgerBurnIndex, LedgerMintIndex, TransactionNonce, Wei,
 };
 use crate::state::eth_logs_scraping::{LogScrapingId, LogScrapings};
 use crate::state::transactions::{Erc20WithdrawalRequest, TransactionCallData, WithdrawalRequest};
-use crate::tx::GasFeeEstimate;
+use crate::tx::{FeeModel, GasFeeEstimate};
 use candid::Principal;
 use ic_canister_log::log;
 use ic_cdk::api::management_canister::ecdsa::EcdsaPublicKeyResponse;
@@ -110,8 +110,10 @@ pub enum InvalidStateError {
     InvalidEthereumContractAddress(String),
     InvalidErc20HelperContractAddress(String),
     InvalidMinimumWithdrawalAmount(String),
+    InvalidEthereumBlockHeight(String),
     InvalidLastScrapedBlockNumber(String),
     InvalidLastErc20ScrapedBlockNumber(String),
+    InvalidFeeModel(String),
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -159,7 +161,10 @@ impl State {
             ));
         }
         let cketh_ledger_transfer_fee = match self.ethereum_network {
-            EthereumNetwork::Mainnet => Wei::new(2_000_000_000_000),
+            EthereumNetwork::Mainnet
+            | EthereumNetwork::ArbitrumOne
+            | EthereumNetwork::Base
+            | EthereumNetwork::Optimism => Wei::new(2_000_000_000_000),
             EthereumNetwork::Sepolia => Wei::new(10_000_000_000),
         };
         if self.cketh_minimum_withdrawal_amount < cketh_ledger_transfer_fee {
@@ -169,6 +174,15 @@ impl State {
                     .to_string(),
             ));
         }
+        if !self
+            .ethereum_network
+            .is_block_tag_final_enough(&self.ethereum_block_height)
+        {
+            return Err(InvalidStateError::InvalidEthereumBlockHeight(format!(
+                "ethereum_block_height {:?} is not final enough for {}",
+                self.ethereum_block_height, self.ethereum_network
+            )));
+        }
         Ok(())
     }
 
@@ -353,15 +367,25 @@ impl State {
             .get_processed_withdrawal_request(withdrawal_id)
             .expect("BUG: missing withdrawal request");
         let charged_tx_fee = match withdrawal_request {
+            // The withdrawal amount minus the cancellation transaction fee is reimbursed.
+            WithdrawalRequest::CkEth(_req) if tx.transaction().is_cancellation() => tx
+                .transaction()
+                .max_transaction_fee(&self.eth_transactions.fee_model()),
             WithdrawalRequest::CkEth(req) => req
                 .withdrawal_amount
                 .checked_sub(tx.transaction().amount)
                 .expect("BUG: withdrawal amount MUST always be at least the transaction amount"),
             WithdrawalRequest::CkErc20(req) => req.max_transaction_fee,
         };
-        let unspent_tx_fee = charged_tx_fee.checked_sub(tx_fee).expect(
-            "BUG: charged transaction fee MUST always be at least the effective transaction fee",
-        );
+        // The L1 data fee charged by rollups is only bounded by an estimate,
+        // so that the minter bears any excess over the amount that was set aside for it.
+        let unspent_tx_fee = charged_tx_fee.checked_sub(tx_fee).unwrap_or_else(|| {
+            assert!(
+                receipt.l1_fee.is_some(),
+                "BUG: charged transaction fee MUST always be at least the effective transaction fee"
+            );
+            Wei::ZERO
+        });
         let debited_amount = match receipt.status {
             TransactionStatus::Success => tx
                 .transaction()
@@ -463,6 +487,8 @@ impl State {
             evm_rpc_id,
             deposit_with_subaccount_helper_contract_address,
             last_deposit_with_subaccount_scraped_block_number,
+            l1_gas_buffer,
+            max_l1_data_fee,
         } = upgrade_args;
         if let Some(nonce) = next_transaction_nonce {
             let nonce = TransactionNonce::try_from(nonce)
@@ -536,6 +562,40 @@ impl State {
         if let Some(evm_id) = evm_rpc_id {
             self.evm_rpc_id = evm_id;
         }
+        if let Some(buffer) = l1_gas_buffer {
+            let buffer = GasAmount::try_from(buffer)
+                .map_err(|e| InvalidStateError::InvalidFeeModel(format!("ERROR: {}", e)))?;
+            match self.eth_transactions.fee_model() {
+                FeeModel::ArbitrumL1Gas { .. } => {
+                    self.eth_transactions
+                        .update_fee_model(FeeModel::ArbitrumL1Gas {
+                            l1_gas_buffer: buffer,
+                        })
+                }
+                fee_model => {
+                    return Err(InvalidStateError::InvalidFeeModel(format!(
+                        "ERROR: l1_gas_buffer is not supported by fee model {fee_model:?}"
+                    )))
+                }
+            }
+        }
+        if let Some(max_fee) = max_l1_data_fee {
+            let max_fee = Wei::try_from(max_fee)
+                .map_err(|e| InvalidStateError::InvalidFeeModel(format!("ERROR: {}", e)))?;
+            match self.eth_transactions.fee_model() {
+                FeeModel::OpStackL1DataFee { .. } => {
+                    self.eth_transactions
+                        .update_fee_model(FeeModel::OpStackL1DataFee {
+                            max_l1_data_fee: max_fee,
+                        })
+                }
+                fee_model => {
+                    return Err(InvalidStateError::InvalidFeeModel(format!(
+                        "ERROR: max_l1_data_fee is not supported by fee model {fee_model:?}"
+                    )))
+                }
+            }
+        }
         self.validate_config()
     }
 
@@ -581,9 +641,7 @@ impl State {
     }
 
     pub fn max_block_spread_for_logs_scraping(&self) -> u16 {
-        // Limit set by the EVM-RPC canister itself, see
-        // https://github.com/internet-computer-protocol/evm-rpc-canister/blob/3cce151d4c1338d83e6741afa354ccf11dff41e8/src/candid_rpc.rs#L192
-        500_u16
+        self.ethereum_network.max_block_spread_for_logs_scraping()
     }
 
     pub const fn evm_rpc_id(&self) -> Principal {
@@ -117,6 +117,32 @@ pub fn apply_state_transition(state: &mut State, payload: &EventType) {
                 *reimbursed_in_block,
             );
         }
+        EventType::RequestedWithdrawalReplacement {
+            withdrawal_id,
+            request,
+            requested_by: _,
+        } => {
+            state
+                .eth_transactions
+                .record_replacement_request(*withdrawal_id, *request);
+        }
+        EventType::CreatedCancellationTransaction {
+            withdrawal_id,
+            transaction,
+        } => {
+            state
+                .eth_transactions
+                .record_cancellation_transaction(*withdrawal_id, transaction.clone());
+        }
+        EventType::FailedWithdrawalReplacement {
+            withdrawal_id,
+            allowed_max_transaction_fee: _,
+            max_transaction_fee: _,
+        } => {
+            state
+                .eth_transactions
+                .record_failed_replacement_request(*withdrawal_id);
+        }
         EventType::SkippedBlockForContract {
             contract_address,
             block_number,
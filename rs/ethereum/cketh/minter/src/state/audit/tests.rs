@@ -90,11 +90,12 @@ impl GetEventsFile {
         use crate::endpoints::events::{
             AccessListItem as CandidAccessListItem, EventSource as CandidEventSource,
             ReimbursementIndex as CandidReimbursementIndex,
+            ReplacementRequest as CandidReplacementRequest,
             TransactionStatus as CandidTransactionStatus,
         };
         use crate::eth_logs::EventSource;
         use crate::state::audit::EventType as ET;
-        use crate::state::transactions::EthWithdrawalRequest;
+        use crate::state::transactions::{EthWithdrawalRequest, ReplacementRequest};
         use crate::state::TransactionStatus;
 
         fn map_event_source(
@@ -352,6 +353,7 @@ impl GetEventsFile {
                             CandidTransactionStatus::Failure => TransactionStatus::Failure,
                         },
                         transaction_hash: transaction_receipt.transaction_hash.parse().unwrap(),
+                        l1_fee: None,
                     },
                 },
                 EventPayload::ReimbursedEthWithdrawal {
@@ -458,6 +460,34 @@ impl GetEventsFile {
                         block_number: block_number.try_into().unwrap(),
                     }
                 }
+                EventPayload::RequestedWithdrawalReplacement {
+                    withdrawal_id,
+                    request,
+                    requested_by,
+                } => ET::RequestedWithdrawalReplacement {
+                    withdrawal_id: map_nat(withdrawal_id),
+                    request: match request {
+                        CandidReplacementRequest::BumpFee => ReplacementRequest::BumpFee,
+                        CandidReplacementRequest::Cancel => ReplacementRequest::Cancel,
+                    },
+                    requested_by,
+                },
+                EventPayload::CreatedCancellationTransaction {
+                    withdrawal_id,
+                    transaction,
+                } => ET::CreatedCancellationTransaction {
+                    withdrawal_id: map_nat(withdrawal_id),
+                    transaction: map_unsigned_transaction(transaction),
+                },
+                EventPayload::FailedWithdrawalReplacement {
+                    withdrawal_id,
+                    allowed_max_transaction_fee,
+                    max_transaction_fee,
+                } => ET::FailedWithdrawalReplacement {
+                    withdrawal_id: map_nat(withdrawal_id),
+                    allowed_max_transaction_fee: allowed_max_transaction_fee.try_into().unwrap(),
+                    max_transaction_fee: max_transaction_fee.try_into().unwrap(),
+                },
             },
         }
     }
@@ -2,10 +2,10 @@ use crate::erc20::CkErc20Token;
 use crate::eth_logs::{EventSource, ReceivedErc20Event, ReceivedEthEvent, ReceivedEvent};
 use crate::eth_rpc_client::responses::TransactionReceipt;
 use crate::lifecycle::{init::InitArg, upgrade::UpgradeArg};
-use crate::numeric::{BlockNumber, LedgerBurnIndex, LedgerMintIndex};
+use crate::numeric::{BlockNumber, LedgerBurnIndex, LedgerMintIndex, Wei};
 use crate::state::transactions::{
     Erc20WithdrawalRequest, EthWithdrawalRequest, Reimbursed, ReimbursementIndex,
-    ReimbursementRequest,
+    ReimbursementRequest, ReplacementRequest,
 };
 use crate::tx::{Eip1559TransactionRequest, SignedEip1559TransactionRequest};
 use candid::Principal;
@@ -171,6 +171,44 @@ pub enum EventType {
         #[n(0)]
         block_number: BlockNumber,
     },
+    /// The owner of a withdrawal or a controller requested to replace its sent transaction.
+    #[n(25)]
+    RequestedWithdrawalReplacement {
+        /// The withdrawal identifier.
+        #[cbor(n(0), with = "crate::cbor::id")]
+        withdrawal_id: LedgerBurnIndex,
+        /// The requested replacement.
+        #[n(1)]
+        request: ReplacementRequest,
+        /// The principal that requested the replacement.
+        #[cbor(n(2), with = "icrc_cbor::principal")]
+        requested_by: Principal,
+    },
+    /// The minter created a transaction cancelling a withdrawal.
+    #[n(26)]
+    CreatedCancellationTransaction {
+        /// The withdrawal identifier.
+        #[cbor(n(0), with = "crate::cbor::id")]
+        withdrawal_id: LedgerBurnIndex,
+        /// The zero-value self-transfer replacing the sent transaction.
+        #[n(1)]
+        transaction: Eip1559TransactionRequest,
+    },
+    /// The minter dropped a requested replacement of a sent transaction,
+    /// because the replacement transaction would exceed the maximum transaction fee
+    /// allowed by the withdrawal request.
+    #[n(27)]
+    FailedWithdrawalReplacement {
+        /// The withdrawal identifier.
+        #[cbor(n(0), with = "crate::cbor::id")]
+        withdrawal_id: LedgerBurnIndex,
+        /// The maximum transaction fee allowed by the withdrawal request.
+        #[n(1)]
+        allowed_max_transaction_fee: Wei,
+        /// The maximum transaction fee of the replacement transaction.
+        #[n(2)]
+        max_transaction_fee: Wei,
+    },
 }
 
 impl ReceivedEvent {
@@ -285,10 +285,11 @@ fn received_erc20_event() -> ReceivedErc20Event {
 mod upgrade {
     use crate::lifecycle::upgrade::UpgradeArg;
     use crate::lifecycle::EthereumNetwork;
-    use crate::numeric::{TransactionNonce, Wei};
+    use crate::numeric::{GasAmount, TransactionNonce, Wei};
     use crate::state::eth_logs_scraping::LogScrapingId;
     use crate::state::tests::initial_state;
     use crate::state::InvalidStateError;
+    use crate::tx::FeeModel;
     use assert_matches::assert_matches;
     use candid::Nat;
     use ic_ethereum_types::Address;
@@ -356,6 +357,65 @@ mod upgrade {
             }),
             Err(InvalidStateError::InvalidEthereumContractAddress(_))
         );
+
+        let mut state = initial_state();
+        assert_eq!(state.eth_transactions.fee_model(), FeeModel::Eip1559);
+        assert_matches!(
+            state.upgrade(UpgradeArg {
+                l1_gas_buffer: Some(Nat::from(100_000_u32)),
+                ..Default::default()
+            }),
+            Err(InvalidStateError::InvalidFeeModel(_))
+        );
+
+        let mut state = initial_state();
+        state
+            .eth_transactions
+            .update_fee_model(EthereumNetwork::ArbitrumOne.default_fee_model());
+        assert_matches!(
+            state.upgrade(UpgradeArg {
+                max_l1_data_fee: Some(Nat::from(1_000_000_000_000_u64)),
+                ..Default::default()
+            }),
+            Err(InvalidStateError::InvalidFeeModel(_))
+        );
+    }
+
+    #[test]
+    fn should_update_fee_model() {
+        let mut state = initial_state();
+        state
+            .eth_transactions
+            .update_fee_model(EthereumNetwork::ArbitrumOne.default_fee_model());
+        state
+            .upgrade(UpgradeArg {
+                l1_gas_buffer: Some(Nat::from(100_000_u32)),
+                ..Default::default()
+            })
+            .expect("valid upgrade args");
+        assert_eq!(
+            state.eth_transactions.fee_model(),
+            FeeModel::ArbitrumL1Gas {
+                l1_gas_buffer: GasAmount::new(100_000)
+            }
+        );
+
+        let mut state = initial_state();
+        state
+            .eth_transactions
+            .update_fee_model(EthereumNetwork::Base.default_fee_model());
+        state
+            .upgrade(UpgradeArg {
+                max_l1_data_fee: Some(Nat::from(1_000_000_000_000_u64)),
+                ..Default::default()
+            })
+            .expect("valid upgrade args");
+        assert_eq!(
+            state.eth_transactions.fee_model(),
+            FeeModel::OpStackL1DataFee {
+                max_l1_data_fee: Wei::new(1_000_000_000_000)
+            }
+        );
     }
 
     #[test]
@@ -613,7 +673,9 @@ prop_compose! {
             last_erc20_scraped_block_number,
             evm_rpc_id,
             deposit_with_subaccount_helper_contract_address: deposit_with_subaccount_helper_contract_address.map(|addr| addr.to_string()),
-            last_deposit_with_subaccount_scraped_block_number
+            last_deposit_with_subaccount_scraped_block_number,
+            l1_gas_buffer: None,
+            max_l1_data_fee: None,
         }
     }
 }
@@ -731,6 +793,7 @@ prop_compose! {
             gas_used,
             status,
             transaction_hash,
+            l1_fee: None,
         }
     }
 }
@@ -951,6 +1014,7 @@ fn state_equivalence() {
                     "0x06afc3c693dc2ba2c19b5c287c4dddce040d766bea5fd13c8a7268b04aa94f2d"
                         .parse()
                         .unwrap(),
+                l1_fee: None,
             })
             .expect("valid receipt"),
         ),
@@ -1293,7 +1357,7 @@ mod eth_balance {
     use crate::state::tests::{initial_state, received_eth_event};
     use crate::state::transactions::{create_transaction, EthWithdrawalRequest, WithdrawalRequest};
     use crate::state::{EthBalance, State};
-    use crate::tx::{Eip1559Signature, SignedEip1559TransactionRequest};
+    use crate::tx::{Eip1559Signature, FeeModel, SignedEip1559TransactionRequest};
     use maplit::btreemap;
 
     #[test]
@@ -1548,6 +1612,87 @@ mod eth_balance {
         );
     }
 
+    #[test]
+    fn should_account_for_l1_fee_reported_in_receipt() {
+        let max_l1_data_fee = Wei::new(50_000_000_000_000);
+        let mut state_before_withdrawal = initial_state();
+        state_before_withdrawal
+            .eth_transactions
+            .update_fee_model(FeeModel::OpStackL1DataFee { max_l1_data_fee });
+        apply_state_transition(
+            &mut state_before_withdrawal,
+            &EventType::AcceptedDeposit(received_eth_event()),
+        );
+        let eth_balance_before_withdrawal = state_before_withdrawal.eth_balance.clone();
+        let withdrawal_request = EthWithdrawalRequest {
+            withdrawal_amount: Wei::new(10_000_000_000_000_000),
+            destination: "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34"
+                .parse()
+                .unwrap(),
+            ledger_burn_index: LedgerBurnIndex::new(0),
+            from: "k2t6j-2nvnp-4zjm3-25dtz-6xhaa-c7boj-5gayf-oj3xs-i43lp-teztq-6ae"
+                .parse()
+                .unwrap(),
+            from_subaccount: None,
+            created_at: Some(1699527697000000000),
+        };
+        let withdrawal_flow = WithdrawalFlow::for_request(withdrawal_request.clone());
+        let l2_fee = WeiPerGas::ONE
+            .transaction_cost(GasAmount::from(21_000_u32))
+            .unwrap();
+
+        let mut state = state_before_withdrawal.clone();
+        let l1_fee = Wei::new(30_000_000_000_000);
+        let receipt = WithdrawalFlow {
+            l1_fee: Some(l1_fee),
+            ..withdrawal_flow.clone()
+        }
+        .apply(&mut state);
+        let tx_fee = l2_fee.checked_add(l1_fee).unwrap();
+        assert_eq!(receipt.effective_transaction_fee(), tx_fee);
+        let tx = state
+            .eth_transactions
+            .get_finalized_transaction(&LedgerBurnIndex::new(0))
+            .unwrap()
+            .transaction()
+            .clone();
+        let charged_tx_fee = withdrawal_request
+            .withdrawal_amount
+            .checked_sub(tx.amount)
+            .unwrap();
+        assert_eq!(
+            state.eth_balance,
+            EthBalance {
+                eth_balance: eth_balance_before_withdrawal
+                    .eth_balance
+                    .checked_sub(tx.amount.checked_add(tx_fee).unwrap())
+                    .unwrap(),
+                total_effective_tx_fees: eth_balance_before_withdrawal
+                    .total_effective_tx_fees
+                    .checked_add(tx_fee)
+                    .unwrap(),
+                total_unspent_tx_fees: eth_balance_before_withdrawal
+                    .total_unspent_tx_fees
+                    .checked_add(charged_tx_fee.checked_sub(tx_fee).unwrap())
+                    .unwrap(),
+            }
+        );
+
+        // The L1 fee set aside is only an estimate, the minter covers any excess.
+        let mut state = state_before_withdrawal.clone();
+        let l1_fee = max_l1_data_fee.checked_mul(2_u8).unwrap();
+        WithdrawalFlow {
+            l1_fee: Some(l1_fee),
+            ..withdrawal_flow
+        }
+        .apply(&mut state);
+        assert_eq!(
+            state.eth_balance.total_effective_tx_fees,
+            l2_fee.checked_add(l1_fee).unwrap()
+        );
+        assert_eq!(state.eth_balance.total_unspent_tx_fees, Wei::ZERO);
+    }
+
     #[derive(Clone)]
     struct WithdrawalFlow {
         withdrawal_request: WithdrawalRequest,
@@ -1556,6 +1701,7 @@ mod eth_balance {
         gas_limit: GasAmount,
         effective_gas_price: WeiPerGas,
         effective_gas_used: GasAmount,
+        l1_fee: Option<Wei>,
         tx_status: TransactionStatus,
     }
 
@@ -1571,6 +1717,7 @@ mod eth_balance {
                 gas_limit: GasAmount::from(21_000_u32),
                 effective_gas_price: WeiPerGas::ONE,
                 effective_gas_used: GasAmount::from(21_000_u32),
+                l1_fee: None,
                 tx_status: TransactionStatus::Success,
             }
         }
@@ -1592,6 +1739,7 @@ mod eth_balance {
                 self.tx_fee,
                 self.gas_limit,
                 EthereumNetwork::Sepolia,
+                &state.eth_transactions.fee_model(),
             )
             .expect("BUG: failed to create transaction");
             apply_state_transition(
@@ -1626,6 +1774,7 @@ mod eth_balance {
                 gas_used: self.effective_gas_used,
                 status: self.tx_status,
                 transaction_hash: signed_tx.hash(),
+                l1_fee: self.l1_fee,
             };
             apply_state_transition(
                 state,
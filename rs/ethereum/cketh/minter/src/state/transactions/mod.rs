@@ -14,8 +14,9 @@ use crate::numeric::{
 };
 use crate::state::event::EventType;
 use crate::tx::{
-    Eip1559TransactionRequest, FinalizedEip1559Transaction, GasFeeEstimate, ResubmissionStrategy,
-    SignedEip1559TransactionRequest, SignedTransactionRequest, TransactionRequest,
+    Eip1559TransactionRequest, FeeModel, FinalizedEip1559Transaction, GasFeeEstimate,
+    ResubmissionStrategy, SignedEip1559TransactionRequest, SignedTransactionRequest,
+    TransactionRequest,
 };
 use candid::Principal;
 use ic_ethereum_types::Address;
@@ -374,6 +375,33 @@ pub struct EthTransactions {
     pub(in crate::state) maybe_reimburse: BTreeSet<LedgerBurnIndex>,
     pub(in crate::state) reimbursement_requests: BTreeMap<ReimbursementIndex, ReimbursementRequest>,
     pub(in crate::state) reimbursed: BTreeMap<ReimbursementIndex, ReimbursedResult>,
+
+    // Explicit requests to replace the sent transaction of a withdrawal, see [`ReplacementRequest`].
+    pub(in crate::state) replacement_requests: BTreeMap<LedgerBurnIndex, ReplacementRequest>,
+
+    // Additional costs charged by the network, used to price created and resubmitted transactions.
+    pub(in crate::state) fee_model: FeeModel,
+}
+
+/// Explicit request to replace the transaction sent for a withdrawal that is stuck.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Decode, Encode)]
+#[cbor(index_only)]
+pub enum ReplacementRequest {
+    /// Resubmit the transaction with a higher fee.
+    #[n(0)]
+    BumpFee,
+    /// Replace the transaction by a zero-value self-transfer with the same nonce
+    /// and reimburse the withdrawn amount minus the transaction fee.
+    #[n(1)]
+    Cancel,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum ReplacementRequestError {
+    WithdrawalNotFound,
+    TransactionNotSent,
+    AlreadyFinalized,
+    AlreadyCancelled,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -407,9 +435,23 @@ impl EthTransactions {
             maybe_reimburse: Default::default(),
             reimbursement_requests: Default::default(),
             reimbursed: Default::default(),
+            replacement_requests: Default::default(),
+            fee_model: FeeModel::Eip1559,
         }
     }
 
+    pub fn with_fee_model(self, fee_model: FeeModel) -> Self {
+        Self { fee_model, ..self }
+    }
+
+    pub fn fee_model(&self) -> FeeModel {
+        self.fee_model
+    }
+
+    pub fn update_fee_model(&mut self, fee_model: FeeModel) {
+        self.fee_model = fee_model;
+    }
+
     pub fn next_transaction_nonce(&self) -> TransactionNonce {
         self.next_nonce
     }
@@ -570,6 +612,13 @@ impl EthTransactions {
             .created_tx
             .remove_entry(&signed_tx.as_ref().nonce())
             .expect("BUG: missing created transaction");
+        // A cancellation request is only fulfilled by a cancellation transaction,
+        // since a fee bump could have been created before the withdrawal was cancelled.
+        if self.replacement_requests.get(&ledger_burn_index) != Some(&ReplacementRequest::Cancel)
+            || signed_tx.as_ref().transaction().is_cancellation()
+        {
+            self.replacement_requests.remove(&ledger_burn_index);
+        }
         if let Some(sent_tx) = self.sent_tx.get_mut(&nonce) {
             sent_tx.push(signed_tx);
         } else {
@@ -605,8 +654,16 @@ impl EthTransactions {
             .iter()
             .filter(|(nonce, _burn_index, _signed_tx)| *nonce >= &first_pending_tx_nonce)
         {
+            if self
+                .created_tx
+                .get_alt(burn_index)
+                .is_some_and(|tx| tx.as_ref().is_cancellation())
+            {
+                // Resubmitting the cancelled transaction would discard the cancellation.
+                continue;
+            }
             let last_signed_tx = signed_tx.last().expect("BUG: empty sent transactions list");
-            match last_signed_tx.resubmit(current_gas_fee.clone()) {
+            match last_signed_tx.resubmit(current_gas_fee.clone(), &self.fee_model) {
                 Ok(Some(new_tx)) => {
                     transactions_to_resubmit.push(Ok((*burn_index, new_tx)));
                 }
@@ -649,6 +706,221 @@ impl EthTransactions {
         );
     }
 
+    /// Checks whether the transaction of the given withdrawal can be replaced as requested.
+    pub fn check_replacement_request(
+        &self,
+        withdrawal_id: &LedgerBurnIndex,
+        request: ReplacementRequest,
+    ) -> Result<&WithdrawalRequest, ReplacementRequestError> {
+        if self
+            .pending_withdrawal_requests
+            .iter()
+            .any(|r| &r.cketh_ledger_burn_index() == withdrawal_id)
+        {
+            return Err(ReplacementRequestError::TransactionNotSent);
+        }
+        let withdrawal_request = self
+            .processed_withdrawal_requests
+            .get(withdrawal_id)
+            .ok_or(ReplacementRequestError::WithdrawalNotFound)?;
+        if self.finalized_tx.contains_alt(withdrawal_id) {
+            return Err(ReplacementRequestError::AlreadyFinalized);
+        }
+        let last_sent_tx = self
+            .sent_tx
+            .get_alt(withdrawal_id)
+            .and_then(|txs| txs.last())
+            .ok_or(ReplacementRequestError::TransactionNotSent)?;
+        if request == ReplacementRequest::Cancel {
+            let is_cancelled = self.replacement_requests.get(withdrawal_id)
+                == Some(&ReplacementRequest::Cancel)
+                || last_sent_tx.as_ref().transaction().is_cancellation()
+                || self
+                    .created_tx
+                    .get_alt(withdrawal_id)
+                    .is_some_and(|tx| tx.as_ref().is_cancellation());
+            if is_cancelled {
+                return Err(ReplacementRequestError::AlreadyCancelled);
+            }
+        }
+        Ok(withdrawal_request)
+    }
+
+    pub fn record_replacement_request(
+        &mut self,
+        withdrawal_id: LedgerBurnIndex,
+        request: ReplacementRequest,
+    ) {
+        if let Err(e) = self.check_replacement_request(&withdrawal_id, request) {
+            panic!("BUG: invalid replacement request {request:?} for withdrawal {withdrawal_id}: {e:?}");
+        }
+        match self.replacement_requests.get(&withdrawal_id) {
+            // A pending cancellation already bumps the transaction fee.
+            Some(ReplacementRequest::Cancel) => {}
+            _ => {
+                self.replacement_requests.insert(withdrawal_id, request);
+            }
+        }
+    }
+
+    /// Create the transactions replacing already sent transactions, as explicitly requested
+    /// with [`Self::record_replacement_request`].
+    /// Each replacement transaction has a higher fee than the last sent transaction with the same nonce,
+    /// even if the transaction price of the latter is still actual.
+    ///
+    /// Must be called after [`Self::create_resubmit_transactions`], so that the replacement transactions
+    /// take precedence over the automatically resubmitted ones.
+    pub fn create_replacement_transactions(
+        &self,
+        latest_transaction_count: TransactionCount,
+        current_gas_fee: GasFeeEstimate,
+        minter_address: Address,
+    ) -> Vec<Result<EventType, ResubmitTransactionError>> {
+        let first_pending_tx_nonce: TransactionNonce = latest_transaction_count.change_units();
+        let mut replacement_transactions = Vec::new();
+        for (nonce, burn_index, signed_tx, request) in self
+            .sent_tx
+            .iter()
+            .filter(|(nonce, _burn_index, _signed_tx)| *nonce >= &first_pending_tx_nonce)
+            .filter_map(|(nonce, burn_index, signed_tx)| {
+                self.replacement_requests
+                    .get(burn_index)
+                    .map(|request| (nonce, burn_index, signed_tx, request))
+            })
+        {
+            let last_signed_tx = signed_tx.last().expect("BUG: empty sent transactions list");
+            replacement_transactions.push(self.create_replacement_transaction(
+                *nonce,
+                *burn_index,
+                last_signed_tx,
+                *request,
+                current_gas_fee.clone(),
+                minter_address,
+            ));
+        }
+        replacement_transactions
+    }
+
+    /// Check that the requested replacement of the last sent transaction of the given withdrawal
+    /// could be created with the current gas fee, without exceeding the maximum transaction fee
+    /// allowed by the withdrawal request.
+    pub fn check_replacement_transaction_fee(
+        &self,
+        withdrawal_id: &LedgerBurnIndex,
+        request: ReplacementRequest,
+        current_gas_fee: GasFeeEstimate,
+        minter_address: Address,
+    ) -> Result<(), ResubmitTransactionError> {
+        let last_sent_tx = self
+            .sent_tx
+            .get_alt(withdrawal_id)
+            .and_then(|txs| txs.last())
+            .unwrap_or_else(|| {
+                panic!("BUG: missing sent transaction for withdrawal {withdrawal_id}")
+            });
+        self.create_replacement_transaction(
+            last_sent_tx.as_ref().transaction().nonce,
+            *withdrawal_id,
+            last_sent_tx,
+            request,
+            current_gas_fee,
+            minter_address,
+        )
+        .map(|_event| ())
+    }
+
+    fn create_replacement_transaction(
+        &self,
+        nonce: TransactionNonce,
+        withdrawal_id: LedgerBurnIndex,
+        last_signed_tx: &SignedTransactionRequest,
+        request: ReplacementRequest,
+        current_gas_fee: GasFeeEstimate,
+        minter_address: Address,
+    ) -> Result<EventType, ResubmitTransactionError> {
+        let new_tx = match request {
+            ReplacementRequest::BumpFee => {
+                last_signed_tx.bump_fee(current_gas_fee, &self.fee_model)
+            }
+            ReplacementRequest::Cancel => {
+                last_signed_tx.cancel(current_gas_fee, minter_address, &self.fee_model)
+            }
+        };
+        new_tx
+            .map(|transaction| match request {
+                ReplacementRequest::BumpFee => EventType::ReplacedTransaction {
+                    withdrawal_id,
+                    transaction,
+                },
+                ReplacementRequest::Cancel => EventType::CreatedCancellationTransaction {
+                    withdrawal_id,
+                    transaction,
+                },
+            })
+            .map_err(
+                |crate::tx::ResubmitTransactionError::InsufficientTransactionFee {
+                     allowed_max_transaction_fee,
+                     actual_max_transaction_fee,
+                 }| {
+                    ResubmitTransactionError::InsufficientTransactionFee {
+                        ledger_burn_index: withdrawal_id,
+                        transaction_nonce: nonce,
+                        allowed_max_transaction_fee,
+                        max_transaction_fee: actual_max_transaction_fee,
+                    }
+                },
+            )
+    }
+
+    /// Drop a pending replacement request that could not be fulfilled,
+    /// e.g., because the replacement transaction would exceed the maximum transaction fee
+    /// allowed by the withdrawal request.
+    /// The last sent transaction is still automatically resubmitted, if needed.
+    pub fn record_failed_replacement_request(&mut self, withdrawal_id: LedgerBurnIndex) {
+        assert!(
+            self.replacement_requests.remove(&withdrawal_id).is_some(),
+            "BUG: no pending replacement request for withdrawal {withdrawal_id}"
+        );
+    }
+
+    pub fn record_cancellation_transaction(
+        &mut self,
+        withdrawal_id: LedgerBurnIndex,
+        cancellation_tx: Eip1559TransactionRequest,
+    ) {
+        assert!(
+            cancellation_tx.is_cancellation(),
+            "BUG: transaction {cancellation_tx:?} is not a cancellation"
+        );
+        let nonce = cancellation_tx.nonce;
+        let (ledger_burn_index, last_sent_tx) =
+            Self::expect_last_sent_tx_entry(&self.sent_tx, &nonce);
+        assert_eq!(
+            ledger_burn_index, &withdrawal_id,
+            "BUG: mismatch between withdrawal ID and the burn index of the transaction to cancel"
+        );
+        assert!(
+            cancellation_tx.max_transaction_fee(&self.fee_model)
+                <= last_sent_tx.resubmission.allowed_max_transaction_fee(),
+            "BUG: cancellation transaction {cancellation_tx:?} exceeds the allowed transaction fee"
+        );
+        Self::cleanup_failed_resubmitted_transactions(&mut self.created_tx, &nonce);
+        let cancellation_tx = TransactionRequest {
+            resubmission: ResubmissionStrategy::CancelWithdrawal {
+                allowed_max_transaction_fee: last_sent_tx
+                    .resubmission
+                    .allowed_max_transaction_fee(),
+                fee_model: self.fee_model,
+            },
+            transaction: cancellation_tx,
+        };
+        assert_eq!(
+            self.created_tx
+                .try_insert(nonce, withdrawal_id, cancellation_tx),
+            Ok(())
+        );
+    }
+
     pub fn sent_transactions_to_finalize(
         &self,
         finalized_transaction_count: &TransactionCount,
@@ -688,11 +960,16 @@ impl EthTransactions {
             .clone()
             .try_finalize(receipt.clone())
             .expect("ERROR: invalid transaction receipt");
+        let max_transaction_fee = sent_tx
+            .as_ref()
+            .transaction()
+            .max_transaction_fee(sent_tx.resubmission.fee_model(&self.fee_model));
 
         let nonce = sent_tx.as_ref().nonce();
         {
             self.sent_tx.remove_entry(&nonce);
             Self::cleanup_failed_resubmitted_transactions(&mut self.created_tx, &nonce);
+            self.replacement_requests.remove(&ledger_burn_index);
         }
         assert_eq!(
             self.finalized_tx
@@ -709,7 +986,27 @@ impl EthTransactions {
             .get(&ledger_burn_index)
             .expect("failed to find entry from processed_withdrawal_requests with block index: {ledger_burn_index}");
         let index = ReimbursementIndex::from(request);
+        let is_cancelled = finalized_tx.transaction().is_cancellation();
         match &request {
+            WithdrawalRequest::CkEth(request) if is_cancelled => {
+                // The withdrawn amount was not transferred, but the cancellation transaction fee is still due.
+                // The fee is computed with the fee model at the time the cancellation was created.
+                let cancellation_fee = max_transaction_fee;
+                self.record_reimbursement_request(
+                    index,
+                    ReimbursementRequest {
+                        ledger_burn_index,
+                        to: request.from,
+                        to_subaccount: request.from_subaccount.clone(),
+                        reimbursed_amount: request
+                            .withdrawal_amount
+                            .checked_sub(cancellation_fee)
+                            .expect("BUG: cancellation transaction fee MUST be covered by the withdrawal amount")
+                            .change_units(),
+                        transaction_hash: Some(receipt.transaction_hash),
+                    },
+                );
+            }
             WithdrawalRequest::CkEth(request) => {
                 if receipt.status == TransactionStatus::Failure {
                     self.record_reimbursement_request(
@@ -725,7 +1022,7 @@ impl EthTransactions {
                 }
             }
             WithdrawalRequest::CkErc20(request) => {
-                if receipt.status == TransactionStatus::Failure {
+                if receipt.status == TransactionStatus::Failure || is_cancelled {
                     self.record_reimbursement_request(
                         index,
                         ReimbursementRequest {
@@ -874,7 +1171,9 @@ impl EthTransactions {
                     Some(tx.as_ref()),
                 );
             }
-            if tx.transaction_status() == &TransactionStatus::Failure {
+            if tx.transaction_status() == &TransactionStatus::Failure
+                || tx.transaction().is_cancellation()
+            {
                 return (
                     RetrieveEthStatus::TxFinalized(TxFinalizedStatus::PendingReimbursement(
                         EthTransaction {
@@ -1080,6 +1379,8 @@ impl EthTransactions {
         ensure_eq!(self.maybe_reimburse, other.maybe_reimburse);
         ensure_eq!(self.reimbursement_requests, other.reimbursement_requests);
         ensure_eq!(self.reimbursed, other.reimbursed);
+        ensure_eq!(self.replacement_requests, other.replacement_requests);
+        ensure_eq!(self.fee_model, other.fee_model);
 
         Ok(())
     }
@@ -1095,6 +1396,7 @@ impl EthTransactions {
 /// Creates an EIP-1559 transaction for the given withdrawal request.
 /// The transaction fees are paid by the beneficiary,
 /// meaning that the fees will be deducted from the withdrawal amount.
+/// This includes any L1 data fee charged by the network, see [`FeeModel`].
 ///
 /// # Errors
 /// * `CreateTransactionError::InsufficientTransactionFee` if the ETH withdrawal amount does not cover the transaction fee.
@@ -1104,6 +1406,7 @@ pub fn create_transaction(
     gas_fee_estimate: GasFeeEstimate,
     gas_limit: GasAmount,
     ethereum_network: EthereumNetwork,
+    fee_model: &FeeModel,
 ) -> Result<Eip1559TransactionRequest, CreateTransactionError> {
     assert!(
         gas_limit > GasAmount::ZERO,
@@ -1112,8 +1415,13 @@ pub fn create_transaction(
     match withdrawal_request {
         WithdrawalRequest::CkEth(request) => {
             let transaction_price = gas_fee_estimate.to_price(gas_limit);
-            let max_transaction_fee = transaction_price.max_transaction_fee();
-            let tx_amount = match request.withdrawal_amount.checked_sub(max_transaction_fee) {
+            let max_transaction_fee = fee_model.max_transaction_fee(&transaction_price);
+            // A zero-value transaction would be indistinguishable from a cancellation.
+            let tx_amount = match request
+                .withdrawal_amount
+                .checked_sub(max_transaction_fee)
+                .filter(|tx_amount| tx_amount > &Wei::ZERO)
+            {
                 Some(tx_amount) => tx_amount,
                 None => {
                     return Err(CreateTransactionError::InsufficientTransactionFee {
@@ -1143,19 +1451,25 @@ pub fn create_transaction(
             // the transaction could still make it as long as `transaction.max_fee_per_gas >=  block.base_fee_per_gas`,
             // since the `priority_fee_per_gas` received by the miner is capped to (see https://eips.ethereum.org/EIPS/eip-1559)
             // min(transaction.max_priority_fee_per_gas, transaction.max_fee_per_gas - block.base_fee_per_gas).
-            let request_max_fee_per_gas = request
-                .max_transaction_fee
-                .into_wei_per_gas(gas_limit)
-                .expect("BUG: gas_limit should be non-zero");
+            // The L1 data fee charged by some networks is not part of the gas used and must be set aside first.
             let actual_min_max_fee_per_gas = gas_fee_estimate.min_max_fee_per_gas();
-            if actual_min_max_fee_per_gas > request_max_fee_per_gas {
-                return Err(CreateTransactionError::InsufficientTransactionFee {
+            let insufficient_transaction_fee =
+                || CreateTransactionError::InsufficientTransactionFee {
                     cketh_ledger_burn_index: request.cketh_ledger_burn_index,
                     allowed_max_transaction_fee: request.max_transaction_fee,
                     actual_max_transaction_fee: actual_min_max_fee_per_gas
                         .transaction_cost(gas_limit)
+                        .and_then(|fee| fee.checked_add(fee_model.max_l1_data_fee()))
                         .unwrap_or(Wei::MAX),
-                });
+                };
+            let request_max_fee_per_gas = request
+                .max_transaction_fee
+                .checked_sub(fee_model.max_l1_data_fee())
+                .ok_or_else(insufficient_transaction_fee)?
+                .into_wei_per_gas(gas_limit)
+                .expect("BUG: gas_limit should be non-zero");
+            if actual_min_max_fee_per_gas > request_max_fee_per_gas {
+                return Err(insufficient_transaction_fee());
             }
             Ok(Eip1559TransactionRequest {
                 chain_id: ethereum_network.chain_id(),
@@ -11,7 +11,7 @@ use crate::state::transactions::{
     WithdrawalRequest,
 };
 use crate::tx::{
-    AccessList, Eip1559Signature, Eip1559TransactionRequest, GasFeeEstimate,
+    AccessList, Eip1559Signature, Eip1559TransactionRequest, FeeModel, GasFeeEstimate,
     SignedEip1559TransactionRequest,
 };
 use crate::withdraw::estimate_gas_limit;
@@ -305,7 +305,7 @@ mod eth_transactions {
         use crate::state::transactions::Erc20Value;
         use crate::state::transactions::{create_transaction, EthTransactions};
         use crate::test_fixtures::expect_panic_with_message;
-        use crate::tx::Eip1559TransactionRequest;
+        use crate::tx::{Eip1559TransactionRequest, FeeModel};
         use crate::withdraw::{
             estimate_gas_limit, CKERC20_WITHDRAWAL_TRANSACTION_GAS_LIMIT,
             CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT,
@@ -326,6 +326,7 @@ mod eth_transactions {
                 gas_fee_estimate(),
                 estimate_gas_limit(&withdrawal_request),
                 EthereumNetwork::Sepolia,
+                &FeeModel::Eip1559,
             )
             .unwrap();
 
@@ -347,6 +348,7 @@ mod eth_transactions {
                 gas_fee_estimate(),
                 estimate_gas_limit(&withdrawal_request.clone().into()),
                 EthereumNetwork::Sepolia,
+                &FeeModel::Eip1559,
             )
             .unwrap();
 
@@ -398,6 +400,7 @@ mod eth_transactions {
                 gas_fee_estimate(),
                 estimate_gas_limit(&withdrawal_request.clone().into()),
                 EthereumNetwork::Sepolia,
+                &FeeModel::Eip1559,
             )
             .unwrap();
             let tx_mixing_payee_address_with_erc20_address = Eip1559TransactionRequest {
@@ -446,6 +449,7 @@ mod eth_transactions {
                     gas_fee_estimate(),
                     CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT,
                     EthereumNetwork::Sepolia,
+                    &FeeModel::Eip1559,
                 )
                 .unwrap();
 
@@ -1837,6 +1841,417 @@ mod eth_transactions {
         }
     }
 
+    mod replacement_requests {
+        use crate::endpoints::{EthTransaction, RetrieveEthStatus, TxFinalizedStatus};
+        use crate::numeric::{LedgerBurnIndex, TransactionCount, TransactionNonce, Wei, WeiPerGas};
+        use crate::state::event::EventType;
+        use crate::state::transactions::tests::{
+            ckerc20_withdrawal_request_with_index, cketh_withdrawal_request_with_index,
+            create_and_record_signed_transaction, create_and_record_transaction, gas_fee_estimate,
+            transaction_receipt,
+        };
+        use crate::state::transactions::{
+            EthTransactions, ReimbursementIndex, ReimbursementRequest, ReplacementRequest,
+            ReplacementRequestError, ResubmitTransactionError, TransactionStatus,
+            WithdrawalRequest,
+        };
+        use crate::tx::{Eip1559TransactionRequest, FeeModel, SignedEip1559TransactionRequest};
+        use crate::withdraw::CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT;
+        use assert_matches::assert_matches;
+        use ic_ethereum_types::Address;
+        use maplit::btreemap;
+
+        const MINTER_ADDRESS: &str = "0xb6bc16189ec3d33041c893b44511c594b1736b8a";
+
+        #[test]
+        fn should_reject_replacement_of_unknown_or_unsent_withdrawal() {
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let withdrawal_id = LedgerBurnIndex::new(15);
+
+            for request in [ReplacementRequest::BumpFee, ReplacementRequest::Cancel] {
+                assert_eq!(
+                    transactions.check_replacement_request(&withdrawal_id, request),
+                    Err(ReplacementRequestError::WithdrawalNotFound)
+                );
+            }
+
+            let withdrawal_request = cketh_withdrawal_request_with_index(withdrawal_id);
+            transactions.record_withdrawal_request(withdrawal_request.clone());
+            for request in [ReplacementRequest::BumpFee, ReplacementRequest::Cancel] {
+                assert_eq!(
+                    transactions.check_replacement_request(&withdrawal_id, request),
+                    Err(ReplacementRequestError::TransactionNotSent)
+                );
+            }
+
+            let _created_tx = create_and_record_transaction(
+                &mut transactions,
+                withdrawal_request,
+                gas_fee_estimate(),
+            );
+            for request in [ReplacementRequest::BumpFee, ReplacementRequest::Cancel] {
+                assert_eq!(
+                    transactions.check_replacement_request(&withdrawal_id, request),
+                    Err(ReplacementRequestError::TransactionNotSent)
+                );
+            }
+        }
+
+        #[test]
+        fn should_reject_replacement_of_finalized_withdrawal() {
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let withdrawal_id = LedgerBurnIndex::new(15);
+            let signed_tx = record_sent_transaction(
+                &mut transactions,
+                cketh_withdrawal_request_with_index(withdrawal_id),
+            );
+            transactions.record_finalized_transaction(
+                withdrawal_id,
+                transaction_receipt(&signed_tx, TransactionStatus::Success),
+            );
+
+            for request in [ReplacementRequest::BumpFee, ReplacementRequest::Cancel] {
+                assert_eq!(
+                    transactions.check_replacement_request(&withdrawal_id, request),
+                    Err(ReplacementRequestError::AlreadyFinalized)
+                );
+            }
+        }
+
+        #[test]
+        fn should_bump_fee_even_when_transaction_price_is_actual() {
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let withdrawal_id = LedgerBurnIndex::new(15);
+            let signed_tx = record_sent_transaction(
+                &mut transactions,
+                cketh_withdrawal_request_with_index(withdrawal_id),
+            );
+            assert_eq!(
+                transactions
+                    .create_resubmit_transactions(TransactionCount::ZERO, gas_fee_estimate()),
+                vec![]
+            );
+
+            transactions.record_replacement_request(withdrawal_id, ReplacementRequest::BumpFee);
+            let replacements = transactions.create_replacement_transactions(
+                TransactionCount::ZERO,
+                gas_fee_estimate(),
+                minter_address(),
+            );
+
+            let sent_tx = signed_tx.transaction();
+            let bumped_tx = match replacements.as_slice() {
+                [Ok(EventType::ReplacedTransaction {
+                    withdrawal_id: id,
+                    transaction,
+                })] if id == &withdrawal_id => transaction.clone(),
+                _ => panic!("unexpected replacements {replacements:?}"),
+            };
+            assert!(bumped_tx.max_fee_per_gas > sent_tx.max_fee_per_gas);
+            assert!(bumped_tx.max_priority_fee_per_gas > sent_tx.max_priority_fee_per_gas);
+            assert!(bumped_tx.amount < sent_tx.amount);
+            assert_eq!(
+                bumped_tx,
+                Eip1559TransactionRequest {
+                    max_priority_fee_per_gas: bumped_tx.max_priority_fee_per_gas,
+                    max_fee_per_gas: bumped_tx.max_fee_per_gas,
+                    amount: bumped_tx.amount,
+                    ..sent_tx.clone()
+                }
+            );
+
+            transactions.record_resubmit_transaction(bumped_tx.clone());
+            create_and_record_signed_transaction(&mut transactions, bumped_tx);
+            assert_eq!(transactions.replacement_requests, btreemap! {});
+        }
+
+        #[test]
+        fn should_cancel_cketh_withdrawal_and_reimburse_withdrawal_amount_minus_fee() {
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let withdrawal_id = LedgerBurnIndex::new(15);
+            let withdrawal_request = cketh_withdrawal_request_with_index(withdrawal_id);
+            let _signed_tx = record_sent_transaction(&mut transactions, withdrawal_request.clone());
+
+            let cancellation_tx = cancel(&mut transactions, withdrawal_id);
+            assert_eq!(cancellation_tx.destination, minter_address());
+            assert_eq!(cancellation_tx.amount, Wei::ZERO);
+            assert_eq!(cancellation_tx.data, Vec::<u8>::new());
+            assert_eq!(
+                cancellation_tx.gas_limit,
+                CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT
+            );
+
+            let signed_cancellation_tx =
+                create_and_record_signed_transaction(&mut transactions, cancellation_tx.clone());
+            assert_eq!(transactions.replacement_requests, btreemap! {});
+            assert_eq!(
+                transactions.check_replacement_request(&withdrawal_id, ReplacementRequest::Cancel),
+                Err(ReplacementRequestError::AlreadyCancelled)
+            );
+
+            let receipt = transaction_receipt(&signed_cancellation_tx, TransactionStatus::Success);
+            transactions.record_finalized_transaction(withdrawal_id, receipt.clone());
+
+            assert_eq!(
+                transactions.transaction_status(&withdrawal_id),
+                RetrieveEthStatus::TxFinalized(TxFinalizedStatus::PendingReimbursement(
+                    EthTransaction {
+                        transaction_hash: receipt.transaction_hash.to_string()
+                    }
+                ))
+            );
+            assert_eq!(
+                transactions.reimbursement_requests,
+                btreemap! {
+                    ReimbursementIndex::CkEth { ledger_burn_index: withdrawal_id } => ReimbursementRequest {
+                        ledger_burn_index: withdrawal_id,
+                        reimbursed_amount: withdrawal_request
+                            .withdrawal_amount
+                            .checked_sub(cancellation_tx.max_transaction_fee(&FeeModel::Eip1559))
+                            .unwrap()
+                            .change_units(),
+                        to: withdrawal_request.from,
+                        to_subaccount: withdrawal_request.from_subaccount,
+                        transaction_hash: Some(receipt.transaction_hash),
+                    }
+                }
+            );
+        }
+
+        #[test]
+        fn should_cancel_ckerc20_withdrawal_and_reimburse_ckerc20() {
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let withdrawal_id = LedgerBurnIndex::new(15);
+            let withdrawal_request =
+                ckerc20_withdrawal_request_with_index(withdrawal_id, LedgerBurnIndex::new(7));
+            let _signed_tx = record_sent_transaction(&mut transactions, withdrawal_request.clone());
+
+            let cancellation_tx = cancel(&mut transactions, withdrawal_id);
+            assert!(
+                cancellation_tx.max_transaction_fee(&FeeModel::Eip1559)
+                    <= withdrawal_request.max_transaction_fee
+            );
+            let signed_cancellation_tx =
+                create_and_record_signed_transaction(&mut transactions, cancellation_tx);
+            let receipt = transaction_receipt(&signed_cancellation_tx, TransactionStatus::Success);
+            transactions.record_finalized_transaction(withdrawal_id, receipt.clone());
+
+            assert_eq!(
+                transactions.reimbursement_requests,
+                btreemap! {
+                    ReimbursementIndex::from(&WithdrawalRequest::from(withdrawal_request.clone())) => ReimbursementRequest {
+                        ledger_burn_index: withdrawal_request.ckerc20_ledger_burn_index,
+                        reimbursed_amount: withdrawal_request.withdrawal_amount.change_units(),
+                        to: withdrawal_request.from,
+                        to_subaccount: withdrawal_request.from_subaccount,
+                        transaction_hash: Some(receipt.transaction_hash),
+                    }
+                }
+            );
+        }
+
+        #[test]
+        fn should_charge_cancellation_fee_with_fee_model_at_cancellation_creation() {
+            let creation_fee_model = FeeModel::OpStackL1DataFee {
+                max_l1_data_fee: Wei::new(1_000_000_000),
+            };
+            let mut transactions =
+                EthTransactions::new(TransactionNonce::ZERO).with_fee_model(creation_fee_model);
+            let withdrawal_id = LedgerBurnIndex::new(15);
+            let withdrawal_request = cketh_withdrawal_request_with_index(withdrawal_id);
+            let _signed_tx = record_sent_transaction(&mut transactions, withdrawal_request.clone());
+
+            let cancellation_tx = cancel(&mut transactions, withdrawal_id);
+            let signed_cancellation_tx =
+                create_and_record_signed_transaction(&mut transactions, cancellation_tx.clone());
+
+            transactions.update_fee_model(FeeModel::OpStackL1DataFee {
+                max_l1_data_fee: Wei::new(1_000_000_000_000),
+            });
+            let receipt = transaction_receipt(&signed_cancellation_tx, TransactionStatus::Success);
+            transactions.record_finalized_transaction(withdrawal_id, receipt.clone());
+
+            assert_eq!(
+                transactions.reimbursement_requests,
+                btreemap! {
+                    ReimbursementIndex::CkEth { ledger_burn_index: withdrawal_id } => ReimbursementRequest {
+                        ledger_burn_index: withdrawal_id,
+                        reimbursed_amount: withdrawal_request
+                            .withdrawal_amount
+                            .checked_sub(cancellation_tx.max_transaction_fee(&creation_fee_model))
+                            .unwrap()
+                            .change_units(),
+                        to: withdrawal_request.from,
+                        to_subaccount: withdrawal_request.from_subaccount,
+                        transaction_hash: Some(receipt.transaction_hash),
+                    }
+                }
+            );
+        }
+
+        #[test]
+        fn should_not_reimburse_when_original_transaction_mined_before_cancellation() {
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let withdrawal_id = LedgerBurnIndex::new(15);
+            let signed_tx = record_sent_transaction(
+                &mut transactions,
+                cketh_withdrawal_request_with_index(withdrawal_id),
+            );
+            let cancellation_tx = cancel(&mut transactions, withdrawal_id);
+            let _signed_cancellation_tx =
+                create_and_record_signed_transaction(&mut transactions, cancellation_tx);
+
+            transactions.record_finalized_transaction(
+                withdrawal_id,
+                transaction_receipt(&signed_tx, TransactionStatus::Success),
+            );
+
+            assert_eq!(transactions.reimbursement_requests, btreemap! {});
+            assert_eq!(transactions.replacement_requests, btreemap! {});
+        }
+
+        #[test]
+        fn should_not_resubmit_cancelled_transaction() {
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let withdrawal_id = LedgerBurnIndex::new(15);
+            let _signed_tx = record_sent_transaction(
+                &mut transactions,
+                cketh_withdrawal_request_with_index(withdrawal_id),
+            );
+            let _cancellation_tx = cancel(&mut transactions, withdrawal_id);
+
+            let higher_gas_fee = {
+                let mut gas_fee = gas_fee_estimate();
+                gas_fee.base_fee_per_gas = gas_fee.base_fee_per_gas.checked_mul(10_u8).unwrap();
+                gas_fee
+            };
+            assert_eq!(
+                transactions.create_resubmit_transactions(TransactionCount::ZERO, higher_gas_fee),
+                vec![]
+            );
+        }
+
+        #[test]
+        fn should_not_let_fee_bump_override_cancellation() {
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let withdrawal_id = LedgerBurnIndex::new(15);
+            let _signed_tx = record_sent_transaction(
+                &mut transactions,
+                cketh_withdrawal_request_with_index(withdrawal_id),
+            );
+
+            transactions.record_replacement_request(withdrawal_id, ReplacementRequest::Cancel);
+            assert_eq!(
+                transactions.check_replacement_request(&withdrawal_id, ReplacementRequest::Cancel),
+                Err(ReplacementRequestError::AlreadyCancelled)
+            );
+            transactions.record_replacement_request(withdrawal_id, ReplacementRequest::BumpFee);
+
+            assert_eq!(
+                transactions.replacement_requests,
+                btreemap! { withdrawal_id => ReplacementRequest::Cancel }
+            );
+        }
+
+        #[test]
+        fn should_reject_and_drop_replacement_exceeding_max_transaction_fee() {
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let withdrawal_id = LedgerBurnIndex::new(15);
+            let _signed_tx = record_sent_transaction(
+                &mut transactions,
+                ckerc20_withdrawal_request_with_index(withdrawal_id, LedgerBurnIndex::new(7)),
+            );
+            let much_higher_gas_fee = {
+                let mut gas_fee = gas_fee_estimate();
+                gas_fee.base_fee_per_gas = WeiPerGas::new(1_000_000_000_000_000);
+                gas_fee
+            };
+
+            for request in [ReplacementRequest::BumpFee, ReplacementRequest::Cancel] {
+                assert_eq!(
+                    transactions.check_replacement_transaction_fee(
+                        &withdrawal_id,
+                        request,
+                        gas_fee_estimate(),
+                        minter_address()
+                    ),
+                    Ok(())
+                );
+                assert_matches!(
+                    transactions.check_replacement_transaction_fee(
+                        &withdrawal_id,
+                        request,
+                        much_higher_gas_fee.clone(),
+                        minter_address()
+                    ),
+                    Err(ResubmitTransactionError::InsufficientTransactionFee { ledger_burn_index, .. })
+                        if ledger_burn_index == withdrawal_id
+                );
+            }
+
+            transactions.record_replacement_request(withdrawal_id, ReplacementRequest::BumpFee);
+            let replacements = transactions.create_replacement_transactions(
+                TransactionCount::ZERO,
+                much_higher_gas_fee,
+                minter_address(),
+            );
+            assert_matches!(
+                replacements.as_slice(),
+                [Err(
+                    ResubmitTransactionError::InsufficientTransactionFee { .. }
+                )]
+            );
+
+            transactions.record_failed_replacement_request(withdrawal_id);
+            assert_eq!(transactions.replacement_requests, btreemap! {});
+            assert_eq!(
+                transactions.create_replacement_transactions(
+                    TransactionCount::ZERO,
+                    gas_fee_estimate(),
+                    minter_address(),
+                ),
+                vec![]
+            );
+        }
+
+        fn record_sent_transaction<R: Into<WithdrawalRequest>>(
+            transactions: &mut EthTransactions,
+            withdrawal_request: R,
+        ) -> SignedEip1559TransactionRequest {
+            let withdrawal_request = withdrawal_request.into();
+            transactions.record_withdrawal_request(withdrawal_request.clone());
+            let created_tx =
+                create_and_record_transaction(transactions, withdrawal_request, gas_fee_estimate());
+            create_and_record_signed_transaction(transactions, created_tx)
+        }
+
+        fn cancel(
+            transactions: &mut EthTransactions,
+            withdrawal_id: LedgerBurnIndex,
+        ) -> Eip1559TransactionRequest {
+            transactions.record_replacement_request(withdrawal_id, ReplacementRequest::Cancel);
+            let replacements = transactions.create_replacement_transactions(
+                TransactionCount::ZERO,
+                gas_fee_estimate(),
+                minter_address(),
+            );
+            let cancellation_tx = match replacements.as_slice() {
+                [Ok(EventType::CreatedCancellationTransaction {
+                    withdrawal_id: id,
+                    transaction,
+                })] if id == &withdrawal_id => transaction.clone(),
+                _ => panic!("unexpected replacements {replacements:?}"),
+            };
+            assert!(cancellation_tx.is_cancellation());
+            transactions.record_cancellation_transaction(withdrawal_id, cancellation_tx.clone());
+            cancellation_tx
+        }
+
+        fn minter_address() -> Address {
+            MINTER_ADDRESS.parse().unwrap()
+        }
+    }
+
     mod transaction_status {
         use crate::endpoints::{RetrieveEthStatus, TxFinalizedStatus};
         use crate::eth_logs::LedgerSubaccount;
@@ -2264,7 +2679,7 @@ mod create_transaction {
         TransactionCallData,
     };
     use crate::tx::GasFeeEstimate;
-    use crate::tx::{AccessList, Eip1559TransactionRequest};
+    use crate::tx::{AccessList, Eip1559TransactionRequest, FeeModel};
     use crate::withdraw::CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT;
     use proptest::collection::vec as pvec;
     use proptest::prelude::any;
@@ -2290,6 +2705,7 @@ mod create_transaction {
                 gas_fee.clone(),
                 gas_limit,
                 EthereumNetwork::Sepolia,
+                &FeeModel::Eip1559,
             );
             prop_assert_eq!(
                 result,
@@ -2312,6 +2728,7 @@ mod create_transaction {
                 gas_fee,
                 gas_limit,
                 EthereumNetwork::Sepolia,
+                &FeeModel::Eip1559,
             );
             prop_assert_eq!(
                 result,
@@ -2326,7 +2743,7 @@ mod create_transaction {
 
     proptest! {
         #[test]
-        fn should_create_transaction(withdrawal_amount in 31_500_001_050_000_u64..=u64::MAX) {
+        fn should_create_transaction(withdrawal_amount in 31_500_001_050_001_u64..=u64::MAX) {
             let gas_fee = gas_fee_estimate();
             let gas_limit = CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT;
             let transaction_price = gas_fee.clone().to_price(gas_limit);
@@ -2348,6 +2765,7 @@ mod create_transaction {
                 gas_fee,
                 gas_limit,
                 EthereumNetwork::Sepolia,
+                &FeeModel::Eip1559,
             );
 
             prop_assert_eq!(result, Ok(Eip1559TransactionRequest {
@@ -2402,6 +2820,7 @@ mod create_transaction {
                 gas_fee.clone(),
                 gas_limit,
                 EthereumNetwork::Mainnet,
+                &FeeModel::Eip1559,
             ).unwrap();
             let tx_max_fee_per_gas = result.max_fee_per_gas;
             let max_tx_fee = tx_max_fee_per_gas.transaction_cost(gas_limit).unwrap();
@@ -2437,6 +2856,96 @@ mod create_transaction {
          }
 
     }
+
+    #[test]
+    fn should_deduct_l1_data_fee_from_cketh_withdrawal_on_op_stack_network() {
+        let gas_fee = gas_fee_estimate();
+        let network = EthereumNetwork::Base;
+        let gas_limit = network
+            .default_fee_model()
+            .gas_limit(CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT);
+        assert_eq!(gas_limit, CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT);
+        let transaction_price = gas_fee.clone().to_price(gas_limit);
+        let withdrawal_request = EthWithdrawalRequest {
+            withdrawal_amount: Wei::from(1_000_000_000_000_000_u64),
+            ..cketh_withdrawal_request_with_index(LedgerBurnIndex::new(15))
+        };
+
+        let tx = create_transaction(
+            &withdrawal_request.clone().into(),
+            TransactionNonce::TWO,
+            gas_fee,
+            gas_limit,
+            network,
+            &network.default_fee_model(),
+        )
+        .unwrap();
+
+        assert_eq!(tx.chain_id, 8453);
+        assert_eq!(
+            tx.amount,
+            withdrawal_request
+                .withdrawal_amount
+                .checked_sub(transaction_price.max_transaction_fee())
+                .and_then(|amount| amount.checked_sub(Wei::new(50_000_000_000_000)))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_set_aside_l1_data_fee_from_ckerc20_max_transaction_fee_on_op_stack_network() {
+        let gas_fee = gas_fee_estimate();
+        let network = EthereumNetwork::Optimism;
+        let max_l1_data_fee = network.default_fee_model().max_l1_data_fee();
+        let gas_limit = GasAmount::from(65_000_u64);
+        let min_tx_fee = gas_fee
+            .min_max_fee_per_gas()
+            .transaction_cost(gas_limit)
+            .unwrap();
+        let cketh_ledger_burn_index = LedgerBurnIndex::new(15);
+
+        let withdrawal_request = Erc20WithdrawalRequest {
+            max_transaction_fee: min_tx_fee.checked_add(max_l1_data_fee).unwrap(),
+            ..ckerc20_withdrawal_request_with_index(
+                cketh_ledger_burn_index,
+                LedgerBurnIndex::new(2),
+            )
+        };
+        let tx = create_transaction(
+            &withdrawal_request.clone().into(),
+            TransactionNonce::TWO,
+            gas_fee.clone(),
+            gas_limit,
+            network,
+            &network.default_fee_model(),
+        )
+        .unwrap();
+        let max_gas_fee = tx.max_fee_per_gas.transaction_cost(gas_limit).unwrap();
+        assert!(
+            max_gas_fee.checked_add(max_l1_data_fee).unwrap()
+                <= withdrawal_request.max_transaction_fee
+        );
+
+        let withdrawal_request_without_l1_data_fee = Erc20WithdrawalRequest {
+            max_transaction_fee: min_tx_fee,
+            ..withdrawal_request
+        };
+        assert_eq!(
+            create_transaction(
+                &withdrawal_request_without_l1_data_fee.clone().into(),
+                TransactionNonce::TWO,
+                gas_fee,
+                gas_limit,
+                network,
+                &network.default_fee_model(),
+            ),
+            Err(CreateTransactionError::InsufficientTransactionFee {
+                cketh_ledger_burn_index,
+                allowed_max_transaction_fee: min_tx_fee,
+                actual_max_transaction_fee: min_tx_fee.checked_add(max_l1_data_fee).unwrap(),
+            })
+        );
+    }
 }
 
 mod withdrawal_flow {
@@ -2444,6 +2953,7 @@ mod withdrawal_flow {
     use crate::numeric::TransactionNonce;
     use crate::state::transactions::tests::sign_transaction;
     use crate::state::transactions::{create_transaction, EthTransactions, EthereumNetwork};
+    use crate::tx::FeeModel;
     use crate::withdraw::estimate_gas_limit;
     use proptest::proptest;
     use std::cell::RefCell;
@@ -2473,6 +2983,7 @@ mod withdrawal_flow {
                     gas_fee_estimate.clone(),
                     estimate_gas_limit(&request),
                     EthereumNetwork::Sepolia,
+                    &FeeModel::Eip1559,
                 ){
                     wrapped_txs.borrow_mut().record_created_transaction(request.cketh_ledger_burn_index(), created_tx);
                 }
@@ -2853,6 +3364,7 @@ fn create_and_record_transaction<R: Into<WithdrawalRequest>>(
         gas_fee_estimate,
         estimate_gas_limit(&withdrawal_request),
         EthereumNetwork::Sepolia,
+        &FeeModel::Eip1559,
     )
     .expect("failed to create transaction");
     transactions.record_created_transaction(withdrawal_request.cketh_ledger_burn_index(), tx);
@@ -2905,6 +3417,7 @@ fn transaction_receipt(
         gas_used: signed_tx.transaction().gas_limit,
         status,
         transaction_hash: signed_tx.hash(),
+        l1_fee: None,
     }
 }
 
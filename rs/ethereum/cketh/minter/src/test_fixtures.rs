@@ -231,6 +231,7 @@ pub mod arb {
                         gas_used,
                         status,
                         transaction_hash,
+                        l1_fee: None,
                     }
                 },
             )
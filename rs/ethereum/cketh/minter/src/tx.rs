@@ -8,6 +8,7 @@ use crate::guard::TimerGuard;
 use crate::logs::{DEBUG, INFO};
 use crate::numeric::{BlockNumber, GasAmount, TransactionNonce, Wei, WeiPerGas};
 use crate::state::{lazy_call_ecdsa_public_key, mutate_state, read_state, TaskType};
+use crate::withdraw::CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT;
 use ethnum::u256;
 use evm_rpc_client::{BlockTag, FeeHistory, FeeHistoryArgs};
 use ic_canister_log::log;
@@ -123,8 +124,19 @@ impl<T> AsRef<T> for Resubmittable<T> {
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum ResubmissionStrategy {
-    ReduceEthAmount { withdrawal_amount: Wei },
-    GuaranteeEthAmount { allowed_max_transaction_fee: Wei },
+    ReduceEthAmount {
+        withdrawal_amount: Wei,
+    },
+    GuaranteeEthAmount {
+        allowed_max_transaction_fee: Wei,
+    },
+    /// Strategy of a transaction cancelling a withdrawal.
+    /// The transaction fee is computed with the fee model at the time the cancellation was created,
+    /// so that the fee charged for the cancellation does not depend on later changes of the fee model.
+    CancelWithdrawal {
+        allowed_max_transaction_fee: Wei,
+        fee_model: FeeModel,
+    },
 }
 
 impl ResubmissionStrategy {
@@ -133,9 +145,22 @@ impl ResubmissionStrategy {
             ResubmissionStrategy::ReduceEthAmount { withdrawal_amount } => *withdrawal_amount,
             ResubmissionStrategy::GuaranteeEthAmount {
                 allowed_max_transaction_fee,
+            }
+            | ResubmissionStrategy::CancelWithdrawal {
+                allowed_max_transaction_fee,
+                ..
             } => *allowed_max_transaction_fee,
         }
     }
+
+    /// The fee model used to compute the transaction fee of transactions with this strategy.
+    pub fn fee_model<'a>(&'a self, current_fee_model: &'a FeeModel) -> &'a FeeModel {
+        match self {
+            ResubmissionStrategy::ReduceEthAmount { .. }
+            | ResubmissionStrategy::GuaranteeEthAmount { .. } => current_fee_model,
+            ResubmissionStrategy::CancelWithdrawal { fee_model, .. } => fee_model,
+        }
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -150,36 +175,96 @@ impl SignedTransactionRequest {
     pub fn resubmit(
         &self,
         new_gas_fee: GasFeeEstimate,
+        fee_model: &FeeModel,
     ) -> Result<Option<Eip1559TransactionRequest>, ResubmitTransactionError> {
-        let transaction_request = self.transaction.transaction();
-        let last_tx_price = transaction_request.transaction_price();
+        let last_tx_price = self.transaction.transaction().transaction_price();
         let new_tx_price = last_tx_price
             .clone()
             .resubmit_transaction_price(new_gas_fee);
         if new_tx_price == last_tx_price {
             return Ok(None);
         }
+        self.replace_with_price(new_tx_price, fee_model).map(Some)
+    }
 
-        if new_tx_price.max_transaction_fee() > self.resubmission.allowed_max_transaction_fee() {
-            return Err(ResubmitTransactionError::InsufficientTransactionFee {
-                allowed_max_transaction_fee: self.resubmission.allowed_max_transaction_fee(),
-                actual_max_transaction_fee: new_tx_price.max_transaction_fee(),
-            });
-        }
+    /// Replace the sent transaction by the same transaction with a higher fee,
+    /// even if the current transaction price is still actual.
+    pub fn bump_fee(
+        &self,
+        new_gas_fee: GasFeeEstimate,
+        fee_model: &FeeModel,
+    ) -> Result<Eip1559TransactionRequest, ResubmitTransactionError> {
+        let new_tx_price = self
+            .transaction
+            .transaction()
+            .transaction_price()
+            .bump_transaction_price(new_gas_fee);
+        self.replace_with_price(new_tx_price, fee_model)
+    }
+
+    /// Replace the sent transaction by a zero-value self-transfer with the same nonce and a higher fee.
+    /// Once mined, the original transaction can no longer be included.
+    ///
+    /// The cancellation only needs the gas of a plain ETH transfer, which leaves room
+    /// to bump the fee per gas of ERC-20 transactions whose fee was capped by the user.
+    pub fn cancel(
+        &self,
+        new_gas_fee: GasFeeEstimate,
+        minter_address: Address,
+        fee_model: &FeeModel,
+    ) -> Result<Eip1559TransactionRequest, ResubmitTransactionError> {
+        let transaction_request = self.transaction.transaction();
+        let new_tx_price = TransactionPrice {
+            gas_limit: fee_model.gas_limit(CKETH_WITHDRAWAL_TRANSACTION_GAS_LIMIT),
+            ..transaction_request
+                .transaction_price()
+                .bump_transaction_price(new_gas_fee)
+        };
+        let replacement_tx = self.replace_with_price(new_tx_price, fee_model)?;
+        Ok(Eip1559TransactionRequest {
+            destination: minter_address,
+            amount: Wei::ZERO,
+            data: Vec::new(),
+            access_list: AccessList::new(),
+            ..replacement_tx
+        })
+    }
+
+    fn replace_with_price(
+        &self,
+        new_tx_price: TransactionPrice,
+        fee_model: &FeeModel,
+    ) -> Result<Eip1559TransactionRequest, ResubmitTransactionError> {
+        let transaction_request = self.transaction.transaction();
+        let new_max_transaction_fee = self
+            .resubmission
+            .fee_model(fee_model)
+            .max_transaction_fee(&new_tx_price);
         let new_amount = match self.resubmission {
-            ResubmissionStrategy::ReduceEthAmount { withdrawal_amount } => {
-                withdrawal_amount.checked_sub(new_tx_price.max_transaction_fee())
-                    .expect("BUG: withdrawal_amount covers new transaction fee because it was checked before")
+            // A zero-value transaction would be indistinguishable from a cancellation.
+            ResubmissionStrategy::ReduceEthAmount { withdrawal_amount } => withdrawal_amount
+                .checked_sub(new_max_transaction_fee)
+                .filter(|amount| amount > &Wei::ZERO),
+            ResubmissionStrategy::GuaranteeEthAmount {
+                allowed_max_transaction_fee,
             }
-            ResubmissionStrategy::GuaranteeEthAmount { .. } => transaction_request.amount,
-        };
-        Ok(Some(Eip1559TransactionRequest {
+            | ResubmissionStrategy::CancelWithdrawal {
+                allowed_max_transaction_fee,
+                ..
+            } => (new_max_transaction_fee <= allowed_max_transaction_fee)
+                .then_some(transaction_request.amount),
+        }
+        .ok_or_else(|| ResubmitTransactionError::InsufficientTransactionFee {
+            allowed_max_transaction_fee: self.resubmission.allowed_max_transaction_fee(),
+            actual_max_transaction_fee: new_max_transaction_fee,
+        })?;
+        Ok(Eip1559TransactionRequest {
             max_priority_fee_per_gas: new_tx_price.max_priority_fee_per_gas,
             max_fee_per_gas: new_tx_price.max_fee_per_gas,
             gas_limit: new_tx_price.gas_limit,
             amount: new_amount,
             ..transaction_request.clone()
-        }))
+        })
     }
 }
 
@@ -448,6 +533,19 @@ impl Eip1559TransactionRequest {
         }
     }
 
+    /// Maximum fee paid by the minter for this transaction, including any L1 data fee.
+    pub fn max_transaction_fee(&self, fee_model: &FeeModel) -> Wei {
+        fee_model.max_transaction_fee(&self.transaction_price())
+    }
+
+    /// Whether this transaction is a zero-value self-transfer cancelling a withdrawal, see
+    /// [`SignedTransactionRequest::cancel`].
+    /// Transactions for ETH withdrawals always transfer a positive amount and transactions for
+    /// ERC-20 withdrawals always have some call data, so that they cannot be mistaken for a cancellation.
+    pub fn is_cancellation(&self) -> bool {
+        self.amount == Wei::ZERO && self.data.is_empty()
+    }
+
     pub async fn sign(self) -> Result<SignedEip1559TransactionRequest, String> {
         let hash = self.hash();
         let key_name = read_state(|s| s.ecdsa_key_name.clone());
@@ -532,6 +630,48 @@ impl GasFeeEstimate {
     }
 }
 
+/// Additional costs charged by a network on top of the gas used by the execution of a transaction.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum FeeModel {
+    /// Ethereum L1: the transaction fee is `gas_used * effective_gas_price`.
+    Eip1559,
+    /// Arbitrum: the cost of posting the transaction to Ethereum is charged as additional L2 gas,
+    /// so that the gas limit must be raised by `l1_gas_buffer`.
+    ArbitrumL1Gas { l1_gas_buffer: GasAmount },
+    /// OP Stack (e.g., Base and Optimism): the cost of posting the transaction to Ethereum
+    /// is charged to the sender as a separate L1 data fee that is not part of the gas used,
+    /// and which the minter bounds by `max_l1_data_fee`.
+    OpStackL1DataFee { max_l1_data_fee: Wei },
+}
+
+impl FeeModel {
+    /// Gas limit of a transaction whose execution requires at most `execution_gas_limit`.
+    pub fn gas_limit(&self, execution_gas_limit: GasAmount) -> GasAmount {
+        match self {
+            FeeModel::Eip1559 | FeeModel::OpStackL1DataFee { .. } => execution_gas_limit,
+            FeeModel::ArbitrumL1Gas { l1_gas_buffer } => execution_gas_limit
+                .checked_add(*l1_gas_buffer)
+                .unwrap_or(GasAmount::MAX),
+        }
+    }
+
+    /// Maximum fee charged by the network outside of the gas used by the transaction.
+    pub fn max_l1_data_fee(&self) -> Wei {
+        match self {
+            FeeModel::Eip1559 | FeeModel::ArbitrumL1Gas { .. } => Wei::ZERO,
+            FeeModel::OpStackL1DataFee { max_l1_data_fee } => *max_l1_data_fee,
+        }
+    }
+
+    /// Maximum total fee paid by the sender of a transaction with the given price.
+    pub fn max_transaction_fee(&self, transaction_price: &TransactionPrice) -> Wei {
+        transaction_price
+            .max_transaction_fee()
+            .checked_add(self.max_l1_data_fee())
+            .unwrap_or(Wei::MAX)
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct TransactionPrice {
     pub gas_limit: GasAmount,
@@ -553,16 +693,6 @@ impl TransactionPrice {
     /// the transaction can be resubmitted (See [Retrying an EIP 1559 transaction](https://docs.alchemy.com/docs/retrying-an-eip-1559-transaction)).
     /// The current `max_fee_per_gas` will be kept as long as it is enough to cover the new `max_priority_fee_per_gas + base_fee_per_gas_next_block`.
     pub fn resubmit_transaction_price(self, new_gas_fee: GasFeeEstimate) -> Self {
-        let plus_10_percent = |amount: WeiPerGas| {
-            amount
-                .checked_add(
-                    amount
-                        .checked_div_ceil(10_u8)
-                        .expect("BUG: must be Some() because divisor is non-zero"),
-                )
-                .unwrap_or(WeiPerGas::MAX)
-        };
-
         if self.max_fee_per_gas >= new_gas_fee.min_max_fee_per_gas()
             && self.max_priority_fee_per_gas >= new_gas_fee.max_priority_fee_per_gas
         {
@@ -595,6 +725,36 @@ impl TransactionPrice {
             }
         }
     }
+
+    /// Estimate the transaction price to replace a transaction on explicit request.
+    ///
+    /// Unlike [`Self::resubmit_transaction_price`], the price is increased even if the current one is still actual:
+    /// both the `max_fee_per_gas` and the `max_priority_fee_per_gas` are increased by at least 10%,
+    /// which is the minimum increase required by Ethereum clients to replace a pending transaction with the same nonce.
+    pub fn bump_transaction_price(self, new_gas_fee: GasFeeEstimate) -> Self {
+        let max_priority_fee_per_gas = plus_10_percent(self.max_priority_fee_per_gas)
+            .max(new_gas_fee.max_priority_fee_per_gas);
+        let new_gas_fee = GasFeeEstimate {
+            max_priority_fee_per_gas,
+            ..new_gas_fee
+        };
+        TransactionPrice {
+            gas_limit: self.gas_limit,
+            max_fee_per_gas: plus_10_percent(self.max_fee_per_gas)
+                .max(new_gas_fee.min_max_fee_per_gas()),
+            max_priority_fee_per_gas,
+        }
+    }
+}
+
+fn plus_10_percent(amount: WeiPerGas) -> WeiPerGas {
+    amount
+        .checked_add(
+            amount
+                .checked_div_ceil(10_u8)
+                .expect("BUG: must be Some() because divisor is non-zero"),
+        )
+        .unwrap_or(WeiPerGas::MAX)
 }
 
 pub async fn lazy_refresh_gas_fee_estimate() -> Option<GasFeeEstimate> {
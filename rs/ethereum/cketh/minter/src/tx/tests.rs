@@ -160,6 +160,26 @@ mod resubmit_transaction_price {
     }
 }
 
+mod bump_transaction_price {
+    use crate::tx::tests::{arb_gas_fee_estimate, arb_transaction_price};
+    use proptest::{prop_assert, prop_assert_eq, proptest};
+
+    proptest! {
+        #[test]
+        fn should_increase_by_at_least_10_percent(initial_price in arb_transaction_price(), new_gas_fee in arb_gas_fee_estimate()) {
+            let updated_price = initial_price
+                .clone()
+                .bump_transaction_price(new_gas_fee.clone());
+
+            prop_assert_eq!(updated_price.gas_limit, initial_price.gas_limit);
+            prop_assert!(updated_price.max_priority_fee_per_gas >= initial_price.max_priority_fee_per_gas.checked_add(initial_price.max_priority_fee_per_gas.checked_div_ceil(10_u8).unwrap()).unwrap());
+            prop_assert!(updated_price.max_fee_per_gas >= initial_price.max_fee_per_gas.checked_add(initial_price.max_fee_per_gas.checked_div_ceil(10_u8).unwrap()).unwrap());
+            prop_assert!(updated_price.max_priority_fee_per_gas >= new_gas_fee.max_priority_fee_per_gas);
+            prop_assert!(updated_price.max_fee_per_gas >= updated_price.max_priority_fee_per_gas);
+        }
+    }
+}
+
 #[test]
 fn should_cbor_encoding_be_stable() {
     use crate::numeric::{GasAmount, TransactionNonce, Wei, WeiPerGas};
@@ -8,10 +8,10 @@ use crate::numeric::{GasAmount, LedgerBurnIndex, LedgerMintIndex, TransactionCou
 use crate::state::audit::{process_event, EventType};
 use crate::state::transactions::{
     create_transaction, CreateTransactionError, Reimbursed, ReimbursementIndex,
-    ReimbursementRequest, WithdrawalRequest,
+    ReimbursementRequest, ResubmitTransactionError, WithdrawalRequest,
 };
 use crate::state::{mutate_state, read_state, State, TaskType};
-use crate::tx::{lazy_refresh_gas_fee_estimate, GasFeeEstimate};
+use crate::tx::{lazy_refresh_gas_fee_estimate, FeeModel, GasFeeEstimate};
 use candid::Nat;
 use evm_rpc_client::SendRawTransactionStatus;
 use futures::future::join_all;
@@ -228,6 +228,48 @@ async fn resubmit_transactions_batch(
             }
         }
     }
+
+    let minter_address = crate::state::minter_address().await;
+    let replacement_transactions = read_state(|s| {
+        s.eth_transactions.create_replacement_transactions(
+            latest_transaction_count,
+            gas_fee_estimate.clone(),
+            minter_address,
+        )
+    });
+    for result in replacement_transactions {
+        match result {
+            Ok(event) => {
+                log!(
+                    INFO,
+                    "[resubmit_transactions_batch]: requested replacement {event:?}"
+                );
+                mutate_state(|s| process_event(s, event));
+            }
+            Err(e) => {
+                log!(
+                    INFO,
+                    "Failed to replace transaction, dropping the replacement request: {e:?}"
+                );
+                let ResubmitTransactionError::InsufficientTransactionFee {
+                    ledger_burn_index,
+                    allowed_max_transaction_fee,
+                    max_transaction_fee,
+                    ..
+                } = e;
+                mutate_state(|s| {
+                    process_event(
+                        s,
+                        EventType::FailedWithdrawalReplacement {
+                            withdrawal_id: ledger_burn_index,
+                            allowed_max_transaction_fee,
+                            max_transaction_fee,
+                        },
+                    )
+                });
+            }
+        }
+    }
 }
 
 fn create_transactions_batch(gas_fee_estimate: GasFeeEstimate) {
@@ -237,14 +279,16 @@ fn create_transactions_batch(gas_fee_estimate: GasFeeEstimate) {
     }) {
         log!(DEBUG, "[create_transactions_batch]: processing {request:?}",);
         let ethereum_network = read_state(State::ethereum_network);
+        let fee_model = read_state(|s| s.eth_transactions.fee_model());
         let nonce = read_state(|s| s.eth_transactions.next_transaction_nonce());
-        let gas_limit = estimate_gas_limit(&request);
+        let gas_limit = fee_model.gas_limit(estimate_gas_limit(&request));
         match create_transaction(
             &request,
             nonce,
             gas_fee_estimate.clone(),
             gas_limit,
             ethereum_network,
+            &fee_model,
         ) {
             Ok(transaction) => {
                 log!(
@@ -378,6 +422,12 @@ async fn finalize_transactions_batch() {
             let expected_finalized_withdrawal_ids: BTreeSet<_> =
                 txs_to_finalize.values().cloned().collect();
             let rpc_client = read_state(EthRpcClient::from_state);
+            let charges_l1_data_fee = read_state(|s| {
+                matches!(
+                    s.eth_transactions.fee_model(),
+                    FeeModel::OpStackL1DataFee { .. }
+                )
+            });
             let results = join_all(
                 txs_to_finalize
                     .keys()
@@ -387,8 +437,23 @@ async fn finalize_transactions_batch() {
             let mut receipts: BTreeMap<LedgerBurnIndex, TransactionReceipt> = BTreeMap::new();
             for ((hash, withdrawal_id), result) in zip(txs_to_finalize, results) {
                 match result {
-                    Ok(Some(receipt)) => {
+                    Ok(Some(mut receipt)) => {
                         log!(DEBUG, "Received transaction receipt {receipt:?} for transaction {hash} and withdrawal ID {withdrawal_id}");
+                        if charges_l1_data_fee {
+                            match rpc_client.eth_get_transaction_l1_fee(hash).await {
+                                Ok(Some(l1_fee)) => {
+                                    receipt.l1_fee = Some(l1_fee);
+                                }
+                                Ok(None) => {
+                                    log!(INFO, "ERROR: missing L1 fee in receipt for transaction {hash} with withdrawal ID {withdrawal_id}. Will retry later");
+                                    return;
+                                }
+                                Err(e) => {
+                                    log!(INFO, "Failed to get L1 fee for transaction {hash} and withdrawal ID {withdrawal_id}: {e:?}. Will retry later");
+                                    return;
+                                }
+                            }
+                        }
                         match receipts.get(&withdrawal_id) {
                             // by construction we never query twice the same transaction hash, which is a field in TransactionReceipt.
                             Some(existing_receipt) => {
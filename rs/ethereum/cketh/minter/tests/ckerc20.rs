@@ -822,6 +822,7 @@ mod withdraw_erc20 {
                             status: transaction_status.clone(),
                             transaction_hash: DEFAULT_CKERC20_WITHDRAWAL_TRANSACTION_HASH
                                 .to_string(),
+                            l1_fee: None,
                         },
                     },
                 ]);
@@ -1177,6 +1178,7 @@ mod withdraw_erc20 {
                         gas_used: Nat::from(21_000_u64),
                         status: TransactionStatus::Success,
                         transaction_hash: format!("{:?}", resubmitted_tx_hash),
+                        l1_fee: None,
                     },
                 },
             ]);
@@ -3,12 +3,14 @@ use candid::{Nat, Principal};
 use ic_base_types::PrincipalId;
 use ic_cketh_minter::blocklist::SAMPLE_BLOCKED_ADDRESS;
 use ic_cketh_minter::endpoints::events::{
-    EventPayload, EventSource, TransactionReceipt, TransactionStatus, UnsignedTransaction,
+    EventPayload, EventSource, ReplacementRequest, TransactionReceipt, TransactionStatus,
+    UnsignedTransaction,
 };
 use ic_cketh_minter::endpoints::CandidBlockTag::Finalized;
 use ic_cketh_minter::endpoints::{
-    CandidBlockTag, EthTransaction, GasFeeEstimate, MinterInfo, RetrieveEthStatus,
-    TxFinalizedStatus, WithdrawalError, WithdrawalStatus,
+    CandidBlockTag, EthTransaction, GasFeeEstimate, MinterInfo, RetrieveEthRequest,
+    RetrieveEthStatus, TxFinalizedStatus, WithdrawalError, WithdrawalReplacementError,
+    WithdrawalStatus,
 };
 use ic_cketh_minter::lifecycle::upgrade::UpgradeArg;
 use ic_cketh_minter::memo::{BurnMemo, MintMemo};
@@ -16,12 +18,13 @@ use ic_cketh_minter::numeric::BlockNumber;
 use ic_cketh_minter::{PROCESS_REIMBURSEMENT, SCRAPING_ETH_LOGS_INTERVAL};
 use ic_cketh_test_utils::flow::{
     double_and_increment_base_fee_per_gas, DepositCkEthParams, DepositCkEthWithSubaccountParams,
-    DepositParams, ProcessWithdrawalParams,
+    DepositParams, ProcessWithdrawal, ProcessWithdrawalParams,
 };
 use ic_cketh_test_utils::mock::{JsonRpcMethod, MockJsonRpcProviders};
 use ic_cketh_test_utils::response::{
     block_response, decode_transaction, default_signed_eip_1559_transaction, empty_logs,
-    hash_transaction, multi_logs_for_single_transaction,
+    encode_transaction, hash_transaction, multi_logs_for_single_transaction,
+    transaction_count_response,
 };
 use ic_cketh_test_utils::{
     CkEthSetup, JsonRpcProvider, CKETH_MINIMUM_WITHDRAWAL_AMOUNT, CKETH_TRANSFER_FEE,
@@ -37,6 +40,7 @@ use icrc_ledger_types::icrc1::transfer::Memo;
 use icrc_ledger_types::icrc3::transactions::{Burn, Mint};
 use num_traits::cast::ToPrimitive;
 use serde_json::json;
+use std::convert::identity;
 use std::str::FromStr;
 use std::time::Duration;
 
@@ -163,6 +167,7 @@ fn should_deposit_and_withdraw() {
                     status: TransactionStatus::Success,
                     transaction_hash:
                     "0x2cf1763e8ee3990103a31a5709b17b83f167738abb400844e67f608a98b0bdb5".to_string(),
+                    l1_fee: None,
                 },
             },
         ]);
@@ -580,6 +585,7 @@ fn should_reimburse() {
                     status: TransactionStatus::Failure,
                     transaction_hash:
                     "0x2cf1763e8ee3990103a31a5709b17b83f167738abb400844e67f608a98b0bdb5".to_string(),
+                    l1_fee: None,
                 },
             },
             EventPayload::ReimbursedEthWithdrawal {
@@ -687,11 +693,208 @@ fn should_resubmit_new_transaction_when_price_increased() {
                     gas_used: Nat::from(21_000_u32),
                     status: TransactionStatus::Success,
                     transaction_hash: format!("{:?}", resubmitted_tx_hash),
+                    l1_fee: None,
                 },
             },
         ]);
 }
 
+#[test]
+fn should_bump_fee_of_stuck_withdrawal_only_when_authorized() {
+    let (cketh, withdrawal_request) = withdrawal_with_pending_transaction();
+    let caller: Principal = cketh.caller.into();
+    let other_user = Principal::from_slice(&[0xff; 29]);
+    let withdrawal_id = withdrawal_request.block_index.clone();
+    let sent_transaction = default_sent_transaction();
+
+    assert_eq!(
+        cketh.bump_withdrawal_fee(caller, &(withdrawal_id.clone() + Nat::from(1_u8))),
+        Err(WithdrawalReplacementError::WithdrawalNotFound)
+    );
+    assert_eq!(
+        cketh.bump_withdrawal_fee(other_user, &withdrawal_id),
+        Err(WithdrawalReplacementError::CallerNotAuthorized)
+    );
+    assert_eq!(cketh.bump_withdrawal_fee(caller, &withdrawal_id), Ok(()));
+
+    ProcessWithdrawal {
+        setup: &cketh,
+        withdrawal_request,
+    }
+    .retry_processing_withdrawals()
+    .retrieve_fee_history(identity)
+    .expect_status(
+        RetrieveEthStatus::TxSent(sent_transaction.clone()),
+        WithdrawalStatus::TxSent(sent_transaction),
+    )
+    .retrieve_latest_transaction_count(identity)
+    .expect_status(RetrieveEthStatus::TxCreated)
+    .send_raw_transaction(identity)
+    .expect_status_sent();
+
+    let events: Vec<_> = cketh
+        .get_all_events()
+        .into_iter()
+        .map(|e| e.payload)
+        .collect();
+    assert!(
+        events.contains(&EventPayload::RequestedWithdrawalReplacement {
+            withdrawal_id: withdrawal_id.clone(),
+            request: ReplacementRequest::BumpFee,
+            requested_by: caller,
+        })
+    );
+    let bumped_tx = events
+        .iter()
+        .find_map(|event| match event {
+            EventPayload::ReplacedTransaction {
+                withdrawal_id: id,
+                transaction,
+            } if id == &withdrawal_id => Some(transaction.clone()),
+            _ => None,
+        })
+        .expect("BUG: missing replaced transaction");
+    let (first_tx, _sig) = default_signed_eip_1559_transaction();
+    assert!(
+        bumped_tx.max_priority_fee_per_gas
+            > Nat::from(first_tx.max_priority_fee_per_gas.unwrap().as_u128())
+    );
+    assert_eq!(
+        bumped_tx.destination,
+        DEFAULT_WITHDRAWAL_DESTINATION_ADDRESS
+    );
+}
+
+#[test]
+fn should_cancel_stuck_withdrawal_only_when_authorized() {
+    let cketh = CkEthSetup::default();
+    let caller: Principal = cketh.caller.into();
+    let withdrawal = cketh
+        .deposit(DepositParams::default())
+        .expect_mint()
+        .call_ledger_approve_minter(caller, EXPECTED_BALANCE, None)
+        .expect_ok(1)
+        .call_minter_withdraw_eth(
+            caller,
+            Nat::from(CKETH_WITHDRAWAL_AMOUNT),
+            DEFAULT_WITHDRAWAL_DESTINATION_ADDRESS.to_string(),
+        )
+        .expect_withdrawal_request_accepted();
+    assert_eq!(
+        withdrawal
+            .setup
+            .cancel_withdrawal(caller, withdrawal.withdrawal_id()),
+        Err(WithdrawalReplacementError::TransactionNotSent)
+    );
+
+    let (cketh, withdrawal_request) = send_stuck_transaction(withdrawal);
+    let other_user = Principal::from_slice(&[0xff; 29]);
+    let withdrawal_id = withdrawal_request.block_index.clone();
+    let sent_transaction = default_sent_transaction();
+
+    assert_eq!(
+        cketh.cancel_withdrawal(other_user, &withdrawal_id),
+        Err(WithdrawalReplacementError::CallerNotAuthorized)
+    );
+    assert_eq!(cketh.cancel_withdrawal(caller, &withdrawal_id), Ok(()));
+    assert_eq!(
+        cketh.cancel_withdrawal(caller, &withdrawal_id),
+        Err(WithdrawalReplacementError::AlreadyCancelled)
+    );
+
+    ProcessWithdrawal {
+        setup: &cketh,
+        withdrawal_request,
+    }
+    .retry_processing_withdrawals()
+    .retrieve_fee_history(identity)
+    .expect_status(
+        RetrieveEthStatus::TxSent(sent_transaction.clone()),
+        WithdrawalStatus::TxSent(sent_transaction),
+    )
+    .retrieve_latest_transaction_count(identity)
+    .expect_status(RetrieveEthStatus::TxCreated)
+    .send_raw_transaction(identity)
+    .expect_status_sent();
+
+    let events: Vec<_> = cketh
+        .get_all_events()
+        .into_iter()
+        .map(|e| e.payload)
+        .collect();
+    assert!(
+        events.contains(&EventPayload::RequestedWithdrawalReplacement {
+            withdrawal_id: withdrawal_id.clone(),
+            request: ReplacementRequest::Cancel,
+            requested_by: caller,
+        })
+    );
+    let cancellation_tx = events
+        .iter()
+        .find_map(|event| match event {
+            EventPayload::CreatedCancellationTransaction {
+                withdrawal_id: id,
+                transaction,
+            } if id == &withdrawal_id => Some(transaction.clone()),
+            _ => None,
+        })
+        .expect("BUG: missing cancellation transaction");
+    assert_eq!(
+        cancellation_tx.destination.to_lowercase(),
+        MINTER_ADDRESS.to_lowercase()
+    );
+    assert_eq!(cancellation_tx.value, Nat::from(0_u8));
+    assert_eq!(cancellation_tx.gas_limit, Nat::from(21_000_u32));
+}
+
+fn default_sent_transaction() -> EthTransaction {
+    let (tx, sig) = default_signed_eip_1559_transaction();
+    EthTransaction {
+        transaction_hash: format!("{:?}", hash_transaction(tx, sig)),
+    }
+}
+
+fn withdrawal_with_pending_transaction() -> (CkEthSetup, RetrieveEthRequest) {
+    let cketh = CkEthSetup::default();
+    let caller: Principal = cketh.caller.into();
+    let withdrawal = cketh
+        .deposit(DepositParams::default())
+        .expect_mint()
+        .call_ledger_approve_minter(caller, EXPECTED_BALANCE, None)
+        .expect_ok(1)
+        .call_minter_withdraw_eth(
+            caller,
+            Nat::from(CKETH_WITHDRAWAL_AMOUNT),
+            DEFAULT_WITHDRAWAL_DESTINATION_ADDRESS.to_string(),
+        )
+        .expect_withdrawal_request_accepted();
+    send_stuck_transaction(withdrawal)
+}
+
+fn send_stuck_transaction(
+    withdrawal: ProcessWithdrawal<CkEthSetup, RetrieveEthRequest>,
+) -> (CkEthSetup, RetrieveEthRequest) {
+    let (expected_tx, expected_sig) = default_signed_eip_1559_transaction();
+    let ProcessWithdrawal {
+        setup,
+        withdrawal_request,
+    } = withdrawal
+        .start_processing_withdrawals()
+        .retrieve_fee_history(identity)
+        .expect_status(RetrieveEthStatus::Pending, WithdrawalStatus::Pending)
+        .retrieve_latest_transaction_count(identity)
+        .expect_status(RetrieveEthStatus::TxCreated)
+        .send_raw_transaction_expecting(&encode_transaction(expected_tx, expected_sig))
+        .expect_status_sent()
+        .retrieve_finalized_transaction_count(|mock| {
+            mock.modify_response_for_all(&mut |count: &mut String| {
+                *count = transaction_count_response(0)
+            })
+        })
+        .expect_pending_transaction();
+    (setup, withdrawal_request)
+}
+
 #[test]
 fn should_not_overlap_when_scrapping_logs() {
     let cketh = CkEthSetup::default();
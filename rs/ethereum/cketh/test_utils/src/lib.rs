@@ -9,7 +9,8 @@ use ic_base_types::{CanisterId, PrincipalId};
 use ic_cketh_minter::endpoints::events::{Event, EventPayload, GetEventsResult};
 use ic_cketh_minter::endpoints::{
     AddCkErc20Token, Eip1559TransactionPriceArg, MinterInfo, RetrieveEthStatus, WithdrawalArg,
-    WithdrawalDetail, WithdrawalSearchParameter,
+    WithdrawalDetail, WithdrawalReplacementArg, WithdrawalReplacementError,
+    WithdrawalSearchParameter,
 };
 use ic_cketh_minter::lifecycle::upgrade::UpgradeArg;
 use ic_cketh_minter::logs::Log;
@@ -226,6 +227,47 @@ impl CkEthSetup {
         .unwrap()
     }
 
+    pub fn bump_withdrawal_fee(
+        &self,
+        from: Principal,
+        withdrawal_id: &Nat,
+    ) -> Result<(), WithdrawalReplacementError> {
+        self.request_withdrawal_replacement(from, "bump_withdrawal_fee", withdrawal_id)
+    }
+
+    pub fn cancel_withdrawal(
+        &self,
+        from: Principal,
+        withdrawal_id: &Nat,
+    ) -> Result<(), WithdrawalReplacementError> {
+        self.request_withdrawal_replacement(from, "cancel_withdrawal", withdrawal_id)
+    }
+
+    fn request_withdrawal_replacement(
+        &self,
+        from: Principal,
+        method: &str,
+        withdrawal_id: &Nat,
+    ) -> Result<(), WithdrawalReplacementError> {
+        let arg = WithdrawalReplacementArg {
+            withdrawal_id: withdrawal_id.0.to_u64().unwrap(),
+        };
+        Decode!(
+            &assert_reply(
+                self.env
+                    .execute_ingress_as(
+                        PrincipalId::from(from),
+                        self.minter_id,
+                        method,
+                        Encode!(&arg).unwrap(),
+                    )
+                    .expect("failed to request withdrawal replacement")
+            ),
+            Result<(), WithdrawalReplacementError>
+        )
+        .unwrap()
+    }
+
     pub fn balance_of(&self, account: impl Into<Account>) -> Nat {
         let ledger_id = self.ledger_id;
         self.balance_of_ledger(ledger_id, account)
@@ -12,9 +12,9 @@ use std::fmt::Debug;
 pub use evm_rpc_types::{
     Block, BlockTag, ConsensusStrategy, EthMainnetService, EthSepoliaService, FeeHistory,
     FeeHistoryArgs, GetLogsArgs, GetTransactionCountArgs, Hex, Hex20, Hex256, Hex32, HexByte,
-    HttpOutcallError, JsonRpcError, LogEntry, MultiRpcResult, Nat256, ProviderError, RpcApi,
-    RpcConfig, RpcError, RpcResult, RpcService, RpcServices, SendRawTransactionStatus,
-    TransactionReceipt, ValidationError,
+    HttpOutcallError, JsonRpcError, L2MainnetService, LogEntry, MultiRpcResult, Nat256,
+    ProviderError, RpcApi, RpcConfig, RpcError, RpcResult, RpcService, RpcServices,
+    SendRawTransactionStatus, TransactionReceipt, ValidationError,
 };
 
 #[async_trait]
@@ -129,6 +129,48 @@ impl<R: Runtime, L: Sink> EvmRpcClient<R, L> {
         .await
     }
 
+    /// Send a raw JSON-RPC request to a single provider.
+    ///
+    /// Useful to query fields that are not part of the typed responses of the EVM RPC canister,
+    /// e.g. the L1 fee contained in the transaction receipts of rollups.
+    pub async fn request(
+        &self,
+        service: RpcService,
+        json_rpc_payload: String,
+        max_response_bytes: u64,
+    ) -> RpcResult<String> {
+        log!(
+            self.logger,
+            "[{}]: Calling provider {:?} with JSON-RPC request '{}' and {} cycles",
+            self.evm_canister_id,
+            service,
+            json_rpc_payload,
+            self.min_attached_cycles
+        );
+        let result: RpcResult<String> = self
+            .runtime
+            .call(
+                self.evm_canister_id,
+                "request",
+                (service, json_rpc_payload, max_response_bytes),
+                self.min_attached_cycles,
+            )
+            .await
+            .unwrap_or_else(|(code, message)| {
+                Err(RpcError::HttpOutcallError(HttpOutcallError::IcError {
+                    code,
+                    message,
+                }))
+            });
+        log!(
+            self.logger,
+            "[{}]: Response to raw JSON-RPC request: {:?}",
+            self.evm_canister_id,
+            result
+        );
+        result
+    }
+
     async fn call_internal<In, Out>(
         &self,
         method: &str,
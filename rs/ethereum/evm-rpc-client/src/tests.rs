@@ -1,6 +1,9 @@
 use crate::tests::mock::{MockLogger, MockRuntime};
 use crate::EvmRpcClient;
-use crate::{Block, BlockTag, MultiRpcResult, ProviderError, RpcConfig, RpcError, RpcServices};
+use crate::{
+    Block, BlockTag, L2MainnetService, MultiRpcResult, ProviderError, RpcConfig, RpcError,
+    RpcResult, RpcService, RpcServices,
+};
 use mockall::Sequence;
 
 #[tokio::test]
@@ -106,6 +109,42 @@ async fn should_not_retry_when_max_num_retries_reached() {
     assert_eq!(result, expected_result);
 }
 
+#[tokio::test]
+async fn should_send_raw_request_to_single_provider() {
+    let mut runtime = MockRuntime::new();
+    let min_attached_cycles = 3_000_000_000_u128;
+    let payload = r#"{"jsonrpc":"2.0","method":"eth_chainId","params":[],"id":1}"#;
+    let expected_result: RpcResult<String> =
+        Ok(r#"{"jsonrpc":"2.0","id":1,"result":"0xa4b1"}"#.to_string());
+    runtime
+        .expect_call::<_, RpcResult<String>>()
+        .times(1)
+        .withf(
+            move |_,
+                  method,
+                  (service, json, max_response_bytes): &(RpcService, String, u64),
+                  attached_cycles| {
+                method == "request"
+                    && service == &RpcService::ArbitrumOne(L2MainnetService::PublicNode)
+                    && json == payload
+                    && max_response_bytes == &1_000
+                    && attached_cycles == &min_attached_cycles
+            },
+        )
+        .return_const(Ok(expected_result.clone()));
+
+    let client = test_client(runtime, min_attached_cycles, 3);
+    let result = client
+        .request(
+            RpcService::ArbitrumOne(L2MainnetService::PublicNode),
+            payload.to_string(),
+            1_000,
+        )
+        .await;
+
+    assert_eq!(result, expected_result);
+}
+
 mod max_expected_too_few_cycles_error {
     use super::*;
     use crate::max_expected_too_few_cycles_error;
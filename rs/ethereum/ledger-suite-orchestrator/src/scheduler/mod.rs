@@ -859,6 +859,7 @@ async fn install_ledger_suite<R: CanisterRuntime>(
     let index_arg = Some(IndexArg::Init(IndexInitArg {
         ledger_id: ledger_canister_id,
         retrieve_blocks_from_ledger_interval_seconds: None,
+        account_field_paths: None,
     }));
     install_canister_once::<Index, _, _>(
         &args.contract,
@@ -932,6 +933,8 @@ fn icrc1_ledger_init_arg(
         ),
         max_memo_length: Some(MAX_MEMO_LENGTH),
         feature_flags: Some(ICRC2_FEATURE),
+        max_transfers_per_batch: None,
+        max_balances_per_batch: None,
     }
 }
 
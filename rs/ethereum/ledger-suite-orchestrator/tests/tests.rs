@@ -84,6 +84,14 @@ fn should_spawn_ledger_with_correct_init_args() {
                 "icrc103:max_take_value".to_string(),
                 LedgerMetadataValue::from(500u64),
             ),
+            (
+                "icrc4:max_transfers".to_string(),
+                LedgerMetadataValue::from(100u64),
+            ),
+            (
+                "icrc4:max_balances".to_string(),
+                LedgerMetadataValue::from(1_000u64),
+            ),
         ]);
 }
 
@@ -14,6 +14,7 @@ use crate::{
     execution_environment_metrics::{
         ExecutionEnvironmentMetrics, SUBMITTED_OUTCOME_LABEL, SUCCESS_STATUS_LABEL,
     },
+    execution_trace::{ExecutionTraceEntry, ExecutionTracer},
     hypervisor::Hypervisor,
     ic00_permissions::Ic00MethodPermissions,
     metrics::{CallTreeMetrics, CallTreeMetricsImpl, IngressFilterMetrics},
@@ -67,7 +68,7 @@ use ic_replicated_state::{
     CanisterState, ExecutionTask, NetworkTopology, ReplicatedState,
 };
 use ic_types::{
-    canister_http::CanisterHttpRequestContext,
+    canister_http::{CanisterHttpRequestContext, Replication},
     crypto::{
         canister_threshold_sig::{MasterPublicKey, PublicKey},
         threshold_sig::ni_dkg::NiDkgTargetId,
@@ -341,6 +342,8 @@ pub struct ExecutionEnvironment {
     // the number of scheduler cores.
     resource_saturation_scaling: usize,
     deallocator_thread: DeallocatorThread,
+    // Receives the messages and tasks executed on the traced canisters (if any).
+    execution_tracer: Option<Arc<dyn ExecutionTracer>>,
 }
 
 /// This is a helper enum that indicates whether the current DTS execution of
@@ -439,6 +442,72 @@ impl ExecutionEnvironment {
             paused_execution_registry: Default::default(),
             resource_saturation_scaling,
             deallocator_thread,
+            execution_tracer: None,
+        }
+    }
+
+    /// Installs a tracer that receives the messages and tasks executed on
+    /// the canisters it traces.
+    pub fn with_execution_tracer(self, execution_tracer: Arc<dyn ExecutionTracer>) -> Self {
+        Self {
+            execution_tracer: Some(execution_tracer),
+            ..self
+        }
+    }
+
+    /// Returns a copy of the given input if executions on the given canister
+    /// are traced.
+    fn traced_input(
+        &self,
+        canister_id: CanisterId,
+        input: &CanisterMessageOrTask,
+    ) -> Option<CanisterMessageOrTask> {
+        match &self.execution_tracer {
+            Some(tracer) if tracer.is_traced(canister_id) => Some(input.clone()),
+            _ => None,
+        }
+    }
+
+    /// Reports a finished subnet message (e.g., `install_code` or
+    /// `update_settings`) to the tracer if it targets a traced canister.
+    fn trace_subnet_message(
+        &self,
+        message: &CanisterCall,
+        instructions_used: NumInstructions,
+        response: &Result<(Vec<u8>, Option<CanisterId>), UserError>,
+    ) {
+        let Some(tracer) = &self.execution_tracer else {
+            return;
+        };
+        // The canister returned in the response is the one the message acted
+        // on, even if it is not in the payload (e.g., `create_canister`).
+        let canister_id = response
+            .as_ref()
+            .ok()
+            .and_then(|(_, canister_id)| *canister_id)
+            .or_else(|| match message {
+                CanisterCall::Ingress(ingress) => ingress.effective_canister_id,
+                CanisterCall::Request(request) => request.extract_effective_canister_id(),
+            });
+        if let Some(canister_id) = canister_id.filter(|id| tracer.is_traced(*id)) {
+            tracer.trace(ExecutionTraceEntry::new_subnet_message(
+                canister_id,
+                message,
+                instructions_used,
+                response,
+            ));
+        }
+    }
+
+    /// Reports the result of executing the given traced input to the tracer.
+    fn trace_execution(
+        &self,
+        canister_id: CanisterId,
+        traced_input: Option<CanisterMessageOrTask>,
+        result: &ExecuteMessageResult,
+    ) {
+        if let (Some(tracer), Some(input)) = (&self.execution_tracer, traced_input) {
+            tracer.trace(ExecutionTraceEntry::new(canister_id, input, result));
         }
     }
 
@@ -586,6 +655,7 @@ impl ExecutionEnvironment {
                         response: Err(err),
                         refund,
                     },
+                    NumInstructions::from(0),
                     since,
                 );
                 return (state, Some(NumInstructions::from(0)));
@@ -953,22 +1023,34 @@ impl ExecutionEnvironment {
                                 response: Err(err),
                                 refund: msg.take_cycles(),
                             },
-                            Ok(args) => match CanisterHttpRequestContext::try_from((
-                                state.time(),
+                            Ok(args) => match self.canister_http_request_context(
+                                &state,
                                 request.as_ref(),
                                 args,
-                            )) {
+                                rng,
+                            ) {
                                 Err(err) => ExecuteSubnetMessageResult::Finished {
-                                    response: Err(err.into()),
+                                    response: Err(err),
                                     refund: msg.take_cycles(),
                                 },
                                 Ok(mut canister_http_request_context) => {
-                                    let http_request_fee =
-                                        self.cycles_account_manager.http_request_fee(
-                                            canister_http_request_context.variable_parts_size(),
-                                            canister_http_request_context.max_response_bytes,
-                                            registry_settings.subnet_size,
-                                        );
+                                    let http_request_fee = match canister_http_request_context
+                                        .replication
+                                    {
+                                        Replication::FullyReplicated => {
+                                            self.cycles_account_manager.http_request_fee(
+                                                canister_http_request_context.variable_parts_size(),
+                                                canister_http_request_context.max_response_bytes,
+                                                registry_settings.subnet_size,
+                                            )
+                                        }
+                                        Replication::NonReplicated(_) => self
+                                            .cycles_account_manager
+                                            .non_replicated_http_request_fee(
+                                                canister_http_request_context.variable_parts_size(),
+                                                canister_http_request_context.max_response_bytes,
+                                            ),
+                                    };
                                     // Here we make sure that we do not let upper layers open new
                                     // http calls while the maximum number of calls is in-flight.
                                     // Later, in the http adapter we also have a bounded queue of
@@ -1485,6 +1567,7 @@ impl ExecutionEnvironment {
             | Ok(Ic00Method::BitcoinGetUtxos)
             | Ok(Ic00Method::BitcoinGetBlockHeaders)
             | Ok(Ic00Method::BitcoinSendTransaction)
+            | Ok(Ic00Method::BitcoinGetBlockFilter)
             | Ok(Ic00Method::BitcoinGetCurrentFeePercentiles) => {
                 // Code path can only be triggered if there are no bitcoin canisters to route
                 // the request to.
@@ -1599,7 +1682,13 @@ impl ExecutionEnvironment {
                         refund: msg.take_cycles(),
                     };
 
-                    let state = self.finish_subnet_message_execution(state, msg, msg_result, since);
+                    let state = self.finish_subnet_message_execution(
+                        state,
+                        msg,
+                        msg_result,
+                        instructions_used,
+                        since,
+                    );
                     return (state, Some(instructions_used));
                 }
             },
@@ -1626,7 +1715,13 @@ impl ExecutionEnvironment {
                         refund: msg.take_cycles(),
                     };
 
-                    let state = self.finish_subnet_message_execution(state, msg, msg_result, since);
+                    let state = self.finish_subnet_message_execution(
+                        state,
+                        msg,
+                        msg_result,
+                        instructions_used,
+                        since,
+                    );
                     return (state, Some(instructions_used));
                 }
             },
@@ -1747,7 +1842,13 @@ impl ExecutionEnvironment {
         //   - `SignWithECDSA`
         // If you modify code below, please also update
         // these cases.
-        let state = self.finish_subnet_message_execution(state, msg, result, since);
+        let state = self.finish_subnet_message_execution(
+            state,
+            msg,
+            result,
+            NumInstructions::from(0),
+            since,
+        );
         (state, Some(NumInstructions::from(0)))
     }
 
@@ -1757,11 +1858,14 @@ impl ExecutionEnvironment {
         mut state: ReplicatedState,
         message: CanisterCall,
         result: ExecuteSubnetMessageResult,
+        instructions_used: NumInstructions,
         since: Instant,
     ) -> ReplicatedState {
         match &result {
             ExecuteSubnetMessageResult::Processing => {}
             ExecuteSubnetMessageResult::Finished { response, .. } => {
+                self.trace_subnet_message(&message, instructions_used, response);
+
                 // Request has been executed. Observe metrics and respond.
                 let method_name = String::from(message.method_name());
 
@@ -3316,6 +3420,7 @@ impl ExecutionEnvironment {
                             response: Err(err),
                             refund,
                         },
+                        NumInstructions::from(0),
                         since,
                     );
                     return (state, Some(NumInstructions::from(0)));
@@ -3484,6 +3589,7 @@ impl ExecutionEnvironment {
                         response: result,
                         refund,
                     },
+                    instructions_used,
                     since,
                 );
                 (state, Some(instructions_used))
@@ -3935,6 +4041,38 @@ impl ExecutionEnvironment {
         state
     }
 
+    /// Builds the context of a canister http request. For a non-replicated
+    /// request, a node of this subnet is picked at random to make the request.
+    fn canister_http_request_context(
+        &self,
+        state: &ReplicatedState,
+        request: &Request,
+        args: CanisterHttpRequestArgs,
+        rng: &mut dyn RngCore,
+    ) -> Result<CanisterHttpRequestContext, UserError> {
+        let is_replicated = args.is_replicated();
+        let mut context = CanisterHttpRequestContext::try_from((state.time(), request, args))?;
+        if !is_replicated {
+            let nodes = state
+                .metadata
+                .network_topology
+                .subnets
+                .get(&self.own_subnet_id)
+                .map(|subnet_topology| &subnet_topology.nodes)
+                .filter(|nodes| !nodes.is_empty())
+                .ok_or_else(|| {
+                    UserError::new(
+                        ErrorCode::CanisterRejectedMessage,
+                        "No node available to make a non-replicated http request.",
+                    )
+                })?;
+            let index = (rng.next_u64() % nodes.len() as u64) as usize;
+            let node_id = *nodes.iter().nth(index).unwrap();
+            context.replication = Replication::NonReplicated(node_id);
+        }
+        Ok(context)
+    }
+
     fn reject_unexpected_ingress(&self, method: Ic00Method) -> ExecuteSubnetMessageResult {
         self.metrics.unfiltered_ingress_error.inc();
         error!(
@@ -4101,6 +4239,8 @@ fn execute_canister_input(
     subnet_size: usize,
 ) -> ExecuteCanisterResult {
     let info = input.to_string();
+    let canister_id = canister.canister_id();
+    let traced_input = exec_env.traced_input(canister_id, &input);
     let result = exec_env.execute_canister_input(
         canister,
         instruction_limits,
@@ -4112,6 +4252,7 @@ fn execute_canister_input(
         round_limits,
         subnet_size,
     );
+    exec_env.trace_execution(canister_id, traced_input, &result);
     let (canister, instructions_used, heap_delta, ingress_status) = exec_env.process_result(result);
     ExecuteCanisterResult {
         canister,
@@ -4149,8 +4290,10 @@ pub fn execute_canister(
 
     let (input, prepaid_execution_cycles) = match canister.system_state.task_queue.pop_front() {
         Some(task) => match task {
-            ExecutionTask::PausedExecution { id, .. } => {
+            ExecutionTask::PausedExecution { id, input } => {
                 let paused = exec_env.take_paused_execution(id).unwrap();
+                let canister_id = canister.canister_id();
+                let traced_input = exec_env.traced_input(canister_id, &input);
                 let round_counters = RoundCounters {
                     execution_refund_error: &exec_env.metrics.execution_cycles_refund_error,
                     state_changes_error: &exec_env.metrics.state_changes_error,
@@ -4177,6 +4320,7 @@ pub fn execute_canister(
                     &exec_env.call_tree_metrics,
                     exec_env.deallocator_thread.sender(),
                 );
+                exec_env.trace_execution(canister_id, traced_input, &result);
                 let (canister, instructions_used, heap_delta, ingress_status) =
                     exec_env.process_result(result);
                 return ExecuteCanisterResult {
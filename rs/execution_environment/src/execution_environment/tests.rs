@@ -26,7 +26,7 @@ use ic_test_utilities_execution_environment::{
 };
 use ic_test_utilities_metrics::{fetch_histogram_vec_count, metric_vec};
 use ic_types::{
-    canister_http::{CanisterHttpMethod, Transform},
+    canister_http::{CanisterHttpMethod, Replication, Transform},
     ingress::{IngressState, IngressStatus, WasmResult},
     messages::{
         CallbackId, Payload, RejectContext, RequestOrResponse, Response, MAX_RESPONSE_COUNT_BYTES,
@@ -40,7 +40,7 @@ use ic_types_test_utils::ids::{canister_test_id, node_test_id, subnet_test_id, u
 use ic_universal_canister::{call_args, wasm, UNIVERSAL_CANISTER_WASM};
 use maplit::btreemap;
 use more_asserts::assert_gt;
-use std::mem::size_of;
+use std::{collections::BTreeSet, mem::size_of};
 
 #[cfg(test)]
 mod canister_task;
@@ -1851,6 +1851,7 @@ fn http_request_bound_holds() {
         headers: BoundedHttpHeaders::new(vec![]),
         body: None,
         method: HttpMethod::GET,
+        is_replicated: None,
         transform: Some(TransformContext {
             function: TransformFunc(candid::Func {
                 principal: caller_canister.get().0,
@@ -2408,6 +2409,7 @@ fn execute_canister_http_request() {
         headers: BoundedHttpHeaders::new(vec![]),
         body: None,
         method: HttpMethod::GET,
+        is_replicated: None,
         transform: Some(TransformContext {
             function: TransformFunc(candid::Func {
                 principal: caller_canister.get().0,
@@ -2469,6 +2471,69 @@ fn execute_canister_http_request() {
     );
 }
 
+#[test]
+fn execute_non_replicated_canister_http_request() {
+    let own_subnet = subnet_test_id(1);
+    let caller_canister = canister_test_id(10);
+    let mut test = ExecutionTestBuilder::new()
+        .with_own_subnet_id(own_subnet)
+        .with_caller(own_subnet, caller_canister)
+        .build();
+    test.state_mut().metadata.own_subnet_features.http_requests = true;
+    let nodes: BTreeSet<_> = vec![node_test_id(1), node_test_id(2), node_test_id(3)]
+        .into_iter()
+        .collect();
+    test.state_mut()
+        .metadata
+        .network_topology
+        .subnets
+        .entry(own_subnet)
+        .or_default()
+        .nodes = nodes.clone();
+
+    let response_size_limit = 1000u64;
+    let args = CanisterHttpRequestArgs {
+        url: "https://".to_string(),
+        max_response_bytes: Some(response_size_limit),
+        headers: BoundedHttpHeaders::new(vec![]),
+        body: None,
+        method: HttpMethod::GET,
+        is_replicated: Some(false),
+        transform: None,
+    };
+
+    let payment = Cycles::new(1_000_000_000);
+    test.inject_call_to_ic00(Method::HttpRequest, args.encode(), payment);
+    test.execute_all();
+
+    let http_request_context = test
+        .state()
+        .metadata
+        .subnet_call_context_manager
+        .canister_http_request_contexts
+        .get(&CallbackId::from(0))
+        .unwrap()
+        .clone();
+    match http_request_context.replication {
+        Replication::NonReplicated(node_id) => assert!(nodes.contains(&node_id)),
+        Replication::FullyReplicated => panic!("Expected a non-replicated request"),
+    }
+
+    let fee = test
+        .cycles_account_manager()
+        .non_replicated_http_request_fee(
+            http_request_context.variable_parts_size(),
+            Some(NumBytes::from(response_size_limit)),
+        );
+    assert!(
+        fee < test.http_request_fee(
+            http_request_context.variable_parts_size(),
+            Some(NumBytes::from(response_size_limit)),
+        )
+    );
+    assert_eq!(http_request_context.request.payment, payment - fee);
+}
+
 #[test]
 fn execute_canister_http_request_disabled() {
     let own_subnet = subnet_test_id(1);
@@ -2487,6 +2552,7 @@ fn execute_canister_http_request_disabled() {
         headers: BoundedHttpHeaders::new(vec![]),
         body: None,
         method: HttpMethod::GET,
+        is_replicated: None,
         transform: Some(TransformContext {
             function: TransformFunc(candid::Func {
                 principal: caller_canister.get().0,
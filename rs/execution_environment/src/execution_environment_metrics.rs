@@ -183,6 +183,7 @@ impl ExecutionEnvironmentMetrics {
                     | ic00::Method::BitcoinGetBlockHeaders
                     | ic00::Method::BitcoinSendTransaction
                     | ic00::Method::BitcoinGetCurrentFeePercentiles
+                    | ic00::Method::BitcoinGetBlockFilter
                     | ic00::Method::NodeMetricsHistory
                     | ic00::Method::SubnetInfo
                     | ic00::Method::FetchCanisterLogs
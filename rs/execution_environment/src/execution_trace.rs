@@ -0,0 +1,206 @@
+//! Opt-in tracing of the messages and tasks executed on canisters in
+//! replicated mode.
+//!
+//! If an [`ExecutionTracer`] is installed via
+//! [`crate::ExecutionServices::setup_execution_with_tracer`], then the
+//! execution environment reports every message or task (including each slice
+//! of a long execution) executed on a canister the tracer is interested in,
+//! as well as every subnet message (e.g., `install_code` or `update_settings`)
+//! that targets such a canister.
+//! This allows tools such as `ic-replay` to observe executions without
+//! rebuilding the replica.
+
+use crate::execution_environment::{ExecuteMessageResult, ExecutionResponse};
+use ic_base_types::PrincipalId;
+use ic_error_types::{RejectCode, UserError};
+use ic_types::{
+    ingress::{IngressState, IngressStatus, WasmResult},
+    messages::{
+        CanisterCall, CanisterMessage, CanisterMessageOrTask, CanisterTask, Payload, RejectContext,
+    },
+    methods::SystemMethod,
+    CanisterId, NumBytes, NumInstructions,
+};
+use std::sync::Arc;
+
+/// The outcome of an executed message or task.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum ExecutionTraceOutcome {
+    /// The message was replied to with the given payload.
+    Reply(Vec<u8>),
+    /// The message was rejected by the canister or the system.
+    Reject(RejectContext),
+    /// The execution of an ingress message failed.
+    Error(UserError),
+    /// The execution finished without producing a response, e.g., a task or a
+    /// call that is still waiting for the responses to its downstream calls.
+    NoResponse,
+    /// The execution was paused and continues in a later round.
+    Paused,
+}
+
+impl ExecutionTraceOutcome {
+    fn new(result: &ExecuteMessageResult) -> Self {
+        match result {
+            ExecuteMessageResult::Paused { .. } => Self::Paused,
+            ExecuteMessageResult::Finished { response, .. } => match response {
+                ExecutionResponse::Ingress((_, IngressStatus::Known { state, .. })) => {
+                    match state {
+                        IngressState::Completed(WasmResult::Reply(payload)) => {
+                            Self::Reply(payload.clone())
+                        }
+                        IngressState::Completed(WasmResult::Reject(message)) => Self::Reject(
+                            RejectContext::new(RejectCode::CanisterReject, message.clone()),
+                        ),
+                        IngressState::Failed(err) => Self::Error(err.clone()),
+                        IngressState::Received | IngressState::Processing | IngressState::Done => {
+                            Self::NoResponse
+                        }
+                    }
+                }
+                ExecutionResponse::Ingress((_, IngressStatus::Unknown)) => Self::NoResponse,
+                ExecutionResponse::Request(response) => match &response.response_payload {
+                    Payload::Data(payload) => Self::Reply(payload.clone()),
+                    Payload::Reject(context) => Self::Reject(context.clone()),
+                },
+                ExecutionResponse::Empty => Self::NoResponse,
+            },
+        }
+    }
+}
+
+/// A message or task executed on a canister.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ExecutionTraceEntry {
+    pub canister_id: CanisterId,
+    /// The executed message or task.
+    pub input: CanisterMessageOrTask,
+    /// The instructions used by the execution or `None` if the execution was
+    /// paused. For a long execution, the instructions of all its slices are
+    /// reported once it finishes.
+    pub instructions_used: Option<NumInstructions>,
+    /// The heap delta produced by the execution. Always zero for subnet messages.
+    pub heap_delta: NumBytes,
+    pub outcome: ExecutionTraceOutcome,
+}
+
+impl ExecutionTraceEntry {
+    pub(crate) fn new(
+        canister_id: CanisterId,
+        input: CanisterMessageOrTask,
+        result: &ExecuteMessageResult,
+    ) -> Self {
+        let (instructions_used, heap_delta) = match result {
+            ExecuteMessageResult::Finished {
+                instructions_used,
+                heap_delta,
+                ..
+            } => (Some(*instructions_used), *heap_delta),
+            ExecuteMessageResult::Paused { .. } => (None, NumBytes::from(0)),
+        };
+        Self {
+            canister_id,
+            input,
+            instructions_used,
+            heap_delta,
+            outcome: ExecutionTraceOutcome::new(result),
+        }
+    }
+
+    /// Creates the entry of a finished subnet message that targets the given
+    /// canister.
+    pub(crate) fn new_subnet_message(
+        canister_id: CanisterId,
+        message: &CanisterCall,
+        instructions_used: NumInstructions,
+        response: &Result<(Vec<u8>, Option<CanisterId>), UserError>,
+    ) -> Self {
+        let (input, outcome) = match message {
+            CanisterCall::Ingress(ingress) => (
+                CanisterMessage::Ingress(Arc::clone(ingress)),
+                match response {
+                    Ok((payload, _)) => ExecutionTraceOutcome::Reply(payload.clone()),
+                    Err(err) => ExecutionTraceOutcome::Error(err.clone()),
+                },
+            ),
+            CanisterCall::Request(request) => (
+                CanisterMessage::Request(Arc::clone(request)),
+                match response {
+                    Ok((payload, _)) => ExecutionTraceOutcome::Reply(payload.clone()),
+                    Err(err) => ExecutionTraceOutcome::Reject(err.clone().into()),
+                },
+            ),
+        };
+        Self {
+            canister_id,
+            input: CanisterMessageOrTask::Message(input),
+            instructions_used: Some(instructions_used),
+            heap_delta: NumBytes::from(0),
+            outcome,
+        }
+    }
+
+    /// Returns the receiver of the executed message: the traced canister
+    /// itself, or the management canister for subnet messages targeting it.
+    pub fn receiver(&self) -> CanisterId {
+        match &self.input {
+            CanisterMessageOrTask::Message(CanisterMessage::Ingress(ingress)) => ingress.receiver,
+            CanisterMessageOrTask::Message(CanisterMessage::Request(request)) => request.receiver,
+            CanisterMessageOrTask::Message(CanisterMessage::Response(_))
+            | CanisterMessageOrTask::Task(_) => self.canister_id,
+        }
+    }
+
+    /// Returns the name of the executed method: the method name of ingress
+    /// messages and requests, the system method of tasks, and
+    /// `callback_<id>` for responses.
+    pub fn method_name(&self) -> String {
+        match &self.input {
+            CanisterMessageOrTask::Message(CanisterMessage::Ingress(ingress)) => {
+                ingress.method_name.clone()
+            }
+            CanisterMessageOrTask::Message(CanisterMessage::Request(request)) => {
+                request.method_name.clone()
+            }
+            CanisterMessageOrTask::Message(CanisterMessage::Response(response)) => {
+                format!("callback_{}", response.originator_reply_callback)
+            }
+            CanisterMessageOrTask::Task(task) => match task {
+                CanisterTask::Heartbeat => SystemMethod::CanisterHeartbeat,
+                CanisterTask::GlobalTimer => SystemMethod::CanisterGlobalTimer,
+                CanisterTask::OnLowWasmMemory => SystemMethod::CanisterOnLowWasmMemory,
+            }
+            .to_string(),
+        }
+    }
+
+    /// Returns the principal that sent the executed message: the user of an
+    /// ingress message, the calling canister of a request, the responding
+    /// canister of a response, and `None` for tasks.
+    pub fn caller(&self) -> Option<PrincipalId> {
+        match &self.input {
+            CanisterMessageOrTask::Message(CanisterMessage::Ingress(ingress)) => {
+                Some(ingress.source.get())
+            }
+            CanisterMessageOrTask::Message(CanisterMessage::Request(request)) => {
+                Some(request.sender.get())
+            }
+            CanisterMessageOrTask::Message(CanisterMessage::Response(response)) => {
+                Some(response.respondent.get())
+            }
+            CanisterMessageOrTask::Task(_) => None,
+        }
+    }
+}
+
+/// Receives the messages and tasks executed on the traced canisters.
+///
+/// The tracer is called from the execution threads of the scheduler, so it
+/// must be cheap and must not block.
+pub trait ExecutionTracer: Send + Sync {
+    /// Returns `true` iff executions on the given canister should be traced.
+    fn is_traced(&self, canister_id: CanisterId) -> bool;
+
+    /// Records the execution of a message or task on a traced canister.
+    fn trace(&self, entry: ExecutionTraceEntry);
+}
@@ -41,6 +41,7 @@ impl Ic00MethodPermissions {
             | Ic00Method::BitcoinGetBlockHeaders
             | Ic00Method::BitcoinSendTransaction
             | Ic00Method::BitcoinGetCurrentFeePercentiles
+            | Ic00Method::BitcoinGetBlockFilter
             | Ic00Method::BitcoinSendTransactionInternal
             | Ic00Method::BitcoinGetSuccessors
             | Ic00Method::NodeMetricsHistory
@@ -4,6 +4,7 @@ mod canister_settings;
 pub mod execution;
 mod execution_environment;
 mod execution_environment_metrics;
+mod execution_trace;
 mod history;
 mod hypervisor;
 mod ic00_permissions;
@@ -19,6 +20,7 @@ pub use execution_environment::{
     as_num_instructions, as_round_instructions, execute_canister, CompilationCostHandling,
     ExecuteMessageResult, ExecutionEnvironment, ExecutionResponse, RoundInstructions, RoundLimits,
 };
+pub use execution_trace::{ExecutionTraceEntry, ExecutionTraceOutcome, ExecutionTracer};
 pub use history::{IngressHistoryReaderImpl, IngressHistoryWriterImpl};
 pub use hypervisor::{Hypervisor, HypervisorMetrics};
 use ic_base_types::PrincipalId;
@@ -39,7 +41,10 @@ use ic_types::{
     Height, SubnetId,
 };
 pub use metrics::IngressFilterMetrics;
-pub use query_handler::InternalHttpQueryHandler;
+pub use query_handler::{
+    get_latest_certified_state_and_data_certificate, InternalHttpQueryHandler, QueryCacheOutcome,
+    QueryCallTrace, QueryCallTraceNode, QueryCallTraceOutcome,
+};
 use query_handler::{HttpQueryHandler, QueryScheduler, QuerySchedulerFlag};
 pub use scheduler::RoundSchedule;
 use scheduler::SchedulerImpl;
@@ -84,6 +89,10 @@ pub struct ExecutionServices {
     pub ingress_history_reader: Box<dyn IngressHistoryReader>,
     pub query_execution_service: QueryExecutionService,
     pub https_outcalls_service: QueryExecutionService,
+    /// The synchronous query handler behind the query execution services. It
+    /// allows tests to execute traced queries, see
+    /// `InternalHttpQueryHandler::query_with_trace`.
+    pub query_handler: Arc<InternalHttpQueryHandler>,
     pub scheduler: Box<dyn Scheduler<State = ReplicatedState>>,
     pub query_stats_payload_builder: QueryStatsPayloadBuilderParams,
 }
@@ -104,6 +113,40 @@ impl ExecutionServices {
         fd_factory: Arc<dyn PageAllocatorFileDescriptor>,
         completed_execution_messages_tx: Sender<(MessageId, Height)>,
         temp_dir: &Path,
+    ) -> ExecutionServices {
+        Self::setup_execution_with_tracer(
+            logger,
+            metrics_registry,
+            own_subnet_id,
+            own_subnet_type,
+            scheduler_config,
+            config,
+            cycles_account_manager,
+            state_reader,
+            fd_factory,
+            completed_execution_messages_tx,
+            temp_dir,
+            None,
+        )
+    }
+
+    /// Same as `setup_execution`, but additionally installs the given tracer
+    /// (if any) that receives the messages and tasks executed on the canisters
+    /// it traces.
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
+    pub fn setup_execution_with_tracer(
+        logger: ReplicaLogger,
+        metrics_registry: &MetricsRegistry,
+        own_subnet_id: SubnetId,
+        own_subnet_type: SubnetType,
+        scheduler_config: SchedulerConfig,
+        config: Config,
+        cycles_account_manager: Arc<CyclesAccountManager>,
+        state_reader: Arc<dyn StateReader<State = ReplicatedState>>,
+        fd_factory: Arc<dyn PageAllocatorFileDescriptor>,
+        completed_execution_messages_tx: Sender<(MessageId, Height)>,
+        temp_dir: &Path,
+        execution_tracer: Option<Arc<dyn ExecutionTracer>>,
     ) -> ExecutionServices {
         let hypervisor = Arc::new(Hypervisor::new(
             config.clone(),
@@ -130,7 +173,7 @@ impl ExecutionServices {
         let (query_stats_collector, query_stats_payload_builder) =
             ic_query_stats::init_query_stats(logger.clone(), &config, metrics_registry);
 
-        let exec_env = Arc::new(ExecutionEnvironment::new(
+        let exec_env = ExecutionEnvironment::new(
             logger.clone(),
             Arc::clone(&hypervisor),
             Arc::clone(&ingress_history_writer) as Arc<_>,
@@ -146,7 +189,11 @@ impl ExecutionServices {
             scheduler_config.upload_wasm_chunk_instructions,
             scheduler_config.canister_snapshot_baseline_instructions,
             scheduler_config.canister_snapshot_data_baseline_instructions,
-        ));
+        );
+        let exec_env = Arc::new(match execution_tracer {
+            Some(execution_tracer) => exec_env.with_execution_tracer(execution_tracer),
+            None => exec_env,
+        });
         let sync_query_handler = Arc::new(InternalHttpQueryHandler::new(
             logger.clone(),
             hypervisor,
@@ -211,6 +258,7 @@ impl ExecutionServices {
             ingress_history_reader,
             query_execution_service,
             https_outcalls_service,
+            query_handler: sync_query_handler,
             scheduler,
             query_stats_payload_builder,
         }
@@ -3,6 +3,7 @@
 
 mod query_cache;
 mod query_call_graph;
+mod query_call_trace;
 mod query_context;
 mod query_scheduler;
 #[cfg(test)]
@@ -49,8 +50,12 @@ use std::{
 use tokio::sync::oneshot;
 use tower::{util::BoxCloneService, Service};
 
+pub use self::query_call_trace::{
+    QueryCacheOutcome, QueryCallTrace, QueryCallTraceNode, QueryCallTraceOutcome,
+};
 pub(crate) use self::query_scheduler::{QueryScheduler, QuerySchedulerFlag};
 use ic_management_canister_types_private::{
+    CanisterLogContentFilter, CanisterLogFilter, CanisterLogRange, CanisterLogRecord,
     FetchCanisterLogsRequest, FetchCanisterLogsResponse, LogVisibilityV2, Payload, QueryMethod,
 };
 
@@ -62,7 +67,9 @@ fn into_cbor<R: Serialize>(r: &R) -> Vec<u8> {
     ser.into_inner()
 }
 
-fn get_latest_certified_state_and_data_certificate(
+/// Returns the latest certified state together with the data certificate of
+/// the given canister.
+pub fn get_latest_certified_state_and_data_certificate(
     state_reader: Arc<dyn StateReader<State = ReplicatedState>>,
     certificate_delegation: Option<CertificateDelegation>,
     canister_id: CanisterId,
@@ -195,6 +202,35 @@ impl InternalHttpQueryHandler {
         query: Query,
         state: Labeled<Arc<ReplicatedState>>,
         data_certificate: Vec<u8>,
+    ) -> Result<WasmResult, UserError> {
+        self.query_impl(query, state, data_certificate, None)
+    }
+
+    /// Same as `query()`, but additionally returns the trace of the query
+    /// call tree of the query.
+    ///
+    /// This is used to debug composite queries in tests.
+    pub fn query_with_trace(
+        &self,
+        query: Query,
+        state: Labeled<Arc<ReplicatedState>>,
+        data_certificate: Vec<u8>,
+    ) -> (Result<WasmResult, UserError>, QueryCallTrace) {
+        let mut trace = QueryCallTrace::new(
+            self.config.max_query_call_graph_depth,
+            self.config.max_query_call_graph_instructions,
+            self.max_instructions_per_query,
+        );
+        let result = self.query_impl(query, state, data_certificate, Some(&mut trace));
+        (result, trace)
+    }
+
+    fn query_impl(
+        &self,
+        query: Query,
+        state: Labeled<Arc<ReplicatedState>>,
+        data_certificate: Vec<u8>,
+        mut trace: Option<&mut QueryCallTrace>,
     ) -> Result<WasmResult, UserError> {
         let measurement_scope = MeasurementScope::root(&self.metrics.query);
 
@@ -240,8 +276,14 @@ impl InternalHttpQueryHandler {
                 self.query_cache
                     .get_valid_result(&key, state, query_stats_collector)
             {
+                if let Some(trace) = trace {
+                    trace.cache = QueryCacheOutcome::Hit;
+                }
                 return result;
             }
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.cache = QueryCacheOutcome::Miss;
+            }
             Some(key)
         } else {
             None
@@ -280,8 +322,14 @@ impl InternalHttpQueryHandler {
             query_stats_collector,
             Arc::clone(&self.cycles_account_manager),
         );
+        if trace.is_some() {
+            context = context.with_call_tracer();
+        }
 
         let result = context.run(query, &self.metrics, &measurement_scope);
+        if let Some(trace) = trace {
+            context.take_call_trace(&result, trace);
+        }
         context.accumulate_transient_errors_from_result(result.as_ref());
         context.observe_metrics(&self.metrics);
 
@@ -327,18 +375,117 @@ fn fetch_canister_logs(
         )),
     }?;
 
+    let canister_log = &canister.system_state.canister_log;
+    let matcher = CanisterLogMatcher::new(args.filter)?;
+    let max_records = args.max_records.unwrap_or(u64::MAX) as usize;
+
+    let mut canister_log_records = Vec::new();
+    let mut next_idx = canister_log.next_idx();
+    for record in canister_log.records().iter() {
+        if !matcher.matches(record) {
+            continue;
+        }
+        if canister_log_records.len() >= max_records {
+            // Resume from this record on the next request.
+            next_idx = record.idx;
+            break;
+        }
+        canister_log_records.push(record.clone());
+    }
+
     let response = FetchCanisterLogsResponse {
-        canister_log_records: canister
-            .system_state
-            .canister_log
-            .records()
-            .iter()
-            .cloned()
-            .collect(),
+        canister_log_records,
+        next_idx: Some(next_idx),
     };
     Ok(WasmResult::Reply(Encode!(&response).unwrap()))
 }
 
+/// The maximum length of a regular expression in a canister log filter.
+const MAX_CANISTER_LOG_REGEX_LEN: usize = 1_024;
+
+/// The maximum size of a compiled regular expression in a canister log filter.
+const MAX_CANISTER_LOG_REGEX_SIZE: usize = 1 << 20;
+
+/// A validated `CanisterLogFilter` that can be applied to log records.
+#[derive(Default)]
+struct CanisterLogMatcher {
+    idx_range: Option<CanisterLogRange>,
+    timestamp_nanos_range: Option<CanisterLogRange>,
+    substring: Option<Vec<u8>>,
+    regex: Option<regex::bytes::Regex>,
+}
+
+impl CanisterLogMatcher {
+    fn new(filter: Option<CanisterLogFilter>) -> Result<Self, UserError> {
+        let Some(filter) = filter else {
+            return Ok(Self::default());
+        };
+        let mut matcher = Self {
+            idx_range: filter.idx_range,
+            timestamp_nanos_range: filter.timestamp_nanos_range,
+            ..Self::default()
+        };
+        match filter.content {
+            None => {}
+            Some(CanisterLogContentFilter::Substring(substring)) => {
+                matcher.substring = Some(substring.into_bytes());
+            }
+            Some(CanisterLogContentFilter::Regex(pattern)) => {
+                if pattern.len() > MAX_CANISTER_LOG_REGEX_LEN {
+                    return Err(UserError::new(
+                        ErrorCode::InvalidManagementPayload,
+                        format!(
+                            "Canister log regex filter is {} bytes long, maximum allowed is {}",
+                            pattern.len(),
+                            MAX_CANISTER_LOG_REGEX_LEN
+                        ),
+                    ));
+                }
+                let regex = regex::bytes::RegexBuilder::new(&pattern)
+                    .size_limit(MAX_CANISTER_LOG_REGEX_SIZE)
+                    .build()
+                    .map_err(|err| {
+                        UserError::new(
+                            ErrorCode::InvalidManagementPayload,
+                            format!("Invalid canister log regex filter: {}", err),
+                        )
+                    })?;
+                matcher.regex = Some(regex);
+            }
+        }
+        Ok(matcher)
+    }
+
+    fn matches(&self, record: &CanisterLogRecord) -> bool {
+        if let Some(range) = &self.idx_range {
+            if !range.contains(record.idx) {
+                return false;
+            }
+        }
+        if let Some(range) = &self.timestamp_nanos_range {
+            if !range.contains(record.timestamp_nanos) {
+                return false;
+            }
+        }
+        if let Some(substring) = &self.substring {
+            if !substring.is_empty()
+                && !record
+                    .content
+                    .windows(substring.len())
+                    .any(|window| window == substring.as_slice())
+            {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.regex {
+            if !regex.is_match(&record.content) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
 impl HttpQueryHandler {
     pub(crate) fn new_service(
         internal: Arc<InternalHttpQueryHandler>,
@@ -0,0 +1,264 @@
+//! Opt-in tracing of the query call tree of a (composite) query.
+//!
+//! The trace records every query call made while evaluating the query call
+//! graph together with the instructions it used and the size of its response.
+//! It is returned by [`super::InternalHttpQueryHandler::query_with_trace`] and
+//! is meant for debugging composite queries that hit one of the call graph
+//! limits, e.g., in `StateMachine` or PocketIC tests.
+
+use crate::{execution_environment::as_num_instructions, RoundInstructions};
+use ic_error_types::{RejectCode, UserError};
+use ic_types::{
+    ingress::WasmResult,
+    messages::{Payload, RejectContext},
+    CanisterId, NumBytes, NumInstructions,
+};
+use std::fmt;
+
+/// Whether the result of the query was served from the query cache.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum QueryCacheOutcome {
+    /// The query cache is disabled or not applicable to the query.
+    #[default]
+    Disabled,
+    /// The result was served from the query cache. The query was not
+    /// executed, so the trace contains no call tree.
+    Hit,
+    /// The query was executed and its result was added to the query cache.
+    Miss,
+}
+
+impl fmt::Display for QueryCacheOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryCacheOutcome::Disabled => write!(f, "disabled"),
+            QueryCacheOutcome::Hit => write!(f, "hit"),
+            QueryCacheOutcome::Miss => write!(f, "miss"),
+        }
+    }
+}
+
+/// The outcome of a query call in the query call tree.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum QueryCallTraceOutcome {
+    /// The callee replied.
+    Reply,
+    /// The callee rejected the call or the call failed.
+    Reject(RejectContext),
+    /// The query failed. Only the root of the call tree can fail with a user
+    /// error, e.g., if one of the call graph limits was exceeded.
+    Error(UserError),
+    /// The evaluation of the call graph was aborted before the callee
+    /// responded.
+    Unfinished,
+}
+
+/// A query call in the query call tree.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct QueryCallTraceNode {
+    pub canister_id: CanisterId,
+    pub method_name: String,
+    /// The instructions used by the query method and all its response
+    /// callbacks, excluding the instructions of the nested calls.
+    pub instructions: NumInstructions,
+    /// The size of the reply payload or of the reject message.
+    pub response_size: NumBytes,
+    pub outcome: QueryCallTraceOutcome,
+    /// The query calls made by the callee in the order they were executed.
+    pub calls: Vec<QueryCallTraceNode>,
+}
+
+impl QueryCallTraceNode {
+    fn new(canister_id: CanisterId, method_name: String) -> Self {
+        Self {
+            canister_id,
+            method_name,
+            instructions: NumInstructions::from(0),
+            response_size: NumBytes::from(0),
+            outcome: QueryCallTraceOutcome::Unfinished,
+            calls: vec![],
+        }
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        write!(
+            f,
+            "{:indent$}- {} {}: ",
+            "",
+            self.canister_id,
+            self.method_name,
+            indent = indent
+        )?;
+        match &self.outcome {
+            QueryCallTraceOutcome::Reply => write!(f, "reply")?,
+            QueryCallTraceOutcome::Reject(context) => {
+                write!(f, "reject ({:?}: {})", context.code(), context.message())?
+            }
+            QueryCallTraceOutcome::Error(err) => write!(f, "error ({})", err)?,
+            QueryCallTraceOutcome::Unfinished => write!(f, "unfinished")?,
+        }
+        writeln!(
+            f,
+            ", {} bytes, {} instructions",
+            self.response_size, self.instructions
+        )?;
+        for call in &self.calls {
+            call.fmt_indented(f, indent + 2)?;
+        }
+        Ok(())
+    }
+}
+
+/// The trace of a query: its call tree and how close it came to the limits
+/// of the query call graph.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct QueryCallTrace {
+    pub cache: QueryCacheOutcome,
+    /// The root of the call tree, i.e., the query sent by the user. It is
+    /// `None` if the query was not executed, e.g., on a query cache hit.
+    pub root: Option<QueryCallTraceNode>,
+    /// The maximum number of nested query calls.
+    pub call_graph_depth: usize,
+    /// The instructions used by the whole call tree including the overhead
+    /// charged per query call.
+    pub instructions_used: NumInstructions,
+    pub max_call_graph_depth: usize,
+    pub max_call_graph_instructions: NumInstructions,
+    pub max_instructions_per_query: NumInstructions,
+}
+
+impl QueryCallTrace {
+    pub(super) fn new(
+        max_call_graph_depth: usize,
+        max_call_graph_instructions: NumInstructions,
+        max_instructions_per_query: NumInstructions,
+    ) -> Self {
+        Self {
+            cache: QueryCacheOutcome::Disabled,
+            root: None,
+            call_graph_depth: 0,
+            instructions_used: NumInstructions::from(0),
+            max_call_graph_depth,
+            max_call_graph_instructions,
+            max_instructions_per_query,
+        }
+    }
+}
+
+impl fmt::Display for QueryCallTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "query cache: {}, call graph depth: {}/{}, instructions: {}/{} (at most {} per call)",
+            self.cache,
+            self.call_graph_depth,
+            self.max_call_graph_depth,
+            self.instructions_used,
+            self.max_call_graph_instructions,
+            self.max_instructions_per_query,
+        )?;
+        match &self.root {
+            Some(root) => root.fmt_indented(f, 0),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Builds the call tree of a query while the query call graph is evaluated.
+///
+/// The tracer keeps the query calls that have not responded yet on a stack
+/// that mirrors the call stack of the DFS traversal: a query call is pushed
+/// when it is executed and popped once it responds.
+pub(super) struct QueryCallTracer {
+    stack: Vec<QueryCallTraceNode>,
+    call_graph_depth: usize,
+    // The instruction budget of the call graph when tracing started.
+    instructions_before: RoundInstructions,
+}
+
+impl QueryCallTracer {
+    pub(super) fn new(instructions_before: RoundInstructions) -> Self {
+        Self {
+            stack: vec![],
+            call_graph_depth: 0,
+            instructions_before,
+        }
+    }
+
+    /// Records the start of a query call to the given canister.
+    pub(super) fn enter(&mut self, canister_id: CanisterId, method_name: String) {
+        self.stack
+            .push(QueryCallTraceNode::new(canister_id, method_name));
+        self.call_graph_depth = self.call_graph_depth.max(self.stack.len() - 1);
+    }
+
+    /// Attributes the given instructions to the query call being executed.
+    pub(super) fn add_instructions(&mut self, instructions: NumInstructions) {
+        if let Some(node) = self.stack.last_mut() {
+            node.instructions += instructions;
+        }
+    }
+
+    /// Records the response of the innermost query call that has not
+    /// responded yet.
+    pub(super) fn exit(&mut self, payload: &Payload) {
+        let Some(mut node) = self.stack.pop() else {
+            return;
+        };
+        let (outcome, response_size) = match payload {
+            Payload::Data(data) => (QueryCallTraceOutcome::Reply, data.len()),
+            Payload::Reject(context) => (
+                QueryCallTraceOutcome::Reject(context.clone()),
+                context.message().len(),
+            ),
+        };
+        node.outcome = outcome;
+        node.response_size = NumBytes::from(response_size as u64);
+        self.attach(node);
+    }
+
+    fn attach(&mut self, node: QueryCallTraceNode) {
+        match self.stack.last_mut() {
+            Some(parent) => parent.calls.push(node),
+            // The root is never popped by `exit()`, so this only happens if
+            // the number of `exit()` calls does not match `enter()` calls.
+            None => self.stack.push(node),
+        }
+    }
+
+    /// Completes the call tree with the final result of the query and stores
+    /// it in the given trace. Query calls that did not respond are marked as
+    /// unfinished.
+    pub(super) fn finish(
+        mut self,
+        result: &Result<WasmResult, UserError>,
+        instructions_left: RoundInstructions,
+        trace: &mut QueryCallTrace,
+    ) {
+        while self.stack.len() > 1 {
+            let node = self.stack.pop().unwrap();
+            self.attach(node);
+        }
+        if let Some(mut root) = self.stack.pop() {
+            let (outcome, response_size) = match result {
+                Ok(WasmResult::Reply(data)) => (QueryCallTraceOutcome::Reply, data.len()),
+                Ok(WasmResult::Reject(message)) => (
+                    QueryCallTraceOutcome::Reject(RejectContext::new(
+                        RejectCode::CanisterReject,
+                        message,
+                    )),
+                    message.len(),
+                ),
+                Err(err) => (
+                    QueryCallTraceOutcome::Error(err.clone()),
+                    err.description().len(),
+                ),
+            };
+            root.outcome = outcome;
+            root.response_size = NumBytes::from(response_size as u64);
+            trace.root = Some(root);
+        }
+        trace.call_graph_depth = self.call_graph_depth;
+        trace.instructions_used = as_num_instructions(self.instructions_before - instructions_left);
+    }
+}
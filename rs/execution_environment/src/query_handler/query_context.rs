@@ -1,4 +1,5 @@
 use super::query_call_graph::evaluate_query_call_graph;
+use super::query_call_trace::{QueryCallTrace, QueryCallTracer};
 use crate::{
     execution::common::{self, validate_method},
     execution::nonreplicated_query::execute_non_replicated_query,
@@ -118,6 +119,8 @@ pub(super) struct QueryContext<'a> {
     /// The number of transient errors.
     transient_errors: usize,
     cycles_account_manager: Arc<CyclesAccountManager>,
+    /// Records the query call tree if the query is traced.
+    call_tracer: Option<QueryCallTracer>,
 }
 
 impl<'a> QueryContext<'a> {
@@ -179,6 +182,25 @@ impl<'a> QueryContext<'a> {
             evaluated_canister_stats: BTreeMap::from([(canister_id, QueryStats::default())]),
             transient_errors: 0,
             cycles_account_manager,
+            call_tracer: None,
+        }
+    }
+
+    /// Enables recording of the query call tree, see `take_call_trace()`.
+    pub(super) fn with_call_tracer(mut self) -> Self {
+        self.call_tracer = Some(QueryCallTracer::new(self.round_limits.instructions));
+        self
+    }
+
+    /// Stores the recorded query call tree (if any) of the query with the
+    /// given result in the given trace.
+    pub(super) fn take_call_trace(
+        &mut self,
+        result: &Result<WasmResult, UserError>,
+        trace: &mut QueryCallTrace,
+    ) {
+        if let Some(call_tracer) = self.call_tracer.take() {
+            call_tracer.finish(result, self.round_limits.instructions, trace);
         }
     }
 
@@ -199,6 +221,9 @@ impl<'a> QueryContext<'a> {
         measurement_scope: &MeasurementScope<'b>,
     ) -> Result<WasmResult, UserError> {
         let canister_id = query.receiver;
+        if let Some(call_tracer) = self.call_tracer.as_mut() {
+            call_tracer.enter(canister_id, query.method_name.clone());
+        }
         let old_canister = self.state.get_ref().get_active_canister(&canister_id)?;
         let call_origin = CallOrigin::Query(query.source().into());
 
@@ -438,6 +463,9 @@ impl<'a> QueryContext<'a> {
             );
         self.add_system_api_call_counters(system_api_call_counters);
         let instructions_executed = instruction_limit - instructions_left;
+        if let Some(call_tracer) = self.call_tracer.as_mut() {
+            call_tracer.add_instructions(instructions_executed);
+        }
 
         let ingress_payload_size = method_payload.len();
         let egress_payload_size = match &result {
@@ -704,6 +732,9 @@ impl<'a> QueryContext<'a> {
                 .get()
                 .saturating_sub(instructions_left.get()),
         );
+        if let Some(call_tracer) = self.call_tracer.as_mut() {
+            call_tracer.add_instructions(instructions_used);
+        }
         let action = self.finish(
             &mut canister,
             call_context_id,
@@ -798,6 +829,19 @@ impl<'a> QueryContext<'a> {
         &mut self,
         request: Arc<Request>,
         measurement_scope: &MeasurementScope,
+    ) -> ExecutionResult {
+        if let Some(call_tracer) = self.call_tracer.as_mut() {
+            call_tracer.enter(request.receiver, request.method_name.clone());
+        }
+        let result = self.execute_request(request, measurement_scope);
+        self.trace_canister_response(&result);
+        result
+    }
+
+    fn execute_request(
+        &mut self,
+        request: Arc<Request>,
+        measurement_scope: &MeasurementScope,
     ) -> ExecutionResult {
         // A handy function to create a `Response` using parameters from the `Request`
         let to_query_result = |payload: Payload| {
@@ -946,6 +990,18 @@ impl<'a> QueryContext<'a> {
     /// If the execution produces a response, then the function returns it and
     /// discards the canister and outgoing requests.
     pub fn handle_response(
+        &mut self,
+        canister: CanisterState,
+        response: Response,
+        requests: VecDeque<Arc<Request>>,
+        measurement_scope: &MeasurementScope,
+    ) -> ExecutionResult {
+        let result = self.execute_response(canister, response, requests, measurement_scope);
+        self.trace_canister_response(&result);
+        result
+    }
+
+    fn execute_response(
         &mut self,
         canister: CanisterState,
         response: Response,
@@ -1025,6 +1081,18 @@ impl<'a> QueryContext<'a> {
         }
     }
 
+    /// Records the response of a query call made by a canister if the query
+    /// is traced. The response to the user is recorded by `take_call_trace()`.
+    fn trace_canister_response(&mut self, result: &ExecutionResult) {
+        if let (
+            Some(call_tracer),
+            ExecutionResult::Response(QueryResponse::CanisterResponse(response)),
+        ) = (self.call_tracer.as_mut(), result)
+        {
+            call_tracer.exit(&response.response_payload);
+        }
+    }
+
     /// Returns true if the total number of instructions executed by queries and
     /// response callbacks exceeds the limit in `round_limits`.
     pub fn instruction_limit_reached(&self) -> bool {
@@ -1039,7 +1107,7 @@ impl<'a> QueryContext<'a> {
     /// Returns a synthetic reject response for the case when a query call
     /// context did not produce any response.
     pub fn empty_response(
-        &self,
+        &mut self,
         canister_id: CanisterId,
         call_origin: CallOrigin,
     ) -> QueryResponse {
@@ -1064,6 +1132,9 @@ impl<'a> QueryContext<'a> {
                     // `CallOrigin::CanisterQuery` has no deadline.
                     deadline: NO_DEADLINE,
                 };
+                if let Some(call_tracer) = self.call_tracer.as_mut() {
+                    call_tracer.exit(&response.response_payload);
+                }
                 QueryResponse::CanisterResponse(response)
             }
         }
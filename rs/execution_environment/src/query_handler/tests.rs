@@ -1,14 +1,15 @@
-use crate::InternalHttpQueryHandler;
+use crate::{InternalHttpQueryHandler, QueryCacheOutcome, QueryCallTrace, QueryCallTraceOutcome};
 use ic_base_types::{CanisterId, NumSeconds};
 use ic_config::execution_environment::INSTRUCTION_OVERHEAD_PER_QUERY_CALL;
 use ic_error_types::{ErrorCode, UserError};
+use ic_interfaces_state_manager::Labeled;
 use ic_test_utilities::universal_canister::{call_args, wasm};
 use ic_test_utilities_execution_environment::{ExecutionTest, ExecutionTestBuilder};
 use ic_test_utilities_types::ids::user_test_id;
 use ic_types::{
     ingress::WasmResult,
     messages::{Query, QuerySource},
-    Cycles, NumInstructions,
+    Cycles, Height, NumInstructions,
 };
 use std::sync::Arc;
 
@@ -1252,3 +1253,149 @@ fn query_call_exceeds_instructions_limit() {
             )
     );
 }
+
+fn traced_composite_query(
+    test: &ExecutionTest,
+    canister_id: CanisterId,
+    method_payload: Vec<u8>,
+) -> (Result<WasmResult, UserError>, QueryCallTrace) {
+    downcast_query_handler(test.query_handler()).query_with_trace(
+        Query {
+            source: QuerySource::User {
+                user_id: user_test_id(0),
+                ingress_expiry: 0,
+                nonce: None,
+            },
+            receiver: canister_id,
+            method_name: "composite_query".to_string(),
+            method_payload,
+        },
+        Labeled::new(Height::from(0), Arc::new(test.state().clone())),
+        vec![],
+    )
+}
+
+#[test]
+fn composite_query_trace_records_call_tree() {
+    // Canister A calls canister B, which replies, and then canister C, which
+    // does not reply. Canister A replies to the user when handling the reject
+    // from canister C.
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister_a = test.universal_canister_with_cycles(CYCLES_BALANCE).unwrap();
+    let canister_b = test.universal_canister_with_cycles(CYCLES_BALANCE).unwrap();
+    let canister_c = test.universal_canister_with_cycles(CYCLES_BALANCE).unwrap();
+
+    let payload = wasm()
+        .inter_query(
+            canister_b,
+            call_args()
+                .other_side(wasm().reply_data(b"pong".as_ref()))
+                .on_reply(wasm().build()),
+        )
+        .composite_query(
+            canister_c,
+            call_args()
+                .other_side(wasm().build())
+                .on_reject(wasm().reply_data(b"done".as_ref())),
+        )
+        .build();
+
+    let (result, trace) = traced_composite_query(&test, canister_a, payload.clone());
+    assert_eq!(result, Ok(WasmResult::Reply(b"done".to_vec())));
+    assert_eq!(trace.cache, QueryCacheOutcome::Miss);
+    assert_eq!(trace.call_graph_depth, 1);
+
+    let root = trace.root.as_ref().unwrap();
+    assert_eq!(root.canister_id, canister_a);
+    assert_eq!(root.method_name, "composite_query");
+    assert_eq!(root.outcome, QueryCallTraceOutcome::Reply);
+    assert_eq!(root.response_size.get(), 4);
+    assert_eq!(root.calls.len(), 2);
+
+    let call_b = &root.calls[0];
+    assert_eq!(call_b.canister_id, canister_b);
+    assert_eq!(call_b.method_name, "query");
+    assert_eq!(call_b.outcome, QueryCallTraceOutcome::Reply);
+    assert_eq!(call_b.response_size.get(), 4);
+    assert!(call_b.calls.is_empty());
+
+    let call_c = &root.calls[1];
+    assert_eq!(call_c.canister_id, canister_c);
+    assert_eq!(call_c.method_name, "composite_query");
+    match &call_c.outcome {
+        QueryCallTraceOutcome::Reject(context) => {
+            assert!(context.message().contains("did not produce a response"))
+        }
+        outcome => panic!("Unexpected outcome: {:?}", outcome),
+    }
+
+    // The total includes the instructions of all calls and the overhead per call.
+    let instructions = root.instructions + call_b.instructions + call_c.instructions;
+    assert!(root.instructions.get() > 0);
+    assert!(call_b.instructions.get() > 0);
+    assert!(
+        trace.instructions_used
+            >= instructions + NumInstructions::from(2 * INSTRUCTION_OVERHEAD_PER_QUERY_CALL)
+    );
+
+    // The second identical query is served from the query cache.
+    let (cached_result, cached_trace) = traced_composite_query(&test, canister_a, payload);
+    assert_eq!(cached_result, result);
+    assert_eq!(cached_trace.cache, QueryCacheOutcome::Hit);
+    assert_eq!(cached_trace.root, None);
+}
+
+#[test]
+fn composite_query_trace_shows_where_call_graph_depth_is_exceeded() {
+    let mut test = ExecutionTestBuilder::new()
+        .with_query_caching_disabled()
+        .build();
+
+    const NUM_CANISTERS: usize = 10;
+    let mut canisters = vec![];
+    for _ in 0..NUM_CANISTERS {
+        canisters.push(test.universal_canister_with_cycles(CYCLES_BALANCE).unwrap());
+    }
+
+    // Canister `i` calls canister `i + 1` and the last canister replies.
+    fn chain(canisters: &[CanisterId], idx: usize) -> ic_universal_canister::PayloadBuilder {
+        if idx + 1 == canisters.len() {
+            wasm().reply_data(b"ignore".as_ref())
+        } else {
+            wasm().composite_query(
+                canisters[idx + 1],
+                call_args().other_side(chain(canisters, idx + 1)),
+            )
+        }
+    }
+
+    let (result, trace) = traced_composite_query(&test, canisters[0], chain(&canisters, 0).build());
+    assert_eq!(result.unwrap_err().code(), ErrorCode::QueryCallGraphTooDeep);
+    assert_eq!(trace.cache, QueryCacheOutcome::Disabled);
+    assert_eq!(trace.call_graph_depth, trace.max_call_graph_depth);
+
+    let root = trace.root.as_ref().unwrap();
+    match &root.outcome {
+        QueryCallTraceOutcome::Error(err) => {
+            assert_eq!(err.code(), ErrorCode::QueryCallGraphTooDeep)
+        }
+        outcome => panic!("Unexpected outcome: {:?}", outcome),
+    }
+
+    // The calls that were pending when the limit was hit are unfinished.
+    let mut node = root;
+    for canister_id in canisters.iter().skip(1).take(trace.max_call_graph_depth) {
+        assert_eq!(node.calls.len(), 1);
+        node = &node.calls[0];
+        assert_eq!(node.canister_id, *canister_id);
+        assert_eq!(node.outcome, QueryCallTraceOutcome::Unfinished);
+    }
+    assert!(node.calls.is_empty());
+
+    let rendered = trace.to_string();
+    assert!(rendered.contains(&format!(
+        "call graph depth: {0}/{0}",
+        trace.max_call_graph_depth
+    )));
+    assert!(rendered.contains("unfinished"));
+}
@@ -2278,6 +2278,7 @@ fn get_instructions_limits_for_subnet_message(
             | BitcoinSendTransaction
             | BitcoinSendTransactionInternal
             | BitcoinGetCurrentFeePercentiles
+            | BitcoinGetBlockFilter
             | BitcoinGetSuccessors
             | NodeMetricsHistory
             | SubnetInfo
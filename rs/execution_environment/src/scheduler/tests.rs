@@ -3029,6 +3029,7 @@ fn canister_is_stopped_if_timeout_occurs_and_ready_to_stop() {
             url: "https://example.com".to_string(),
             headers: BoundedHttpHeaders::new(vec![]),
             method: HttpMethod::GET,
+            is_replicated: None,
             body: None,
             transform: None,
             max_response_bytes: None,
@@ -4097,6 +4098,7 @@ fn consumed_cycles_http_outcalls_are_added_to_consumed_cycles_total() {
         headers: BoundedHttpHeaders::new(vec![]),
         body: None,
         method: HttpMethod::GET,
+        is_replicated: None,
         transform: Some(TransformContext {
             function: TransformFunc(candid::Func {
                 principal: caller_canister.get().0,
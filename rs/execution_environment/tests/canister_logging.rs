@@ -2,7 +2,8 @@ use ic_base_types::PrincipalId;
 use ic_config::execution_environment::Config as ExecutionConfig;
 use ic_config::subnet_config::SubnetConfig;
 use ic_management_canister_types_private::{
-    self as ic00, BoundedAllowedViewers, CanisterIdRecord, CanisterInstallMode, CanisterLogRecord,
+    self as ic00, BoundedAllowedViewers, CanisterIdRecord, CanisterInstallMode,
+    CanisterLogContentFilter, CanisterLogFilter, CanisterLogRange, CanisterLogRecord,
     CanisterSettingsArgs, CanisterSettingsArgsBuilder, DataSize, EmptyBlob,
     FetchCanisterLogsRequest, FetchCanisterLogsResponse, LogVisibilityV2, Payload,
 };
@@ -34,6 +35,14 @@ fn system_time_to_nanos(t: SystemTime) -> u64 {
 }
 
 fn canister_log_response(data: Vec<(u64, u64, Vec<u8>)>) -> FetchCanisterLogsResponse {
+    let next_idx = data.last().map_or(0, |(idx, _, _)| idx + 1);
+    canister_log_response_with_next_idx(data, next_idx)
+}
+
+fn canister_log_response_with_next_idx(
+    data: Vec<(u64, u64, Vec<u8>)>,
+    next_idx: u64,
+) -> FetchCanisterLogsResponse {
     FetchCanisterLogsResponse {
         canister_log_records: data
             .into_iter()
@@ -43,6 +52,7 @@ fn canister_log_response(data: Vec<(u64, u64, Vec<u8>)>) -> FetchCanisterLogsRes
                 content,
             })
             .collect(),
+        next_idx: Some(next_idx),
     }
 }
 
@@ -131,6 +141,19 @@ fn fetch_canister_logs(
     )
 }
 
+fn fetch_canister_logs_with_request(
+    env: &StateMachine,
+    sender: PrincipalId,
+    request: FetchCanisterLogsRequest,
+) -> Result<WasmResult, UserError> {
+    env.query_as(
+        sender,
+        CanisterId::ic_00(),
+        "fetch_canister_logs",
+        request.encode(),
+    )
+}
+
 #[test]
 fn test_fetch_canister_logs_via_submit_ingress() {
     let (env, canister_id) = setup_and_install_wasm(
@@ -198,6 +221,7 @@ fn test_fetch_canister_logs_via_query_call() {
         Ok(WasmResult::Reply(
             FetchCanisterLogsResponse {
                 canister_log_records: vec![],
+                next_idx: Some(0),
             }
             .encode(),
         ))
@@ -282,6 +306,7 @@ fn test_log_visibility_of_fetch_canister_logs() {
     let ok = Ok(WasmResult::Reply(
         FetchCanisterLogsResponse {
             canister_log_records: vec![],
+            next_idx: Some(0),
         }
         .encode(),
     ));
@@ -499,6 +524,198 @@ fn test_canister_log_record_index_increment_after_node_restart() {
     );
 }
 
+/// Sets up a canister with four log records: two written at `timestamp_01`
+/// and two written at `timestamp_23`.
+fn setup_with_four_log_records() -> (StateMachine, CanisterId, PrincipalId, u64, u64) {
+    let (env, canister_id, controller) = setup_with_controller(
+        wat_canister()
+            .update(
+                "test1",
+                wat_fn().debug_print(b"message 0").debug_print(b"error 1"),
+            )
+            .update(
+                "test2",
+                wat_fn().debug_print(b"message 2").debug_print(b"error 3"),
+            )
+            .build_wasm(),
+    );
+    // advance time so that time does not grow implicitly when executing a round
+    env.advance_time(Duration::from_secs(1));
+    let timestamp_01 = system_time_to_nanos(env.time());
+    let _ = env.execute_ingress(canister_id, "test1", vec![]);
+    env.advance_time(Duration::from_secs(1));
+    let timestamp_23 = system_time_to_nanos(env.time());
+    let _ = env.execute_ingress(canister_id, "test2", vec![]);
+    (env, canister_id, controller, timestamp_01, timestamp_23)
+}
+
+#[test]
+fn test_fetch_canister_logs_with_idx_and_timestamp_filters() {
+    let (env, canister_id, controller, timestamp_01, timestamp_23) = setup_with_four_log_records();
+
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id).with_filter(CanisterLogFilter {
+            idx_range: Some(CanisterLogRange::new(1, 3)),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(
+        FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
+        canister_log_response_with_next_idx(
+            vec![
+                (1, timestamp_01, b"error 1".to_vec()),
+                (2, timestamp_23, b"message 2".to_vec()),
+            ],
+            4
+        )
+    );
+
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id).with_filter(CanisterLogFilter {
+            timestamp_nanos_range: Some(CanisterLogRange::starting_at(timestamp_23)),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(
+        FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
+        canister_log_response(vec![
+            (2, timestamp_23, b"message 2".to_vec()),
+            (3, timestamp_23, b"error 3".to_vec()),
+        ])
+    );
+}
+
+#[test]
+fn test_fetch_canister_logs_with_content_filters() {
+    let (env, canister_id, controller, timestamp_01, timestamp_23) = setup_with_four_log_records();
+
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id).with_filter(CanisterLogFilter {
+            content: Some(CanisterLogContentFilter::Substring("error".to_string())),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(
+        FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
+        canister_log_response(vec![
+            (1, timestamp_01, b"error 1".to_vec()),
+            (3, timestamp_23, b"error 3".to_vec()),
+        ])
+    );
+
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id).with_filter(CanisterLogFilter {
+            content: Some(CanisterLogContentFilter::Regex(
+                "^(message|error) [02]$".to_string(),
+            )),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(
+        FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
+        canister_log_response_with_next_idx(
+            vec![
+                (0, timestamp_01, b"message 0".to_vec()),
+                (2, timestamp_23, b"message 2".to_vec()),
+            ],
+            4
+        )
+    );
+}
+
+#[test]
+fn test_fetch_canister_logs_with_invalid_regex_filter() {
+    let (env, canister_id, controller, _, _) = setup_with_four_log_records();
+
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id).with_filter(CanisterLogFilter {
+            content: Some(CanisterLogContentFilter::Regex("(unclosed".to_string())),
+            ..Default::default()
+        }),
+    );
+    let error = result.unwrap_err();
+    assert_eq!(error.code(), ErrorCode::InvalidManagementPayload);
+    assert!(
+        error
+            .description()
+            .contains("Invalid canister log regex filter"),
+        "Unexpected error: {}",
+        error.description()
+    );
+}
+
+#[test]
+fn test_fetch_canister_logs_pagination_and_tailing() {
+    let (env, canister_id, controller, timestamp_01, timestamp_23) = setup_with_four_log_records();
+
+    // First page.
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id).with_max_records(3),
+    );
+    let response = FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap();
+    assert_eq!(
+        response,
+        canister_log_response_with_next_idx(
+            vec![
+                (0, timestamp_01, b"message 0".to_vec()),
+                (1, timestamp_01, b"error 1".to_vec()),
+                (2, timestamp_23, b"message 2".to_vec()),
+            ],
+            3
+        )
+    );
+
+    // Second page continues from the returned cursor.
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id)
+            .with_filter(CanisterLogFilter {
+                idx_range: Some(CanisterLogRange::starting_at(response.next_idx.unwrap())),
+                ..Default::default()
+            })
+            .with_max_records(3),
+    );
+    let response = FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap();
+    assert_eq!(
+        response,
+        canister_log_response(vec![(3, timestamp_23, b"error 3".to_vec())])
+    );
+
+    // Tailing with the cursor returns only new records.
+    let cursor = response.next_idx.unwrap();
+    env.advance_time(Duration::from_secs(1));
+    let timestamp_45 = system_time_to_nanos(env.time());
+    let _ = env.execute_ingress(canister_id, "test1", vec![]);
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id).with_filter(CanisterLogFilter {
+            idx_range: Some(CanisterLogRange::starting_at(cursor)),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(
+        FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
+        canister_log_response(vec![
+            (4, timestamp_45, b"message 0".to_vec()),
+            (5, timestamp_45, b"error 1".to_vec()),
+        ])
+    );
+}
+
 #[test]
 fn test_logging_in_trapped_wasm_execution() {
     let (env, canister_id, controller) = setup_with_controller(
@@ -760,7 +977,8 @@ fn test_deleting_logs_on_uninstall() {
     let result = fetch_canister_logs(&env, controller, canister_id);
     assert_eq!(
         FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
-        canister_log_response(vec![])
+        // The index of the next record is preserved.
+        canister_log_response_with_next_idx(vec![], 3)
     );
 }
 
@@ -1083,6 +1083,7 @@ fn dts_aborted_execution_does_not_block_subnet_messages() {
             | Method::BitcoinGetBlockHeaders
             | Method::BitcoinSendTransaction
             | Method::BitcoinGetCurrentFeePercentiles
+            | Method::BitcoinGetBlockFilter
             | Method::BitcoinSendTransactionInternal
             | Method::BitcoinGetSuccessors
             | Method::NodeMetricsHistory
@@ -0,0 +1,84 @@
+use ic_execution_environment::{ExecutionTraceEntry, ExecutionTraceOutcome, ExecutionTracer};
+use ic_management_canister_types_private::{
+    CanisterSettingsArgsBuilder, EmptyBlob, Method, Payload, UpdateSettingsArgs, IC_00,
+};
+use ic_test_utilities_execution_environment::{check_ingress_status, ExecutionTestBuilder};
+use ic_test_utilities_types::messages::IngressBuilder;
+use ic_types::{messages::MessageId, CanisterId, Cycles};
+use ic_types_test_utils::ids::canister_test_id;
+use std::sync::{Arc, Mutex};
+
+/// Collects the entries of a single traced canister.
+struct TestTracer {
+    canister_id: CanisterId,
+    entries: Mutex<Vec<ExecutionTraceEntry>>,
+}
+
+impl ExecutionTracer for TestTracer {
+    fn is_traced(&self, canister_id: CanisterId) -> bool {
+        canister_id == self.canister_id
+    }
+
+    fn trace(&self, entry: ExecutionTraceEntry) {
+        self.entries.lock().unwrap().push(entry);
+    }
+}
+
+#[test]
+fn subnet_messages_targeting_traced_canister_are_traced() {
+    let traced = canister_test_id(1);
+    let tracer = Arc::new(TestTracer {
+        canister_id: traced,
+        entries: Default::default(),
+    });
+    let mut test = ExecutionTestBuilder::new()
+        .with_execution_tracer(tracer.clone())
+        .build();
+    let other = test.create_canister(Cycles::new(1_000_000_000_000));
+    let canister_id = test.create_canister(Cycles::new(1_000_000_000_000));
+    assert_eq!(canister_id, traced);
+    let controllers = vec![test.user_id().get()];
+    test.canister_update_controller(other, controllers.clone())
+        .unwrap();
+    test.canister_update_controller(canister_id, controllers)
+        .unwrap();
+    // A failed subnet message is traced by its effective canister ID.
+    let message_id = MessageId::from([42; 32]);
+    let ingress = IngressBuilder::new()
+        .message_id(message_id.clone())
+        .source(test.user_id())
+        .receiver(CanisterId::ic_00())
+        .effective_canister_id(Some(canister_id))
+        .method_name(Method::UpdateSettings)
+        .method_payload(
+            UpdateSettingsArgs {
+                canister_id: canister_id.get(),
+                settings: CanisterSettingsArgsBuilder::new()
+                    .with_compute_allocation(1_000)
+                    .build(),
+                sender_canister_version: None,
+            }
+            .encode(),
+        )
+        .build();
+    test.state_mut().subnet_queues_mut().push_ingress(ingress);
+    test.execute_subnet_message();
+    let err = check_ingress_status(test.ingress_status(&message_id)).unwrap_err();
+
+    let entries = tracer.entries.lock().unwrap();
+    // The creation of the traced canister, and both updates of its settings.
+    assert_eq!(entries.len(), 3);
+    assert!(entries
+        .iter()
+        .all(|entry| entry.canister_id == traced && entry.receiver() == IC_00));
+    assert_eq!(
+        entries[0].method_name(),
+        Method::ProvisionalCreateCanisterWithCycles.to_string()
+    );
+    assert_eq!(entries[1].method_name(), Method::UpdateSettings.to_string());
+    assert_eq!(
+        entries[1].outcome,
+        ExecutionTraceOutcome::Reply(EmptyBlob.encode())
+    );
+    assert_eq!(entries[2].outcome, ExecutionTraceOutcome::Error(err));
+}
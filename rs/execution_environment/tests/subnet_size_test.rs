@@ -525,6 +525,7 @@ fn simulate_http_request_cost(subnet_type: SubnetType, subnet_size: usize) -> Cy
                     headers: BoundedHttpHeaders::new(vec![]),
                     body: None,
                     method: HttpMethod::GET,
+                    is_replicated: None,
                     transform: Some(TransformContext {
                         function: TransformFunc(candid::Func {
                             principal: canister_id.get().0,
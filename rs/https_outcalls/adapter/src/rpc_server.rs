@@ -369,6 +369,9 @@ impl HttpsOutcallsService for CanisterHttp {
                 HttpMethod::Get => Ok(Method::GET),
                 HttpMethod::Post => Ok(Method::POST),
                 HttpMethod::Head => Ok(Method::HEAD),
+                HttpMethod::Put => Ok(Method::PUT),
+                HttpMethod::Patch => Ok(Method::PATCH),
+                HttpMethod::Delete => Ok(Method::DELETE),
                 _ => {
                     self.metrics
                         .request_errors
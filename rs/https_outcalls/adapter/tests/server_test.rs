@@ -127,9 +127,26 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
 
         let basic_head = warp::head().and(warp::path("head")).map(warp::reply::reply);
 
+        let basic_put = warp::put()
+            .and(warp::path("put"))
+            .and(warp::body::json())
+            .map(|req: u64| Response::builder().body(req.to_string()));
+
+        let basic_patch = warp::patch()
+            .and(warp::path("patch"))
+            .and(warp::body::json())
+            .map(|req: u64| Response::builder().body(req.to_string()));
+
+        let basic_delete = warp::delete()
+            .and(warp::path("delete"))
+            .map(|| Response::builder().body("deleted".to_string()));
+
         basic_post
             .or(basic_get)
             .or(basic_head)
+            .or(basic_put)
+            .or(basic_patch)
+            .or(basic_delete)
             .or(get_response_size)
             .or(get_delay)
             .or(invalid_header)
@@ -460,6 +477,42 @@ MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgob29X4H4m2XOkSZE
         assert_eq!(http_response.status, StatusCode::OK.as_u16() as u32);
     }
 
+    #[tokio::test]
+    async fn test_canister_http_server_put_patch_and_delete() {
+        let path = "/tmp/canister-http-test-".to_string() + &Uuid::new_v4().to_string();
+        let server_config = Config {
+            incoming_source: IncomingSource::Path(path.into()),
+            ..Default::default()
+        };
+
+        let url = start_server(CERT_INIT.get_or_init(generate_certs));
+        let mut client = spawn_grpc_server(server_config);
+
+        for (method, path, body, expected_content) in [
+            (HttpMethod::Put, "put", "420", "420"),
+            (HttpMethod::Patch, "patch", "421", "421"),
+            (HttpMethod::Delete, "delete", "", "deleted"),
+        ] {
+            let request = tonic::Request::new(HttpsOutcallRequest {
+                url: format!("https://{}/{}", &url, path),
+                headers: Vec::new(),
+                method: method as i32,
+                body: body.as_bytes().to_vec(),
+                max_response_size_bytes: 512,
+                socks_proxy_allowed: false,
+                ..Default::default()
+            });
+
+            let response = client.https_outcall(request).await;
+            let http_response = response.unwrap().into_inner();
+            assert_eq!(http_response.status, StatusCode::OK.as_u16() as u32);
+            assert_eq!(
+                String::from_utf8_lossy(&http_response.content),
+                expected_content
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_response_limit_exceeded() {
         // Check if response with higher than allowed response limit is rejected.
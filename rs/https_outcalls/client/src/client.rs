@@ -168,6 +168,9 @@ impl NonBlockingChannel<CanisterHttpRequest> for CanisterHttpAdapterClientImpl {
                         CanisterHttpMethod::GET => HttpMethod::Get.into(),
                         CanisterHttpMethod::POST => HttpMethod::Post.into(),
                         CanisterHttpMethod::HEAD => HttpMethod::Head.into(),
+                        CanisterHttpMethod::PUT => HttpMethod::Put.into(),
+                        CanisterHttpMethod::PATCH => HttpMethod::Patch.into(),
+                        CanisterHttpMethod::DELETE => HttpMethod::Delete.into(),
                     },
                     max_response_size_bytes,
                     headers: request_headers
@@ -394,7 +397,7 @@ mod tests {
     use ic_interfaces::execution_environment::{QueryExecutionError, QueryExecutionResponse};
     use ic_logger::replica_logger::no_op_logger;
     use ic_test_utilities_types::messages::RequestBuilder;
-    use ic_types::canister_http::Transform;
+    use ic_types::canister_http::{Replication, Transform};
     use ic_types::{
         canister_http::CanisterHttpMethod,
         messages::{CallbackId, CertificateDelegation},
@@ -491,6 +494,7 @@ mod tests {
                     context: vec![],
                 }),
                 time: UNIX_EPOCH,
+                replication: Replication::FullyReplicated,
             },
             socks_proxy_addrs: vec![],
         }
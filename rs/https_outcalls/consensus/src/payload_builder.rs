@@ -34,7 +34,7 @@ use ic_types::{
     canister_http::{
         CanisterHttpResponse, CanisterHttpResponseContent, CanisterHttpResponseDivergence,
         CanisterHttpResponseMetadata, CanisterHttpResponseProof, CanisterHttpResponseWithConsensus,
-        CANISTER_HTTP_MAX_RESPONSES_PER_BLOCK, CANISTER_HTTP_TIMEOUT_INTERVAL,
+        Replication, CANISTER_HTTP_MAX_RESPONSES_PER_BLOCK, CANISTER_HTTP_TIMEOUT_INTERVAL,
     },
     consensus::Committee,
     crypto::Signed,
@@ -200,6 +200,7 @@ impl CanisterHttpPayloadBuilderImpl {
         let mut candidates = vec![];
         let mut timeouts = vec![];
         let mut divergence_responses = vec![];
+        let mut non_replicated_node_ids = BTreeMap::new();
 
         // Metrics counters
         let mut unique_includable_responses = 0;
@@ -218,14 +219,21 @@ impl CanisterHttpPayloadBuilderImpl {
             .state_reader
             .get_state_at(validation_context.certified_height)
         {
-            // Iterate over all outstanding canister http requests
-            for (callback_id, request) in state
+            let http_contexts = &state
                 .get_ref()
                 .metadata
                 .subnet_call_context_manager
-                .canister_http_request_contexts
+                .canister_http_request_contexts;
+            non_replicated_node_ids = http_contexts
                 .iter()
-            {
+                .filter_map(|(callback_id, request)| match request.replication {
+                    Replication::NonReplicated(node_id) => Some((*callback_id, node_id)),
+                    Replication::FullyReplicated => None,
+                })
+                .collect();
+
+            // Iterate over all outstanding canister http requests
+            for (callback_id, request) in http_contexts.iter() {
                 unique_includable_responses += 1;
                 let candidate_size = callback_id.count_bytes();
                 let size = NumBytes::new((accumulated_size + candidate_size) as u64);
@@ -279,7 +287,25 @@ impl CanisterHttpPayloadBuilderImpl {
 
             let candidates_and_divergences = response_candidates_by_callback_id
                 .into_iter()
-                .filter_map(|(_, grouped_shares)| {
+                .filter_map(|(callback_id, grouped_shares)| {
+                    if let Some(node_id) = non_replicated_node_ids.get(&callback_id) {
+                        // The response to a non-replicated request only needs
+                        // the share of the node the request was delegated to.
+                        return grouped_shares.iter().find_map(|(metadata, shares)| {
+                            let share = shares
+                                .iter()
+                                .find(|share| share.signature.signer == *node_id)?;
+                            pool_access
+                                .get_response_content_by_hash(&metadata.content_hash)
+                                .map(|content| {
+                                    CandidateOrDivergence::Candidate((
+                                        metadata.clone(),
+                                        BTreeSet::from([share.signature.clone()]),
+                                        content,
+                                    ))
+                                })
+                        });
+                    }
                     if let Some((metadata, shares)) = grouped_shares.iter().find(|(_, shares)| {
                         unique_responses_count += 1;
                         let signers: BTreeSet<_> =
@@ -499,7 +525,19 @@ impl CanisterHttpPayloadBuilderImpl {
                     valid_signers,
                 });
             }
-            if valid_signers.len() < threshold {
+            if let Some(Replication::NonReplicated(delegated_node_id)) = http_contexts
+                .get(&response.content.id)
+                .map(|context| context.replication)
+            {
+                if valid_signers != [delegated_node_id] {
+                    return invalid_artifact(
+                        InvalidCanisterHttpPayloadReason::NonReplicatedSignerMismatch {
+                            delegated_node_id,
+                            signers: valid_signers,
+                        },
+                    );
+                }
+            } else if valid_signers.len() < threshold {
                 return invalid_artifact(InvalidCanisterHttpPayloadReason::NotEnoughSigners {
                     committee,
                     signers: valid_signers,
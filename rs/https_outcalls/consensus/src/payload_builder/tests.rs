@@ -34,9 +34,9 @@ use ic_types::{
     batch::{CanisterHttpPayload, ValidationContext, MAX_CANISTER_HTTP_PAYLOAD_SIZE},
     canister_http::{
         CanisterHttpMethod, CanisterHttpRequestContext, CanisterHttpResponse,
-        CanisterHttpResponseContent, CanisterHttpResponseDivergence, CanisterHttpResponseMetadata,
-        CanisterHttpResponseShare, CanisterHttpResponseWithConsensus,
-        CANISTER_HTTP_MAX_RESPONSES_PER_BLOCK, CANISTER_HTTP_TIMEOUT_INTERVAL,
+        CanisterHttpResponseArtifact, CanisterHttpResponseContent, CanisterHttpResponseDivergence,
+        CanisterHttpResponseMetadata, CanisterHttpResponseShare, CanisterHttpResponseWithConsensus,
+        Replication, CANISTER_HTTP_MAX_RESPONSES_PER_BLOCK, CANISTER_HTTP_TIMEOUT_INTERVAL,
     },
     consensus::get_faults_tolerated,
     crypto::{crypto_hash, BasicSig, BasicSigOf, CryptoHash, CryptoHashOf, Signed},
@@ -338,6 +338,7 @@ fn timeout_priority() {
                     transform: None,
                     // this is the important one
                     time: UNIX_EPOCH,
+                    replication: Replication::FullyReplicated,
                 };
                 init_state
                     .metadata
@@ -378,6 +379,163 @@ fn timeout_priority() {
     });
 }
 
+/// Check that the response to a non-replicated request is included with the
+/// share of the delegated node alone, and that shares of other nodes are ignored.
+#[test]
+fn non_replicated_request_test() {
+    let context = default_validation_context();
+    let delegated_node = 2;
+
+    test_config_with_http_feature(true, 4, |mut payload_builder, canister_http_pool| {
+        payload_builder.state_reader = state_with_non_replicated_request(0, delegated_node);
+
+        let (response, metadata) = test_response_and_metadata(0);
+        let shares = metadata_to_shares(4, &metadata);
+
+        {
+            // A share from a node that is not the delegated node is not enough.
+            let mut pool_access = canister_http_pool.write().unwrap();
+            add_own_share_to_pool(pool_access.deref_mut(), &shares[0], &response);
+        }
+        let payload = payload_builder.build_payload(
+            Height::new(1),
+            NumBytes::new(4 * 1024 * 1024),
+            &[],
+            &context,
+        );
+        let parsed_payload = bytes_to_payload(&payload).expect("Failed to parse the payload");
+        assert_eq!(parsed_payload.num_responses(), 0);
+
+        {
+            let mut pool_access = canister_http_pool.write().unwrap();
+            add_received_shares_to_pool(
+                pool_access.deref_mut(),
+                vec![shares[delegated_node as usize].clone()],
+            );
+        }
+        let payload = payload_builder.build_payload(
+            Height::new(1),
+            NumBytes::new(4 * 1024 * 1024),
+            &[],
+            &context,
+        );
+        let parsed_payload = bytes_to_payload(&payload).expect("Failed to parse the payload");
+        assert_eq!(parsed_payload.num_responses(), 1);
+        assert_eq!(parsed_payload.responses[0].content, response);
+        assert_eq!(
+            parsed_payload.responses[0]
+                .proof
+                .signature
+                .signatures_map
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![node_test_id(delegated_node)]
+        );
+
+        assert!(payload_builder
+            .validate_payload(
+                Height::new(1),
+                &test_proposal_context(&context),
+                &payload,
+                &[],
+            )
+            .is_ok());
+    });
+}
+
+/// Check that any block maker can include the response to a non-replicated
+/// request, once it received the delegated node's share together with the response.
+#[test]
+fn non_replicated_response_gossiped_with_share_test() {
+    let context = default_validation_context();
+    let delegated_node = 2;
+
+    test_config_with_http_feature(true, 4, |mut payload_builder, canister_http_pool| {
+        payload_builder.state_reader = state_with_non_replicated_request(0, delegated_node);
+
+        let (response, metadata) = test_response_and_metadata(0);
+        let share = metadata_to_share(delegated_node, &metadata);
+
+        {
+            let mut pool_access = canister_http_pool.write().unwrap();
+            pool_access.insert(UnvalidatedArtifact {
+                message: CanisterHttpResponseArtifact {
+                    share: share.clone(),
+                    response: Some(response.clone()),
+                },
+                peer_id: node_test_id(delegated_node),
+                timestamp: UNIX_EPOCH,
+            });
+            pool_access.apply(vec![CanisterHttpChangeAction::MoveToValidated(share)]);
+        }
+        let payload = payload_builder.build_payload(
+            Height::new(1),
+            NumBytes::new(4 * 1024 * 1024),
+            &[],
+            &context,
+        );
+        let parsed_payload = bytes_to_payload(&payload).expect("Failed to parse the payload");
+        assert_eq!(parsed_payload.num_responses(), 1);
+        assert_eq!(parsed_payload.responses[0].content, response);
+
+        assert!(payload_builder
+            .validate_payload(
+                Height::new(1),
+                &test_proposal_context(&context),
+                &payload,
+                &[],
+            )
+            .is_ok());
+    });
+}
+
+/// Check that the response to a non-replicated request is rejected, if it is
+/// not signed by exactly the delegated node.
+#[test]
+fn non_replicated_signer_validation() {
+    let delegated_node = 2;
+
+    test_config_with_http_feature(true, 4, |mut payload_builder, _| {
+        payload_builder.state_reader = state_with_non_replicated_request(0, delegated_node);
+
+        let (response, metadata) = test_response_and_metadata(0);
+        let mut proof = response_and_metadata_to_proof(&response, &metadata);
+        proof
+            .proof
+            .signature
+            .signatures_map
+            .insert(node_test_id(1), BasicSigOf::new(BasicSig(vec![])));
+
+        let payload = CanisterHttpPayload {
+            responses: vec![proof],
+            timeouts: vec![],
+            divergence_responses: vec![],
+        };
+        let payload = payload_to_bytes(&payload, NumBytes::new(4 * 1024 * 1024));
+
+        let validation_result = payload_builder.validate_payload(
+            Height::from(1),
+            &test_proposal_context(&default_validation_context()),
+            &payload,
+            &[],
+        );
+
+        match validation_result {
+            Err(ValidationError::InvalidArtifact(
+                InvalidPayloadReason::InvalidCanisterHttpPayload(
+                    InvalidCanisterHttpPayloadReason::NonReplicatedSignerMismatch {
+                        delegated_node_id,
+                        signers,
+                    },
+                ),
+            )) if delegated_node_id == node_test_id(delegated_node)
+                && signers == vec![node_test_id(1)] => {}
+            x => panic!("Expected NonReplicatedSignerMismatch, got {:?}", x),
+        }
+    });
+}
+
 /// Check that the payload builder includes a divergence responses
 #[test]
 fn divergence_response_inclusion_test() {
@@ -825,6 +983,43 @@ fn divergence_error_message() {
     );
 }
 
+/// Mocks a state manager whose state contains a single non-replicated request
+/// delegated to the given node.
+fn state_with_non_replicated_request(
+    callback_id: u64,
+    delegated_node: u64,
+) -> Arc<RefMockStateManager> {
+    let mut state = ic_test_utilities_state::get_initial_state(0, 0);
+    state
+        .metadata
+        .subnet_call_context_manager
+        .canister_http_request_contexts
+        .insert(
+            CallbackId::from(callback_id),
+            CanisterHttpRequestContext {
+                request: RequestBuilder::default().build(),
+                url: String::new(),
+                max_response_bytes: None,
+                headers: vec![],
+                body: None,
+                http_method: CanisterHttpMethod::GET,
+                transform: None,
+                time: UNIX_EPOCH,
+                replication: Replication::NonReplicated(node_test_id(delegated_node)),
+            },
+        );
+
+    let state_manager = Arc::new(RefMockStateManager::default());
+    state_manager
+        .get_mut()
+        .expect_get_state_at()
+        .return_const(Ok(ic_interfaces_state_manager::Labeled::new(
+            Height::new(0),
+            Arc::new(state),
+        )));
+    state_manager
+}
+
 /// Build some test metadata and response, which is valid and can be used in
 /// different tests
 pub(crate) fn test_response_and_metadata(
@@ -881,12 +1076,15 @@ fn test_response_and_metadata_full(
 }
 /// Replicates the behaviour of receiving and successfully validating a share over the network
 pub(crate) fn add_received_shares_to_pool(
-    pool: &mut dyn MutablePool<CanisterHttpResponseShare, Mutations = CanisterHttpChangeSet>,
+    pool: &mut dyn MutablePool<CanisterHttpResponseArtifact, Mutations = CanisterHttpChangeSet>,
     shares: Vec<CanisterHttpResponseShare>,
 ) {
     for share in shares {
         pool.insert(UnvalidatedArtifact {
-            message: share.clone(),
+            message: CanisterHttpResponseArtifact {
+                share: share.clone(),
+                response: None,
+            },
             peer_id: node_test_id(0),
             timestamp: UNIX_EPOCH,
         });
@@ -897,7 +1095,7 @@ pub(crate) fn add_received_shares_to_pool(
 
 /// Replicates the behaviour of adding your own share (and content) to the pool
 pub(crate) fn add_own_share_to_pool(
-    pool: &mut dyn MutablePool<CanisterHttpResponseShare, Mutations = CanisterHttpChangeSet>,
+    pool: &mut dyn MutablePool<CanisterHttpResponseArtifact, Mutations = CanisterHttpChangeSet>,
     share: &CanisterHttpResponseShare,
     content: &CanisterHttpResponse,
 ) {
@@ -20,12 +20,12 @@ use ic_registry_client_helpers::subnet::SubnetRegistry;
 use ic_replicated_state::ReplicatedState;
 use ic_types::{
     canister_http::*, consensus::HasHeight, crypto::Signed, messages::CallbackId,
-    replica_config::ReplicaConfig, Height,
+    replica_config::ReplicaConfig, Height, NodeId,
 };
 use rand::Rng;
 use std::{
     cell::RefCell,
-    collections::{BTreeSet, HashSet},
+    collections::{BTreeMap, BTreeSet, HashSet},
     convert::TryInto,
     sync::{Arc, Mutex},
     time::Duration,
@@ -239,6 +239,12 @@ impl CanisterHttpPoolManagerImpl {
         };
 
         for (id, context) in http_requests {
+            // A non-replicated request is only made by the node it was delegated to.
+            if let Replication::NonReplicated(node_id) = context.replication {
+                if node_id != self.replica_config.node_id {
+                    continue;
+                }
+            }
             if !request_ids_already_made.contains(&id) {
                 let timeout = context.time + Duration::from_secs(5 * 60);
                 if let Err(err) = self
@@ -282,6 +288,7 @@ impl CanisterHttpPoolManagerImpl {
             );
             return Vec::new();
         };
+        let non_replicated_node_ids = self.non_replicated_node_ids();
         let mut change_set = Vec::new();
         loop {
             match self.http_adapter_shim.lock().unwrap().try_receive() {
@@ -312,7 +319,15 @@ impl CanisterHttpPoolManagerImpl {
                     };
                     self.requested_id_cache.borrow_mut().remove(&response.id);
                     self.metrics.shares_signed.inc();
-                    change_set.push(CanisterHttpChangeAction::AddToValidated(share, response));
+                    // The response to a non-replicated request is gossiped along with the
+                    // share, so that any block maker can include it.
+                    if non_replicated_node_ids.contains_key(&response.id) {
+                        change_set.push(CanisterHttpChangeAction::AddToValidatedWithResponse(
+                            share, response,
+                        ));
+                    } else {
+                        change_set.push(CanisterHttpChangeAction::AddToValidated(share, response));
+                    }
                 }
             }
         }
@@ -345,6 +360,7 @@ impl CanisterHttpPoolManagerImpl {
 
         let active_callback_ids = self.active_callback_ids();
         let next_callback_id = self.next_callback_id();
+        let non_replicated_node_ids = self.non_replicated_node_ids();
 
         let key_from_share =
             |share: &CanisterHttpResponseShare| (share.signature.signer, share.content.id);
@@ -393,6 +409,30 @@ impl CanisterHttpPoolManagerImpl {
                             .to_string(),
                     ));
                 }
+                if let Some(node_id) = non_replicated_node_ids.get(&share.content.id) {
+                    if *node_id != share.signature.signer {
+                        return Some(CanisterHttpChangeAction::HandleInvalid(
+                            share.clone(),
+                            "Share of a non-replicated request signed by a node other than the delegated node"
+                                .to_string(),
+                        ));
+                    }
+                    let response_matches_share = canister_http_pool
+                        .get_unvalidated_response(share)
+                        .is_some_and(|response| {
+                            response.id == share.content.id
+                                && response.timeout == share.content.timeout
+                                && ic_types::crypto::crypto_hash(response)
+                                    == share.content.content_hash
+                        });
+                    if !response_matches_share {
+                        return Some(CanisterHttpChangeAction::HandleInvalid(
+                            share.clone(),
+                            "Share of a non-replicated request without a matching response"
+                                .to_string(),
+                        ));
+                    }
+                }
                 // TODO: more precise error handling
                 if let Err(err) = self.crypto.verify(share, registry_version) {
                     error!(self.log, "Unable to verify signature of share, {}", err);
@@ -468,6 +508,22 @@ impl CanisterHttpPoolManagerImpl {
             .collect()
     }
 
+    /// Returns the delegated node of every active non-replicated request.
+    fn non_replicated_node_ids(&self) -> BTreeMap<CallbackId, NodeId> {
+        self.state_reader
+            .get_latest_state()
+            .get_ref()
+            .metadata
+            .subnet_call_context_manager
+            .canister_http_request_contexts
+            .iter()
+            .filter_map(|(id, context)| match context.replication {
+                Replication::NonReplicated(node_id) => Some((*id, node_id)),
+                Replication::FullyReplicated => None,
+            })
+            .collect()
+    }
+
     fn next_callback_id(&self) -> CallbackId {
         self.state_reader
             .get_latest_state()
@@ -507,7 +563,7 @@ pub mod test {
     use ic_registry_subnet_type::SubnetType;
     use ic_replicated_state::metadata_state::subnet_call_context_manager::SubnetCallContext;
     use ic_test_utilities_logger::with_test_replica_logger;
-    use ic_test_utilities_types::ids::subnet_test_id;
+    use ic_test_utilities_types::ids::{node_test_id, subnet_test_id};
     use ic_types::{
         crypto::{CryptoHash, CryptoHashOf},
         messages::CallbackId,
@@ -584,6 +640,7 @@ pub mod test {
                     http_method: CanisterHttpMethod::GET,
                     transform: None,
                     time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    replication: Replication::FullyReplicated,
                 };
 
                 state_manager
@@ -623,7 +680,10 @@ pub mod test {
                         signature,
                     };
                     canister_http_pool.insert(UnvalidatedArtifact {
-                        message: share,
+                        message: CanisterHttpResponseArtifact {
+                            share,
+                            response: None,
+                        },
                         peer_id: replica_config.node_id,
                         timestamp: UNIX_EPOCH,
                     });
@@ -681,6 +741,7 @@ pub mod test {
                     http_method: CanisterHttpMethod::GET,
                     transform: None,
                     time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    replication: Replication::FullyReplicated,
                 };
 
                 state_manager
@@ -740,7 +801,10 @@ pub mod test {
                         signature,
                     };
                     canister_http_pool.insert(UnvalidatedArtifact {
-                        message: share,
+                        message: CanisterHttpResponseArtifact {
+                            share,
+                            response: None,
+                        },
                         peer_id: replica_config.node_id,
                         timestamp: UNIX_EPOCH,
                     });
@@ -805,6 +869,7 @@ pub mod test {
                     http_method: CanisterHttpMethod::GET,
                     transform: None,
                     time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    replication: Replication::FullyReplicated,
                 };
 
                 state_manager
@@ -864,6 +929,196 @@ pub mod test {
         });
     }
 
+    #[test]
+    pub fn test_non_replicated_request_only_made_by_delegated_node() {
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            with_test_replica_logger(|log| {
+                let Dependencies {
+                    pool,
+                    replica_config,
+                    crypto,
+                    state_manager,
+                    registry,
+                    ..
+                } = dependencies(pool_config.clone(), 4);
+                let mut shim_mock = MockNonBlockingChannel::<CanisterHttpRequest>::new();
+                shim_mock
+                    .expect_try_receive()
+                    .return_const(Err(TryReceiveError::Empty));
+
+                let shim: Arc<Mutex<CanisterHttpAdapterClient>> =
+                    Arc::new(Mutex::new(Box::new(shim_mock)));
+
+                let delegated_node_id = node_test_id(1);
+                assert_ne!(delegated_node_id, replica_config.node_id);
+                let request = CanisterHttpRequestContext {
+                    request: ic_test_utilities_types::messages::RequestBuilder::new().build(),
+                    url: "".to_string(),
+                    max_response_bytes: None,
+                    headers: vec![],
+                    body: None,
+                    http_method: CanisterHttpMethod::GET,
+                    transform: None,
+                    time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    replication: Replication::NonReplicated(delegated_node_id),
+                };
+
+                state_manager
+                    .get_mut()
+                    .expect_get_latest_state()
+                    .return_const(Labeled::new(
+                        Height::from(1),
+                        Arc::new(state_with_pending_http_calls(BTreeMap::from([(
+                            CallbackId::from(7),
+                            request,
+                        )]))),
+                    ));
+
+                let canister_http_pool =
+                    CanisterHttpPoolImpl::new(MetricsRegistry::new(), no_op_logger());
+                let pool_manager = CanisterHttpPoolManagerImpl::new(
+                    state_manager as Arc<_>,
+                    shim,
+                    crypto,
+                    pool.get_cache(),
+                    replica_config,
+                    Arc::clone(&registry) as Arc<_>,
+                    MetricsRegistry::new(),
+                    log,
+                );
+
+                // The request is delegated to another node. We haven't set an
+                // expectation on send, so this will fail if send is called.
+                pool_manager.generate_change_set(&canister_http_pool);
+            })
+        });
+    }
+
+    #[test]
+    pub fn test_validation_of_non_replicated_shares_requires_response() {
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            with_test_replica_logger(|log| {
+                let Dependencies {
+                    pool,
+                    replica_config,
+                    crypto,
+                    state_manager,
+                    registry,
+                    ..
+                } = dependencies(pool_config.clone(), 5);
+                let mut shim_mock = MockNonBlockingChannel::<CanisterHttpRequest>::new();
+                shim_mock
+                    .expect_try_receive()
+                    .return_const(Err(TryReceiveError::Empty));
+
+                let request = CanisterHttpRequestContext {
+                    request: ic_test_utilities_types::messages::RequestBuilder::new().build(),
+                    url: "".to_string(),
+                    max_response_bytes: None,
+                    headers: vec![],
+                    body: None,
+                    http_method: CanisterHttpMethod::GET,
+                    transform: None,
+                    time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    replication: Replication::NonReplicated(replica_config.node_id),
+                };
+
+                state_manager
+                    .get_mut()
+                    .expect_get_latest_state()
+                    .return_const(Labeled::new(
+                        Height::from(1),
+                        Arc::new(state_with_pending_http_calls(BTreeMap::from([(
+                            CallbackId::from(0),
+                            request,
+                        )]))),
+                    ));
+
+                let response = empty_canister_http_response(0);
+                let response_metadata = CanisterHttpResponseMetadata {
+                    id: response.id,
+                    timeout: response.timeout,
+                    registry_version: RegistryVersion::from(1),
+                    content_hash: ic_types::crypto::crypto_hash(&response),
+                };
+                let signature = crypto
+                    .sign(
+                        &response_metadata,
+                        replica_config.node_id,
+                        RegistryVersion::from(1),
+                    )
+                    .unwrap();
+                let share = Signed {
+                    content: response_metadata,
+                    signature,
+                };
+
+                let mut canister_http_pool =
+                    CanisterHttpPoolImpl::new(MetricsRegistry::new(), no_op_logger());
+
+                let shim: Arc<Mutex<CanisterHttpAdapterClient>> =
+                    Arc::new(Mutex::new(Box::new(shim_mock)));
+
+                let pool_manager = CanisterHttpPoolManagerImpl::new(
+                    state_manager as Arc<_>,
+                    shim,
+                    crypto,
+                    pool.get_cache(),
+                    replica_config.clone(),
+                    Arc::clone(&registry) as Arc<_>,
+                    MetricsRegistry::new(),
+                    log,
+                );
+
+                // A share of a non-replicated request that comes without the response
+                // can not be included by other block makers, so it is rejected.
+                canister_http_pool.insert(UnvalidatedArtifact {
+                    message: CanisterHttpResponseArtifact {
+                        share: share.clone(),
+                        response: None,
+                    },
+                    peer_id: replica_config.node_id,
+                    timestamp: UNIX_EPOCH,
+                });
+                let changes = pool_manager.validate_shares(
+                    pool.get_cache().as_ref(),
+                    &canister_http_pool,
+                    Height::from(0),
+                );
+                assert_eq!(changes.len(), 1);
+                if let CanisterHttpChangeAction::HandleInvalid(_, err) = &changes[0] {
+                    assert_eq!(
+                        err,
+                        "Share of a non-replicated request without a matching response"
+                    );
+                } else {
+                    panic!("unexpected change action");
+                }
+
+                // The same share gossiped together with the response is valid.
+                canister_http_pool.insert(UnvalidatedArtifact {
+                    message: CanisterHttpResponseArtifact {
+                        share: share.clone(),
+                        response: Some(response),
+                    },
+                    peer_id: replica_config.node_id,
+                    timestamp: UNIX_EPOCH,
+                });
+                let changes = pool_manager.validate_shares(
+                    pool.get_cache().as_ref(),
+                    &canister_http_pool,
+                    Height::from(0),
+                );
+                assert_eq!(changes.len(), 1);
+                if let CanisterHttpChangeAction::MoveToValidated(validated) = &changes[0] {
+                    assert_eq!(*validated, share);
+                } else {
+                    panic!("unexpected change action");
+                }
+            })
+        });
+    }
+
     #[test]
     pub fn test_create_shares() {
         ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
@@ -949,6 +1204,7 @@ pub mod test {
                     http_method: CanisterHttpMethod::GET,
                     transform: None,
                     time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    replication: Replication::FullyReplicated,
                 };
 
                 // Expect times to be called exactly once to check that already
@@ -64,6 +64,12 @@ pub enum InvalidCanisterHttpPayloadReason {
         signers: Vec<NodeId>,
         expected_threshold: Threshold,
     },
+    /// The response to a non-replicated request was not signed by exactly
+    /// the node the request was delegated to
+    NonReplicatedSignerMismatch {
+        delegated_node_id: NodeId,
+        signers: Vec<NodeId>,
+    },
     /// The payload contains a duplicate response
     DuplicateResponse(CallbackId),
     DivergenceProofContainsMultipleCallbackIds,
@@ -90,6 +96,9 @@ pub type CanisterHttpPayloadValidationError =
 
 pub enum CanisterHttpChangeAction {
     AddToValidated(CanisterHttpResponseShare, CanisterHttpResponse),
+    /// Like [`CanisterHttpChangeAction::AddToValidated`], but the response is
+    /// gossiped together with the share. Used for non-replicated requests.
+    AddToValidatedWithResponse(CanisterHttpResponseShare, CanisterHttpResponse),
     MoveToValidated(CanisterHttpResponseShare),
     RemoveValidated(CanisterHttpResponseId),
     RemoveUnvalidated(CanisterHttpResponseId),
@@ -103,6 +112,12 @@ pub type CanisterHttpChangeSet = Vec<CanisterHttpChangeAction>;
 pub trait CanisterHttpPool: Send + Sync {
     fn get_validated_shares(&self) -> Box<dyn Iterator<Item = &CanisterHttpResponseShare> + '_>;
     fn get_unvalidated_shares(&self) -> Box<dyn Iterator<Item = &CanisterHttpResponseShare> + '_>;
+    /// Returns the response that was gossiped together with the given
+    /// unvalidated share, if any.
+    fn get_unvalidated_response(
+        &self,
+        share: &CanisterHttpResponseShare,
+    ) -> Option<&CanisterHttpResponse>;
     // TODO: Likely not needed
     fn get_response_content_items(
         &self,
@@ -13,16 +13,28 @@ pub enum IndexArg {
     Upgrade(UpgradeArg),
 }
 
+/// The path of a field in an ICRC-3 block, e.g., `["tx", "from"]`
+/// for the `from` field of the transaction of the block.
+pub type FieldPath = Vec<String>;
+
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub struct InitArg {
     pub ledger_id: Principal,
     pub retrieve_blocks_from_ledger_interval_seconds: Option<u64>,
+    // The paths of the fields containing the accounts involved in a block
+    // that the index cannot decode as an ICRC-1 block. If None then the
+    // `from`, `to` and `spender` fields of the transaction are used.
+    pub account_field_paths: Option<Vec<FieldPath>>,
 }
 
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub struct UpgradeArg {
     pub ledger_id: Option<Principal>,
     pub retrieve_blocks_from_ledger_interval_seconds: Option<u64>,
+    // If set then replaces the paths of the fields containing the accounts
+    // involved in a block that the index cannot decode as an ICRC-1 block.
+    // The new paths only apply to the blocks indexed after the upgrade.
+    pub account_field_paths: Option<Vec<FieldPath>>,
 }
 
 #[derive(Eq, PartialEq, Debug, CandidType, Deserialize)]
@@ -44,6 +56,49 @@ pub struct GetAccountTransactionsArgs {
     pub start: Option<BlockIndex>,
     // Maximum number of transactions to fetch.
     pub max_results: Nat,
+    // If set then only the transactions matching the filter are returned.
+    pub filter: Option<TransactionFilter>,
+}
+
+/// A filter on the transactions of an account. All the conditions that
+/// are set must hold for a transaction to be returned. The blocks that the
+/// index cannot decode as ICRC-1 blocks never match a filter.
+#[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
+pub struct TransactionFilter {
+    // Only return transactions of one of the given kinds.
+    pub kinds: Option<Vec<TransactionKind>>,
+    // Only return transactions in the given direction with respect to the account.
+    pub direction: Option<TransactionDirection>,
+    // Only return transactions between the account and the counterparty,
+    // i.e., the other party of a transfer, the spender of an approve
+    // or the spender of a burn.
+    pub counterparty: Option<Account>,
+    // Only return transactions with an amount greater than or equal to this value.
+    pub min_amount: Option<Nat>,
+    // Only return transactions with an amount less than or equal to this value.
+    pub max_amount: Option<Nat>,
+    // Only return transactions with a timestamp (in nanoseconds since the
+    // UNIX epoch) greater than or equal to this value.
+    pub start_timestamp: Option<u64>,
+    // Only return transactions with a timestamp (in nanoseconds since the
+    // UNIX epoch) strictly less than this value.
+    pub end_timestamp: Option<u64>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, CandidType, Deserialize)]
+pub enum TransactionKind {
+    Mint,
+    Burn,
+    Transfer,
+    Approve,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, CandidType, Deserialize)]
+pub enum TransactionDirection {
+    // Transactions crediting the account, i.e., mints and transfers to the account.
+    Incoming,
+    // Transactions debiting the account, i.e., burns, transfers and approves from the account.
+    Outgoing,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
@@ -58,6 +113,10 @@ pub struct GetAccountTransactionsResponse {
     pub transactions: Vec<TransactionWithId>,
     // The txid of the oldest transaction the account has
     pub oldest_tx_id: Option<BlockIndex>,
+    // Set if the scan of the transactions matching the filter stopped before
+    // returning max_results transactions because too many transactions had to
+    // be scanned. Pass it as start to resume the scan.
+    pub scan_cursor: Option<BlockIndex>,
 }
 
 #[derive(Eq, PartialEq, Debug, CandidType, Deserialize)]
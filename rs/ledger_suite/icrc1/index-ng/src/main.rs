@@ -10,9 +10,10 @@ use ic_icrc1::blocks::{encoded_block_to_generic_block, generic_block_to_encoded_
 use ic_icrc1::endpoints::StandardRecord;
 use ic_icrc1::{Block, Operation};
 use ic_icrc1_index_ng::{
-    FeeCollectorRanges, GetAccountTransactionsArgs, GetAccountTransactionsResponse,
-    GetAccountTransactionsResult, GetBlocksMethod, IndexArg, InitArg, ListSubaccountsArgs, Log,
-    LogEntry, Status, TransactionWithId, UpgradeArg, DEFAULT_MAX_BLOCKS_PER_RESPONSE,
+    FeeCollectorRanges, FieldPath, GetAccountTransactionsArgs, GetAccountTransactionsError,
+    GetAccountTransactionsResponse, GetAccountTransactionsResult, GetBlocksMethod, IndexArg,
+    InitArg, ListSubaccountsArgs, Log, LogEntry, Status, TransactionDirection, TransactionFilter,
+    TransactionKind, TransactionWithId, UpgradeArg, DEFAULT_MAX_BLOCKS_PER_RESPONSE,
 };
 use ic_ledger_canister_core::runtime::heap_memory_size_bytes;
 use ic_ledger_core::block::{BlockIndex as BlockIndex64, BlockType, EncodedBlock};
@@ -31,19 +32,19 @@ use icrc_ledger_types::icrc3::blocks::{
     GetBlocksResult,
 };
 use icrc_ledger_types::icrc3::transactions::Transaction;
-use num_traits::ToPrimitive;
+use num_traits::{Bounded, ToPrimitive};
 use scopeguard::guard;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::cmp::Reverse;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
 use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::io::Read;
-use std::ops::Bound::{Excluded, Included};
-use std::ops::Range;
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::ops::{Range, RangeInclusive};
 use std::time::Duration;
 
 pub mod logs;
@@ -55,9 +56,33 @@ const BLOCK_LOG_INDEX_MEMORY_ID: MemoryId = MemoryId::new(1);
 const BLOCK_LOG_DATA_MEMORY_ID: MemoryId = MemoryId::new(2);
 const ACCOUNT_BLOCK_IDS_MEMORY_ID: MemoryId = MemoryId::new(3);
 const ACCOUNT_DATA_MEMORY_ID: MemoryId = MemoryId::new(4);
+const ACCOUNT_TRANSACTION_METADATA_MEMORY_ID: MemoryId = MemoryId::new(5);
+const ACCOUNT_COUNTERPARTY_BLOCK_IDS_MEMORY_ID: MemoryId = MemoryId::new(6);
+const ACCOUNT_AMOUNT_BLOCK_IDS_MEMORY_ID: MemoryId = MemoryId::new(7);
+const BLOCK_TIMESTAMPS_MEMORY_ID: MemoryId = MemoryId::new(8);
 
 const DEFAULT_RETRIEVE_BLOCKS_FROM_LEDGER_INTERVAL: Duration = Duration::from_secs(1);
 
+/// The maximum number of blocks for which the transaction metadata is
+/// backfilled in a single [build_index] round.
+const MAX_BLOCKS_TO_BACKFILL_PER_ROUND: u64 = 10_000;
+
+/// The maximum number of transactions of an account that are checked
+/// against a filter by a single call to [get_account_transactions].
+/// Only the transactions selected by the index that matches the filter
+/// best (see [get_filtered_account_block_ids]) are checked.
+const MAX_TRANSACTIONS_TO_SCAN_PER_FILTER: usize = 50_000;
+
+/// The maximum number of distinct amounts in the amount range of a filter
+/// for which the transactions are looked up in [ACCOUNT_AMOUNT_BLOCK_IDS].
+/// The transactions of wider ranges are scanned instead.
+const MAX_AMOUNTS_TO_MERGE_PER_FILTER: usize = 1_000;
+
+/// The paths of the fields containing the accounts involved in a block
+/// that cannot be decoded as an ICRC-1 block if none is configured.
+const DEFAULT_ACCOUNT_FIELD_PATHS: [[&str; 2]; 3] =
+    [["tx", "from"], ["tx", "to"], ["tx", "spender"]];
+
 #[cfg(not(feature = "u256-tokens"))]
 type Tokens = ic_icrc1_tokens_u64::U64;
 
@@ -77,6 +102,25 @@ type AccountBlockIdsMap = StableBTreeMap<AccountBlockIdsMapKey, (), VM>;
 type AccountDataMapKey = (AccountDataType, (Blob<29>, [u8; 32]));
 type AccountDataMap = StableBTreeMap<AccountDataMapKey, Tokens, VM>;
 
+// The transaction metadata is keyed like the account block ids so that
+// the transactions of an account can be filtered without decoding blocks.
+type AccountTransactionMetadataMap = StableBTreeMap<AccountBlockIdsMapKey, TransactionMetadata, VM>;
+
+// The secondary indexes of the transactions of an account are keyed by the
+// account, the indexed value and the block index, so that the transactions of
+// an account with a given value are also returned in reversed order.
+type AccountCounterpartyBlockIdsMapKey = (
+    [u8; Sha256::DIGEST_LEN],
+    ([u8; Sha256::DIGEST_LEN], Reverse<u64>),
+);
+type AccountCounterpartyBlockIdsMap = StableBTreeMap<AccountCounterpartyBlockIdsMapKey, (), VM>;
+type AccountAmountBlockIdsMapKey = ([u8; Sha256::DIGEST_LEN], (Tokens, Reverse<u64>));
+type AccountAmountBlockIdsMap = StableBTreeMap<AccountAmountBlockIdsMapKey, (), VM>;
+
+// The blocks with a timestamp keyed by timestamp and block index, so that
+// the blocks in a time range can be found without decoding blocks.
+type BlockTimestampsMap = StableBTreeMap<(u64, BlockIndex64), (), VM>;
+
 thread_local! {
     /// Static memory manager to manage the memory available for stable structures.
     static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(MemoryManager::init(DefaultMemoryImpl::default()));
@@ -104,6 +148,29 @@ thread_local! {
         RefCell::new(AccountDataMap::init(memory_manager.get(ACCOUNT_DATA_MEMORY_ID)))
     });
 
+    /// Map that contains the metadata of the transactions of an account,
+    /// used to filter the account transactions.
+    static ACCOUNT_TRANSACTION_METADATA: RefCell<AccountTransactionMetadataMap> = with_memory_manager(|memory_manager| {
+        RefCell::new(AccountTransactionMetadataMap::init(memory_manager.get(ACCOUNT_TRANSACTION_METADATA_MEMORY_ID)))
+    });
+
+    /// Map that contains the block ids of the transactions of an account
+    /// with a counterparty. Both accounts are hashed to save space.
+    static ACCOUNT_COUNTERPARTY_BLOCK_IDS: RefCell<AccountCounterpartyBlockIdsMap> = with_memory_manager(|memory_manager| {
+        RefCell::new(AccountCounterpartyBlockIdsMap::init(memory_manager.get(ACCOUNT_COUNTERPARTY_BLOCK_IDS_MEMORY_ID)))
+    });
+
+    /// Map that contains the block ids of the transactions of an account
+    /// by amount. The account is hashed to save space.
+    static ACCOUNT_AMOUNT_BLOCK_IDS: RefCell<AccountAmountBlockIdsMap> = with_memory_manager(|memory_manager| {
+        RefCell::new(AccountAmountBlockIdsMap::init(memory_manager.get(ACCOUNT_AMOUNT_BLOCK_IDS_MEMORY_ID)))
+    });
+
+    /// Map that contains the timestamps of the blocks that have one.
+    static BLOCK_TIMESTAMPS: RefCell<BlockTimestampsMap> = with_memory_manager(|memory_manager| {
+        RefCell::new(BlockTimestampsMap::init(memory_manager.get(BLOCK_TIMESTAMPS_MEMORY_ID)))
+    });
+
     /// Profiling data to understand cycles usage
     static PROFILING_DATA: RefCell<SpanStats> = RefCell::new(SpanStats::default());
 
@@ -136,6 +203,23 @@ struct State {
     /// index. Lower values will result in a more responsive UI, but higher costs due to increased
     /// cycle burn for the index, ledger and archive(s).
     retrieve_blocks_from_ledger_interval: Option<Duration>,
+
+    /// The range of blocks that were indexed before the transaction metadata
+    /// was introduced and whose transaction metadata and timestamps are still
+    /// missing.
+    /// Transaction filters are unavailable until the range is fully backfilled.
+    #[serde(default)]
+    transaction_metadata_backfill: Option<Range<BlockIndex64>>,
+
+    /// The paths of the fields containing the accounts involved in a block
+    /// that cannot be decoded as an ICRC-1 block.
+    #[serde(default)]
+    account_field_paths: Option<Vec<FieldPath>>,
+
+    /// The number of blocks that could not be decoded as ICRC-1 blocks and
+    /// whose effect on the balances was therefore not tracked.
+    #[serde(default)]
+    num_generic_blocks: u64,
 }
 
 impl State {
@@ -143,6 +227,15 @@ impl State {
         self.retrieve_blocks_from_ledger_interval
             .unwrap_or(DEFAULT_RETRIEVE_BLOCKS_FROM_LEDGER_INTERVAL)
     }
+
+    pub fn account_field_paths(&self) -> Vec<FieldPath> {
+        self.account_field_paths.clone().unwrap_or_else(|| {
+            DEFAULT_ACCOUNT_FIELD_PATHS
+                .iter()
+                .map(|path| path.iter().map(|field| field.to_string()).collect())
+                .collect()
+        })
+    }
 }
 
 // NOTE: the default configuration is dysfunctional, but it's convenient to have
@@ -157,6 +250,9 @@ impl Default for State {
             fee_collectors: Default::default(),
             last_fee: None,
             retrieve_blocks_from_ledger_interval: None,
+            transaction_metadata_backfill: None,
+            account_field_paths: None,
+            num_generic_blocks: 0,
         }
     }
 }
@@ -208,6 +304,165 @@ impl Storable for AccountDataType {
     };
 }
 
+/// The data of a transaction needed to filter the transactions of an account.
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct TransactionMetadata {
+    kind: TransactionKind,
+    /// Whether the transaction credits the account, see [TransactionDirection::Incoming].
+    incoming: bool,
+    /// Whether the transaction debits the account, see [TransactionDirection::Outgoing].
+    outgoing: bool,
+    timestamp: u64,
+    /// The hash of the counterparty account, see [TransactionFilter::counterparty].
+    counterparty: Option<[u8; Sha256::DIGEST_LEN]>,
+    amount: Tokens,
+}
+
+impl TransactionMetadata {
+    fn new(account: Account, block: &Block<Tokens>) -> Self {
+        let (kind, incoming, outgoing, counterparty, amount) = match block.transaction.operation {
+            Operation::Mint { to, amount } => {
+                (TransactionKind::Mint, account == to, false, None, amount)
+            }
+            Operation::Burn {
+                from,
+                spender,
+                amount,
+            } => (
+                TransactionKind::Burn,
+                false,
+                account == from,
+                spender,
+                amount,
+            ),
+            Operation::Transfer {
+                from, to, amount, ..
+            } => {
+                let counterparty = if account == from { to } else { from };
+                (
+                    TransactionKind::Transfer,
+                    account == to,
+                    account == from,
+                    Some(counterparty),
+                    amount,
+                )
+            }
+            Operation::Approve {
+                from,
+                spender,
+                amount,
+                ..
+            } => (
+                TransactionKind::Approve,
+                false,
+                account == from,
+                Some(spender),
+                amount,
+            ),
+        };
+        Self {
+            kind,
+            incoming,
+            outgoing,
+            timestamp: block.timestamp,
+            counterparty: counterparty.map(account_sha256),
+            amount,
+        }
+    }
+
+    fn matches(
+        &self,
+        filter: &TransactionFilter,
+        counterparty: Option<&[u8; Sha256::DIGEST_LEN]>,
+    ) -> bool {
+        filter
+            .kinds
+            .as_ref()
+            .map_or(true, |kinds| kinds.contains(&self.kind))
+            && filter.direction.map_or(true, |direction| match direction {
+                TransactionDirection::Incoming => self.incoming,
+                TransactionDirection::Outgoing => self.outgoing,
+            })
+            && counterparty.map_or(true, |counterparty| {
+                self.counterparty.as_ref() == Some(counterparty)
+            })
+            && filter
+                .min_amount
+                .as_ref()
+                .map_or(true, |min_amount| &Nat::from(self.amount) >= min_amount)
+            && filter
+                .max_amount
+                .as_ref()
+                .map_or(true, |max_amount| &Nat::from(self.amount) <= max_amount)
+            && filter
+                .start_timestamp
+                .map_or(true, |start_timestamp| self.timestamp >= start_timestamp)
+            && filter
+                .end_timestamp
+                .map_or(true, |end_timestamp| self.timestamp < end_timestamp)
+    }
+}
+
+impl Storable for TransactionMetadata {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let mut buf = Vec::with_capacity(Self::BOUND.max_size() as usize);
+        buf.push(match self.kind {
+            TransactionKind::Mint => 0x00,
+            TransactionKind::Burn => 0x01,
+            TransactionKind::Transfer => 0x02,
+            TransactionKind::Approve => 0x03,
+        });
+        buf.push(u8::from(self.incoming) | (u8::from(self.outgoing) << 1));
+        buf.extend_from_slice(&self.timestamp.to_be_bytes());
+        match &self.counterparty {
+            Some(counterparty) => {
+                buf.push(0x01);
+                buf.extend_from_slice(counterparty);
+            }
+            None => buf.push(0x00),
+        }
+        buf.extend_from_slice(&self.amount.to_bytes());
+        Cow::Owned(buf)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        let kind = match bytes[0] {
+            0x00 => TransactionKind::Mint,
+            0x01 => TransactionKind::Burn,
+            0x02 => TransactionKind::Transfer,
+            0x03 => TransactionKind::Approve,
+            kind => panic!("Unknown TransactionKind {}", kind),
+        };
+        let direction_flags = bytes[1];
+        if direction_flags > 0b11 {
+            panic!("Invalid direction flags {}", direction_flags);
+        }
+        let timestamp = u64::from_be_bytes(bytes[2..10].try_into().unwrap());
+        let (counterparty, amount_offset) = match bytes[10] {
+            0x00 => (None, 11),
+            0x01 => (
+                Some(bytes[11..11 + Sha256::DIGEST_LEN].try_into().unwrap()),
+                11 + Sha256::DIGEST_LEN,
+            ),
+            flag => panic!("Invalid counterparty flag {}", flag),
+        };
+        let amount = Tokens::from_bytes(Cow::Borrowed(&bytes[amount_offset..]));
+        Self {
+            kind,
+            incoming: direction_flags & 0b01 != 0,
+            outgoing: direction_flags & 0b10 != 0,
+            timestamp,
+            counterparty,
+            amount,
+        }
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 1 + 1 + 8 + 1 + Sha256::DIGEST_LEN as u32 + Tokens::BOUND.max_size(),
+        is_fixed_size: false,
+    };
+}
+
 // Ephemeral data that doesn't need to be saved between upgrades
 #[derive(Clone, Debug, Default)]
 struct Cache {
@@ -222,6 +477,272 @@ fn test_account_data_type_storable() {
     );
 }
 
+#[test]
+fn test_transaction_metadata_storable() {
+    for (kind, incoming, outgoing, counterparty) in [
+        (TransactionKind::Mint, true, false, None),
+        (
+            TransactionKind::Burn,
+            false,
+            true,
+            Some([0x11; Sha256::DIGEST_LEN]),
+        ),
+        (
+            TransactionKind::Transfer,
+            true,
+            true,
+            Some([0xff; Sha256::DIGEST_LEN]),
+        ),
+        (
+            TransactionKind::Approve,
+            false,
+            false,
+            Some([0x00; Sha256::DIGEST_LEN]),
+        ),
+    ] {
+        let metadata = TransactionMetadata {
+            kind,
+            incoming,
+            outgoing,
+            timestamp: 1_700_000_000_000_000_000,
+            counterparty,
+            amount: Tokens::from(1_000_000_u64),
+        };
+        let bytes = metadata.to_bytes();
+        assert!(bytes.len() <= TransactionMetadata::BOUND.max_size() as usize);
+        assert_eq!(metadata, TransactionMetadata::from_bytes(bytes));
+    }
+}
+
+#[test]
+fn test_scan_transaction_metadata() {
+    // Blocks 9 to 0, newest first, where the blocks with an even id are transfers.
+    let metadata = (0..10_u64).rev().map(|block_index| {
+        let kind = if block_index % 2 == 0 {
+            TransactionKind::Transfer
+        } else {
+            TransactionKind::Approve
+        };
+        let metadata = TransactionMetadata {
+            kind,
+            incoming: false,
+            outgoing: true,
+            timestamp: block_index,
+            counterparty: None,
+            amount: Tokens::from(block_index),
+        };
+        (block_index, metadata)
+    });
+    let transfers = TransactionFilter {
+        kinds: Some(vec![TransactionKind::Transfer]),
+        ..Default::default()
+    };
+    let scan = |length: usize, scan_budget: usize, filter: &TransactionFilter| {
+        scan_transaction_metadata(metadata.clone(), filter, None, length, scan_budget)
+    };
+
+    assert_eq!(scan(10, 100, &transfers), (vec![8, 6, 4, 2, 0], None));
+    assert_eq!(scan(2, 100, &transfers), (vec![8, 6], None));
+    // The scan stops after scanning blocks 9 to 5 and resumes from block 5.
+    assert_eq!(scan(10, 5, &transfers), (vec![8, 6], Some(5)));
+    // The scan ends before the budget when enough transactions match.
+    assert_eq!(scan(2, 4, &transfers), (vec![8, 6], None));
+    // The scan ends at the first transaction before the start of the time range.
+    let recent_transfers = TransactionFilter {
+        start_timestamp: Some(5),
+        ..transfers.clone()
+    };
+    assert_eq!(scan(10, 6, &recent_transfers), (vec![8, 6], None));
+    // Filter by direction.
+    let incoming = TransactionFilter {
+        direction: Some(TransactionDirection::Incoming),
+        ..Default::default()
+    };
+    assert_eq!(scan(10, 100, &incoming), (vec![], None));
+    let outgoing = TransactionFilter {
+        direction: Some(TransactionDirection::Outgoing),
+        ..Default::default()
+    };
+    assert_eq!(scan(3, 100, &outgoing), (vec![9, 8, 7], None));
+}
+
+#[test]
+fn test_amount_block_ids() {
+    let memory_manager = MemoryManager::init(DefaultMemoryImpl::default());
+    let mut block_ids = AccountAmountBlockIdsMap::init(memory_manager.get(MemoryId::new(0)));
+    let (account, other_account) = ([1; Sha256::DIGEST_LEN], [2; Sha256::DIGEST_LEN]);
+    for (account, amount, block_index) in [
+        (account, 10_u64, 1),
+        (account, 20, 2),
+        (account, 30, 3),
+        (account, 10, 4),
+        (account, 20, 5),
+        (other_account, 10, 6),
+        (account, 10, 7),
+    ] {
+        block_ids.insert((account, (Tokens::from(amount), Reverse(block_index))), ());
+    }
+    let merge = |min_amount: u64, max_amount: u64, ids: Range<BlockIndex64>| {
+        AmountBlockIds::new(
+            &block_ids,
+            account,
+            Tokens::from(min_amount)..=Tokens::from(max_amount),
+            ids,
+        )
+        .unwrap()
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(merge(10, 20, 0..10), vec![7, 5, 4, 2, 1]);
+    assert_eq!(merge(10, 20, 2..6), vec![5, 4, 2]);
+    assert_eq!(merge(20, 30, 0..10), vec![5, 3, 2]);
+    assert_eq!(merge(30, 30, 0..3), vec![]);
+    assert_eq!(merge(40, 50, 0..10), vec![]);
+
+    // Too many distinct amounts to merge.
+    let account = [3; Sha256::DIGEST_LEN];
+    for amount in 0..=MAX_AMOUNTS_TO_MERGE_PER_FILTER as u64 {
+        block_ids.insert((account, (Tokens::from(amount), Reverse(amount))), ());
+    }
+    let max_amount = Tokens::from(MAX_AMOUNTS_TO_MERGE_PER_FILTER as u64);
+    assert!(AmountBlockIds::new(
+        &block_ids,
+        account,
+        Tokens::zero()..=max_amount,
+        0..u64::MAX
+    )
+    .is_none());
+    let max_amount = Tokens::from(MAX_AMOUNTS_TO_MERGE_PER_FILTER as u64 - 1);
+    assert!(AmountBlockIds::new(
+        &block_ids,
+        account,
+        Tokens::zero()..=max_amount,
+        0..u64::MAX
+    )
+    .is_some());
+}
+
+#[test]
+fn test_get_accounts_at_paths() {
+    let owner = Principal::from_slice(&[1, 2, 3]);
+    let account1 = Account {
+        owner,
+        subaccount: None,
+    };
+    let account2 = Account {
+        owner,
+        subaccount: Some([7; 32]),
+    };
+    let block = Value::map(vec![
+        ("btype", Value::text("107feecol")),
+        ("ts", Value::Nat64(1_000)),
+        (
+            "tx",
+            Value::map(vec![
+                ("fee_collector", Value::from(account1)),
+                ("caller", Value::from(account2)),
+                ("op", Value::text("107set_fee_collector")),
+                ("empty", Value::Array(vec![])),
+            ]),
+        ),
+    ]);
+    let paths = |paths: &[&[&str]]| -> Vec<FieldPath> {
+        paths
+            .iter()
+            .map(|path| path.iter().map(|field| field.to_string()).collect())
+            .collect()
+    };
+
+    assert_eq!(
+        get_accounts_at_paths(
+            &block,
+            &paths(&[&["tx", "fee_collector"], &["tx", "caller"]])
+        ),
+        vec![account1, account2]
+    );
+    // Duplicated accounts are returned once.
+    assert_eq!(
+        get_accounts_at_paths(&block, &paths(&[&["tx", "caller"], &["tx", "caller"]])),
+        vec![account2]
+    );
+    // Missing fields and fields that are not accounts are ignored.
+    assert_eq!(
+        get_accounts_at_paths(
+            &block,
+            &paths(&[
+                &["tx", "from"],
+                &["tx", "op"],
+                &["tx", "empty"],
+                &["ts", "fee_collector"],
+                &["btype"],
+            ])
+        ),
+        vec![]
+    );
+    assert_eq!(
+        get_accounts_at_paths(&block, &State::default().account_field_paths()),
+        vec![]
+    );
+}
+
+#[test]
+fn test_has_icrc1_block_type() {
+    let block = |btype: Option<&str>, op: Option<&str>| {
+        let mut tx = vec![];
+        if let Some(op) = op {
+            tx.push(("op", Value::text(op)));
+        }
+        let mut fields = vec![("tx", Value::map(tx))];
+        if let Some(btype) = btype {
+            fields.push(("btype", Value::text(btype)));
+        }
+        Value::map(fields)
+    };
+    assert!(has_icrc1_block_type(&block(None, Some("xfer"))));
+    assert!(has_icrc1_block_type(&block(None, Some("approve"))));
+    assert!(has_icrc1_block_type(&block(Some("1mint"), None)));
+    assert!(has_icrc1_block_type(&block(
+        Some("2approve"),
+        Some("approve")
+    )));
+    assert!(!has_icrc1_block_type(&block(None, None)));
+    assert!(!has_icrc1_block_type(&block(
+        Some("107feecol"),
+        Some("107set_fee_collector")
+    )));
+    assert!(!has_icrc1_block_type(&Value::text("xfer")));
+}
+
+#[test]
+fn test_generic_block_to_flat_transaction() {
+    let transaction = generic_block_to_flat_transaction(Value::map(vec![
+        ("btype", Value::text("107feecol")),
+        ("ts", Value::Nat64(1_000)),
+        (
+            "tx",
+            Value::map(vec![("op", Value::text("107set_fee_collector"))]),
+        ),
+    ]));
+    assert_eq!(transaction.kind, "107feecol");
+    assert_eq!(transaction.timestamp, 1_000);
+    assert_eq!(transaction.mint, None);
+    assert_eq!(transaction.burn, None);
+    assert_eq!(transaction.transfer, None);
+    assert_eq!(transaction.approve, None);
+
+    // Without a block type the operation of the transaction is used.
+    let transaction = generic_block_to_flat_transaction(Value::map(vec![
+        ("ts", Value::Nat(Nat::from(2_000_u64))),
+        ("tx", Value::map(vec![("op", Value::text("custom"))])),
+    ]));
+    assert_eq!(transaction.kind, "custom");
+    assert_eq!(transaction.timestamp, 2_000);
+
+    let transaction = generic_block_to_flat_transaction(Value::Array(vec![]));
+    assert_eq!(transaction.kind, "unknown");
+    assert_eq!(transaction.timestamp, 0);
+}
+
 /// A helper function to access the scalar state.
 fn with_state<R>(f: impl FnOnce(&State) -> R) -> R {
     STATE.with(|cell| f(cell.borrow().get()))
@@ -259,17 +780,49 @@ fn with_account_data<R>(f: impl FnOnce(&mut AccountDataMap) -> R) -> R {
     ACCOUNT_DATA.with(|cell| f(&mut cell.borrow_mut()))
 }
 
+/// A helper function to access the account transaction metadata.
+fn with_account_transaction_metadata<R>(
+    f: impl FnOnce(&mut AccountTransactionMetadataMap) -> R,
+) -> R {
+    ACCOUNT_TRANSACTION_METADATA.with(|cell| f(&mut cell.borrow_mut()))
+}
+
+/// A helper function to access the account block ids by counterparty.
+fn with_account_counterparty_block_ids<R>(
+    f: impl FnOnce(&mut AccountCounterpartyBlockIdsMap) -> R,
+) -> R {
+    ACCOUNT_COUNTERPARTY_BLOCK_IDS.with(|cell| f(&mut cell.borrow_mut()))
+}
+
+/// A helper function to access the account block ids by amount.
+fn with_account_amount_block_ids<R>(f: impl FnOnce(&mut AccountAmountBlockIdsMap) -> R) -> R {
+    ACCOUNT_AMOUNT_BLOCK_IDS.with(|cell| f(&mut cell.borrow_mut()))
+}
+
+/// A helper function to access the block timestamps.
+fn with_block_timestamps<R>(f: impl FnOnce(&mut BlockTimestampsMap) -> R) -> R {
+    BLOCK_TIMESTAMPS.with(|cell| f(&mut cell.borrow_mut()))
+}
+
 /// A helper function that returns a decoded block stored in the
 /// block log at the given index or None if there is no block at that index.
-/// This function can trap if the index at the given block cannot be decoded
-/// because all blocks stored in the transaction log should be decodable
-/// (see [append_blocks]). If not then something is wrong with the log.
+/// This function traps if the block at the given index cannot be decoded
+/// as an ICRC-1 block, therefore it must only be used for blocks that are
+/// known to be ICRC-1 blocks (see [append_block]).
 fn get_decoded_block(block_index: BlockIndex64) -> Option<Block<Tokens>> {
     with_blocks(|blocks| blocks.get(block_index))
         .map(EncodedBlock::from)
         .map(|block| decode_encoded_block_or_trap(block_index, block))
 }
 
+/// A helper function that returns the ICRC-3 value of the block stored in the
+/// block log at the given index or None if there is no block at that index.
+fn get_generic_block(block_index: BlockIndex64) -> Option<GenericBlock> {
+    with_blocks(|blocks| blocks.get(block_index))
+        .map(EncodedBlock::from)
+        .map(|block| encoded_block_to_generic_block(&block))
+}
+
 /// A helper function to access the balance of an account.
 fn get_balance(account: Account) -> Tokens {
     with_account_data(|account_data| {
@@ -300,16 +853,21 @@ fn init(index_arg: Option<IndexArg>) {
     let InitArg {
         ledger_id,
         retrieve_blocks_from_ledger_interval_seconds,
+        account_field_paths,
     } = match index_arg {
         Some(IndexArg::Init(arg)) => arg,
         _ => trap("Index initialization must take in input an InitArg argument"),
     };
+    if let Some(account_field_paths) = &account_field_paths {
+        validate_account_field_paths(account_field_paths);
+    }
 
     // stable memory initialization
     mutate_state(|state| {
         state.ledger_id = ledger_id;
         state.retrieve_blocks_from_ledger_interval =
             retrieve_blocks_from_ledger_interval_seconds.map(Duration::from_secs);
+        state.account_field_paths = account_field_paths;
     });
 
     // set the first build_index to be called after init
@@ -318,6 +876,12 @@ fn init(index_arg: Option<IndexArg>) {
     }));
 }
 
+fn validate_account_field_paths(account_field_paths: &[FieldPath]) {
+    if account_field_paths.iter().any(|path| path.is_empty()) {
+        trap("The account field paths must not contain an empty path");
+    }
+}
+
 // The part of the legacy index (//rs/ledger_suite/icrc1/index) state
 // that reads the ledger_id. This struct is used to deserialize
 // the state of the legacy index during post_upgrade in case
@@ -354,7 +918,11 @@ fn post_upgrade(index_arg: Option<IndexArg>) {
             let UpgradeArg {
                 ledger_id,
                 retrieve_blocks_from_ledger_interval_seconds,
+                account_field_paths,
             } = upgrade;
+            if let Some(account_field_paths) = &account_field_paths {
+                validate_account_field_paths(account_field_paths);
+            }
 
             mutate_state(|state| {
                 if let Some(new_value) = ledger_id {
@@ -365,6 +933,10 @@ fn post_upgrade(index_arg: Option<IndexArg>) {
                     state.retrieve_blocks_from_ledger_interval =
                         Some(Duration::from_secs(new_value));
                 }
+
+                if let Some(new_value) = account_field_paths {
+                    state.account_field_paths = Some(new_value);
+                }
             });
         }
         Some(IndexArg::Init(..)) => trap("Index upgrade argument cannot be of variant Init"),
@@ -381,6 +953,26 @@ fn post_upgrade(index_arg: Option<IndexArg>) {
         let _maybe_first_key_value = account_data.first_key_value();
     });
 
+    // The blocks indexed before the transaction metadata was introduced
+    // have no metadata. Note that every block involves at least one account
+    // and therefore the metadata map is empty only if no block has metadata.
+    let num_blocks = with_blocks(|blocks| blocks.len());
+    let has_transaction_metadata =
+        with_account_transaction_metadata(|metadata| !metadata.is_empty());
+    mutate_state(|state| {
+        if state.transaction_metadata_backfill.is_none()
+            && !has_transaction_metadata
+            && num_blocks > 0
+        {
+            log!(
+                P1,
+                "Scheduling the backfill of the transaction metadata of {} blocks",
+                num_blocks
+            );
+            state.transaction_metadata_backfill = Some(0..num_blocks);
+        }
+    });
+
     // set the first build_index to be called after init
     set_build_index_timer(with_state(|state| {
         state.retrieve_blocks_from_ledger_interval()
@@ -570,6 +1162,7 @@ pub async fn build_index() -> Option<()> {
             state.is_build_index_running = false;
         });
     });
+    backfill_transaction_metadata();
     let num_indexed = match find_get_blocks_method().await {
         GetBlocksMethod::GetBlocks => fetch_blocks_via_get_blocks().await?,
         GetBlocksMethod::ICRC3GetBlocks => fetch_blocks_via_icrc3().await?,
@@ -678,6 +1271,52 @@ async fn fetch_blocks_via_icrc3() -> Option<u64> {
     }
 }
 
+/// Adds the transaction metadata and the timestamps of at most
+/// [MAX_BLOCKS_TO_BACKFILL_PER_ROUND] blocks that were indexed before the
+/// transaction metadata was introduced.
+fn backfill_transaction_metadata() {
+    let Some(backfill) = with_state(|state| state.transaction_metadata_backfill.clone()) else {
+        return;
+    };
+    let end = backfill.end.min(
+        backfill
+            .start
+            .saturating_add(MAX_BLOCKS_TO_BACKFILL_PER_ROUND),
+    );
+    measure_span(&PROFILING_DATA, "backfill_transaction_metadata", || {
+        for block_index in backfill.start..end {
+            let block = with_blocks(|blocks| blocks.get(block_index)).unwrap_or_else(|| {
+                trap(&format!(
+                    "Block {} not found in the block log while backfilling the transaction metadata",
+                    block_index
+                ))
+            });
+            let block = EncodedBlock::from(block);
+            match Block::<Tokens>::decode(block.clone()) {
+                Ok(decoded_block) => {
+                    index_block_timestamp(block_index, Some(decoded_block.timestamp));
+                    index_transaction_metadata(block_index, &decoded_block);
+                }
+                // Blocks that are not ICRC-1 blocks have no transaction metadata.
+                Err(_) => index_block_timestamp(
+                    block_index,
+                    get_generic_block_timestamp(&encoded_block_to_generic_block(&block)),
+                ),
+            }
+        }
+    });
+    log!(
+        P1,
+        "[backfill_transaction_metadata]: backfilled blocks {}..{} out of {:?}",
+        backfill.start,
+        end,
+        backfill
+    );
+    mutate_state(|state| {
+        state.transaction_metadata_backfill = (end < backfill.end).then_some(end..backfill.end);
+    });
+}
+
 fn set_build_index_timer(after: Duration) -> TimerId {
     ic_cdk_timers::set_timer_interval(after, || {
         ic_cdk::spawn(async {
@@ -688,6 +1327,8 @@ fn set_build_index_timer(after: Duration) -> TimerId {
 
 fn append_block(block_index: BlockIndex64, block: GenericBlock) {
     measure_span(&PROFILING_DATA, "append_blocks", move || {
+        let is_icrc1_block = has_icrc1_block_type(&block);
+        let timestamp = get_generic_block_timestamp(&block);
         let block = generic_block_to_encoded_block_or_trap(block_index, block);
 
         // append the encoded block to the block log
@@ -696,8 +1337,27 @@ fn append_block(block_index: BlockIndex64, block: GenericBlock) {
                 .append(&block.0)
                 .unwrap_or_else(|_| trap("no space left"))
         });
+        index_block_timestamp(block_index, timestamp);
 
-        let decoded_block = decode_encoded_block_or_trap(block_index, block);
+        let decoded_block = match Block::<Tokens>::decode(block) {
+            Ok(decoded_block) => decoded_block,
+            Err(err) if !is_icrc1_block => {
+                log!(
+                    P1,
+                    "[append_block]: block {} is not an ICRC-1 block, only its accounts are indexed. Error: {}",
+                    block_index,
+                    err
+                );
+                index_generic_block(block_index);
+                return;
+            }
+            // A block of a known type that cannot be decoded, e.g., because its
+            // amount doesn't fit the token type of the index, must not be skipped.
+            Err(err) => trap(&format!(
+                "Unable to decode encoded block at index {}. Error: {}",
+                block_index, err
+            )),
+        };
 
         // add the block idx to the indices
         with_account_block_ids(|account_block_ids| {
@@ -705,6 +1365,7 @@ fn append_block(block_index: BlockIndex64, block: GenericBlock) {
                 account_block_ids.insert(account_block_ids_key(account, block_index), ());
             }
         });
+        index_transaction_metadata(block_index, &decoded_block);
 
         // add the block to the fee_collector if one is set
         index_fee_collector(block_index, &decoded_block);
@@ -714,6 +1375,104 @@ fn append_block(block_index: BlockIndex64, block: GenericBlock) {
     });
 }
 
+/// Returns whether the block has the type or the operation of an ICRC-1 block,
+/// in which case it must be decodable as an ICRC-1 block.
+fn has_icrc1_block_type(block: &GenericBlock) -> bool {
+    const ICRC1_BLOCK_TYPES: [&str; 5] = ["1burn", "1mint", "1xfer", "2xfer", "2approve"];
+    const ICRC1_OPERATIONS: [&str; 4] = ["burn", "mint", "xfer", "approve"];
+    let Value::Map(map) = block else {
+        return false;
+    };
+    let btype = match map.get("btype") {
+        Some(Value::Text(btype)) => Some(btype.as_str()),
+        _ => None,
+    };
+    let op = match map.get("tx") {
+        Some(Value::Map(tx)) => match tx.get("op") {
+            Some(Value::Text(op)) => Some(op.as_str()),
+            _ => None,
+        },
+        _ => None,
+    };
+    btype.is_some_and(|btype| ICRC1_BLOCK_TYPES.contains(&btype))
+        || op.is_some_and(|op| ICRC1_OPERATIONS.contains(&op))
+}
+
+/// Indexes a block that cannot be decoded as an ICRC-1 block. The block
+/// is added to the transactions of the accounts found at the configured
+/// field paths but its effect on the balances is not tracked.
+fn index_generic_block(block_index: BlockIndex64) {
+    let block = get_generic_block(block_index).unwrap_or_else(|| {
+        trap(&format!(
+            "Block {} not found in the block log right after appending it",
+            block_index
+        ))
+    });
+    let accounts = get_accounts_at_paths(&block, &with_state(|state| state.account_field_paths()));
+    with_account_block_ids(|account_block_ids| {
+        for account in accounts {
+            account_block_ids.insert(account_block_ids_key(account, block_index), ());
+        }
+    });
+    mutate_state(|state| state.num_generic_blocks += 1);
+}
+
+/// Returns the accounts found at the given field paths of the given block.
+/// The paths that don't exist or don't contain an account are ignored.
+fn get_accounts_at_paths(block: &GenericBlock, paths: &[FieldPath]) -> Vec<Account> {
+    let mut accounts = vec![];
+    for path in paths {
+        let field = path.iter().try_fold(block, |value, field| match value {
+            Value::Map(map) => map.get(field),
+            _ => None,
+        });
+        match field {
+            // `Account::try_from` expects a non-empty array.
+            Some(value @ Value::Array(parts)) if !parts.is_empty() => {
+                if let Ok(account) = Account::try_from(value.clone()) {
+                    if !accounts.contains(&account) {
+                        accounts.push(account);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    accounts
+}
+
+/// Returns the timestamp of the given block or None if it has no valid timestamp.
+fn get_generic_block_timestamp(block: &GenericBlock) -> Option<u64> {
+    match block {
+        Value::Map(map) => map.get("ts").cloned()?.as_nat().ok()?.0.to_u64(),
+        _ => None,
+    }
+}
+
+/// Converts a block that cannot be decoded as an ICRC-1 block into a
+/// transaction without operation whose kind is the type of the block.
+fn generic_block_to_flat_transaction(block: GenericBlock) -> Transaction {
+    let timestamp = get_generic_block_timestamp(&block).unwrap_or_default();
+    let kind = match &block {
+        Value::Map(map) => map
+            .get("btype")
+            .or_else(|| match map.get("tx") {
+                Some(Value::Map(tx)) => tx.get("op"),
+                _ => None,
+            })
+            .and_then(|kind| kind.clone().as_text().ok()),
+        _ => None,
+    };
+    Transaction {
+        kind: kind.unwrap_or_else(|| "unknown".to_string()),
+        mint: None,
+        burn: None,
+        transfer: None,
+        approve: None,
+        timestamp,
+    }
+}
+
 fn append_blocks(new_blocks: Vec<GenericBlock>) {
     // the index of the next block that we
     // are going to append
@@ -747,6 +1506,28 @@ fn append_icrc3_blocks(new_blocks: Vec<BlockWithId>) -> Option<()> {
     Some(())
 }
 
+fn index_block_timestamp(block_index: BlockIndex64, timestamp: Option<u64>) {
+    if let Some(timestamp) = timestamp {
+        with_block_timestamps(|timestamps| timestamps.insert((timestamp, block_index), ()));
+    }
+}
+
+fn index_transaction_metadata(block_index: BlockIndex64, block: &Block<Tokens>) {
+    for account in get_accounts(block) {
+        let (account_hash, block_id) = account_block_ids_key(account, block_index);
+        let metadata = TransactionMetadata::new(account, block);
+        if let Some(counterparty) = metadata.counterparty {
+            with_account_counterparty_block_ids(|block_ids| {
+                block_ids.insert((account_hash, (counterparty, block_id)), ())
+            });
+        }
+        with_account_amount_block_ids(|block_ids| {
+            block_ids.insert((account_hash, (metadata.amount, block_id)), ())
+        });
+        with_account_transaction_metadata(|m| m.insert((account_hash, block_id), metadata));
+    }
+}
+
 fn index_fee_collector(block_index: BlockIndex64, block: &Block<Tokens>) {
     if let Some(fee_collector) = get_fee_collector(block_index, block) {
         mutate_state(|s| {
@@ -972,16 +1753,22 @@ fn get_account_transactions(arg: GetAccountTransactionsArgs) -> GetAccountTransa
         .map_or(u64::MAX, |n| n.0.to_u64().expect("start must be a u64!"));
     let key = account_block_ids_key(arg.account, start);
     let mut transactions = vec![];
-    let indices = with_account_block_ids(|account_block_ids| {
-        account_block_ids
-            .range(key..)
-            // old txs of the requested account and skip the start index
-            .take_while(|(k, _)| k.0 == key.0)
-            .filter(|(k, _)| k.1 .0 < start)
-            .take(length)
-            .map(|(k, _)| k.1 .0)
-            .collect::<Vec<BlockIndex64>>()
-    });
+    let (indices, scan_cursor) = match &arg.filter {
+        None => (
+            with_account_block_ids(|account_block_ids| {
+                account_block_ids
+                    .range(key..)
+                    // old txs of the requested account and skip the start index
+                    .take_while(|(k, _)| k.0 == key.0)
+                    .filter(|(k, _)| k.1 .0 < start)
+                    .take(length)
+                    .map(|(k, _)| k.1 .0)
+                    .collect::<Vec<BlockIndex64>>()
+            }),
+            None,
+        ),
+        Some(filter) => get_filtered_account_block_ids(arg.account, start, length, filter)?,
+    };
     for id in indices {
         let block = with_blocks(|blocks| {
             blocks.get(id).unwrap_or_else(|| {
@@ -991,7 +1778,7 @@ fn get_account_transactions(arg: GetAccountTransactionsArgs) -> GetAccountTransa
                 ))
             })
         });
-        let transaction = encoded_block_bytes_to_flat_transaction(id, block);
+        let transaction = encoded_block_bytes_to_flat_transaction(block);
         let transaction_with_idx = TransactionWithId {
             id: id.into(),
             transaction,
@@ -1004,20 +1791,246 @@ fn get_account_transactions(arg: GetAccountTransactionsArgs) -> GetAccountTransa
         balance,
         transactions,
         oldest_tx_id,
+        scan_cursor: scan_cursor.map(|block_index| block_index.into()),
     })
 }
 
-fn encoded_block_bytes_to_flat_transaction(
-    block_index: BlockIndex64,
-    block: Vec<u8>,
-) -> Transaction {
-    let block = Block::<Tokens>::decode(EncodedBlock::from(block)).unwrap_or_else(|e| {
-        trap(&format!(
-            "Unable to decode encoded block at index {}. Error: {}",
-            block_index, e
-        ))
+/// Returns the ids of at most `length` blocks older than `start` that
+/// involve the given account and match the given filter, newest first.
+/// The candidate transactions are taken from the index that matches the
+/// filter best: the transactions with the counterparty of the filter, or
+/// else the transactions in the amount range of the filter, or else all the
+/// transactions of the account in the time range of the filter.
+/// At most [MAX_TRANSACTIONS_TO_SCAN_PER_FILTER] candidates are scanned; if
+/// the scan stops because of this limit, then the id of the last scanned
+/// block is returned as well to resume the scan.
+fn get_filtered_account_block_ids(
+    account: Account,
+    start: BlockIndex64,
+    length: usize,
+    filter: &TransactionFilter,
+) -> Result<(Vec<BlockIndex64>, Option<BlockIndex64>), GetAccountTransactionsError> {
+    if with_state(|state| state.transaction_metadata_backfill.is_some()) {
+        return Err(GetAccountTransactionsError {
+            message: "Transaction filters are not available until the index has finished indexing the transactions metadata. Please retry later.".to_string(),
+        });
+    }
+    // The blocks are ordered by timestamp, therefore the time range
+    // corresponds to a range of block ids.
+    let end = filter.end_timestamp.map_or(start, |end_timestamp| {
+        start.min(first_block_at_or_after(end_timestamp))
     });
-    block.into()
+    let low = filter.start_timestamp.map_or(0, first_block_at_or_after);
+    if low >= end {
+        return Ok((vec![], None));
+    }
+    let account_hash = account_sha256(account);
+    let counterparty = filter.counterparty.map(account_sha256);
+    let get_metadata = |block_index| {
+        with_account_transaction_metadata(|metadata| {
+            metadata
+                .get(&(account_hash, Reverse(block_index)))
+                .unwrap_or_else(|| {
+                    trap(&format!(
+                        "Transaction metadata of block {} not found, the account transaction indexes are corrupted!",
+                        block_index
+                    ))
+                })
+        })
+    };
+    let scan = |candidates: &mut dyn Iterator<Item = BlockIndex64>| {
+        scan_transaction_metadata(
+            candidates.map(|block_index| (block_index, get_metadata(block_index))),
+            filter,
+            counterparty.as_ref(),
+            length,
+            MAX_TRANSACTIONS_TO_SCAN_PER_FILTER,
+        )
+    };
+
+    if let Some(counterparty) = counterparty {
+        return Ok(with_account_counterparty_block_ids(|block_ids| {
+            scan(
+                &mut block_ids
+                    .range((account_hash, (counterparty, Reverse(end - 1)))..)
+                    .take_while(|(k, _)| k.0 == account_hash && k.1 .0 == counterparty)
+                    .map(|(k, _)| k.1 .1 .0)
+                    .take_while(|block_index| *block_index >= low),
+            )
+        }));
+    }
+    if let Some(amounts) = amount_range(filter) {
+        let result = with_account_amount_block_ids(|block_ids| {
+            AmountBlockIds::new(block_ids, account_hash, amounts, low..end)
+                .map(|mut candidates| scan(&mut candidates))
+        });
+        if let Some(result) = result {
+            return Ok(result);
+        }
+    }
+    let key = account_block_ids_key(account, end - 1);
+    Ok(with_account_transaction_metadata(|metadata| {
+        scan_transaction_metadata(
+            metadata
+                .range(key..)
+                // old txs of the requested account down to the start of the time range
+                .take_while(|(k, _)| k.0 == key.0 && k.1 .0 >= low)
+                .map(|(k, m)| (k.1 .0, m)),
+            filter,
+            counterparty.as_ref(),
+            length,
+            MAX_TRANSACTIONS_TO_SCAN_PER_FILTER,
+        )
+    }))
+}
+
+/// Returns the range of amounts of the given filter as tokens, or None if
+/// the filter does not restrict the amount.
+fn amount_range(filter: &TransactionFilter) -> Option<RangeInclusive<Tokens>> {
+    if filter.min_amount.is_none() && filter.max_amount.is_none() {
+        return None;
+    }
+    // Amounts that do not fit the token type are clamped, the amounts of the
+    // transactions are checked against the filter again when scanning them.
+    let to_tokens = |amount: &Nat| Tokens::try_from(amount.clone()).unwrap_or(Tokens::max_value());
+    let min_amount = filter.min_amount.as_ref().map_or(Tokens::zero(), to_tokens);
+    let max_amount = filter
+        .max_amount
+        .as_ref()
+        .map_or(Tokens::max_value(), to_tokens);
+    Some(min_amount..=max_amount)
+}
+
+/// Iterates over the ids of the blocks of an account that have an amount in
+/// the given range and an id in the given range, newest first, by merging
+/// the block ids of each amount in the account amount block ids map.
+struct AmountBlockIds<'a> {
+    block_ids: &'a AccountAmountBlockIdsMap,
+    account_hash: [u8; Sha256::DIGEST_LEN],
+    low: BlockIndex64,
+    /// The newest block id not returned yet of each amount.
+    newest: BinaryHeap<(BlockIndex64, Tokens)>,
+}
+
+impl<'a> AmountBlockIds<'a> {
+    /// Returns None if the account has transactions with more than
+    /// [MAX_AMOUNTS_TO_MERGE_PER_FILTER] distinct amounts in the given range.
+    fn new(
+        block_ids: &'a AccountAmountBlockIdsMap,
+        account_hash: [u8; Sha256::DIGEST_LEN],
+        amounts: RangeInclusive<Tokens>,
+        ids: Range<BlockIndex64>,
+    ) -> Option<Self> {
+        let mut this = Self {
+            block_ids,
+            account_hash,
+            low: ids.start,
+            newest: BinaryHeap::new(),
+        };
+        if ids.is_empty() {
+            return Some(this);
+        }
+        let mut lower_bound = Included((account_hash, (*amounts.start(), Reverse(u64::MAX))));
+        for _ in 0..=MAX_AMOUNTS_TO_MERGE_PER_FILTER {
+            let Some(((_, (amount, _)), _)) = block_ids
+                .range((lower_bound, Unbounded))
+                .next()
+                .filter(|(k, _)| k.0 == account_hash && k.1 .0 <= *amounts.end())
+            else {
+                return Some(this);
+            };
+            this.push_newest(amount, ids.end - 1);
+            // skip the other block ids of this amount
+            lower_bound = Excluded((account_hash, (amount, Reverse(0))));
+        }
+        None
+    }
+
+    /// Adds the newest block id of the given amount that is lower than or
+    /// equal to `max_block_index`, if any.
+    fn push_newest(&mut self, amount: Tokens, max_block_index: BlockIndex64) {
+        let key = (self.account_hash, (amount, Reverse(max_block_index)));
+        let newest = self
+            .block_ids
+            .range(key..)
+            .next()
+            .filter(|(k, _)| k.0 == self.account_hash && k.1 .0 == amount && k.1 .1 .0 >= self.low)
+            .map(|(k, _)| k.1 .1 .0);
+        if let Some(block_index) = newest {
+            self.newest.push((block_index, amount));
+        }
+    }
+}
+
+impl Iterator for AmountBlockIds<'_> {
+    type Item = BlockIndex64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (block_index, amount) = self.newest.pop()?;
+        if let Some(older_block_index) = block_index.checked_sub(1) {
+            self.push_newest(amount, older_block_index);
+        }
+        Some(block_index)
+    }
+}
+
+/// Returns the ids of at most `length` blocks of the given transaction
+/// metadata, ordered newest first, that match the given filter.
+/// The scan stops after `scan_budget` transactions, in which case the id
+/// of the last scanned block is returned as well.
+fn scan_transaction_metadata(
+    metadata: impl Iterator<Item = (BlockIndex64, TransactionMetadata)>,
+    filter: &TransactionFilter,
+    counterparty: Option<&[u8; Sha256::DIGEST_LEN]>,
+    length: usize,
+    scan_budget: usize,
+) -> (Vec<BlockIndex64>, Option<BlockIndex64>) {
+    let mut block_ids = vec![];
+    let mut last_scanned = None;
+    for (num_scanned, (block_index, m)) in metadata.enumerate() {
+        if block_ids.len() >= length {
+            break;
+        }
+        if num_scanned >= scan_budget {
+            return (block_ids, last_scanned);
+        }
+        // older txs are before the start of the time range
+        if filter
+            .start_timestamp
+            .is_some_and(|start_timestamp| m.timestamp < start_timestamp)
+        {
+            break;
+        }
+        if m.matches(filter, counterparty) {
+            block_ids.push(block_index);
+        }
+        last_scanned = Some(block_index);
+    }
+    (block_ids, None)
+}
+
+/// Returns the id of the first block with a timestamp greater than
+/// or equal to the given one, or the number of blocks if there is none.
+/// A block without timestamp is considered to have the timestamp of
+/// the next block with a timestamp, so that the timestamps stay sorted.
+fn first_block_at_or_after(timestamp: u64) -> BlockIndex64 {
+    // The blocks are ordered by timestamp, therefore the first block at or after
+    // the timestamp follows the last block with a timestamp before it.
+    with_block_timestamps(|timestamps| {
+        timestamps
+            .iter_upper_bound(&(timestamp, 0))
+            .next()
+            .map_or(0, |((_, block_index), _)| block_index + 1)
+    })
+}
+
+fn encoded_block_bytes_to_flat_transaction(block: Vec<u8>) -> Transaction {
+    let block = EncodedBlock::from(block);
+    match Block::<Tokens>::decode(block.clone()) {
+        Ok(decoded_block) => decoded_block.into(),
+        // The blocks that are not ICRC-1 blocks are indexed by account only.
+        Err(_) => generic_block_to_flat_transaction(encoded_block_to_generic_block(&block)),
+    }
 }
 
 fn get_oldest_tx_id(account: Account) -> Option<BlockIndex64> {
@@ -1146,6 +2159,11 @@ pub fn encode_metrics(w: &mut ic_metrics_encoder::MetricsEncoder<Vec<u8>>) -> st
         with_blocks(|blocks| blocks.len()) as f64,
         "Total number of blocks stored in the stable memory.",
     )?;
+    w.encode_gauge(
+        "index_number_of_generic_blocks",
+        with_state(|state| state.num_generic_blocks) as f64,
+        "Total number of blocks that are not ICRC-1 blocks and whose effect on balances is not tracked.",
+    )?;
     w.encode_gauge(
         "index_last_wait_time",
         with_state(|state| state.last_wait_time)
@@ -58,6 +58,7 @@ fn index_install_and_upgrade_test(index_install_wasm: Vec<u8>, index_upgrade_was
     let index_init_arg = IndexArg::Init(IndexInitArg {
         ledger_id: Principal::from(ledger_id),
         retrieve_blocks_from_ledger_interval_seconds: None,
+        account_field_paths: None,
     });
     let index_id = env
         .install_canister(index_install_wasm, Encode!(&index_init_arg).unwrap(), None)
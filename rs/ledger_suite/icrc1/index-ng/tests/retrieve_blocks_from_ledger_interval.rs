@@ -46,6 +46,7 @@ fn install_and_upgrade(
     let args = IndexArg::Init(ic_icrc1_index_ng::InitArg {
         ledger_id: Principal::from(ledger_id),
         retrieve_blocks_from_ledger_interval_seconds: install_interval,
+        account_field_paths: None,
     });
     let index_id = env.install_canister_with_cycles(
         index_ng_wasm(),
@@ -59,6 +60,7 @@ fn install_and_upgrade(
     let upgrade_arg = IndexArg::Upgrade(UpgradeArg {
         ledger_id: None,
         retrieve_blocks_from_ledger_interval_seconds: upgrade_interval,
+        account_field_paths: None,
     });
     env.upgrade_canister(index_id, index_ng_wasm(), Encode!(&upgrade_arg).unwrap())?;
 
@@ -202,6 +204,7 @@ fn should_sync_according_to_interval() {
                 let args = IndexArg::Init(InitArg {
                     ledger_id: Principal::from(ledger_id),
                     retrieve_blocks_from_ledger_interval_seconds: install_interval,
+                    account_field_paths: None,
                 });
                 let index_id = env.install_canister_with_cycles(
                     index_ng_wasm(),
@@ -227,6 +230,7 @@ fn should_sync_according_to_interval() {
                 let upgrade_arg = IndexArg::Upgrade(UpgradeArg {
                     ledger_id: None,
                     retrieve_blocks_from_ledger_interval_seconds: upgrade_interval,
+                    account_field_paths: None,
                 });
                 env.upgrade_canister(index_id, index_ng_wasm(), Encode!(&upgrade_arg).unwrap())?;
 
@@ -419,6 +423,7 @@ fn should_consume_expected_amount_of_cycles() {
             InitArg {
                 ledger_id: Principal::from(ledger_id),
                 retrieve_blocks_from_ledger_interval_seconds: initial_interval,
+                account_field_paths: None,
             },
         );
 
@@ -428,6 +433,7 @@ fn should_consume_expected_amount_of_cycles() {
         let upgrade_arg = IndexArg::Upgrade(UpgradeArg {
             ledger_id: None,
             retrieve_blocks_from_ledger_interval_seconds: upgrade_interval,
+            account_field_paths: None,
         });
         env.upgrade_canister(index_id, index_ng_wasm(), Encode!(&upgrade_arg).unwrap())
             .unwrap();
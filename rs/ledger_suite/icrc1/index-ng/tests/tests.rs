@@ -9,7 +9,8 @@ use ic_base_types::{CanisterId, PrincipalId};
 use ic_icrc1_index_ng::{
     FeeCollectorRanges, GetAccountTransactionsArgs, GetAccountTransactionsResponse,
     GetAccountTransactionsResult, GetBlocksResponse, IndexArg, InitArg as IndexInitArg,
-    ListSubaccountsArgs, TransactionWithId, DEFAULT_MAX_BLOCKS_PER_RESPONSE,
+    ListSubaccountsArgs, TransactionDirection, TransactionFilter, TransactionKind,
+    TransactionWithId, DEFAULT_MAX_BLOCKS_PER_RESPONSE,
 };
 use ic_icrc1_ledger::{ChangeFeeCollector, LedgerArgument, UpgradeArgs as LedgerUpgradeArgs};
 use ic_icrc1_test_utils::{
@@ -56,6 +57,8 @@ fn upgrade_ledger(
         max_memo_length: None,
         feature_flags: None,
         change_archive_options: None,
+        max_transfers_per_batch: None,
+        max_balances_per_batch: None,
     }));
     env.upgrade_canister(ledger_id, ledger_wasm(), Encode!(&args).unwrap())
         .unwrap()
@@ -65,6 +68,7 @@ fn index_init_arg_without_interval(ledger_id: CanisterId) -> IndexInitArg {
     IndexInitArg {
         ledger_id: Principal::from(ledger_id),
         retrieve_blocks_from_ledger_interval_seconds: None,
+        account_field_paths: None,
     }
 }
 
@@ -243,11 +247,23 @@ fn get_account_transactions(
     account: Account,
     start: Option<u64>,
     max_results: u64,
+) -> GetAccountTransactionsResponse {
+    get_account_transactions_with_filter(env, index_id, account, start, max_results, None)
+}
+
+fn get_account_transactions_with_filter(
+    env: &StateMachine,
+    index_id: CanisterId,
+    account: Account,
+    start: Option<u64>,
+    max_results: u64,
+    filter: Option<TransactionFilter>,
 ) -> GetAccountTransactionsResponse {
     let req = GetAccountTransactionsArgs {
         account,
         start: start.map(|n| n.into()),
         max_results: max_results.into(),
+        filter,
     };
     let req = Encode!(&req).expect("Failed to encode GetAccountTransactionsArgs");
     let res = env
@@ -746,6 +762,209 @@ fn test_get_account_transactions_pagination() {
     }
 }
 
+#[test]
+fn test_get_account_transactions_with_filters() {
+    let env = &StateMachine::new();
+    let minter = minter_identity().sender().unwrap();
+    let ledger_id = install_ledger(
+        env,
+        vec![(account(1, 0), 1_000_000_000_000)],
+        default_archive_options(),
+        None,
+        minter,
+    );
+    let index_id = install_index_ng(env, index_init_arg_without_interval(ledger_id));
+
+    // Block 0 is the mint to (1, 0) at ledger init.
+    // Block 1: transfer from (1, 0) to (2, 0).
+    transfer(env, ledger_id, account(1, 0), account(2, 0), 1_000_000);
+    env.advance_time(Duration::from_secs(60));
+    // Block 2: approve from (1, 0) to spender (3, 0).
+    approve(env, ledger_id, account(1, 0), account(3, 0), 5_000_000);
+    // Block 3: transfer from (1, 0) to (3, 0).
+    transfer(env, ledger_id, account(1, 0), account(3, 0), 2_000_000);
+    env.advance_time(Duration::from_secs(60));
+    // Block 4: transfer from (2, 0) to (1, 0).
+    transfer(env, ledger_id, account(2, 0), account(1, 0), 500_000);
+    // Block 5: burn from (1, 0).
+    let minting_account = Account {
+        owner: minter,
+        subaccount: None,
+    };
+    transfer(env, ledger_id, account(1, 0), minting_account, 3_000_000);
+    wait_until_sync_is_completed(env, index_id, ledger_id);
+
+    let all_txs =
+        get_account_transactions(env, index_id, account(1, 0), None, u64::MAX).transactions;
+    assert_eq!(
+        all_txs.iter().map(|tx| tx.id.clone()).collect::<Vec<_>>(),
+        vec![5u8, 4, 3, 2, 1, 0]
+            .into_iter()
+            .map(Nat::from)
+            .collect::<Vec<_>>()
+    );
+    let timestamp_of = |id: usize| all_txs[5 - id].transaction.timestamp;
+
+    let get_filtered_ids = |start: Option<u64>, max_results: u64, filter: TransactionFilter| {
+        let res = get_account_transactions_with_filter(
+            env,
+            index_id,
+            account(1, 0),
+            start,
+            max_results,
+            Some(filter),
+        );
+        // The filters affect neither the balance nor the oldest_tx_id.
+        assert_eq!(
+            res.balance,
+            Nat::from(icrc1_balance_of(env, ledger_id, account(1, 0)))
+        );
+        assert_eq!(res.oldest_tx_id, Some(0u8.into()));
+        assert_eq!(res.scan_cursor, None);
+        res.transactions
+            .into_iter()
+            .map(|tx| tx.id.0.to_u64().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    // An empty filter returns all the transactions.
+    assert_eq!(
+        get_filtered_ids(None, u64::MAX, TransactionFilter::default()),
+        vec![5, 4, 3, 2, 1, 0]
+    );
+
+    // Filter by kinds.
+    let kinds_filter = |kinds: Vec<TransactionKind>| TransactionFilter {
+        kinds: Some(kinds),
+        ..Default::default()
+    };
+    assert_eq!(
+        get_filtered_ids(
+            None,
+            u64::MAX,
+            kinds_filter(vec![TransactionKind::Transfer])
+        ),
+        vec![4, 3, 1]
+    );
+    assert_eq!(
+        get_filtered_ids(
+            None,
+            u64::MAX,
+            kinds_filter(vec![TransactionKind::Mint, TransactionKind::Burn])
+        ),
+        vec![5, 0]
+    );
+    assert_eq!(
+        get_filtered_ids(None, u64::MAX, kinds_filter(vec![TransactionKind::Approve])),
+        vec![2]
+    );
+    assert_eq!(
+        get_filtered_ids(None, u64::MAX, kinds_filter(vec![])),
+        Vec::<u64>::new()
+    );
+
+    // Filter by counterparty.
+    let counterparty_filter = |counterparty: Account| TransactionFilter {
+        counterparty: Some(counterparty),
+        ..Default::default()
+    };
+    assert_eq!(
+        get_filtered_ids(None, u64::MAX, counterparty_filter(account(2, 0))),
+        vec![4, 1]
+    );
+    assert_eq!(
+        get_filtered_ids(None, u64::MAX, counterparty_filter(account(3, 0))),
+        vec![3, 2]
+    );
+    assert_eq!(
+        get_filtered_ids(None, u64::MAX, counterparty_filter(account(4, 0))),
+        Vec::<u64>::new()
+    );
+
+    // Filter by direction.
+    let direction_filter = |direction: TransactionDirection| TransactionFilter {
+        direction: Some(direction),
+        ..Default::default()
+    };
+    assert_eq!(
+        get_filtered_ids(
+            None,
+            u64::MAX,
+            direction_filter(TransactionDirection::Incoming)
+        ),
+        vec![4, 0]
+    );
+    assert_eq!(
+        get_filtered_ids(
+            None,
+            u64::MAX,
+            direction_filter(TransactionDirection::Outgoing)
+        ),
+        vec![5, 3, 2, 1]
+    );
+
+    // Filter by amount.
+    let amount_filter = |min_amount: Option<u64>, max_amount: Option<u64>| TransactionFilter {
+        min_amount: min_amount.map(Nat::from),
+        max_amount: max_amount.map(Nat::from),
+        ..Default::default()
+    };
+    assert_eq!(
+        get_filtered_ids(None, u64::MAX, amount_filter(Some(2_000_000), None)),
+        vec![5, 3, 2, 0]
+    );
+    assert_eq!(
+        get_filtered_ids(None, u64::MAX, amount_filter(None, Some(1_000_000))),
+        vec![4, 1]
+    );
+    assert_eq!(
+        get_filtered_ids(
+            None,
+            u64::MAX,
+            amount_filter(Some(1_000_000), Some(3_000_000))
+        ),
+        vec![5, 3, 1]
+    );
+
+    // Filter by time range.
+    let time_filter =
+        |start_timestamp: Option<u64>, end_timestamp: Option<u64>| TransactionFilter {
+            start_timestamp,
+            end_timestamp,
+            ..Default::default()
+        };
+    assert_eq!(
+        get_filtered_ids(
+            None,
+            u64::MAX,
+            time_filter(Some(timestamp_of(2)), Some(timestamp_of(4)))
+        ),
+        vec![3, 2]
+    );
+    assert_eq!(
+        get_filtered_ids(None, u64::MAX, time_filter(Some(timestamp_of(4)), None)),
+        vec![5, 4]
+    );
+    assert_eq!(
+        get_filtered_ids(None, u64::MAX, time_filter(None, Some(timestamp_of(2)))),
+        vec![1, 0]
+    );
+
+    // Filters combine with each other and with start and max_results.
+    let filter = TransactionFilter {
+        kinds: Some(vec![TransactionKind::Transfer]),
+        min_amount: Some(Nat::from(1_000_000_u64)),
+        ..Default::default()
+    };
+    assert_eq!(get_filtered_ids(None, u64::MAX, filter.clone()), vec![3, 1]);
+    assert_eq!(get_filtered_ids(None, 1, filter.clone()), vec![3]);
+    assert_eq!(get_filtered_ids(Some(3), 1, filter), vec![1]);
+    assert_eq!(
+        get_filtered_ids(Some(3), u64::MAX, time_filter(None, Some(timestamp_of(4)))),
+        vec![2, 1, 0]
+    );
+}
+
 #[test]
 fn test_icrc1_balance_of() {
     // 1 case only because the test is expensive to run.
@@ -790,6 +1009,77 @@ fn test_icrc1_balance_of() {
         .unwrap();
 }
 
+// The Ledger without ICRC-3 predates ICRC-4.
+#[cfg(not(feature = "icrc3_disabled"))]
+#[test]
+fn test_icrc4_transfer_batch() {
+    use icrc_ledger_types::icrc4::batch::TransferBatchResults;
+
+    let env = &StateMachine::new();
+    let minter = minter_identity().sender().unwrap();
+    let sender = account(1, 0);
+    let ledger_id = install_ledger(
+        env,
+        vec![(sender, 1_000_000)],
+        default_archive_options(),
+        None,
+        minter,
+    );
+    let index_id = install_index_ng(env, index_init_arg_without_interval(ledger_id));
+
+    let receivers: Vec<_> = (2..5).map(|owner| account(owner, 0)).collect();
+    let args: Vec<_> = receivers
+        .iter()
+        .map(|to| TransferArg {
+            from_subaccount: sender.subaccount,
+            to: *to,
+            amount: 10_000_u64.into(),
+            created_at_time: None,
+            fee: None,
+            memo: None,
+        })
+        .collect();
+    let res = env
+        .execute_ingress_as(
+            sender.owner.into(),
+            ledger_id,
+            "icrc4_transfer_batch",
+            Encode!(&args).unwrap(),
+        )
+        .expect("Failed to execute icrc4_transfer_batch")
+        .bytes();
+    let results = Decode!(&res, TransferBatchResults)
+        .expect("Failed to decode icrc4_transfer_batch response");
+    assert_eq!(
+        results,
+        (1..=3_u64)
+            .map(|i| Some(Ok(Nat::from(i))))
+            .collect::<Vec<_>>()
+    );
+
+    wait_until_sync_is_completed(env, index_id, ledger_id);
+    assert_ledger_index_parity(env, ledger_id, index_id);
+    for account in receivers.iter().chain(std::iter::once(&sender)) {
+        assert_eq!(
+            icrc1_balance_of(env, ledger_id, *account),
+            icrc1_balance_of(env, index_id, *account)
+        );
+    }
+    for receiver in receivers {
+        let txs = get_account_transactions(env, index_id, receiver, None, u64::MAX);
+        assert_eq!(txs.transactions.len(), 1);
+        assert_eq!(
+            txs.transactions[0]
+                .transaction
+                .transfer
+                .as_ref()
+                .unwrap()
+                .to,
+            receiver
+        );
+    }
+}
+
 #[test]
 fn test_list_subaccounts() {
     // For this test, we add minting operations for some principals:
@@ -1230,6 +1520,7 @@ mod metrics {
         Some(IndexArg::Init(InitArg {
             ledger_id,
             retrieve_blocks_from_ledger_interval_seconds: None,
+            account_field_paths: None,
         }))
     }
 }
@@ -0,0 +1,246 @@
+use candid::{Decode, Encode, Nat, Principal};
+use ic_base_types::{CanisterId, PrincipalId};
+use ic_icrc1::blocks::encoded_block_to_generic_block;
+use ic_icrc1::{Operation, Transaction};
+use ic_icrc1_index_ng::{
+    GetAccountTransactionsArgs, GetAccountTransactionsResult, IndexArg, InitArg as IndexInitArg,
+    Status, TransactionFilter, TransactionWithId,
+};
+use ic_ledger_core::block::BlockType;
+use ic_state_machine_tests::StateMachine;
+use icrc_ledger_types::icrc::generic_value::{ICRC3Value, Value};
+use icrc_ledger_types::icrc1::account::Account;
+use num_traits::cast::ToPrimitive;
+use std::time::Duration;
+
+type Tokens = ic_icrc1_tokens_u64::U64;
+type Block = ic_icrc1::Block<Tokens>;
+
+const MAX_ATTEMPTS_FOR_INDEX_SYNC_WAIT: u8 = 100;
+
+fn index_wasm() -> Vec<u8> {
+    ic_test_utilities_load_wasm::load_wasm(
+        std::env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "ic-icrc1-index-ng",
+        &[],
+    )
+}
+
+fn test_ledger_wasm() -> Vec<u8> {
+    let test_ledger_wasm_path = std::env::var("IC_ICRC3_TEST_LEDGER_WASM_PATH").expect(
+        "The test Ledger wasm path must be set using the env variable IC_ICRC3_TEST_LEDGER_WASM_PATH",
+    );
+    std::fs::read(&test_ledger_wasm_path).unwrap_or_else(|e| {
+        panic!(
+            "failed to load Wasm file from path {} (env var IC_ICRC3_TEST_LEDGER_WASM_PATH): {}",
+            test_ledger_wasm_path, e
+        )
+    })
+}
+
+fn account(owner: u64) -> Account {
+    Account::from(PrincipalId::new_user_test_id(owner).0)
+}
+
+fn icrc1_block(operation: Operation<Tokens>, timestamp: u64) -> ICRC3Value {
+    let block = Block {
+        parent_hash: None,
+        transaction: Transaction {
+            operation,
+            created_at_time: None,
+            memo: None,
+        },
+        effective_fee: None,
+        timestamp,
+        fee_collector: None,
+        fee_collector_block_index: None,
+    };
+    ICRC3Value::from(encoded_block_to_generic_block(&block.encode()))
+}
+
+fn add_block(env: &StateMachine, ledger_id: CanisterId, block: ICRC3Value) -> u64 {
+    let res = env
+        .execute_ingress(ledger_id, "add_block", Encode!(&block).unwrap())
+        .expect("Failed to send add_block")
+        .bytes();
+    Decode!(&res, Nat)
+        .expect("Failed to decode add_block response")
+        .0
+        .to_u64()
+        .unwrap()
+}
+
+fn num_blocks_synced(env: &StateMachine, index_id: CanisterId) -> u64 {
+    let res = env
+        .query(index_id, "status", Encode!(&()).unwrap())
+        .expect("Failed to send status")
+        .bytes();
+    Decode!(&res, Status)
+        .expect("Failed to decode status response")
+        .num_blocks_synced
+        .0
+        .to_u64()
+        .unwrap()
+}
+
+fn wait_until_num_blocks_synced(env: &StateMachine, index_id: CanisterId, num_blocks: u64) {
+    for _ in 0..MAX_ATTEMPTS_FOR_INDEX_SYNC_WAIT {
+        env.advance_time(Duration::from_secs(60));
+        env.tick();
+        if num_blocks_synced(env, index_id) == num_blocks {
+            return;
+        }
+    }
+    panic!(
+        "The index canister synced {} blocks instead of {}",
+        num_blocks_synced(env, index_id),
+        num_blocks
+    );
+}
+
+fn get_account_transactions(
+    env: &StateMachine,
+    index_id: CanisterId,
+    account: Account,
+    filter: Option<TransactionFilter>,
+) -> Vec<TransactionWithId> {
+    let req = GetAccountTransactionsArgs {
+        account,
+        start: None,
+        max_results: Nat::from(100_u64),
+        filter,
+    };
+    let res = env
+        .execute_ingress(index_id, "get_account_transactions", Encode!(&req).unwrap())
+        .expect("Failed to get_account_transactions")
+        .bytes();
+    Decode!(&res, GetAccountTransactionsResult)
+        .expect("Failed to decode GetAccountTransactionsResult")
+        .expect("Failed to perform GetAccountTransactionsArgs")
+        .transactions
+}
+
+fn ids(transactions: &[TransactionWithId]) -> Vec<u64> {
+    transactions
+        .iter()
+        .map(|transaction| transaction.id.0.to_u64().unwrap())
+        .collect()
+}
+
+#[test]
+fn should_index_blocks_of_unknown_type() {
+    let env = &StateMachine::new();
+    let ledger_id = env
+        .install_canister(test_ledger_wasm(), vec![], None)
+        .unwrap();
+    let (account1, account2) = (account(1), account(2));
+    let transfer = |timestamp| {
+        icrc1_block(
+            Operation::Transfer {
+                from: account1,
+                to: account2,
+                spender: None,
+                amount: Tokens::from(100_000_u64),
+                fee: Some(Tokens::from(10_000_u64)),
+            },
+            timestamp,
+        )
+    };
+    let mint = icrc1_block(
+        Operation::Mint {
+            to: account1,
+            amount: Tokens::from(1_000_000_u64),
+        },
+        1_000,
+    );
+    // A block of a type unknown to the index and without timestamp.
+    let set_fee_collector = ICRC3Value::from(Value::map(vec![
+        ("btype", Value::text("107feecol")),
+        (
+            "tx",
+            Value::map(vec![
+                ("op", Value::text("107set_fee_collector")),
+                ("fee_collector", Value::from(account2)),
+            ]),
+        ),
+    ]));
+    assert_eq!(add_block(env, ledger_id, mint), 0);
+    assert_eq!(add_block(env, ledger_id, transfer(2_000)), 1);
+    assert_eq!(add_block(env, ledger_id, set_fee_collector), 2);
+    assert_eq!(add_block(env, ledger_id, transfer(4_000)), 3);
+
+    let index_id = env
+        .install_canister(
+            index_wasm(),
+            Encode!(&IndexArg::Init(IndexInitArg {
+                ledger_id: Principal::from(ledger_id),
+                retrieve_blocks_from_ledger_interval_seconds: None,
+                account_field_paths: Some(vec![vec![
+                    "tx".to_string(),
+                    "fee_collector".to_string(),
+                ]]),
+            }))
+            .unwrap(),
+            None,
+        )
+        .unwrap();
+    wait_until_num_blocks_synced(env, index_id, 4);
+
+    let transactions = get_account_transactions(env, index_id, account2, None);
+    assert_eq!(ids(&transactions), vec![3, 2, 1]);
+    assert_eq!(transactions[1].transaction.kind, "107feecol");
+    assert_eq!(
+        ids(&get_account_transactions(env, index_id, account1, None)),
+        vec![3, 1, 0]
+    );
+
+    // The block without timestamp must not break the search of the first
+    // block after the end of the time range.
+    let before_first_transfer = TransactionFilter {
+        end_timestamp: Some(1_500),
+        ..Default::default()
+    };
+    assert_eq!(
+        ids(&get_account_transactions(
+            env,
+            index_id,
+            account1,
+            Some(before_first_transfer)
+        )),
+        vec![0]
+    );
+    // Nor the search of the first block at the start of the time range.
+    let after_first_transfer = TransactionFilter {
+        start_timestamp: Some(3_000),
+        ..Default::default()
+    };
+    assert_eq!(
+        ids(&get_account_transactions(
+            env,
+            index_id,
+            account1,
+            Some(after_first_transfer)
+        )),
+        vec![3]
+    );
+
+    // A block of a known type that cannot be decoded stops the indexing
+    // instead of being indexed as a block of unknown type.
+    let mint_too_large = ICRC3Value::from(Value::map(vec![
+        ("ts", Value::Nat64(5_000)),
+        (
+            "tx",
+            Value::map(vec![
+                ("op", Value::text("mint")),
+                ("to", Value::from(account1)),
+                ("amt", Value::Nat(Nat::from(u128::from(u64::MAX) + 1))),
+            ]),
+        ),
+    ]));
+    assert_eq!(add_block(env, ledger_id, mint_too_large), 4);
+    for _ in 0..5 {
+        env.advance_time(Duration::from_secs(60));
+        env.tick();
+    }
+    assert_eq!(num_blocks_synced(env, index_id), 4);
+}
@@ -78,6 +78,12 @@ const METADATA_MAX_MEMO_LENGTH: &str = "icrc1:max_memo_length";
 const METADATA_PUBLIC_ALLOWANCES: &str = "icrc103:public_allowances";
 const METADATA_MAX_TAKE_ALLOWANCES: &str = "icrc103:max_take_value";
 const MAX_TAKE_ALLOWANCES: u64 = 500;
+/// The default maximum number of transfers in a single `icrc4_transfer_batch` call.
+const DEFAULT_MAX_TRANSFERS_PER_BATCH: u64 = 100;
+/// The default maximum number of accounts in a single `icrc4_balance_of_batch` call.
+const DEFAULT_MAX_BALANCES_PER_BATCH: u64 = 1_000;
+const METADATA_MAX_TRANSFERS_PER_BATCH: &str = "icrc4:max_transfers";
+const METADATA_MAX_BALANCES_PER_BATCH: &str = "icrc4:max_balances";
 
 #[cfg(not(feature = "u256-tokens"))]
 pub type Tokens = ic_icrc1_tokens_u64::U64;
@@ -190,6 +196,8 @@ impl InitArgsBuilder {
             },
             max_memo_length: None,
             feature_flags: None,
+            max_transfers_per_batch: None,
+            max_balances_per_batch: None,
         })
     }
 
@@ -254,6 +262,16 @@ impl InitArgsBuilder {
         self
     }
 
+    pub fn with_max_transfers_per_batch(mut self, limit: u64) -> Self {
+        self.0.max_transfers_per_batch = Some(limit);
+        self
+    }
+
+    pub fn with_max_balances_per_batch(mut self, limit: u64) -> Self {
+        self.0.max_balances_per_batch = Some(limit);
+        self
+    }
+
     pub fn build(self) -> InitArgs {
         self.0
     }
@@ -272,6 +290,10 @@ pub struct InitArgs {
     pub archive_options: ArchiveOptions,
     pub max_memo_length: Option<u16>,
     pub feature_flags: Option<FeatureFlags>,
+    /// The maximum number of transfers in a single `icrc4_transfer_batch` call.
+    pub max_transfers_per_batch: Option<u64>,
+    /// The maximum number of accounts in a single `icrc4_balance_of_batch` call.
+    pub max_balances_per_batch: Option<u64>,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
@@ -348,6 +370,10 @@ pub struct UpgradeArgs {
     pub feature_flags: Option<FeatureFlags>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub change_archive_options: Option<ChangeArchiveOptions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_transfers_per_batch: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_balances_per_batch: Option<u64>,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Encode, Decode)]
@@ -590,6 +616,11 @@ pub struct Ledger {
     #[serde(default)]
     feature_flags: FeatureFlags,
 
+    #[serde(default = "default_max_transfers_per_batch")]
+    max_transfers_per_batch: u64,
+    #[serde(default = "default_max_balances_per_batch")]
+    max_balances_per_batch: u64,
+
     // DEPRECATED
     #[serde(default)]
     maximum_number_of_accounts: usize,
@@ -622,12 +653,20 @@ fn default_max_memo_length() -> u16 {
     DEFAULT_MAX_MEMO_LENGTH
 }
 
+fn default_max_transfers_per_batch() -> u64 {
+    DEFAULT_MAX_TRANSFERS_PER_BATCH
+}
+
+fn default_max_balances_per_batch() -> u64 {
+    DEFAULT_MAX_BALANCES_PER_BATCH
+}
+
 fn default_decimals() -> u8 {
     ic_ledger_core::tokens::DECIMAL_PLACES as u8
 }
 
 fn map_metadata_or_trap(arg_metadata: Vec<(String, Value)>) -> Vec<(String, StoredValue)> {
-    const DISALLOWED_METADATA_FIELDS: [&str; 7] = [
+    const DISALLOWED_METADATA_FIELDS: [&str; 9] = [
         METADATA_DECIMALS,
         METADATA_NAME,
         METADATA_SYMBOL,
@@ -635,6 +674,8 @@ fn map_metadata_or_trap(arg_metadata: Vec<(String, Value)>) -> Vec<(String, Stor
         METADATA_MAX_MEMO_LENGTH,
         METADATA_PUBLIC_ALLOWANCES,
         METADATA_MAX_TAKE_ALLOWANCES,
+        METADATA_MAX_TRANSFERS_PER_BATCH,
+        METADATA_MAX_BALANCES_PER_BATCH,
     ];
     arg_metadata
         .into_iter()
@@ -665,6 +706,8 @@ impl Ledger {
             fee_collector_account,
             max_memo_length,
             feature_flags,
+            max_transfers_per_batch,
+            max_balances_per_batch,
         }: InitArgs,
         now: TimeStamp,
     ) -> Self {
@@ -696,6 +739,10 @@ impl Ledger {
             metadata: map_metadata_or_trap(metadata),
             max_memo_length: max_memo_length.unwrap_or(DEFAULT_MAX_MEMO_LENGTH),
             feature_flags: feature_flags.unwrap_or_default(),
+            max_transfers_per_batch: max_transfers_per_batch
+                .unwrap_or(DEFAULT_MAX_TRANSFERS_PER_BATCH),
+            max_balances_per_batch: max_balances_per_batch
+                .unwrap_or(DEFAULT_MAX_BALANCES_PER_BATCH),
             maximum_number_of_accounts: 0,
             accounts_overflow_trim_quantity: 0,
             ledger_version: LEDGER_VERSION,
@@ -886,6 +933,14 @@ impl Ledger {
         MAX_TAKE_ALLOWANCES
     }
 
+    pub fn max_transfers_per_batch(&self) -> u64 {
+        self.max_transfers_per_batch
+    }
+
+    pub fn max_balances_per_batch(&self) -> u64 {
+        self.max_balances_per_batch
+    }
+
     pub fn metadata(&self) -> Vec<(String, Value)> {
         let mut records: Vec<(String, Value)> = self
             .metadata
@@ -906,6 +961,14 @@ impl Ledger {
             METADATA_MAX_TAKE_ALLOWANCES,
             Nat::from(self.max_take_allowances()),
         ));
+        records.push(Value::entry(
+            METADATA_MAX_TRANSFERS_PER_BATCH,
+            Nat::from(self.max_transfers_per_batch()),
+        ));
+        records.push(Value::entry(
+            METADATA_MAX_BALANCES_PER_BATCH,
+            Nat::from(self.max_balances_per_batch()),
+        ));
         // When adding new entries that cannot be set by the user
         // (e.g. because they are fixed or computed dynamically)
         // please also add them to `map_metadata_or_trap` to prevent
@@ -959,6 +1022,12 @@ impl Ledger {
             }
             self.feature_flags = feature_flags;
         }
+        if let Some(max_transfers_per_batch) = args.max_transfers_per_batch {
+            self.max_transfers_per_batch = max_transfers_per_batch;
+        }
+        if let Some(max_balances_per_batch) = args.max_balances_per_batch {
+            self.max_balances_per_batch = max_balances_per_batch;
+        }
         if let Some(change_archive_options) = args.change_archive_options {
             let mut maybe_archive = self.blockchain.archive.write().expect(
                 "BUG: should be unreachable since upgrade has exclusive write access to the ledger",
@@ -43,6 +43,10 @@ use icrc_ledger_types::icrc3::blocks::DataCertificate;
 #[cfg(not(feature = "get-blocks-disabled"))]
 use icrc_ledger_types::icrc3::blocks::GetBlocksResponse;
 use icrc_ledger_types::icrc3::blocks::ICRC3DataCertificate;
+use icrc_ledger_types::icrc4::batch::{
+    AccountBalance, BalanceQueryArgs, BalanceQueryResult, TransferBatchArgs, TransferBatchError,
+    TransferBatchResults,
+};
 use icrc_ledger_types::{
     icrc::generic_metadata_value::MetadataValue as Value,
     icrc3::{
@@ -76,6 +80,10 @@ use std::{
 
 const MAX_MESSAGE_SIZE: u64 = 1024 * 1024;
 
+/// The `error_code` of the `GenericError` returned for a transfer of an
+/// `icrc4_transfer_batch` call whose memo is above the allowed limit.
+const BATCH_MEMO_TOO_LONG_ERROR_CODE: u64 = 1;
+
 #[cfg(not(feature = "u256-tokens"))]
 pub type Tokens = ic_icrc1_tokens_u64::U64;
 
@@ -799,6 +807,97 @@ async fn icrc1_transfer(arg: TransferArg) -> Result<Nat, TransferError> {
     })
 }
 
+/// Executes the transfers of the batch one by one, each with its own result
+/// and deduplication. Blocks are archived once all transfers are executed.
+#[update]
+#[candid_method(update)]
+async fn icrc4_transfer_batch(args: TransferBatchArgs) -> TransferBatchResults {
+    panic_if_not_ready();
+    let max_transfers_per_batch = Access::with_ledger(|ledger| ledger.max_transfers_per_batch());
+    if args.len() as u64 > max_transfers_per_batch {
+        return args
+            .iter()
+            .map(|_| {
+                Some(Err(TransferBatchError::TooManyRequests {
+                    limit: Nat::from(max_transfers_per_batch),
+                }))
+            })
+            .collect();
+    }
+    let max_memo_length = Access::with_ledger(|ledger| ledger.max_memo_length());
+    let caller = ic_cdk::api::caller();
+    let results: TransferBatchResults = args
+        .into_iter()
+        .map(|arg| {
+            // A single transfer traps on a memo above the limit, which would
+            // abort the whole batch, so the memo is validated per transfer.
+            if let Some(memo) = arg.memo.as_ref() {
+                if memo.0.len() > max_memo_length as usize {
+                    return Some(Err(TransferBatchError::GenericError {
+                        error_code: Nat::from(BATCH_MEMO_TOO_LONG_ERROR_CODE),
+                        message: format!(
+                            "the memo field size of {} bytes is above the allowed limit of {} bytes",
+                            memo.0.len(),
+                            max_memo_length
+                        ),
+                    }));
+                }
+            }
+            let from_account = Account {
+                owner: caller,
+                subaccount: arg.from_subaccount,
+            };
+            let result = execute_transfer_not_async(
+                from_account,
+                arg.to,
+                None,
+                arg.fee,
+                arg.amount,
+                arg.memo,
+                arg.created_at_time,
+            )
+            .map(Nat::from)
+            .map_err(convert_transfer_error)
+            .map_err(|err| {
+                let err: TransferError = match err.try_into() {
+                    Ok(err) => err,
+                    Err(err) => ic_cdk::trap(&err),
+                };
+                TransferBatchError::from(err)
+            });
+            Some(result)
+        })
+        .collect();
+
+    // NB. we need to set the certified data before the first async call to make sure that the
+    // blockchain state agrees with the certificate while archiving is in progress.
+    ic_cdk::api::set_certified_data(&Access::with_ledger(Ledger::root_hash));
+
+    archive_blocks::<Access>(&LOG, MAX_MESSAGE_SIZE).await;
+    results
+}
+
+#[query]
+#[candid_method(query)]
+fn icrc4_balance_of_batch(args: BalanceQueryArgs) -> BalanceQueryResult {
+    Access::with_ledger(|ledger| {
+        if args.accounts.len() as u64 > ledger.max_balances_per_batch() {
+            ic_cdk::trap(&format!(
+                "the number of accounts {} is above the allowed limit of {}",
+                args.accounts.len(),
+                ledger.max_balances_per_batch()
+            ));
+        }
+        args.accounts
+            .into_iter()
+            .map(|account| AccountBalance {
+                account,
+                balance: ledger.balances().account_balance(&account).into(),
+            })
+            .collect()
+    })
+}
+
 #[update]
 #[candid_method(update)]
 async fn icrc2_transfer_from(arg: TransferFromArgs) -> Result<Nat, TransferFromError> {
@@ -867,6 +966,10 @@ fn supported_standards() -> Vec<StandardRecord> {
             name: "ICRC-3".to_string(),
             url: "https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-3".to_string(),
         },
+        StandardRecord {
+            name: "ICRC-4".to_string(),
+            url: "https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-4/ICRC-4.md".to_string(),
+        },
         StandardRecord {
             name: "ICRC-10".to_string(),
             url: "https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-10/ICRC-10.md".to_string(),
@@ -85,6 +85,8 @@ fn default_init_args() -> InitArgs {
         },
         max_memo_length: None,
         feature_flags: None,
+        max_transfers_per_batch: None,
+        max_balances_per_batch: None,
     }
 }
 
@@ -211,6 +211,8 @@ fn encode_init_args(args: ic_ledger_suite_state_machine_tests::InitArgs) -> Ledg
         archive_options: args.archive_options,
         max_memo_length: None,
         feature_flags: args.feature_flags,
+        max_transfers_per_batch: None,
+        max_balances_per_batch: None,
     })
 }
 
@@ -945,6 +947,8 @@ fn test_icrc2_feature_flag_doesnt_disable_icrc2_endpoints() {
         },
         max_memo_length: None,
         feature_flags: Some(FeatureFlags { icrc2: false }),
+        max_transfers_per_batch: None,
+        max_balances_per_batch: None,
     }))
     .unwrap();
     let ledger_id = env
@@ -1119,6 +1123,8 @@ fn test_icrc3_get_archives() {
         },
         max_memo_length: None,
         feature_flags: None,
+        max_transfers_per_batch: None,
+        max_balances_per_batch: None,
     });
     let args = Encode!(&args).unwrap();
     let ledger_id = env
@@ -1194,6 +1200,8 @@ fn test_icrc3_get_blocks() {
         },
         max_memo_length: None,
         feature_flags: None,
+        max_transfers_per_batch: None,
+        max_balances_per_batch: None,
     });
     let args = Encode!(&args).unwrap();
     let ledger_id = env
@@ -1468,6 +1476,8 @@ fn test_icrc3_get_blocks_number_of_blocks_limit() {
         },
         max_memo_length: None,
         feature_flags: None,
+        max_transfers_per_batch: None,
+        max_balances_per_batch: None,
     });
 
     let args = Encode!(&args).unwrap();
@@ -1769,6 +1779,245 @@ fn is_valid_root_hash(
     cert_hash == root_hash
 }
 
+mod icrc4 {
+    use super::*;
+    use ic_icrc1_ledger::UpgradeArgs;
+    use ic_ledger_suite_state_machine_tests::{balance_of, metadata, system_time_to_nanos};
+    use ic_state_machine_tests::ErrorCode;
+    use icrc_ledger_types::icrc4::batch::{
+        AccountBalance, BalanceQueryArgs, BalanceQueryResult, TransferBatchError,
+        TransferBatchResults,
+    };
+
+    const INITIAL_BALANCE: u64 = 1_000_000;
+    const MAX_TRANSFERS_PER_BATCH: u64 = 3;
+    const MAX_BALANCES_PER_BATCH: u64 = 2;
+
+    fn setup() -> (StateMachine, CanisterId) {
+        let env = StateMachine::new();
+        let init_args = LedgerInitArgsBuilder::with_symbol_and_name(TOKEN_SYMBOL, TOKEN_NAME)
+            .with_minting_account(MINTER)
+            .with_transfer_fee(FEE)
+            .with_initial_balance(account(1), INITIAL_BALANCE)
+            .with_max_transfers_per_batch(MAX_TRANSFERS_PER_BATCH)
+            .with_max_balances_per_batch(MAX_BALANCES_PER_BATCH)
+            .build();
+        let ledger_id = env
+            .install_canister(
+                ledger_wasm(),
+                Encode!(&LedgerArgument::Init(init_args)).unwrap(),
+                None,
+            )
+            .unwrap();
+        (env, ledger_id)
+    }
+
+    fn transfer_arg(to: Account, amount: u64, created_at_time: Option<u64>) -> TransferArg {
+        TransferArg {
+            from_subaccount: None,
+            to,
+            fee: None,
+            created_at_time,
+            memo: None,
+            amount: Nat::from(amount),
+        }
+    }
+
+    fn transfer_batch(
+        env: &StateMachine,
+        ledger_id: CanisterId,
+        from: Account,
+        args: Vec<TransferArg>,
+    ) -> TransferBatchResults {
+        Decode!(
+            &env.execute_ingress_as(
+                PrincipalId(from.owner),
+                ledger_id,
+                "icrc4_transfer_batch",
+                Encode!(&args).unwrap()
+            )
+            .expect("failed to execute the transfer batch")
+            .bytes(),
+            TransferBatchResults
+        )
+        .expect("failed to decode icrc4_transfer_batch response")
+    }
+
+    fn balance_of_batch(
+        env: &StateMachine,
+        ledger_id: CanisterId,
+        accounts: Vec<Account>,
+    ) -> Result<BalanceQueryResult, ic_state_machine_tests::UserError> {
+        env.query(
+            ledger_id,
+            "icrc4_balance_of_batch",
+            Encode!(&BalanceQueryArgs { accounts }).unwrap(),
+        )
+        .map(|result| Decode!(&result.bytes(), BalanceQueryResult).unwrap())
+    }
+
+    #[test]
+    fn should_execute_each_transfer_of_the_batch() {
+        let (env, ledger_id) = setup();
+        let now = system_time_to_nanos(env.time());
+
+        let results = transfer_batch(
+            &env,
+            ledger_id,
+            account(1),
+            vec![
+                transfer_arg(account(2), 100_000, Some(now)),
+                transfer_arg(account(3), 2 * INITIAL_BALANCE, None),
+                transfer_arg(account(2), 100_000, Some(now)),
+            ],
+        );
+
+        // Block 0 is the mint of the initial balance.
+        assert_eq!(
+            results,
+            vec![
+                Some(Ok(Nat::from(1_u64))),
+                Some(Err(TransferBatchError::InsufficientFunds {
+                    balance: Nat::from(INITIAL_BALANCE - 100_000 - FEE),
+                })),
+                Some(Err(TransferBatchError::Duplicate {
+                    duplicate_of: Nat::from(1_u64),
+                })),
+            ]
+        );
+        assert_eq!(
+            balance_of(&env, ledger_id, account(1)),
+            INITIAL_BALANCE - 100_000 - FEE
+        );
+        assert_eq!(balance_of(&env, ledger_id, account(2)), 100_000);
+        assert_eq!(balance_of(&env, ledger_id, account(3)), 0);
+
+        // Items are deduplicated against transfers of previous calls as well.
+        let results = transfer_batch(
+            &env,
+            ledger_id,
+            account(1),
+            vec![
+                transfer_arg(account(3), 1_000, None),
+                transfer_arg(account(2), 100_000, Some(now)),
+            ],
+        );
+        assert_eq!(
+            results,
+            vec![
+                Some(Ok(Nat::from(2_u64))),
+                Some(Err(TransferBatchError::Duplicate {
+                    duplicate_of: Nat::from(1_u64),
+                })),
+            ]
+        );
+        assert_eq!(balance_of(&env, ledger_id, account(3)), 1_000);
+
+        let blocks = get_all_ledger_and_archive_blocks::<Tokens>(&env, ledger_id, None, None);
+        assert_eq!(blocks.len(), 3);
+    }
+
+    #[test]
+    fn should_reject_batches_above_the_limit() {
+        let (env, ledger_id) = setup();
+        let args: Vec<_> = (2..=MAX_TRANSFERS_PER_BATCH + 2)
+            .map(|n| transfer_arg(account(n), 1_000, None))
+            .collect();
+
+        let results = transfer_batch(&env, ledger_id, account(1), args.clone());
+
+        assert_eq!(
+            results,
+            vec![
+                Some(Err(TransferBatchError::TooManyRequests {
+                    limit: Nat::from(MAX_TRANSFERS_PER_BATCH),
+                }));
+                args.len()
+            ]
+        );
+        assert_eq!(balance_of(&env, ledger_id, account(1)), INITIAL_BALANCE);
+
+        // Raising the limit on upgrade allows the same batch.
+        let upgrade_args = LedgerArgument::Upgrade(Some(UpgradeArgs {
+            max_transfers_per_batch: Some(MAX_TRANSFERS_PER_BATCH + 1),
+            ..UpgradeArgs::default()
+        }));
+        env.upgrade_canister(ledger_id, ledger_wasm(), Encode!(&upgrade_args).unwrap())
+            .unwrap();
+        let results = transfer_batch(&env, ledger_id, account(1), args);
+        assert!(results.iter().all(|result| matches!(result, Some(Ok(_)))));
+        assert_eq!(
+            metadata(&env, ledger_id).get("icrc4:max_transfers"),
+            Some(&MetadataValue::from(MAX_TRANSFERS_PER_BATCH + 1))
+        );
+    }
+
+    #[test]
+    fn should_reject_only_the_transfers_with_a_memo_above_the_limit() {
+        let (env, ledger_id) = setup();
+        let memo_too_long = TransferArg {
+            memo: Some(icrc_ledger_types::icrc1::transfer::Memo::from(vec![
+                0;
+                1_000
+            ])),
+            ..transfer_arg(account(3), 1_000, None)
+        };
+
+        let results = transfer_batch(
+            &env,
+            ledger_id,
+            account(1),
+            vec![
+                transfer_arg(account(2), 1_000, None),
+                memo_too_long,
+                transfer_arg(account(2), 2_000, None),
+            ],
+        );
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], Some(Ok(Nat::from(1_u64))));
+        match &results[1] {
+            Some(Err(TransferBatchError::GenericError { error_code, .. })) => {
+                assert_eq!(error_code, &Nat::from(1_u64))
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(results[2], Some(Ok(Nat::from(2_u64))));
+        assert_eq!(balance_of(&env, ledger_id, account(2)), 3_000);
+        assert_eq!(balance_of(&env, ledger_id, account(3)), 0);
+    }
+
+    #[test]
+    fn should_return_the_balances_of_the_batch() {
+        let (env, ledger_id) = setup();
+
+        assert_eq!(
+            balance_of_batch(&env, ledger_id, vec![account(1), account(2)]).unwrap(),
+            vec![
+                AccountBalance {
+                    account: account(1),
+                    balance: Nat::from(INITIAL_BALANCE),
+                },
+                AccountBalance {
+                    account: account(2),
+                    balance: Nat::from(0_u64),
+                },
+            ]
+        );
+
+        let err = balance_of_batch(&env, ledger_id, vec![account(1), account(2), account(3)])
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::CanisterCalledTrap);
+        assert!(err
+            .description()
+            .contains("the number of accounts 3 is above the allowed limit of 2"));
+        assert_eq!(
+            metadata(&env, ledger_id).get("icrc4:max_balances"),
+            Some(&MetadataValue::from(MAX_BALANCES_PER_BATCH))
+        );
+    }
+}
+
 mod verify_written_blocks {
     use super::*;
     use ic_icrc1_ledger::FeatureFlags;
@@ -2028,6 +2277,8 @@ mod verify_written_blocks {
                 },
                 max_memo_length: None,
                 feature_flags: Some(FeatureFlags { icrc2: true }),
+                max_transfers_per_batch: None,
+                max_balances_per_batch: None,
             });
 
             let args = Encode!(&ledger_arg_init).unwrap();
@@ -2244,6 +2495,8 @@ mod incompatible_token_type_upgrade {
             },
             max_memo_length: None,
             feature_flags: Some(FeatureFlags { icrc2: false }),
+            max_transfers_per_batch: None,
+            max_balances_per_batch: None,
         }))
         .unwrap()
     }
@@ -0,0 +1,93 @@
+//! A ledger serving arbitrary ICRC-3 blocks, used to test how clients
+//! like the index handle blocks that the ICRC-1 ledger doesn't produce.
+use candid::{candid_method, CandidType, Nat};
+use ic_cdk::{query, update};
+use icrc_ledger_types::icrc::generic_value::ICRC3Value;
+use icrc_ledger_types::icrc3::blocks::{BlockWithId, GetBlocksRequest, GetBlocksResult};
+use serde::Deserialize;
+use std::cell::RefCell;
+
+thread_local! {
+    static BLOCKS: RefCell<Vec<ICRC3Value>> = const { RefCell::new(vec![]) };
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct StandardRecord {
+    pub name: String,
+    pub url: String,
+}
+
+#[update]
+#[candid_method(update)]
+fn add_block(block: ICRC3Value) -> Nat {
+    BLOCKS.with(|blocks| {
+        let mut blocks = blocks.borrow_mut();
+        blocks.push(block);
+        Nat::from(blocks.len() - 1)
+    })
+}
+
+#[query]
+#[candid_method(query)]
+fn icrc1_supported_standards() -> Vec<StandardRecord> {
+    vec![
+        StandardRecord {
+            name: "ICRC-1".to_string(),
+            url: "https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-1".to_string(),
+        },
+        StandardRecord {
+            name: "ICRC-3".to_string(),
+            url: "https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-3".to_string(),
+        },
+    ]
+}
+
+#[query]
+#[candid_method(query)]
+fn icrc3_get_blocks(args: Vec<GetBlocksRequest>) -> GetBlocksResult {
+    BLOCKS.with(|blocks| {
+        let blocks = blocks.borrow();
+        let mut result = vec![];
+        for arg in args {
+            let (start, length) = arg
+                .as_start_and_length()
+                .unwrap_or_else(|err| ic_cdk::trap(&err));
+            let end = start.saturating_add(length).min(blocks.len() as u64);
+            for id in start..end {
+                result.push(BlockWithId {
+                    id: Nat::from(id),
+                    block: blocks[id as usize].clone(),
+                });
+            }
+        }
+        GetBlocksResult {
+            log_length: Nat::from(blocks.len()),
+            blocks: result,
+            archived_blocks: vec![],
+        }
+    })
+}
+
+fn main() {}
+
+candid::export_service!();
+
+#[test]
+fn check_candid_interface() {
+    use candid_parser::utils::{service_equal, CandidSource};
+
+    let new_interface = __export_service();
+    let manifest_dir = std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let old_interface = manifest_dir.join("icrc3_test_ledger.did");
+    service_equal(
+        CandidSource::Text(&new_interface),
+        CandidSource::File(old_interface.as_path()),
+    )
+    .unwrap_or_else(|e| {
+        panic!(
+            "the service interface is not compatible with {}: {:?}",
+            old_interface.display(),
+            e
+        )
+    });
+}
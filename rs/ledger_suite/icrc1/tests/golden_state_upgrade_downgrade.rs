@@ -260,6 +260,7 @@ impl LedgerSuiteConfig {
         let index_upgrade_arg = IndexArg::Upgrade(IndexUpgradeArg {
             ledger_id: None,
             retrieve_blocks_from_ledger_interval_seconds: None,
+            account_field_paths: None,
         });
         let args = Encode!(&index_upgrade_arg).unwrap();
         state_machine
@@ -1,7 +1,11 @@
 use crate::common::{index_ng_wasm, ledger_wasm, load_wasm_using_env_var};
-use candid::{Encode, Principal};
+use candid::{Decode, Encode, Nat, Principal};
 use ic_base_types::{CanisterId, PrincipalId};
-use ic_icrc1_index_ng::{IndexArg, InitArg as IndexInitArg, UpgradeArg as IndexUpgradeArg};
+use ic_icrc1_index_ng::{
+    GetAccountTransactionsArgs, GetAccountTransactionsResult, IndexArg, InitArg as IndexInitArg,
+    Status, TransactionDirection, TransactionFilter, TransactionKind,
+    UpgradeArg as IndexUpgradeArg,
+};
 use ic_icrc1_ledger::{FeatureFlags, InitArgsBuilder, LedgerArgument};
 use ic_ledger_canister_core::archive::ArchiveOptions;
 use ic_ledger_suite_state_machine_tests::{
@@ -11,6 +15,8 @@ use ic_ledger_suite_state_machine_tests::{
 use ic_registry_subnet_type::SubnetType;
 use ic_state_machine_tests::{StateMachine, StateMachineBuilder};
 use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::{TransferArg, TransferError};
+use num_traits::ToPrimitive;
 use std::time::{Duration, SystemTime};
 
 mod common;
@@ -20,6 +26,7 @@ const STARTING_CYCLES_PER_CANISTER: u128 = 2_000_000_000_000_000;
 const ARCHIVE_TRIGGER_THRESHOLD: u64 = 10;
 const NUM_BLOCKS_TO_ARCHIVE: usize = 5;
 const MAX_BLOCKS_FROM_ARCHIVE: u64 = 10;
+const MAX_ATTEMPTS_FOR_INDEX_SYNC_WAIT: u8 = 100;
 
 #[test]
 fn should_upgrade_and_downgrade_ledger_canister_suite() {
@@ -42,6 +49,7 @@ fn should_upgrade_and_downgrade_ledger_canister_suite() {
         IndexInitArg {
             ledger_id: Principal::from(ledger_id),
             retrieve_blocks_from_ledger_interval_seconds: None,
+            account_field_paths: None,
         },
     );
 
@@ -51,6 +59,7 @@ fn should_upgrade_and_downgrade_ledger_canister_suite() {
     let index_upgrade_arg = IndexArg::Upgrade(IndexUpgradeArg {
         ledger_id: None,
         retrieve_blocks_from_ledger_interval_seconds: None,
+        account_field_paths: None,
     });
     env.upgrade_canister(
         index_id,
@@ -84,6 +93,159 @@ fn should_upgrade_and_downgrade_ledger_canister_suite() {
     ).expect("Downgrading ledger to the mainnet version should succeed, since there are no breaking changes");
 }
 
+#[test]
+fn should_backfill_transaction_metadata_after_upgrading_deployed_index() {
+    let env = &StateMachine::new();
+    let ledger_id = install_ledger(
+        env,
+        vec![(account(1), 10_000_000)],
+        default_archive_options(),
+        None,
+        MINTER_PRINCIPAL,
+    );
+    let index_id = install_index_ng(
+        env,
+        IndexInitArg {
+            ledger_id: Principal::from(ledger_id),
+            retrieve_blocks_from_ledger_interval_seconds: None,
+            account_field_paths: None,
+        },
+    );
+
+    // Block 0 is the mint to account 1 at ledger init.
+    // Block 1: transfer from account 1 to account 2.
+    transfer(env, ledger_id, account(1), account(2), 1_000_000);
+    // Block 2: transfer from account 2 to account 1.
+    transfer(env, ledger_id, account(2), account(1), 100_000);
+    wait_until_index_synced(env, index_id, 3);
+
+    // The deployed index has no transaction metadata for the blocks above.
+    env.upgrade_canister(
+        index_id,
+        index_ng_wasm(),
+        Encode!(&None::<IndexArg>).unwrap(),
+    )
+    .unwrap();
+    let incoming = TransactionFilter {
+        direction: Some(TransactionDirection::Incoming),
+        ..Default::default()
+    };
+    assert!(
+        get_filtered_account_transactions(env, index_id, account(1), incoming.clone()).is_err(),
+        "Transaction filters should be unavailable until the backfill completes"
+    );
+
+    // The next build_index round backfills the metadata of the existing blocks.
+    env.advance_time(Duration::from_secs(60));
+    env.tick();
+
+    let filtered_ids = |filter: TransactionFilter| {
+        get_filtered_account_transactions(env, index_id, account(1), filter)
+            .expect("transaction filters should be available after the backfill")
+            .transactions
+            .into_iter()
+            .map(|tx| tx.id.0.to_u64().unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(filtered_ids(incoming), vec![2, 0]);
+    assert_eq!(
+        filtered_ids(TransactionFilter {
+            direction: Some(TransactionDirection::Outgoing),
+            ..Default::default()
+        }),
+        vec![1]
+    );
+    assert_eq!(
+        filtered_ids(TransactionFilter {
+            kinds: Some(vec![TransactionKind::Transfer]),
+            counterparty: Some(account(2)),
+            ..Default::default()
+        }),
+        vec![2, 1]
+    );
+
+    // Blocks indexed after the upgrade get their metadata right away.
+    // Block 3: transfer from account 1 to account 3.
+    transfer(env, ledger_id, account(1), account(3), 200_000);
+    wait_until_index_synced(env, index_id, 4);
+    assert_eq!(
+        filtered_ids(TransactionFilter {
+            counterparty: Some(account(3)),
+            ..Default::default()
+        }),
+        vec![3]
+    );
+}
+
+fn account(n: u64) -> Account {
+    Account {
+        owner: PrincipalId::new_user_test_id(n).0,
+        subaccount: None,
+    }
+}
+
+fn transfer(env: &StateMachine, ledger_id: CanisterId, from: Account, to: Account, amount: u64) {
+    let arg = TransferArg {
+        from_subaccount: from.subaccount,
+        to,
+        fee: None,
+        created_at_time: None,
+        memo: None,
+        amount: Nat::from(amount),
+    };
+    let res = env
+        .execute_ingress_as(
+            PrincipalId(from.owner),
+            ledger_id,
+            "icrc1_transfer",
+            Encode!(&arg).unwrap(),
+        )
+        .expect("Failed to send icrc1_transfer")
+        .bytes();
+    Decode!(&res, Result<Nat, TransferError>)
+        .expect("Failed to decode icrc1_transfer response")
+        .expect("Failed to perform icrc1_transfer");
+}
+
+fn wait_until_index_synced(env: &StateMachine, index_id: CanisterId, num_blocks: u64) {
+    for _ in 0..MAX_ATTEMPTS_FOR_INDEX_SYNC_WAIT {
+        env.advance_time(Duration::from_secs(60));
+        env.tick();
+        let res = env
+            .query(index_id, "status", Encode!(&()).unwrap())
+            .expect("Failed to send status")
+            .bytes();
+        let status = Decode!(&res, Status).expect("Failed to decode status response");
+        if status.num_blocks_synced.0.to_u64() == Some(num_blocks) {
+            return;
+        }
+    }
+    panic!(
+        "The index canister was unable to sync {} blocks",
+        num_blocks
+    );
+}
+
+fn get_filtered_account_transactions(
+    env: &StateMachine,
+    index_id: CanisterId,
+    account: Account,
+    filter: TransactionFilter,
+) -> GetAccountTransactionsResult {
+    let arg = GetAccountTransactionsArgs {
+        account,
+        start: None,
+        max_results: Nat::from(u64::MAX),
+        filter: Some(filter),
+    };
+    let res = env
+        .query(index_id, "get_account_transactions", Encode!(&arg).unwrap())
+        .expect("Failed to send get_account_transactions")
+        .bytes();
+    Decode!(&res, GetAccountTransactionsResult)
+        .expect("Failed to decode get_account_transactions response")
+}
+
 fn default_archive_options() -> ArchiveOptions {
     ArchiveOptions {
         trigger_threshold: ARCHIVE_TRIGGER_THRESHOLD as usize,
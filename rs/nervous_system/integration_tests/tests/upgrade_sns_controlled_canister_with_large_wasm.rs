@@ -195,6 +195,8 @@ async fn upgrade_sns_controlled_canister_with_large_wasm() {
         canister_upgrade_arg,
         mode,
         chunked_canister_wasm,
+        take_canister_snapshot: None,
+        health_check_method: None,
     }) = action
     else {
         panic!("unexpected proposal action {:?}", action);
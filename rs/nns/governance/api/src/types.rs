@@ -4379,3 +4379,144 @@ pub struct MaturityDisbursement {
     /// The account to disburse the maturity to.
     pub account_to_disburse_to: Option<Account>,
 }
+
+/// The input of the simulate_proposal method.
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default,
+)]
+pub struct SimulateProposalRequest {
+    /// The proposal to simulate. It is not submitted; therefore, no neuron is needed.
+    pub proposal: Option<MakeProposalRequest>,
+}
+
+/// The output of the simulate_proposal method. Describes what executing a proposal would do, had
+/// it been adopted right now.
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default,
+)]
+pub struct SimulateProposalResponse {
+    /// The topic that the proposal would be in. See the Topic enum.
+    pub topic: Option<i32>,
+    /// The canister method that would be called to execute the proposal. This is not populated
+    /// for proposals that are executed by Governance itself (e.g. ManageNetworkEconomics).
+    pub canister_call: Option<SimulatedCanisterCall>,
+    pub effects: Option<ProposalEffects>,
+}
+
+/// A canister method call that Governance would make in order to execute a proposal.
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default,
+)]
+pub struct SimulatedCanisterCall {
+    pub canister_id: Option<PrincipalId>,
+    pub method_name: Option<String>,
+    /// The (candid-encoded) argument of the call. Only its size and hash are given, since it can
+    /// be large (e.g. when it contains a WASM module).
+    pub payload_size_bytes: Option<u64>,
+    pub payload_sha256: Option<Vec<u8>>,
+}
+
+/// Action-specific description of what executing a proposal would do.
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+pub enum ProposalEffects {
+    InstallCode(InstallCodeEffects),
+    UpdateCanisterSettings(UpdateCanisterSettingsEffects),
+    ExecuteNnsFunction(ExecuteNnsFunctionEffects),
+    ManageNetworkEconomics(ManageNetworkEconomicsEffects),
+}
+
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default,
+)]
+pub struct InstallCodeEffects {
+    /// The canister whose code would be installed.
+    pub canister_id: Option<PrincipalId>,
+    /// See install_code::CanisterInstallMode.
+    pub install_mode: Option<i32>,
+    pub wasm_module_sha256: Option<Vec<u8>>,
+    /// Empty when the argument is empty.
+    pub arg_sha256: Option<Vec<u8>>,
+    /// Whether the canister would be stopped before installing the code (and started afterwards).
+    pub stop_before_installing: Option<bool>,
+}
+
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default,
+)]
+pub struct UpdateCanisterSettingsEffects {
+    /// The canister whose settings would be updated.
+    pub canister_id: Option<PrincipalId>,
+    /// The settings that would be set. old_value is only populated when the current settings of
+    /// the canister could be obtained from the NNS root canister.
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default,
+)]
+pub struct ExecuteNnsFunctionEffects {
+    /// See the NnsFunction enum.
+    pub nns_function: Option<i32>,
+    pub payload: Option<NnsFunctionPayload>,
+    /// The registry records that would be written. Empty when the NnsFunction does not target the
+    /// registry.
+    pub registry_changes: Vec<RegistryRecordChange>,
+}
+
+/// What is known about the payload of an ExecuteNnsFunction proposal.
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, Debug)]
+pub enum NnsFunctionPayload {
+    Decoded(DecodedNnsFunctionPayload),
+    Unsupported(UnsupportedNnsFunctionPayload),
+}
+
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default,
+)]
+pub struct DecodedNnsFunctionPayload {
+    /// The name of the type that the payload was decoded into, e.g. "UpdateSubnetPayload".
+    pub type_name: Option<String>,
+    /// Human-readable rendering of the decoded payload.
+    pub rendering: Option<String>,
+}
+
+/// The payload is not decoded (e.g. because the NnsFunction is obsolete).
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default,
+)]
+pub struct UnsupportedNnsFunctionPayload {
+    pub reason: Option<String>,
+}
+
+/// A registry record that executing a proposal would write.
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default,
+)]
+pub struct RegistryRecordChange {
+    /// The key of the record, e.g. "subnet_record_<subnet ID>". Not populated when the key is
+    /// only determined at execution time (e.g. the records of a subnet that is yet to be created).
+    pub key: Option<String>,
+    /// What would happen to the record(s), e.g. "Delete the record.".
+    pub description: Option<String>,
+}
+
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default,
+)]
+pub struct ManageNetworkEconomicsEffects {
+    /// The fields of NetworkEconomics that would change.
+    pub changes: Vec<FieldChange>,
+}
+
+/// A change to the value of one field.
+#[derive(
+    candid::CandidType, candid::Deserialize, serde::Serialize, Clone, PartialEq, Debug, Default,
+)]
+pub struct FieldChange {
+    /// The path to the field, e.g. "voting_power_economics.clear_following_after_seconds".
+    pub field_name: Option<String>,
+    /// Human-readable rendering of the current value, if known.
+    pub old_value: Option<String>,
+    /// Human-readable rendering of the value after the change.
+    pub new_value: Option<String>,
+}
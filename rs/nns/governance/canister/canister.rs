@@ -44,7 +44,8 @@ use ic_nns_governance_api::{
     ManageNeuronResponse, MonthlyNodeProviderRewards, NetworkEconomics, Neuron, NeuronInfo,
     NodeProvider, Proposal, ProposalInfo, RestoreAgingSummary, RewardEvent,
     SettleCommunityFundParticipation, SettleNeuronsFundParticipationRequest,
-    SettleNeuronsFundParticipationResponse, UpdateNodeProvider, Vote,
+    SettleNeuronsFundParticipationResponse, SimulateProposalRequest, SimulateProposalResponse,
+    UpdateNodeProvider, Vote,
 };
 use std::sync::Arc;
 use std::{boxed::Box, time::Duration};
@@ -318,6 +319,23 @@ fn simulate_manage_neuron(manage_neuron: ManageNeuronRequest) -> ManageNeuronRes
     governance().simulate_manage_neuron(&caller(), gov_pb::ManageNeuron::from(manage_neuron))
 }
 
+/// An update (rather than a query), because the current settings of the target canister of
+/// UpdateCanisterSettings proposals are fetched from the NNS root canister.
+#[update]
+async fn simulate_proposal(
+    request: SimulateProposalRequest,
+) -> Result<SimulateProposalResponse, GovernanceError> {
+    debug_log("simulate_proposal");
+    let SimulateProposalRequest { proposal } = request;
+    let proposal = proposal.ok_or_else(|| {
+        GovernanceError::new_with_message(ErrorType::InvalidCommand, "proposal is required.")
+    })?;
+    governance()
+        .simulate_proposal(&gov_pb::Proposal::from(proposal))
+        .await
+        .map_err(GovernanceError::from)
+}
+
 #[query]
 fn get_full_neuron_by_id_or_subaccount(
     by: NeuronIdOrSubaccount,
@@ -66,7 +66,15 @@ use crate::{
             WaitForQuietState, XdrConversionRate as XdrConversionRatePb,
         },
     },
-    proposals::{call_canister::CallCanister, sum_weighted_voting_power},
+    proposals::{
+        call_canister::CallCanister,
+        simulate_proposal::{
+            fetch_current_canister_settings, simulate_call_canister, simulate_canister_call,
+            simulate_execute_nns_function, simulate_install_code,
+            simulate_manage_network_economics, simulate_update_canister_settings,
+        },
+        sum_weighted_voting_power,
+    },
     use_node_provider_reward_canister,
 };
 use async_trait::async_trait;
@@ -4985,6 +4993,93 @@ impl Governance {
         Ok(action.clone())
     }
 
+    /// Validates proposal exactly like make_proposal would, and describes what executing it would
+    /// do, had it been adopted right now. Nothing is submitted nor executed.
+    ///
+    /// Only InstallCode, UpdateCanisterSettings, ExecuteNnsFunction, and ManageNetworkEconomics
+    /// proposals are supported. For UpdateCanisterSettings, the current settings of the target
+    /// canister are fetched from root, so that they can be shown next to the new ones.
+    pub async fn simulate_proposal(
+        &self,
+        proposal: &Proposal,
+    ) -> Result<api::SimulateProposalResponse, GovernanceError> {
+        let action = self.validate_proposal(proposal)?;
+        let topic = proposal.compute_topic_at_creation();
+
+        let (canister_call, effects) = match &action {
+            Action::InstallCode(install_code) => (
+                Some(simulate_call_canister(install_code)?),
+                api::ProposalEffects::InstallCode(simulate_install_code(install_code)),
+            ),
+            Action::UpdateCanisterSettings(update_canister_settings) => {
+                let canister_call = simulate_call_canister(update_canister_settings)?;
+                let canister_id = update_canister_settings
+                    .canister_id
+                    .and_then(|canister_id| CanisterId::try_from(canister_id).ok())
+                    .ok_or_else(|| GovernanceError::new(ErrorType::InvalidProposal))?;
+                let current_settings =
+                    fetch_current_canister_settings(&*self.env, canister_id).await;
+                (
+                    Some(canister_call),
+                    api::ProposalEffects::UpdateCanisterSettings(
+                        simulate_update_canister_settings(
+                            update_canister_settings,
+                            current_settings.as_ref(),
+                        ),
+                    ),
+                )
+            }
+            Action::ExecuteNnsFunction(execute_nns_function) => {
+                let effects = simulate_execute_nns_function(execute_nns_function)?;
+                let (canister_id, method_name) =
+                    NnsFunction::try_from(execute_nns_function.nns_function)
+                        .map_err(|_| GovernanceError::new(ErrorType::InvalidProposal))?
+                        .canister_and_function()?;
+                (
+                    Some(simulate_canister_call(
+                        canister_id,
+                        method_name,
+                        &execute_nns_function.payload,
+                    )),
+                    api::ProposalEffects::ExecuteNnsFunction(effects),
+                )
+            }
+            Action::ManageNetworkEconomics(proposed_network_economics) => {
+                // This was already done by validate_proposal, but we need the result here.
+                let new_network_economics = self
+                    .economics()
+                    .apply_changes_and_validate(proposed_network_economics)
+                    .map_err(|defects| {
+                        GovernanceError::new_with_message(
+                            ErrorType::InvalidProposal,
+                            defects.join("\n"),
+                        )
+                    })?;
+                (
+                    None,
+                    api::ProposalEffects::ManageNetworkEconomics(
+                        simulate_manage_network_economics(self.economics(), &new_network_economics),
+                    ),
+                )
+            }
+            _ => {
+                return Err(GovernanceError::new_with_message(
+                    ErrorType::InvalidCommand,
+                    format!(
+                        "Simulating {} proposals is not supported.",
+                        action.as_str_name()
+                    ),
+                ));
+            }
+        };
+
+        Ok(api::SimulateProposalResponse {
+            topic: Some(topic as i32),
+            canister_call,
+            effects: Some(effects),
+        })
+    }
+
     fn validate_execute_nns_function(
         &self,
         update: &ExecuteNnsFunction,
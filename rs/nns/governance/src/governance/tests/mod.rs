@@ -1489,6 +1489,198 @@ fn test_validate_execute_nns_function() {
     }
 }
 
+#[tokio::test]
+async fn test_simulate_proposal() {
+    use crate::{
+        pb::v1::update_canister_settings::{CanisterSettings, Controllers},
+        test_utils::ExpectedCallCanisterMethodCallArguments,
+    };
+    use ic_nervous_system_clients::{
+        canister_id_record::CanisterIdRecord,
+        canister_status::{CanisterStatusResult, CanisterStatusResultFromManagementCanister},
+    };
+
+    let current_controllers = vec![ROOT_CANISTER_ID.get()];
+    let canister_status = CanisterStatusResult::from(
+        CanisterStatusResultFromManagementCanister::dummy_with_controllers(
+            current_controllers.clone(),
+        ),
+    );
+    let governance = Governance::new(
+        GovernanceProto {
+            economics: Some(NetworkEconomics::with_default_values()),
+            ..Default::default()
+        },
+        Arc::new(MockEnvironment::new(
+            vec![(
+                ExpectedCallCanisterMethodCallArguments::new(
+                    ROOT_CANISTER_ID,
+                    "canister_status",
+                    Encode!(&CanisterIdRecord::from(REGISTRY_CANISTER_ID)).unwrap(),
+                ),
+                Ok(Encode!(&canister_status).unwrap()),
+            )],
+            100,
+        )),
+        Arc::new(StubIcpLedger {}),
+        Arc::new(StubCMC {}),
+        Box::new(MockRandomness::new()),
+    );
+
+    let new_proposal = |action: Action| Proposal {
+        title: Some("A Reasonable Title".to_string()),
+        summary: "Summary".to_string(),
+        url: "".to_string(),
+        action: Some(action),
+    };
+
+    // ManageNetworkEconomics: the changed fields are listed, along with their current values.
+    let default_network_economics = NetworkEconomics::with_default_values();
+    assert_eq!(
+        governance
+            .simulate_proposal(&new_proposal(Action::ManageNetworkEconomics(
+                NetworkEconomics {
+                    reject_cost_e8s: default_network_economics.reject_cost_e8s + 1,
+                    ..Default::default()
+                }
+            )))
+            .await,
+        Ok(api::SimulateProposalResponse {
+            topic: Some(Topic::NetworkEconomics as i32),
+            canister_call: None,
+            effects: Some(api::ProposalEffects::ManageNetworkEconomics(
+                api::ManageNetworkEconomicsEffects {
+                    changes: vec![api::FieldChange {
+                        field_name: Some("reject_cost_e8s".to_string()),
+                        old_value: Some(default_network_economics.reject_cost_e8s.to_string()),
+                        new_value: Some(
+                            (default_network_economics.reject_cost_e8s + 1).to_string()
+                        ),
+                    }],
+                }
+            )),
+        })
+    );
+
+    // UpdateCanisterSettings: the current settings are fetched from root.
+    let update_canister_settings = UpdateCanisterSettings {
+        canister_id: Some(REGISTRY_CANISTER_ID.get()),
+        settings: Some(CanisterSettings {
+            controllers: Some(Controllers {
+                controllers: vec![ROOT_CANISTER_ID.get(), PrincipalId::new_user_test_id(1)],
+            }),
+            wasm_memory_limit: Some(1 << 32),
+            ..Default::default()
+        }),
+    };
+    let response = governance
+        .simulate_proposal(&new_proposal(Action::UpdateCanisterSettings(
+            update_canister_settings.clone(),
+        )))
+        .await
+        .unwrap();
+    assert_eq!(
+        response.effects,
+        Some(api::ProposalEffects::UpdateCanisterSettings(
+            api::UpdateCanisterSettingsEffects {
+                canister_id: Some(REGISTRY_CANISTER_ID.get()),
+                changes: vec![
+                    api::FieldChange {
+                        field_name: Some("controllers".to_string()),
+                        old_value: Some(ROOT_CANISTER_ID.get().to_string()),
+                        new_value: Some(format!(
+                            "{}, {}",
+                            ROOT_CANISTER_ID.get(),
+                            PrincipalId::new_user_test_id(1)
+                        )),
+                    },
+                    api::FieldChange {
+                        field_name: Some("wasm_memory_limit".to_string()),
+                        old_value: Some("48".to_string()),
+                        new_value: Some("4294967296".to_string()),
+                    },
+                ],
+            }
+        ))
+    );
+
+    // ExecuteNnsFunction: the target canister and method are resolved, and the payload is decoded.
+    let payload = UpdateIcpXdrConversionRatePayload {
+        xdr_permyriad_per_icp: 1_000_000,
+        ..Default::default()
+    };
+    let encoded_payload = Encode!(&payload).unwrap();
+    let (canister_id, method_name) = NnsFunction::IcpXdrConversionRate
+        .canister_and_function()
+        .unwrap();
+    assert_eq!(
+        governance
+            .simulate_proposal(&new_proposal(Action::ExecuteNnsFunction(
+                ExecuteNnsFunction {
+                    nns_function: NnsFunction::IcpXdrConversionRate as i32,
+                    payload: encoded_payload.clone(),
+                }
+            )))
+            .await,
+        Ok(api::SimulateProposalResponse {
+            topic: Some(Topic::ExchangeRate as i32),
+            canister_call: Some(api::SimulatedCanisterCall {
+                canister_id: Some(canister_id.get()),
+                method_name: Some(method_name.to_string()),
+                payload_size_bytes: Some(encoded_payload.len() as u64),
+                payload_sha256: Some(ic_crypto_sha2::Sha256::hash(&encoded_payload).to_vec()),
+            }),
+            effects: Some(api::ProposalEffects::ExecuteNnsFunction(
+                api::ExecuteNnsFunctionEffects {
+                    nns_function: Some(NnsFunction::IcpXdrConversionRate as i32),
+                    payload: Some(api::NnsFunctionPayload::Decoded(
+                        api::DecodedNnsFunctionPayload {
+                            type_name: Some("UpdateIcpXdrConversionRatePayload".to_string()),
+                            rendering: Some(format!("{:#?}", payload)),
+                        }
+                    )),
+                    registry_changes: vec![],
+                }
+            )),
+        })
+    );
+
+    // Proposals that would not pass validation cannot be simulated either.
+    let err = governance
+        .simulate_proposal(&new_proposal(Action::ExecuteNnsFunction(
+            ExecuteNnsFunction {
+                nns_function: NnsFunction::IcpXdrConversionRate as i32,
+                payload: Encode!(&UpdateIcpXdrConversionRatePayload {
+                    xdr_permyriad_per_icp: 0,
+                    ..Default::default()
+                })
+                .unwrap(),
+            },
+        )))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.error_type,
+        ErrorType::InvalidProposal as i32,
+        "{:?}",
+        err
+    );
+
+    // Only some types of proposals are supported.
+    let err = governance
+        .simulate_proposal(&new_proposal(Action::Motion(Motion {
+            motion_text: "Do something".to_string(),
+        })))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.error_type,
+        ErrorType::InvalidCommand as i32,
+        "{:?}",
+        err
+    );
+}
+
 #[test]
 fn test_canister_and_function_no_unreachable() {
     use strum::IntoEnumIterator;
@@ -11,6 +11,7 @@ use std::collections::HashMap;
 pub mod call_canister;
 pub mod create_service_nervous_system;
 pub mod install_code;
+pub mod simulate_proposal;
 pub mod stop_or_start_canister;
 pub mod update_canister_settings;
 
@@ -0,0 +1,1415 @@
+//! Describes what executing a proposal would do, without executing (or even submitting) it. This
+//! backs the simulate_proposal method, so that reviewers do not have to decode raw payloads
+//! themselves.
+use super::invalid_proposal_error;
+use crate::{
+    decoder_config,
+    governance::{Environment, LOG_PREFIX},
+    pb::v1::{
+        update_canister_settings::LogVisibility, ExecuteNnsFunction, GovernanceError, InstallCode,
+        NetworkEconomics, NnsFunction, UpdateCanisterSettings,
+    },
+    proposals::call_canister::CallCanister,
+};
+
+use candid::{CandidType, Decode, Deserialize, Encode, Nat};
+use cycles_minting_canister::{
+    ChangeSubnetTypeAssignmentArgs, SetAuthorizedSubnetworkListArgs, UpdateSubnetTypeArgs,
+};
+use ic_base_types::{CanisterId, SubnetId};
+use ic_crypto_sha2::Sha256;
+use ic_management_canister_types_private::UninstallCodeArgs;
+use ic_nervous_system_clients::{
+    canister_id_record::CanisterIdRecord,
+    canister_status::{
+        CanisterStatusResult, DefiniteCanisterSettings, LogVisibility as RootLogVisibility,
+    },
+};
+use ic_nervous_system_root::change_canister::{AddCanisterRequest, StopOrStartCanisterRequest};
+use ic_nns_common::types::UpdateIcpXdrConversionRatePayload;
+use ic_nns_constants::ROOT_CANISTER_ID;
+use ic_nns_governance_api::{
+    bitcoin::BitcoinSetConfigProposal, subnet_rental::SubnetRentalRequest,
+    DecodedNnsFunctionPayload, ExecuteNnsFunctionEffects, FieldChange, InstallCodeEffects,
+    ManageNetworkEconomicsEffects, NnsFunctionPayload, RegistryRecordChange, SimulatedCanisterCall,
+    UnsupportedNnsFunctionPayload, UpdateCanisterSettingsEffects,
+};
+use ic_nns_handler_lifeline_interface::HardResetNnsRootToVersionPayload;
+use ic_protobuf::registry::{
+    dc::v1::AddOrRemoveDataCentersProposalPayload,
+    node_rewards::v2::UpdateNodeRewardsTableProposalPayload,
+};
+use ic_registry_keys::{
+    make_api_boundary_node_record_key, make_blessed_replica_versions_key,
+    make_canister_migrations_record_key, make_catch_up_package_contents_key,
+    make_chain_key_enabled_subnet_list_key, make_crypto_threshold_signing_pubkey_key,
+    make_data_center_record_key, make_firewall_config_record_key, make_firewall_rules_record_key,
+    make_hostos_version_key, make_node_operator_record_key, make_node_record_key,
+    make_provisional_whitelist_record_key, make_replica_version_key, make_routing_table_record_key,
+    make_subnet_list_record_key, make_subnet_record_key, make_unassigned_nodes_config_record_key,
+    NODE_REWARDS_TABLE_KEY,
+};
+use ic_sns_wasm::pb::v1::{
+    AddWasmRequest, InsertUpgradePathEntriesRequest, SnsCanisterType, UpdateSnsSubnetListRequest,
+};
+use registry_canister::mutations::{
+    complete_canister_migration::CompleteCanisterMigrationPayload,
+    do_add_api_boundary_nodes::AddApiBoundaryNodesPayload,
+    do_add_node_operator::AddNodeOperatorPayload,
+    do_add_nodes_to_subnet::AddNodesToSubnetPayload,
+    do_change_subnet_membership::ChangeSubnetMembershipPayload,
+    do_create_subnet::CreateSubnetPayload,
+    do_deploy_guestos_to_all_subnet_nodes::DeployGuestosToAllSubnetNodesPayload,
+    do_deploy_guestos_to_all_unassigned_nodes::DeployGuestosToAllUnassignedNodesPayload,
+    do_recover_subnet::RecoverSubnetPayload,
+    do_remove_api_boundary_nodes::RemoveApiBoundaryNodesPayload,
+    do_remove_node_operators::RemoveNodeOperatorsPayload,
+    do_remove_nodes_from_subnet::RemoveNodesFromSubnetPayload,
+    do_revise_elected_replica_versions::ReviseElectedGuestosVersionsPayload,
+    do_set_firewall_config::SetFirewallConfigPayload,
+    do_update_api_boundary_nodes_version::DeployGuestosToSomeApiBoundaryNodes,
+    do_update_elected_hostos_versions::ReviseElectedHostosVersionsPayload,
+    do_update_node_operator_config::UpdateNodeOperatorConfigPayload,
+    do_update_nodes_hostos_version::DeployHostosToSomeNodes,
+    do_update_ssh_readonly_access_for_all_unassigned_nodes::UpdateSshReadOnlyAccessForAllUnassignedNodesPayload,
+    do_update_subnet::UpdateSubnetPayload,
+    firewall::{AddFirewallRulesPayload, RemoveFirewallRulesPayload, UpdateFirewallRulesPayload},
+    node_management::do_remove_nodes::RemoveNodesPayload,
+    prepare_canister_migration::PrepareCanisterMigrationPayload,
+    reroute_canister_ranges::RerouteCanisterRangesPayload,
+};
+use std::fmt::{Debug, Display};
+
+/// Describes the call that Governance would make to execute a proposal.
+///
+/// For a few NnsFunctions (e.g. SubnetRentalRequest), the payload is transformed right before the
+/// call is made, because the transformed payload depends on the proposal ID. In those cases, this
+/// describes the payload as it appears in the proposal.
+pub(crate) fn simulate_canister_call(
+    canister_id: CanisterId,
+    method_name: &str,
+    payload: &[u8],
+) -> SimulatedCanisterCall {
+    SimulatedCanisterCall {
+        canister_id: Some(canister_id.get()),
+        method_name: Some(method_name.to_string()),
+        payload_size_bytes: Some(payload.len() as u64),
+        payload_sha256: Some(Sha256::hash(payload).to_vec()),
+    }
+}
+
+/// Like simulate_canister_call, but for proposal types that implement CallCanister.
+pub(crate) fn simulate_call_canister(
+    call_canister: &impl CallCanister,
+) -> Result<SimulatedCanisterCall, GovernanceError> {
+    let (canister_id, method_name) = call_canister.canister_and_function()?;
+    let payload = call_canister.payload()?;
+
+    Ok(simulate_canister_call(canister_id, method_name, &payload))
+}
+
+/// Assumes that install_code has already been validated.
+pub(crate) fn simulate_install_code(install_code: &InstallCode) -> InstallCodeEffects {
+    InstallCodeEffects {
+        canister_id: install_code.canister_id,
+        install_mode: install_code.install_mode,
+        wasm_module_sha256: install_code.wasm_module_hash.clone(),
+        arg_sha256: install_code.arg_hash.clone(),
+        stop_before_installing: Some(
+            !install_code
+                .skip_stopping_before_installing
+                .unwrap_or(false),
+        ),
+    }
+}
+
+/// Fetches the current settings of canister_id from the NNS root canister, which controls all NNS
+/// canisters except itself. Returns None if they cannot be obtained, in which case the old values
+/// are simply not shown.
+pub(crate) async fn fetch_current_canister_settings(
+    env: &dyn Environment,
+    canister_id: CanisterId,
+) -> Option<DefiniteCanisterSettings> {
+    // Root is controlled by lifeline, which does not expose the status of root.
+    if canister_id == ROOT_CANISTER_ID {
+        return None;
+    }
+
+    let request = Encode!(&CanisterIdRecord::from(canister_id)).ok()?;
+    let response = match env
+        .call_canister_method(ROOT_CANISTER_ID, "canister_status", request)
+        .await
+    {
+        Ok(response) => response,
+        Err((code, message)) => {
+            println!(
+                "{}Unable to get the status of canister {} from root: code {:?}: {}",
+                LOG_PREFIX, canister_id, code, message,
+            );
+            return None;
+        }
+    };
+
+    match Decode!([decoder_config()]; &response, CanisterStatusResult) {
+        Ok(canister_status) => Some(canister_status.settings),
+        Err(err) => {
+            println!(
+                "{}Unable to decode the status of canister {}: {}",
+                LOG_PREFIX, canister_id, err,
+            );
+            None
+        }
+    }
+}
+
+/// Assumes that update_canister_settings has already been validated. current_settings should be
+/// the result of fetch_current_canister_settings.
+pub(crate) fn simulate_update_canister_settings(
+    update_canister_settings: &UpdateCanisterSettings,
+    current_settings: Option<&DefiniteCanisterSettings>,
+) -> UpdateCanisterSettingsEffects {
+    let settings = update_canister_settings
+        .settings
+        .clone()
+        .unwrap_or_default();
+    // Unlike the Display implementation of Nat, this does not insert underscores.
+    let render_nat = |value: &Nat| value.0.to_string();
+
+    let mut changes = vec![];
+    let mut push_change =
+        |field_name: &str, old_value: Option<String>, new_value: Option<String>| {
+            if let Some(new_value) = new_value {
+                changes.push(FieldChange {
+                    field_name: Some(field_name.to_string()),
+                    old_value,
+                    new_value: Some(new_value),
+                });
+            }
+        };
+
+    push_change(
+        "controllers",
+        current_settings.map(|current| join(&current.controllers)),
+        settings
+            .controllers
+            .map(|controllers| join(&controllers.controllers)),
+    );
+    push_change(
+        "compute_allocation",
+        current_settings.and_then(|current| current.compute_allocation.as_ref().map(render_nat)),
+        settings.compute_allocation.map(|value| value.to_string()),
+    );
+    push_change(
+        "memory_allocation",
+        current_settings.and_then(|current| current.memory_allocation.as_ref().map(render_nat)),
+        settings.memory_allocation.map(|value| value.to_string()),
+    );
+    push_change(
+        "freezing_threshold",
+        current_settings.and_then(|current| current.freezing_threshold.as_ref().map(render_nat)),
+        settings.freezing_threshold.map(|value| value.to_string()),
+    );
+    push_change(
+        "log_visibility",
+        current_settings.and_then(|current| {
+            current
+                .log_visibility
+                .as_ref()
+                .map(|log_visibility| match log_visibility {
+                    RootLogVisibility::Controllers => "Controllers".to_string(),
+                    RootLogVisibility::Public => "Public".to_string(),
+                    RootLogVisibility::AllowedViewers(viewers) => {
+                        format!("AllowedViewers ({})", join(viewers))
+                    }
+                })
+        }),
+        settings.log_visibility.map(|log_visibility| {
+            match LogVisibility::try_from(log_visibility) {
+                Ok(log_visibility) => format!("{:?}", log_visibility),
+                Err(_) => format!("Unknown ({})", log_visibility),
+            }
+        }),
+    );
+    push_change(
+        "wasm_memory_limit",
+        current_settings.and_then(|current| current.wasm_memory_limit.as_ref().map(render_nat)),
+        settings.wasm_memory_limit.map(|value| value.to_string()),
+    );
+    push_change(
+        "wasm_memory_threshold",
+        current_settings.and_then(|current| current.wasm_memory_threshold.as_ref().map(render_nat)),
+        settings
+            .wasm_memory_threshold
+            .map(|value| value.to_string()),
+    );
+
+    UpdateCanisterSettingsEffects {
+        canister_id: update_canister_settings.canister_id,
+        changes,
+    }
+}
+
+/// Decodes the payload of execute_nns_function, and describes the registry records that would be
+/// written if the NnsFunction targets the registry. Returns Err if the payload cannot be decoded
+/// into the type that the target canister expects, since execution would then fail.
+pub(crate) fn simulate_execute_nns_function(
+    execute_nns_function: &ExecuteNnsFunction,
+) -> Result<ExecuteNnsFunctionEffects, GovernanceError> {
+    let nns_function = NnsFunction::try_from(execute_nns_function.nns_function).map_err(|_| {
+        invalid_proposal_error(&format!(
+            "invalid NnsFunction id: {}",
+            execute_nns_function.nns_function
+        ))
+    })?;
+
+    let (payload, registry_changes) =
+        decode_nns_function_payload(nns_function, &execute_nns_function.payload)
+            .map_err(|err| invalid_proposal_error(&err))?;
+
+    Ok(ExecuteNnsFunctionEffects {
+        nns_function: Some(execute_nns_function.nns_function),
+        payload: Some(payload),
+        registry_changes,
+    })
+}
+
+type DecodedPayloadAndRegistryChanges = (NnsFunctionPayload, Vec<RegistryRecordChange>);
+
+/// Every NnsFunction is handled explicitly, so that adding one requires deciding how to describe
+/// its payload.
+fn decode_nns_function_payload(
+    nns_function: NnsFunction,
+    payload: &[u8],
+) -> Result<DecodedPayloadAndRegistryChanges, String> {
+    fn decode<T>(payload: &[u8]) -> Result<T, String>
+    where
+        T: CandidType + for<'de> Deserialize<'de>,
+    {
+        Decode!([decoder_config()]; payload, T).map_err(|err| {
+            format!(
+                "the payload could not be decoded into a {}: {}",
+                type_name::<T>(),
+                err
+            )
+        })
+    }
+
+    fn type_name<T>() -> String {
+        std::any::type_name::<T>()
+            .rsplit("::")
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn decode_and_describe<T>(
+        payload: &[u8],
+        describe_registry_changes: impl FnOnce(&T) -> Vec<RegistryRecordChange>,
+    ) -> Result<DecodedPayloadAndRegistryChanges, String>
+    where
+        T: CandidType + for<'de> Deserialize<'de> + Debug,
+    {
+        let decoded_payload = decode::<T>(payload)?;
+        let registry_changes = describe_registry_changes(&decoded_payload);
+        let payload = NnsFunctionPayload::Decoded(DecodedNnsFunctionPayload {
+            type_name: Some(type_name::<T>()),
+            rendering: Some(format!("{:#?}", decoded_payload)),
+        });
+        Ok((payload, registry_changes))
+    }
+
+    fn decode_only<T>(payload: &[u8]) -> Result<DecodedPayloadAndRegistryChanges, String>
+    where
+        T: CandidType + for<'de> Deserialize<'de> + Debug,
+    {
+        decode_and_describe::<T>(payload, |_| vec![])
+    }
+
+    fn unsupported(reason: &str) -> Result<DecodedPayloadAndRegistryChanges, String> {
+        let payload = NnsFunctionPayload::Unsupported(UnsupportedNnsFunctionPayload {
+            reason: Some(reason.to_string()),
+        });
+        Ok((payload, vec![]))
+    }
+
+    match nns_function {
+        NnsFunction::Unspecified => unsupported("The NnsFunction is not specified."),
+
+        // Registry.
+        NnsFunction::AssignNoid => decode_and_describe(payload, describe_add_node_operator),
+        NnsFunction::CreateSubnet => decode_and_describe(payload, describe_create_subnet),
+        NnsFunction::AddNodeToSubnet => decode_and_describe(payload, describe_add_nodes_to_subnet),
+        NnsFunction::RemoveNodesFromSubnet => {
+            decode_and_describe(payload, describe_remove_nodes_from_subnet)
+        }
+        NnsFunction::ChangeSubnetMembership => {
+            decode_and_describe(payload, describe_change_subnet_membership)
+        }
+        NnsFunction::RecoverSubnet => decode_and_describe(payload, describe_recover_subnet),
+        NnsFunction::ReviseElectedGuestosVersions => {
+            decode_and_describe(payload, describe_revise_elected_guestos_versions)
+        }
+        NnsFunction::UpdateNodeOperatorConfig => {
+            decode_and_describe(payload, describe_update_node_operator_config)
+        }
+        NnsFunction::DeployGuestosToAllSubnetNodes => {
+            decode_and_describe(payload, describe_deploy_guestos_to_all_subnet_nodes)
+        }
+        NnsFunction::ReviseElectedHostosVersions => {
+            decode_and_describe(payload, describe_revise_elected_hostos_versions)
+        }
+        NnsFunction::DeployHostosToSomeNodes => {
+            decode_and_describe(payload, describe_deploy_hostos_to_some_nodes)
+        }
+        NnsFunction::UpdateConfigOfSubnet => decode_and_describe(payload, describe_update_subnet),
+        NnsFunction::ClearProvisionalWhitelist => {
+            decode_and_describe(payload, describe_clear_provisional_whitelist)
+        }
+        NnsFunction::SetFirewallConfig => {
+            decode_and_describe(payload, describe_set_firewall_config)
+        }
+        NnsFunction::AddFirewallRules => decode_and_describe(payload, describe_add_firewall_rules),
+        NnsFunction::RemoveFirewallRules => {
+            decode_and_describe(payload, describe_remove_firewall_rules)
+        }
+        NnsFunction::UpdateFirewallRules => {
+            decode_and_describe(payload, describe_update_firewall_rules)
+        }
+        NnsFunction::RemoveNodes => decode_and_describe(payload, describe_remove_nodes),
+        NnsFunction::UpdateNodeRewardsTable => {
+            decode_and_describe(payload, describe_update_node_rewards_table)
+        }
+        NnsFunction::AddOrRemoveDataCenters => {
+            decode_and_describe(payload, describe_add_or_remove_data_centers)
+        }
+        NnsFunction::RemoveNodeOperators => {
+            decode_and_describe(payload, describe_remove_node_operators)
+        }
+        NnsFunction::RerouteCanisterRanges => {
+            decode_and_describe(payload, describe_reroute_canister_ranges)
+        }
+        NnsFunction::PrepareCanisterMigration => {
+            decode_and_describe(payload, describe_prepare_canister_migration)
+        }
+        NnsFunction::CompleteCanisterMigration => {
+            decode_and_describe(payload, describe_complete_canister_migration)
+        }
+        NnsFunction::AddApiBoundaryNodes => {
+            decode_and_describe(payload, describe_add_api_boundary_nodes)
+        }
+        NnsFunction::RemoveApiBoundaryNodes => {
+            decode_and_describe(payload, describe_remove_api_boundary_nodes)
+        }
+        NnsFunction::DeployGuestosToSomeApiBoundaryNodes => {
+            decode_and_describe(payload, describe_deploy_guestos_to_some_api_boundary_nodes)
+        }
+        NnsFunction::DeployGuestosToAllUnassignedNodes => {
+            decode_and_describe(payload, describe_deploy_guestos_to_all_unassigned_nodes)
+        }
+        NnsFunction::UpdateSshReadonlyAccessForAllUnassignedNodes => decode_and_describe(
+            payload,
+            describe_update_ssh_readonly_access_for_all_unassigned_nodes,
+        ),
+
+        // Root and lifeline. The Debug implementations of these payloads only show the hashes of
+        // WASM modules, not the modules themselves.
+        NnsFunction::NnsCanisterInstall => decode_only::<AddCanisterRequest>(payload),
+        NnsFunction::StopOrStartNnsCanister => decode_only::<StopOrStartCanisterRequest>(payload),
+        NnsFunction::HardResetNnsRootToVersion => {
+            decode_only::<HardResetNnsRootToVersionPayload>(payload)
+        }
+        NnsFunction::BitcoinSetConfig => decode_only::<BitcoinSetConfigProposal>(payload),
+
+        // Management canister.
+        NnsFunction::UninstallCode => decode_only::<UninstallCodeArgs>(payload),
+
+        // Cycles minting canister.
+        NnsFunction::IcpXdrConversionRate => {
+            decode_only::<UpdateIcpXdrConversionRatePayload>(payload)
+        }
+        NnsFunction::SetAuthorizedSubnetworks => {
+            decode_only::<SetAuthorizedSubnetworkListArgs>(payload)
+        }
+        NnsFunction::UpdateSubnetType => decode_only::<UpdateSubnetTypeArgs>(payload),
+        NnsFunction::ChangeSubnetTypeAssignment => {
+            decode_only::<ChangeSubnetTypeAssignmentArgs>(payload)
+        }
+
+        // SNS-W.
+        NnsFunction::AddSnsWasm => {
+            // Unlike the other payloads, the derived Debug implementation would show the whole
+            // WASM module.
+            let add_wasm_request = decode::<AddWasmRequest>(payload)?;
+            let sns_wasm = add_wasm_request.wasm.unwrap_or_default();
+            let canister_type = match SnsCanisterType::try_from(sns_wasm.canister_type) {
+                Ok(canister_type) => format!("{:?}", canister_type),
+                Err(_) => format!("Unknown ({})", sns_wasm.canister_type),
+            };
+            let rendering = format!(
+                "AddWasmRequest {{\n    \
+                     canister_type: {},\n    \
+                     wasm_size_bytes: {},\n    \
+                     wasm_sha256: {:x?},\n    \
+                     hash: {:x?},\n\
+                 }}",
+                canister_type,
+                sns_wasm.wasm.len(),
+                Sha256::hash(&sns_wasm.wasm),
+                add_wasm_request.hash,
+            );
+            let payload = NnsFunctionPayload::Decoded(DecodedNnsFunctionPayload {
+                type_name: Some(type_name::<AddWasmRequest>()),
+                rendering: Some(rendering),
+            });
+            Ok((payload, vec![]))
+        }
+        NnsFunction::UpdateSnsWasmSnsSubnetIds => {
+            decode_only::<UpdateSnsSubnetListRequest>(payload)
+        }
+        NnsFunction::InsertSnsWasmUpgradePathEntries => {
+            decode_only::<InsertUpgradePathEntriesRequest>(payload)
+        }
+
+        // Subnet rental canister.
+        NnsFunction::SubnetRentalRequest => decode_only::<SubnetRentalRequest>(payload),
+
+        // Proposals with these NnsFunctions do not pass validation.
+        NnsFunction::BlessReplicaVersion
+        | NnsFunction::RetireReplicaVersion
+        | NnsFunction::UpdateElectedHostosVersions
+        | NnsFunction::UpdateAllowedPrincipals
+        | NnsFunction::UpdateApiBoundaryNodesVersion
+        | NnsFunction::UpdateUnassignedNodesConfig
+        | NnsFunction::UpdateNodesHostosVersion
+        | NnsFunction::NnsCanisterUpgrade
+        | NnsFunction::NnsRootUpgrade => unsupported("The NnsFunction is obsolete."),
+    }
+}
+
+fn join<T: Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn render_option<T: Display>(value: &Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "<none>".to_string(),
+    }
+}
+
+fn registry_record_change(key: String, description: String) -> RegistryRecordChange {
+    RegistryRecordChange {
+        key: Some(key),
+        description: Some(description),
+    }
+}
+
+/// For records whose keys are only known at execution time.
+fn unkeyed_registry_record_change(description: String) -> RegistryRecordChange {
+    RegistryRecordChange {
+        key: None,
+        description: Some(description),
+    }
+}
+
+fn describe_add_node_operator(payload: &AddNodeOperatorPayload) -> Vec<RegistryRecordChange> {
+    let description = format!(
+        "Add a node operator of node provider {}, in data center {:?}, with a node allowance of \
+         {}.",
+        render_option(&payload.node_provider_principal_id),
+        payload.dc_id,
+        payload.node_allowance,
+    );
+    match payload.node_operator_principal_id {
+        Some(node_operator_id) => vec![registry_record_change(
+            make_node_operator_record_key(node_operator_id),
+            description,
+        )],
+        None => vec![unkeyed_registry_record_change(description)],
+    }
+}
+
+fn describe_create_subnet(payload: &CreateSubnetPayload) -> Vec<RegistryRecordChange> {
+    let mut changes = vec![registry_record_change(
+        make_subnet_list_record_key(),
+        "Add the new subnet.".to_string(),
+    )];
+
+    let new_subnet_records = [
+        (
+            "subnet record",
+            format!(
+                "with nodes [{}] and replica version {}",
+                join(&payload.node_ids),
+                payload.replica_version_id
+            ),
+        ),
+        (
+            "catch-up package contents",
+            "containing the initial DKG transcripts".to_string(),
+        ),
+        (
+            "threshold signing public key",
+            "generated by the initial DKG".to_string(),
+        ),
+    ];
+    match payload.subnet_id_override {
+        Some(subnet_id) => {
+            let subnet_id = SubnetId::from(subnet_id);
+            let keys = [
+                make_subnet_record_key(subnet_id),
+                make_catch_up_package_contents_key(subnet_id),
+                make_crypto_threshold_signing_pubkey_key(subnet_id),
+            ];
+            for (key, (record, details)) in keys.into_iter().zip(new_subnet_records) {
+                changes.push(registry_record_change(
+                    key,
+                    format!("Add the {} of the new subnet, {}.", record, details),
+                ));
+            }
+        }
+        None => {
+            // The ID of the subnet is derived from the initial DKG transcripts.
+            for (record, details) in new_subnet_records {
+                changes.push(unkeyed_registry_record_change(format!(
+                    "Add the {} of the new subnet, {}.",
+                    record, details
+                )));
+            }
+        }
+    }
+
+    changes.push(registry_record_change(
+        make_routing_table_record_key(),
+        "Assign a range of canister IDs to the new subnet.".to_string(),
+    ));
+    changes
+}
+
+fn describe_add_nodes_to_subnet(payload: &AddNodesToSubnetPayload) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        make_subnet_record_key(SubnetId::from(payload.subnet_id)),
+        format!("Add nodes [{}] to the subnet.", join(&payload.node_ids)),
+    )]
+}
+
+fn describe_remove_nodes_from_subnet(
+    payload: &RemoveNodesFromSubnetPayload,
+) -> Vec<RegistryRecordChange> {
+    vec![unkeyed_registry_record_change(format!(
+        "Remove nodes [{}] from the subnet records of the subnets that they are members of.",
+        join(&payload.node_ids)
+    ))]
+}
+
+fn describe_change_subnet_membership(
+    payload: &ChangeSubnetMembershipPayload,
+) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        make_subnet_record_key(SubnetId::from(payload.subnet_id)),
+        format!(
+            "Add nodes [{}] to, and remove nodes [{}] from the subnet.",
+            join(&payload.node_ids_add),
+            join(&payload.node_ids_remove)
+        ),
+    )]
+}
+
+fn describe_recover_subnet(payload: &RecoverSubnetPayload) -> Vec<RegistryRecordChange> {
+    let subnet_id = SubnetId::from(payload.subnet_id);
+    let mut changes = vec![];
+
+    let mut subnet_record_updates = vec![];
+    if let Some(replacement_nodes) = &payload.replacement_nodes {
+        subnet_record_updates.push(format!(
+            "replace the members of the subnet with nodes [{}]",
+            join(replacement_nodes)
+        ));
+    }
+    if payload.chain_key_config.is_some() {
+        subnet_record_updates.push("set the chain key config".to_string());
+    }
+    if !subnet_record_updates.is_empty() {
+        changes.push(registry_record_change(
+            make_subnet_record_key(subnet_id),
+            format!(
+                "Update the subnet record: {}.",
+                subnet_record_updates.join("; ")
+            ),
+        ));
+    }
+    if payload.replacement_nodes.is_some() {
+        changes.push(registry_record_change(
+            make_crypto_threshold_signing_pubkey_key(subnet_id),
+            "Set the threshold signing public key generated for the replacement nodes.".to_string(),
+        ));
+    }
+    changes.push(registry_record_change(
+        make_catch_up_package_contents_key(subnet_id),
+        format!(
+            "Set the recovery catch-up package at height {} with state hash {:x?}.",
+            payload.height, payload.state_hash
+        ),
+    ));
+    changes
+}
+
+fn describe_revise_elected_guestos_versions(
+    payload: &ReviseElectedGuestosVersionsPayload,
+) -> Vec<RegistryRecordChange> {
+    let mut changes = vec![];
+    if let Some(version) = &payload.replica_version_to_elect {
+        changes.push(registry_record_change(
+            make_replica_version_key(version),
+            format!(
+                "Add the replica version, with release package SHA-256 {} and URLs [{}].",
+                render_option(&payload.release_package_sha256_hex),
+                payload.release_package_urls.join(", ")
+            ),
+        ));
+    }
+    for version in &payload.replica_versions_to_unelect {
+        changes.push(registry_record_change(
+            make_replica_version_key(version),
+            "Delete the replica version.".to_string(),
+        ));
+    }
+    changes.push(registry_record_change(
+        make_blessed_replica_versions_key(),
+        format!(
+            "Add {} to, and remove [{}] from the elected replica versions.",
+            render_option(&payload.replica_version_to_elect),
+            payload.replica_versions_to_unelect.join(", ")
+        ),
+    ));
+    changes
+}
+
+fn describe_update_node_operator_config(
+    payload: &UpdateNodeOperatorConfigPayload,
+) -> Vec<RegistryRecordChange> {
+    let description =
+        "Update the fields of the node operator record that are set in the payload.".to_string();
+    match payload.node_operator_id {
+        Some(node_operator_id) => vec![registry_record_change(
+            make_node_operator_record_key(node_operator_id),
+            description,
+        )],
+        None => vec![unkeyed_registry_record_change(description)],
+    }
+}
+
+fn describe_deploy_guestos_to_all_subnet_nodes(
+    payload: &DeployGuestosToAllSubnetNodesPayload,
+) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        make_subnet_record_key(SubnetId::from(payload.subnet_id)),
+        format!(
+            "Set the replica version of the subnet to {}.",
+            payload.replica_version_id
+        ),
+    )]
+}
+
+fn describe_revise_elected_hostos_versions(
+    payload: &ReviseElectedHostosVersionsPayload,
+) -> Vec<RegistryRecordChange> {
+    let mut changes = vec![];
+    if let Some(version) = &payload.hostos_version_to_elect {
+        changes.push(registry_record_change(
+            make_hostos_version_key(version),
+            format!(
+                "Add the HostOS version, with release package SHA-256 {} and URLs [{}].",
+                render_option(&payload.release_package_sha256_hex),
+                payload.release_package_urls.join(", ")
+            ),
+        ));
+    }
+    for version in &payload.hostos_versions_to_unelect {
+        changes.push(registry_record_change(
+            make_hostos_version_key(version),
+            "Delete the HostOS version.".to_string(),
+        ));
+    }
+    changes
+}
+
+fn describe_deploy_hostos_to_some_nodes(
+    payload: &DeployHostosToSomeNodes,
+) -> Vec<RegistryRecordChange> {
+    payload
+        .node_ids
+        .iter()
+        .map(|node_id| {
+            registry_record_change(
+                make_node_record_key(*node_id),
+                format!(
+                    "Set the HostOS version of the node to {}.",
+                    render_option(&payload.hostos_version_id)
+                ),
+            )
+        })
+        .collect()
+}
+
+fn describe_update_subnet(payload: &UpdateSubnetPayload) -> Vec<RegistryRecordChange> {
+    let mut changes = vec![registry_record_change(
+        make_subnet_record_key(payload.subnet_id),
+        "Update the fields of the subnet record that are set in the payload.".to_string(),
+    )];
+    for key_id in payload.chain_key_signing_enable.iter().flatten() {
+        changes.push(registry_record_change(
+            make_chain_key_enabled_subnet_list_key(key_id),
+            "Add the subnet to the subnets that sign with the key.".to_string(),
+        ));
+    }
+    for key_id in payload.chain_key_signing_disable.iter().flatten() {
+        changes.push(registry_record_change(
+            make_chain_key_enabled_subnet_list_key(key_id),
+            "Remove the subnet from the subnets that sign with the key.".to_string(),
+        ));
+    }
+    changes
+}
+
+fn describe_clear_provisional_whitelist(_payload: &()) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        make_provisional_whitelist_record_key(),
+        "Remove all principals from the provisional whitelist.".to_string(),
+    )]
+}
+
+fn describe_set_firewall_config(payload: &SetFirewallConfigPayload) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        make_firewall_config_record_key(),
+        format!(
+            "Replace the firewall config, with IPv4 prefixes [{}] and IPv6 prefixes [{}].",
+            payload.ipv4_prefixes.join(", "),
+            payload.ipv6_prefixes.join(", ")
+        ),
+    )]
+}
+
+fn describe_add_firewall_rules(payload: &AddFirewallRulesPayload) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        make_firewall_rules_record_key(&payload.scope),
+        format!(
+            "Add {} rules at positions {:?}.",
+            payload.rules.len(),
+            payload.positions
+        ),
+    )]
+}
+
+fn describe_remove_firewall_rules(
+    payload: &RemoveFirewallRulesPayload,
+) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        make_firewall_rules_record_key(&payload.scope),
+        format!("Remove the rules at positions {:?}.", payload.positions),
+    )]
+}
+
+fn describe_update_firewall_rules(
+    payload: &UpdateFirewallRulesPayload,
+) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        make_firewall_rules_record_key(&payload.scope),
+        format!(
+            "Replace the rules at positions {:?} with {} rules.",
+            payload.positions,
+            payload.rules.len()
+        ),
+    )]
+}
+
+fn describe_remove_nodes(payload: &RemoveNodesPayload) -> Vec<RegistryRecordChange> {
+    let mut changes = payload
+        .node_ids
+        .iter()
+        .map(|node_id| {
+            registry_record_change(
+                make_node_record_key(*node_id),
+                "Delete the node, along with its keys and TLS certificate.".to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    changes.push(unkeyed_registry_record_change(
+        "Increase the node allowance of the node operators of the removed nodes by the number of \
+         their removed nodes."
+            .to_string(),
+    ));
+    changes
+}
+
+fn describe_update_node_rewards_table(
+    payload: &UpdateNodeRewardsTableProposalPayload,
+) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        NODE_REWARDS_TABLE_KEY.to_string(),
+        format!(
+            "Set the rewards of the regions [{}], keeping the other regions.",
+            payload
+                .new_entries
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    )]
+}
+
+fn describe_add_or_remove_data_centers(
+    payload: &AddOrRemoveDataCentersProposalPayload,
+) -> Vec<RegistryRecordChange> {
+    let added = payload.data_centers_to_add.iter().map(|data_center| {
+        registry_record_change(
+            make_data_center_record_key(&data_center.id),
+            format!(
+                "Add the data center in region {}, owned by {}.",
+                data_center.region, data_center.owner
+            ),
+        )
+    });
+    let removed = payload.data_centers_to_remove.iter().map(|data_center_id| {
+        registry_record_change(
+            make_data_center_record_key(data_center_id),
+            "Delete the data center.".to_string(),
+        )
+    });
+    added.chain(removed).collect()
+}
+
+fn describe_remove_node_operators(
+    payload: &RemoveNodeOperatorsPayload,
+) -> Vec<RegistryRecordChange> {
+    payload
+        .principal_ids_to_remove()
+        .into_iter()
+        .map(|node_operator_id| {
+            registry_record_change(
+                make_node_operator_record_key(node_operator_id),
+                "Delete the node operator, unless it still has nodes.".to_string(),
+            )
+        })
+        .collect()
+}
+
+fn describe_reroute_canister_ranges(
+    payload: &RerouteCanisterRangesPayload,
+) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        make_routing_table_record_key(),
+        format!(
+            "Route the canister ID ranges {:?} to subnet {} instead of subnet {}.",
+            payload.reassigned_canister_ranges, payload.destination_subnet, payload.source_subnet
+        ),
+    )]
+}
+
+fn describe_prepare_canister_migration(
+    payload: &PrepareCanisterMigrationPayload,
+) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        make_canister_migrations_record_key(),
+        format!(
+            "Add the migration of the canister ID ranges {:?} from subnet {} to subnet {}.",
+            payload.canister_id_ranges, payload.source_subnet, payload.destination_subnet
+        ),
+    )]
+}
+
+fn describe_complete_canister_migration(
+    payload: &CompleteCanisterMigrationPayload,
+) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        make_canister_migrations_record_key(),
+        format!(
+            "Remove the migration of the canister ID ranges {:?} along [{}].",
+            payload.canister_id_ranges,
+            join(&payload.migration_trace)
+        ),
+    )]
+}
+
+fn describe_add_api_boundary_nodes(
+    payload: &AddApiBoundaryNodesPayload,
+) -> Vec<RegistryRecordChange> {
+    payload
+        .node_ids
+        .iter()
+        .map(|node_id| {
+            registry_record_change(
+                make_api_boundary_node_record_key(*node_id),
+                format!(
+                    "Turn the node into an API boundary node running version {}.",
+                    payload.version
+                ),
+            )
+        })
+        .collect()
+}
+
+fn describe_remove_api_boundary_nodes(
+    payload: &RemoveApiBoundaryNodesPayload,
+) -> Vec<RegistryRecordChange> {
+    payload
+        .node_ids
+        .iter()
+        .map(|node_id| {
+            registry_record_change(
+                make_api_boundary_node_record_key(*node_id),
+                "Delete the API boundary node.".to_string(),
+            )
+        })
+        .collect()
+}
+
+fn describe_deploy_guestos_to_some_api_boundary_nodes(
+    payload: &DeployGuestosToSomeApiBoundaryNodes,
+) -> Vec<RegistryRecordChange> {
+    payload
+        .node_ids
+        .iter()
+        .map(|node_id| {
+            registry_record_change(
+                make_api_boundary_node_record_key(*node_id),
+                format!(
+                    "Set the version of the API boundary node to {}.",
+                    payload.version
+                ),
+            )
+        })
+        .collect()
+}
+
+fn describe_deploy_guestos_to_all_unassigned_nodes(
+    payload: &DeployGuestosToAllUnassignedNodesPayload,
+) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        make_unassigned_nodes_config_record_key(),
+        format!(
+            "Set the replica version of unassigned nodes to {}.",
+            payload.elected_replica_version
+        ),
+    )]
+}
+
+fn describe_update_ssh_readonly_access_for_all_unassigned_nodes(
+    payload: &UpdateSshReadOnlyAccessForAllUnassignedNodesPayload,
+) -> Vec<RegistryRecordChange> {
+    vec![registry_record_change(
+        make_unassigned_nodes_config_record_key(),
+        format!(
+            "Set the SSH read-only access keys of unassigned nodes ({} keys).",
+            payload.ssh_readonly_keys.len()
+        ),
+    )]
+}
+
+/// Lists the fields of NetworkEconomics that differ between current and new.
+pub(crate) fn simulate_manage_network_economics(
+    current: &NetworkEconomics,
+    new: &NetworkEconomics,
+) -> ManageNetworkEconomicsEffects {
+    let mut changes = vec![];
+    let mut compare = |field_name: &str, old_value: String, new_value: String| {
+        if old_value != new_value {
+            changes.push(FieldChange {
+                field_name: Some(field_name.to_string()),
+                old_value: Some(old_value),
+                new_value: Some(new_value),
+            });
+        }
+    };
+
+    compare(
+        "reject_cost_e8s",
+        current.reject_cost_e8s.to_string(),
+        new.reject_cost_e8s.to_string(),
+    );
+    compare(
+        "neuron_minimum_stake_e8s",
+        current.neuron_minimum_stake_e8s.to_string(),
+        new.neuron_minimum_stake_e8s.to_string(),
+    );
+    compare(
+        "neuron_management_fee_per_proposal_e8s",
+        current.neuron_management_fee_per_proposal_e8s.to_string(),
+        new.neuron_management_fee_per_proposal_e8s.to_string(),
+    );
+    compare(
+        "minimum_icp_xdr_rate",
+        current.minimum_icp_xdr_rate.to_string(),
+        new.minimum_icp_xdr_rate.to_string(),
+    );
+    compare(
+        "neuron_spawn_dissolve_delay_seconds",
+        current.neuron_spawn_dissolve_delay_seconds.to_string(),
+        new.neuron_spawn_dissolve_delay_seconds.to_string(),
+    );
+    compare(
+        "maximum_node_provider_rewards_e8s",
+        current.maximum_node_provider_rewards_e8s.to_string(),
+        new.maximum_node_provider_rewards_e8s.to_string(),
+    );
+    compare(
+        "transaction_fee_e8s",
+        current.transaction_fee_e8s.to_string(),
+        new.transaction_fee_e8s.to_string(),
+    );
+    compare(
+        "max_proposals_to_keep_per_topic",
+        current.max_proposals_to_keep_per_topic.to_string(),
+        new.max_proposals_to_keep_per_topic.to_string(),
+    );
+
+    let current_neurons_fund_economics = current.neurons_fund_economics.clone().unwrap_or_default();
+    let new_neurons_fund_economics = new.neurons_fund_economics.clone().unwrap_or_default();
+    compare(
+        "neurons_fund_economics.max_theoretical_neurons_fund_participation_amount_xdr",
+        format!(
+            "{:?}",
+            current_neurons_fund_economics.max_theoretical_neurons_fund_participation_amount_xdr
+        ),
+        format!(
+            "{:?}",
+            new_neurons_fund_economics.max_theoretical_neurons_fund_participation_amount_xdr
+        ),
+    );
+    compare(
+        "neurons_fund_economics.neurons_fund_matched_funding_curve_coefficients",
+        format!(
+            "{:?}",
+            current_neurons_fund_economics.neurons_fund_matched_funding_curve_coefficients
+        ),
+        format!(
+            "{:?}",
+            new_neurons_fund_economics.neurons_fund_matched_funding_curve_coefficients
+        ),
+    );
+    compare(
+        "neurons_fund_economics.minimum_icp_xdr_rate",
+        format!("{:?}", current_neurons_fund_economics.minimum_icp_xdr_rate),
+        format!("{:?}", new_neurons_fund_economics.minimum_icp_xdr_rate),
+    );
+    compare(
+        "neurons_fund_economics.maximum_icp_xdr_rate",
+        format!("{:?}", current_neurons_fund_economics.maximum_icp_xdr_rate),
+        format!("{:?}", new_neurons_fund_economics.maximum_icp_xdr_rate),
+    );
+
+    let current_voting_power_economics = current.voting_power_economics.unwrap_or_default();
+    let new_voting_power_economics = new.voting_power_economics.unwrap_or_default();
+    compare(
+        "voting_power_economics.start_reducing_voting_power_after_seconds",
+        format!(
+            "{:?}",
+            current_voting_power_economics.start_reducing_voting_power_after_seconds
+        ),
+        format!(
+            "{:?}",
+            new_voting_power_economics.start_reducing_voting_power_after_seconds
+        ),
+    );
+    compare(
+        "voting_power_economics.clear_following_after_seconds",
+        format!(
+            "{:?}",
+            current_voting_power_economics.clear_following_after_seconds
+        ),
+        format!(
+            "{:?}",
+            new_voting_power_economics.clear_following_after_seconds
+        ),
+    );
+    compare(
+        "voting_power_economics.neuron_minimum_dissolve_delay_to_vote_seconds",
+        format!(
+            "{:?}",
+            current_voting_power_economics.neuron_minimum_dissolve_delay_to_vote_seconds
+        ),
+        format!(
+            "{:?}",
+            new_voting_power_economics.neuron_minimum_dissolve_delay_to_vote_seconds
+        ),
+    );
+
+    ManageNetworkEconomicsEffects { changes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::pb::v1::{
+        governance_error::ErrorType,
+        install_code::CanisterInstallMode,
+        update_canister_settings::{CanisterSettings, Controllers},
+        VotingPowerEconomics,
+    };
+    use candid::Encode;
+    use ic_base_types::{NodeId, PrincipalId};
+    use ic_nervous_system_clients::canister_status::CanisterStatusResultFromManagementCanister;
+    use ic_nns_constants::{REGISTRY_CANISTER_ID, ROOT_CANISTER_ID};
+
+    #[test]
+    fn test_simulate_install_code() {
+        let install_code = InstallCode {
+            canister_id: Some(REGISTRY_CANISTER_ID.get()),
+            wasm_module: Some(vec![1, 2, 3]),
+            install_mode: Some(CanisterInstallMode::Upgrade as i32),
+            arg: Some(vec![4, 5, 6]),
+            skip_stopping_before_installing: None,
+            wasm_module_hash: Some(Sha256::hash(&[1, 2, 3]).to_vec()),
+            arg_hash: Some(Sha256::hash(&[4, 5, 6]).to_vec()),
+        };
+
+        assert_eq!(
+            simulate_install_code(&install_code),
+            InstallCodeEffects {
+                canister_id: Some(REGISTRY_CANISTER_ID.get()),
+                install_mode: Some(CanisterInstallMode::Upgrade as i32),
+                wasm_module_sha256: Some(Sha256::hash(&[1, 2, 3]).to_vec()),
+                arg_sha256: Some(Sha256::hash(&[4, 5, 6]).to_vec()),
+                stop_before_installing: Some(true),
+            }
+        );
+
+        // The call goes to root, which then upgrades the target canister.
+        let canister_call = simulate_call_canister(&install_code).unwrap();
+        assert_eq!(canister_call.canister_id, Some(ROOT_CANISTER_ID.get()));
+        assert_eq!(
+            canister_call.method_name,
+            Some("change_nns_canister".to_string())
+        );
+        let payload = install_code.payload().unwrap();
+        assert_eq!(canister_call.payload_size_bytes, Some(payload.len() as u64));
+        assert_eq!(
+            canister_call.payload_sha256,
+            Some(Sha256::hash(&payload).to_vec())
+        );
+    }
+
+    #[test]
+    fn test_simulate_update_canister_settings() {
+        let update_canister_settings = UpdateCanisterSettings {
+            canister_id: Some(REGISTRY_CANISTER_ID.get()),
+            settings: Some(CanisterSettings {
+                controllers: Some(Controllers {
+                    controllers: vec![ROOT_CANISTER_ID.get(), PrincipalId::new_user_test_id(1)],
+                }),
+                log_visibility: Some(LogVisibility::Public as i32),
+                wasm_memory_limit: Some(1 << 32),
+                ..Default::default()
+            }),
+        };
+
+        let new_field_change = |field_name: &str, new_value: String| FieldChange {
+            field_name: Some(field_name.to_string()),
+            old_value: None,
+            new_value: Some(new_value),
+        };
+        assert_eq!(
+            simulate_update_canister_settings(&update_canister_settings, None),
+            UpdateCanisterSettingsEffects {
+                canister_id: Some(REGISTRY_CANISTER_ID.get()),
+                changes: vec![
+                    new_field_change(
+                        "controllers",
+                        format!(
+                            "{}, {}",
+                            ROOT_CANISTER_ID.get(),
+                            PrincipalId::new_user_test_id(1)
+                        ),
+                    ),
+                    new_field_change("log_visibility", "Public".to_string()),
+                    new_field_change("wasm_memory_limit", "4294967296".to_string()),
+                ],
+            }
+        );
+
+        // When the current settings are known, they are shown as the old values.
+        let current_settings = CanisterStatusResult::from(
+            CanisterStatusResultFromManagementCanister::dummy_with_controllers(vec![
+                ROOT_CANISTER_ID.get(),
+            ]),
+        )
+        .settings;
+        assert_eq!(
+            simulate_update_canister_settings(&update_canister_settings, Some(&current_settings)),
+            UpdateCanisterSettingsEffects {
+                canister_id: Some(REGISTRY_CANISTER_ID.get()),
+                changes: vec![
+                    FieldChange {
+                        field_name: Some("controllers".to_string()),
+                        old_value: Some(ROOT_CANISTER_ID.get().to_string()),
+                        new_value: Some(format!(
+                            "{}, {}",
+                            ROOT_CANISTER_ID.get(),
+                            PrincipalId::new_user_test_id(1)
+                        )),
+                    },
+                    FieldChange {
+                        field_name: Some("log_visibility".to_string()),
+                        old_value: Some("Controllers".to_string()),
+                        new_value: Some("Public".to_string()),
+                    },
+                    FieldChange {
+                        field_name: Some("wasm_memory_limit".to_string()),
+                        old_value: Some("48".to_string()),
+                        new_value: Some("4294967296".to_string()),
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_simulate_execute_nns_function_decodes_payload() {
+        let payload = UpdateIcpXdrConversionRatePayload {
+            xdr_permyriad_per_icp: 123_456,
+            ..Default::default()
+        };
+        let execute_nns_function = ExecuteNnsFunction {
+            nns_function: NnsFunction::IcpXdrConversionRate as i32,
+            payload: Encode!(&payload).unwrap(),
+        };
+
+        assert_eq!(
+            simulate_execute_nns_function(&execute_nns_function),
+            Ok(ExecuteNnsFunctionEffects {
+                nns_function: Some(NnsFunction::IcpXdrConversionRate as i32),
+                payload: Some(NnsFunctionPayload::Decoded(DecodedNnsFunctionPayload {
+                    type_name: Some("UpdateIcpXdrConversionRatePayload".to_string()),
+                    rendering: Some(format!("{:#?}", payload)),
+                })),
+                registry_changes: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn test_simulate_execute_nns_function_rejects_undecodable_payload() {
+        let execute_nns_function = ExecuteNnsFunction {
+            nns_function: NnsFunction::CreateSubnet as i32,
+            payload: vec![1, 2, 3],
+        };
+
+        let err = simulate_execute_nns_function(&execute_nns_function).unwrap_err();
+        assert_eq!(err.error_type, ErrorType::InvalidProposal as i32);
+        assert!(
+            err.error_message
+                .contains("could not be decoded into a CreateSubnetPayload"),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_simulate_execute_nns_function_describes_registry_changes() {
+        let node_ids = vec![
+            NodeId::from(PrincipalId::new_node_test_id(1)),
+            NodeId::from(PrincipalId::new_node_test_id(2)),
+        ];
+        let payload = RemoveNodesPayload {
+            node_ids: node_ids.clone(),
+        };
+        let execute_nns_function = ExecuteNnsFunction {
+            nns_function: NnsFunction::RemoveNodes as i32,
+            payload: Encode!(&payload).unwrap(),
+        };
+
+        let effects = simulate_execute_nns_function(&execute_nns_function).unwrap();
+        assert_eq!(
+            effects.payload,
+            Some(NnsFunctionPayload::Decoded(DecodedNnsFunctionPayload {
+                type_name: Some("RemoveNodesPayload".to_string()),
+                rendering: Some(format!("{:#?}", payload)),
+            }))
+        );
+        let keys = effects
+            .registry_changes
+            .iter()
+            .map(|change| change.key.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            vec![
+                Some(make_node_record_key(node_ids[0])),
+                Some(make_node_record_key(node_ids[1])),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_simulate_execute_nns_function_obsolete_function() {
+        let execute_nns_function = ExecuteNnsFunction {
+            nns_function: NnsFunction::NnsCanisterUpgrade as i32,
+            payload: vec![1, 2, 3],
+        };
+
+        assert_eq!(
+            simulate_execute_nns_function(&execute_nns_function),
+            Ok(ExecuteNnsFunctionEffects {
+                nns_function: Some(NnsFunction::NnsCanisterUpgrade as i32),
+                payload: Some(NnsFunctionPayload::Unsupported(
+                    UnsupportedNnsFunctionPayload {
+                        reason: Some("The NnsFunction is obsolete.".to_string()),
+                    }
+                )),
+                registry_changes: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn test_simulate_manage_network_economics() {
+        let current = NetworkEconomics::with_default_values();
+        let new = NetworkEconomics {
+            reject_cost_e8s: current.reject_cost_e8s + 1,
+            voting_power_economics: Some(VotingPowerEconomics {
+                clear_following_after_seconds: Some(42),
+                ..current.voting_power_economics.unwrap()
+            }),
+            ..current.clone()
+        };
+
+        assert_eq!(
+            simulate_manage_network_economics(&current, &new),
+            ManageNetworkEconomicsEffects {
+                changes: vec![
+                    FieldChange {
+                        field_name: Some("reject_cost_e8s".to_string()),
+                        old_value: Some(current.reject_cost_e8s.to_string()),
+                        new_value: Some(new.reject_cost_e8s.to_string()),
+                    },
+                    FieldChange {
+                        field_name: Some(
+                            "voting_power_economics.clear_following_after_seconds".to_string()
+                        ),
+                        old_value: Some(format!(
+                            "{:?}",
+                            current
+                                .voting_power_economics
+                                .unwrap()
+                                .clear_following_after_seconds
+                        )),
+                        new_value: Some("Some(42)".to_string()),
+                    },
+                ],
+            }
+        );
+
+        // No changes.
+        assert_eq!(
+            simulate_manage_network_economics(&current, &current),
+            ManageNetworkEconomicsEffects { changes: vec![] }
+        );
+    }
+}
@@ -42,7 +42,7 @@ use ::pocket_ic::common::rest::{BinaryBlob, BlobId, RawSubnetBlockmaker};
 use async_trait::async_trait;
 use candid::Principal;
 use ic_types::{NodeId, PrincipalId, SubnetId};
-use pocket_ic::PocketIc;
+use pocket_ic::{PocketIc, RecordedOperation};
 use serde::Deserialize;
 
 /// Represents an identifiable operation on PocketIC.
@@ -59,6 +59,13 @@ pub trait Operation {
 
     /// Returns the unique identifier of this operation.
     fn id(&self) -> OpId;
+
+    /// Returns the representation of this operation in an operation log
+    /// or `None` if this operation does not change the instance state
+    /// (and thus needs not be recorded).
+    fn record(&self) -> Option<RecordedOperation> {
+        None
+    }
 }
 
 /// Uniquely identifies an operation.
@@ -78,9 +78,11 @@ use ic_validator_ingress_message::StandaloneIngressSigVerifier;
 use itertools::Itertools;
 use pocket_ic::common::rest::{
     self, BinaryBlob, BlobCompression, CanisterHttpHeader, CanisterHttpMethod, CanisterHttpRequest,
-    CanisterHttpResponse, ExtendedSubnetConfigSet, MockCanisterHttpResponse, RawAddCycles,
-    RawCanisterCall, RawCanisterId, RawEffectivePrincipal, RawMessageId, RawSetStableMemory,
-    SubnetInstructionConfig, SubnetKind, TickConfigs, Topology,
+    CanisterHttpResponse, CoverageBlock, CoverageReport, ExtendedSubnetConfigSet, InstanceConfig,
+    MockCanisterHttpResponse, QueryCacheOutcome, QueryCallOutcome, RawAddCycles, RawCanisterCall,
+    RawCanisterId, RawEffectivePrincipal, RawMessageId, RawQueryCallTrace, RawQueryCallTraceNode,
+    RawSetStableMemory, RawTracedCanisterResult, SubnetInstructionConfig, SubnetKind, TickConfigs,
+    Topology,
 };
 use pocket_ic::{copy_dir, ErrorCode, RejectCode, RejectResponse};
 use serde::{Deserialize, Serialize};
@@ -90,9 +92,9 @@ use std::str::FromStr;
 use std::{
     collections::{BTreeMap, BTreeSet},
     fs::{remove_file, File},
-    io::{BufReader, Write},
+    io::{BufRead, BufReader, Write},
     net::SocketAddr,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex, RwLock},
     time::{Duration, SystemTime},
 };
@@ -105,6 +107,7 @@ use tonic::transport::{Channel, Server};
 use tonic::transport::{Endpoint, Uri};
 use tonic::{Code, Request, Response, Status};
 use tower::{service_fn, util::ServiceExt};
+use tracing::error;
 
 // See build.rs
 include!(concat!(env!("OUT_DIR"), "/dashboard.rs"));
@@ -454,6 +457,7 @@ struct PocketIcSubnets {
     routing_table: RoutingTable,
     chain_keys: BTreeMap<MasterPublicKeyId, Vec<SubnetId>>,
     nonmainnet_features: bool,
+    wasm_coverage: bool,
     log_level: Option<Level>,
     bitcoind_addr: Option<Vec<SocketAddr>>,
     _bitcoin_adapter_parts: Option<BitcoinAdapterParts>,
@@ -469,6 +473,7 @@ impl PocketIcSubnets {
         registry_data_provider: Arc<ProtoRegistryDataProvider>,
         time: SystemTime,
         nonmainnet_features: bool,
+        wasm_coverage: bool,
         log_level: Option<Level>,
         bitcoin_adapter_uds_path: Option<PathBuf>,
     ) -> StateMachineBuilder {
@@ -507,6 +512,9 @@ impl PocketIcSubnets {
             NumBytes::new(2 * 1024 * 1024 * 1024);
         // shorter query stats epoch length for faster query stats aggregation
         hypervisor_config.query_stats_epoch_length = 60;
+        // enable downloading and uploading canister snapshots (used by snapshot archives)
+        hypervisor_config.canister_snapshot_download = FlagStatus::Enabled;
+        hypervisor_config.canister_snapshot_upload = FlagStatus::Enabled;
         // enable canister debug prints
         hypervisor_config
             .embedders_config
@@ -529,12 +537,14 @@ impl PocketIcSubnets {
             .with_registry_data_provider(registry_data_provider.clone())
             .with_log_level(log_level)
             .with_bitcoin_testnet_uds_path(bitcoin_adapter_uds_path)
+            .with_wasm_coverage_enabled(wasm_coverage)
     }
 
     fn new(
         runtime: Arc<Runtime>,
         state_dir: Option<PathBuf>,
         nonmainnet_features: bool,
+        wasm_coverage: bool,
         log_level: Option<Level>,
         bitcoind_addr: Option<Vec<SocketAddr>>,
     ) -> Self {
@@ -551,6 +561,7 @@ impl PocketIcSubnets {
             routing_table,
             chain_keys,
             nonmainnet_features,
+            wasm_coverage,
             log_level,
             bitcoind_addr,
             _bitcoin_adapter_parts: None,
@@ -641,6 +652,7 @@ impl PocketIcSubnets {
             self.registry_data_provider.clone(),
             time,
             self.nonmainnet_features,
+            self.wasm_coverage,
             self.log_level,
             bitcoin_adapter_uds_path.clone(),
         );
@@ -798,6 +810,7 @@ pub struct PocketIc {
     state_label: StateLabel,
     subnets: PocketIcSubnets,
     topology: TopologyInternal,
+    operation_log: Option<OperationLog>,
 }
 
 impl Drop for PocketIc {
@@ -904,6 +917,7 @@ impl PocketIc {
         subnet_configs: ExtendedSubnetConfigSet,
         state_dir: Option<PathBuf>,
         nonmainnet_features: bool,
+        wasm_coverage: bool,
         log_level: Option<Level>,
         bitcoind_addr: Option<Vec<SocketAddr>>,
     ) -> Result<Self, String> {
@@ -1086,6 +1100,7 @@ impl PocketIc {
             runtime.clone(),
             state_dir,
             nonmainnet_features,
+            wasm_coverage,
             log_level,
             bitcoind_addr,
         );
@@ -1123,6 +1138,7 @@ impl PocketIc {
             state_label,
             subnets,
             topology,
+            operation_log: None,
         })
     }
 
@@ -1130,6 +1146,26 @@ impl PocketIc {
         self.state_label.bump();
     }
 
+    /// Starts appending all subsequent state-changing operations to the given operation log.
+    pub(crate) fn record_operations(&mut self, operation_log: OperationLog) {
+        self.operation_log = Some(operation_log);
+    }
+
+    /// Appends the given operation to the operation log (if recording is enabled).
+    pub(crate) fn record_operation(&mut self, operation: &RecordedOperation) {
+        if let Some(operation_log) = self.operation_log.as_mut() {
+            operation_log.append(operation);
+        }
+    }
+
+    /// Applies the operations of an operation log in order, as if they were submitted by a client.
+    pub(crate) fn replay(&mut self, operations: &[RecordedOperation]) {
+        for operation in operations {
+            operation.compute(self);
+            self.bump_state_label();
+        }
+    }
+
     fn try_route_canister(&self, canister_id: CanisterId) -> Option<Arc<StateMachine>> {
         self.subnets.route(canister_id)
     }
@@ -1321,7 +1357,7 @@ pub struct ConversionError {
     message: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SetTime {
     pub time: Time,
 }
@@ -1355,12 +1391,16 @@ impl Operation for SetTime {
         set_time(pic, self.time, false)
     }
 
+    fn record(&self) -> Option<RecordedOperation> {
+        Some(RecordedOperation::SetTime(self.clone()))
+    }
+
     fn id(&self) -> OpId {
         OpId(format!("set_time_{}", self.time))
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SetCertifiedTime {
     pub time: Time,
 }
@@ -1370,6 +1410,10 @@ impl Operation for SetCertifiedTime {
         set_time(pic, self.time, true)
     }
 
+    fn record(&self) -> Option<RecordedOperation> {
+        Some(RecordedOperation::SetCertifiedTime(self.clone()))
+    }
+
     fn id(&self) -> OpId {
         OpId(format!("set_certified_time_{}", self.time))
     }
@@ -1417,6 +1461,9 @@ fn http_method_from(
         ic_types::canister_http::CanisterHttpMethod::GET => CanisterHttpMethod::GET,
         ic_types::canister_http::CanisterHttpMethod::POST => CanisterHttpMethod::POST,
         ic_types::canister_http::CanisterHttpMethod::HEAD => CanisterHttpMethod::HEAD,
+        ic_types::canister_http::CanisterHttpMethod::PUT => CanisterHttpMethod::PUT,
+        ic_types::canister_http::CanisterHttpMethod::PATCH => CanisterHttpMethod::PATCH,
+        ic_types::canister_http::CanisterHttpMethod::DELETE => CanisterHttpMethod::DELETE,
     }
 }
 
@@ -1468,11 +1515,14 @@ impl Operation for GetCanisterHttp {
 /// The operation `ProcessCanisterHttpInternal` changes the instance state in a non-deterministic way!
 /// It should only be used internally in auto-progress mode
 /// which changes the instance state in a non-deterministic way anyway.
+/// The responses it delivers are recorded as individual [`CanisterHttpAdapterResponse`] operations
+/// so that an operation log can still be replayed deterministically.
 #[derive(Copy, Clone, Debug)]
 pub struct ProcessCanisterHttpInternal;
 
 impl Operation for ProcessCanisterHttpInternal {
     fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let mut delivered_responses = vec![];
         for subnet in pic.subnets.get_all() {
             let sm = subnet.state_machine.clone();
             let mut canister_http = subnet.canister_http.lock().unwrap();
@@ -1502,17 +1552,25 @@ impl Operation for ProcessCanisterHttpInternal {
                         canister_http.pending.remove(&response.id);
                         if let Some(context) = sm.canister_http_request_contexts().get(&response.id)
                         {
-                            sm.mock_canister_http_response(
-                                response.id.get(),
-                                response.timeout,
-                                context.request.sender,
-                                vec![response.content; sm.nodes.len()],
-                            );
+                            let adapter_response = CanisterHttpAdapterResponse {
+                                subnet_id: sm.get_subnet_id(),
+                                request_id: response.id.get(),
+                                timeout: response.timeout,
+                                sender: context.request.sender,
+                                content: response.content,
+                            };
+                            adapter_response.deliver(&sm);
+                            delivered_responses.push(adapter_response);
                         }
                     }
                 }
             }
         }
+        for adapter_response in delivered_responses {
+            pic.record_operation(&RecordedOperation::CanisterHttpAdapterResponse(
+                adapter_response,
+            ));
+        }
         OpOut::NoOutput
     }
 
@@ -1521,6 +1579,56 @@ impl Operation for ProcessCanisterHttpInternal {
     }
 }
 
+/// A response to a canister HTTP outcall that was obtained from the canister HTTP adapter
+/// in auto-progress mode. It is only used to replay operation logs
+/// and is never submitted by a client.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CanisterHttpAdapterResponse {
+    subnet_id: SubnetId,
+    request_id: u64,
+    timeout: Time,
+    sender: CanisterId,
+    content: CanisterHttpResponseContent,
+}
+
+impl CanisterHttpAdapterResponse {
+    fn deliver(&self, sm: &StateMachine) {
+        sm.mock_canister_http_response(
+            self.request_id,
+            self.timeout,
+            self.sender,
+            vec![self.content.clone(); sm.nodes.len()],
+        );
+    }
+}
+
+impl Operation for CanisterHttpAdapterResponse {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let Some(sm) = pic.subnets.get(self.subnet_id) else {
+            return OpOut::Error(PocketIcError::SubnetNotFound(self.subnet_id.get().0));
+        };
+        let request_id = CanisterHttpRequestId::from(self.request_id);
+        if !sm
+            .canister_http_request_contexts()
+            .contains_key(&request_id)
+        {
+            return OpOut::Error(PocketIcError::InvalidCanisterHttpRequestId((
+                self.subnet_id,
+                request_id,
+            )));
+        }
+        self.deliver(&sm);
+        OpOut::NoOutput
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "canister_http_adapter_response({},{})",
+            self.subnet_id, self.request_id
+        ))
+    }
+}
+
 // START COPY from rs/https_outcalls/client/src/client.rs
 
 #[derive(Clone)]
@@ -1701,7 +1809,7 @@ fn process_mock_canister_https_response(
     OpOut::NoOutput
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct MockCanisterHttp {
     pub mock_canister_http_response: MockCanisterHttpResponse,
 }
@@ -1711,6 +1819,10 @@ impl Operation for MockCanisterHttp {
         process_mock_canister_https_response(pic, &self.mock_canister_http_response)
     }
 
+    fn record(&self) -> Option<RecordedOperation> {
+        Some(RecordedOperation::MockCanisterHttp(self.clone()))
+    }
+
     fn id(&self) -> OpId {
         OpId(format!(
             "mock_canister_http({:?})",
@@ -1738,7 +1850,7 @@ impl Operation for PubKey {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Tick {
     pub configs: TickConfigs,
 }
@@ -1817,12 +1929,16 @@ impl Operation for Tick {
         OpOut::NoOutput
     }
 
+    fn record(&self) -> Option<RecordedOperation> {
+        Some(RecordedOperation::Tick(self.clone()))
+    }
+
     fn id(&self) -> OpId {
         OpId("tick".to_string())
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
 pub struct AdvanceTimeAndTick(pub Duration);
 
 impl Operation for AdvanceTimeAndTick {
@@ -1834,12 +1950,16 @@ impl Operation for AdvanceTimeAndTick {
         OpOut::NoOutput
     }
 
+    fn record(&self) -> Option<RecordedOperation> {
+        Some(RecordedOperation::AdvanceTimeAndTick(*self))
+    }
+
     fn id(&self) -> OpId {
         OpId(format!("advance_time_and_tick({:?})", self.0))
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SubmitIngressMessage(pub CanisterCall);
 
 impl Operation for SubmitIngressMessage {
@@ -1872,13 +1992,17 @@ impl Operation for SubmitIngressMessage {
         }
     }
 
+    fn record(&self) -> Option<RecordedOperation> {
+        Some(RecordedOperation::SubmitIngressMessage(self.clone()))
+    }
+
     fn id(&self) -> OpId {
         let call_id = self.0.id();
         OpId(format!("submit_update_{}", call_id.0))
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct MessageId {
     effective_principal: EffectivePrincipal,
     msg_id: OtherMessageId,
@@ -1908,7 +2032,7 @@ impl TryFrom<RawMessageId> for MessageId {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AwaitIngressMessage(pub MessageId);
 
 impl Operation for AwaitIngressMessage {
@@ -1951,6 +2075,10 @@ impl Operation for AwaitIngressMessage {
         }
     }
 
+    fn record(&self) -> Option<RecordedOperation> {
+        Some(RecordedOperation::AwaitIngressMessage(self.clone()))
+    }
+
     fn id(&self) -> OpId {
         OpId(format!("await_update_{}", self.0.msg_id))
     }
@@ -2037,6 +2165,85 @@ impl Operation for Query {
     }
 }
 
+pub struct QueryWithTrace(pub CanisterCall);
+
+fn query_call_trace_node_to_raw(
+    node: ic_state_machine_tests::QueryCallTraceNode,
+) -> RawQueryCallTraceNode {
+    let outcome = match node.outcome {
+        ic_state_machine_tests::QueryCallTraceOutcome::Reply => QueryCallOutcome::Reply,
+        ic_state_machine_tests::QueryCallTraceOutcome::Reject(context) => {
+            QueryCallOutcome::Reject {
+                reject_code: RejectCode::try_from(context.code() as u64).unwrap(),
+                reject_message: context.message().clone(),
+            }
+        }
+        ic_state_machine_tests::QueryCallTraceOutcome::Error(user_error) => {
+            QueryCallOutcome::Error(user_error_to_reject_response(user_error, false))
+        }
+        ic_state_machine_tests::QueryCallTraceOutcome::Unfinished => QueryCallOutcome::Unfinished,
+    };
+    RawQueryCallTraceNode {
+        canister_id: node.canister_id.get().0.into(),
+        method: node.method_name,
+        instructions: node.instructions.get(),
+        response_size: node.response_size.get(),
+        outcome,
+        calls: node
+            .calls
+            .into_iter()
+            .map(query_call_trace_node_to_raw)
+            .collect(),
+    }
+}
+
+fn query_call_trace_to_raw(trace: ic_state_machine_tests::QueryCallTrace) -> RawQueryCallTrace {
+    RawQueryCallTrace {
+        cache: match trace.cache {
+            ic_state_machine_tests::QueryCacheOutcome::Disabled => QueryCacheOutcome::Disabled,
+            ic_state_machine_tests::QueryCacheOutcome::Hit => QueryCacheOutcome::Hit,
+            ic_state_machine_tests::QueryCacheOutcome::Miss => QueryCacheOutcome::Miss,
+        },
+        root: trace.root.map(query_call_trace_node_to_raw),
+        call_graph_depth: trace.call_graph_depth as u64,
+        instructions_used: trace.instructions_used.get(),
+        max_call_graph_depth: trace.max_call_graph_depth as u64,
+        max_call_graph_instructions: trace.max_call_graph_instructions.get(),
+        max_instructions_per_query: trace.max_instructions_per_query.get(),
+    }
+}
+
+impl Operation for QueryWithTrace {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let canister_call = self.0.clone();
+        let subnet = route_call(pic, canister_call);
+        match subnet {
+            Ok(subnet) => {
+                let (result, trace) = subnet.query_as_with_trace(
+                    self.0.sender,
+                    self.0.canister_id,
+                    self.0.method.clone(),
+                    self.0.payload.clone(),
+                );
+                let result = match result {
+                    Ok(result) => wasm_result_to_canister_result(result, false),
+                    Err(user_error) => Err(user_error_to_reject_response(user_error, false)),
+                };
+                OpOut::TracedCanisterResult(RawTracedCanisterResult {
+                    result: result.into(),
+                    trace: query_call_trace_to_raw(trace),
+                })
+            }
+            Err(e) => OpOut::Error(PocketIcError::BadIngressMessage(e)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        let call_id = self.0.id();
+        OpId(format!("canister_query_with_trace_{}", call_id.0))
+    }
+}
+
 pub struct DashboardRequest {}
 
 impl Operation for DashboardRequest {
@@ -2140,6 +2347,7 @@ impl Operation for StatusRequest {
     }
 }
 
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
 pub enum CallRequestVersion {
     V2,
     V3,
@@ -2270,6 +2478,14 @@ impl Operation for CallRequest {
         true
     }
 
+    fn record(&self) -> Option<RecordedOperation> {
+        Some(RecordedOperation::CallRequest {
+            effective_canister_id: self.effective_canister_id,
+            bytes: self.bytes.to_vec(),
+            version: self.version,
+        })
+    }
+
     fn id(&self) -> OpId {
         let mut hasher = Sha256::new();
         self.bytes.hash(&mut hasher);
@@ -2511,7 +2727,7 @@ impl From<EffectivePrincipal> for RawEffectivePrincipal {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct CanisterCall {
     pub effective_principal: EffectivePrincipal,
     pub sender: PrincipalId,
@@ -2571,7 +2787,7 @@ impl CanisterCall {
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub struct SetStableMemory {
     pub canister_id: CanisterId,
     pub data: Vec<u8>,
@@ -2638,6 +2854,10 @@ impl Operation for SetStableMemory {
         }
     }
 
+    fn record(&self) -> Option<RecordedOperation> {
+        Some(RecordedOperation::SetStableMemory(self.clone()))
+    }
+
     fn id(&self) -> OpId {
         // TODO: consider tupling the hash with the data everywhere,
         // from the sender up to here. so the blobstore can be lazier,
@@ -2726,6 +2946,53 @@ impl Operation for GetCyclesBalance {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct GetCoverageReport {
+    pub canister_id: CanisterId,
+}
+
+impl Operation for GetCoverageReport {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let subnet = pic.try_route_canister(self.canister_id);
+        match subnet {
+            Some(subnet) => {
+                if !subnet.canister_exists(self.canister_id) {
+                    return OpOut::Error(PocketIcError::CanisterNotFound(self.canister_id));
+                }
+                match subnet.coverage_report(self.canister_id) {
+                    Ok(report) => OpOut::CoverageReport(CoverageReport {
+                        lcov: report.to_lcov(),
+                        blocks: report
+                            .blocks
+                            .into_iter()
+                            .map(|block| {
+                                let (file, line) = match block.location {
+                                    Some(location) => (Some(location.file), Some(location.line)),
+                                    None => (None, None),
+                                };
+                                CoverageBlock {
+                                    function_index: block.function_index,
+                                    function_name: block.function_name,
+                                    code_offset: block.code_offset,
+                                    file,
+                                    line,
+                                    hits: block.hits,
+                                }
+                            })
+                            .collect(),
+                    }),
+                    Err(err) => OpOut::Error(PocketIcError::CoverageReportUnavailable(err)),
+                }
+            }
+            None => OpOut::Error(PocketIcError::CanisterNotFound(self.canister_id)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("get_coverage_report({})", self.canister_id))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct GetSubnet {
     pub canister_id: CanisterId,
@@ -2756,7 +3023,7 @@ impl Operation for GetSubnet {
 /// # Panics
 ///
 /// Panics if the canister does not exist.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AddCycles {
     canister_id: CanisterId,
     amount: u128,
@@ -2797,11 +3064,134 @@ impl Operation for AddCycles {
         }
     }
 
+    fn record(&self) -> Option<RecordedOperation> {
+        Some(RecordedOperation::AddCycles(self.clone()))
+    }
+
     fn id(&self) -> OpId {
         OpId(format!("add_cycles({},{})", self.canister_id, self.amount))
     }
 }
 
+/// A state-changing operation as recorded in an operation log.
+///
+/// Note that the operation `ProcessCanisterHttpInternal` is not recorded
+/// since it makes actual HTTP requests and thus cannot be replayed deterministically.
+/// Instead, every response it delivers is recorded as a [`CanisterHttpAdapterResponse`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum RecordedOperation {
+    SetTime(SetTime),
+    SetCertifiedTime(SetCertifiedTime),
+    Tick(Tick),
+    AdvanceTimeAndTick(AdvanceTimeAndTick),
+    SubmitIngressMessage(SubmitIngressMessage),
+    AwaitIngressMessage(AwaitIngressMessage),
+    CallRequest {
+        effective_canister_id: CanisterId,
+        bytes: Vec<u8>,
+        version: CallRequestVersion,
+    },
+    MockCanisterHttp(MockCanisterHttp),
+    CanisterHttpAdapterResponse(CanisterHttpAdapterResponse),
+    SetStableMemory(SetStableMemory),
+    AddCycles(AddCycles),
+}
+
+impl RecordedOperation {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match self {
+            RecordedOperation::SetTime(op) => op.compute(pic),
+            RecordedOperation::SetCertifiedTime(op) => op.compute(pic),
+            RecordedOperation::Tick(op) => op.compute(pic),
+            RecordedOperation::AdvanceTimeAndTick(op) => op.compute(pic),
+            RecordedOperation::SubmitIngressMessage(op) => op.compute(pic),
+            RecordedOperation::AwaitIngressMessage(op) => op.compute(pic),
+            RecordedOperation::CallRequest {
+                effective_canister_id,
+                bytes,
+                version,
+            } => CallRequest {
+                effective_canister_id: *effective_canister_id,
+                bytes: bytes.clone().into(),
+                version: *version,
+            }
+            .compute(pic),
+            RecordedOperation::MockCanisterHttp(op) => op.compute(pic),
+            RecordedOperation::CanisterHttpAdapterResponse(op) => op.compute(pic),
+            RecordedOperation::SetStableMemory(op) => op.compute(pic),
+            RecordedOperation::AddCycles(op) => op.compute(pic),
+        }
+    }
+}
+
+/// An append-only log of the state-changing operations applied to an instance.
+///
+/// The log is a file in the JSON lines format: the first line contains the configuration
+/// of the instance and every subsequent line contains a single [`RecordedOperation`].
+/// Every operation is flushed to the file immediately so that the log is complete
+/// even if the PocketIC server crashes.
+pub(crate) struct OperationLog {
+    file: File,
+}
+
+impl OperationLog {
+    /// Creates a new operation log at the given path (overwriting an existing file)
+    /// for an instance with the given configuration.
+    pub(crate) fn create(path: &Path, instance_config: &InstanceConfig) -> Result<Self, String> {
+        let mut file = File::create(path)
+            .map_err(|e| format!("Failed to create operation log {}: {}", path.display(), e))?;
+        let instance_config = InstanceConfig {
+            operation_log: None,
+            ..instance_config.clone()
+        };
+        Self::write_line(&mut file, &instance_config)?;
+        Ok(Self { file })
+    }
+
+    fn append(&mut self, operation: &RecordedOperation) {
+        if let Err(e) = Self::write_line(&mut self.file, operation) {
+            error!("Failed to append to operation log: {}", e);
+        }
+    }
+
+    fn write_line(file: &mut File, value: &impl Serialize) -> Result<(), String> {
+        let mut line = serde_json::to_vec(value).map_err(|e| e.to_string())?;
+        line.push(b'\n');
+        file.write_all(&line)
+            .and_then(|_| file.flush())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Reads the instance configuration and the operations recorded in the operation log at the given path.
+    pub(crate) fn read(path: &Path) -> Result<(InstanceConfig, Vec<RecordedOperation>), String> {
+        let file = File::open(path)
+            .map_err(|e| format!("Failed to open operation log {}: {}", path.display(), e))?;
+        let mut lines = BufReader::new(file).lines().enumerate();
+        let instance_config = match lines.next() {
+            Some((_, line)) => {
+                let line = line.map_err(|e| format!("Failed to read operation log: {}", e))?;
+                serde_json::from_str(&line).map_err(|e| {
+                    format!("Invalid instance configuration in operation log: {}", e)
+                })?
+            }
+            None => return Err("The operation log is empty.".to_string()),
+        };
+        let operations = lines
+            .map(|(i, line)| {
+                let line = line.map_err(|e| format!("Failed to read operation log: {}", e))?;
+                serde_json::from_str(&line).map_err(|e| {
+                    format!(
+                        "Invalid operation in line {} of operation log: {}",
+                        i + 1,
+                        e
+                    )
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok((instance_config, operations))
+    }
+}
+
 struct Digest([u8; 32]);
 
 impl std::fmt::Debug for Digest {
@@ -2993,6 +3383,7 @@ mod tests {
                 },
                 None,
                 false,
+                false,
                 None,
                 None,
             )
@@ -3006,6 +3397,7 @@ mod tests {
                 },
                 None,
                 false,
+                false,
                 None,
                 None,
             )
@@ -8,10 +8,10 @@
 use super::state::{ApiState, OpOut, PocketIcError, StateLabel, UpdateReply};
 use crate::pocket_ic::{
     AddCycles, AwaitIngressMessage, CallRequest, CallRequestVersion, CanisterReadStateRequest,
-    DashboardRequest, GetCanisterHttp, GetControllers, GetCyclesBalance, GetStableMemory,
-    GetSubnet, GetTime, GetTopology, IngressMessageStatus, MockCanisterHttp, PubKey, Query,
-    QueryRequest, SetCertifiedTime, SetStableMemory, SetTime, StatusRequest, SubmitIngressMessage,
-    SubnetReadStateRequest, Tick,
+    DashboardRequest, GetCanisterHttp, GetControllers, GetCoverageReport, GetCyclesBalance,
+    GetStableMemory, GetSubnet, GetTime, GetTopology, IngressMessageStatus, MockCanisterHttp,
+    OperationLog, PubKey, Query, QueryRequest, QueryWithTrace, RecordedOperation, SetCertifiedTime,
+    SetStableMemory, SetTime, StatusRequest, SubmitIngressMessage, SubnetReadStateRequest, Tick,
 };
 use crate::{async_trait, pocket_ic::PocketIc, BlobStore, InstanceId, OpId, Operation};
 use aide::{
@@ -36,11 +36,12 @@ use hyper::header;
 use ic_http_endpoints_public::cors_layer;
 use ic_types::{CanisterId, SubnetId};
 use pocket_ic::common::rest::{
-    self, ApiResponse, AutoProgressConfig, ExtendedSubnetConfigSet, HttpGatewayConfig,
-    HttpGatewayDetails, InstanceConfig, MockCanisterHttpResponse, RawAddCycles, RawCanisterCall,
-    RawCanisterHttpRequest, RawCanisterId, RawCanisterResult, RawCycles, RawIngressStatusArgs,
-    RawMessageId, RawMockCanisterHttpResponse, RawPrincipalId, RawSetStableMemory, RawStableMemory,
-    RawSubnetId, RawTime, TickConfigs, Topology,
+    self, ApiResponse, AutoProgressConfig, CoverageReport, ExtendedSubnetConfigSet,
+    HttpGatewayConfig, HttpGatewayDetails, InstanceConfig, MockCanisterHttpResponse, RawAddCycles,
+    RawCanisterCall, RawCanisterHttpRequest, RawCanisterId, RawCanisterResult, RawCycles,
+    RawIngressStatusArgs, RawMessageId, RawMockCanisterHttpResponse, RawPrincipalId,
+    RawSetStableMemory, RawStableMemory, RawSubnetId, RawTime, RawTracedCanisterResult,
+    ReplayInstanceConfig, TickConfigs, Topology,
 };
 use pocket_ic::RejectResponse;
 use serde::Serialize;
@@ -74,11 +75,13 @@ where
 {
     ApiRouter::new()
         .directory_route("/query", post(handler_json_query))
+        .directory_route("/query_with_trace", post(handler_query_with_trace))
         .directory_route("/topology", get(handler_topology))
         .directory_route("/get_time", get(handler_get_time))
         .directory_route("/get_canister_http", get(handler_get_canister_http))
         .directory_route("/get_controllers", post(handler_get_controllers))
         .directory_route("/get_cycles", post(handler_get_cycles))
+        .directory_route("/get_coverage_report", post(handler_get_coverage_report))
         .directory_route("/get_stable_memory", post(handler_get_stable_memory))
         .directory_route("/get_subnet", post(handler_get_subnet))
         .directory_route("/pub_key", post(handler_pub_key))
@@ -177,6 +180,10 @@ where
         // Returns an InstanceId.
         .api_route("/", post(create_instance))
         //
+        // Create a new IC instance by replaying an operation log. Takes a ReplayInstanceConfig.
+        // Returns an InstanceId.
+        .api_route("/replay", post(replay_instance))
+        //
         // Deletes an instance.
         .directory_route("/{id}", delete(delete_instance))
         //
@@ -428,6 +435,26 @@ impl TryFrom<OpOut> for Option<RawCanisterResult> {
     }
 }
 
+impl TryFrom<OpOut> for RawTracedCanisterResult {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::TracedCanisterResult(traced_result) => Ok(traced_result),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
+impl TryFrom<OpOut> for CoverageReport {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::CoverageReport(report) => Ok(report),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
 impl TryFrom<OpOut> for PocketIcError {
     type Error = OpConversionError;
     fn try_from(value: OpOut) -> Result<Self, Self::Error> {
@@ -552,6 +579,28 @@ pub async fn handler_json_query(
     }
 }
 
+pub async fn handler_query_with_trace(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(raw_canister_call): extract::Json<RawCanisterCall>,
+) -> (StatusCode, Json<ApiResponse<RawTracedCanisterResult>>) {
+    let timeout = timeout_or_default(headers);
+    match crate::pocket_ic::CanisterCall::try_from(raw_canister_call) {
+        Ok(canister_call) => {
+            let query_op = QueryWithTrace(canister_call);
+            let (code, response) = run_operation(api_state, instance_id, timeout, query_op).await;
+            (code, Json(response))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
 pub async fn handler_topology(
     State(AppState { api_state, .. }): State<AppState>,
     headers: HeaderMap,
@@ -625,6 +674,28 @@ pub async fn handler_get_controllers(
     }
 }
 
+pub async fn handler_get_coverage_report(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(raw_canister_id): extract::Json<RawCanisterId>,
+) -> (StatusCode, Json<ApiResponse<CoverageReport>>) {
+    let timeout = timeout_or_default(headers);
+    match CanisterId::try_from(raw_canister_id.canister_id) {
+        Ok(canister_id) => {
+            let get_op = GetCoverageReport { canister_id };
+            let (code, response) = run_operation(api_state, instance_id, timeout, get_op).await;
+            (code, Json(response))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
 pub async fn handler_get_cycles(
     State(AppState { api_state, .. }): State<AppState>,
     Path(instance_id): Path<InstanceId>,
@@ -838,6 +909,20 @@ async fn op_out_to_response(op_out: OpOut) -> Response {
             )),
         )
             .into_response(),
+        opout @ OpOut::TracedCanisterResult(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::Success(
+                RawTracedCanisterResult::try_from(opout).unwrap(),
+            )),
+        )
+            .into_response(),
+        opout @ OpOut::CoverageReport(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::Success(
+                CoverageReport::try_from(opout).unwrap(),
+            )),
+        )
+            .into_response(),
         opout @ OpOut::CanisterId(_) => (
             StatusCode::OK,
             Json(ApiResponse::Success(
@@ -1146,7 +1231,46 @@ pub async fn create_instance(
     }): State<AppState>,
     extract::Json(instance_config): extract::Json<InstanceConfig>,
 ) -> (StatusCode, Json<rest::CreateInstanceResponse>) {
-    let subnet_configs = instance_config.subnet_config_set;
+    add_instance(api_state, runtime, instance_config, vec![]).await
+}
+
+/// Creates a new instance by replaying an operation log recorded by another instance
+/// (see `InstanceConfig::operation_log`). The new instance is created with the same
+/// configuration as the recorded instance and all recorded operations are applied to it in order.
+pub async fn replay_instance(
+    State(AppState {
+        api_state, runtime, ..
+    }): State<AppState>,
+    extract::Json(replay_config): extract::Json<ReplayInstanceConfig>,
+) -> (StatusCode, Json<rest::CreateInstanceResponse>) {
+    let (instance_config, operations) = match OperationLog::read(&replay_config.operation_log) {
+        Ok(operation_log) => operation_log,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(rest::CreateInstanceResponse::Error { message }),
+            )
+        }
+    };
+    // The initial state of an instance created from a state directory is not part of the operation log.
+    if instance_config.state_dir.is_some() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(rest::CreateInstanceResponse::Error {
+                message: "Cannot replay an instance created from a state directory.".to_string(),
+            }),
+        );
+    }
+    add_instance(api_state, runtime, instance_config, operations).await
+}
+
+async fn add_instance(
+    api_state: Arc<ApiState>,
+    runtime: Arc<Runtime>,
+    instance_config: InstanceConfig,
+    operations: Vec<RecordedOperation>,
+) -> (StatusCode, Json<rest::CreateInstanceResponse>) {
+    let subnet_configs = instance_config.subnet_config_set.clone();
 
     let skip_validate_subnet_configs = instance_config
         .state_dir
@@ -1173,8 +1297,8 @@ pub async fn create_instance(
         );
     }
 
-    let log_level = if let Some(log_level) = instance_config.log_level {
-        match Level::from_str(&log_level) {
+    let log_level = if let Some(ref log_level) = instance_config.log_level {
+        match Level::from_str(log_level) {
             Ok(log_level) => Some(log_level),
             Err(e) => {
                 return (
@@ -1191,15 +1315,21 @@ pub async fn create_instance(
 
     match api_state
         .add_instance(move |seed| {
-            PocketIc::try_new(
+            let mut pocket_ic = PocketIc::try_new(
                 runtime,
                 seed,
                 subnet_configs,
-                instance_config.state_dir,
+                instance_config.state_dir.clone(),
                 instance_config.nonmainnet_features,
+                instance_config.wasm_coverage,
                 log_level,
-                instance_config.bitcoind_addr,
-            )
+                instance_config.bitcoind_addr.clone(),
+            )?;
+            pocket_ic.replay(&operations);
+            if let Some(ref operation_log) = instance_config.operation_log {
+                pocket_ic.record_operations(OperationLog::create(operation_log, &instance_config)?);
+            }
+            Ok(pocket_ic)
         })
         .await
     {
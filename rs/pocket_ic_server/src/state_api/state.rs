@@ -37,8 +37,8 @@ use ic_http_gateway::{CanisterRequest, HttpGatewayClient, HttpGatewayRequestArgs
 use ic_types::{canister_http::CanisterHttpRequestId, CanisterId, NodeId, PrincipalId, SubnetId};
 use itertools::Itertools;
 use pocket_ic::common::rest::{
-    CanisterHttpRequest, HttpGatewayBackend, HttpGatewayConfig, HttpGatewayDetails,
-    HttpGatewayInfo, Topology,
+    CanisterHttpRequest, CoverageReport, HttpGatewayBackend, HttpGatewayConfig, HttpGatewayDetails,
+    HttpGatewayInfo, RawTracedCanisterResult, Topology,
 };
 use pocket_ic::RejectResponse;
 use reqwest::Url;
@@ -234,6 +234,8 @@ pub enum OpOut {
     NoOutput,
     Time(u64),
     CanisterResult(Result<Vec<u8>, RejectResponse>),
+    TracedCanisterResult(RawTracedCanisterResult),
+    CoverageReport(CoverageReport),
     CanisterId(CanisterId),
     Controllers(Vec<PrincipalId>),
     Cycles(u128),
@@ -261,6 +263,7 @@ pub enum PocketIcError {
     Forbidden(String),
     BlockmakerNotFound(NodeId),
     BlockmakerContainedInFailed(NodeId),
+    CoverageReportUnavailable(String),
 }
 
 impl std::fmt::Debug for OpOut {
@@ -278,6 +281,8 @@ impl std::fmt::Debug for OpOut {
             OpOut::Cycles(x) => write!(f, "Cycles({})", x),
             OpOut::CanisterResult(Ok(x)) => write!(f, "CanisterResult: Ok({:?})", x),
             OpOut::CanisterResult(Err(x)) => write!(f, "CanisterResult: Err({})", x),
+            OpOut::TracedCanisterResult(x) => write!(f, "TracedCanisterResult({:?})", x),
+            OpOut::CoverageReport(x) => write!(f, "CoverageReport({:?})", x),
             OpOut::Error(PocketIcError::CanisterNotFound(cid)) => {
                 write!(f, "CanisterNotFound({})", cid)
             }
@@ -325,6 +330,9 @@ impl std::fmt::Debug for OpOut {
             OpOut::Error(PocketIcError::Forbidden(msg)) => {
                 write!(f, "Forbidden({})", msg)
             }
+            OpOut::Error(PocketIcError::CoverageReportUnavailable(msg)) => {
+                write!(f, "CoverageReportUnavailable({})", msg)
+            }
             OpOut::Bytes(bytes) => write!(f, "Bytes({})", base64::encode(bytes)),
             OpOut::StableMemBytes(bytes) => write!(f, "StableMemory({})", base64::encode(bytes)),
             OpOut::MaybeSubnetId(Some(subnet_id)) => write!(f, "SubnetId({})", subnet_id),
@@ -1236,6 +1244,9 @@ impl ApiState {
                                 op_id.0,
                             );
                             let result = op.compute(&mut pocket_ic);
+                            if let Some(recorded_op) = op.record() {
+                                pocket_ic.record_operation(&recorded_op);
+                            }
                             pocket_ic.bump_state_label();
                             let new_state_label = pocket_ic.get_state_label();
                             // add result to graph, but grab instance lock first!
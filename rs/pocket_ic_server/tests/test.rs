@@ -190,6 +190,8 @@ fn test_creation_of_instance_extended() {
         nonmainnet_features: false,
         log_level: None,
         bitcoind_addr: None,
+        operation_log: None,
+        wasm_coverage: false,
     };
     let response = client
         .post(url.join("instances").unwrap())
@@ -601,6 +601,14 @@ pub struct CanisterHttpShare {
     pub signature: ::core::option::Option<CanisterHttpResponseSignature>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CanisterHttpArtifact {
+    #[prost(message, optional, tag = "1")]
+    pub share: ::core::option::Option<CanisterHttpShare>,
+    /// The full response, only set for shares of non-replicated requests.
+    #[prost(message, optional, tag = "2")]
+    pub response: ::core::option::Option<CanisterHttpResponse>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterHttpResponseDivergence {
     #[prost(message, repeated, tag = "1")]
     pub shares: ::prost::alloc::vec::Vec<CanisterHttpShare>,
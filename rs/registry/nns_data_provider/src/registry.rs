@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use candid::{Decode, Encode};
 use prost::Message;
 use rand::seq::SliceRandom;
-use std::time::Duration;
+use std::{collections::BTreeMap, time::Duration};
 use url::Url;
 
 use ic_canister_client::{Agent, Sender};
@@ -167,6 +167,46 @@ impl RegistryCanister {
         ))
     }
 
+    /// Queries the registry for the time at which each version after `version`
+    /// was created.
+    ///
+    /// Returns a map from version to timestamp (in nanoseconds since the Unix
+    /// epoch), and the latest version available. Only a prefix of the versions
+    /// may be returned, in the same way as `get_changes_since`. Versions that
+    /// were created before the registry started recording timestamps map to 0.
+    ///
+    /// Unlike `get_changes_since`, large values are not fetched, because only
+    /// their timestamps are needed.
+    pub async fn get_version_timestamps_since(
+        &self,
+        version: u64,
+    ) -> Result<(BTreeMap<u64, u64>, u64), Error> {
+        let payload = serialize_get_changes_since_request(version).unwrap();
+        let response = self
+            .choose_random_agent()
+            .execute_query(&self.canister_id, "get_changes_since", payload)
+            .await
+            .map_err(|error_string| {
+                Error::UnknownError(format!(
+                    "Error on registry_get_changes_since: {}",
+                    error_string
+                ))
+            })?
+            .ok_or_else(|| {
+                Error::UnknownError(
+                    "No response was received from registry_get_changes_since.".to_string(),
+                )
+            })?;
+        let (deltas, latest_version) = deserialize_get_changes_since_response(response)?;
+
+        let mut timestamps = BTreeMap::new();
+        for value in deltas.into_iter().flat_map(|delta| delta.values) {
+            timestamps.insert(value.version, value.timestamp_nanoseconds);
+        }
+
+        Ok((timestamps, latest_version))
+    }
+
     /// Queries the registry for a prefix of all the changes that occurred since
     /// `version`, using a certified endpoint.
     ///
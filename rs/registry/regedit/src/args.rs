@@ -1,4 +1,5 @@
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::DateTime;
 use clap::Parser;
 use ic_crypto_utils_threshold_sig_der::threshold_sig_public_key_from_der;
 use ic_registry_client::client::RegistryVersion;
@@ -129,6 +130,88 @@ pub enum CommandArg {
         /// Path to the local store (may not be specified together with --url).
         snapshot_file: PathBuf,
     },
+    /// Read-only: shows the registry as it was at a given version or point in
+    /// time.
+    Show {
+        /// The registry version to show. (default: latest available version.)
+        #[clap(short, long, allow_hyphen_values = true, conflicts_with = "timestamp")]
+        version: Option<i64>,
+
+        /// Show the latest version that was created at or before this point in
+        /// time, given in RFC 3339 format (e.g. 2024-05-01T12:00:00Z). Requires
+        /// --url, since local stores do not record when versions were created.
+        #[clap(short, long, requires = "url")]
+        timestamp: Option<String>,
+
+        /// Comma-separated list of key prefixes by which the content of the
+        /// registry should be filtered by.
+        #[clap(short, long)]
+        keys: Option<String>,
+
+        /// Path to the local store (may not be specified together with --url).
+        #[clap(long, required_unless_present = "url", conflicts_with = "url")]
+        local_store_path: Option<PathBuf>,
+
+        /// Url to a node hosting the registry canister (may not be specified
+        /// together with --local-store-path).
+        #[clap(long)]
+        url: Option<Url>,
+
+        /// Optional path to the threshold public key of the root subnet
+        /// (a.k.a. NNS public key). One way to get this key is via
+        /// "ic-admin --nns-url https://nns.ic0.app  get-subnet-public-key"
+        #[clap(long, requires = "url")]
+        nns_public_key: Option<PathBuf>,
+    },
+    /// Read-only: shows what changed between two registry versions, grouped by
+    /// key family (subnets, nodes, routing table, replica versions, etc.),
+    /// together with a human-readable summary.
+    DiffVersions {
+        /// The version to diff from. Negative values are relative to the latest
+        /// version.
+        #[clap(
+            long,
+            allow_hyphen_values = true,
+            conflicts_with = "from_timestamp",
+            required_unless_present = "from_timestamp"
+        )]
+        from_version: Option<i64>,
+
+        /// Diff from the latest version that was created at or before this
+        /// point in time (RFC 3339). Requires --url.
+        #[clap(long, requires = "url")]
+        from_timestamp: Option<String>,
+
+        /// The version to diff to. Negative values are relative to the latest
+        /// version. (default: latest available version.)
+        #[clap(long, allow_hyphen_values = true, conflicts_with = "to_timestamp")]
+        to_version: Option<i64>,
+
+        /// Diff to the latest version that was created at or before this point
+        /// in time (RFC 3339). Requires --url.
+        #[clap(long, requires = "url")]
+        to_timestamp: Option<String>,
+
+        /// Comma-separated list of key prefixes. If provided, only changes to
+        /// keys with one of these prefixes are shown.
+        #[clap(short, long)]
+        keys: Option<String>,
+
+        /// Path to the local store (may not be specified together with --url).
+        #[clap(long, required_unless_present = "url", conflicts_with = "url")]
+        local_store_path: Option<PathBuf>,
+
+        /// Url to a node hosting the registry canister (may not be specified
+        /// together with --local-store-path).
+        #[clap(long)]
+        url: Option<Url>,
+
+        /// Optional path to the threshold public key of the root subnet
+        /// (a.k.a. NNS public key). One way to get this key is via
+        /// "ic-admin --nns-url https://nns.ic0.app  get-subnet-public-key"
+        #[clap(long, requires = "url")]
+        nns_public_key: Option<PathBuf>,
+    },
 }
 
 impl CliArgs {
@@ -223,10 +306,70 @@ impl CliArgs {
                     snapshot,
                 }
             }
+            CommandArg::Show {
+                version,
+                timestamp,
+                keys,
+                local_store_path,
+                url,
+                nns_public_key,
+            } => {
+                let version = Self::version_spec(version, timestamp)?;
+                let source = Self::source_spec(local_store_path, url, nns_public_key)?;
+                let projection = Self::keys_to_projection(keys);
+                Command::Snapshot {
+                    registry_spec: RegistrySpec { version, source },
+                    projection,
+                }
+            }
+            CommandArg::DiffVersions {
+                from_version,
+                from_timestamp,
+                to_version,
+                to_timestamp,
+                keys,
+                local_store_path,
+                url,
+                nns_public_key,
+            } => {
+                let from_version = Self::version_spec(from_version, from_timestamp)?;
+                let to_version = Self::version_spec(to_version, to_timestamp)?;
+                let source = Self::source_spec(local_store_path, url, nns_public_key)?;
+                let projection = Self::keys_to_projection(keys);
+                Command::DiffVersions {
+                    source,
+                    from_version,
+                    to_version,
+                    projection,
+                }
+            }
         };
         Ok(res)
     }
 
+    /// A timestamp, if provided, takes precedence over the version. (Clap
+    /// ensures that at most one of them is provided.)
+    fn version_spec(version: Option<i64>, timestamp: Option<String>) -> Result<VersionSpec> {
+        match timestamp {
+            Some(timestamp) => Ok(VersionSpec::AtTimestamp(parse_timestamp(&timestamp)?)),
+            None => Ok(version.into()),
+        }
+    }
+
+    fn source_spec(
+        local_store_path: Option<PathBuf>,
+        url: Option<Url>,
+        nns_public_key: Option<PathBuf>,
+    ) -> Result<SourceSpec> {
+        match (local_store_path, url) {
+            (Some(local_store_path), None) => {
+                Ok(SourceSpec::LocalStore(Self::is_dir(local_store_path)?))
+            }
+            (None, Some(url)) => Ok(SourceSpec::Canister(url, get_key_material(nns_public_key)?)),
+            _ => bail!(ArgError::AmbiguousSource),
+        }
+    }
+
     /// Normalize the provided keys argument to a projection. I.e. if the
     /// argument is `None`, this corresponds to any set containing the empty
     /// string.
@@ -284,6 +427,12 @@ pub enum ArgError {
 
     #[error("JsonError when reading file `{0:?}`: {1:?}")]
     JsonError(PathBuf, serde_json::Error),
+
+    #[error("Exactly one of --local-store-path and --url must be specified.")]
+    AmbiguousSource,
+
+    #[error("`{0}` is not a valid RFC 3339 timestamp (e.g. 2024-05-01T12:00:00Z): {1}")]
+    InvalidTimestamp(String, String),
 }
 
 #[derive(Clone, Debug)]
@@ -319,12 +468,22 @@ pub enum Command {
         snapshot: Value,
         amend: bool,
     },
+    DiffVersions {
+        source: SourceSpec,
+        from_version: VersionSpec,
+        to_version: VersionSpec,
+        projection: Projection,
+    },
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum VersionSpec {
     RelativeToLatest(u64),
     Absolute(RegistryVersion),
+    /// The latest version that was created at or before the given time (in
+    /// nanoseconds since the Unix epoch). Needs to be resolved to an absolute
+    /// version (see `source::resolve_version`) before a snapshot can be taken.
+    AtTimestamp(u64),
 }
 
 impl From<Option<i64>> for VersionSpec {
@@ -338,6 +497,16 @@ impl From<Option<i64>> for VersionSpec {
     }
 }
 
+/// Parses an RFC 3339 timestamp into nanoseconds since the Unix epoch.
+fn parse_timestamp(timestamp: &str) -> Result<u64> {
+    let invalid_timestamp = |reason: String| ArgError::InvalidTimestamp(timestamp.into(), reason);
+    let nanos = DateTime::parse_from_rfc3339(timestamp)
+        .map_err(|e| invalid_timestamp(e.to_string()))?
+        .timestamp_nanos_opt()
+        .ok_or_else(|| invalid_timestamp("out of range".into()))?;
+    u64::try_from(nanos).map_err(|_| anyhow!(invalid_timestamp("before the Unix epoch".into())))
+}
+
 // This code is taken from rs/prep/src/prep_state_directory.rs
 fn parse_threshold_sig_key<P: AsRef<std::path::Path> + fmt::Debug>(pem_file: P) -> Result<Vec<u8>> {
     let buf =
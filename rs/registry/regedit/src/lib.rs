@@ -7,6 +7,7 @@ mod protobuf;
 mod snapshot;
 mod source;
 mod tests;
+mod version_diff;
 
 use anyhow::{anyhow, Result};
 use args::{universal_projection, Command, RegistrySpec, SourceSpec, VersionSpec};
@@ -20,8 +21,9 @@ use snapshot::Snapshot;
 use std::{fs::File, io::Write, path::PathBuf};
 
 fn registry_spec_to_snapshot(registry_spec: RegistrySpec) -> Result<Snapshot> {
+    let version = source::resolve_version(&registry_spec.source, registry_spec.version)?;
     let cl = source::get_changelog(registry_spec.source)?;
-    snapshot::changelog_to_snapshot(cl, registry_spec.version)
+    snapshot::changelog_to_snapshot(cl, version)
 }
 
 /// Returns registry entries in delta pb encoded format with the latest version that appears in the pb.
@@ -132,6 +134,27 @@ pub fn execute_command(cmd: Command) -> Result<Value> {
             local_store.store(v, changelog_entry)?;
             diff.0
         }
+        Command::DiffVersions {
+            source,
+            from_version,
+            to_version,
+            projection,
+        } => {
+            let from_version = source::resolve_version(&source, from_version)?;
+            let to_version = source::resolve_version(&source, to_version)?;
+            // Both snapshots are taken from the same changelog, so that it only
+            // needs to be fetched once.
+            let cl = source::get_changelog(source)?;
+            let from_snapshot = snapshot::changelog_to_snapshot(cl.clone(), from_version)?;
+            let to_snapshot = snapshot::changelog_to_snapshot(cl, to_version)?;
+
+            let (normalized_from_snapshot, _) = normalization::normalize(from_snapshot.0);
+            let (normalized_to_snapshot, _) = normalization::normalize(to_snapshot.0);
+            version_diff::make_version_diff(
+                &projection::project(normalized_from_snapshot.0, projection.clone()),
+                &projection::project(normalized_to_snapshot.0, projection),
+            )?
+        }
     };
     Ok(res)
 }
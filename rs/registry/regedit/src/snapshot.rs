@@ -24,6 +24,9 @@ pub fn changelog_to_snapshot(changelog: Changelog, version: VersionSpec) -> Resu
             }
         }
         VersionSpec::Absolute(v) => v.get(),
+        VersionSpec::AtTimestamp(timestamp) => {
+            bail!(SnapshotCreationError::UnresolvedTimestamp(timestamp))
+        }
     };
 
     changelog.retain(|x| x.version.get() <= bound);
@@ -63,4 +66,7 @@ pub enum SnapshotCreationError {
         latest_version: u64,
         relative_version: i64,
     },
+
+    #[error("Timestamp {0} has not been resolved to a registry version.")]
+    UnresolvedTimestamp(u64),
 }
@@ -1,11 +1,14 @@
-use crate::args::SourceSpec;
-use anyhow::Result;
+use crate::args::{SourceSpec, VersionSpec};
+use anyhow::{bail, Result};
 use ic_registry_client::client::{
     RegistryDataProvider, RegistryTransportRecord, RegistryVersion, ZERO_REGISTRY_VERSION,
 };
 use ic_registry_local_store::LocalStoreImpl;
+use ic_registry_nns_data_provider::registry::RegistryCanister;
 use ic_registry_nns_data_provider_wrappers::create_nns_data_provider;
-use std::sync::Arc;
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+use thiserror::Error;
+use url::Url;
 
 pub type Changelog = (Vec<RegistryTransportRecord>, RegistryVersion);
 
@@ -31,6 +34,76 @@ pub fn get_changelog(source_spec: SourceSpec) -> Result<Changelog> {
     Ok((records, version))
 }
 
+/// Turns a `VersionSpec::AtTimestamp` into the absolute version that was
+/// current at that time. Other version specs are returned as they are.
+///
+/// Only the registry canister records when versions were created, thus, this
+/// fails for local stores. Note that the timestamps are not certified, even if
+/// an NNS public key is provided.
+pub fn resolve_version(source_spec: &SourceSpec, version: VersionSpec) -> Result<VersionSpec> {
+    let timestamp = match version {
+        VersionSpec::AtTimestamp(timestamp) => timestamp,
+        version => return Ok(version),
+    };
+
+    let url = match source_spec {
+        SourceSpec::LocalStore(path) => bail!(SourceError::NoTimestamps(path.clone())),
+        SourceSpec::Canister(url, _) => url.clone(),
+    };
+    let version_timestamps = get_version_timestamps(url)?;
+
+    Ok(VersionSpec::Absolute(version_at_timestamp(
+        &version_timestamps,
+        timestamp,
+    )?))
+}
+
+/// Returns the creation time of each version.
+fn get_version_timestamps(url: Url) -> Result<BTreeMap<u64, u64>> {
+    let registry_canister = RegistryCanister::new(vec![url]);
+    let rt_handle = tokio::runtime::Handle::current();
+
+    let mut version = ZERO_REGISTRY_VERSION.get();
+    let mut version_timestamps = BTreeMap::new();
+
+    loop {
+        #[allow(clippy::disallowed_methods)]
+        let (mut batch, latest_version) = tokio::task::block_in_place(|| {
+            rt_handle.block_on(registry_canister.get_version_timestamps_since(version))
+        })?;
+        let Some(&last_version_in_batch) = batch.keys().next_back() else {
+            break;
+        };
+        version = last_version_in_batch;
+        version_timestamps.append(&mut batch);
+        if version >= latest_version {
+            break;
+        }
+    }
+
+    Ok(version_timestamps)
+}
+
+/// Returns the latest version whose timestamp is at or before `timestamp`.
+///
+/// Versions with a timestamp of 0 were created before the registry started
+/// recording timestamps; they are considered to be older than any timestamp.
+fn version_at_timestamp(version_timestamps: &BTreeMap<u64, u64>, timestamp: u64) -> Result<u64> {
+    let version = version_timestamps
+        .iter()
+        .take_while(|(_, version_timestamp)| **version_timestamp <= timestamp)
+        .last()
+        .map(|(version, _)| *version);
+
+    match version {
+        Some(version) => Ok(version),
+        None => bail!(SourceError::NoVersionAtTimestamp {
+            timestamp,
+            earliest_timestamp: version_timestamps.values().next().copied(),
+        }),
+    }
+}
+
 fn source_to_dataprovider(source_spec: SourceSpec) -> Arc<dyn RegistryDataProvider> {
     match source_spec {
         SourceSpec::LocalStore(path) => Arc::new(LocalStoreImpl::new(path)) as Arc<_>,
@@ -39,3 +112,54 @@ fn source_to_dataprovider(source_spec: SourceSpec) -> Arc<dyn RegistryDataProvid
         }
     }
 }
+
+#[derive(Debug, Error)]
+pub enum SourceError {
+    #[error(
+        "The local store at `{0:?}` does not record when versions were created. Use --url \
+        to look up a version by timestamp."
+    )]
+    NoTimestamps(PathBuf),
+
+    #[error(
+        "No registry version was created at or before timestamp {timestamp} \
+        (earliest timestamp: {earliest_timestamp:?})."
+    )]
+    NoVersionAtTimestamp {
+        timestamp: u64,
+        earliest_timestamp: Option<u64>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_at_timestamp_picks_latest_version_not_after_timestamp() {
+        // Versions 1 and 2 predate timestamps.
+        let version_timestamps: BTreeMap<u64, u64> =
+            [(1, 0), (2, 0), (3, 100), (4, 200), (5, 200), (6, 300)]
+                .into_iter()
+                .collect();
+
+        assert_eq!(version_at_timestamp(&version_timestamps, 99).unwrap(), 2);
+        assert_eq!(version_at_timestamp(&version_timestamps, 100).unwrap(), 3);
+        assert_eq!(version_at_timestamp(&version_timestamps, 250).unwrap(), 5);
+        assert_eq!(version_at_timestamp(&version_timestamps, 1000).unwrap(), 6);
+
+        let version_timestamps: BTreeMap<u64, u64> = [(1, 100)].into_iter().collect();
+        assert!(version_at_timestamp(&version_timestamps, 99).is_err());
+    }
+
+    #[test]
+    fn timestamps_cannot_be_resolved_for_local_stores() {
+        let source_spec = SourceSpec::LocalStore(PathBuf::from("/does/not/matter"));
+
+        assert_eq!(
+            resolve_version(&source_spec, VersionSpec::RelativeToLatest(1)).unwrap(),
+            VersionSpec::RelativeToLatest(1)
+        );
+        assert!(resolve_version(&source_spec, VersionSpec::AtTimestamp(42)).is_err());
+    }
+}
@@ -4,6 +4,7 @@ use crate::{
     diff::DELETED_MARKER,
     execute_command, normalization,
     snapshot::SPECIAL_FIELD_PREFIX,
+    version_diff::KeyFamily,
 };
 use ic_prep_lib::{
     internet_computer::{IcConfig, TopologyConfig},
@@ -111,6 +112,52 @@ fn adding_deleting_values_shows_up_in_diff() {
     assert_eq!(expected_snapshot.0, final_snapshot);
 }
 
+#[test]
+fn diff_versions_groups_changes_by_key_family() {
+    let (_guard, ic_prep_dir) = run_ic_prep();
+    let local_store_path = ic_prep_dir.registry_local_store_path();
+    let mut snapshot = execute_command(Command::Snapshot {
+        registry_spec: local_store_latest_snapshot(local_store_path.clone()),
+        projection: universal_projection(),
+    })
+    .unwrap();
+
+    // remove last key (arbitrary choice)
+    let obj = snapshot.as_object_mut().unwrap();
+    let removed_key = filter_special_keys(obj.keys().cloned().collect())
+        .pop()
+        .unwrap();
+    let removed_value = obj.remove(&removed_key).unwrap();
+
+    execute_command(Command::ApplyUpdate {
+        local_store_path: local_store_path.clone(),
+        snapshot,
+        amend: false,
+    })
+    .unwrap();
+
+    let diff = execute_command(Command::DiffVersions {
+        source: SourceSpec::LocalStore(local_store_path),
+        from_version: VersionSpec::RelativeToLatest(1),
+        to_version: VersionSpec::RelativeToLatest(0),
+        projection: universal_projection(),
+    })
+    .unwrap();
+
+    let family = KeyFamily::of(&removed_key).name();
+    assert_eq!(diff["__from_version"], serde_json::json!(1));
+    assert_eq!(diff["__to_version"], serde_json::json!(2));
+    assert_eq!(diff[family]["removed"][&removed_key], removed_value);
+    assert_eq!(
+        filter_special_keys(diff.as_object().unwrap().keys().cloned().collect()),
+        vec![family.to_string()]
+    );
+    assert!(diff["__summary"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!(format!("  - {}", removed_key))));
+}
+
 pub fn local_store_latest_snapshot(path: PathBuf) -> RegistrySpec {
     let source = SourceSpec::LocalStore(path);
     let version = VersionSpec::RelativeToLatest(0);
@@ -0,0 +1,335 @@
+//! Read-only diff between two (normalized) registry snapshots, grouped by key
+//! family. In contrast to [crate::diff], the result is meant to be read by
+//! humans, not to be applied to a registry.
+use crate::{diff::snapshot_to_version, json, snapshot::SPECIAL_FIELD_PREFIX};
+use anyhow::{anyhow, ensure, Result};
+use ic_registry_keys::{
+    make_blessed_replica_versions_key, make_canister_migrations_record_key,
+    make_routing_table_record_key, make_subnet_list_record_key,
+    make_unassigned_nodes_config_record_key, API_BOUNDARY_NODE_RECORD_KEY_PREFIX,
+    CANISTER_RANGES_PREFIX, CHAIN_KEY_ENABLED_SUBNET_LIST_KEY_PREFIX, CRYPTO_RECORD_KEY_PREFIX,
+    CRYPTO_THRESHOLD_SIGNING_KEY_PREFIX, CRYPTO_TLS_CERT_KEY_PREFIX, DATA_CENTER_KEY_PREFIX,
+    ECDSA_SIGNING_SUBNET_LIST_KEY_PREFIX, HOSTOS_VERSION_KEY_PREFIX,
+    NODE_OPERATOR_RECORD_KEY_PREFIX, NODE_RECORD_KEY_PREFIX, REPLICA_VERSION_KEY_PREFIX,
+    ROOT_SUBNET_ID_KEY, SUBNET_RECORD_KEY_PREFIX,
+};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use thiserror::Error;
+
+pub const FROM_VERSION_FIELD: &str = "__from_version";
+pub const TO_VERSION_FIELD: &str = "__to_version";
+pub const SUMMARY_FIELD: &str = "__summary";
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum KeyFamily {
+    Subnets,
+    Nodes,
+    NodeOperators,
+    RoutingTable,
+    ReplicaVersions,
+    Crypto,
+    Firewall,
+    Other,
+}
+
+impl KeyFamily {
+    pub fn of(key: &str) -> Self {
+        let is_any_of = |keys: &[String]| keys.iter().any(|k| k == key);
+        let starts_with_any_of = |prefixes: &[&str]| prefixes.iter().any(|p| key.starts_with(p));
+
+        if key == ROOT_SUBNET_ID_KEY
+            || is_any_of(&[make_subnet_list_record_key()])
+            || starts_with_any_of(&[
+                SUBNET_RECORD_KEY_PREFIX,
+                "catch_up_package_contents_",
+                ECDSA_SIGNING_SUBNET_LIST_KEY_PREFIX,
+                CHAIN_KEY_ENABLED_SUBNET_LIST_KEY_PREFIX,
+            ])
+        {
+            Self::Subnets
+        } else if is_any_of(&[make_unassigned_nodes_config_record_key()])
+            || starts_with_any_of(&[NODE_RECORD_KEY_PREFIX, API_BOUNDARY_NODE_RECORD_KEY_PREFIX])
+        {
+            Self::Nodes
+        } else if starts_with_any_of(&[NODE_OPERATOR_RECORD_KEY_PREFIX, DATA_CENTER_KEY_PREFIX]) {
+            Self::NodeOperators
+        } else if is_any_of(&[
+            make_routing_table_record_key(),
+            make_canister_migrations_record_key(),
+        ]) || starts_with_any_of(&[CANISTER_RANGES_PREFIX])
+        {
+            Self::RoutingTable
+        } else if is_any_of(&[make_blessed_replica_versions_key()])
+            || starts_with_any_of(&[REPLICA_VERSION_KEY_PREFIX, HOSTOS_VERSION_KEY_PREFIX])
+        {
+            Self::ReplicaVersions
+        } else if starts_with_any_of(&[
+            CRYPTO_RECORD_KEY_PREFIX,
+            CRYPTO_TLS_CERT_KEY_PREFIX,
+            CRYPTO_THRESHOLD_SIGNING_KEY_PREFIX,
+        ]) {
+            Self::Crypto
+        } else if key.starts_with("firewall_") {
+            Self::Firewall
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Subnets => "subnets",
+            Self::Nodes => "nodes",
+            Self::NodeOperators => "node_operators",
+            Self::RoutingTable => "routing_table",
+            Self::ReplicaVersions => "replica_versions",
+            Self::Crypto => "crypto",
+            Self::Firewall => "firewall",
+            Self::Other => "other",
+        }
+    }
+}
+
+#[derive(Default)]
+struct FamilyDiff {
+    added: BTreeMap<String, Value>,
+    removed: BTreeMap<String, Value>,
+    modified: BTreeMap<String, (Vec<String>, Value, Value)>,
+}
+
+/// Returns the changes from `from_snapshot` to `to_snapshot`. Only families
+/// that have changes are included. For each family, the output lists the added
+/// and removed values, as well as the modified values (before and after),
+/// together with the names of the top-level fields that changed.
+///
+/// The output also contains a summary with one line per changed key.
+pub fn make_version_diff(from_snapshot: &Value, to_snapshot: &Value) -> Result<Value> {
+    let from_version = snapshot_to_version(from_snapshot)?;
+    let to_version = snapshot_to_version(to_snapshot)?;
+    ensure!(
+        from_version <= to_version,
+        VersionDiffError::FromVersionAfterToVersion {
+            from_version,
+            to_version
+        }
+    );
+
+    let from_obj = as_entries(from_snapshot)?;
+    let to_obj = as_entries(to_snapshot)?;
+
+    let mut families: BTreeMap<KeyFamily, FamilyDiff> = BTreeMap::new();
+    for (key, before) in from_obj.iter() {
+        let family = families.entry(KeyFamily::of(key)).or_default();
+        match to_obj.get(key) {
+            None => {
+                family.removed.insert(key.clone(), before.clone());
+            }
+            Some(after) if after != before => {
+                let changed_fields = changed_fields(before, after);
+                family
+                    .modified
+                    .insert(key.clone(), (changed_fields, before.clone(), after.clone()));
+            }
+            Some(_) => (),
+        }
+    }
+    for (key, after) in to_obj.iter().filter(|(k, _)| !from_obj.contains_key(*k)) {
+        families
+            .entry(KeyFamily::of(key))
+            .or_default()
+            .added
+            .insert(key.clone(), after.clone());
+    }
+    families.retain(|_, family| {
+        !(family.added.is_empty() && family.removed.is_empty() && family.modified.is_empty())
+    });
+
+    let mut summary = vec![format!(
+        "Changes from version {} to version {}:",
+        from_version, to_version
+    )];
+    let mut res = BTreeMap::new();
+    for (family, family_diff) in families {
+        summary.extend(summarize(family, &family_diff));
+
+        let FamilyDiff {
+            added,
+            removed,
+            modified,
+        } = family_diff;
+        let modified: BTreeMap<_, _> = modified
+            .into_iter()
+            .map(|(key, (changed_fields, before, after))| {
+                let mut entry = Map::new();
+                entry.insert(
+                    "changed_fields".into(),
+                    json::assert_to_value(changed_fields),
+                );
+                entry.insert("before".into(), before);
+                entry.insert("after".into(), after);
+                (key, Value::Object(entry))
+            })
+            .collect();
+
+        let mut family_value = Map::new();
+        family_value.insert("added".into(), json::assert_to_value(added));
+        family_value.insert("removed".into(), json::assert_to_value(removed));
+        family_value.insert("modified".into(), json::assert_to_value(modified));
+        res.insert(family.name().to_string(), Value::Object(family_value));
+    }
+
+    res.insert(
+        FROM_VERSION_FIELD.to_string(),
+        json::assert_to_value(from_version),
+    );
+    res.insert(
+        TO_VERSION_FIELD.to_string(),
+        json::assert_to_value(to_version),
+    );
+    res.insert(SUMMARY_FIELD.to_string(), json::assert_to_value(summary));
+
+    Ok(json::assert_to_value(res))
+}
+
+/// Returns the registry entries of a snapshot, i.e., without special fields.
+fn as_entries(snapshot: &Value) -> Result<BTreeMap<String, Value>> {
+    let obj = snapshot
+        .as_object()
+        .ok_or_else(|| anyhow!(VersionDiffError::NotAnObject))?;
+    Ok(obj
+        .iter()
+        .filter(|(k, _)| !k.starts_with(SPECIAL_FIELD_PREFIX))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect())
+}
+
+/// Returns the names of the top-level fields that differ between `before` and
+/// `after`, if both are objects. Otherwise, returns an empty list.
+fn changed_fields(before: &Value, after: &Value) -> Vec<String> {
+    let (Some(before), Some(after)) = (before.as_object(), after.as_object()) else {
+        return vec![];
+    };
+    let field_names: BTreeSet<_> = before.keys().chain(after.keys()).collect();
+    field_names
+        .into_iter()
+        .filter(|field_name| before.get(*field_name) != after.get(*field_name))
+        .cloned()
+        .collect()
+}
+
+fn summarize(family: KeyFamily, family_diff: &FamilyDiff) -> Vec<String> {
+    let mut res = vec![format!(
+        "{}: {} added, {} removed, {} modified",
+        family.name(),
+        family_diff.added.len(),
+        family_diff.removed.len(),
+        family_diff.modified.len()
+    )];
+    res.extend(family_diff.added.keys().map(|key| format!("  + {}", key)));
+    res.extend(family_diff.removed.keys().map(|key| format!("  - {}", key)));
+    res.extend(
+        family_diff
+            .modified
+            .iter()
+            .map(|(key, (changed_fields, _, _))| {
+                if changed_fields.is_empty() {
+                    format!("  ~ {}", key)
+                } else {
+                    format!("  ~ {} ({})", key, changed_fields.join(", "))
+                }
+            }),
+    );
+    res
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum VersionDiffError {
+    #[error(
+        "The version to diff from ({from_version}) is newer than the version to diff to \
+        ({to_version})."
+    )]
+    FromVersionAfterToVersion { from_version: u64, to_version: u64 },
+
+    #[error("Snapshot is not an object.")]
+    NotAnObject,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn changes_are_grouped_by_key_family() {
+        let from_snapshot = json!({
+            "__version": 3,
+            "subnet_record_abc": { "max_ingress_bytes_per_message": 1, "replica_version_id": "a" },
+            "node_record_xyz": { "http": "1.1.1.1" },
+            "routing_table": { "entries": [] },
+            "blessed_replica_versions": { "blessed_version_ids": ["a"] },
+        });
+        let to_snapshot = json!({
+            "__version": 7,
+            "subnet_record_abc": { "max_ingress_bytes_per_message": 2, "replica_version_id": "a" },
+            "node_record_uvw": { "http": "2.2.2.2" },
+            "routing_table": { "entries": [] },
+            "blessed_replica_versions": { "blessed_version_ids": ["a", "b"] },
+        });
+
+        let diff = make_version_diff(&from_snapshot, &to_snapshot).unwrap();
+
+        assert_eq!(
+            diff,
+            json!({
+                "__from_version": 3,
+                "__to_version": 7,
+                "__summary": [
+                    "Changes from version 3 to version 7:",
+                    "subnets: 0 added, 0 removed, 1 modified",
+                    "  ~ subnet_record_abc (max_ingress_bytes_per_message)",
+                    "nodes: 1 added, 1 removed, 0 modified",
+                    "  + node_record_uvw",
+                    "  - node_record_xyz",
+                    "replica_versions: 0 added, 0 removed, 1 modified",
+                    "  ~ blessed_replica_versions (blessed_version_ids)",
+                ],
+                "subnets": {
+                    "added": {},
+                    "removed": {},
+                    "modified": {
+                        "subnet_record_abc": {
+                            "changed_fields": ["max_ingress_bytes_per_message"],
+                            "before": { "max_ingress_bytes_per_message": 1, "replica_version_id": "a" },
+                            "after": { "max_ingress_bytes_per_message": 2, "replica_version_id": "a" },
+                        },
+                    },
+                },
+                "nodes": {
+                    "added": { "node_record_uvw": { "http": "2.2.2.2" } },
+                    "removed": { "node_record_xyz": { "http": "1.1.1.1" } },
+                    "modified": {},
+                },
+                "replica_versions": {
+                    "added": {},
+                    "removed": {},
+                    "modified": {
+                        "blessed_replica_versions": {
+                            "changed_fields": ["blessed_version_ids"],
+                            "before": { "blessed_version_ids": ["a"] },
+                            "after": { "blessed_version_ids": ["a", "b"] },
+                        },
+                    },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn cannot_diff_backwards() {
+        let from_snapshot = json!({ "__version": 7 });
+        let to_snapshot = json!({ "__version": 3 });
+
+        assert!(make_version_diff(&from_snapshot, &to_snapshot).is_err());
+    }
+}
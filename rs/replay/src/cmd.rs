@@ -40,6 +40,15 @@ pub struct ReplayToolArgs {
     #[clap(long)]
     /// The replay will stop at this height and make a checkpoint.
     pub replay_until_height: Option<u64>,
+
+    /// Trace every message executed on this canister during the replay.
+    #[clap(long)]
+    pub trace_canister: Option<CanisterId>,
+
+    /// File to write the canister trace to as JSON lines;
+    /// defaults to `<canister_id>.trace.jsonl`.
+    #[clap(long, requires = "trace_canister")]
+    pub trace_output: Option<PathBuf>,
 }
 
 #[derive(Clone, Subcommand)]
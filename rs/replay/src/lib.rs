@@ -32,6 +32,7 @@ pub mod ingress;
 mod mocks;
 pub mod player;
 mod registry_helper;
+mod trace;
 mod validator;
 
 /// Replays the past blocks and creates a checkpoint of the latest state.
@@ -53,6 +54,8 @@ mod validator;
 ///     canister_caller_id: None,
 ///     replay_until_height: None,
 ///     data_root: None,
+///     trace_canister: None,
+///     trace_output: None,
 ///     subcmd: Some(SubCommand::RestoreFromBackup(RestoreFromBackupCmd {
 ///         registry_local_store_path: PathBuf::from("/path/to/ic_registry_local_store"),
 ///         backup_spool_path: PathBuf::from("/path/to/spool"),
@@ -98,6 +101,8 @@ pub fn replay(args: ReplayToolArgs) -> ReplayResult {
             .0;
 
         let target_height = args.replay_until_height;
+        let trace_canister = args.trace_canister;
+        let trace_output = args.trace_output;
         if let Some(h) = target_height {
             let question = format!("The checkpoint created at height {} ", h)
                 + "cannot be used for deterministic state computation if it is not a CUP height.\n"
@@ -119,7 +124,8 @@ pub fn replay(args: ReplayToolArgs) -> ReplayResult {
                 subnet_id,
                 cmd.start_height,
             )
-            .with_replay_target_height(target_height);
+            .with_replay_target_height(target_height)
+            .with_trace_canister(trace_canister, trace_output);
             *res_clone.borrow_mut() = player.restore_from_backup(cmd.start_height + 1);
             return;
         }
@@ -132,9 +138,9 @@ pub fn replay(args: ReplayToolArgs) -> ReplayResult {
                     "Target height cannot be used with any sub-command in subnet-recovery mode."
                 );
                 }
-                (_, target_height) => {
-                    Player::new(cfg, subnet_id).with_replay_target_height(target_height)
-                }
+                (_, target_height) => Player::new(cfg, subnet_id)
+                    .with_replay_target_height(target_height)
+                    .with_trace_canister(trace_canister, trace_output),
             };
 
             if let Some(SubCommand::GetRecoveryCup(cmd)) = subcmd {
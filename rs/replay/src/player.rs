@@ -2,6 +2,7 @@ use crate::{
     backup,
     backup::{cup_file_name, rename_file},
     ingress::IngressWithPrinter,
+    trace::CanisterTracer,
     validator::{InvalidArtifact, ReplayValidator},
 };
 use async_trait::async_trait;
@@ -70,8 +71,8 @@ use ic_types::{
     messages::{Query, QuerySource},
     signature::ThresholdSignature,
     time::{current_time, expiry_time_from_now},
-    CryptoHashOfPartialState, CryptoHashOfState, Height, NodeId, PrincipalId, Randomness,
-    RegistryVersion, ReplicaVersion, SubnetId, Time, UserId,
+    CanisterId, CryptoHashOfPartialState, CryptoHashOfState, Height, NodeId, PrincipalId,
+    Randomness, RegistryVersion, ReplicaVersion, SubnetId, Time, UserId,
 };
 use mockall::automock;
 use serde::{Deserialize, Serialize};
@@ -141,6 +142,8 @@ pub(crate) struct Player {
     // The target height until which the state will be replayed.
     // None means finalized height.
     replay_target_height: Option<u64>,
+    // Traces the executions on a single canister, if enabled.
+    canister_tracer: Arc<CanisterTracer>,
     runtime: Runtime,
 }
 
@@ -312,7 +315,8 @@ impl Player {
             MaliciousFlags::default(),
         ));
         let (completed_execution_messages_tx, _) = tokio::sync::mpsc::channel(1);
-        let execution_service = ExecutionServices::setup_execution(
+        let canister_tracer = Arc::new(CanisterTracer::default());
+        let execution_service = ExecutionServices::setup_execution_with_tracer(
             log.clone(),
             &metrics_registry,
             subnet_id,
@@ -324,6 +328,7 @@ impl Player {
             state_manager.get_fd_factory(),
             completed_execution_messages_tx,
             &state_manager.state_layout().tmp(),
+            Some(Arc::clone(&canister_tracer) as Arc<_>),
         );
         let message_routing = Arc::new(MessageRoutingImpl::new(
             state_manager.clone(),
@@ -390,6 +395,7 @@ impl Player {
             _async_log_guard,
             tmp_dir: None,
             replay_target_height: None,
+            canister_tracer,
             runtime,
         }
     }
@@ -400,6 +406,29 @@ impl Player {
         self
     }
 
+    /// Trace every message executed on the given canister (if any) during the
+    /// replay and write the trace as JSON lines to the given output file
+    /// (`<canister_id>.trace.jsonl` by default).
+    pub fn with_trace_canister(
+        self,
+        canister_id: Option<CanisterId>,
+        output: Option<PathBuf>,
+    ) -> Self {
+        if let Some(canister_id) = canister_id {
+            let output = output.unwrap_or_else(|| format!("{}.trace.jsonl", canister_id).into());
+            let file = std::fs::File::create(&output).unwrap_or_else(|err| {
+                panic!(
+                    "Couldn't create the trace output file {:?}: {}",
+                    output, err
+                )
+            });
+            println!("Tracing canister {} into {:?}...", canister_id, output);
+            self.canister_tracer
+                .enable(canister_id, Box::new(std::io::BufWriter::new(file)));
+        }
+        self
+    }
+
     /// In case a consensus pool was supplied, replay past finalized but
     /// un-executed blocks by delivering ingress messages for execution,
     /// and make a full checkpoint of the latest state when they all finish.
@@ -697,12 +726,56 @@ impl Player {
     }
 
     /// Deliver finalized batches since last expected batch height.
+    ///
+    /// If a canister is traced, the batches are delivered one by one so that
+    /// the trace of each batch can be written once it is executed.
     fn deliver_batches(
         &self,
         message_routing: &dyn MessageRouting,
         pool: &PoolReader<'_>,
         membership: &Membership,
         replay_target_height: Option<Height>,
+    ) -> Height {
+        // Nothing can be delivered before the first batch is expected.
+        if !self.canister_tracer.is_enabled()
+            || message_routing.expected_batch_height() == Height::from(0)
+        {
+            return self.deliver_batches_until(
+                message_routing,
+                pool,
+                membership,
+                replay_target_height,
+            );
+        }
+        let expected_batch_height = message_routing.expected_batch_height();
+        let mut last_batch_height = expected_batch_height.decrement();
+        loop {
+            let next_batch_height = message_routing.expected_batch_height();
+            let max_batch_height = replay_target_height
+                .map_or(next_batch_height, |height| height.min(next_batch_height));
+            let batch_height = self.deliver_batches_until(
+                message_routing,
+                pool,
+                membership,
+                Some(max_batch_height),
+            );
+            if batch_height < next_batch_height {
+                break last_batch_height;
+            }
+            self.wait_for_state(batch_height);
+            self.flush_trace(batch_height);
+            last_batch_height = batch_height;
+        }
+    }
+
+    /// Deliver finalized batches since last expected batch height up to the
+    /// given height.
+    fn deliver_batches_until(
+        &self,
+        message_routing: &dyn MessageRouting,
+        pool: &PoolReader<'_>,
+        membership: &Membership,
+        replay_target_height: Option<Height>,
     ) -> Height {
         let expected_batch_height = message_routing.expected_batch_height();
         let last_batch_height = loop {
@@ -798,6 +871,7 @@ impl Player {
                 Ok(()) => {
                     println!("Delivered batch {}", extra_batch.batch_number);
                     self.wait_for_state(extra_batch.batch_number);
+                    self.flush_trace(extra_batch.batch_number);
 
                     // We are done once we delivered a batch for a new checkpoint
                     if extra_batch.requires_full_state_hash {
@@ -1190,6 +1264,29 @@ impl Player {
     fn get_state_hash(&self, height: Height) -> Option<CryptoHashOfState> {
         get_state_hash(self.state_manager.as_ref(), &self.log, height)
     }
+
+    /// Writes the trace of the executions on the traced canister (if any) in
+    /// the batch at the given height, which must have been executed already.
+    fn flush_trace(&self, height: Height) {
+        if !self.canister_tracer.is_enabled() {
+            return;
+        }
+        // The partial state hashes of all heights are available until the
+        // certifications are redelivered at the end of the replay.
+        let state_hashes: BTreeMap<_, _> = self
+            .state_manager
+            .list_state_hashes_to_certify()
+            .into_iter()
+            .filter(|(h, _)| *h == height.decrement() || *h == height)
+            .collect();
+        self.canister_tracer
+            .flush(
+                height,
+                state_hashes.get(&height.decrement()),
+                state_hashes.get(&height),
+            )
+            .unwrap_or_else(|err| panic!("Couldn't write the canister trace: {}", err));
+    }
 }
 
 // This is just to avoid clippy complaints about complicated return type.
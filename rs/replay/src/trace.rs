@@ -0,0 +1,274 @@
+//! Execution trace of a single canister during a replay (`--trace-canister`).
+//!
+//! Every message or task executed on the traced canister is written as a
+//! JSON object on its own line (JSON lines). The entries of a batch are
+//! written once the batch is executed, together with the partial state hashes
+//! of the subnet before and after the batch.
+
+use ic_error_types::ErrorCode;
+use ic_execution_environment::{ExecutionTraceEntry, ExecutionTraceOutcome, ExecutionTracer};
+use ic_types::{CanisterId, CryptoHashOfPartialState, Height};
+use serde::Serialize;
+use std::{
+    io::Write,
+    sync::{Mutex, OnceLock},
+};
+
+/// A line of the execution trace.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub(crate) struct TraceRecord {
+    /// The height of the batch in which the message or task was executed.
+    pub height: u64,
+    pub canister_id: String,
+    /// The traced canister or, for subnet messages targeting it (e.g.,
+    /// `install_code`), the management canister.
+    pub receiver: String,
+    pub method: String,
+    pub caller: Option<String>,
+    /// `None` if the execution was paused, see `instructions_used` of
+    /// [`ExecutionTraceEntry`].
+    pub instructions: Option<u64>,
+    pub heap_delta: u64,
+    /// One of `reply`, `reject`, `error`, `no_response`, or `paused`.
+    pub outcome: &'static str,
+    /// The hex-encoded reply payload.
+    pub reply: Option<String>,
+    pub reject_code: Option<u64>,
+    pub error_code: Option<ErrorCode>,
+    pub reject_message: Option<String>,
+    /// The hex-encoded partial state hash of the subnet before the batch.
+    pub state_hash_before: Option<String>,
+    /// The hex-encoded partial state hash of the subnet after the batch.
+    pub state_hash_after: Option<String>,
+}
+
+impl TraceRecord {
+    fn new(
+        height: Height,
+        entry: ExecutionTraceEntry,
+        state_hash_before: Option<&CryptoHashOfPartialState>,
+        state_hash_after: Option<&CryptoHashOfPartialState>,
+    ) -> Self {
+        let mut record = Self {
+            height: height.get(),
+            canister_id: entry.canister_id.to_string(),
+            receiver: entry.receiver().to_string(),
+            method: entry.method_name(),
+            caller: entry.caller().map(|caller| caller.to_string()),
+            instructions: entry
+                .instructions_used
+                .map(|instructions| instructions.get()),
+            heap_delta: entry.heap_delta.get(),
+            outcome: "",
+            reply: None,
+            reject_code: None,
+            error_code: None,
+            reject_message: None,
+            state_hash_before: state_hash_before.map(|hash| hex::encode(&hash.get_ref().0)),
+            state_hash_after: state_hash_after.map(|hash| hex::encode(&hash.get_ref().0)),
+        };
+        match entry.outcome {
+            ExecutionTraceOutcome::Reply(payload) => {
+                record.outcome = "reply";
+                record.reply = Some(hex::encode(payload));
+            }
+            ExecutionTraceOutcome::Reject(context) => {
+                record.outcome = "reject";
+                record.reject_code = Some(context.code() as u64);
+                record.reject_message = Some(context.message().clone());
+            }
+            ExecutionTraceOutcome::Error(err) => {
+                record.outcome = "error";
+                record.reject_code = Some(err.reject_code() as u64);
+                record.error_code = Some(err.code());
+                record.reject_message = Some(err.description().to_string());
+            }
+            ExecutionTraceOutcome::NoResponse => record.outcome = "no_response",
+            ExecutionTraceOutcome::Paused => record.outcome = "paused",
+        }
+        record
+    }
+}
+
+/// Collects the executions of the traced canister (if any) and writes them
+/// to the trace output after each batch.
+#[derive(Default)]
+pub(crate) struct CanisterTracer {
+    canister_id: OnceLock<CanisterId>,
+    entries: Mutex<Vec<ExecutionTraceEntry>>,
+    output: Mutex<Option<Box<dyn Write + Send>>>,
+}
+
+impl CanisterTracer {
+    /// Starts tracing the given canister into the given output.
+    pub(crate) fn enable(&self, canister_id: CanisterId, output: Box<dyn Write + Send>) {
+        self.canister_id
+            .set(canister_id)
+            .expect("Canister tracing can only be enabled once");
+        *self.output.lock().unwrap() = Some(output);
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.canister_id.get().is_some()
+    }
+
+    /// Writes the entries traced since the last call, i.e., while executing
+    /// the batch at the given height.
+    pub(crate) fn flush(
+        &self,
+        height: Height,
+        state_hash_before: Option<&CryptoHashOfPartialState>,
+        state_hash_after: Option<&CryptoHashOfPartialState>,
+    ) -> std::io::Result<()> {
+        let entries = std::mem::take(&mut *self.entries.lock().unwrap());
+        let mut output = self.output.lock().unwrap();
+        let Some(output) = output.as_mut() else {
+            return Ok(());
+        };
+        for entry in entries {
+            let record = TraceRecord::new(height, entry, state_hash_before, state_hash_after);
+            serde_json::to_writer(&mut *output, &record)?;
+            writeln!(output)?;
+        }
+        output.flush()
+    }
+}
+
+impl ExecutionTracer for CanisterTracer {
+    fn is_traced(&self, canister_id: CanisterId) -> bool {
+        self.canister_id.get() == Some(&canister_id)
+    }
+
+    fn trace(&self, entry: ExecutionTraceEntry) {
+        self.entries.lock().unwrap().push(entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_error_types::{RejectCode, UserError};
+    use ic_test_utilities_types::{
+        ids::{canister_test_id, user_test_id},
+        messages::IngressBuilder,
+    };
+    use ic_types::{
+        crypto::CryptoHash,
+        messages::{CanisterMessage, CanisterMessageOrTask, CanisterTask, RejectContext},
+        NumBytes, NumInstructions,
+    };
+    use std::sync::Arc;
+
+    /// A writer whose output can be inspected after it was handed to the tracer.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn lines(buffer: &SharedBuffer) -> Vec<serde_json::Value> {
+        String::from_utf8(buffer.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn entry(
+        canister_id: CanisterId,
+        input: CanisterMessageOrTask,
+        outcome: ExecutionTraceOutcome,
+    ) -> ExecutionTraceEntry {
+        ExecutionTraceEntry {
+            canister_id,
+            input,
+            instructions_used: Some(NumInstructions::from(1_000)),
+            heap_delta: NumBytes::from(4096),
+            outcome,
+        }
+    }
+
+    #[test]
+    fn only_traces_the_enabled_canister() {
+        let tracer = CanisterTracer::default();
+        assert!(!tracer.is_enabled());
+        assert!(!tracer.is_traced(canister_test_id(1)));
+
+        tracer.enable(canister_test_id(1), Box::new(SharedBuffer::default()));
+        assert!(tracer.is_enabled());
+        assert!(tracer.is_traced(canister_test_id(1)));
+        assert!(!tracer.is_traced(canister_test_id(2)));
+    }
+
+    #[test]
+    fn flush_writes_one_json_line_per_execution() {
+        let canister_id = canister_test_id(1);
+        let buffer = SharedBuffer::default();
+        let tracer = CanisterTracer::default();
+        tracer.enable(canister_id, Box::new(buffer.clone()));
+
+        let ingress = IngressBuilder::new()
+            .source(user_test_id(7))
+            .receiver(canister_id)
+            .method_name("transfer")
+            .build();
+        tracer.trace(entry(
+            canister_id,
+            CanisterMessageOrTask::Message(CanisterMessage::Ingress(Arc::new(ingress))),
+            ExecutionTraceOutcome::Reply(vec![0xca, 0xfe]),
+        ));
+        tracer.trace(entry(
+            canister_id,
+            CanisterMessageOrTask::Task(CanisterTask::Heartbeat),
+            ExecutionTraceOutcome::Reject(RejectContext::new(RejectCode::CanisterError, "trapped")),
+        ));
+        let hash_before = CryptoHashOfPartialState::from(CryptoHash(vec![1; 32]));
+        let hash_after = CryptoHashOfPartialState::from(CryptoHash(vec![2; 32]));
+        tracer
+            .flush(Height::from(10), Some(&hash_before), Some(&hash_after))
+            .unwrap();
+
+        let lines = lines(&buffer);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["height"], 10);
+        assert_eq!(lines[0]["canister_id"], canister_id.to_string());
+        assert_eq!(lines[0]["receiver"], canister_id.to_string());
+        assert_eq!(lines[0]["method"], "transfer");
+        assert_eq!(lines[0]["caller"], user_test_id(7).get().to_string());
+        assert_eq!(lines[0]["instructions"], 1_000);
+        assert_eq!(lines[0]["outcome"], "reply");
+        assert_eq!(lines[0]["reply"], "cafe");
+        assert_eq!(lines[0]["state_hash_before"], hex::encode([1; 32]));
+        assert_eq!(lines[0]["state_hash_after"], hex::encode([2; 32]));
+        assert_eq!(lines[1]["method"], "canister_heartbeat");
+        assert_eq!(lines[1]["caller"], serde_json::Value::Null);
+        assert_eq!(lines[1]["outcome"], "reject");
+        assert_eq!(lines[1]["reject_code"], RejectCode::CanisterError as u64);
+        assert_eq!(lines[1]["reject_message"], "trapped");
+
+        // Entries are only written once.
+        tracer.flush(Height::from(11), None, None).unwrap();
+        assert_eq!(lines(&buffer).len(), 2);
+
+        tracer.trace(entry(
+            canister_id,
+            CanisterMessageOrTask::Task(CanisterTask::GlobalTimer),
+            ExecutionTraceOutcome::Error(UserError::new(ErrorCode::CanisterTrapped, "boom")),
+        ));
+        tracer.flush(Height::from(12), None, None).unwrap();
+        let lines = lines(&buffer);
+        assert_eq!(lines[2]["height"], 12);
+        assert_eq!(lines[2]["method"], "canister_global_timer");
+        assert_eq!(lines[2]["outcome"], "error");
+        assert_eq!(lines[2]["error_code"], "CanisterTrapped");
+        assert_eq!(lines[2]["reject_message"], "boom");
+        assert_eq!(lines[2]["state_hash_before"], serde_json::Value::Null);
+    }
+}
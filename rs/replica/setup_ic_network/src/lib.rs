@@ -45,7 +45,7 @@ use ic_replicated_state::ReplicatedState;
 use ic_state_manager::state_sync::types::StateSyncMessage;
 use ic_types::{
     artifact::UnvalidatedArtifactMutation,
-    canister_http::{CanisterHttpRequest, CanisterHttpResponse, CanisterHttpResponseShare},
+    canister_http::{CanisterHttpRequest, CanisterHttpResponse, CanisterHttpResponseArtifact},
     consensus::{
         certification::CertificationMessage, dkg, idkg::IDkgMessage, CatchUpPackage,
         ConsensusMessage, HasHeight,
@@ -192,7 +192,7 @@ struct AbortableBroadcastChannels {
     certifier: AbortableBroadcastChannel<CertificationMessage>,
     dkg: AbortableBroadcastChannel<dkg::Message>,
     idkg: AbortableBroadcastChannel<IDkgMessage>,
-    https_outcalls: AbortableBroadcastChannel<CanisterHttpResponseShare>,
+    https_outcalls: AbortableBroadcastChannel<CanisterHttpResponseArtifact>,
 }
 
 impl AbortableBroadcastChannels {
@@ -4,6 +4,7 @@ use ic_btc_interface::NetworkInRequest as BitcoinNetwork;
 use ic_btc_replica_types::{GetSuccessorsResponseComplete, GetSuccessorsResponsePartial};
 use ic_btc_service::{
     btc_service_server::{BtcService, BtcServiceServer},
+    BtcServiceGetBlockFiltersRequest, BtcServiceGetBlockFiltersResponse,
     BtcServiceGetSuccessorsRequest, BtcServiceGetSuccessorsResponse,
     BtcServiceSendTransactionRequest, BtcServiceSendTransactionResponse,
 };
@@ -15,9 +16,9 @@ use ic_config::{
 use ic_error_types::RejectCode;
 use ic_http_endpoints_async_utils::incoming_from_path;
 use ic_management_canister_types_private::{
-    self as ic00, BitcoinGetBalanceArgs, BitcoinGetCurrentFeePercentilesArgs,
-    BitcoinGetSuccessorsArgs, BitcoinGetUtxosArgs, BitcoinSendTransactionArgs,
-    BitcoinSendTransactionInternalArgs, EmptyBlob, Method, Payload,
+    self as ic00, BitcoinGetBalanceArgs, BitcoinGetBlockFilterArgs,
+    BitcoinGetCurrentFeePercentilesArgs, BitcoinGetSuccessorsArgs, BitcoinGetUtxosArgs,
+    BitcoinSendTransactionArgs, BitcoinSendTransactionInternalArgs, EmptyBlob, Method, Payload,
 };
 use ic_registry_subnet_type::SubnetType;
 use ic_replica_tests as utils;
@@ -108,6 +109,15 @@ impl BtcService for MockBitcoinAdapter {
             .clone()
             .map(tonic::Response::new)
     }
+
+    async fn get_block_filters(
+        &self,
+        _request: tonic::Request<BtcServiceGetBlockFiltersRequest>,
+    ) -> Result<tonic::Response<BtcServiceGetBlockFiltersResponse>, tonic::Status> {
+        Ok(tonic::Response::new(
+            BtcServiceGetBlockFiltersResponse::default(),
+        ))
+    }
 }
 
 fn spawn_mock_bitcoin_adapter(
@@ -478,6 +488,7 @@ fn mock_bitcoin_canister_wat(network: BitcoinNetwork) -> String {
               (export "canister_update bitcoin_get_block_headers" (func $ping))
               (export "canister_update bitcoin_send_transaction" (func $ping))
               (export "canister_update bitcoin_get_current_fee_percentiles" (func $ping))
+              (export "canister_update bitcoin_get_block_filter" (func $ping))
             )"#,
         network
     )
@@ -519,6 +530,14 @@ fn test_canister_routing(env: StateMachine, networks: Vec<BitcoinNetwork>) {
                 "bitcoin_get_current_fee_percentiles",
                 BitcoinGetCurrentFeePercentilesArgs { network }.encode(),
             ),
+            (
+                "bitcoin_get_block_filter",
+                BitcoinGetBlockFilterArgs {
+                    network,
+                    block_hash: vec![0; 32],
+                }
+                .encode(),
+            ),
             (
                 "bitcoin_send_transaction",
                 BitcoinSendTransactionArgs {
@@ -679,6 +698,14 @@ fn requests_are_rejected_if_no_bitcoin_canisters_are_set() {
                 "bitcoin_get_current_fee_percentiles",
                 BitcoinGetCurrentFeePercentilesArgs { network }.encode(),
             ),
+            (
+                "bitcoin_get_block_filter",
+                BitcoinGetBlockFilterArgs {
+                    network,
+                    block_hash: vec![0; 32],
+                }
+                .encode(),
+            ),
             (
                 "bitcoin_send_transaction",
                 BitcoinSendTransactionArgs {
@@ -475,6 +475,8 @@ pub async fn exec<C: CallCanisters>(
                     store_canister_id: Some(store_canister_id.get()),
                     chunk_hashes_list,
                 }),
+                take_canister_snapshot: None,
+                health_check_method: None,
             },
         )),
     };
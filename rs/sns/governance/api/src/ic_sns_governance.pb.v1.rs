@@ -370,6 +370,13 @@ pub struct UpgradeSnsControlledCanister {
     /// If the entire WASM does not fit into the 2 MiB ingress limit, then `new_canister_wasm` should be
     /// an empty, and this field should be set instead.
     pub chunked_canister_wasm: ::core::option::Option<ChunkedCanisterWasm>,
+    /// If true, a snapshot of the canister is taken before the upgrade, and the snapshot is loaded
+    /// back into the canister if the health check (see `health_check_method`) fails.
+    pub take_canister_snapshot: Option<bool>,
+    /// The name of an update method of the upgraded canister that is called (with no arguments)
+    /// after the upgrade. If the call is rejected, the upgrade is considered to have failed.
+    /// Can only be set if `take_canister_snapshot` is true.
+    pub health_check_method: Option<String>,
 }
 /// A proposal to transfer SNS treasury funds to (optionally a Subaccount of) the
 /// target principal.
@@ -452,6 +459,30 @@ pub struct MintSnsTokens {
     /// An (optional) Subaccount of the principal to transfer the funds to.
     pub to_subaccount: Option<Subaccount>,
 }
+/// A proposal to create a stream of recurring payments from the SNS treasury. Once the
+/// proposal is executed, Governance makes `payment` every `period_seconds`, until
+/// `number_of_payments` payments have been made, or until the stream is cancelled by a
+/// CancelTreasuryPaymentStream proposal.
+///
+/// Each payment is subject to the same 7-day upper bound on the total amount transferred from
+/// the treasury as TransferSnsTreasuryFunds proposals, based on the valuation of the treasury at
+/// the time when this proposal was submitted.
+#[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, PartialEq)]
+pub struct CreateTreasuryPaymentStream {
+    /// The transfer to make in each period.
+    pub payment: Option<TransferSnsTreasuryFunds>,
+    /// The amount of time between two consecutive payments. The first payment is made right
+    /// after the proposal is executed.
+    pub period_seconds: u64,
+    /// The total number of payments to make.
+    pub number_of_payments: u64,
+}
+/// A proposal to stop making payments of a treasury payment stream.
+#[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CancelTreasuryPaymentStream {
+    /// The ID of the stream to cancel, i.e., the ID of the proposal that created it.
+    pub treasury_payment_stream_id: u64,
+}
 /// A proposal function to change the values of SNS metadata.
 /// Fields with None values will remain unchanged.
 #[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, PartialEq)]
@@ -648,6 +679,14 @@ pub mod proposal {
         ///
         /// Id = 16;
         SetTopicsForCustomProposals(super::SetTopicsForCustomProposals),
+        /// Create a stream of recurring payments from the SNS treasury.
+        ///
+        /// Id = 17.
+        CreateTreasuryPaymentStream(super::CreateTreasuryPaymentStream),
+        /// Cancel a stream of recurring payments from the SNS treasury.
+        ///
+        /// Id = 18.
+        CancelTreasuryPaymentStream(super::CancelTreasuryPaymentStream),
     }
 }
 #[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, PartialEq)]
@@ -959,6 +998,10 @@ pub struct ProposalData {
     pub action_auxiliary: Option<proposal_data::ActionAuxiliary>,
     /// This proposal's topic.
     pub topic: Option<topics::Topic>,
+    /// The steps that were taken to execute the proposal, for proposals whose execution
+    /// consists of several steps (currently, UpgradeSnsControlledCanister proposals with
+    /// take_canister_snapshot set).
+    pub execution_steps: Option<Vec<ProposalExecutionStep>>,
 }
 /// Nested message and enum types in `ProposalData`.
 pub mod proposal_data {
@@ -971,6 +1014,10 @@ pub mod proposal_data {
         pub valuation: Option<super::Valuation>,
     }
     #[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, PartialEq)]
+    pub struct CreateTreasuryPaymentStreamActionAuxiliary {
+        pub valuation: Option<super::Valuation>,
+    }
+    #[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, PartialEq)]
     pub struct AdvanceSnsTargetVersionActionAuxiliary {
         /// Corresponds to the Some(target_version) from an AdvanceSnsTargetVersion proposal, or
         /// to the last SNS version known to this SNS at the time of AdvanceSnsTargetVersion creation.
@@ -983,6 +1030,59 @@ pub mod proposal_data {
         TransferSnsTreasuryFunds(TransferSnsTreasuryFundsActionAuxiliary),
         MintSnsTokens(MintSnsTokensActionAuxiliary),
         AdvanceSnsTargetVersion(AdvanceSnsTargetVersionActionAuxiliary),
+        CreateTreasuryPaymentStream(CreateTreasuryPaymentStreamActionAuxiliary),
+    }
+}
+/// A step in the execution of a proposal.
+#[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, PartialEq)]
+pub struct ProposalExecutionStep {
+    /// The timestamp, in seconds since the Unix epoch, when the step was completed.
+    pub timestamp_seconds: u64,
+    /// What was done in this step, e.g., "Took snapshot 0a1b... of canister ...".
+    pub description: String,
+    /// If set, the step failed for this reason.
+    pub error: Option<String>,
+}
+/// A stream of recurring payments from the SNS treasury, created by a
+/// CreateTreasuryPaymentStream proposal.
+#[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, PartialEq)]
+pub struct TreasuryPaymentStream {
+    /// The ID of the proposal that created this stream.
+    pub id: u64,
+    /// The parameters of the stream, as specified in the proposal that created it.
+    pub parameters: Option<CreateTreasuryPaymentStream>,
+    /// The most recent valuation of the treasury, which the 7-day upper bound on the amount
+    /// transferred from the treasury is based on. Initially, this is the valuation at the time when
+    /// the proposal that created this stream was submitted. The treasury is valued again before
+    /// each payment.
+    pub valuation: Option<Valuation>,
+    /// When the next payment is due (seconds since the Unix epoch).
+    pub next_payment_timestamp_seconds: u64,
+    /// The payments that have been made so far, oldest first.
+    pub payments: Vec<treasury_payment_stream::Payment>,
+    /// Set if the stream was cancelled by a CancelTreasuryPaymentStream proposal.
+    pub cancelled_timestamp_seconds: Option<u64>,
+    /// The last failed attempt to make the next payment, if any. Cleared once the payment is made.
+    pub last_failed_payment_attempt: Option<treasury_payment_stream::FailedPaymentAttempt>,
+}
+/// Nested message and enum types in `TreasuryPaymentStream`.
+pub mod treasury_payment_stream {
+    #[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, Copy, PartialEq)]
+    pub struct Payment {
+        /// When the payment was initiated (seconds since the Unix epoch).
+        pub timestamp_seconds: u64,
+        /// The amount transferred, in e8s.
+        pub amount_e8s: u64,
+        /// The index of the ledger block containing the transfer. Not set while the transfer is
+        /// in progress.
+        pub block_index: Option<u64>,
+    }
+    #[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, PartialEq)]
+    pub struct FailedPaymentAttempt {
+        /// When the attempt was made (seconds since the Unix epoch).
+        pub timestamp_seconds: u64,
+        /// Why the payment could not be made.
+        pub reason: String,
     }
 }
 #[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, PartialEq)]
@@ -1374,6 +1474,12 @@ pub struct Governance {
     /// Information about the timers that perform periodic tasks of this Governance canister.
     pub timers: Option<::ic_nervous_system_proto::pb::v1::Timers>,
     pub upgrade_journal: Option<UpgradeJournal>,
+    /// The streams of recurring payments from the treasury that are active, or finished recently
+    /// enough that their payments still count towards the 7-day upper bound on the amount
+    /// transferred from the treasury.
+    pub treasury_payment_streams: Option<Vec<TreasuryPaymentStream>>,
+    /// The dapp canister upgrade in progress, if any.
+    pub pending_dapp_canister_upgrade: Option<governance::PendingDappCanisterUpgrade>,
 }
 /// Nested message and enum types in `Governance`.
 pub mod governance {
@@ -1560,6 +1666,23 @@ pub mod governance {
         /// The proposal that initiated this upgrade
         pub proposal_id: Option<u64>,
     }
+    /// An upgrade of a dapp canister, requested by an UpgradeSnsControlledCanister proposal with
+    /// take_canister_snapshot set, that is carried out one step at a time by run_periodic_tasks.
+    #[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, PartialEq)]
+    pub struct PendingDappCanisterUpgrade {
+        /// The UpgradeSnsControlledCanister proposal that requested the upgrade.
+        pub proposal_id: u64,
+        /// The canister being upgraded.
+        pub canister_id: Option<::ic_base_types::PrincipalId>,
+        /// The method called to check the health of the canister after the upgrade, if any.
+        pub health_check_method: Option<String>,
+        /// The step that the next invocation of run_periodic_tasks performs.
+        pub next_step: i32,
+        /// The ID of the snapshot, once it is taken.
+        pub snapshot_id: Option<Vec<u8>>,
+        /// If set, the proposal fails with this reason once the remaining steps are performed.
+        pub failure_reason: Option<String>,
+    }
     #[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, Copy, PartialEq)]
     pub struct MaturityModulation {
         /// When X maturity is disbursed, the amount that goes to the destination
@@ -1658,6 +1781,8 @@ pub mod get_sns_status_response {
     pub struct Metrics {
         pub num_recently_submitted_proposals: Option<u64>,
         pub last_ledger_block_timestamp: Option<u64>,
+        pub num_active_treasury_payment_streams: Option<u64>,
+        pub num_recent_treasury_payment_stream_payments: Option<u64>,
     }
 
     #[derive(candid::CandidType, candid::Deserialize, Debug, Clone, PartialEq)]
@@ -1668,7 +1793,7 @@ pub mod get_sns_status_response {
 
     #[derive(candid::CandidType, candid::Deserialize, Debug, Clone, PartialEq)]
     pub struct GetMetricsResponse {
-        get_metrics_result: Option<GetMetricsResult>,
+        pub get_metrics_result: Option<GetMetricsResult>,
     }
 }
 
@@ -2214,6 +2339,14 @@ pub struct ListNervousSystemFunctionsResponse {
     /// used to add new NervousSystemFunctions.
     pub reserved_ids: Vec<u64>,
 }
+/// A request to list the treasury payment streams.
+#[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ListTreasuryPaymentStreamsRequest {}
+/// The response to a ListTreasuryPaymentStreams request.
+#[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, PartialEq)]
+pub struct ListTreasuryPaymentStreamsResponse {
+    pub treasury_payment_streams: Vec<TreasuryPaymentStream>,
+}
 #[derive(Default, candid::CandidType, candid::Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct SetMode {
     pub mode: i32,
@@ -6,6 +6,7 @@ use ic_base_types::{CanisterId, PrincipalId};
 use ic_canister_log::log;
 use ic_canister_profiler::{measure_span, measure_span_async};
 use ic_cdk::{caller as cdk_caller, init, post_upgrade, pre_upgrade, query, update};
+use ic_cdk_next::call::{Call, CallFailed};
 use ic_cdk_timers::TimerId;
 use ic_http_types::{HttpRequest, HttpResponse, HttpResponseBuilder};
 use ic_nervous_system_canisters::{cmc::CMCCanister, ledger::IcpLedgerCanister};
@@ -42,14 +43,15 @@ use ic_sns_governance_api::pb::v1::{
     GetRunningSnsVersionRequest, GetRunningSnsVersionResponse,
     GetSnsInitializationParametersRequest, GetSnsInitializationParametersResponse,
     GetUpgradeJournalRequest, GetUpgradeJournalResponse, Governance as GovernanceApi,
-    ListNervousSystemFunctionsResponse, ListNeurons, ListNeuronsResponse, ListProposals,
-    ListProposalsResponse, ManageNeuron, ManageNeuronResponse, NervousSystemParameters,
-    RewardEvent, SetMode, SetModeResponse,
+    GovernanceError, ListNervousSystemFunctionsResponse, ListNeurons, ListNeuronsResponse,
+    ListProposals, ListProposalsResponse, ListTreasuryPaymentStreamsRequest,
+    ListTreasuryPaymentStreamsResponse, ManageNeuron, ManageNeuronResponse,
+    NervousSystemParameters, RewardEvent, SetMode, SetModeResponse,
 };
 #[cfg(feature = "test")]
 use ic_sns_governance_api::pb::v1::{
     AddMaturityRequest, AddMaturityResponse, AdvanceTargetVersionRequest,
-    AdvanceTargetVersionResponse, GovernanceError, MintTokensRequest, MintTokensResponse,
+    AdvanceTargetVersionResponse, MintTokensRequest, MintTokensResponse,
     RefreshCachedUpgradeStepsRequest, RefreshCachedUpgradeStepsResponse,
 };
 use prost::Message;
@@ -165,6 +167,35 @@ impl Environment for CanisterEnv {
             .map_err(|(rejection_code, message)| (Some(rejection_code as i32), message))
     }
 
+    // Calls a method of another canister with a bounded wait, so that Governance does not wait
+    // for the response indefinitely, e.g., when checking the health of an upgraded dapp canister.
+    async fn call_canister_with_timeout(
+        &self,
+        canister_id: CanisterId,
+        method_name: &str,
+        arg: Vec<u8>,
+        timeout_seconds: u32,
+    ) -> Result<
+        /* reply: */ Vec<u8>,
+        (
+            /* error_code: */ Option<i32>,
+            /* message: */ String,
+        ),
+    > {
+        Call::bounded_wait(canister_id.get().0, method_name)
+            .change_timeout(timeout_seconds)
+            .with_raw_args(&arg)
+            .await
+            .map(|response| response.into_bytes())
+            .map_err(|err| match err {
+                CallFailed::CallRejected(rejected) => (
+                    Some(rejected.raw_reject_code() as i32),
+                    rejected.reject_message().to_string(),
+                ),
+                err => (None, err.to_string()),
+            })
+    }
+
     #[cfg(target_arch = "wasm32")]
     fn heap_growth_potential(&self) -> HeapGrowthPotential {
         if core::arch::wasm32::memory_size(0)
@@ -354,8 +385,15 @@ fn get_metadata(request: GetMetadataRequest) -> GetMetadataResponse {
 
 /// Returns aggregate SNS metrics.
 #[query(composite = true)]
-async fn get_metrics(_request: GetMetricsRequest) -> get_sns_status_response::GetMetricsResponse {
-    unimplemented!()
+async fn get_metrics(request: GetMetricsRequest) -> get_sns_status_response::GetMetricsResponse {
+    log!(INFO, "get_metrics");
+    let get_metrics_result = match governance().get_metrics(request) {
+        Ok(metrics) => get_sns_status_response::GetMetricsResult::Ok(metrics),
+        Err(err) => get_sns_status_response::GetMetricsResult::Err(GovernanceError::from(err)),
+    };
+    get_sns_status_response::GetMetricsResponse {
+        get_metrics_result: Some(get_metrics_result),
+    }
 }
 
 /// Returns the initialization parameters used to spawn an SNS
@@ -466,6 +504,17 @@ fn list_nervous_system_functions() -> ListNervousSystemFunctionsResponse {
     ListNervousSystemFunctionsResponse::from(governance().list_nervous_system_functions())
 }
 
+/// Returns the treasury payment streams that are active, or finished recently.
+#[query]
+fn list_treasury_payment_streams(
+    request: ListTreasuryPaymentStreamsRequest,
+) -> ListTreasuryPaymentStreamsResponse {
+    log!(INFO, "list_treasury_payment_streams");
+    ListTreasuryPaymentStreamsResponse::from(governance().list_treasury_payment_streams(
+        sns_gov_pb::ListTreasuryPaymentStreamsRequest::from(request),
+    ))
+}
+
 /// Returns the latest reward event.
 #[query]
 fn get_latest_reward_event() -> RewardEvent {
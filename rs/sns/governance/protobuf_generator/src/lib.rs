@@ -135,6 +135,15 @@ pub fn generate_prost_files(proto: ProtoPaths<'_>, out: &Path) {
         );
     }
 
+    // Fields that are missing from older encodings of the governance state.
+    let default_fields = vec!["Governance.treasury_payment_streams"];
+    for field in default_fields {
+        config.field_attribute(
+            format!(".ic_sns_governance.pb.v1.{}", field),
+            "#[serde(default)]",
+        );
+    }
+
     std::fs::create_dir_all(out).expect("failed to create output directory");
     config.out_dir(out);
 
@@ -471,6 +471,15 @@ pub struct UpgradeSnsControlledCanister {
     /// empty, and this field should be set instead.
     #[prost(message, optional, tag = "5")]
     pub chunked_canister_wasm: ::core::option::Option<ChunkedCanisterWasm>,
+    /// If true, a snapshot of the canister is taken before the upgrade, and the snapshot is loaded
+    /// back into the canister if the health check (see `health_check_method`) fails.
+    #[prost(bool, optional, tag = "6")]
+    pub take_canister_snapshot: ::core::option::Option<bool>,
+    /// The name of an update method of the upgraded canister that is called (with no arguments)
+    /// after the upgrade. If the call is rejected, the upgrade is considered to have failed.
+    /// Can only be set if `take_canister_snapshot` is true.
+    #[prost(string, optional, tag = "7")]
+    pub health_check_method: ::core::option::Option<::prost::alloc::string::String>,
 }
 /// A proposal to transfer SNS treasury funds to (optionally a Subaccount of) the
 /// target principal.
@@ -589,6 +598,49 @@ pub struct MintSnsTokens {
     #[prost(message, optional, tag = "4")]
     pub to_subaccount: ::core::option::Option<Subaccount>,
 }
+/// A proposal to create a stream of recurring payments from the SNS treasury. Once the
+/// proposal is executed, Governance makes `payment` every `period_seconds`, until
+/// `number_of_payments` payments have been made, or until the stream is cancelled by a
+/// CancelTreasuryPaymentStream proposal.
+///
+/// Each payment is subject to the same 7-day upper bound on the total amount transferred from
+/// the treasury as TransferSnsTreasuryFunds proposals, based on the valuation of the treasury at
+/// the time when this proposal was submitted.
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    Clone,
+    PartialEq,
+    ::prost::Message,
+)]
+pub struct CreateTreasuryPaymentStream {
+    /// The transfer to make in each period.
+    #[prost(message, optional, tag = "1")]
+    pub payment: ::core::option::Option<TransferSnsTreasuryFunds>,
+    /// The amount of time between two consecutive payments. The first payment is made right
+    /// after the proposal is executed.
+    #[prost(uint64, tag = "2")]
+    pub period_seconds: u64,
+    /// The total number of payments to make.
+    #[prost(uint64, tag = "3")]
+    pub number_of_payments: u64,
+}
+/// A proposal to stop making payments of a treasury payment stream.
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    Clone,
+    Copy,
+    PartialEq,
+    ::prost::Message,
+)]
+pub struct CancelTreasuryPaymentStream {
+    /// The ID of the stream to cancel, i.e., the ID of the proposal that created it.
+    #[prost(uint64, tag = "1")]
+    pub treasury_payment_stream_id: u64,
+}
 /// A proposal function to change the values of SNS metadata.
 /// Fields with None values will remain unchanged.
 #[derive(
@@ -776,7 +828,7 @@ pub struct Proposal {
     /// of this mapping.
     #[prost(
         oneof = "proposal::Action",
-        tags = "4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20"
+        tags = "4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22"
     )]
     pub action: ::core::option::Option<proposal::Action>,
 }
@@ -891,6 +943,16 @@ pub mod proposal {
         /// Id = 16;
         #[prost(message, tag = "20")]
         SetTopicsForCustomProposals(super::SetTopicsForCustomProposals),
+        /// Create a stream of recurring payments from the SNS treasury.
+        ///
+        /// Id = 17.
+        #[prost(message, tag = "21")]
+        CreateTreasuryPaymentStream(super::CreateTreasuryPaymentStream),
+        /// Cancel a stream of recurring payments from the SNS treasury.
+        ///
+        /// Id = 18.
+        #[prost(message, tag = "22")]
+        CancelTreasuryPaymentStream(super::CancelTreasuryPaymentStream),
     }
 }
 #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
@@ -1138,6 +1200,8 @@ pub struct ProposalData {
     /// Id 14 - ManageDappCanisterSettings proposals.
     /// Id 15 - AdvanceSnsTargetVersion proposals.
     /// Id 16 - SetTopicsForCustomProposals proposals.
+    /// Id 17 - CreateTreasuryPaymentStream proposals.
+    /// Id 18 - CancelTreasuryPaymentStream proposals.
     #[prost(uint64, tag = "1")]
     pub action: u64,
     /// This is stored here temporarily. It is also stored on the map
@@ -1262,9 +1326,14 @@ pub struct ProposalData {
     /// This proposal's topic.
     #[prost(enumeration = "Topic", optional, tag = "25")]
     pub topic: ::core::option::Option<i32>,
+    /// The steps that were taken to execute the proposal, for proposals whose execution
+    /// consists of several steps (currently, UpgradeSnsControlledCanister proposals with
+    /// take_canister_snapshot set).
+    #[prost(message, repeated, tag = "26")]
+    pub execution_steps: ::prost::alloc::vec::Vec<ProposalExecutionStep>,
     /// In general, this holds data retrieved at proposal submission/creation time and used later
     /// during execution. This varies based on the action of the proposal.
-    #[prost(oneof = "proposal_data::ActionAuxiliary", tags = "22, 23, 24")]
+    #[prost(oneof = "proposal_data::ActionAuxiliary", tags = "22, 23, 24, 27")]
     pub action_auxiliary: ::core::option::Option<proposal_data::ActionAuxiliary>,
 }
 /// Nested message and enum types in `ProposalData`.
@@ -1301,6 +1370,18 @@ pub mod proposal_data {
         PartialEq,
         ::prost::Message,
     )]
+    pub struct CreateTreasuryPaymentStreamActionAuxiliary {
+        #[prost(message, optional, tag = "1")]
+        pub valuation: ::core::option::Option<super::Valuation>,
+    }
+    #[derive(
+        candid::CandidType,
+        candid::Deserialize,
+        comparable::Comparable,
+        Clone,
+        PartialEq,
+        ::prost::Message,
+    )]
     pub struct AdvanceSnsTargetVersionActionAuxiliary {
         /// Corresponds to the Some(target_version) from an AdvanceSnsTargetVersion proposal, or
         /// to the last SNS version known to this SNS at the time of AdvanceSnsTargetVersion creation.
@@ -1324,6 +1405,105 @@ pub mod proposal_data {
         MintSnsTokens(MintSnsTokensActionAuxiliary),
         #[prost(message, tag = "24")]
         AdvanceSnsTargetVersion(AdvanceSnsTargetVersionActionAuxiliary),
+        #[prost(message, tag = "27")]
+        CreateTreasuryPaymentStream(CreateTreasuryPaymentStreamActionAuxiliary),
+    }
+}
+/// A step in the execution of a proposal.
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    Clone,
+    PartialEq,
+    ::prost::Message,
+)]
+pub struct ProposalExecutionStep {
+    /// The timestamp, in seconds since the Unix epoch, when the step was completed.
+    #[prost(uint64, tag = "1")]
+    pub timestamp_seconds: u64,
+    /// What was done in this step, e.g., "Took snapshot 0a1b... of canister ...".
+    #[prost(string, tag = "2")]
+    pub description: ::prost::alloc::string::String,
+    /// If set, the step failed for this reason.
+    #[prost(string, optional, tag = "3")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// A stream of recurring payments from the SNS treasury, created by a
+/// CreateTreasuryPaymentStream proposal.
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    Clone,
+    PartialEq,
+    ::prost::Message,
+)]
+pub struct TreasuryPaymentStream {
+    /// The ID of the proposal that created this stream.
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    /// The parameters of the stream, as specified in the proposal that created it.
+    #[prost(message, optional, tag = "2")]
+    pub parameters: ::core::option::Option<CreateTreasuryPaymentStream>,
+    /// The most recent valuation of the treasury, which the 7-day upper bound on the amount
+    /// transferred from the treasury is based on. Initially, this is the valuation at the time when
+    /// the proposal that created this stream was submitted. The treasury is valued again before
+    /// each payment.
+    #[prost(message, optional, tag = "3")]
+    pub valuation: ::core::option::Option<Valuation>,
+    /// When the next payment is due (seconds since the Unix epoch).
+    #[prost(uint64, tag = "4")]
+    pub next_payment_timestamp_seconds: u64,
+    /// The payments that have been made so far, oldest first.
+    #[prost(message, repeated, tag = "5")]
+    pub payments: ::prost::alloc::vec::Vec<treasury_payment_stream::Payment>,
+    /// Set if the stream was cancelled by a CancelTreasuryPaymentStream proposal.
+    #[prost(uint64, optional, tag = "6")]
+    pub cancelled_timestamp_seconds: ::core::option::Option<u64>,
+    /// The last failed attempt to make the next payment, if any. Cleared once the payment is made.
+    #[prost(message, optional, tag = "7")]
+    pub last_failed_payment_attempt:
+        ::core::option::Option<treasury_payment_stream::FailedPaymentAttempt>,
+}
+/// Nested message and enum types in `TreasuryPaymentStream`.
+pub mod treasury_payment_stream {
+    #[derive(
+        candid::CandidType,
+        candid::Deserialize,
+        comparable::Comparable,
+        Clone,
+        Copy,
+        PartialEq,
+        ::prost::Message,
+    )]
+    pub struct Payment {
+        /// When the payment was initiated (seconds since the Unix epoch).
+        #[prost(uint64, tag = "1")]
+        pub timestamp_seconds: u64,
+        /// The amount transferred, in e8s.
+        #[prost(uint64, tag = "2")]
+        pub amount_e8s: u64,
+        /// The index of the ledger block containing the transfer. Not set while the transfer is
+        /// in progress.
+        #[prost(uint64, optional, tag = "3")]
+        pub block_index: ::core::option::Option<u64>,
+    }
+    #[derive(
+        candid::CandidType,
+        candid::Deserialize,
+        comparable::Comparable,
+        Clone,
+        PartialEq,
+        ::prost::Message,
+    )]
+    pub struct FailedPaymentAttempt {
+        /// When the attempt was made (seconds since the Unix epoch).
+        #[prost(uint64, tag = "1")]
+        pub timestamp_seconds: u64,
+        /// Why the payment could not be made.
+        #[prost(string, tag = "2")]
+        pub reason: ::prost::alloc::string::String,
     }
 }
 #[derive(
@@ -1836,6 +2016,16 @@ pub struct Governance {
     pub timers: ::core::option::Option<::ic_nervous_system_proto::pb::v1::Timers>,
     #[prost(message, optional, tag = "32")]
     pub upgrade_journal: ::core::option::Option<UpgradeJournal>,
+    /// The streams of recurring payments from the treasury that are active, or finished recently
+    /// enough that their payments still count towards the 7-day upper bound on the amount
+    /// transferred from the treasury.
+    #[prost(message, repeated, tag = "33")]
+    #[serde(default)]
+    pub treasury_payment_streams: ::prost::alloc::vec::Vec<TreasuryPaymentStream>,
+    /// The dapp canister upgrade in progress, if any.
+    #[prost(message, optional, tag = "34")]
+    pub pending_dapp_canister_upgrade:
+        ::core::option::Option<governance::PendingDappCanisterUpgrade>,
 }
 /// Nested message and enum types in `Governance`.
 pub mod governance {
@@ -2140,6 +2330,95 @@ pub mod governance {
         #[prost(uint64, optional, tag = "3")]
         pub response_timestamp_seconds: ::core::option::Option<u64>,
     }
+    /// An upgrade of a dapp canister, requested by an UpgradeSnsControlledCanister proposal with
+    /// take_canister_snapshot set, that is carried out one step at a time by run_periodic_tasks.
+    #[derive(
+        candid::CandidType,
+        candid::Deserialize,
+        comparable::Comparable,
+        Clone,
+        PartialEq,
+        ::prost::Message,
+    )]
+    pub struct PendingDappCanisterUpgrade {
+        /// The UpgradeSnsControlledCanister proposal that requested the upgrade.
+        #[prost(uint64, tag = "1")]
+        pub proposal_id: u64,
+        /// The canister being upgraded.
+        #[prost(message, optional, tag = "2")]
+        pub canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
+        /// The method called to check the health of the canister after the upgrade, if any.
+        #[prost(string, optional, tag = "3")]
+        pub health_check_method: ::core::option::Option<::prost::alloc::string::String>,
+        /// The step that the next invocation of run_periodic_tasks performs.
+        #[prost(enumeration = "pending_dapp_canister_upgrade::Step", tag = "4")]
+        pub next_step: i32,
+        /// The ID of the snapshot, once it is taken.
+        #[prost(bytes = "vec", optional, tag = "5")]
+        pub snapshot_id: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+        /// If set, the proposal fails with this reason once the remaining steps are performed.
+        #[prost(string, optional, tag = "6")]
+        pub failure_reason: ::core::option::Option<::prost::alloc::string::String>,
+    }
+    /// Nested message and enum types in `PendingDappCanisterUpgrade`.
+    pub mod pending_dapp_canister_upgrade {
+        #[derive(
+            candid::CandidType,
+            candid::Deserialize,
+            comparable::Comparable,
+            Clone,
+            Copy,
+            Debug,
+            PartialEq,
+            Eq,
+            Hash,
+            PartialOrd,
+            Ord,
+            ::prost::Enumeration,
+        )]
+        #[repr(i32)]
+        pub enum Step {
+            Unspecified = 0,
+            /// Take a snapshot of the canister.
+            TakeSnapshot = 1,
+            /// Install the new WASM in the canister.
+            Upgrade = 2,
+            /// Call the health check method of the canister.
+            CheckHealth = 3,
+            /// Load the snapshot back into the canister, because the health check failed.
+            LoadSnapshot = 4,
+            /// Delete the snapshot, which is no longer needed.
+            DeleteSnapshot = 5,
+        }
+        impl Step {
+            /// String value of the enum field names used in the ProtoBuf definition.
+            ///
+            /// The values are not transformed in any way and thus are considered stable
+            /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+            pub fn as_str_name(&self) -> &'static str {
+                match self {
+                    Self::Unspecified => "STEP_UNSPECIFIED",
+                    Self::TakeSnapshot => "STEP_TAKE_SNAPSHOT",
+                    Self::Upgrade => "STEP_UPGRADE",
+                    Self::CheckHealth => "STEP_CHECK_HEALTH",
+                    Self::LoadSnapshot => "STEP_LOAD_SNAPSHOT",
+                    Self::DeleteSnapshot => "STEP_DELETE_SNAPSHOT",
+                }
+            }
+            /// Creates an enum from field names used in the ProtoBuf definition.
+            pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+                match value {
+                    "STEP_UNSPECIFIED" => Some(Self::Unspecified),
+                    "STEP_TAKE_SNAPSHOT" => Some(Self::TakeSnapshot),
+                    "STEP_UPGRADE" => Some(Self::Upgrade),
+                    "STEP_CHECK_HEALTH" => Some(Self::CheckHealth),
+                    "STEP_LOAD_SNAPSHOT" => Some(Self::LoadSnapshot),
+                    "STEP_DELETE_SNAPSHOT" => Some(Self::DeleteSnapshot),
+                    _ => None,
+                }
+            }
+        }
+    }
     #[derive(
         candid::CandidType,
         candid::Deserialize,
@@ -3283,6 +3562,30 @@ pub struct ListNervousSystemFunctionsResponse {
     #[prost(uint64, repeated, tag = "2")]
     pub reserved_ids: ::prost::alloc::vec::Vec<u64>,
 }
+/// A request to list the treasury payment streams.
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    Clone,
+    Copy,
+    PartialEq,
+    ::prost::Message,
+)]
+pub struct ListTreasuryPaymentStreamsRequest {}
+/// The response to a ListTreasuryPaymentStreams request.
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    Clone,
+    PartialEq,
+    ::prost::Message,
+)]
+pub struct ListTreasuryPaymentStreamsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub treasury_payment_streams: ::prost::alloc::vec::Vec<TreasuryPaymentStream>,
+}
 #[derive(
     candid::CandidType,
     candid::Deserialize,
@@ -19,9 +19,12 @@ use crate::{
     },
     pb::{
         sns_root_types::{
-            ManageDappCanisterSettingsRequest, ManageDappCanisterSettingsResponse,
-            RegisterDappCanistersRequest, RegisterDappCanistersResponse, SetDappControllersRequest,
-            SetDappControllersResponse,
+            ChangeDappCanisterResponse, DeleteCanisterSnapshotRequest,
+            DeleteCanisterSnapshotResponse, LoadCanisterSnapshotRequest,
+            LoadCanisterSnapshotResponse, ManageDappCanisterSettingsRequest,
+            ManageDappCanisterSettingsResponse, RegisterDappCanistersRequest,
+            RegisterDappCanistersResponse, SetDappControllersRequest, SetDappControllersResponse,
+            TakeCanisterSnapshotRequest, TakeCanisterSnapshotResponse,
         },
         v1::{
             claim_swap_neurons_response::SwapNeuron,
@@ -29,7 +32,8 @@ use crate::{
             governance::{
                 self,
                 neuron_in_flight_command::{self, Command as InFlightCommand},
-                MaturityModulation, NeuronInFlightCommand, PendingVersion, SnsMetadata, Version,
+                pending_dapp_canister_upgrade, MaturityModulation, NeuronInFlightCommand,
+                PendingDappCanisterUpgrade, PendingVersion, SnsMetadata, Version,
             },
             governance_error::ErrorType,
             manage_neuron::{
@@ -46,38 +50,47 @@ use crate::{
             proposal::Action,
             proposal_data::ActionAuxiliary as ActionAuxiliaryPb,
             transfer_sns_treasury_funds::TransferFrom,
+            treasury_payment_stream::{FailedPaymentAttempt, Payment},
             upgrade_journal_entry, Account as AccountProto, AddMaturityRequest,
             AddMaturityResponse, AdvanceTargetVersionRequest, AdvanceTargetVersionResponse, Ballot,
-            ClaimSwapNeuronsError, ClaimSwapNeuronsRequest, ClaimSwapNeuronsResponse,
-            ClaimedSwapNeuronStatus, DefaultFollowees, DeregisterDappCanisters,
-            DisburseMaturityInProgress, Empty, ExecuteGenericNervousSystemFunction,
-            FailStuckUpgradeInProgressRequest, FailStuckUpgradeInProgressResponse,
-            GetMaturityModulationRequest, GetMaturityModulationResponse, GetMetadataRequest,
-            GetMetadataResponse, GetMode, GetModeResponse, GetNeuron, GetNeuronResponse,
-            GetProposal, GetProposalResponse, GetSnsInitializationParametersRequest,
-            GetSnsInitializationParametersResponse, Governance as GovernanceProto, GovernanceError,
-            ListNervousSystemFunctionsResponse, ListNeurons, ListNeuronsResponse, ListProposals,
-            ListProposalsResponse, ManageDappCanisterSettings, ManageLedgerParameters,
-            ManageNeuron, ManageNeuronResponse, ManageSnsMetadata, MintSnsTokens,
-            MintTokensRequest, MintTokensResponse, NervousSystemFunction, NervousSystemParameters,
-            Neuron, NeuronId, NeuronPermission, NeuronPermissionList, NeuronPermissionType,
-            Proposal, ProposalData, ProposalDecisionStatus, ProposalId, ProposalRewardStatus,
+            CancelTreasuryPaymentStream, ClaimSwapNeuronsError, ClaimSwapNeuronsRequest,
+            ClaimSwapNeuronsResponse, ClaimedSwapNeuronStatus, CreateTreasuryPaymentStream,
+            DefaultFollowees, DeregisterDappCanisters, DisburseMaturityInProgress, Empty,
+            ExecuteGenericNervousSystemFunction, FailStuckUpgradeInProgressRequest,
+            FailStuckUpgradeInProgressResponse, GetMaturityModulationRequest,
+            GetMaturityModulationResponse, GetMetadataRequest, GetMetadataResponse, GetMode,
+            GetModeResponse, GetNeuron, GetNeuronResponse, GetProposal, GetProposalResponse,
+            GetSnsInitializationParametersRequest, GetSnsInitializationParametersResponse,
+            Governance as GovernanceProto, GovernanceError, ListNervousSystemFunctionsResponse,
+            ListNeurons, ListNeuronsResponse, ListProposals, ListProposalsResponse,
+            ListTreasuryPaymentStreamsRequest, ListTreasuryPaymentStreamsResponse,
+            ManageDappCanisterSettings, ManageLedgerParameters, ManageNeuron, ManageNeuronResponse,
+            ManageSnsMetadata, MintSnsTokens, MintTokensRequest, MintTokensResponse,
+            NervousSystemFunction, NervousSystemParameters, Neuron, NeuronId, NeuronPermission,
+            NeuronPermissionList, NeuronPermissionType, Proposal, ProposalData,
+            ProposalDecisionStatus, ProposalExecutionStep, ProposalId, ProposalRewardStatus,
             RegisterDappCanisters, RewardEvent, SetTopicsForCustomProposals, Tally, Topic,
-            TransferSnsTreasuryFunds, UpgradeSnsControlledCanister, Vote, WaitForQuietState,
+            TransferSnsTreasuryFunds, TreasuryPaymentStream, UpgradeSnsControlledCanister,
+            Valuation as ValuationPb, Vote, WaitForQuietState,
         },
     },
     proposal::{
         get_action_auxiliary,
         transfer_sns_treasury_funds_amount_is_small_enough_at_execution_time_or_err,
-        validate_and_render_proposal, validate_and_render_set_topics_for_custom_proposals,
-        ValidGenericNervousSystemFunction, MAX_LIST_PROPOSAL_RESULTS,
+        treasury_token_and_account, validate_and_render_proposal,
+        validate_and_render_set_topics_for_custom_proposals, ValidGenericNervousSystemFunction,
+        EXECUTED_TRANSFER_SNS_TREASURY_FUNDS_PROPOSAL_RETENTION_DURATION_SECONDS,
+        MAX_LIST_PROPOSAL_RESULTS, MAX_NUMBER_OF_ACTIVE_TREASURY_PAYMENT_STREAMS,
         MAX_NUMBER_OF_PROPOSALS_WITH_BALLOTS,
     },
     sns_upgrade::{
         canister_type_and_wasm_hash_for_upgrade, get_all_sns_canisters, get_canisters_to_upgrade,
         get_running_version, get_upgrade_params, get_wasm, SnsCanisterType, UpgradeSnsParams,
     },
-    types::{is_registered_function_id, Environment, HeapGrowthPotential, LedgerUpdateLock, Wasm},
+    types::{
+        is_registered_function_id, CanisterTokenValuer, Environment, HeapGrowthPotential,
+        LedgerUpdateLock, TokenValuer, Wasm,
+    },
 };
 use candid::{Decode, Encode};
 #[cfg(not(target_arch = "wasm32"))]
@@ -107,6 +120,7 @@ use ic_nervous_system_root::change_canister::ChangeCanisterRequest;
 use ic_nervous_system_timestamp::format_timestamp_for_humans;
 use ic_nns_constants::LEDGER_CANISTER_ID as NNS_LEDGER_CANISTER_ID;
 use ic_protobuf::types::v1::CanisterInstallMode as CanisterInstallModeProto;
+use ic_sns_governance_api::pb::v1::{get_sns_status_response::Metrics, GetMetricsRequest};
 use ic_sns_governance_proposal_criticality::ProposalCriticality;
 use ic_sns_governance_token_valuation::Valuation;
 use icp_ledger::DEFAULT_TRANSFER_FEE as NNS_DEFAULT_TRANSFER_FEE;
@@ -178,6 +192,13 @@ pub const UPGRADE_PERIODIC_TASK_LOCK_TIMEOUT_SECONDS: u64 = 600;
 /// But this is only true for proposals that are less than 1 day old, to prevent a stuck proposal from blocking all upgrades forever.
 const UPGRADE_PROPOSAL_BLOCK_EXPIRY_SECONDS: u64 = 60 * 60 * 24; // 1 day
 
+/// After a payment of a treasury payment stream fails, it is not attempted again for this long.
+pub const TREASURY_PAYMENT_STREAM_RETRY_INTERVAL_SECONDS: u64 = 60 * 60; // 1 hour
+
+/// How long the health check of a dapp canister that was upgraded by an
+/// UpgradeSnsControlledCanister proposal may take before it is considered failed.
+pub const DAPP_CANISTER_HEALTH_CHECK_TIMEOUT_SECONDS: u32 = 60;
+
 /// Converts bytes to a subaccountpub fn bytes_to_subaccount(bytes: &[u8]) -> Result<icrc_ledger_types::icrc1::account::Subaccount, GovernanceError> {
 pub fn bytes_to_subaccount(
     bytes: &[u8],
@@ -618,6 +639,9 @@ pub struct Governance {
     /// Implementation of the interface with the CMC canister.
     cmc: Box<dyn CMC>,
 
+    /// Assesses the value of the treasury before each payment of a treasury payment stream.
+    token_valuer: Box<dyn TokenValuer>,
+
     // Stores information about the instruction usage of various "spans", which
     // map roughly to the execution of a single update call.
     pub profiling_information: &'static LocalKey<RefCell<SpanStats>>,
@@ -744,6 +768,7 @@ impl Governance {
             profiling_information: &PROFILING_INFORMATION,
             nns_ledger,
             cmc,
+            token_valuer: Box::new(CanisterTokenValuer),
             function_followee_index: BTreeMap::new(),
             topic_follower_index: BTreeMap::new(),
             principal_to_neuron_ids_index: BTreeMap::new(),
@@ -764,6 +789,12 @@ impl Governance {
         self
     }
 
+    /// Replaces how the value of the treasury is assessed, e.g., with a fake in tests.
+    pub fn with_token_valuer(mut self, token_valuer: Box<dyn TokenValuer>) -> Self {
+        self.token_valuer = token_valuer;
+        self
+    }
+
     pub fn check_test_features_enabled(&self) {
         assert!(self.test_features_enabled, "Test features are not enabled");
     }
@@ -1886,6 +1917,69 @@ impl Governance {
         }
     }
 
+    /// Returns the treasury payment streams that are active, or finished recently.
+    pub fn list_treasury_payment_streams(
+        &self,
+        request: ListTreasuryPaymentStreamsRequest,
+    ) -> ListTreasuryPaymentStreamsResponse {
+        let ListTreasuryPaymentStreamsRequest {} = request;
+
+        ListTreasuryPaymentStreamsResponse {
+            treasury_payment_streams: self.proto.treasury_payment_streams.clone(),
+        }
+    }
+
+    /// Returns metrics about this SNS. Metrics about events (e.g. proposal submissions) only
+    /// consider events from the past `time_window_seconds`.
+    pub fn get_metrics(&self, request: GetMetricsRequest) -> Result<Metrics, GovernanceError> {
+        let GetMetricsRequest {
+            time_window_seconds,
+        } = request;
+
+        let Some(time_window_seconds) = time_window_seconds else {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::InvalidCommand,
+                "time_window_seconds must be specified.",
+            ));
+        };
+
+        let min_timestamp_seconds = self.env.now().saturating_sub(time_window_seconds);
+
+        let num_recently_submitted_proposals = self
+            .proto
+            .proposals
+            .values()
+            .filter(|proposal| {
+                proposal.proposal_creation_timestamp_seconds >= min_timestamp_seconds
+            })
+            .count() as u64;
+
+        let num_active_treasury_payment_streams = self
+            .proto
+            .treasury_payment_streams
+            .iter()
+            .filter(|stream| stream.is_active())
+            .count() as u64;
+
+        let num_recent_treasury_payment_stream_payments = self
+            .proto
+            .treasury_payment_streams
+            .iter()
+            .flat_map(|stream| stream.payments.iter())
+            .filter(|payment| payment.timestamp_seconds >= min_timestamp_seconds)
+            .count() as u64;
+
+        Ok(Metrics {
+            num_recently_submitted_proposals: Some(num_recently_submitted_proposals),
+            // The ledger is not queried for its blocks (yet).
+            last_ledger_block_timestamp: None,
+            num_active_treasury_payment_streams: Some(num_active_treasury_payment_streams),
+            num_recent_treasury_payment_stream_payments: Some(
+                num_recent_treasury_payment_stream_payments,
+            ),
+        })
+    }
+
     /// Returns the proposal IDs for all proposals that have reward status ReadyToSettle
     fn ready_to_be_settled_proposal_ids(&self) -> impl Iterator<Item = ProposalId> + '_ {
         let now = self.env.now();
@@ -2041,8 +2135,16 @@ impl Governance {
                 self.perform_manage_nervous_system_parameters(params)
             }
             Action::UpgradeSnsControlledCanister(params) => {
-                self.perform_upgrade_sns_controlled_canister(proposal_id, params)
+                // As for UpgradeSnsToNextVersion below, `Ok(false)` means that the upgrade is
+                // carried out by run_periodic_tasks, which then sets the proposal status.
+                match self
+                    .perform_upgrade_sns_controlled_canister(proposal_id, params)
                     .await
+                {
+                    Ok(true) => Ok(()),
+                    Ok(false) => return,
+                    Err(e) => Err(e),
+                }
             }
             Action::UpgradeSnsToNextVersion(_) => {
                 log!(INFO, "Executing UpgradeSnsToNextVersion action",);
@@ -2114,6 +2216,17 @@ impl Governance {
             Action::SetTopicsForCustomProposals(set_topics_for_custom_proposals) => {
                 self.perform_set_topics_for_custom_proposals(set_topics_for_custom_proposals)
             }
+            Action::CreateTreasuryPaymentStream(create) => {
+                let valuation =
+                    get_action_auxiliary(&self.proto.proposals, ProposalId { id: proposal_id })
+                        .and_then(|action_auxiliary| {
+                            action_auxiliary.unwrap_create_treasury_payment_stream_or_err()
+                        });
+                self.perform_create_treasury_payment_stream(proposal_id, valuation, create)
+            }
+            Action::CancelTreasuryPaymentStream(cancel) => {
+                self.perform_cancel_treasury_payment_stream(cancel)
+            }
             // This should not be possible, because Proposal validation is performed when
             // a proposal is first made.
             Action::Unspecified(_) => Err(GovernanceError::new_with_message(
@@ -2453,11 +2566,15 @@ impl Governance {
     /// Executes a UpgradeSnsControlledCanister proposal by calling the root canister
     /// to upgrade an SNS controlled canister.  This does not upgrade "core" SNS canisters
     /// (i.e. Root, Governance, Ledger, Ledger Archives, or Sale)
+    ///
+    /// Returns `Ok(true)` if the canister was upgraded, and `Ok(false)` if the upgrade was
+    /// started, but is carried out by run_periodic_tasks, which then also sets the execution
+    /// status of the proposal. The latter is the case if a snapshot of the canister is taken.
     async fn perform_upgrade_sns_controlled_canister(
         &mut self,
         proposal_id: u64,
         upgrade: UpgradeSnsControlledCanister,
-    ) -> Result<(), GovernanceError> {
+    ) -> Result<bool, GovernanceError> {
         self.check_no_upgrades_in_progress(Some(proposal_id))?;
 
         let sns_canisters =
@@ -2489,20 +2606,441 @@ impl Governance {
             ));
         }
 
-        let mode = upgrade.mode_or_upgrade() as i32;
+        let take_canister_snapshot = upgrade.take_canister_snapshot.unwrap_or_default();
+        let health_check_method = upgrade.health_check_method.clone();
+        let (wasm, arg, mode) = dapp_canister_install_code_args(upgrade)?;
 
-        let wasm = Wasm::try_from(&upgrade)
-            .map_err(|err| GovernanceError::new_with_message(ErrorType::InvalidCommand, err))?;
+        if take_canister_snapshot {
+            self.start_dapp_canister_upgrade_with_snapshot(
+                proposal_id,
+                target_canister_id,
+                health_check_method,
+            );
+            return Ok(false);
+        }
 
-        self.upgrade_non_root_canister(
-            target_canister_id,
-            wasm,
-            upgrade
-                .canister_upgrade_arg
-                .unwrap_or_else(|| Encode!().unwrap()),
-            CanisterInstallMode::try_from(CanisterInstallModeProto::try_from(mode)?)?,
-        )
-        .await
+        self.upgrade_non_root_canister(target_canister_id, wasm, arg, mode)
+            .await
+            .map(|()| true)
+    }
+
+    /// Starts an upgrade of a dapp canister, like `upgrade_non_root_canister`, that takes a
+    /// snapshot of the canister before the upgrade, and loads the snapshot back into the
+    /// canister if the health check fails after the upgrade.
+    ///
+    /// The upgrade is carried out by run_periodic_tasks, one step per invocation (see
+    /// `advance_pending_dapp_canister_upgrade`), so that no single call chain waits for all the
+    /// steps to complete.
+    fn start_dapp_canister_upgrade_with_snapshot(
+        &mut self,
+        proposal_id: u64,
+        target_canister_id: CanisterId,
+        health_check_method: Option<String>,
+    ) {
+        self.proto.pending_dapp_canister_upgrade = Some(PendingDappCanisterUpgrade {
+            proposal_id,
+            canister_id: Some(target_canister_id.get()),
+            health_check_method,
+            next_step: pending_dapp_canister_upgrade::Step::TakeSnapshot as i32,
+            snapshot_id: None,
+            failure_reason: None,
+        });
+    }
+
+    /// Performs the next step of the pending dapp canister upgrade, if any, and records it in
+    /// the `execution_steps` of the proposal. Once no steps remain, sets the execution status of
+    /// the proposal.
+    ///
+    /// If the upgrade itself fails, the snapshot is not loaded, because a failed install_code
+    /// leaves the canister unchanged.
+    async fn advance_pending_dapp_canister_upgrade(&mut self) {
+        use pending_dapp_canister_upgrade::Step;
+
+        let Some(PendingDappCanisterUpgrade {
+            proposal_id,
+            canister_id,
+            health_check_method,
+            next_step,
+            snapshot_id,
+            failure_reason,
+        }) = self.proto.pending_dapp_canister_upgrade.clone()
+        else {
+            return;
+        };
+        let Some(target_canister_id) = canister_id.map(CanisterId::unchecked_from_principal) else {
+            self.finish_pending_dapp_canister_upgrade(
+                proposal_id,
+                Err("The pending dapp canister upgrade has no canister ID.".to_string()),
+            );
+            return;
+        };
+
+        let (next_step, snapshot_id, failure_reason) = match (
+            Step::try_from(next_step),
+            snapshot_id,
+            health_check_method.clone(),
+        ) {
+            (Ok(Step::TakeSnapshot), None, _) => {
+                match self
+                    .take_dapp_canister_snapshot(proposal_id, target_canister_id)
+                    .await
+                {
+                    Ok(snapshot_id) => (Step::Upgrade, snapshot_id, None),
+                    Err(err) => {
+                        self.finish_pending_dapp_canister_upgrade(
+                            proposal_id,
+                            Err(format!(
+                                "Failed to take a snapshot of canister {}, which was therefore \
+                                 not upgraded: {}",
+                                target_canister_id, err
+                            )),
+                        );
+                        return;
+                    }
+                }
+            }
+            (Ok(Step::Upgrade), Some(snapshot_id), health_check_method) => {
+                match self
+                    .upgrade_dapp_canister(proposal_id, target_canister_id)
+                    .await
+                {
+                    Ok(()) if health_check_method.is_some() => {
+                        (Step::CheckHealth, snapshot_id, None)
+                    }
+                    Ok(()) => (Step::DeleteSnapshot, snapshot_id, None),
+                    Err(err) => (
+                        Step::DeleteSnapshot,
+                        snapshot_id,
+                        Some(format!(
+                            "Failed to upgrade canister {}: {}",
+                            target_canister_id, err
+                        )),
+                    ),
+                }
+            }
+            (Ok(Step::CheckHealth), Some(snapshot_id), Some(health_check_method)) => {
+                match self
+                    .check_dapp_canister_health(
+                        proposal_id,
+                        target_canister_id,
+                        &health_check_method,
+                    )
+                    .await
+                {
+                    Ok(()) => (Step::DeleteSnapshot, snapshot_id, None),
+                    Err(err) => (
+                        Step::LoadSnapshot,
+                        snapshot_id,
+                        Some(format!(
+                            "The health check of canister {} failed after the upgrade: {}.",
+                            target_canister_id, err
+                        )),
+                    ),
+                }
+            }
+            (Ok(Step::LoadSnapshot), Some(snapshot_id), _) => {
+                let health_check_failure = failure_reason.unwrap_or_default();
+                match self
+                    .load_dapp_canister_snapshot(proposal_id, target_canister_id, &snapshot_id)
+                    .await
+                {
+                    Ok(()) => {
+                        let failure_reason = format!(
+                            "{} The canister was rolled back to snapshot {}.",
+                            health_check_failure,
+                            hex::encode(&snapshot_id),
+                        );
+                        (Step::DeleteSnapshot, snapshot_id, Some(failure_reason))
+                    }
+                    // The snapshot is kept, so that the canister can still be rolled back
+                    // manually.
+                    Err(err) => {
+                        self.finish_pending_dapp_canister_upgrade(
+                            proposal_id,
+                            Err(format!(
+                                "{} Loading snapshot {} into the canister failed as well: {}",
+                                health_check_failure,
+                                hex::encode(&snapshot_id),
+                                err,
+                            )),
+                        );
+                        return;
+                    }
+                }
+            }
+            (Ok(Step::DeleteSnapshot), Some(snapshot_id), _) => {
+                self.delete_dapp_canister_snapshot(proposal_id, target_canister_id, snapshot_id)
+                    .await;
+                self.finish_pending_dapp_canister_upgrade(
+                    proposal_id,
+                    failure_reason.map_or(Ok(()), Err),
+                );
+                return;
+            }
+            (next_step, snapshot_id, _) => {
+                self.finish_pending_dapp_canister_upgrade(
+                    proposal_id,
+                    Err(format!(
+                        "The pending upgrade of canister {} is in an inconsistent state \
+                         (next step: {:?}, snapshot ID: {:?}).",
+                        target_canister_id,
+                        next_step,
+                        snapshot_id.map(hex::encode),
+                    )),
+                );
+                return;
+            }
+        };
+
+        self.proto.pending_dapp_canister_upgrade = Some(PendingDappCanisterUpgrade {
+            proposal_id,
+            canister_id: Some(target_canister_id.get()),
+            health_check_method,
+            next_step: next_step as i32,
+            snapshot_id: Some(snapshot_id),
+            failure_reason,
+        });
+    }
+
+    /// Clears the pending dapp canister upgrade, and sets the execution status of the proposal
+    /// that requested it.
+    fn finish_pending_dapp_canister_upgrade(
+        &mut self,
+        proposal_id: u64,
+        result: Result<(), String>,
+    ) {
+        self.proto.pending_dapp_canister_upgrade = None;
+        self.set_proposal_execution_status(
+            proposal_id,
+            result
+                .map_err(|message| GovernanceError::new_with_message(ErrorType::External, message)),
+        );
+    }
+
+    /// Takes a snapshot of the canister, and returns its ID.
+    async fn take_dapp_canister_snapshot(
+        &mut self,
+        proposal_id: u64,
+        target_canister_id: CanisterId,
+    ) -> Result<Vec<u8>, String> {
+        let snapshot_result = self
+            .call_root::<_, TakeCanisterSnapshotResponse>(
+                "take_canister_snapshot",
+                &TakeCanisterSnapshotRequest {
+                    canister_id: Some(target_canister_id.get()),
+                    replace_snapshot: None,
+                },
+            )
+            .await
+            .and_then(|response| match response {
+                TakeCanisterSnapshotResponse {
+                    snapshot_id: Some(snapshot_id),
+                    failure_reason: None,
+                } => Ok(snapshot_id),
+                TakeCanisterSnapshotResponse { failure_reason, .. } => Err(failure_reason
+                    .unwrap_or_else(|| "The response contains no snapshot ID.".to_string())),
+            });
+        self.record_proposal_execution_step(
+            proposal_id,
+            match &snapshot_result {
+                Ok(snapshot_id) => format!(
+                    "Take snapshot {} of canister {}.",
+                    hex::encode(snapshot_id),
+                    target_canister_id
+                ),
+                Err(_) => format!("Take a snapshot of canister {}.", target_canister_id),
+            },
+            snapshot_result.as_ref().err(),
+        );
+        snapshot_result
+    }
+
+    /// Installs the WASM of the UpgradeSnsControlledCanister proposal in the canister. The WASM
+    /// is taken from the proposal, rather than stored with the pending upgrade, because it may
+    /// be large.
+    async fn upgrade_dapp_canister(
+        &mut self,
+        proposal_id: u64,
+        target_canister_id: CanisterId,
+    ) -> Result<(), String> {
+        let install_code_args = match self
+            .proto
+            .proposals
+            .get(&proposal_id)
+            .and_then(|proposal_data| proposal_data.proposal.as_ref())
+            .and_then(|proposal| proposal.action.as_ref())
+        {
+            Some(Action::UpgradeSnsControlledCanister(upgrade)) => {
+                dapp_canister_install_code_args(upgrade.clone()).map_err(|err| err.error_message)
+            }
+            _ => Err(format!(
+                "Proposal {} is not an UpgradeSnsControlledCanister proposal.",
+                proposal_id
+            )),
+        };
+        let upgrade_result = match install_code_args {
+            Ok((wasm, arg, mode)) => self
+                .call_root::<_, ChangeDappCanisterResponse>(
+                    "change_dapp_canister",
+                    &change_canister_request(target_canister_id, wasm, arg, mode),
+                )
+                .await
+                .and_then(|ChangeDappCanisterResponse { failure_reason }| {
+                    failure_reason.map_or(Ok(()), Err)
+                }),
+            Err(err) => Err(err),
+        };
+        self.record_proposal_execution_step(
+            proposal_id,
+            format!("Upgrade canister {}.", target_canister_id),
+            upgrade_result.as_ref().err(),
+        );
+        upgrade_result
+    }
+
+    /// Calls the health check method of the canister. The call is a bounded-wait call, so that
+    /// an unresponsive canister makes the health check fail after
+    /// `DAPP_CANISTER_HEALTH_CHECK_TIMEOUT_SECONDS`, rather than stalling the upgrade.
+    async fn check_dapp_canister_health(
+        &mut self,
+        proposal_id: u64,
+        target_canister_id: CanisterId,
+        health_check_method: &str,
+    ) -> Result<(), String> {
+        let health_check_result = self
+            .env
+            .call_canister_with_timeout(
+                target_canister_id,
+                health_check_method,
+                Encode!().unwrap(),
+                DAPP_CANISTER_HEALTH_CHECK_TIMEOUT_SECONDS,
+            )
+            .await
+            .map(|_reply| ())
+            .map_err(|(code, message)| format!("{} (error code: {:?})", message, code));
+        self.record_proposal_execution_step(
+            proposal_id,
+            format!(
+                "Call health check method `{}` of canister {}.",
+                health_check_method, target_canister_id
+            ),
+            health_check_result.as_ref().err(),
+        );
+        health_check_result
+    }
+
+    /// Loads the snapshot back into the canister.
+    async fn load_dapp_canister_snapshot(
+        &mut self,
+        proposal_id: u64,
+        target_canister_id: CanisterId,
+        snapshot_id: &[u8],
+    ) -> Result<(), String> {
+        let rollback_result = self
+            .call_root::<_, LoadCanisterSnapshotResponse>(
+                "load_canister_snapshot",
+                &LoadCanisterSnapshotRequest {
+                    canister_id: Some(target_canister_id.get()),
+                    snapshot_id: snapshot_id.to_vec(),
+                },
+            )
+            .await
+            .and_then(|LoadCanisterSnapshotResponse { failure_reason }| {
+                failure_reason.map_or(Ok(()), Err)
+            });
+        self.record_proposal_execution_step(
+            proposal_id,
+            format!(
+                "Load snapshot {} into canister {}.",
+                hex::encode(snapshot_id),
+                target_canister_id
+            ),
+            rollback_result.as_ref().err(),
+        );
+        rollback_result
+    }
+
+    /// Deletes the snapshot once it is no longer needed, so that snapshots do not pile up until
+    /// the canister reaches the maximum number of snapshots. A failure is recorded in the
+    /// `execution_steps` of the proposal, but does not make the proposal fail, because the
+    /// upgrade itself is not affected.
+    async fn delete_dapp_canister_snapshot(
+        &mut self,
+        proposal_id: u64,
+        target_canister_id: CanisterId,
+        snapshot_id: Vec<u8>,
+    ) {
+        let description = format!(
+            "Delete snapshot {} of canister {}.",
+            hex::encode(&snapshot_id),
+            target_canister_id
+        );
+        let delete_result = self
+            .call_root::<_, DeleteCanisterSnapshotResponse>(
+                "delete_canister_snapshot",
+                &DeleteCanisterSnapshotRequest {
+                    canister_id: Some(target_canister_id.get()),
+                    snapshot_id,
+                },
+            )
+            .await
+            .and_then(|DeleteCanisterSnapshotResponse { failure_reason }| {
+                failure_reason.map_or(Ok(()), Err)
+            });
+        self.record_proposal_execution_step(proposal_id, description, delete_result.as_ref().err());
+    }
+
+    /// Appends a step to the `execution_steps` of the given proposal.
+    fn record_proposal_execution_step(
+        &mut self,
+        proposal_id: u64,
+        description: String,
+        error: Option<&String>,
+    ) {
+        match error {
+            None => log!(INFO, "Proposal {}: {}", proposal_id, description),
+            Some(error) => log!(
+                ERROR,
+                "Proposal {}: {} Failed: {}",
+                proposal_id,
+                description,
+                error
+            ),
+        }
+        let timestamp_seconds = self.env.now();
+        match self.proto.proposals.get_mut(&proposal_id) {
+            Some(proposal_data) => proposal_data.execution_steps.push(ProposalExecutionStep {
+                timestamp_seconds,
+                description,
+                error: error.cloned(),
+            }),
+            None => log!(
+                ERROR,
+                "Proposal {} not found when attempting to record an execution step.",
+                proposal_id
+            ),
+        }
+    }
+
+    /// Calls a method of the SNS root canister, and decodes the reply.
+    async fn call_root<Request, Response>(
+        &self,
+        method_name: &str,
+        request: &Request,
+    ) -> Result<Response, String>
+    where
+        Request: candid::CandidType,
+        Response: candid::CandidType + for<'de> candid::Deserialize<'de>,
+    {
+        let payload = Encode!(request)
+            .map_err(|err| format!("Could not encode the request for {}: {}", method_name, err))?;
+        let reply = self
+            .env
+            .call_canister(self.proto.root_canister_id_or_panic(), method_name, payload)
+            .await
+            .map_err(|err| format!("Canister method call failed: {:?}", err))?;
+        Decode!(&reply, Response)
+            .map_err(|err| format!("Could not decode the reply of {}: {}", method_name, err))
     }
 
     async fn upgrade_non_root_canister(
@@ -2513,39 +3051,13 @@ impl Governance {
         mode: CanisterInstallMode,
     ) -> Result<(), GovernanceError> {
         // Serialize upgrade.
-        let payload = {
-            // We need to stop a canister before we upgrade it. Otherwise it might
-            // receive callbacks to calls it made before the upgrade after the
-            // upgrade when it might not have the context to parse those usefully.
-            //
-            // For more details, please refer to the comments above the (definition of the)
-            // stop_before_installing field in ChangeCanisterRequest.
-            let stop_before_installing = true;
-
-            let mut change_canister_arg =
-                ChangeCanisterRequest::new(stop_before_installing, mode, target_canister_id)
-                    .with_arg(arg)
-                    .with_mode(mode);
-
-            match wasm {
-                Wasm::Bytes(bytes) => {
-                    change_canister_arg = change_canister_arg.with_wasm(bytes);
-                }
-                Wasm::Chunked {
-                    wasm_module_hash,
-                    store_canister_id,
-                    chunk_hashes_list,
-                } => {
-                    change_canister_arg = change_canister_arg.with_chunked_wasm(
-                        wasm_module_hash,
-                        store_canister_id,
-                        chunk_hashes_list,
-                    );
-                }
-            };
-
-            Encode!(&change_canister_arg).unwrap()
-        };
+        let payload = Encode!(&change_canister_request(
+            target_canister_id,
+            wasm,
+            arg,
+            mode
+        ))
+        .unwrap();
 
         self.env
             .call_canister(
@@ -2585,6 +3097,19 @@ impl Governance {
             ));
         }
 
+        if let Some(pending_dapp_canister_upgrade) = &self.proto.pending_dapp_canister_upgrade {
+            if Some(pending_dapp_canister_upgrade.proposal_id) != proposal_id {
+                return Err(GovernanceError::new_with_message(
+                    ErrorType::ResourceExhausted,
+                    format!(
+                        "The upgrade of a dapp canister requested by proposal {} is still in \
+                         progress. Please, try again later.",
+                        pending_dapp_canister_upgrade.proposal_id
+                    ),
+                ));
+            }
+        }
+
         if self.proto.pending_version.is_some() {
             return Err(GovernanceError::new_with_message(
                 ErrorType::ResourceExhausted,
@@ -2802,9 +3327,21 @@ impl Governance {
             transfer,
             valuation?,
             self.proto.proposals.values(),
+            &self.proto.treasury_payment_streams,
             self.env.now(),
         )?;
 
+        self.transfer_from_treasury(transfer).await.map(|_| ())
+    }
+
+    /// Makes the transfer described by `transfer` from one of the treasuries. This does not check
+    /// that the amount is within the allowed limits; that is up to the caller.
+    ///
+    /// Returns the index of the ledger block containing the transfer.
+    async fn transfer_from_treasury(
+        &self,
+        transfer: &TransferSnsTreasuryFunds,
+    ) -> Result<u64, GovernanceError> {
         let to = Account {
             owner: transfer
                 .to_principal
@@ -2826,7 +3363,6 @@ impl Governance {
                     transfer.memo.unwrap_or(0),
                 )
                 .await
-                .map(|_| ())
                 .map_err(|e| {
                     GovernanceError::new_with_message(
                         ErrorType::External,
@@ -2849,7 +3385,6 @@ impl Governance {
                         transfer.memo.unwrap_or(0),
                     )
                     .await
-                    .map(|_| ())
                     .map_err(|e| {
                         GovernanceError::new_with_message(
                             ErrorType::External,
@@ -2864,6 +3399,98 @@ impl Governance {
         }
     }
 
+    fn perform_create_treasury_payment_stream(
+        &mut self,
+        proposal_id: u64,
+        valuation: Result<Valuation, GovernanceError>,
+        create: CreateTreasuryPaymentStream,
+    ) -> Result<(), GovernanceError> {
+        let valuation = ValuationPb::try_from(valuation?).map_err(|err| {
+            GovernanceError::new_with_message(
+                ErrorType::InconsistentInternalData,
+                format!("Unable to store the treasury valuation: {}", err),
+            )
+        })?;
+
+        if self
+            .proto
+            .treasury_payment_streams
+            .iter()
+            .any(|stream| stream.id == proposal_id)
+        {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                format!("Treasury payment stream {} already exists.", proposal_id),
+            ));
+        }
+
+        let num_active_streams = self
+            .proto
+            .treasury_payment_streams
+            .iter()
+            .filter(|stream| stream.is_active())
+            .count();
+        if num_active_streams >= MAX_NUMBER_OF_ACTIVE_TREASURY_PAYMENT_STREAMS {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::ResourceExhausted,
+                format!(
+                    "There are already {} active treasury payment streams, which is the maximum.",
+                    num_active_streams,
+                ),
+            ));
+        }
+
+        // The first payment is made by the next run of run_periodic_tasks.
+        self.proto
+            .treasury_payment_streams
+            .push(TreasuryPaymentStream {
+                id: proposal_id,
+                parameters: Some(create),
+                valuation: Some(valuation),
+                next_payment_timestamp_seconds: self.env.now(),
+                payments: vec![],
+                cancelled_timestamp_seconds: None,
+                last_failed_payment_attempt: None,
+            });
+
+        log!(INFO, "Created treasury payment stream {}.", proposal_id);
+
+        Ok(())
+    }
+
+    fn perform_cancel_treasury_payment_stream(
+        &mut self,
+        cancel: CancelTreasuryPaymentStream,
+    ) -> Result<(), GovernanceError> {
+        let id = cancel.treasury_payment_stream_id;
+        let now = self.env.now();
+
+        let stream = self
+            .proto
+            .treasury_payment_streams
+            .iter_mut()
+            .find(|stream| stream.id == id)
+            .ok_or_else(|| {
+                GovernanceError::new_with_message(
+                    ErrorType::NotFound,
+                    format!("There is no treasury payment stream with ID {}.", id),
+                )
+            })?;
+
+        if !stream.is_active() {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                format!("Treasury payment stream {} is no longer active.", id),
+            ));
+        }
+
+        stream.cancelled_timestamp_seconds = Some(now);
+
+        log!(INFO, "Cancelled treasury payment stream {}.", id);
+
+        Ok(())
+    }
+
     async fn perform_mint_sns_tokens(
         &mut self,
         mint: MintSnsTokens,
@@ -3509,6 +4136,7 @@ impl Governance {
             is_eligible_for_rewards: true,
             action_auxiliary,
             topic: Some(i32::from(proposal_topic)),
+            execution_steps: vec![],
         };
 
         proposal_data.wait_for_quiet_state = Some(WaitForQuietState {
@@ -4789,6 +5417,224 @@ impl Governance {
         }
     }
 
+    /// Makes the payments of treasury payment streams that are due. Afterwards, removes streams
+    /// that finished long enough ago that their payments no longer count towards the 7-day upper
+    /// bound on the amount transferred from the treasury.
+    async fn maybe_make_treasury_payment_stream_payments(&mut self) {
+        let now = self.env.now();
+
+        let due_stream_ids = self
+            .proto
+            .treasury_payment_streams
+            .iter()
+            .filter(|stream| stream.is_active() && stream.next_payment_timestamp_seconds <= now)
+            .filter(|stream| {
+                stream
+                    .last_failed_payment_attempt
+                    .as_ref()
+                    .is_none_or(|attempt| {
+                        now >= attempt.timestamp_seconds
+                            + TREASURY_PAYMENT_STREAM_RETRY_INTERVAL_SECONDS
+                    })
+            })
+            .map(|stream| stream.id)
+            .collect::<Vec<_>>();
+
+        for stream_id in due_stream_ids {
+            self.make_treasury_payment_stream_payment(stream_id).await;
+        }
+
+        self.proto.treasury_payment_streams.retain(|stream| {
+            stream.is_active()
+                || now
+                    < stream.last_activity_timestamp_seconds()
+                        + EXECUTED_TRANSFER_SNS_TREASURY_FUNDS_PROPOSAL_RETENTION_DURATION_SECONDS
+        });
+    }
+
+    /// Assesses the current value of the treasury that `transfer` takes tokens from.
+    async fn assess_treasury_of_transfer(
+        &self,
+        transfer: &TransferSnsTreasuryFunds,
+    ) -> Result<Valuation, GovernanceError> {
+        let (token, account) = treasury_token_and_account(transfer, self.env.canister_id())
+            .map_err(|message| {
+                GovernanceError::new_with_message(ErrorType::InconsistentInternalData, message)
+            })?;
+
+        self.token_valuer
+            .assess_balance(
+                token,
+                self.proto.ledger_canister_id_or_panic(),
+                self.proto.swap_canister_id_or_panic(),
+                account,
+            )
+            .await
+            .map_err(|err| {
+                GovernanceError::new_with_message(
+                    ErrorType::External,
+                    format!("Unable to assess the value of the treasury: {:?}", err),
+                )
+            })
+    }
+
+    async fn make_treasury_payment_stream_payment(&mut self, stream_id: u64) {
+        let now = self.env.now();
+
+        // Things might have changed while awaiting previous payments; hence, look again.
+        let Some(stream) = self
+            .proto
+            .treasury_payment_streams
+            .iter()
+            .find(|stream| stream.id == stream_id)
+        else {
+            return;
+        };
+        if !stream.is_active() || stream.next_payment_timestamp_seconds > now {
+            return;
+        }
+        let Some(parameters) = stream.parameters.clone() else {
+            log!(
+                ERROR,
+                "Treasury payment stream {} has no parameters. This should never happen.",
+                stream_id,
+            );
+            return;
+        };
+        let CreateTreasuryPaymentStream {
+            payment,
+            period_seconds,
+            number_of_payments: _,
+        } = parameters;
+        let Some(payment) = payment else {
+            log!(
+                ERROR,
+                "Treasury payment stream {} has no payment. This should never happen.",
+                stream_id,
+            );
+            return;
+        };
+        let previous_next_payment_timestamp_seconds = stream.next_payment_timestamp_seconds;
+
+        // The treasury is valued again before each payment, rather than relying on the valuation
+        // from when the stream was created, because the value of the treasury may have fallen
+        // since then (e.g., because of earlier payments, or because the price of the token fell).
+        let valuation = self.assess_treasury_of_transfer(&payment).await;
+
+        // Things might have changed while awaiting the valuation; hence, look again.
+        let is_still_due = self
+            .proto
+            .treasury_payment_streams
+            .iter()
+            .find(|stream| stream.id == stream_id)
+            .is_some_and(|stream| {
+                stream.is_active()
+                    && stream.next_payment_timestamp_seconds
+                        == previous_next_payment_timestamp_seconds
+            });
+        if !is_still_due {
+            return;
+        }
+
+        // Each payment is subject to the same limit as TransferSnsTreasuryFunds proposals.
+        let valuation = valuation.and_then(|valuation| {
+            transfer_sns_treasury_funds_amount_is_small_enough_at_execution_time_or_err(
+                &payment,
+                valuation,
+                self.proto.proposals.values(),
+                &self.proto.treasury_payment_streams,
+                now,
+            )?;
+            ValuationPb::try_from(valuation).map_err(|err| {
+                GovernanceError::new_with_message(
+                    ErrorType::InconsistentInternalData,
+                    format!("Unable to store the treasury valuation: {}", err),
+                )
+            })
+        });
+
+        // Record the payment before making the transfer, so that it counts towards the 7-day
+        // limit, and so that it is not made again by a concurrent call.
+        let Some(stream) = self
+            .proto
+            .treasury_payment_streams
+            .iter_mut()
+            .find(|stream| stream.id == stream_id)
+        else {
+            return;
+        };
+        let valuation = match valuation {
+            Ok(valuation) => valuation,
+            Err(err) => {
+                log!(
+                    ERROR,
+                    "Unable to make payment of treasury payment stream {}: {}",
+                    stream_id,
+                    err,
+                );
+                stream.last_failed_payment_attempt = Some(FailedPaymentAttempt {
+                    timestamp_seconds: now,
+                    reason: err.to_string(),
+                });
+                return;
+            }
+        };
+        stream.valuation = Some(valuation);
+        let payment_index = stream.payments.len();
+        stream.payments.push(Payment {
+            timestamp_seconds: now,
+            amount_e8s: payment.amount_e8s,
+            block_index: None,
+        });
+        stream.next_payment_timestamp_seconds =
+            previous_next_payment_timestamp_seconds.saturating_add(period_seconds);
+
+        let result = self.transfer_from_treasury(&payment).await;
+
+        let Some(stream) = self
+            .proto
+            .treasury_payment_streams
+            .iter_mut()
+            .find(|stream| stream.id == stream_id)
+        else {
+            log!(
+                ERROR,
+                "Treasury payment stream {} disappeared while making a payment ({:?}).",
+                stream_id,
+                result,
+            );
+            return;
+        };
+        match result {
+            Ok(block_index) => {
+                log!(
+                    INFO,
+                    "Made payment {} of treasury payment stream {} in block {}.",
+                    payment_index + 1,
+                    stream_id,
+                    block_index,
+                );
+                stream.payments[payment_index].block_index = Some(block_index);
+                stream.last_failed_payment_attempt = None;
+            }
+            Err(err) => {
+                log!(
+                    ERROR,
+                    "Unable to make payment of treasury payment stream {}: {}",
+                    stream_id,
+                    err,
+                );
+                // Undo the bookkeeping, so that the payment is attempted again later.
+                stream.payments.remove(payment_index);
+                stream.next_payment_timestamp_seconds = previous_next_payment_timestamp_seconds;
+                stream.last_failed_payment_attempt = Some(FailedPaymentAttempt {
+                    timestamp_seconds: now,
+                    reason: err.to_string(),
+                });
+            }
+        }
+    }
+
     // Disburses any maturity that should be disbursed, unless this is already happening.
     async fn maybe_finalize_disburse_maturity(&mut self) {
         if !self.can_finalize_disburse_maturity() {
@@ -5057,6 +5903,9 @@ impl Governance {
                 self.check_upgrade_status().await;
             }
 
+            // Performs the next step of the dapp canister upgrade in progress, if any.
+            self.advance_pending_dapp_canister_upgrade().await;
+
             if self.should_refresh_cached_upgrade_steps() {
                 match self.try_temporarily_lock_refresh_cached_upgrade_steps() {
                     Err(err) => {
@@ -5099,6 +5948,8 @@ impl Governance {
 
         self.maybe_finalize_disburse_maturity().await;
 
+        self.maybe_make_treasury_payment_stream_payments().await;
+
         self.maybe_move_staked_maturity();
 
         self.maybe_gc();
@@ -6143,6 +6994,59 @@ thread_local! {
     static ATTEMPTED_FIXING_MEMORY_ALLOCATIONS: RefCell<bool> = const { RefCell::new(false) };
 }
 
+/// Returns the request to root's change_canister (or change_dapp_canister) method
+/// that upgrades the given (non-root) canister.
+/// Returns the WASM, the argument, and the mode with which to install the new code of an
+/// UpgradeSnsControlledCanister proposal.
+fn dapp_canister_install_code_args(
+    upgrade: UpgradeSnsControlledCanister,
+) -> Result<(Wasm, Vec<u8>, CanisterInstallMode), GovernanceError> {
+    let mode = upgrade.mode_or_upgrade() as i32;
+
+    let wasm = Wasm::try_from(&upgrade)
+        .map_err(|err| GovernanceError::new_with_message(ErrorType::InvalidCommand, err))?;
+
+    let arg = upgrade
+        .canister_upgrade_arg
+        .unwrap_or_else(|| Encode!().unwrap());
+    let mode = CanisterInstallMode::try_from(CanisterInstallModeProto::try_from(mode)?)?;
+
+    Ok((wasm, arg, mode))
+}
+
+fn change_canister_request(
+    target_canister_id: CanisterId,
+    wasm: Wasm,
+    arg: Vec<u8>,
+    mode: CanisterInstallMode,
+) -> ChangeCanisterRequest {
+    // We need to stop a canister before we upgrade it. Otherwise it might
+    // receive callbacks to calls it made before the upgrade after the
+    // upgrade when it might not have the context to parse those usefully.
+    //
+    // For more details, please refer to the comments above the (definition of the)
+    // stop_before_installing field in ChangeCanisterRequest.
+    let stop_before_installing = true;
+
+    let change_canister_arg =
+        ChangeCanisterRequest::new(stop_before_installing, mode, target_canister_id)
+            .with_arg(arg)
+            .with_mode(mode);
+
+    match wasm {
+        Wasm::Bytes(bytes) => change_canister_arg.with_wasm(bytes),
+        Wasm::Chunked {
+            wasm_module_hash,
+            store_canister_id,
+            chunk_hashes_list,
+        } => change_canister_arg.with_chunked_wasm(
+            wasm_module_hash,
+            store_canister_id,
+            chunk_hashes_list,
+        ),
+    }
+}
+
 /// Affects the perception of time by users of CanisterEnv (i.e. Governance).
 ///
 /// Specifically, the time that Governance sees is the real time + delta.
@@ -47,7 +47,7 @@ use ic_nervous_system_common_test_keys::{
 };
 use ic_nns_constants::SNS_WASM_CANISTER_ID;
 use ic_sns_governance_api::pb::v1::topics::Topic;
-use ic_sns_governance_token_valuation::{Token, ValuationFactors};
+use ic_sns_governance_token_valuation::{Token, ValuationError, ValuationFactors};
 use ic_sns_test_utils::itest_helpers::UserInfo;
 use ic_test_utilities_types::ids::canister_test_id;
 use maplit::btreemap;
@@ -859,6 +859,22 @@ async fn no_new_reward_event_when_there_are_no_new_proposals() {
             unimplemented!();
         }
 
+        async fn call_canister_with_timeout(
+            &self,
+            _canister_id: CanisterId,
+            _method_name: &str,
+            _arg: Vec<u8>,
+            _timeout_seconds: u32,
+        ) -> Result<
+            /* reply: */ Vec<u8>,
+            (
+                /* error_code: */ Option<i32>,
+                /* message: */ String,
+            ),
+        > {
+            unimplemented!();
+        }
+
         fn heap_growth_potential(&self) -> HeapGrowthPotential {
             HeapGrowthPotential::NoIssue
         }
@@ -2995,6 +3011,8 @@ fn test_sns_controlled_canister_upgrade_only_upgrades_dapp_canisters() {
             canister_upgrade_arg: None,
             mode: Some(CanisterInstallModeProto::Upgrade.into()),
             chunked_canister_wasm: None,
+            take_canister_snapshot: None,
+            health_check_method: None,
         });
 
         // Upgrade Proposal
@@ -3109,6 +3127,267 @@ fn test_sns_controlled_canister_upgrade_only_upgrades_dapp_canisters() {
     );
 }
 
+/// Sets up a governance with an open UpgradeSnsControlledCanister proposal (with ID 1) that asks
+/// for a snapshot of the target dapp canister, and the environment to respond to all calls made
+/// during its execution, except for the call to the health check method `is_healthy`.
+fn governance_with_snapshot_upgrade_proposal(
+    health_check_response: Result<Vec<u8>, (Option<i32>, String)>,
+) -> Governance {
+    let root_canister_id = *TEST_ROOT_CANISTER_ID;
+    let governance_canister_id = *TEST_GOVERNANCE_CANISTER_ID;
+    let target_canister_id = TEST_DAPP_CANISTER_IDS[0];
+    let new_canister_wasm = vec![0, 0x61, 0x73, 0x6D, 2, 0, 0, 0];
+    let snapshot_id = vec![0xAB, 0xCD];
+
+    let action = Action::UpgradeSnsControlledCanister(UpgradeSnsControlledCanister {
+        canister_id: Some(target_canister_id.get()),
+        new_canister_wasm: new_canister_wasm.clone(),
+        canister_upgrade_arg: None,
+        mode: Some(CanisterInstallModeProto::Upgrade.into()),
+        chunked_canister_wasm: None,
+        take_canister_snapshot: Some(true),
+        health_check_method: Some("is_healthy".to_string()),
+    });
+    let proposal = ProposalData {
+        action: (&action).into(),
+        id: Some(1.into()),
+        ballots: btreemap! {
+            "neuron 1".to_string() => Ballot {
+                vote: Vote::Yes as i32,
+                voting_power: 9001,
+                cast_timestamp_seconds: 1,
+            },
+        },
+        wait_for_quiet_state: Some(WaitForQuietState::default()),
+        proposal: Some(Proposal {
+            title: "Upgrade Proposal".to_string(),
+            action: Some(action),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let mut env = NativeEnvironment::new(Some(governance_canister_id));
+    env.default_canister_call_response = Err((Some(1), "Unexpected call!".to_string()));
+    env.set_call_canister_response(
+        root_canister_id,
+        "get_sns_canisters_summary",
+        Encode!(&GetSnsCanistersSummaryRequest {
+            update_canister_list: Some(true)
+        })
+        .unwrap(),
+        Ok(Encode!(&std_sns_canisters_summary_response()).unwrap()),
+    );
+    env.require_call_canister_invocation(
+        root_canister_id,
+        "take_canister_snapshot",
+        Encode!(&TakeCanisterSnapshotRequest {
+            canister_id: Some(target_canister_id.get()),
+            replace_snapshot: None,
+        })
+        .unwrap(),
+        Some(Ok(Encode!(&TakeCanisterSnapshotResponse {
+            snapshot_id: Some(snapshot_id.clone()),
+            failure_reason: None,
+        })
+        .unwrap())),
+    );
+    env.require_call_canister_invocation(
+        root_canister_id,
+        "change_dapp_canister",
+        Encode!(&change_canister_request(
+            target_canister_id,
+            Wasm::Bytes(new_canister_wasm),
+            Encode!().unwrap(),
+            CanisterInstallMode::Upgrade,
+        ))
+        .unwrap(),
+        Some(Ok(Encode!(&ChangeDappCanisterResponse {
+            failure_reason: None
+        })
+        .unwrap())),
+    );
+    env.require_call_canister_invocation(
+        target_canister_id,
+        "is_healthy",
+        Encode!().unwrap(),
+        Some(health_check_response),
+    );
+    env.set_call_canister_response(
+        root_canister_id,
+        "load_canister_snapshot",
+        Encode!(&LoadCanisterSnapshotRequest {
+            canister_id: Some(target_canister_id.get()),
+            snapshot_id: snapshot_id.clone(),
+        })
+        .unwrap(),
+        Ok(Encode!(&LoadCanisterSnapshotResponse {
+            failure_reason: None
+        })
+        .unwrap()),
+    );
+    env.require_call_canister_invocation(
+        root_canister_id,
+        "delete_canister_snapshot",
+        Encode!(&DeleteCanisterSnapshotRequest {
+            canister_id: Some(target_canister_id.get()),
+            snapshot_id,
+        })
+        .unwrap(),
+        Some(Ok(Encode!(&DeleteCanisterSnapshotResponse {
+            failure_reason: None
+        })
+        .unwrap())),
+    );
+
+    Governance::new(
+        GovernanceProto {
+            proposals: btreemap! { 1 => proposal },
+            root_canister_id: Some(root_canister_id.get()),
+            ..basic_governance_proto()
+        }
+        .try_into()
+        .unwrap(),
+        Box::new(env),
+        Box::new(DoNothingLedger {}),
+        Box::new(DoNothingLedger {}),
+        Box::new(FakeCmc::new()),
+    )
+}
+
+/// Executes the proposal with the given ID, which upgrades a dapp canister with a snapshot, and
+/// then performs the steps of the upgrade, like run_periodic_tasks does. Returns the final state
+/// of the proposal, and the number of steps performed.
+fn execute_snapshot_upgrade_proposal(
+    governance: &mut Governance,
+    proposal_id: u64,
+) -> (ProposalData, usize) {
+    governance.process_proposal(proposal_id);
+
+    let mut num_steps = 0;
+    while governance.proto.pending_dapp_canister_upgrade.is_some() {
+        // The proposal is only executed once all steps are performed.
+        assert_eq!(
+            governance.proto.proposals[&proposal_id].status(),
+            ProposalDecisionStatus::Adopted
+        );
+        assert!(num_steps < 10, "The upgrade takes too many steps.");
+
+        governance
+            .advance_pending_dapp_canister_upgrade()
+            .now_or_never()
+            .unwrap();
+        num_steps += 1;
+    }
+
+    (governance.proto.proposals[&proposal_id].clone(), num_steps)
+}
+
+#[test]
+fn test_sns_controlled_canister_upgrade_with_snapshot_succeeds_if_healthy() {
+    let mut governance = governance_with_snapshot_upgrade_proposal(Ok(Encode!().unwrap()));
+
+    let (proposal_data, num_steps) = execute_snapshot_upgrade_proposal(&mut governance, 1);
+
+    // Take snapshot, upgrade, check health, delete snapshot.
+    assert_eq!(num_steps, 4);
+
+    assert_eq!(
+        proposal_data.status(),
+        ProposalDecisionStatus::Executed,
+        "{:#?}",
+        proposal_data
+    );
+    let target_canister_id = TEST_DAPP_CANISTER_IDS[0];
+    let timestamp_seconds = NativeEnvironment::DEFAULT_TEST_START_TIMESTAMP_SECONDS;
+    assert_eq!(
+        proposal_data.execution_steps,
+        vec![
+            ProposalExecutionStep {
+                timestamp_seconds,
+                description: format!("Take snapshot abcd of canister {}.", target_canister_id),
+                error: None,
+            },
+            ProposalExecutionStep {
+                timestamp_seconds,
+                description: format!("Upgrade canister {}.", target_canister_id),
+                error: None,
+            },
+            ProposalExecutionStep {
+                timestamp_seconds,
+                description: format!(
+                    "Call health check method `is_healthy` of canister {}.",
+                    target_canister_id
+                ),
+                error: None,
+            },
+            ProposalExecutionStep {
+                timestamp_seconds,
+                description: format!("Delete snapshot abcd of canister {}.", target_canister_id),
+                error: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_sns_controlled_canister_upgrade_with_snapshot_rolls_back_if_unhealthy() {
+    let mut governance =
+        governance_with_snapshot_upgrade_proposal(Err((Some(5), "Not healthy.".to_string())));
+
+    let (proposal_data, num_steps) = execute_snapshot_upgrade_proposal(&mut governance, 1);
+
+    // Take snapshot, upgrade, check health, load snapshot, delete snapshot.
+    assert_eq!(num_steps, 5);
+
+    assert_eq!(
+        proposal_data.status(),
+        ProposalDecisionStatus::Failed,
+        "{:#?}",
+        proposal_data
+    );
+    let failure_reason = proposal_data.failure_reason.clone().unwrap();
+    assert_eq!(failure_reason.error_type, ErrorType::External as i32);
+    assert!(
+        failure_reason
+            .error_message
+            .contains("The canister was rolled back to snapshot abcd."),
+        "{:#?}",
+        failure_reason
+    );
+    let target_canister_id = TEST_DAPP_CANISTER_IDS[0];
+    let execution_steps = proposal_data
+        .execution_steps
+        .iter()
+        .map(|step| (step.description.clone(), step.error.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        execution_steps,
+        vec![
+            (
+                format!("Take snapshot abcd of canister {}.", target_canister_id),
+                None
+            ),
+            (format!("Upgrade canister {}.", target_canister_id), None),
+            (
+                format!(
+                    "Call health check method `is_healthy` of canister {}.",
+                    target_canister_id
+                ),
+                Some("Not healthy. (error code: Some(5))".to_string())
+            ),
+            (
+                format!("Load snapshot abcd into canister {}.", target_canister_id),
+                None
+            ),
+            (
+                format!("Delete snapshot abcd of canister {}.", target_canister_id),
+                None
+            ),
+        ]
+    );
+}
+
 #[test]
 fn test_allow_canister_upgrades_while_motion_proposal_execution_is_in_progress() {
     // Step 1: Prepare the world.
@@ -4892,6 +5171,30 @@ fn test_list_topics() {
                             ),
                         ),
                     },
+                    NervousSystemFunction {
+                        id: 17,
+                        name: "Create treasury payment stream".to_string(),
+                        description: Some(
+                            "Proposal to make recurring transfers from an SNS Governance controlled treasury account".to_string(),
+                        ),
+                        function_type: Some(
+                            FunctionType::NativeNervousSystemFunction(
+                                Empty {},
+                            ),
+                        ),
+                    },
+                    NervousSystemFunction {
+                        id: 18,
+                        name: "Cancel treasury payment stream".to_string(),
+                        description: Some(
+                            "Proposal to stop the recurring transfers of a treasury payment stream.".to_string(),
+                        ),
+                        function_type: Some(
+                            FunctionType::NativeNervousSystemFunction(
+                                Empty {},
+                            ),
+                        ),
+                    },
                 ],
                 custom_functions: vec![],
             },
@@ -4959,3 +5262,435 @@ fn test_list_topics() {
     ];
     assert_eq!(topic_infos, expected_topic_infos);
 }
+
+/// Records the amounts of successful transfers. Transfers fail while `fail` is set.
+#[derive(Clone, Default)]
+struct RecordingLedger {
+    transfers_e8s: Arc<Mutex<Vec<u64>>>,
+    fail: Arc<Mutex<bool>>,
+}
+
+impl RecordingLedger {
+    fn transfers_e8s(&self) -> Vec<u64> {
+        self.transfers_e8s.lock().unwrap().clone()
+    }
+
+    fn set_fail(&self, fail: bool) {
+        *self.fail.lock().unwrap() = fail;
+    }
+}
+
+#[async_trait]
+impl ICRC1Ledger for RecordingLedger {
+    async fn transfer_funds(
+        &self,
+        amount_e8s: u64,
+        _fee_e8s: u64,
+        _from_subaccount: Option<Subaccount>,
+        _to: Account,
+        _memo: u64,
+    ) -> Result<u64, NervousSystemError> {
+        if *self.fail.lock().unwrap() {
+            return Err(NervousSystemError::new_with_message(
+                "Ledger is unavailable.",
+            ));
+        }
+
+        let mut transfers_e8s = self.transfers_e8s.lock().unwrap();
+        transfers_e8s.push(amount_e8s);
+        Ok(transfers_e8s.len() as u64)
+    }
+
+    async fn total_supply(&self) -> Result<Tokens, NervousSystemError> {
+        unimplemented!()
+    }
+
+    async fn account_balance(&self, _account: Account) -> Result<Tokens, NervousSystemError> {
+        unimplemented!()
+    }
+
+    fn canister_id(&self) -> CanisterId {
+        unimplemented!()
+    }
+}
+
+/// Values the treasury as though it held `tokens` tokens; other factors are as in `valuation`.
+#[derive(Clone)]
+struct FakeTokenValuer {
+    valuation: Arc<Mutex<Valuation>>,
+}
+
+impl FakeTokenValuer {
+    fn new(valuation: Valuation) -> Self {
+        Self {
+            valuation: Arc::new(Mutex::new(valuation)),
+        }
+    }
+
+    fn set_tokens(&self, tokens: Decimal) {
+        self.valuation.lock().unwrap().valuation_factors.tokens = tokens;
+    }
+}
+
+#[async_trait]
+impl TokenValuer for FakeTokenValuer {
+    async fn assess_balance(
+        &self,
+        _token: Token,
+        _sns_ledger_canister_id: CanisterId,
+        _swap_canister_id: CanisterId,
+        _account: Account,
+    ) -> Result<Valuation, ValuationError> {
+        Ok(*self.valuation.lock().unwrap())
+    }
+}
+
+fn governance_with_icp_ledger(
+    icp_ledger: RecordingLedger,
+    token_valuer: FakeTokenValuer,
+) -> Governance {
+    Governance::new(
+        ValidGovernanceProto::try_from(basic_governance_proto()).unwrap(),
+        Box::new(NativeEnvironment::new(Some(*TEST_GOVERNANCE_CANISTER_ID))),
+        Box::new(DoNothingLedger {}), // SNS token ledger.
+        Box::new(icp_ledger),         // ICP ledger.
+        Box::new(FakeCmc::new()),
+    )
+    .with_token_valuer(Box::new(token_valuer))
+}
+
+/// A small treasury (314 ICP). Hence, at most 314 ICP may be transferred out within 7 days.
+fn small_icp_treasury_valuation() -> Valuation {
+    Valuation {
+        token: Token::Icp,
+        account: Account {
+            owner: Principal::from(PrincipalId::new_user_test_id(104_622_969)),
+            subaccount: None,
+        },
+        timestamp: SystemTime::now(),
+        valuation_factors: ValuationFactors {
+            tokens: Decimal::from(314),
+            icps_per_token: Decimal::from(2),
+            xdrs_per_icp: Decimal::from(5),
+        },
+    }
+}
+
+fn create_treasury_payment_stream_for_test(
+    amount_e8s: u64,
+    number_of_payments: u64,
+) -> CreateTreasuryPaymentStream {
+    CreateTreasuryPaymentStream {
+        payment: Some(TransferSnsTreasuryFunds {
+            from_treasury: TransferFrom::IcpTreasury as i32,
+            amount_e8s,
+            memo: None,
+            to_principal: Some(PrincipalId::new_user_test_id(181_931_560)),
+            to_subaccount: None,
+        }),
+        period_seconds: ONE_DAY_SECONDS,
+        number_of_payments,
+    }
+}
+
+fn warp_days(governance: &mut Governance, days: u64) {
+    governance.env.set_time_warp(TimeWarp {
+        delta_s: (days * ONE_DAY_SECONDS) as i64,
+    });
+}
+
+#[tokio::test]
+async fn test_treasury_payment_stream_makes_payments_on_schedule() {
+    let icp_ledger = RecordingLedger::default();
+    let mut governance = governance_with_icp_ledger(
+        icp_ledger.clone(),
+        FakeTokenValuer::new(small_icp_treasury_valuation()),
+    );
+    let start_timestamp_seconds = governance.env.now();
+
+    governance
+        .perform_create_treasury_payment_stream(
+            42,
+            Ok(small_icp_treasury_valuation()),
+            create_treasury_payment_stream_for_test(E8, 3),
+        )
+        .unwrap();
+
+    // The first payment is made right away.
+    governance
+        .maybe_make_treasury_payment_stream_payments()
+        .await;
+    assert_eq!(icp_ledger.transfers_e8s(), vec![E8]);
+
+    // The next payment is not due yet.
+    governance
+        .maybe_make_treasury_payment_stream_payments()
+        .await;
+    assert_eq!(icp_ledger.transfers_e8s(), vec![E8]);
+
+    for _ in 0..3 {
+        warp_days(&mut governance, 1);
+        governance
+            .maybe_make_treasury_payment_stream_payments()
+            .await;
+    }
+
+    // No more than number_of_payments payments are made.
+    assert_eq!(icp_ledger.transfers_e8s(), vec![E8, E8, E8]);
+
+    let ListTreasuryPaymentStreamsResponse {
+        treasury_payment_streams,
+    } = governance.list_treasury_payment_streams(ListTreasuryPaymentStreamsRequest {});
+    assert_eq!(treasury_payment_streams.len(), 1);
+    let stream = &treasury_payment_streams[0];
+    assert_eq!(stream.id, 42);
+    assert!(!stream.is_active());
+    assert_eq!(stream.last_failed_payment_attempt, None);
+    assert_eq!(
+        stream.payments,
+        (0..3)
+            .map(|i| Payment {
+                timestamp_seconds: start_timestamp_seconds + i * ONE_DAY_SECONDS,
+                amount_e8s: E8,
+                block_index: Some(i + 1),
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    let metrics = governance
+        .get_metrics(GetMetricsRequest {
+            time_window_seconds: Some(ONE_DAY_SECONDS + 1),
+        })
+        .unwrap();
+    assert_eq!(metrics.num_active_treasury_payment_streams, Some(0));
+    assert_eq!(metrics.num_recent_treasury_payment_stream_payments, Some(1));
+
+    // Once its payments no longer count towards the 7-day limit, the stream is forgotten.
+    warp_days(&mut governance, 7);
+    governance
+        .maybe_make_treasury_payment_stream_payments()
+        .await;
+    assert_eq!(
+        governance
+            .list_treasury_payment_streams(ListTreasuryPaymentStreamsRequest {})
+            .treasury_payment_streams,
+        vec![],
+    );
+}
+
+#[tokio::test]
+async fn test_treasury_payment_stream_is_limited_and_can_be_cancelled() {
+    let icp_ledger = RecordingLedger::default();
+    let mut governance = governance_with_icp_ledger(
+        icp_ledger.clone(),
+        FakeTokenValuer::new(small_icp_treasury_valuation()),
+    );
+
+    governance
+        .perform_create_treasury_payment_stream(
+            42,
+            Ok(small_icp_treasury_valuation()),
+            create_treasury_payment_stream_for_test(100 * E8, 10),
+        )
+        .unwrap();
+
+    let metrics = governance
+        .get_metrics(GetMetricsRequest {
+            time_window_seconds: Some(ONE_DAY_SECONDS),
+        })
+        .unwrap();
+    assert_eq!(metrics.num_active_treasury_payment_streams, Some(1));
+
+    governance
+        .maybe_make_treasury_payment_stream_payments()
+        .await;
+    for _ in 0..3 {
+        warp_days(&mut governance, 1);
+        governance
+            .maybe_make_treasury_payment_stream_payments()
+            .await;
+    }
+
+    // The fourth payment would take the 7-day total above 314 ICP.
+    assert_eq!(icp_ledger.transfers_e8s(), vec![100 * E8; 3]);
+    let stream = &governance.proto.treasury_payment_streams[0];
+    assert!(stream.is_active());
+    assert_eq!(stream.payments.len(), 3);
+    let failed_payment_attempt = stream.last_failed_payment_attempt.clone().unwrap();
+    assert_eq!(
+        failed_payment_attempt.timestamp_seconds,
+        governance.env.now()
+    );
+    assert!(
+        failed_payment_attempt.reason.contains("7 days"),
+        "{:#?}",
+        failed_payment_attempt,
+    );
+
+    governance
+        .perform_cancel_treasury_payment_stream(CancelTreasuryPaymentStream {
+            treasury_payment_stream_id: 42,
+        })
+        .unwrap();
+
+    // No more payments are made, even once the 7-day limit would allow them again.
+    warp_days(&mut governance, 6);
+    governance
+        .maybe_make_treasury_payment_stream_payments()
+        .await;
+    assert_eq!(icp_ledger.transfers_e8s(), vec![100 * E8; 3]);
+    let stream = &governance.proto.treasury_payment_streams[0];
+    assert!(!stream.is_active());
+    assert_eq!(stream.payments.len(), 3);
+
+    // Cancelling again does not work.
+    let err = governance
+        .perform_cancel_treasury_payment_stream(CancelTreasuryPaymentStream {
+            treasury_payment_stream_id: 42,
+        })
+        .unwrap_err();
+    assert_eq!(
+        ErrorType::try_from(err.error_type),
+        Ok(ErrorType::PreconditionFailed),
+        "{:#?}",
+        err,
+    );
+}
+
+#[tokio::test]
+async fn test_failed_treasury_payment_stream_payment_is_retried() {
+    let icp_ledger = RecordingLedger::default();
+    let mut governance = governance_with_icp_ledger(
+        icp_ledger.clone(),
+        FakeTokenValuer::new(small_icp_treasury_valuation()),
+    );
+    let start_timestamp_seconds = governance.env.now();
+
+    governance
+        .perform_create_treasury_payment_stream(
+            42,
+            Ok(small_icp_treasury_valuation()),
+            create_treasury_payment_stream_for_test(E8, 3),
+        )
+        .unwrap();
+
+    // The payment fails, and the stream is left as though it was never attempted.
+    icp_ledger.set_fail(true);
+    governance
+        .maybe_make_treasury_payment_stream_payments()
+        .await;
+    let stream = &governance.proto.treasury_payment_streams[0];
+    assert_eq!(stream.payments, vec![]);
+    assert_eq!(
+        stream.next_payment_timestamp_seconds,
+        start_timestamp_seconds
+    );
+    assert_eq!(
+        stream
+            .last_failed_payment_attempt
+            .as_ref()
+            .unwrap()
+            .timestamp_seconds,
+        start_timestamp_seconds,
+    );
+
+    // The payment is not retried right away.
+    icp_ledger.set_fail(false);
+    governance
+        .maybe_make_treasury_payment_stream_payments()
+        .await;
+    assert_eq!(icp_ledger.transfers_e8s(), vec![]);
+
+    // But it is retried later.
+    governance.env.set_time_warp(TimeWarp {
+        delta_s: TREASURY_PAYMENT_STREAM_RETRY_INTERVAL_SECONDS as i64,
+    });
+    governance
+        .maybe_make_treasury_payment_stream_payments()
+        .await;
+    assert_eq!(icp_ledger.transfers_e8s(), vec![E8]);
+    let stream = &governance.proto.treasury_payment_streams[0];
+    assert_eq!(stream.payments.len(), 1);
+    assert_eq!(stream.last_failed_payment_attempt, None);
+    // The schedule is not shifted by the failure.
+    assert_eq!(
+        stream.next_payment_timestamp_seconds,
+        start_timestamp_seconds + ONE_DAY_SECONDS
+    );
+}
+
+#[tokio::test]
+async fn test_treasury_payment_stream_is_limited_by_current_valuation() {
+    let icp_ledger = RecordingLedger::default();
+    let token_valuer = FakeTokenValuer::new(small_icp_treasury_valuation());
+    let mut governance = governance_with_icp_ledger(icp_ledger.clone(), token_valuer.clone());
+
+    governance
+        .perform_create_treasury_payment_stream(
+            42,
+            Ok(small_icp_treasury_valuation()),
+            create_treasury_payment_stream_for_test(50 * E8, 5),
+        )
+        .unwrap();
+
+    governance
+        .maybe_make_treasury_payment_stream_payments()
+        .await;
+    assert_eq!(icp_ledger.transfers_e8s(), vec![50 * E8]);
+
+    // The value of the treasury falls. At the valuation from when the stream was created, the
+    // second payment would be allowed (100 ICP out of 314 ICP in 7 days), but not at the current
+    // valuation (100 ICP out of 60 ICP in 7 days).
+    token_valuer.set_tokens(Decimal::from(60));
+    warp_days(&mut governance, 1);
+    governance
+        .maybe_make_treasury_payment_stream_payments()
+        .await;
+
+    assert_eq!(icp_ledger.transfers_e8s(), vec![50 * E8]);
+    let stream = &governance.proto.treasury_payment_streams[0];
+    assert!(stream.is_active());
+    assert_eq!(stream.payments.len(), 1);
+    let failed_payment_attempt = stream.last_failed_payment_attempt.clone().unwrap();
+    assert!(
+        failed_payment_attempt.reason.contains("7 days"),
+        "{:#?}",
+        failed_payment_attempt,
+    );
+
+    // Once the value of the treasury recovers, payments resume, and the stored valuation is
+    // that of the most recent payment.
+    token_valuer.set_tokens(Decimal::from(200));
+    governance.env.set_time_warp(TimeWarp {
+        delta_s: (ONE_DAY_SECONDS + TREASURY_PAYMENT_STREAM_RETRY_INTERVAL_SECONDS) as i64,
+    });
+    governance
+        .maybe_make_treasury_payment_stream_payments()
+        .await;
+    assert_eq!(icp_ledger.transfers_e8s(), vec![50 * E8; 2]);
+    let stream = &governance.proto.treasury_payment_streams[0];
+    assert_eq!(stream.payments.len(), 2);
+    assert_eq!(stream.last_failed_payment_attempt, None);
+    let valuation = Valuation::try_from(stream.valuation.as_ref().unwrap()).unwrap();
+    assert_eq!(valuation.valuation_factors.tokens, Decimal::from(200));
+}
+
+#[test]
+fn test_get_metrics_requires_time_window() {
+    let governance = governance_with_icp_ledger(
+        RecordingLedger::default(),
+        FakeTokenValuer::new(small_icp_treasury_valuation()),
+    );
+
+    let err = governance
+        .get_metrics(GetMetricsRequest {
+            time_window_seconds: None,
+        })
+        .unwrap_err();
+    assert_eq!(
+        ErrorType::try_from(err.error_type),
+        Ok(ErrorType::InvalidCommand),
+        "{:#?}",
+        err,
+    );
+}
@@ -149,6 +149,20 @@ fn test_all_topics() {
                 ProposalCriticality::Critical,
             )),
         ),
+        (
+            pb::proposal::Action::CreateTreasuryPaymentStream(Default::default()),
+            Ok((
+                Some(pb::Topic::TreasuryAssetManagement),
+                ProposalCriticality::Critical,
+            )),
+        ),
+        (
+            pb::proposal::Action::CancelTreasuryPaymentStream(Default::default()),
+            Ok((
+                Some(pb::Topic::TreasuryAssetManagement),
+                ProposalCriticality::Critical,
+            )),
+        ),
         // CriticalDappOperations
         (
             pb::proposal::Action::DeregisterDappCanisters(Default::default()),
@@ -396,6 +396,8 @@ impl From<pb::UpgradeSnsControlledCanister> for pb_api::UpgradeSnsControlledCani
             chunked_canister_wasm: item
                 .chunked_canister_wasm
                 .map(pb_api::ChunkedCanisterWasm::from),
+            take_canister_snapshot: item.take_canister_snapshot,
+            health_check_method: item.health_check_method,
         }
     }
 }
@@ -430,6 +432,8 @@ impl From<pb_api::UpgradeSnsControlledCanister> for pb::UpgradeSnsControlledCani
             chunked_canister_wasm: item
                 .chunked_canister_wasm
                 .map(pb::ChunkedCanisterWasm::from),
+            take_canister_snapshot: item.take_canister_snapshot,
+            health_check_method: item.health_check_method,
         }
     }
 }
@@ -534,6 +538,40 @@ impl From<pb_api::MintSnsTokens> for pb::MintSnsTokens {
     }
 }
 
+impl From<pb::CreateTreasuryPaymentStream> for pb_api::CreateTreasuryPaymentStream {
+    fn from(item: pb::CreateTreasuryPaymentStream) -> Self {
+        Self {
+            payment: item.payment.map(|x| x.into()),
+            period_seconds: item.period_seconds,
+            number_of_payments: item.number_of_payments,
+        }
+    }
+}
+impl From<pb_api::CreateTreasuryPaymentStream> for pb::CreateTreasuryPaymentStream {
+    fn from(item: pb_api::CreateTreasuryPaymentStream) -> Self {
+        Self {
+            payment: item.payment.map(|x| x.into()),
+            period_seconds: item.period_seconds,
+            number_of_payments: item.number_of_payments,
+        }
+    }
+}
+
+impl From<pb::CancelTreasuryPaymentStream> for pb_api::CancelTreasuryPaymentStream {
+    fn from(item: pb::CancelTreasuryPaymentStream) -> Self {
+        Self {
+            treasury_payment_stream_id: item.treasury_payment_stream_id,
+        }
+    }
+}
+impl From<pb_api::CancelTreasuryPaymentStream> for pb::CancelTreasuryPaymentStream {
+    fn from(item: pb_api::CancelTreasuryPaymentStream) -> Self {
+        Self {
+            treasury_payment_stream_id: item.treasury_payment_stream_id,
+        }
+    }
+}
+
 impl From<pb::ManageSnsMetadata> for pb_api::ManageSnsMetadata {
     fn from(item: pb::ManageSnsMetadata) -> Self {
         Self {
@@ -768,6 +806,12 @@ impl From<pb::proposal::Action> for pb_api::proposal::Action {
             pb::proposal::Action::SetTopicsForCustomProposals(v) => {
                 pb_api::proposal::Action::SetTopicsForCustomProposals(v.into())
             }
+            pb::proposal::Action::CreateTreasuryPaymentStream(v) => {
+                pb_api::proposal::Action::CreateTreasuryPaymentStream(v.into())
+            }
+            pb::proposal::Action::CancelTreasuryPaymentStream(v) => {
+                pb_api::proposal::Action::CancelTreasuryPaymentStream(v.into())
+            }
         }
     }
 }
@@ -821,6 +865,12 @@ impl From<pb_api::proposal::Action> for pb::proposal::Action {
             pb_api::proposal::Action::SetTopicsForCustomProposals(v) => {
                 pb::proposal::Action::SetTopicsForCustomProposals(v.into())
             }
+            pb_api::proposal::Action::CreateTreasuryPaymentStream(v) => {
+                pb::proposal::Action::CreateTreasuryPaymentStream(v.into())
+            }
+            pb_api::proposal::Action::CancelTreasuryPaymentStream(v) => {
+                pb::proposal::Action::CancelTreasuryPaymentStream(v.into())
+            }
         }
     }
 }
@@ -1054,6 +1104,16 @@ impl From<pb::ProposalData> for pb_api::ProposalData {
             minimum_yes_proportion_of_exercised: item.minimum_yes_proportion_of_exercised,
             action_auxiliary: item.action_auxiliary.map(|x| x.into()),
             topic: item.topic.and_then(topic_id_to_api),
+            execution_steps: if item.execution_steps.is_empty() {
+                None
+            } else {
+                Some(
+                    item.execution_steps
+                        .into_iter()
+                        .map(pb_api::ProposalExecutionStep::from)
+                        .collect(),
+                )
+            },
         }
     }
 }
@@ -1087,6 +1147,31 @@ impl From<pb_api::ProposalData> for pb::ProposalData {
             minimum_yes_proportion_of_exercised: item.minimum_yes_proportion_of_exercised,
             action_auxiliary: item.action_auxiliary.map(|x| x.into()),
             topic: item.topic.map(|topic| i32::from(pb::Topic::from(topic))),
+            execution_steps: item
+                .execution_steps
+                .unwrap_or_default()
+                .into_iter()
+                .map(pb::ProposalExecutionStep::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<pb::ProposalExecutionStep> for pb_api::ProposalExecutionStep {
+    fn from(item: pb::ProposalExecutionStep) -> Self {
+        Self {
+            timestamp_seconds: item.timestamp_seconds,
+            description: item.description,
+            error: item.error,
+        }
+    }
+}
+impl From<pb_api::ProposalExecutionStep> for pb::ProposalExecutionStep {
+    fn from(item: pb_api::ProposalExecutionStep) -> Self {
+        Self {
+            timestamp_seconds: item.timestamp_seconds,
+            description: item.description,
+            error: item.error,
         }
     }
 }
@@ -1129,6 +1214,25 @@ impl From<pb_api::proposal_data::MintSnsTokensActionAuxiliary>
     }
 }
 
+impl From<pb::proposal_data::CreateTreasuryPaymentStreamActionAuxiliary>
+    for pb_api::proposal_data::CreateTreasuryPaymentStreamActionAuxiliary
+{
+    fn from(item: pb::proposal_data::CreateTreasuryPaymentStreamActionAuxiliary) -> Self {
+        Self {
+            valuation: item.valuation.map(|x| x.into()),
+        }
+    }
+}
+impl From<pb_api::proposal_data::CreateTreasuryPaymentStreamActionAuxiliary>
+    for pb::proposal_data::CreateTreasuryPaymentStreamActionAuxiliary
+{
+    fn from(item: pb_api::proposal_data::CreateTreasuryPaymentStreamActionAuxiliary) -> Self {
+        Self {
+            valuation: item.valuation.map(|x| x.into()),
+        }
+    }
+}
+
 impl From<pb::proposal_data::AdvanceSnsTargetVersionActionAuxiliary>
     for pb_api::proposal_data::AdvanceSnsTargetVersionActionAuxiliary
 {
@@ -1160,6 +1264,9 @@ impl From<pb::proposal_data::ActionAuxiliary> for pb_api::proposal_data::ActionA
             pb::proposal_data::ActionAuxiliary::AdvanceSnsTargetVersion(v) => {
                 pb_api::proposal_data::ActionAuxiliary::AdvanceSnsTargetVersion(v.into())
             }
+            pb::proposal_data::ActionAuxiliary::CreateTreasuryPaymentStream(v) => {
+                pb_api::proposal_data::ActionAuxiliary::CreateTreasuryPaymentStream(v.into())
+            }
         }
     }
 }
@@ -1176,6 +1283,76 @@ impl From<pb_api::proposal_data::ActionAuxiliary> for pb::proposal_data::ActionA
             pb_api::proposal_data::ActionAuxiliary::AdvanceSnsTargetVersion(v) => {
                 pb::proposal_data::ActionAuxiliary::AdvanceSnsTargetVersion(v.into())
             }
+            pb_api::proposal_data::ActionAuxiliary::CreateTreasuryPaymentStream(v) => {
+                pb::proposal_data::ActionAuxiliary::CreateTreasuryPaymentStream(v.into())
+            }
+        }
+    }
+}
+
+impl From<pb::TreasuryPaymentStream> for pb_api::TreasuryPaymentStream {
+    fn from(item: pb::TreasuryPaymentStream) -> Self {
+        Self {
+            id: item.id,
+            parameters: item.parameters.map(|x| x.into()),
+            valuation: item.valuation.map(|x| x.into()),
+            next_payment_timestamp_seconds: item.next_payment_timestamp_seconds,
+            payments: item.payments.into_iter().map(|x| x.into()).collect(),
+            cancelled_timestamp_seconds: item.cancelled_timestamp_seconds,
+            last_failed_payment_attempt: item.last_failed_payment_attempt.map(|x| x.into()),
+        }
+    }
+}
+impl From<pb_api::TreasuryPaymentStream> for pb::TreasuryPaymentStream {
+    fn from(item: pb_api::TreasuryPaymentStream) -> Self {
+        Self {
+            id: item.id,
+            parameters: item.parameters.map(|x| x.into()),
+            valuation: item.valuation.map(|x| x.into()),
+            next_payment_timestamp_seconds: item.next_payment_timestamp_seconds,
+            payments: item.payments.into_iter().map(|x| x.into()).collect(),
+            cancelled_timestamp_seconds: item.cancelled_timestamp_seconds,
+            last_failed_payment_attempt: item.last_failed_payment_attempt.map(|x| x.into()),
+        }
+    }
+}
+
+impl From<pb::treasury_payment_stream::Payment> for pb_api::treasury_payment_stream::Payment {
+    fn from(item: pb::treasury_payment_stream::Payment) -> Self {
+        Self {
+            timestamp_seconds: item.timestamp_seconds,
+            amount_e8s: item.amount_e8s,
+            block_index: item.block_index,
+        }
+    }
+}
+impl From<pb_api::treasury_payment_stream::Payment> for pb::treasury_payment_stream::Payment {
+    fn from(item: pb_api::treasury_payment_stream::Payment) -> Self {
+        Self {
+            timestamp_seconds: item.timestamp_seconds,
+            amount_e8s: item.amount_e8s,
+            block_index: item.block_index,
+        }
+    }
+}
+
+impl From<pb::treasury_payment_stream::FailedPaymentAttempt>
+    for pb_api::treasury_payment_stream::FailedPaymentAttempt
+{
+    fn from(item: pb::treasury_payment_stream::FailedPaymentAttempt) -> Self {
+        Self {
+            timestamp_seconds: item.timestamp_seconds,
+            reason: item.reason,
+        }
+    }
+}
+impl From<pb_api::treasury_payment_stream::FailedPaymentAttempt>
+    for pb::treasury_payment_stream::FailedPaymentAttempt
+{
+    fn from(item: pb_api::treasury_payment_stream::FailedPaymentAttempt) -> Self {
+        Self {
+            timestamp_seconds: item.timestamp_seconds,
+            reason: item.reason,
         }
     }
 }
@@ -1431,6 +1608,13 @@ impl From<pb::Governance> for pb_api::Governance {
             cached_upgrade_steps: item.cached_upgrade_steps.map(|x| x.into()),
             timers: item.timers,
             upgrade_journal: item.upgrade_journal.map(|x| x.into()),
+            treasury_payment_streams: Some(
+                item.treasury_payment_streams
+                    .into_iter()
+                    .map(|x| x.into())
+                    .collect(),
+            ),
+            pending_dapp_canister_upgrade: item.pending_dapp_canister_upgrade.map(|x| x.into()),
         }
     }
 }
@@ -1475,6 +1659,13 @@ impl From<pb_api::Governance> for pb::Governance {
             cached_upgrade_steps: item.cached_upgrade_steps.map(|x| x.into()),
             timers: item.timers,
             upgrade_journal: item.upgrade_journal.map(|x| x.into()),
+            treasury_payment_streams: item
+                .treasury_payment_streams
+                .unwrap_or_default()
+                .into_iter()
+                .map(|x| x.into())
+                .collect(),
+            pending_dapp_canister_upgrade: item.pending_dapp_canister_upgrade.map(|x| x.into()),
         }
     }
 }
@@ -1751,6 +1942,35 @@ impl From<pb_api::governance::PendingVersion> for pb::governance::PendingVersion
     }
 }
 
+impl From<pb::governance::PendingDappCanisterUpgrade>
+    for pb_api::governance::PendingDappCanisterUpgrade
+{
+    fn from(item: pb::governance::PendingDappCanisterUpgrade) -> Self {
+        Self {
+            proposal_id: item.proposal_id,
+            canister_id: item.canister_id,
+            health_check_method: item.health_check_method,
+            next_step: item.next_step,
+            snapshot_id: item.snapshot_id,
+            failure_reason: item.failure_reason,
+        }
+    }
+}
+impl From<pb_api::governance::PendingDappCanisterUpgrade>
+    for pb::governance::PendingDappCanisterUpgrade
+{
+    fn from(item: pb_api::governance::PendingDappCanisterUpgrade) -> Self {
+        Self {
+            proposal_id: item.proposal_id,
+            canister_id: item.canister_id,
+            health_check_method: item.health_check_method,
+            next_step: item.next_step,
+            snapshot_id: item.snapshot_id,
+            failure_reason: item.failure_reason,
+        }
+    }
+}
+
 impl From<pb::governance::MaturityModulation> for pb_api::governance::MaturityModulation {
     fn from(item: pb::governance::MaturityModulation) -> Self {
         Self {
@@ -3050,6 +3270,40 @@ impl From<pb_api::ListNervousSystemFunctionsResponse> for pb::ListNervousSystemF
     }
 }
 
+impl From<pb::ListTreasuryPaymentStreamsRequest> for pb_api::ListTreasuryPaymentStreamsRequest {
+    fn from(_: pb::ListTreasuryPaymentStreamsRequest) -> Self {
+        Self {}
+    }
+}
+impl From<pb_api::ListTreasuryPaymentStreamsRequest> for pb::ListTreasuryPaymentStreamsRequest {
+    fn from(_: pb_api::ListTreasuryPaymentStreamsRequest) -> Self {
+        Self {}
+    }
+}
+
+impl From<pb::ListTreasuryPaymentStreamsResponse> for pb_api::ListTreasuryPaymentStreamsResponse {
+    fn from(item: pb::ListTreasuryPaymentStreamsResponse) -> Self {
+        Self {
+            treasury_payment_streams: item
+                .treasury_payment_streams
+                .into_iter()
+                .map(|x| x.into())
+                .collect(),
+        }
+    }
+}
+impl From<pb_api::ListTreasuryPaymentStreamsResponse> for pb::ListTreasuryPaymentStreamsResponse {
+    fn from(item: pb_api::ListTreasuryPaymentStreamsResponse) -> Self {
+        Self {
+            treasury_payment_streams: item
+                .treasury_payment_streams
+                .into_iter()
+                .map(|x| x.into())
+                .collect(),
+        }
+    }
+}
+
 impl From<pb::SetMode> for pb_api::SetMode {
     fn from(item: pb::SetMode) -> Self {
         Self { mode: item.mode }
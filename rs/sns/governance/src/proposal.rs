@@ -16,14 +16,16 @@ use crate::{
         proposal::Action,
         proposal_data::{
             self, ActionAuxiliary as ActionAuxiliaryPb, AdvanceSnsTargetVersionActionAuxiliary,
-            MintSnsTokensActionAuxiliary, TransferSnsTreasuryFundsActionAuxiliary,
+            CreateTreasuryPaymentStreamActionAuxiliary, MintSnsTokensActionAuxiliary,
+            TransferSnsTreasuryFundsActionAuxiliary,
         },
         transfer_sns_treasury_funds::TransferFrom,
-        DeregisterDappCanisters, ExecuteGenericNervousSystemFunction, Governance, GovernanceError,
-        LogVisibility, ManageDappCanisterSettings, ManageLedgerParameters, ManageSnsMetadata,
-        MintSnsTokens, Motion, NervousSystemFunction, NervousSystemParameters, Proposal,
-        ProposalData, ProposalDecisionStatus, ProposalId, ProposalRewardStatus,
-        RegisterDappCanisters, SnsVersion, Tally, Topic as TopicPb, TransferSnsTreasuryFunds,
+        CancelTreasuryPaymentStream, CreateTreasuryPaymentStream, DeregisterDappCanisters,
+        ExecuteGenericNervousSystemFunction, Governance, GovernanceError, LogVisibility,
+        ManageDappCanisterSettings, ManageLedgerParameters, ManageSnsMetadata, MintSnsTokens,
+        Motion, NervousSystemFunction, NervousSystemParameters, Proposal, ProposalData,
+        ProposalDecisionStatus, ProposalId, ProposalRewardStatus, RegisterDappCanisters,
+        SnsVersion, Tally, Topic as TopicPb, TransferSnsTreasuryFunds, TreasuryPaymentStream,
         UpgradeSnsControlledCanister, UpgradeSnsToNextVersion, Valuation as ValuationPb, Vote,
     },
     sns_upgrade::{get_proposal_id_that_added_wasm, get_upgrade_params, UpgradeSnsParams},
@@ -36,7 +38,7 @@ use ic_canister_log::log;
 use ic_crypto_sha2::Sha256;
 use ic_nervous_system_common::{
     denominations_to_tokens, i2d, ledger::compute_distribution_subaccount_bytes, ledger_validation,
-    DEFAULT_TRANSFER_FEE, E8, ONE_DAY_SECONDS,
+    DEFAULT_TRANSFER_FEE, E8, ONE_DAY_SECONDS, ONE_YEAR_SECONDS,
 };
 use ic_nervous_system_proto::pb::v1::Percentage;
 use ic_nervous_system_timestamp::format_timestamp_for_humans;
@@ -81,6 +83,9 @@ pub const MAX_NUMBER_OF_GENERIC_NERVOUS_SYSTEM_FUNCTIONS: usize = 200_000;
 /// or ManageDappCanisterSettings).
 pub const MAX_NUMBER_OF_DAPPS_TO_MANAGE_PER_PROPOSAL: usize = 1_000;
 
+/// The maximum length of the health_check_method of UpgradeSnsControlledCanister proposals.
+pub const MAX_HEALTH_CHECK_METHOD_NAME_BYTES: usize = 256;
+
 // The maximum number of ballots for a proposal that can be returned as part of list_proposals
 // response.
 pub const MAX_NUMBER_OF_BALLOTS_IN_LIST_PROPOSALS_RESPONSE: usize = 100;
@@ -95,6 +100,17 @@ pub const EXECUTED_TRANSFER_SNS_TREASURY_FUNDS_PROPOSAL_RETENTION_DURATION_SECON
 /// the same, but we keep separate constants, because we consider this to be a coincidence.
 pub const EXECUTED_MINT_SNS_TOKENS_PROPOSAL_RETENTION_DURATION_SECONDS: u64 = 7 * ONE_DAY_SECONDS;
 
+/// The maximum number of treasury payment streams that can be active at the same time.
+pub const MAX_NUMBER_OF_ACTIVE_TREASURY_PAYMENT_STREAMS: usize = 50;
+
+/// Payments of a treasury payment stream cannot be made more often than this.
+pub const MIN_TREASURY_PAYMENT_STREAM_PERIOD_SECONDS: u64 = ONE_DAY_SECONDS;
+
+/// The time between the first and the last payment of a treasury payment stream cannot exceed
+/// this. The amount of each payment is checked against the treasury valuation taken when the
+/// stream was proposed, and that valuation should not be used indefinitely.
+pub const MAX_TREASURY_PAYMENT_STREAM_DURATION_SECONDS: u64 = ONE_YEAR_SECONDS;
+
 impl Proposal {
     /// Returns whether a proposal is allowed to be submitted when
     /// the heap growth potential is low.
@@ -174,6 +190,7 @@ pub(crate) enum ActionAuxiliary {
     TransferSnsTreasuryFunds(Valuation),
     MintSnsTokens(Valuation),
     AdvanceSnsTargetVersion(Version),
+    CreateTreasuryPaymentStream(Valuation),
     None,
 }
 
@@ -193,6 +210,23 @@ impl ActionAuxiliary {
         }
     }
 
+    pub fn unwrap_create_treasury_payment_stream_or_err(
+        self,
+    ) -> Result<Valuation, GovernanceError> {
+        match self {
+            Self::CreateTreasuryPaymentStream(valuation) => Ok(valuation),
+
+            wrong => Err(GovernanceError::new_with_message(
+                ErrorType::InconsistentInternalData,
+                format!(
+                    "Missing supporting information. Specifically, \
+                     no treasury valuation factors: {:#?}",
+                    wrong,
+                ),
+            )),
+        }
+    }
+
     pub fn unwrap_advance_sns_target_version_or_err(self) -> Result<Version, GovernanceError> {
         match self {
             Self::AdvanceSnsTargetVersion(new_target) => Ok(new_target),
@@ -239,6 +273,14 @@ impl TryFrom<ActionAuxiliary> for Option<ActionAuxiliaryPb> {
                     },
                 ))
             }
+
+            ActionAuxiliary::CreateTreasuryPaymentStream(valuation) => {
+                Some(ActionAuxiliaryPb::CreateTreasuryPaymentStream(
+                    CreateTreasuryPaymentStreamActionAuxiliary {
+                        valuation: Some(ValuationPb::try_from(valuation)?),
+                    },
+                ))
+            }
         };
 
         Ok(result)
@@ -284,6 +326,14 @@ impl TryFrom<&Option<ActionAuxiliaryPb>> for ActionAuxiliary {
 
                 ActionAuxiliary::AdvanceSnsTargetVersion(target_version)
             }
+            Some(ActionAuxiliaryPb::CreateTreasuryPaymentStream(action_auxiliary)) => {
+                let CreateTreasuryPaymentStreamActionAuxiliary { valuation } = action_auxiliary;
+
+                let valuation = Valuation::try_from(valuation.as_ref().unwrap_or_default())
+                    .map_err(|err| format!("Invalid ActionAuxiliaryPb {:?}: {}", src, err))?;
+
+                ActionAuxiliary::CreateTreasuryPaymentStream(valuation)
+            }
         };
 
         Ok(result)
@@ -398,6 +448,7 @@ pub(crate) async fn validate_and_render_action(
     let swap_canister_id = governance_proto.swap_canister_id_or_panic();
     let sns_ledger_canister_id = governance_proto.ledger_canister_id_or_panic();
     let proposals = governance_proto.proposals.values();
+    let treasury_payment_streams = &governance_proto.treasury_payment_streams;
 
     match action {
         proposal::Action::Unspecified(_unspecified) => {
@@ -465,6 +516,7 @@ pub(crate) async fn validate_and_render_action(
                 swap_canister_id,
                 sns_ledger_canister_id,
                 proposals,
+                treasury_payment_streams,
             )
             .await;
         }
@@ -476,6 +528,7 @@ pub(crate) async fn validate_and_render_action(
                 swap_canister_id,
                 sns_ledger_canister_id,
                 proposals,
+                treasury_payment_streams,
             )
             .await;
         }
@@ -498,6 +551,21 @@ pub(crate) async fn validate_and_render_action(
                 &governance_proto.custom_functions_to_topics(),
             )
         }
+        proposal::Action::CreateTreasuryPaymentStream(create) => {
+            return validate_and_render_create_treasury_payment_stream(
+                create,
+                sns_transfer_fee_e8s,
+                env,
+                swap_canister_id,
+                sns_ledger_canister_id,
+                proposals,
+                treasury_payment_streams,
+            )
+            .await;
+        }
+        proposal::Action::CancelTreasuryPaymentStream(cancel) => {
+            validate_and_render_cancel_treasury_payment_stream(cancel, treasury_payment_streams)
+        }
     }
     .map(|rendering| (rendering, ActionAuxiliary::None))
 }
@@ -554,6 +622,7 @@ async fn validate_and_render_transfer_sns_treasury_funds(
     swap_canister_id: CanisterId,
     sns_ledger_canister_id: CanisterId,
     proposals: impl Iterator<Item = &ProposalData>,
+    treasury_payment_streams: &[TreasuryPaymentStream],
 ) -> Result<
     (
         String, // Rendering.
@@ -569,6 +638,7 @@ async fn validate_and_render_transfer_sns_treasury_funds(
         sns_ledger_canister_id,
         swap_canister_id,
         proposals,
+        treasury_payment_streams,
         transfer,
     )
     .await;
@@ -615,6 +685,32 @@ fn locally_validate_and_render_transfer_sns_treasury_funds(
     sns_transfer_fee_e8s: u64,
     mut defects: Vec<String>,
 ) -> Result<String, String> {
+    let rendering =
+        validate_and_render_treasury_transfer(transfer, sns_transfer_fee_e8s, &mut defects);
+
+    // Generate final report.
+    if !defects.is_empty() {
+        return Err(format!(
+            "TransferSnsTreasuryFunds proposal was invalid for the following reason(s):\n{}",
+            defects.join("\n"),
+        ));
+    }
+
+    Ok(format!(
+        "# Proposal to transfer SNS Treasury funds:\n{}",
+        rendering
+    ))
+}
+
+/// Validates a transfer from one of the treasuries, i.e., the action of a TransferSnsTreasuryFunds
+/// proposal, or a payment of a treasury payment stream. Problems are added to `defects`.
+///
+/// Returns a rendering of the transfer, which is only meaningful if no defects were found.
+fn validate_and_render_treasury_transfer(
+    transfer: &TransferSnsTreasuryFunds,
+    sns_transfer_fee_e8s: u64,
+    defects: &mut Vec<String>,
+) -> String {
     // Two things are happening here:
     //
     //     1. make sure that from_treasury is not Unspecified.
@@ -675,18 +771,9 @@ fn locally_validate_and_render_transfer_sns_treasury_funds(
         },
     };
 
-    // Generate final report.
-    if !defects.is_empty() {
-        return Err(format!(
-            "TransferSnsTreasuryFunds proposal was invalid for the following reason(s):\n{}",
-            defects.join("\n"),
-        ));
-    }
-
     let display_amount_tokens = i2d(transfer.amount_e8s) / i2d(E8);
-    Ok(format!(
-        r"# Proposal to transfer SNS Treasury funds:
-## Source treasury: {from}
+    format!(
+        r"## Source treasury: {from}
 ## Amount: {display_amount_tokens:.8} {unit}
 ## Amount (e8s): {amount_e8s}
 ## Target principal: {to_principal}
@@ -694,7 +781,7 @@ fn locally_validate_and_render_transfer_sns_treasury_funds(
 ## Memo: {memo}",
         amount_e8s = transfer.amount_e8s,
         memo = transfer.memo.unwrap_or(0)
-    ))
+    )
 }
 
 /// The only thing that implements this is Token.
@@ -726,7 +813,18 @@ impl TreasuryAccount for Token {
     }
 }
 
-/// Currently, two Actions implement this: TransferSnsTreasuryFunds, and MintSnsTokens.
+/// Returns the token that `transfer` takes from the treasury, and the account of that treasury.
+pub(crate) fn treasury_token_and_account(
+    transfer: &TransferSnsTreasuryFunds,
+    sns_governance_canister_id: CanisterId,
+) -> Result<(Token, Account), String> {
+    let token = transfer.token()?;
+    let account = token.treasury_account(sns_governance_canister_id)?;
+    Ok((token, account))
+}
+
+/// Currently, three Actions implement this: TransferSnsTreasuryFunds, MintSnsTokens, and
+/// CreateTreasuryPaymentStream.
 ///
 /// The thing that they have in common here is that we want to limit the 7-day amount total of these
 /// proposals.
@@ -744,6 +842,7 @@ trait TokenProposalAction {
     fn recent_amount_total_tokens<'a>(
         &self,
         proposals: impl Iterator<Item = &'a ProposalData>,
+        treasury_payment_streams: &[TreasuryPaymentStream],
         now_timestamp_seconds: u64,
     ) -> Result<Decimal, String>;
 
@@ -769,12 +868,14 @@ async fn treasury_valuation_if_proposal_amount_is_small_enough_or_err<MyTokenPro
     sns_ledger_canister_id: CanisterId,
     swap_canister_id: CanisterId,
     proposals: impl Iterator<Item = &ProposalData>,
+    treasury_payment_streams: &[TreasuryPaymentStream],
     action: &MyTokenProposalAction,
 ) -> Result<Valuation, String>
 where
     MyTokenProposalAction: TokenProposalAction,
 {
-    let spent_tokens = action.recent_amount_total_tokens(proposals, env.now())?;
+    let spent_tokens =
+        action.recent_amount_total_tokens(proposals, treasury_payment_streams, env.now())?;
 
     // Get valuation of the tokens in the treasury.
     let token = action.token()?;
@@ -851,10 +952,12 @@ impl TokenProposalAction for TransferSnsTreasuryFunds {
     fn recent_amount_total_tokens<'a>(
         &self,
         proposals: impl Iterator<Item = &'a ProposalData>,
+        treasury_payment_streams: &[TreasuryPaymentStream],
         now_timestamp_seconds: u64,
     ) -> Result<Decimal, String> {
         total_treasury_transfer_amount_tokens(
             proposals,
+            treasury_payment_streams,
             self.from_treasury(),
             now_timestamp_seconds - 7 * ONE_DAY_SECONDS,
         )
@@ -879,6 +982,7 @@ async fn validate_and_render_mint_sns_tokens(
     swap_canister_id: CanisterId,
     sns_ledger_canister_id: CanisterId,
     proposals: impl Iterator<Item = &ProposalData>,
+    treasury_payment_streams: &[TreasuryPaymentStream],
 ) -> Result<
     (
         String, // Rendering.
@@ -894,6 +998,7 @@ async fn validate_and_render_mint_sns_tokens(
         sns_ledger_canister_id,
         swap_canister_id,
         proposals,
+        treasury_payment_streams,
         mint_sns_tokens,
     )
     .await;
@@ -1020,6 +1125,7 @@ impl TokenProposalAction for MintSnsTokens {
     fn recent_amount_total_tokens<'a>(
         &self,
         proposals: impl Iterator<Item = &'a ProposalData>,
+        _treasury_payment_streams: &[TreasuryPaymentStream],
         now_timestamp_seconds: u64,
     ) -> Result<Decimal, String> {
         total_minting_amount_tokens(proposals, now_timestamp_seconds - 7 * ONE_DAY_SECONDS)
@@ -1044,6 +1150,244 @@ impl TokenProposalAction for MintSnsTokens {
     }
 }
 
+/// Validates and renders a CreateTreasuryPaymentStream proposal.
+///
+/// Returns ActionAuxiliary::CreateTreasuryPaymentStream.
+async fn validate_and_render_create_treasury_payment_stream(
+    create: &CreateTreasuryPaymentStream,
+    sns_transfer_fee_e8s: u64,
+    env: &dyn Environment,
+    swap_canister_id: CanisterId,
+    sns_ledger_canister_id: CanisterId,
+    proposals: impl Iterator<Item = &ProposalData>,
+    treasury_payment_streams: &[TreasuryPaymentStream],
+) -> Result<
+    (
+        String, // Rendering.
+        ActionAuxiliary,
+    ),
+    String,
+> {
+    let mut defects = vec![];
+
+    // Validate the amount that the stream could transfer within 7 days. (This requires calling
+    // CMC and the swap canister; hence, await.)
+    let valuation = treasury_valuation_if_proposal_amount_is_small_enough_or_err(
+        env,
+        sns_ledger_canister_id,
+        swap_canister_id,
+        proposals,
+        treasury_payment_streams,
+        create,
+    )
+    .await;
+    let valuation = match valuation {
+        Ok(ok) => Some(ok),
+        Err(err) => {
+            defects.push(err);
+            None
+        }
+    };
+
+    locally_validate_and_render_create_treasury_payment_stream(
+        create,
+        sns_transfer_fee_e8s,
+        treasury_payment_streams,
+        defects,
+    )
+    .and_then(|rendering| match valuation {
+        Some(valuation) => Ok((
+            rendering,
+            ActionAuxiliary::CreateTreasuryPaymentStream(valuation),
+        )),
+
+        // This never happens, for the same reasons as in
+        // validate_and_render_transfer_sns_treasury_funds.
+        None => Err(
+            "There is a bug in the amount validator. Somehow, no valuation, \
+             even though a rendering was generated."
+                .to_string(),
+        ),
+    })
+}
+
+/// Performs all the validation on a CreateTreasuryPaymentStream that does not require fetching
+/// information from other canisters.
+fn locally_validate_and_render_create_treasury_payment_stream(
+    create: &CreateTreasuryPaymentStream,
+    sns_transfer_fee_e8s: u64,
+    treasury_payment_streams: &[TreasuryPaymentStream],
+    mut defects: Vec<String>,
+) -> Result<String, String> {
+    let CreateTreasuryPaymentStream {
+        payment,
+        period_seconds,
+        number_of_payments,
+    } = create;
+
+    let payment_rendering = match payment {
+        Some(payment) => {
+            validate_and_render_treasury_transfer(payment, sns_transfer_fee_e8s, &mut defects)
+        }
+        None => {
+            defects.push("Must specify the payment to make in each period.".to_string());
+            "".to_string()
+        }
+    };
+
+    if *period_seconds < MIN_TREASURY_PAYMENT_STREAM_PERIOD_SECONDS {
+        defects.push(format!(
+            "period_seconds must be at least {} (one day).",
+            MIN_TREASURY_PAYMENT_STREAM_PERIOD_SECONDS,
+        ));
+    }
+
+    if *number_of_payments == 0 {
+        defects.push("number_of_payments must be positive.".to_string());
+    }
+
+    let duration_seconds = number_of_payments
+        .saturating_sub(1)
+        .saturating_mul(*period_seconds);
+    if duration_seconds > MAX_TREASURY_PAYMENT_STREAM_DURATION_SECONDS {
+        defects.push(format!(
+            "The last payment would be made {} seconds after the first one, whereas at most \
+             {} seconds (one year) is allowed.",
+            duration_seconds, MAX_TREASURY_PAYMENT_STREAM_DURATION_SECONDS,
+        ));
+    }
+
+    let total_amount_e8s = payment
+        .as_ref()
+        .and_then(|payment| payment.amount_e8s.checked_mul(*number_of_payments));
+    if payment.is_some() && total_amount_e8s.is_none() {
+        defects.push("The total amount of all payments is too large.".to_string());
+    }
+
+    let num_active_streams = treasury_payment_streams
+        .iter()
+        .filter(|stream| stream.is_active())
+        .count();
+    if num_active_streams >= MAX_NUMBER_OF_ACTIVE_TREASURY_PAYMENT_STREAMS {
+        defects.push(format!(
+            "There are already {} active treasury payment streams, which is the maximum.",
+            num_active_streams,
+        ));
+    }
+
+    // Generate final report.
+    if !defects.is_empty() {
+        return Err(format!(
+            "CreateTreasuryPaymentStream proposal was invalid for the following reason(s):\n{}",
+            defects.join("\n"),
+        ));
+    }
+
+    Ok(format!(
+        r"# Proposal to create a treasury payment stream:
+## Number of payments: {number_of_payments}
+## Period (seconds): {period_seconds}
+## Total amount (e8s): {total_amount_e8s}
+## Each payment:
+{payment_rendering}",
+        total_amount_e8s = total_amount_e8s.unwrap_or_default(),
+    ))
+}
+
+impl CreateTreasuryPaymentStream {
+    fn payment_or_err(&self) -> Result<&TransferSnsTreasuryFunds, String> {
+        self.payment.as_ref().ok_or_else(|| {
+            "Invalid CreateTreasuryPaymentStream: The `payment` field is not populated.".to_string()
+        })
+    }
+
+    /// The greatest number of payments of this stream that can be made within any 7 day window.
+    fn max_number_of_payments_within_7_days(&self) -> u64 {
+        let max_number_of_periods = (7 * ONE_DAY_SECONDS)
+            .checked_div(self.period_seconds)
+            .unwrap_or(u64::MAX);
+
+        max_number_of_periods
+            .saturating_add(1)
+            .min(self.number_of_payments)
+    }
+}
+
+/// Each payment of the stream counts towards the same 7-day limit as TransferSnsTreasuryFunds
+/// proposals. At proposal submission time, we require that the payments that the stream could
+/// make within 7 days fit within that limit.
+impl TokenProposalAction for CreateTreasuryPaymentStream {
+    fn token(&self) -> Result<Token, String> {
+        self.payment_or_err()?.token()
+    }
+
+    fn proposal_amount_tokens(&self) -> Result<Decimal, String> {
+        let payment_amount_tokens = self.payment_or_err()?.proposal_amount_tokens()?;
+        let max_number_of_payments = self.max_number_of_payments_within_7_days();
+
+        payment_amount_tokens
+            .checked_mul(Decimal::from(max_number_of_payments))
+            .ok_or_else(|| {
+                format!(
+                    "Arithmetic error while performing {} * {}",
+                    payment_amount_tokens, max_number_of_payments,
+                )
+            })
+    }
+
+    fn recent_amount_total_tokens<'a>(
+        &self,
+        proposals: impl Iterator<Item = &'a ProposalData>,
+        treasury_payment_streams: &[TreasuryPaymentStream],
+        now_timestamp_seconds: u64,
+    ) -> Result<Decimal, String> {
+        self.payment_or_err()?.recent_amount_total_tokens(
+            proposals,
+            treasury_payment_streams,
+            now_timestamp_seconds,
+        )
+    }
+
+    fn recent_amount_total_upper_bound_tokens(valuation: &Valuation) -> Result<Decimal, String> {
+        TransferSnsTreasuryFunds::recent_amount_total_upper_bound_tokens(valuation)
+    }
+}
+
+/// Validates and renders a CancelTreasuryPaymentStream proposal.
+fn validate_and_render_cancel_treasury_payment_stream(
+    cancel: &CancelTreasuryPaymentStream,
+    treasury_payment_streams: &[TreasuryPaymentStream],
+) -> Result<String, String> {
+    let id = cancel.treasury_payment_stream_id;
+
+    let Some(stream) = treasury_payment_streams
+        .iter()
+        .find(|stream| stream.id == id)
+    else {
+        return Err(format!(
+            "CancelTreasuryPaymentStream proposal was invalid, because there is no \
+             treasury payment stream with ID {}.",
+            id,
+        ));
+    };
+
+    if !stream.is_active() {
+        return Err(format!(
+            "CancelTreasuryPaymentStream proposal was invalid, because treasury payment \
+             stream {} is no longer active.",
+            id,
+        ));
+    }
+
+    Ok(format!(
+        r"# Proposal to cancel a treasury payment stream:
+## Treasury payment stream ID: {id}
+## Payments made so far: {num_payments} of {number_of_payments}",
+        num_payments = stream.payments.len(),
+        number_of_payments = stream.number_of_payments(),
+    ))
+}
+
 /// Validates and renders a proposal with action UpgradeSnsControlledCanister.
 async fn validate_and_render_upgrade_sns_controlled_canister(
     upgrade: &UpgradeSnsControlledCanister,
@@ -1056,6 +1400,8 @@ async fn validate_and_render_upgrade_sns_controlled_canister(
         canister_id,
         canister_upgrade_arg,
         mode,
+        take_canister_snapshot,
+        health_check_method,
         // The WASM-related fields are extracted separately.
         chunked_canister_wasm: _,
         new_canister_wasm: _,
@@ -1089,6 +1435,25 @@ async fn validate_and_render_upgrade_sns_controlled_canister(
         },
     };
 
+    // Inspect the rollback options.
+    let take_canister_snapshot = take_canister_snapshot.unwrap_or_default();
+    if let Some(health_check_method) = health_check_method {
+        if !take_canister_snapshot {
+            defects.push(
+                "health_check_method can only be set if take_canister_snapshot is true."
+                    .to_string(),
+            );
+        }
+        if health_check_method.is_empty()
+            || health_check_method.len() > MAX_HEALTH_CHECK_METHOD_NAME_BYTES
+        {
+            defects.push(format!(
+                "health_check_method must be between 1 and {} bytes long.",
+                MAX_HEALTH_CHECK_METHOD_NAME_BYTES
+            ));
+        }
+    }
+
     // Inspect wasm.
     let wasm_info = match Wasm::try_from(upgrade) {
         Err(err) => {
@@ -1138,6 +1503,25 @@ async fn validate_and_render_upgrade_sns_controlled_canister(
         })
         .unwrap_or_else(|| "No upgrade argument.".to_string());
 
+    let rollback_info = if take_canister_snapshot {
+        let health_check_info = match health_check_method {
+            Some(health_check_method) => format!(
+                "After the upgrade, the method `{health_check_method}` of the canister is called. \
+                 If the call fails, the snapshot is loaded back into the canister."
+            ),
+            None => "No health check is performed after the upgrade.".to_string(),
+        };
+        format!(
+            r"
+
+## Rollback
+
+A snapshot of the canister is taken before the upgrade. {health_check_info}"
+        )
+    } else {
+        "".to_string()
+    };
+
     Ok(format!(
         r"# Proposal to Upgrade an SNS Controlled Canister
 
@@ -1151,7 +1535,7 @@ async fn validate_and_render_upgrade_sns_controlled_canister(
 
 ## Argument info
 
-{args_info}",
+{args_info}{rollback_info}",
     ))
 }
 
@@ -2406,6 +2790,7 @@ impl ProposalData {
             minimum_yes_proportion_of_exercised,
             action_auxiliary,
             topic,
+            execution_steps,
         } = self;
 
         let limited_ballots: BTreeMap<_, _> = ballots
@@ -2436,6 +2821,7 @@ impl ProposalData {
             minimum_yes_proportion_of_exercised: *minimum_yes_proportion_of_exercised,
             action_auxiliary: action_auxiliary.clone(),
             topic: *topic,
+            execution_steps: execution_steps.clone(),
 
             // The following fields are truncated:
             payload_text_rendering: None,
@@ -2494,6 +2880,7 @@ pub(crate) fn transfer_sns_treasury_funds_amount_is_small_enough_at_execution_ti
     transfer: &TransferSnsTreasuryFunds,
     valuation: Valuation,
     proposals: impl Iterator<Item = &'a ProposalData>,
+    treasury_payment_streams: &[TreasuryPaymentStream],
     now_timestamp_seconds: u64,
 ) -> Result<(), GovernanceError> {
     let allowance_tokens = transfer_sns_treasury_funds_7_day_total_upper_bound_tokens(valuation)
@@ -2512,9 +2899,11 @@ pub(crate) fn transfer_sns_treasury_funds_amount_is_small_enough_at_execution_ti
 
     // The total calculated here _could_ be different from what was calculated at proposal
     // submission/creation time. A difference would result from the execution of (another)
-    // TransferSnsTreasuryFunds proposal between now and then.
+    // TransferSnsTreasuryFunds proposal (or a treasury payment stream payment) between now and
+    // then.
     let spent_tokens = total_treasury_transfer_amount_tokens(
         proposals,
+        treasury_payment_streams,
         transfer.from_treasury(),
         now_timestamp_seconds - 7 * ONE_DAY_SECONDS,
     )
@@ -2554,20 +2943,23 @@ pub(crate) fn transfer_sns_treasury_funds_amount_is_small_enough_at_execution_ti
 }
 
 /// Returns the total amount (in e8s) that was transfered from the treasury via
-/// TransferSnsTreasuryFunds proposals, or None if there was an overflow.
+/// TransferSnsTreasuryFunds proposals and treasury payment streams, or None if there was an
+/// overflow.
 ///
 /// Arguments:
 /// * `proposals` - Self-explanatory.
+/// * `treasury_payment_streams` - Also self-explanatory.
 /// * `filter_from_treasury` - Specify the token type (ICP or SNS). The name of this parameter is
 ///   based on TransferSnsTreasuryFunds.from_treasury, which specifies which token the proposal is
 ///   concerned about. Furthermore, that field is compared against this parameter.
-/// * `min_executed_timestamp_seconds` - Older proposals are not considered.
+/// * `min_executed_timestamp_seconds` - Older proposals (and payments) are not considered.
 ///
 /// Currently, the only known way for this to return Err is if proposals is not valid. Specifically,
 /// we require that the `proposal` (singular) field in each element of `proposals` (plural) is
 /// Some(...).
 fn total_treasury_transfer_amount_tokens<'a>(
     proposals: impl Iterator<Item = &'a ProposalData>,
+    treasury_payment_streams: &[TreasuryPaymentStream],
     filter_from_treasury: TransferFrom,
     min_executed_timestamp_seconds: u64,
 ) -> Result<Decimal, String> {
@@ -2589,12 +2981,82 @@ fn total_treasury_transfer_amount_tokens<'a>(
         Some(transfer.amount_e8s)
     };
 
-    total_proposal_amounts_tokens(
+    let proposals_total_tokens = total_proposal_amounts_tokens(
         proposals,
         &format!("{:?} transfer", filter_from_treasury),
         filter_proposal_action_amount_e8s,
         min_executed_timestamp_seconds,
-    )
+    )?;
+
+    let streams_total_tokens = total_treasury_payment_stream_amount_tokens(
+        treasury_payment_streams,
+        filter_from_treasury,
+        min_executed_timestamp_seconds,
+    )?;
+
+    proposals_total_tokens
+        .checked_add(streams_total_tokens)
+        .ok_or_else(|| {
+            format!(
+                "Arithmetic error while performing {} + {}",
+                proposals_total_tokens, streams_total_tokens,
+            )
+        })
+}
+
+/// Returns the total amount of the payments of treasury payment streams that were made from the
+/// treasury given by `filter_from_treasury` no earlier than `min_payment_timestamp_seconds`.
+/// Payments that are still in progress are included.
+fn total_treasury_payment_stream_amount_tokens(
+    treasury_payment_streams: &[TreasuryPaymentStream],
+    filter_from_treasury: TransferFrom,
+    min_payment_timestamp_seconds: u64,
+) -> Result<Decimal, String> {
+    let mut total_tokens = Decimal::from(0);
+
+    for stream in treasury_payment_streams {
+        let Some(payment) = stream
+            .parameters
+            .as_ref()
+            .and_then(|parameters| parameters.payment.as_ref())
+        else {
+            return Err(format!(
+                "TreasuryPaymentStream {} is invalid, because it has no payment.",
+                stream.id,
+            ));
+        };
+
+        if TransferFrom::try_from(payment.from_treasury) != Ok(filter_from_treasury) {
+            continue;
+        }
+
+        for payment in &stream.payments {
+            if payment.timestamp_seconds < min_payment_timestamp_seconds {
+                continue;
+            }
+
+            // This Err is impossible, because we are dividing a u64 by a positive number.
+            let payment_amount_tokens = denominations_to_tokens(payment.amount_e8s, E8)
+                .ok_or_else(|| {
+                    format!(
+                        "Unable to convert amount {} e8s to whole tokens in treasury \
+                         payment stream {}.",
+                        payment.amount_e8s, stream.id,
+                    )
+                })?;
+
+            total_tokens = total_tokens
+                .checked_add(payment_amount_tokens)
+                .ok_or_else(|| {
+                    format!(
+                        "Overflow while adding the payments of treasury payment stream {}.",
+                        stream.id,
+                    )
+                })?;
+        }
+    }
+
+    Ok(total_tokens)
 }
 
 /// Analogous to total_treasury_transfer_amount_tokens. Of course, this considers MintSnsTokens
@@ -2768,6 +3230,8 @@ mod tests {
             target_version: None,
             timers: None,
             upgrade_journal: None,
+            treasury_payment_streams: vec![],
+            pending_dapp_canister_upgrade: None,
         }
     }
 
@@ -2932,6 +3396,8 @@ mod tests {
             canister_upgrade_arg: None,
             mode: Some(CanisterInstallModeProto::Upgrade.into()),
             chunked_canister_wasm: None,
+            take_canister_snapshot: None,
+            health_check_method: None,
         };
         let env = setup_for_upgrade_sns_controlled_canister_tests(&upgrade);
         let text = validate_and_render_upgrade_sns_controlled_canister(
@@ -2973,6 +3439,8 @@ No upgrade argument."#
                 store_canister_id: Some(canister_test_id(111).get()),
                 chunk_hashes_list: vec![vec![1, 1, 1], vec![2, 2, 2], vec![3, 3, 3]],
             }),
+            take_canister_snapshot: None,
+            health_check_method: None,
         };
         let env = setup_for_upgrade_sns_controlled_canister_tests(&upgrade);
         let text = validate_and_render_upgrade_sns_controlled_canister(
@@ -3020,6 +3488,8 @@ No upgrade argument."#
             canister_upgrade_arg: None,
             mode: Some(CanisterInstallModeProto::Upgrade.into()),
             chunked_canister_wasm: Some(chunked_canister_wasm.clone()),
+            take_canister_snapshot: None,
+            health_check_method: None,
         };
 
         let env = setup_for_upgrade_sns_controlled_canister_tests(&upgrade);
@@ -3056,6 +3526,8 @@ No upgrade argument."#
             canister_upgrade_arg: None,
             mode: Some(CanisterInstallModeProto::Upgrade.into()),
             chunked_canister_wasm: Some(chunked_canister_wasm.clone()),
+            take_canister_snapshot: None,
+            health_check_method: None,
         };
 
         let env = setup_for_upgrade_sns_controlled_canister_tests(&upgrade);
@@ -3094,6 +3566,8 @@ No upgrade argument."#
             canister_upgrade_arg: None,
             mode: Some(CanisterInstallModeProto::Upgrade.into()),
             chunked_canister_wasm: Some(chunked_canister_wasm.clone()),
+            take_canister_snapshot: None,
+            health_check_method: None,
         };
 
         let env = setup_for_upgrade_sns_controlled_canister_tests(&upgrade);
@@ -3128,6 +3602,8 @@ No upgrade argument."#
             canister_upgrade_arg: None,
             mode: Some(CanisterInstallModeProto::Upgrade.into()),
             chunked_canister_wasm: Some(chunked_canister_wasm.clone()),
+            take_canister_snapshot: None,
+            health_check_method: None,
         };
 
         let env = setup_for_upgrade_sns_controlled_canister_tests(&upgrade);
@@ -3157,6 +3633,8 @@ No upgrade argument."#
             canister_upgrade_arg: Some(vec![10, 20, 30, 40, 50, 60, 70, 80]),
             mode: Some(CanisterInstallModeProto::Upgrade.into()),
             chunked_canister_wasm: None,
+            take_canister_snapshot: None,
+            health_check_method: None,
         };
         let env = setup_for_upgrade_sns_controlled_canister_tests(&upgrade);
         let text = validate_and_render_upgrade_sns_controlled_canister(
@@ -3186,6 +3664,81 @@ Upgrade argument with 8 bytes and SHA256 `0a141e28323c4650`."#
         );
     }
 
+    #[tokio::test]
+    async fn render_upgrade_sns_controlled_canister_proposal_with_snapshot_and_health_check() {
+        let upgrade = UpgradeSnsControlledCanister {
+            canister_id: Some(basic_canister_id()),
+            new_canister_wasm: vec![0, 0x61, 0x73, 0x6D, 1, 0, 0, 0],
+            canister_upgrade_arg: None,
+            mode: Some(CanisterInstallModeProto::Upgrade.into()),
+            chunked_canister_wasm: None,
+            take_canister_snapshot: Some(true),
+            health_check_method: Some("is_healthy".to_string()),
+        };
+        let env = setup_for_upgrade_sns_controlled_canister_tests(&upgrade);
+        let text = validate_and_render_upgrade_sns_controlled_canister(
+            &upgrade,
+            &env,
+            canister_test_id(55),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            text,
+            r#"# Proposal to Upgrade an SNS Controlled Canister
+
+## Target canister: xbgkv-fyaaa-aaaaa-aaava-cai
+
+## Wasm info
+
+Embedded module with 8 bytes and SHA256 `93a44bbb96c751218e4c00d479e4c14358122a389acca16205b1e4d0dc5f9476`.
+
+## Mode: Upgrade
+
+## Argument info
+
+No upgrade argument.
+
+## Rollback
+
+A snapshot of the canister is taken before the upgrade. After the upgrade, the method `is_healthy` of the canister is called. If the call fails, the snapshot is loaded back into the canister."#
+                .to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn render_upgrade_sns_controlled_canister_proposal_validates_health_check_method() {
+        let upgrade = UpgradeSnsControlledCanister {
+            canister_id: Some(basic_canister_id()),
+            new_canister_wasm: vec![0, 0x61, 0x73, 0x6D, 1, 0, 0, 0],
+            canister_upgrade_arg: None,
+            mode: Some(CanisterInstallModeProto::Upgrade.into()),
+            chunked_canister_wasm: None,
+            take_canister_snapshot: None,
+            health_check_method: Some("".to_string()),
+        };
+        let env = setup_for_upgrade_sns_controlled_canister_tests(&upgrade);
+        let err = validate_and_render_upgrade_sns_controlled_canister(
+            &upgrade,
+            &env,
+            canister_test_id(55),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(
+            err.contains("health_check_method can only be set if take_canister_snapshot is true."),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains("health_check_method must be between 1 and 256 bytes long."),
+            "{}",
+            err
+        );
+    }
+
     #[tokio::test]
     async fn render_upgrade_sns_controlled_canister_proposal_validates_mode() {
         let upgrade = UpgradeSnsControlledCanister {
@@ -3194,6 +3747,8 @@ Upgrade argument with 8 bytes and SHA256 `0a141e28323c4650`."#
             canister_upgrade_arg: None,
             mode: Some(100), // 100 is not a valid mode
             chunked_canister_wasm: None,
+            take_canister_snapshot: None,
+            health_check_method: None,
         };
         let env = setup_for_upgrade_sns_controlled_canister_tests(&upgrade);
         let text = validate_and_render_upgrade_sns_controlled_canister(
@@ -3213,6 +3768,8 @@ Upgrade argument with 8 bytes and SHA256 `0a141e28323c4650`."#
             canister_upgrade_arg: None,
             mode: Some(CanisterInstallModeProto::Upgrade.into()),
             chunked_canister_wasm: None,
+            take_canister_snapshot: None,
+            health_check_method: None,
         };
         let result = validate_and_render_upgrade_sns_controlled_canister(
             &upgrade,
@@ -4750,6 +5307,7 @@ Version {
             executed_timestamp_seconds: 0,
             action_auxiliary: None,
             topic: Some(Topic::Governance as i32),
+            execution_steps: vec![],
         };
     }
 
@@ -5312,6 +5870,8 @@ Payload rendering here"#
                         canister_upgrade_arg: Some(vec![4, 5, 6, 7]),
                         mode: Some(1),
                         chunked_canister_wasm: None,
+                        take_canister_snapshot: None,
+                        health_check_method: None,
                     },
                 )),
                 ..Default::default()
@@ -5333,6 +5893,8 @@ Payload rendering here"#
                             canister_upgrade_arg: Some(vec![4, 5, 6, 7]),
                             mode: Some(1),
                             chunked_canister_wasm: None,
+                            take_canister_snapshot: None,
+                            health_check_method: None,
                         },
                     )),
                     ..Default::default()
@@ -63,6 +63,8 @@ fn standard_governance_proto_for_tests(deployed_version: Option<Version>) -> Gov
         timers: None,
         upgrade_journal: None,
         cached_upgrade_steps: None,
+        treasury_payment_streams: vec![],
+        pending_dapp_canister_upgrade: None,
     }
 }
 
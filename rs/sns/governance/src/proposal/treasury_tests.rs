@@ -205,6 +205,7 @@ fn test_total_treasury_transfer_amount_tokens() {
     assert_eq!(
         total_treasury_transfer_amount_tokens(
             proposals.iter(),
+            &[],
             TransferFrom::IcpTreasury,
             min_executed_timestamp_seconds,
         ),
@@ -217,6 +218,7 @@ fn test_total_treasury_transfer_amount_tokens() {
     assert_eq!(
         total_treasury_transfer_amount_tokens(
             proposals.iter(),
+            &[],
             TransferFrom::IcpTreasury,
             // This is somewhat pathological, but the behavior is still well-defined. Therefore, the
             // code under test should be able to handle this even though we do not expect to see
@@ -251,6 +253,7 @@ fn test_total_treasury_transfer_amount_tokens() {
     assert_eq!(
         total_treasury_transfer_amount_tokens(
             proposals.iter(),
+            &[],
             TransferFrom::IcpTreasury,
             min_executed_timestamp_seconds,
         ),
@@ -280,9 +283,288 @@ fn test_total_treasury_transfer_amount_tokens() {
     assert_eq!(
         total_treasury_transfer_amount_tokens(
             proposals.iter(),
+            &[],
             TransferFrom::IcpTreasury,
             min_executed_timestamp_seconds,
         ),
         Ok((Decimal::from(u64::MAX) + Decimal::from(1)) / Decimal::from(E8)),
     );
 }
+
+fn treasury_payment_stream_for_test(
+    id: u64,
+    from_treasury: TransferFrom,
+    payment_timestamps_seconds: &[u64],
+) -> TreasuryPaymentStream {
+    let payment = TransferSnsTreasuryFunds {
+        from_treasury: from_treasury as i32,
+        amount_e8s: 100,
+        memo: None,
+        to_principal: Some(PrincipalId::new_user_test_id(42)),
+        to_subaccount: None,
+    };
+
+    TreasuryPaymentStream {
+        id,
+        parameters: Some(CreateTreasuryPaymentStream {
+            payment: Some(payment),
+            period_seconds: ONE_DAY_SECONDS,
+            number_of_payments: 10,
+        }),
+        payments: payment_timestamps_seconds
+            .iter()
+            .map(
+                |&timestamp_seconds| crate::pb::v1::treasury_payment_stream::Payment {
+                    timestamp_seconds,
+                    amount_e8s: 100,
+                    block_index: Some(timestamp_seconds),
+                },
+            )
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_total_treasury_transfer_amount_tokens_includes_treasury_payment_streams() {
+    let min_executed_timestamp_seconds = 123_456_789;
+
+    let proposals = vec![ProposalData {
+        proposal: Some(Proposal {
+            action: Some(Action::TransferSnsTreasuryFunds(TransferSnsTreasuryFunds {
+                from_treasury: TransferFrom::IcpTreasury as i32,
+                amount_e8s: 1,
+                memo: None,
+                to_principal: Some(PrincipalId::new_user_test_id(42)),
+                to_subaccount: None,
+            })),
+            ..Default::default()
+        }),
+        executed_timestamp_seconds: min_executed_timestamp_seconds,
+        ..Default::default()
+    }];
+
+    let treasury_payment_streams = vec![
+        // The first payment is too old, but the other two count.
+        treasury_payment_stream_for_test(
+            1,
+            TransferFrom::IcpTreasury,
+            &[
+                min_executed_timestamp_seconds - 1,
+                min_executed_timestamp_seconds,
+                min_executed_timestamp_seconds + ONE_DAY_SECONDS,
+            ],
+        ),
+        // Skip because wrong type of token (SNS instead of ICP).
+        treasury_payment_stream_for_test(
+            2,
+            TransferFrom::SnsTokenTreasury,
+            &[min_executed_timestamp_seconds],
+        ),
+    ];
+
+    assert_eq!(
+        total_treasury_transfer_amount_tokens(
+            proposals.iter(),
+            &treasury_payment_streams,
+            TransferFrom::IcpTreasury,
+            min_executed_timestamp_seconds,
+        ),
+        Ok(Decimal::from(201) / Decimal::from(E8)),
+    );
+
+    assert_eq!(
+        total_treasury_transfer_amount_tokens(
+            proposals.iter(),
+            &treasury_payment_streams,
+            TransferFrom::SnsTokenTreasury,
+            min_executed_timestamp_seconds,
+        ),
+        Ok(Decimal::from(100) / Decimal::from(E8)),
+    );
+}
+
+#[test]
+fn test_create_treasury_payment_stream_max_number_of_payments_within_7_days() {
+    let create = |period_seconds, number_of_payments| CreateTreasuryPaymentStream {
+        payment: None,
+        period_seconds,
+        number_of_payments,
+    };
+
+    // A payment at the start and at the end of a 7 day window.
+    assert_eq!(
+        create(7 * ONE_DAY_SECONDS, 10).max_number_of_payments_within_7_days(),
+        2
+    );
+    assert_eq!(
+        create(ONE_DAY_SECONDS, 100).max_number_of_payments_within_7_days(),
+        8
+    );
+    // Limited by the number of payments.
+    assert_eq!(
+        create(ONE_DAY_SECONDS, 3).max_number_of_payments_within_7_days(),
+        3
+    );
+    assert_eq!(
+        create(30 * ONE_DAY_SECONDS, 12).max_number_of_payments_within_7_days(),
+        1
+    );
+}
+
+#[test]
+fn test_locally_validate_and_render_create_treasury_payment_stream() {
+    let create = CreateTreasuryPaymentStream {
+        payment: Some(TransferSnsTreasuryFunds {
+            from_treasury: TransferFrom::IcpTreasury as i32,
+            amount_e8s: 10 * E8,
+            memo: None,
+            to_principal: Some(PrincipalId::new_user_test_id(42)),
+            to_subaccount: None,
+        }),
+        period_seconds: 30 * ONE_DAY_SECONDS,
+        number_of_payments: 12,
+    };
+
+    let rendering =
+        locally_validate_and_render_create_treasury_payment_stream(&create, 10_000, &[], vec![])
+            .unwrap();
+    assert!(
+        rendering.starts_with(
+            "# Proposal to create a treasury payment stream:\n\
+             ## Number of payments: 12\n\
+             ## Period (seconds): 2592000\n\
+             ## Total amount (e8s): 12000000000\n\
+             ## Each payment:\n\
+             ## Source treasury: ICP Treasury (ICP Ledger)\n"
+        ),
+        "{}",
+        rendering
+    );
+
+    let assert_invalid = |create: CreateTreasuryPaymentStream,
+                          treasury_payment_streams: &[TreasuryPaymentStream],
+                          expected_defect: &str| {
+        let err = locally_validate_and_render_create_treasury_payment_stream(
+            &create,
+            10_000,
+            treasury_payment_streams,
+            vec![],
+        )
+        .unwrap_err();
+        assert!(err.contains(expected_defect), "{}", err);
+    };
+
+    assert_invalid(
+        CreateTreasuryPaymentStream {
+            payment: None,
+            ..create.clone()
+        },
+        &[],
+        "Must specify the payment",
+    );
+    assert_invalid(
+        CreateTreasuryPaymentStream {
+            period_seconds: ONE_DAY_SECONDS - 1,
+            ..create.clone()
+        },
+        &[],
+        "period_seconds must be at least",
+    );
+    assert_invalid(
+        CreateTreasuryPaymentStream {
+            number_of_payments: 0,
+            ..create.clone()
+        },
+        &[],
+        "number_of_payments must be positive",
+    );
+    // 14 monthly payments span more than a year.
+    assert_invalid(
+        CreateTreasuryPaymentStream {
+            number_of_payments: 14,
+            ..create.clone()
+        },
+        &[],
+        "(one year) is allowed",
+    );
+    assert_invalid(
+        CreateTreasuryPaymentStream {
+            payment: Some(TransferSnsTreasuryFunds {
+                amount_e8s: u64::MAX,
+                ..create.payment.clone().unwrap()
+            }),
+            ..create.clone()
+        },
+        &[],
+        "The total amount of all payments is too large",
+    );
+
+    let active_treasury_payment_streams = (0..MAX_NUMBER_OF_ACTIVE_TREASURY_PAYMENT_STREAMS as u64)
+        .map(|id| treasury_payment_stream_for_test(id, TransferFrom::IcpTreasury, &[]))
+        .collect::<Vec<_>>();
+    assert_invalid(
+        create.clone(),
+        &active_treasury_payment_streams,
+        "active treasury payment streams, which is the maximum",
+    );
+
+    // Streams that are no longer active do not count towards the maximum.
+    let cancelled_treasury_payment_streams = active_treasury_payment_streams
+        .into_iter()
+        .map(|stream| TreasuryPaymentStream {
+            cancelled_timestamp_seconds: Some(123_456_789),
+            ..stream
+        })
+        .collect::<Vec<_>>();
+    let result = locally_validate_and_render_create_treasury_payment_stream(
+        &create,
+        10_000,
+        &cancelled_treasury_payment_streams,
+        vec![],
+    );
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn test_validate_and_render_cancel_treasury_payment_stream() {
+    let stream = treasury_payment_stream_for_test(
+        42,
+        TransferFrom::IcpTreasury,
+        &[123_456_789, 123_456_789 + ONE_DAY_SECONDS],
+    );
+    let cancel = CancelTreasuryPaymentStream {
+        treasury_payment_stream_id: 42,
+    };
+
+    assert_eq!(
+        validate_and_render_cancel_treasury_payment_stream(&cancel, &[stream.clone()]),
+        Ok("# Proposal to cancel a treasury payment stream:\n\
+            ## Treasury payment stream ID: 42\n\
+            ## Payments made so far: 2 of 10"
+            .to_string()),
+    );
+
+    // No such stream.
+    let err = validate_and_render_cancel_treasury_payment_stream(
+        &CancelTreasuryPaymentStream {
+            treasury_payment_stream_id: 43,
+        },
+        &[stream.clone()],
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("there is no treasury payment stream with ID 43"),
+        "{}",
+        err
+    );
+
+    // Already cancelled.
+    let cancelled_stream = TreasuryPaymentStream {
+        cancelled_timestamp_seconds: Some(123_456_789),
+        ..stream
+    };
+    let err = validate_and_render_cancel_treasury_payment_stream(&cancel, &[cancelled_stream])
+        .unwrap_err();
+    assert!(err.contains("is no longer active"), "{}", err);
+}
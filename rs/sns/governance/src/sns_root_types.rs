@@ -196,3 +196,76 @@ pub struct ManageDappCanisterSettingsResponse {
     #[prost(string, optional, tag = "1")]
     pub failure_reason: ::core::option::Option<::prost::alloc::string::String>,
 }
+
+/// Same proto in root.proto. TODO(NNS1-1589)
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TakeCanisterSnapshotRequest {
+    #[prost(message, optional, tag = "1")]
+    pub canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
+    #[prost(bytes = "vec", optional, tag = "2")]
+    pub replace_snapshot: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+}
+
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TakeCanisterSnapshotResponse {
+    /// Set if and only if failure_reason is not set.
+    #[prost(bytes = "vec", optional, tag = "1")]
+    pub snapshot_id: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    /// Absence of failure_reason indicates success.
+    #[prost(string, optional, tag = "2")]
+    pub failure_reason: ::core::option::Option<::prost::alloc::string::String>,
+}
+
+/// Same proto in root.proto. TODO(NNS1-1589)
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LoadCanisterSnapshotRequest {
+    #[prost(message, optional, tag = "1")]
+    pub canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub snapshot_id: ::prost::alloc::vec::Vec<u8>,
+}
+
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LoadCanisterSnapshotResponse {
+    /// Absence of failure_reason indicates success.
+    #[prost(string, optional, tag = "1")]
+    pub failure_reason: ::core::option::Option<::prost::alloc::string::String>,
+}
+
+/// Same proto in root.proto. TODO(NNS1-1589)
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChangeDappCanisterResponse {
+    /// Absence of failure_reason indicates success.
+    #[prost(string, optional, tag = "1")]
+    pub failure_reason: ::core::option::Option<::prost::alloc::string::String>,
+}
+
+/// Same proto in root.proto. TODO(NNS1-1589)
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteCanisterSnapshotRequest {
+    #[prost(message, optional, tag = "1")]
+    pub canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub snapshot_id: ::prost::alloc::vec::Vec<u8>,
+}
+
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteCanisterSnapshotResponse {
+    /// Absence of failure_reason indicates success.
+    #[prost(string, optional, tag = "1")]
+    pub failure_reason: ::core::option::Option<::prost::alloc::string::String>,
+}
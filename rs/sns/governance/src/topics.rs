@@ -46,7 +46,8 @@ pub struct ListTopicsResponse {
 /// Topics may be nested within other topics, and each topic may have a list of built-in functions that are categorized within that topic.
 pub fn topic_descriptions() -> [TopicInfo<NativeFunctions>; 7] {
     use crate::types::native_action_ids::{
-        ADD_GENERIC_NERVOUS_SYSTEM_FUNCTION, ADVANCE_SNS_TARGET_VERSION, DEREGISTER_DAPP_CANISTERS,
+        ADD_GENERIC_NERVOUS_SYSTEM_FUNCTION, ADVANCE_SNS_TARGET_VERSION,
+        CANCEL_TREASURY_PAYMENT_STREAM, CREATE_TREASURY_PAYMENT_STREAM, DEREGISTER_DAPP_CANISTERS,
         MANAGE_DAPP_CANISTER_SETTINGS, MANAGE_LEDGER_PARAMETERS, MANAGE_NERVOUS_SYSTEM_PARAMETERS,
         MANAGE_SNS_METADATA, MINT_SNS_TOKENS, MOTION, REGISTER_DAPP_CANISTERS,
         REMOVE_GENERIC_NERVOUS_SYSTEM_FUNCTION, TRANSFER_SNS_TREASURY_FUNDS,
@@ -118,6 +119,8 @@ pub fn topic_descriptions() -> [TopicInfo<NativeFunctions>; 7] {
                 native_functions: vec![
                     TRANSFER_SNS_TREASURY_FUNDS,
                     MINT_SNS_TOKENS,
+                    CREATE_TREASURY_PAYMENT_STREAM,
+                    CANCEL_TREASURY_PAYMENT_STREAM,
                 ],
             },
             is_critical: true,
@@ -34,8 +34,8 @@ use crate::{
             ManageSnsMetadata, MintSnsTokens, Motion, NervousSystemFunction,
             NervousSystemParameters, Neuron, NeuronId, NeuronIds, NeuronPermission,
             NeuronPermissionList, NeuronPermissionType, ProposalId, RegisterDappCanisters,
-            RewardEvent, SnsVersion, TransferSnsTreasuryFunds, UpgradeSnsControlledCanister,
-            UpgradeSnsToNextVersion, Vote, VotingRewardsParameters,
+            RewardEvent, SnsVersion, TransferSnsTreasuryFunds, TreasuryPaymentStream,
+            UpgradeSnsControlledCanister, UpgradeSnsToNextVersion, Vote, VotingRewardsParameters,
         },
     },
     proposal::ValidGenericNervousSystemFunction,
@@ -58,7 +58,8 @@ use ic_nervous_system_common_validation::validate_proposal_url;
 use ic_nervous_system_proto::pb::v1::{Duration as PbDuration, Percentage};
 use ic_sns_governance_api::format_full_hash;
 use ic_sns_governance_proposal_criticality::{ProposalCriticality, VotingDurationParameters};
-use icrc_ledger_types::icrc::generic_metadata_value::MetadataValue;
+use ic_sns_governance_token_valuation::{Token, Valuation, ValuationError};
+use icrc_ledger_types::{icrc::generic_metadata_value::MetadataValue, icrc1::account::Account};
 use lazy_static::lazy_static;
 use std::{
     collections::{BTreeMap, BTreeSet, HashSet},
@@ -137,6 +138,12 @@ pub mod native_action_ids {
     /// SetTopicsForCustomProposals Action.
     pub const SET_TOPICS_FOR_CUSTOM_PROPOSALS_ACTION: u64 = 16;
 
+    /// CreateTreasuryPaymentStream Action.
+    pub const CREATE_TREASURY_PAYMENT_STREAM: u64 = 17;
+
+    /// CancelTreasuryPaymentStream Action.
+    pub const CANCEL_TREASURY_PAYMENT_STREAM: u64 = 18;
+
     // When adding something to this list, make sure to update the below function.
     pub fn nervous_system_functions() -> Vec<NervousSystemFunction> {
         vec![
@@ -156,6 +163,8 @@ pub mod native_action_ids {
             NervousSystemFunction::manage_dapp_canister_settings(),
             NervousSystemFunction::advance_sns_target_version(),
             NervousSystemFunction::set_topics_for_custom_proposals(),
+            NervousSystemFunction::create_treasury_payment_stream(),
+            NervousSystemFunction::cancel_treasury_payment_stream(),
         ]
     }
 }
@@ -256,6 +265,7 @@ impl governance::Mode {
             NervousSystemFunction::manage_nervous_system_parameters(),
             NervousSystemFunction::transfer_sns_treasury_funds(),
             NervousSystemFunction::mint_sns_tokens(),
+            NervousSystemFunction::create_treasury_payment_stream(),
             NervousSystemFunction::upgrade_sns_controlled_canister(),
             NervousSystemFunction::register_dapp_canisters(),
             NervousSystemFunction::deregister_dapp_canisters(),
@@ -1252,6 +1262,31 @@ impl NervousSystemFunction {
             function_type: Some(FunctionType::NativeNervousSystemFunction(Empty {})),
         }
     }
+
+    fn create_treasury_payment_stream() -> NervousSystemFunction {
+        NervousSystemFunction {
+            id: native_action_ids::CREATE_TREASURY_PAYMENT_STREAM,
+            name: "Create treasury payment stream".to_string(),
+            description: Some(
+                "Proposal to make recurring transfers from an SNS Governance controlled treasury \
+                 account"
+                    .to_string(),
+            ),
+            function_type: Some(FunctionType::NativeNervousSystemFunction(Empty {})),
+        }
+    }
+
+    fn cancel_treasury_payment_stream() -> NervousSystemFunction {
+        NervousSystemFunction {
+            id: native_action_ids::CANCEL_TREASURY_PAYMENT_STREAM,
+            name: "Cancel treasury payment stream".to_string(),
+            description: Some(
+                "Proposal to stop the recurring transfers of a treasury payment stream."
+                    .to_string(),
+            ),
+            function_type: Some(FunctionType::NativeNervousSystemFunction(Empty {})),
+        }
+    }
 }
 
 impl From<Action> for NervousSystemFunction {
@@ -1298,6 +1333,12 @@ impl From<Action> for NervousSystemFunction {
             Action::SetTopicsForCustomProposals(_) => {
                 NervousSystemFunction::set_topics_for_custom_proposals()
             }
+            Action::CreateTreasuryPaymentStream(_) => {
+                NervousSystemFunction::create_treasury_payment_stream()
+            }
+            Action::CancelTreasuryPaymentStream(_) => {
+                NervousSystemFunction::cancel_treasury_payment_stream()
+            }
         }
     }
 }
@@ -1753,6 +1794,8 @@ impl UpgradeSnsControlledCanister {
                 .map(|blob| summarize_blob_field(blob)),
             mode: self.mode,
             chunked_canister_wasm: self.chunked_canister_wasm.clone(),
+            take_canister_snapshot: self.take_canister_snapshot,
+            health_check_method: self.health_check_method.clone(),
         }
     }
 
@@ -1764,6 +1807,8 @@ impl UpgradeSnsControlledCanister {
             mode: self.mode,
             new_canister_wasm: Vec::new(),
             chunked_canister_wasm: self.chunked_canister_wasm.clone(),
+            take_canister_snapshot: self.take_canister_snapshot,
+            health_check_method: self.health_check_method.clone(),
         }
     }
 }
@@ -1881,6 +1926,12 @@ impl From<&Action> for u64 {
             Action::SetTopicsForCustomProposals(_) => {
                 native_action_ids::SET_TOPICS_FOR_CUSTOM_PROPOSALS_ACTION
             }
+            Action::CreateTreasuryPaymentStream(_) => {
+                native_action_ids::CREATE_TREASURY_PAYMENT_STREAM
+            }
+            Action::CancelTreasuryPaymentStream(_) => {
+                native_action_ids::CANCEL_TREASURY_PAYMENT_STREAM
+            }
         }
     }
 }
@@ -2009,6 +2060,23 @@ pub trait Environment: Send + Sync {
         ),
     >;
 
+    /// Calls another canister like `call_canister`, but with a bounded wait: if the remote
+    /// canister does not respond within `timeout_seconds`, the call fails instead of blocking
+    /// the caller indefinitely.
+    async fn call_canister_with_timeout(
+        &self,
+        canister_id: CanisterId,
+        method_name: &str,
+        arg: Vec<u8>,
+        timeout_seconds: u32,
+    ) -> Result<
+        /* reply: */ Vec<u8>,
+        (
+            /* error_code: */ Option<i32>,
+            /* message: */ String,
+        ),
+    >;
+
     /// Returns rough information as to how much the heap can grow.
     ///
     /// The intended use case is for the governance canister to avoid
@@ -2023,6 +2091,38 @@ pub trait Environment: Send + Sync {
     fn canister_version(&self) -> Option<u64>;
 }
 
+/// Assesses the value of the tokens in an account, e.g., in a treasury of the SNS.
+///
+/// See CanisterTokenValuer for the implementation used in production.
+#[async_trait]
+pub trait TokenValuer: Send + Sync {
+    async fn assess_balance(
+        &self,
+        token: Token,
+        sns_ledger_canister_id: CanisterId,
+        swap_canister_id: CanisterId,
+        account: Account,
+    ) -> Result<Valuation, ValuationError>;
+}
+
+/// Assesses the value of tokens by calling the ledger, swap, and cycles minting canisters.
+pub struct CanisterTokenValuer;
+
+#[async_trait]
+impl TokenValuer for CanisterTokenValuer {
+    async fn assess_balance(
+        &self,
+        token: Token,
+        sns_ledger_canister_id: CanisterId,
+        swap_canister_id: CanisterId,
+        account: Account,
+    ) -> Result<Valuation, ValuationError> {
+        token
+            .assess_balance(sns_ledger_canister_id, swap_canister_id, account)
+            .await
+    }
+}
+
 /// Rough buckets for how much the heap can still grow.
 pub enum HeapGrowthPotential {
     /// The heap can grow without issue.
@@ -2573,6 +2673,34 @@ impl From<MintSnsTokens> for Action {
     }
 }
 
+impl TreasuryPaymentStream {
+    /// The total number of payments that this stream is supposed to make.
+    pub fn number_of_payments(&self) -> u64 {
+        self.parameters
+            .as_ref()
+            .map(|parameters| parameters.number_of_payments)
+            .unwrap_or_default()
+    }
+
+    /// Returns whether more payments are to be made, i.e., the stream has been neither cancelled,
+    /// nor has it (started to) make all of its payments.
+    pub fn is_active(&self) -> bool {
+        self.cancelled_timestamp_seconds.is_none()
+            && (self.payments.len() as u64) < self.number_of_payments()
+    }
+
+    /// The timestamp of the most recent payment, or when it was cancelled, whichever is later.
+    pub fn last_activity_timestamp_seconds(&self) -> u64 {
+        let last_payment_timestamp_seconds = self
+            .payments
+            .last()
+            .map(|payment| payment.timestamp_seconds)
+            .unwrap_or_default();
+
+        last_payment_timestamp_seconds.max(self.cancelled_timestamp_seconds.unwrap_or_default())
+    }
+}
+
 pub enum Wasm {
     Bytes(Vec<u8>),
     Chunked {
@@ -3101,6 +3229,17 @@ pub mod test_helpers {
             }.clone()
         }
 
+        /// The timeout is ignored, because calls to the mocked canisters return immediately.
+        async fn call_canister_with_timeout(
+            &self,
+            canister_id: CanisterId,
+            method_name: &str,
+            arg: Vec<u8>,
+            _timeout_seconds: u32,
+        ) -> CanisterCallResult {
+            self.call_canister(canister_id, method_name, arg).await
+        }
+
         /// At least in the case of Governance (the only known user of
         /// Environment), this is only used to determine whether to "short
         /// circuit", i.e. return ResourceExhausted instead of doing the "real
@@ -1578,6 +1578,8 @@ fn test_from_manage_ledger_parameters_into_ledger_upgrade_args() {
             max_memo_length: None,
             feature_flags: None,
             change_archive_options: None,
+            max_transfers_per_batch: None,
+            max_balances_per_batch: None,
         }
     );
 }
@@ -1604,6 +1606,8 @@ fn test_from_manage_ledger_parameters_into_ledger_upgrade_args_no_logo() {
             max_memo_length: None,
             feature_flags: None,
             change_archive_options: None,
+            max_transfers_per_batch: None,
+            max_balances_per_batch: None,
         }
     );
 }
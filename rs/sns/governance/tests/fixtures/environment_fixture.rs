@@ -182,6 +182,16 @@ impl Environment for EnvironmentFixture {
         }
     }
 
+    async fn call_canister_with_timeout(
+        &self,
+        canister_id: CanisterId,
+        method_name: &str,
+        arg: Vec<u8>,
+        _timeout_seconds: u32,
+    ) -> Result<Vec<u8>, (Option<i32>, String)> {
+        self.call_canister(canister_id, method_name, arg).await
+    }
+
     fn heap_growth_potential(&self) -> HeapGrowthPotential {
         HeapGrowthPotential::NoIssue
     }
@@ -633,6 +633,7 @@ impl SnsInitPayload {
         Some(IndexArg::Init(InitArg {
             ledger_id: Principal::from(sns_canister_ids.ledger),
             retrieve_blocks_from_ledger_interval_seconds: None,
+            account_field_paths: None,
         }))
     }
 
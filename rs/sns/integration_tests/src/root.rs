@@ -1,3 +1,4 @@
+use crate::manage_dapp_canister_settings::EMPTY_WASM;
 use candid::{Decode, Encode};
 use canister_test::Project;
 use dfn_candid::candid;
@@ -13,7 +14,12 @@ use ic_nervous_system_root::change_canister::ChangeCanisterRequest;
 use ic_nns_constants::GOVERNANCE_CANISTER_ID;
 use ic_nns_test_utils::state_test_helpers::{get_controllers, set_controllers, update_with_sender};
 use ic_sns_root::{
-    pb::v1::SnsRootCanister, GetSnsCanistersSummaryRequest, GetSnsCanistersSummaryResponse,
+    pb::v1::{
+        ChangeDappCanisterResponse, DeleteCanisterSnapshotRequest, DeleteCanisterSnapshotResponse,
+        LoadCanisterSnapshotRequest, LoadCanisterSnapshotResponse, SnsRootCanister,
+        TakeCanisterSnapshotRequest, TakeCanisterSnapshotResponse,
+    },
+    GetSnsCanistersSummaryRequest, GetSnsCanistersSummaryResponse,
 };
 use ic_sns_test_utils::{
     itest_helpers::{
@@ -26,7 +32,7 @@ use ic_sns_test_utils::{
 };
 use ic_state_machine_tests::StateMachine;
 use ic_types::ingress::WasmResult;
-use ic_universal_canister::UNIVERSAL_CANISTER_WASM;
+use ic_universal_canister::{UNIVERSAL_CANISTER_WASM, UNIVERSAL_CANISTER_WASM_SHA256};
 use std::{collections::BTreeSet, time::Duration};
 
 #[test]
@@ -343,6 +349,139 @@ fn test_root_restarts_governance_on_stop_canister_timeout() {
     // We assert no upgrade happened.
     assert_eq!(get_gov_hash(), installed_gov_hash)
 }
+#[test]
+fn test_upgrade_dapp_canister_with_snapshot() {
+    let state_machine = state_machine_builder_for_sns_tests().build();
+
+    let scenario = Scenario::new(&state_machine, Tokens::from_tokens(100).unwrap());
+    scenario.init_all_canisters(&state_machine);
+    let root_canister_id = scenario.root_canister_id;
+    let governance_principal_id = scenario.governance_canister_id.get();
+    let dapp_canister_id = scenario.dapp_canister_ids[0];
+
+    // Install a minimal wasm into the dapp canister and register it with root.
+    state_machine
+        .install_wasm_in_mode(
+            dapp_canister_id,
+            CanisterInstallMode::Install,
+            EMPTY_WASM.to_vec(),
+            vec![],
+        )
+        .unwrap();
+    set_controllers(
+        &state_machine,
+        *TEST_USER1_PRINCIPAL,
+        dapp_canister_id,
+        vec![root_canister_id.into()],
+    );
+    let _response = sns_root_register_dapp_canister(
+        &state_machine,
+        root_canister_id,
+        scenario.governance_canister_id,
+        dapp_canister_id,
+    );
+    let original_module_hash = state_machine.module_hash(dapp_canister_id).unwrap();
+    let upgraded_module_hash = *UNIVERSAL_CANISTER_WASM_SHA256;
+
+    let take_snapshot = |replace_snapshot: Option<Vec<u8>>| {
+        let response: TakeCanisterSnapshotResponse = update_with_sender(
+            &state_machine,
+            root_canister_id,
+            "take_canister_snapshot",
+            TakeCanisterSnapshotRequest {
+                canister_id: Some(dapp_canister_id.get()),
+                replace_snapshot,
+            },
+            governance_principal_id,
+        )
+        .unwrap();
+        assert_eq!(response.failure_reason, None);
+        response.snapshot_id.unwrap()
+    };
+    let load_snapshot = |snapshot_id: Vec<u8>| {
+        let response: LoadCanisterSnapshotResponse = update_with_sender(
+            &state_machine,
+            root_canister_id,
+            "load_canister_snapshot",
+            LoadCanisterSnapshotRequest {
+                canister_id: Some(dapp_canister_id.get()),
+                snapshot_id,
+            },
+            governance_principal_id,
+        )
+        .unwrap();
+        response.failure_reason
+    };
+    let upgrade = || {
+        let response: ChangeDappCanisterResponse = update_with_sender(
+            &state_machine,
+            root_canister_id,
+            "change_dapp_canister",
+            ChangeCanisterRequest::new(false, CanisterInstallMode::Reinstall, dapp_canister_id)
+                .with_wasm(UNIVERSAL_CANISTER_WASM.to_vec())
+                .with_arg(vec![]),
+            governance_principal_id,
+        )
+        .unwrap();
+        assert_eq!(response.failure_reason, None);
+    };
+    let assert_module_hash_and_running = |expected_module_hash: [u8; 32]| {
+        assert_eq!(
+            state_machine.module_hash(dapp_canister_id),
+            Some(expected_module_hash)
+        );
+        let status = state_machine
+            .canister_status_as(root_canister_id.get(), dapp_canister_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            status.status(),
+            ic_management_canister_types_private::CanisterStatusType::Running
+        );
+    };
+
+    // Only governance can take snapshots of dapp canisters.
+    let unauthorized: Result<TakeCanisterSnapshotResponse, String> = update_with_sender(
+        &state_machine,
+        root_canister_id,
+        "take_canister_snapshot",
+        TakeCanisterSnapshotRequest {
+            canister_id: Some(dapp_canister_id.get()),
+            replace_snapshot: None,
+        },
+        *TEST_USER1_PRINCIPAL,
+    );
+    assert!(unauthorized.is_err(), "{:?}", unauthorized);
+
+    // Rollback: the canister is restored to the state of the snapshot.
+    let snapshot_id = take_snapshot(None);
+    upgrade();
+    assert_module_hash_and_running(upgraded_module_hash);
+    assert_eq!(load_snapshot(snapshot_id.clone()), None);
+    assert_module_hash_and_running(original_module_hash);
+
+    // Success: the new snapshot replaces the previous one, and is deleted
+    // once it is no longer needed.
+    let new_snapshot_id = take_snapshot(Some(snapshot_id.clone()));
+    upgrade();
+    assert_module_hash_and_running(upgraded_module_hash);
+    let response: DeleteCanisterSnapshotResponse = update_with_sender(
+        &state_machine,
+        root_canister_id,
+        "delete_canister_snapshot",
+        DeleteCanisterSnapshotRequest {
+            canister_id: Some(dapp_canister_id.get()),
+            snapshot_id: new_snapshot_id.clone(),
+        },
+        governance_principal_id,
+    )
+    .unwrap();
+    assert_eq!(response.failure_reason, None);
+    for deleted_snapshot_id in [snapshot_id, new_snapshot_id] {
+        assert!(load_snapshot(deleted_snapshot_id).is_some());
+    }
+    assert_module_hash_and_running(upgraded_module_hash);
+}
 
 fn root_get_sns_canisters_summary(
     scenario: &Scenario,
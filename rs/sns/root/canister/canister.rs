@@ -22,11 +22,13 @@ use ic_nervous_system_runtime::{CdkRuntime, Runtime};
 use ic_sns_root::{
     logs::{ERROR, INFO},
     pb::v1::{
-        CanisterCallError, ListSnsCanistersRequest, ListSnsCanistersResponse,
+        CanisterCallError, ChangeDappCanisterResponse, DeleteCanisterSnapshotRequest,
+        DeleteCanisterSnapshotResponse, ListSnsCanistersRequest, ListSnsCanistersResponse,
+        LoadCanisterSnapshotRequest, LoadCanisterSnapshotResponse,
         ManageDappCanisterSettingsRequest, ManageDappCanisterSettingsResponse,
         RegisterDappCanisterRequest, RegisterDappCanisterResponse, RegisterDappCanistersRequest,
         RegisterDappCanistersResponse, SetDappControllersRequest, SetDappControllersResponse,
-        SnsRootCanister,
+        SnsRootCanister, TakeCanisterSnapshotRequest, TakeCanisterSnapshotResponse,
     },
     types::Environment,
     GetSnsCanistersSummaryRequest, GetSnsCanistersSummaryResponse, LedgerCanisterClient,
@@ -356,6 +358,52 @@ async fn manage_dapp_canister_settings(
     })
 }
 
+/// Takes a snapshot of a registered dapp canister. Only SNS governance is
+/// authorized to call this method.
+#[candid_method(update)]
+#[update]
+async fn take_canister_snapshot(
+    request: TakeCanisterSnapshotRequest,
+) -> TakeCanisterSnapshotResponse {
+    log!(INFO, "take_canister_snapshot");
+    assert_eq_governance_canister_id(PrincipalId(ic_cdk::api::caller()));
+    SnsRootCanister::take_dapp_canister_snapshot::<CanisterRuntime>(&STATE, request).await
+}
+
+/// Loads a snapshot of a registered dapp canister. Only SNS governance is
+/// authorized to call this method.
+#[candid_method(update)]
+#[update]
+async fn load_canister_snapshot(
+    request: LoadCanisterSnapshotRequest,
+) -> LoadCanisterSnapshotResponse {
+    log!(INFO, "load_canister_snapshot");
+    assert_eq_governance_canister_id(PrincipalId(ic_cdk::api::caller()));
+    SnsRootCanister::load_dapp_canister_snapshot::<CanisterRuntime>(&STATE, request).await
+}
+
+/// Deletes a snapshot of a registered dapp canister. Only SNS governance is
+/// authorized to call this method.
+#[candid_method(update)]
+#[update]
+async fn delete_canister_snapshot(
+    request: DeleteCanisterSnapshotRequest,
+) -> DeleteCanisterSnapshotResponse {
+    log!(INFO, "delete_canister_snapshot");
+    assert_eq_governance_canister_id(PrincipalId(ic_cdk::api::caller()));
+    SnsRootCanister::delete_dapp_canister_snapshot::<CanisterRuntime>(&STATE, request).await
+}
+
+/// Same as change_canister, but only for registered dapp canisters, and only
+/// replies once the change has been completed (or has failed).
+#[candid_method(update)]
+#[update]
+async fn change_dapp_canister(request: ChangeCanisterRequest) -> ChangeDappCanisterResponse {
+    log!(INFO, "change_dapp_canister");
+    assert_eq_governance_canister_id(PrincipalId(ic_cdk::api::caller()));
+    SnsRootCanister::change_dapp_canister::<CanisterRuntime>(&STATE, request).await
+}
+
 fn assert_state_is_valid(state: &SnsRootCanister) {
     assert!(state.governance_canister_id.is_some());
     assert!(state.ledger_canister_id.is_some());
@@ -250,6 +250,110 @@ pub struct ManageDappCanisterSettingsResponse {
     #[prost(string, optional, tag = "1")]
     pub failure_reason: ::core::option::Option<::prost::alloc::string::String>,
 }
+/// Takes a snapshot of a registered dapp canister.
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    Clone,
+    PartialEq,
+    ::prost::Message,
+)]
+pub struct TakeCanisterSnapshotRequest {
+    #[prost(message, optional, tag = "1")]
+    pub canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
+    /// If set, this (existing) snapshot of the canister is replaced by the new one.
+    #[prost(bytes = "vec", optional, tag = "2")]
+    pub replace_snapshot: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    Clone,
+    PartialEq,
+    ::prost::Message,
+)]
+pub struct TakeCanisterSnapshotResponse {
+    /// The ID of the new snapshot. Set if and only if failure_reason is not set.
+    #[prost(bytes = "vec", optional, tag = "1")]
+    pub snapshot_id: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    /// Absence of failure_reason indicates success.
+    #[prost(string, optional, tag = "2")]
+    pub failure_reason: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Loads a snapshot of a registered dapp canister, that is, restores the code
+/// and state of the canister at the time when the snapshot was taken.
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    Clone,
+    PartialEq,
+    ::prost::Message,
+)]
+pub struct LoadCanisterSnapshotRequest {
+    #[prost(message, optional, tag = "1")]
+    pub canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub snapshot_id: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    Clone,
+    PartialEq,
+    ::prost::Message,
+)]
+pub struct LoadCanisterSnapshotResponse {
+    /// Absence of failure_reason indicates success.
+    #[prost(string, optional, tag = "1")]
+    pub failure_reason: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Deletes a snapshot of a registered dapp canister.
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    Clone,
+    PartialEq,
+    ::prost::Message,
+)]
+pub struct DeleteCanisterSnapshotRequest {
+    #[prost(message, optional, tag = "1")]
+    pub canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub snapshot_id: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    Clone,
+    PartialEq,
+    ::prost::Message,
+)]
+pub struct DeleteCanisterSnapshotResponse {
+    /// Absence of failure_reason indicates success.
+    #[prost(string, optional, tag = "1")]
+    pub failure_reason: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// The response of change_dapp_canister, which (unlike change_canister) only
+/// replies once the change has been completed (or has failed).
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    Clone,
+    PartialEq,
+    ::prost::Message,
+)]
+pub struct ChangeDappCanisterResponse {
+    /// Absence of failure_reason indicates success.
+    #[prost(string, optional, tag = "1")]
+    pub failure_reason: ::core::option::Option<::prost::alloc::string::String>,
+}
 #[derive(
     candid::CandidType,
     candid::Deserialize,
@@ -1,30 +1,41 @@
 use crate::{
     logs::{ERROR, INFO},
     pb::v1::{
-        set_dapp_controllers_response, CanisterCallError, ListSnsCanistersResponse,
+        set_dapp_controllers_response, CanisterCallError, ChangeDappCanisterResponse,
+        DeleteCanisterSnapshotRequest, DeleteCanisterSnapshotResponse, ListSnsCanistersResponse,
+        LoadCanisterSnapshotRequest, LoadCanisterSnapshotResponse,
         ManageDappCanisterSettingsRequest, ManageDappCanisterSettingsResponse,
         RegisterDappCanistersRequest, RegisterDappCanistersResponse, SetDappControllersRequest,
-        SetDappControllersResponse, SnsRootCanister,
+        SetDappControllersResponse, SnsRootCanister, TakeCanisterSnapshotRequest,
+        TakeCanisterSnapshotResponse,
     },
     types::Environment,
 };
 use async_trait::async_trait;
 use candid::{Decode, Encode, Nat};
 use futures::{future::join_all, join};
-use ic_base_types::{CanisterId, PrincipalId};
+use ic_base_types::{CanisterId, PrincipalId, SnapshotId};
 use ic_canister_log::log;
+use ic_management_canister_types_private::{
+    CanisterSnapshotResponse, DeleteCanisterSnapshotArgs, LoadCanisterSnapshotArgs,
+    TakeCanisterSnapshotArgs,
+};
 use ic_nervous_system_clients::{
     canister_id_record::CanisterIdRecord,
     canister_status::CanisterStatusResultV2,
     management_canister_client::ManagementCanisterClient,
     update_settings::{CanisterSettings, LogVisibility, UpdateSettings},
 };
+use ic_nervous_system_root::change_canister::{
+    change_canister, start_canister, stop_canister, ChangeCanisterRequest,
+};
 use ic_nervous_system_runtime::{CdkRuntime, Runtime};
 use ic_sns_swap::pb::v1::GetCanisterStatusRequest;
 use std::{
     cell::RefCell,
     collections::{BTreeSet, HashSet},
     fmt::Write,
+    future::Future,
     thread::LocalKey,
 };
 use types::SnsCanisterType;
@@ -731,6 +742,200 @@ impl SnsRootCanister {
         }
     }
 
+    /// Takes a snapshot of a dapp canister. The canister is stopped while the
+    /// snapshot is taken, so that the snapshot does not capture any outstanding
+    /// calls, and restarted afterwards.
+    pub async fn take_dapp_canister_snapshot<Rt: Runtime>(
+        self_ref: &'static LocalKey<RefCell<Self>>,
+        request: TakeCanisterSnapshotRequest,
+    ) -> TakeCanisterSnapshotResponse {
+        let TakeCanisterSnapshotRequest {
+            canister_id,
+            replace_snapshot,
+        } = request;
+        let result = async {
+            let canister_id =
+                self_ref.with(|state| state.borrow().validate_dapp_canister_id(canister_id))?;
+            let replace_snapshot = replace_snapshot
+                .map(|snapshot_id| {
+                    SnapshotId::try_from(&snapshot_id)
+                        .map_err(|err| format!("Invalid replace_snapshot: {err:?}"))
+                })
+                .transpose()?;
+            let args = TakeCanisterSnapshotArgs::new(canister_id, replace_snapshot);
+            while_stopped::<Rt, _, _>(canister_id, async {
+                Rt::call_with_cleanup(CanisterId::ic_00(), "take_canister_snapshot", (args,))
+                    .await
+                    .map(|(snapshot,): (CanisterSnapshotResponse,)| snapshot.id)
+                    .map_err(|(code, message)| {
+                        format!(
+                            "Failed to take a snapshot of canister {canister_id}: {message} \
+                             (code {code})."
+                        )
+                    })
+            })
+            .await
+        }
+        .await;
+
+        match result {
+            Ok(snapshot_id) => {
+                log!(
+                    INFO,
+                    "Took snapshot {} of dapp canister {:?}",
+                    hex::encode(&snapshot_id),
+                    canister_id,
+                );
+                TakeCanisterSnapshotResponse {
+                    snapshot_id: Some(snapshot_id),
+                    failure_reason: None,
+                }
+            }
+            Err(failure_reason) => {
+                log!(ERROR, "{}", failure_reason);
+                TakeCanisterSnapshotResponse {
+                    snapshot_id: None,
+                    failure_reason: Some(failure_reason),
+                }
+            }
+        }
+    }
+
+    /// Loads a snapshot of a dapp canister. The canister is stopped while the
+    /// snapshot is loaded, and restarted afterwards.
+    pub async fn load_dapp_canister_snapshot<Rt: Runtime>(
+        self_ref: &'static LocalKey<RefCell<Self>>,
+        request: LoadCanisterSnapshotRequest,
+    ) -> LoadCanisterSnapshotResponse {
+        let LoadCanisterSnapshotRequest {
+            canister_id,
+            snapshot_id,
+        } = request;
+        let result = async {
+            let canister_id =
+                self_ref.with(|state| state.borrow().validate_dapp_canister_id(canister_id))?;
+            let snapshot_id = SnapshotId::try_from(&snapshot_id)
+                .map_err(|err| format!("Invalid snapshot_id: {err:?}"))?;
+            let args = LoadCanisterSnapshotArgs::new(
+                canister_id,
+                snapshot_id,
+                Some(Rt::canister_version()),
+            );
+            while_stopped::<Rt, _, _>(canister_id, async {
+                Rt::call_with_cleanup(CanisterId::ic_00(), "load_canister_snapshot", (args,))
+                    .await
+                    .map(|(): ()| ())
+                    .map_err(|(code, message)| {
+                        format!(
+                            "Failed to load snapshot {} into canister {canister_id}: {message} \
+                             (code {code}).",
+                            hex::encode(snapshot_id.as_slice()),
+                        )
+                    })
+            })
+            .await
+        }
+        .await;
+
+        let failure_reason = result.err();
+        match &failure_reason {
+            None => log!(
+                INFO,
+                "Loaded snapshot {} into dapp canister {:?}",
+                hex::encode(&snapshot_id),
+                canister_id,
+            ),
+            Some(failure_reason) => log!(ERROR, "{}", failure_reason),
+        }
+        LoadCanisterSnapshotResponse { failure_reason }
+    }
+
+    /// Deletes a snapshot of a dapp canister.
+    pub async fn delete_dapp_canister_snapshot<Rt: Runtime>(
+        self_ref: &'static LocalKey<RefCell<Self>>,
+        request: DeleteCanisterSnapshotRequest,
+    ) -> DeleteCanisterSnapshotResponse {
+        let DeleteCanisterSnapshotRequest {
+            canister_id,
+            snapshot_id,
+        } = request;
+        let result = async {
+            let canister_id =
+                self_ref.with(|state| state.borrow().validate_dapp_canister_id(canister_id))?;
+            let snapshot_id = SnapshotId::try_from(&snapshot_id)
+                .map_err(|err| format!("Invalid snapshot_id: {err:?}"))?;
+            let args = DeleteCanisterSnapshotArgs::new(canister_id, snapshot_id);
+            Rt::call_with_cleanup(CanisterId::ic_00(), "delete_canister_snapshot", (args,))
+                .await
+                .map(|(): ()| ())
+                .map_err(|(code, message)| {
+                    format!(
+                        "Failed to delete snapshot {} of canister {canister_id}: {message} \
+                         (code {code}).",
+                        hex::encode(snapshot_id.as_slice()),
+                    )
+                })
+        }
+        .await;
+
+        let failure_reason = result.err();
+        match &failure_reason {
+            None => log!(
+                INFO,
+                "Deleted snapshot {} of dapp canister {:?}",
+                hex::encode(&snapshot_id),
+                canister_id,
+            ),
+            Some(failure_reason) => log!(ERROR, "{}", failure_reason),
+        }
+        DeleteCanisterSnapshotResponse { failure_reason }
+    }
+
+    /// Changes (e.g., upgrades) a dapp canister. Unlike change_canister, which
+    /// is also used to upgrade the SNS canisters (including governance, which
+    /// cannot be stopped while it is waiting for the reply), this only returns
+    /// once the change has been completed.
+    pub async fn change_dapp_canister<Rt: Runtime>(
+        self_ref: &'static LocalKey<RefCell<Self>>,
+        request: ChangeCanisterRequest,
+    ) -> ChangeDappCanisterResponse {
+        let canister_id = request.canister_id;
+        let result = match self_ref.with(|state| {
+            state
+                .borrow()
+                .validate_dapp_canister_id(Some(canister_id.get()))
+        }) {
+            Ok(_) => change_canister::<Rt>(request).await,
+            Err(err) => Err(err),
+        };
+
+        let failure_reason = result.err();
+        match &failure_reason {
+            None => log!(INFO, "Changed dapp canister {}", canister_id),
+            Some(failure_reason) => log!(
+                ERROR,
+                "Failed to change dapp canister {}: {}",
+                canister_id,
+                failure_reason
+            ),
+        }
+        ChangeDappCanisterResponse { failure_reason }
+    }
+
+    /// Returns the ID of the given canister, if it is a registered dapp canister.
+    fn validate_dapp_canister_id(
+        &self,
+        canister_id: Option<PrincipalId>,
+    ) -> Result<CanisterId, String> {
+        let canister_id = canister_id.ok_or_else(|| "canister_id is required.".to_string())?;
+        if !self.dapp_canister_ids.contains(&canister_id) {
+            return Err(format!(
+                "Canister {canister_id} is not a registered dapp canister."
+            ));
+        }
+        CanisterId::try_from(canister_id).map_err(|err| format!("Invalid canister_id: {err}"))
+    }
+
     /// Polls for new archives canisters from the ledger canister.
     pub async fn poll_for_new_archive_canisters(
         self_ref: &'static LocalKey<RefCell<Self>>,
@@ -875,6 +1080,36 @@ async fn get_owned_canister_summary(
     }
 }
 
+/// Stops the given canister, runs `operation`, and restarts the canister, even if
+/// `operation` failed.
+async fn while_stopped<Rt, F, T>(canister_id: CanisterId, operation: F) -> Result<T, String>
+where
+    Rt: Runtime,
+    F: Future<Output = Result<T, String>>,
+{
+    if let Err((code, message)) = stop_canister::<Rt>(canister_id).await {
+        // Make sure that the canister does not get stuck in the stopping state.
+        let restart_result = start_canister::<Rt>(canister_id).await;
+        return Err(format!(
+            "Failed to stop canister {canister_id}: {message} (code {code}). \
+             Restarting the canister: {restart_result:?}."
+        ));
+    }
+
+    let result = operation.await;
+
+    if let Err((code, message)) = start_canister::<Rt>(canister_id).await {
+        let restart_error =
+            format!("Failed to restart canister {canister_id}: {message} (code {code}).");
+        return Err(match result {
+            Ok(_) => restart_error,
+            Err(err) => format!("{err} {restart_error}"),
+        });
+    }
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2261,6 +2496,27 @@ mod tests {
         assert!(failure_reason.contains(&PrincipalId::new_user_test_id(4).to_string()));
     }
 
+    #[test]
+    fn test_validate_dapp_canister_id() {
+        let dapp_canister_id = CanisterId::from_u64(10000);
+        let state = SnsRootCanister {
+            governance_canister_id: Some(PrincipalId::new_user_test_id(1)),
+            dapp_canister_ids: vec![dapp_canister_id.get()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            state.validate_dapp_canister_id(Some(dapp_canister_id.get())),
+            Ok(dapp_canister_id)
+        );
+        assert!(state.validate_dapp_canister_id(None).is_err());
+        // SNS canisters are not dapp canisters.
+        let failure_reason = state
+            .validate_dapp_canister_id(Some(PrincipalId::new_user_test_id(1)))
+            .unwrap_err();
+        assert!(failure_reason.contains("not a registered dapp canister"));
+    }
+
     #[test]
     fn test_list_sns_canisters() {
         let state = SnsRootCanister {
@@ -156,6 +156,7 @@ impl SnsTestsInitPayloadBuilder {
         let index_ng = Some(IndexArg::Init(InitArg {
             ledger_id: CanisterId::from_u64(0).into(),
             retrieve_blocks_from_ledger_interval_seconds: None,
+            account_field_paths: None,
         }));
 
         let mut governance = GovernanceCanisterInitPayloadBuilder::new();
@@ -0,0 +1,250 @@
+//! Reports of the Wasm-level code coverage collected by a `StateMachine`
+//! with Wasm coverage enabled (see `ic_embedders::wasm_utils::coverage`).
+//!
+//! The basic blocks of a canister's Wasm module are mapped back to source code
+//! using the DWARF debug information of the module (if present) and the
+//! function names from its `name` section.
+
+use gimli::{Dwarf, EndianSlice, LittleEndian, SectionId};
+use ic_embedders::wasm_utils::coverage::{coverage_counters, CoverageMap};
+use ic_management_canister_types_private::Global;
+use ic_types::CanisterId;
+use ic_wasm_types::BinaryEncodedWasm;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use wasmparser::{Parser, Payload};
+
+/// A source code location.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u64,
+}
+
+/// Coverage of a single basic block of a canister's Wasm module.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct BlockCoverage {
+    /// Index of the function in the function index space of the module.
+    pub function_index: u32,
+    /// Name of the function from the `name` section (if any).
+    pub function_name: Option<String>,
+    /// Offset of the first instruction of the block relative to the start of the code section.
+    pub code_offset: u64,
+    /// Source location of the first instruction of the block from the DWARF debug information (if any).
+    pub location: Option<SourceLocation>,
+    /// Number of times the block was executed.
+    pub hits: u64,
+}
+
+/// Coverage of a canister's Wasm module since it was last installed, reinstalled, or upgraded.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CoverageReport {
+    pub canister_id: CanisterId,
+    pub blocks: Vec<BlockCoverage>,
+}
+
+impl CoverageReport {
+    /// Creates a coverage report from the (decoded) Wasm module of a canister,
+    /// the module instrumented with Wasm coverage enabled, and the exported globals
+    /// of the execution state of the canister.
+    pub(crate) fn new(
+        canister_id: CanisterId,
+        wasm: &BinaryEncodedWasm,
+        instrumented_wasm: &BinaryEncodedWasm,
+        exported_globals: &[Global],
+    ) -> Result<Self, String> {
+        let coverage_map = CoverageMap::new(wasm).map_err(|err| err.to_string())?;
+        let counters = coverage_counters(instrumented_wasm, exported_globals).map_err(|err| {
+            format!(
+                "failed to read the coverage counters of canister {}: {} \
+                 (was the canister installed with Wasm coverage enabled?)",
+                canister_id, err
+            )
+        })?;
+        if counters.len() != coverage_map.blocks.len() {
+            return Err(format!(
+                "canister {} has {} coverage counters, but its module has {} basic blocks: \
+                 was the canister installed with Wasm coverage enabled?",
+                canister_id,
+                counters.len(),
+                coverage_map.blocks.len()
+            ));
+        }
+        let line_table = LineTable::new(wasm)?;
+        let blocks = coverage_map
+            .blocks
+            .into_iter()
+            .zip(counters)
+            .map(|(block, hits)| BlockCoverage {
+                function_index: block.function_index,
+                function_name: block.function_name,
+                code_offset: block.code_offset,
+                location: line_table.lookup(block.code_offset),
+                hits,
+            })
+            .collect();
+        Ok(Self {
+            canister_id,
+            blocks,
+        })
+    }
+
+    /// Renders the report in the LCOV tracefile format.
+    ///
+    /// Lines are taken from the DWARF debug information of the module. The hit
+    /// count of a line is the maximum hit count of the basic blocks starting
+    /// at that line. If the module has no debug information, a single record
+    /// for the pseudo source file `<canister_id>.wasm` is rendered in which the
+    /// line numbers are the function indices and the hit count of a function
+    /// is the hit count of its entry block.
+    pub fn to_lcov(&self) -> String {
+        // Source file -> function name -> (first line, entry block hits).
+        let mut functions: BTreeMap<String, BTreeMap<String, (u64, u64)>> = BTreeMap::new();
+        // Source file -> line -> hits.
+        let mut lines: BTreeMap<String, BTreeMap<u64, u64>> = BTreeMap::new();
+        let mut seen_functions = BTreeSet::new();
+        let has_debug_info = self.blocks.iter().any(|block| block.location.is_some());
+        for block in &self.blocks {
+            let is_entry_block = seen_functions.insert(block.function_index);
+            let location = match &block.location {
+                Some(location) => location.clone(),
+                None if has_debug_info => continue,
+                None => SourceLocation {
+                    file: format!("{}.wasm", self.canister_id),
+                    line: block.function_index as u64 + 1,
+                },
+            };
+            if is_entry_block {
+                let name = block
+                    .function_name
+                    .clone()
+                    .unwrap_or_else(|| format!("func[{}]", block.function_index));
+                functions
+                    .entry(location.file.clone())
+                    .or_default()
+                    .insert(name, (location.line, block.hits));
+            }
+            let hits = lines
+                .entry(location.file)
+                .or_default()
+                .entry(location.line)
+                .or_default();
+            *hits = std::cmp::max(*hits, block.hits);
+        }
+
+        let mut lcov = String::new();
+        for (file, lines) in lines {
+            writeln!(lcov, "TN:").unwrap();
+            writeln!(lcov, "SF:{}", file).unwrap();
+            let functions = functions.remove(&file).unwrap_or_default();
+            for (name, (line, _)) in &functions {
+                writeln!(lcov, "FN:{},{}", line, name).unwrap();
+            }
+            for (name, (_, hits)) in &functions {
+                writeln!(lcov, "FNDA:{},{}", hits, name).unwrap();
+            }
+            writeln!(lcov, "FNF:{}", functions.len()).unwrap();
+            writeln!(
+                lcov,
+                "FNH:{}",
+                functions.values().filter(|(_, hits)| *hits > 0).count()
+            )
+            .unwrap();
+            for (line, hits) in &lines {
+                writeln!(lcov, "DA:{},{}", line, hits).unwrap();
+            }
+            writeln!(lcov, "LF:{}", lines.len()).unwrap();
+            writeln!(
+                lcov,
+                "LH:{}",
+                lines.values().filter(|hits| **hits > 0).count()
+            )
+            .unwrap();
+            writeln!(lcov, "end_of_record").unwrap();
+        }
+        lcov
+    }
+}
+
+/// Addresses (relative to the start of the code section) of the rows of the
+/// DWARF line number programs of a Wasm module sorted by address.
+/// The location is `None` for the end of a sequence.
+struct LineTable(Vec<(u64, Option<SourceLocation>)>);
+
+impl LineTable {
+    fn new(wasm: &BinaryEncodedWasm) -> Result<Self, String> {
+        let mut debug_sections = BTreeMap::new();
+        for payload in Parser::new(0).parse_all(wasm.as_slice()) {
+            if let Payload::CustomSection(reader) = payload.map_err(|err| err.to_string())? {
+                if reader.name().starts_with(".debug_") {
+                    debug_sections.insert(reader.name(), reader.data());
+                }
+            }
+        }
+        if debug_sections.is_empty() {
+            return Ok(Self(vec![]));
+        }
+        Self::from_dwarf(&debug_sections)
+            .map(Self)
+            .map_err(|err| format!("failed to parse DWARF debug information: {}", err))
+    }
+
+    fn from_dwarf(
+        debug_sections: &BTreeMap<&str, &[u8]>,
+    ) -> Result<Vec<(u64, Option<SourceLocation>)>, gimli::Error> {
+        let dwarf = Dwarf::load(|id: SectionId| {
+            let data = debug_sections.get(id.name()).copied().unwrap_or_default();
+            Ok::<_, gimli::Error>(EndianSlice::new(data, LittleEndian))
+        })?;
+        let mut rows = vec![];
+        let mut units = dwarf.units();
+        while let Some(header) = units.next()? {
+            let unit = dwarf.unit(header)?;
+            let Some(program) = unit.line_program.clone() else {
+                continue;
+            };
+            let mut program_rows = program.rows();
+            while let Some((header, row)) = program_rows.next_row()? {
+                if row.end_sequence() {
+                    rows.push((row.address(), None));
+                    continue;
+                }
+                let Some(file) = row.file(header) else {
+                    continue;
+                };
+                let mut path = String::new();
+                if let Some(directory) = file.directory(header) {
+                    path.push_str(&dwarf.attr_string(&unit, directory)?.to_string_lossy());
+                    if !path.is_empty() && !path.ends_with('/') {
+                        path.push('/');
+                    }
+                }
+                let name = dwarf.attr_string(&unit, file.path_name())?;
+                let name = name.to_string_lossy();
+                if name.starts_with('/') {
+                    path = name.into_owned();
+                } else {
+                    path.push_str(&name);
+                }
+                let line = row.line().map(|line| line.get()).unwrap_or(0);
+                rows.push((row.address(), Some(SourceLocation { file: path, line })));
+            }
+        }
+        // If a sequence starts at the address at which another sequence ends,
+        // the end of the latter must precede the start of the former.
+        // Sorting is stable, so rows with the same address stay in program order otherwise.
+        rows.sort_by_key(|(address, location)| (*address, location.is_some()));
+        Ok(rows)
+    }
+
+    /// Returns the location of the last row at or before the given address.
+    fn lookup(&self, address: u64) -> Option<SourceLocation> {
+        let index = self
+            .0
+            .partition_point(|(row_address, _)| *row_address <= address);
+        if index == 0 {
+            return None;
+        }
+        self.0[index - 1].1.clone()
+    }
+}
@@ -6,6 +6,7 @@ use ic_btc_consensus::BitcoinPayloadBuilder;
 use ic_config::{
     adapters::AdaptersConfig,
     bitcoin_payload_builder_config::Config as BitcoinPayloadBuilderConfig,
+    embedders::{Config as EmbeddersConfig, WASM_MAX_SIZE},
     execution_environment::Config as HypervisorConfig,
     flag_status::FlagStatus,
     message_routing::{MAX_STREAM_MESSAGES, TARGET_STREAM_SIZE_BYTES},
@@ -23,8 +24,18 @@ use ic_crypto_test_utils_ni_dkg::{
 use ic_crypto_tree_hash::{sparse_labeled_tree_from_paths, Label, Path as LabeledTreePath};
 use ic_crypto_utils_threshold_sig_der::threshold_sig_public_key_to_der;
 use ic_cycles_account_manager::CyclesAccountManager;
+use ic_embedders::{
+    wasm_utils::{decoding::decode_wasm, validate_and_instrument_for_testing},
+    WasmtimeEmbedder,
+};
 pub use ic_error_types::{ErrorCode, UserError};
-use ic_execution_environment::{ExecutionServices, IngressHistoryReaderImpl};
+use ic_execution_environment::{
+    get_latest_certified_state_and_data_certificate, ExecutionServices, IngressHistoryReaderImpl,
+    InternalHttpQueryHandler,
+};
+pub use ic_execution_environment::{
+    QueryCacheOutcome, QueryCallTrace, QueryCallTraceNode, QueryCallTraceOutcome,
+};
 use ic_http_endpoints_public::{metrics::HttpHandlerMetrics, IngressWatcher, IngressWatcherHandle};
 use ic_https_outcalls_consensus::payload_builder::CanisterHttpPayloadBuilderImpl;
 use ic_ingress_manager::{IngressManager, RandomStateKind};
@@ -55,9 +66,10 @@ use ic_management_canister_types_private::{
 use ic_management_canister_types_private::{
     CanisterHttpResponsePayload, CanisterInstallMode, CanisterSettingsArgs,
     CanisterSnapshotResponse, CanisterStatusResultV2, ClearChunkStoreArgs, EcdsaCurve, EcdsaKeyId,
-    InstallChunkedCodeArgs, LoadCanisterSnapshotArgs, SchnorrAlgorithm, SignWithECDSAReply,
-    SignWithSchnorrReply, TakeCanisterSnapshotArgs, UpdateSettingsArgs, UploadChunkArgs,
-    UploadChunkReply, VetKdDeriveKeyResult,
+    FetchCanisterLogsRequest, FetchCanisterLogsResponse, InstallChunkedCodeArgs,
+    LoadCanisterSnapshotArgs, SchnorrAlgorithm, SignWithECDSAReply, SignWithSchnorrReply,
+    TakeCanisterSnapshotArgs, UpdateSettingsArgs, UploadChunkArgs, UploadChunkReply,
+    VetKdDeriveKeyResult,
 };
 use ic_messaging::SyncMessageRouting;
 use ic_metrics::MetricsRegistry;
@@ -193,9 +205,15 @@ use tower::ServiceExt;
 /// execution. Mirrors the size used in production defined in `setup_ic_stack.rs`
 const COMPLETED_EXECUTION_MESSAGES_BUFFER_SIZE: usize = 10_000;
 
+mod coverage;
+mod profiling;
 #[cfg(test)]
 mod tests;
 
+pub use coverage::{BlockCoverage, CoverageReport, SourceLocation};
+use profiling::CostSnapshot;
+pub use profiling::{MessageProfile, ProfilingReport};
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Deserialize, Serialize)]
 pub enum SubmitIngressError {
     HttpError(String),
@@ -870,6 +888,8 @@ pub struct StateMachine {
     pub metrics_registry: MetricsRegistry,
     ingress_history_reader: Box<dyn IngressHistoryReader>,
     pub query_handler: Arc<Mutex<QueryExecutionService>>,
+    // The query handler behind `query_handler` used to execute traced queries.
+    internal_query_handler: Arc<InternalHttpQueryHandler>,
     pub runtime: Arc<Runtime>,
     // The atomicity is required for internal mutability and sending across threads.
     checkpoint_interval_length: AtomicU64,
@@ -899,6 +919,8 @@ pub struct StateMachine {
     query_stats_payload_builder: Arc<PocketQueryStatsPayloadBuilderImpl>,
     vetkd_payload_builder: Arc<dyn BatchPayloadBuilder>,
     remove_old_states: bool,
+    // The profiling report of executed ingress messages (`None` if profiling is disabled).
+    profiling_report: Mutex<Option<ProfilingReport>>,
     // This field must be the last one so that the temporary directory is deleted at the very end.
     state_dir: Box<dyn StateMachineStateDir>,
     // DO NOT PUT ANY FIELDS AFTER `state_dir`!!!
@@ -968,6 +990,7 @@ pub struct StateMachineBuilder {
     is_vetkd_enabled: bool,
     is_snapshot_download_enabled: bool,
     is_snapshot_upload_enabled: bool,
+    is_wasm_coverage_enabled: bool,
     features: SubnetFeatures,
     runtime: Option<Arc<Runtime>>,
     registry_data_provider: Arc<ProtoRegistryDataProvider>,
@@ -977,6 +1000,7 @@ pub struct StateMachineBuilder {
     log_level: Option<Level>,
     bitcoin_testnet_uds_path: Option<PathBuf>,
     remove_old_states: bool,
+    profiling: bool,
 }
 
 impl StateMachineBuilder {
@@ -1003,6 +1027,7 @@ impl StateMachineBuilder {
             is_vetkd_enabled: true,
             is_snapshot_download_enabled: false,
             is_snapshot_upload_enabled: false,
+            is_wasm_coverage_enabled: false,
             features: SubnetFeatures {
                 http_requests: true,
                 ..SubnetFeatures::default()
@@ -1015,6 +1040,7 @@ impl StateMachineBuilder {
             log_level: Some(Level::Warning),
             bitcoin_testnet_uds_path: None,
             remove_old_states: true,
+            profiling: false,
         }
     }
 
@@ -1219,6 +1245,14 @@ impl StateMachineBuilder {
         }
     }
 
+    /// Enables collection of Wasm-level code coverage, see [`StateMachine::coverage_report`].
+    pub fn with_wasm_coverage_enabled(self, is_wasm_coverage_enabled: bool) -> Self {
+        Self {
+            is_wasm_coverage_enabled,
+            ..self
+        }
+    }
+
     pub fn with_log_level(self, log_level: Option<Level>) -> Self {
         Self { log_level, ..self }
     }
@@ -1237,8 +1271,17 @@ impl StateMachineBuilder {
         }
     }
 
+    /// Enables profiling of the cost of ingress messages, see [`StateMachine::enable_profiling`].
+    pub fn with_profiling(self) -> Self {
+        Self {
+            profiling: true,
+            ..self
+        }
+    }
+
     pub fn build_internal(self) -> StateMachine {
-        StateMachine::setup_from_dir(
+        let profiling = self.profiling;
+        let sm = StateMachine::setup_from_dir(
             self.state_dir,
             self.nonce,
             self.time,
@@ -1258,6 +1301,7 @@ impl StateMachineBuilder {
             self.is_vetkd_enabled,
             self.is_snapshot_download_enabled,
             self.is_snapshot_upload_enabled,
+            self.is_wasm_coverage_enabled,
             self.features,
             self.runtime.unwrap_or_else(|| {
                 tokio::runtime::Builder::new_current_thread()
@@ -1270,7 +1314,11 @@ impl StateMachineBuilder {
             self.seed,
             self.log_level,
             self.remove_old_states,
-        )
+        );
+        if profiling {
+            sm.enable_profiling();
+        }
+        sm
     }
 
     pub fn build(self) -> StateMachine {
@@ -1603,6 +1651,7 @@ impl StateMachine {
         is_vetkd_enabled: bool,
         is_snapshot_download_enabled: bool,
         is_snapshot_upload_enabled: bool,
+        is_wasm_coverage_enabled: bool,
         features: SubnetFeatures,
         runtime: Arc<Runtime>,
         registry_data_provider: Arc<ProtoRegistryDataProvider>,
@@ -1629,6 +1678,12 @@ impl StateMachine {
         if is_snapshot_upload_enabled {
             hypervisor_config.canister_snapshot_upload = FlagStatus::Enabled;
         }
+        if is_wasm_coverage_enabled {
+            hypervisor_config
+                .embedders_config
+                .feature_flags
+                .wasm_coverage = FlagStatus::Enabled;
+        }
         if let Some(ecdsa_signature_fee) = ecdsa_signature_fee {
             subnet_config
                 .cycles_account_manager_config
@@ -1965,6 +2020,7 @@ impl StateMachine {
             message_routing,
             metrics_registry: metrics_registry.clone(),
             query_handler: Arc::new(Mutex::new(execution_services.query_execution_service)),
+            internal_query_handler: execution_services.query_handler,
             ingress_watcher_handle,
             _ingress_watcher_drop_guard: ingress_watcher_drop_guard,
             certified_height_tx,
@@ -1990,6 +2046,7 @@ impl StateMachine {
             query_stats_payload_builder: pocket_query_stats_payload_builder,
             vetkd_payload_builder,
             remove_old_states,
+            profiling_report: Mutex::new(None),
         }
     }
 
@@ -3485,6 +3542,38 @@ impl StateMachine {
         }
     }
 
+    /// Queries the canister with the specified ID like [`StateMachine::query_as`]
+    /// and additionally returns the trace of the query call tree. The trace
+    /// shows the instructions and response size of every query call and how
+    /// close a composite query came to the call graph limits.
+    pub fn query_as_with_trace(
+        &self,
+        sender: PrincipalId,
+        receiver: CanisterId,
+        method: impl ToString,
+        method_payload: Vec<u8>,
+    ) -> (Result<WasmResult, UserError>, QueryCallTrace) {
+        self.certify_latest_state();
+        let user_query = Query {
+            source: QuerySource::User {
+                user_id: UserId::from(sender),
+                ingress_expiry: 0,
+                nonce: None,
+            },
+            receiver,
+            method_name: method.to_string(),
+            method_payload,
+        };
+        let (state, data_certificate) = get_latest_certified_state_and_data_certificate(
+            self.state_manager.clone(),
+            None,
+            receiver,
+        )
+        .expect("The latest state must be certified");
+        self.internal_query_handler
+            .query_with_trace(user_query, state, data_certificate)
+    }
+
     /// Returns the module hash of the specified canister.
     pub fn module_hash(&self, canister_id: CanisterId) -> Option<[u8; 32]> {
         let state = self.state_manager.get_latest_state().take();
@@ -3499,6 +3588,37 @@ impl StateMachine {
         )
     }
 
+    /// Returns the Wasm-level code coverage of the specified canister collected
+    /// since its code was last installed, reinstalled, or upgraded.
+    /// Wasm coverage must have been enabled by [`StateMachineBuilder::with_wasm_coverage_enabled`]
+    /// when the code was installed.
+    pub fn coverage_report(&self, canister_id: CanisterId) -> Result<CoverageReport, String> {
+        let state = self.state_manager.get_latest_state().take();
+        let execution_state = state
+            .canister_state(&canister_id)
+            .ok_or_else(|| format!("canister {} not found", canister_id))?
+            .execution_state
+            .as_ref()
+            .ok_or_else(|| format!("canister {} is empty", canister_id))?;
+        let wasm = decode_wasm(
+            WASM_MAX_SIZE,
+            Arc::new(execution_state.wasm_binary.binary.as_slice().to_vec()),
+        )
+        .map_err(|err| err.to_string())?;
+        // The names of the exported globals are only known after instrumentation.
+        let mut embedders_config = EmbeddersConfig::default();
+        embedders_config.feature_flags.wasm_coverage = FlagStatus::Enabled;
+        let embedder = WasmtimeEmbedder::new(embedders_config, self.replica_logger.clone());
+        let (_, instrumentation_output) = validate_and_instrument_for_testing(&embedder, &wasm)
+            .map_err(|err| format!("failed to instrument canister {}: {}", canister_id, err))?;
+        CoverageReport::new(
+            canister_id,
+            &wasm,
+            &instrumentation_output.binary,
+            &execution_state.exported_globals,
+        )
+    }
+
     /// Executes an ingress message on the canister with the specified ID.
     ///
     /// This function is synchronous, it blocks until the result of the ingress
@@ -3518,8 +3638,55 @@ impl StateMachine {
         // Largest single message is 1T for system subnet install messages
         // Considered with 2B instruction slices, this gives us 500 ticks
         const MAX_TICKS: usize = 500;
-        let msg_id = self.send_ingress_safe(sender, canister_id, method, payload)?;
-        self.await_ingress(msg_id, MAX_TICKS)
+        if !self.is_profiling_enabled() {
+            let msg_id = self.send_ingress_safe(sender, canister_id, method, payload)?;
+            return self.await_ingress(msg_id, MAX_TICKS);
+        }
+
+        let method = method.to_string();
+        let snapshot = CostSnapshot::take(&self.metrics_registry, &self.get_latest_state());
+        let result = self
+            .send_ingress_safe(sender, canister_id, method.clone(), payload)
+            .and_then(|msg_id| self.await_ingress(msg_id, MAX_TICKS));
+        let profile = snapshot.profile(
+            &self.metrics_registry,
+            &self.get_latest_state(),
+            sender,
+            canister_id,
+            method,
+            result.is_ok(),
+        );
+        if let Some(report) = self.profiling_report.lock().unwrap().as_mut() {
+            report.messages.push(profile);
+        }
+        result
+    }
+
+    /// Enables profiling of the cost of ingress messages executed via
+    /// [`StateMachine::execute_ingress_as`] (and the functions built on top of it,
+    /// e.g., [`StateMachine::install_canister`]). For every such ingress message,
+    /// the Wasm instructions, the cycles charged (by canister and use case),
+    /// the dirty pages, and the wall time are collected in a [`ProfilingReport`]
+    /// which can be retrieved via [`StateMachine::profiling_report`].
+    ///
+    /// The profile of an ingress message includes the cost of all messages executed
+    /// until the ingress message completed, e.g., downstream inter-canister calls.
+    pub fn enable_profiling(&self) {
+        let mut report = self.profiling_report.lock().unwrap();
+        if report.is_none() {
+            *report = Some(ProfilingReport::default());
+        }
+    }
+
+    /// Returns `true` iff profiling of ingress messages is enabled.
+    pub fn is_profiling_enabled(&self) -> bool {
+        self.profiling_report.lock().unwrap().is_some()
+    }
+
+    /// Returns the profiling report of all ingress messages executed since profiling
+    /// was enabled or `None` if profiling is disabled.
+    pub fn profiling_report(&self) -> Option<ProfilingReport> {
+        self.profiling_report.lock().unwrap().clone()
     }
 
     pub fn execute_ingress(
@@ -3885,6 +4052,28 @@ impl StateMachine {
         canister_state.system_state.canister_log.clone()
     }
 
+    /// Fetches the canister logs via a query call to the management canister,
+    /// applying the filter and the limit on the number of records in the request.
+    pub fn fetch_canister_logs(
+        &self,
+        sender: PrincipalId,
+        request: FetchCanisterLogsRequest,
+    ) -> Result<FetchCanisterLogsResponse, UserError> {
+        self.query_as(
+            sender,
+            CanisterId::ic_00(),
+            "fetch_canister_logs",
+            request.encode(),
+        )
+        .map(|res| match res {
+            WasmResult::Reply(data) => FetchCanisterLogsResponse::decode(&data),
+            WasmResult::Reject(reason) => Err(UserError::new(
+                ErrorCode::CanisterRejectedMessage,
+                format!("fetch_canister_logs call rejected: {}", reason),
+            )),
+        })?
+    }
+
     /// Sets the content of the stable memory for the specified canister.
     ///
     /// If the `data` is not aligned to the Wasm page boundary, this function will extend the stable
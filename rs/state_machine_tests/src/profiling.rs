@@ -0,0 +1,169 @@
+//! Opt-in profiling of the cost of ingress messages executed by a `StateMachine`.
+//!
+//! The cost of an ingress message is attributed by taking snapshots of the
+//! relevant metrics and canister states before and after the ingress message
+//! is executed. Hence the profile of an ingress message includes the cost of
+//! all inter-canister messages, heartbeats, and timers executed until the
+//! ingress message completed.
+
+use ic_metrics::MetricsRegistry;
+use ic_replicated_state::{canister_state::system_state::CyclesUseCase, ReplicatedState};
+use ic_test_utilities_metrics::{fetch_histogram_stats, fetch_histogram_vec_stats};
+use ic_types::{CanisterId, PrincipalId};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Cost profile of a single ingress message.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub struct MessageProfile {
+    pub sender: PrincipalId,
+    pub canister_id: CanisterId,
+    pub method: String,
+    /// Whether the canister replied to or rejected the ingress message (`false` if the
+    /// ingress message failed with an error, e.g., the method does not exist or the canister trapped).
+    pub completed: bool,
+    /// Wasm instructions executed.
+    pub instructions: u64,
+    /// Cycles charged by the cycles account manager per canister and use case (fee type).
+    pub cycles: BTreeMap<CanisterId, BTreeMap<CyclesUseCase, u128>>,
+    /// Number of OS pages dirtied in the Wasm and stable memories of the executed canisters.
+    /// Only available if canister sandboxing is enabled (the default).
+    pub dirty_pages: u64,
+    /// Wall time spent executing the ingress message.
+    pub wall_time: Duration,
+}
+
+impl MessageProfile {
+    /// Returns the total number of cycles charged across all canisters and use cases.
+    pub fn total_cycles(&self) -> u128 {
+        self.cycles
+            .values()
+            .flat_map(|cycles_by_use_case| cycles_by_use_case.values())
+            .sum()
+    }
+}
+
+/// Report of the cost profiles of all ingress messages executed
+/// since profiling was enabled (in the order of execution).
+#[derive(Clone, Eq, PartialEq, Debug, Default, Serialize)]
+pub struct ProfilingReport {
+    pub messages: Vec<MessageProfile>,
+}
+
+impl ProfilingReport {
+    /// Renders the report as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("failed to serialize the profiling report")
+    }
+
+    /// Renders the instructions in the folded stacks format
+    /// (one `<canister_id>;<method> <instructions>` line per message)
+    /// that can be turned into a flamegraph, e.g., by `inferno-flamegraph`.
+    pub fn to_folded_stacks(&self) -> String {
+        let mut instructions: BTreeMap<(CanisterId, &str), u64> = BTreeMap::new();
+        for message in &self.messages {
+            *instructions
+                .entry((message.canister_id, message.method.as_str()))
+                .or_default() += message.instructions;
+        }
+        instructions
+            .into_iter()
+            .map(|((canister_id, method), instructions)| {
+                format!("{};{} {}\n", canister_id, method, instructions)
+            })
+            .collect()
+    }
+}
+
+/// Snapshot of the cumulative costs taken before an ingress message is executed.
+pub(crate) struct CostSnapshot {
+    instructions: f64,
+    dirty_pages: f64,
+    cycles: BTreeMap<CanisterId, BTreeMap<CyclesUseCase, u128>>,
+    started_at: Instant,
+}
+
+impl CostSnapshot {
+    pub(crate) fn take(metrics_registry: &MetricsRegistry, state: &ReplicatedState) -> Self {
+        Self {
+            instructions: instructions_consumed(metrics_registry),
+            dirty_pages: dirty_pages(metrics_registry),
+            cycles: consumed_cycles(state),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Returns the profile of an ingress message executed since this snapshot was taken.
+    pub(crate) fn profile(
+        self,
+        metrics_registry: &MetricsRegistry,
+        state: &ReplicatedState,
+        sender: PrincipalId,
+        canister_id: CanisterId,
+        method: String,
+        completed: bool,
+    ) -> MessageProfile {
+        let wall_time = self.started_at.elapsed();
+        let cycles = consumed_cycles(state)
+            .into_iter()
+            .filter_map(|(canister_id, cycles_by_use_case)| {
+                let before = self.cycles.get(&canister_id);
+                let delta: BTreeMap<_, _> = cycles_by_use_case
+                    .into_iter()
+                    .filter_map(|(use_case, cycles)| {
+                        let before = before
+                            .and_then(|before| before.get(&use_case))
+                            .copied()
+                            .unwrap_or_default();
+                        let delta = cycles.saturating_sub(before);
+                        (delta > 0).then_some((use_case, delta))
+                    })
+                    .collect();
+                (!delta.is_empty()).then_some((canister_id, delta))
+            })
+            .collect();
+        MessageProfile {
+            sender,
+            canister_id,
+            method,
+            completed,
+            instructions: (instructions_consumed(metrics_registry) - self.instructions) as u64,
+            cycles,
+            dirty_pages: (dirty_pages(metrics_registry) - self.dirty_pages) as u64,
+            wall_time,
+        }
+    }
+}
+
+fn instructions_consumed(metrics_registry: &MetricsRegistry) -> f64 {
+    fetch_histogram_stats(
+        metrics_registry,
+        "scheduler_instructions_consumed_per_message",
+    )
+    .map(|stats| stats.sum)
+    .unwrap_or(0.0)
+}
+
+fn dirty_pages(metrics_registry: &MetricsRegistry) -> f64 {
+    fetch_histogram_vec_stats(metrics_registry, "sandboxed_execution_dirty_pages")
+        .values()
+        .map(|stats| stats.sum)
+        .sum()
+}
+
+fn consumed_cycles(state: &ReplicatedState) -> BTreeMap<CanisterId, BTreeMap<CyclesUseCase, u128>> {
+    state
+        .canisters_iter()
+        .map(|canister| {
+            let cycles_by_use_case = canister
+                .system_state
+                .canister_metrics
+                .get_consumed_cycles_by_use_cases()
+                .iter()
+                .map(|(use_case, cycles)| (*use_case, cycles.get()))
+                .collect();
+            (canister.canister_id(), cycles_by_use_case)
+        })
+        .collect()
+}
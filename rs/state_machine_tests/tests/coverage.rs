@@ -0,0 +1,120 @@
+use ic_state_machine_tests::StateMachineBuilder;
+
+const WAT: &str = r#"
+    (module
+        (import "ic0" "msg_reply" (func $msg_reply))
+        (func $classify (param i32) (result i32)
+            (if (result i32) (local.get 0)
+                (then (i32.const 1))
+                (else (i32.const 2))
+            )
+        )
+        (func $yes (export "canister_update yes")
+            (drop (call $classify (i32.const 1)))
+            (call $msg_reply)
+        )
+        (func $no (export "canister_update no")
+            (drop (call $classify (i32.const 0)))
+            (call $msg_reply)
+        )
+        (memory 1)
+    )"#;
+
+#[test]
+fn coverage_report_requires_wasm_coverage() {
+    let env = StateMachineBuilder::new().build();
+    let canister_id = env.install_canister_wat(WAT, vec![], None);
+    env.execute_ingress(canister_id, "yes", vec![]).unwrap();
+    assert!(env.coverage_report(canister_id).is_err());
+}
+
+#[test]
+fn coverage_report_counts_executed_basic_blocks() {
+    let env = StateMachineBuilder::new()
+        .with_wasm_coverage_enabled(true)
+        .build();
+    let canister_id = env.install_canister_wat(WAT, vec![], None);
+    env.execute_ingress(canister_id, "yes", vec![]).unwrap();
+    env.execute_ingress(canister_id, "yes", vec![]).unwrap();
+
+    let report = env.coverage_report(canister_id).unwrap();
+    let hits: Vec<_> = report
+        .blocks
+        .iter()
+        .map(|block| (block.function_name.clone().unwrap(), block.hits))
+        .collect();
+    let hits: Vec<_> = hits
+        .iter()
+        .map(|(name, hits)| (name.as_str(), *hits))
+        .collect();
+    assert_eq!(
+        hits,
+        vec![
+            // Entry, `then`, `else`, and the end of `$classify`.
+            ("classify", 2),
+            ("classify", 2),
+            ("classify", 0),
+            ("classify", 2),
+            ("yes", 2),
+            ("no", 0),
+        ]
+    );
+    assert!(report.blocks.iter().all(|block| block.location.is_none()));
+
+    // Without debug information, the functions are reported as lines of a pseudo source file.
+    let expected_lcov = format!(
+        "TN:\n\
+         SF:{}.wasm\n\
+         FN:2,classify\n\
+         FN:4,no\n\
+         FN:3,yes\n\
+         FNDA:2,classify\n\
+         FNDA:0,no\n\
+         FNDA:2,yes\n\
+         FNF:3\n\
+         FNH:2\n\
+         DA:2,2\n\
+         DA:3,2\n\
+         DA:4,0\n\
+         LF:3\n\
+         LH:2\n\
+         end_of_record\n",
+        canister_id
+    );
+    assert_eq!(report.to_lcov(), expected_lcov);
+
+    // Counts are reset when the canister is reinstalled.
+    env.reinstall_canister_wat(canister_id, WAT, vec![]);
+    let report = env.coverage_report(canister_id).unwrap();
+    assert!(report.blocks.iter().all(|block| block.hits == 0));
+}
+
+#[test]
+fn coverage_report_skips_exported_globals_of_the_module() {
+    // The exported globals of the module precede and follow the function exports.
+    const WAT_WITH_GLOBALS: &str = r#"
+        (module
+            (import "ic0" "msg_reply" (func $msg_reply))
+            (global $first (export "first") (mut i64) (i64.const 42))
+            (func $bump (export "canister_update bump")
+                (global.set $first (i64.add (global.get $first) (i64.const 1)))
+                (call $msg_reply)
+            )
+            (global (export "second") i32 (i32.const 7))
+            (memory 1)
+        )"#;
+    let env = StateMachineBuilder::new()
+        .with_wasm_coverage_enabled(true)
+        .build();
+    let canister_id = env.install_canister_wat(WAT_WITH_GLOBALS, vec![], None);
+    env.execute_ingress(canister_id, "bump", vec![]).unwrap();
+    env.execute_ingress(canister_id, "bump", vec![]).unwrap();
+
+    let report = env.coverage_report(canister_id).unwrap();
+    let hits: Vec<_> = report
+        .blocks
+        .iter()
+        .map(|block| (block.function_name.as_deref(), block.hits))
+        .collect();
+    assert_eq!(hits, vec![(Some("bump"), 2)]);
+}
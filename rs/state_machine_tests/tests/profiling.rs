@@ -0,0 +1,76 @@
+use ic_registry_subnet_type::SubnetType;
+use ic_replicated_state::canister_state::system_state::CyclesUseCase;
+use ic_state_machine_tests::StateMachineBuilder;
+use ic_types::{ingress::WasmResult, Cycles};
+use ic_universal_canister::{wasm, UNIVERSAL_CANISTER_WASM};
+
+const INITIAL_CYCLES_BALANCE: Cycles = Cycles::new(100_000_000_000_000);
+
+#[test]
+fn profiling_is_disabled_by_default() {
+    let env = StateMachineBuilder::new().build();
+    assert!(!env.is_profiling_enabled());
+    assert_eq!(env.profiling_report(), None);
+}
+
+#[test]
+fn profiling_report_contains_executed_ingress_messages() {
+    let env = StateMachineBuilder::new()
+        .with_subnet_type(SubnetType::Application)
+        .with_profiling()
+        .build();
+    let canister_id = env
+        .install_canister_with_cycles(
+            UNIVERSAL_CANISTER_WASM.to_vec(),
+            vec![],
+            None,
+            INITIAL_CYCLES_BALANCE,
+        )
+        .unwrap();
+    let num_setup_messages = env.profiling_report().unwrap().messages.len();
+    assert!(num_setup_messages > 0);
+
+    let payload = wasm()
+        .stable_grow(1)
+        .stable_write(0, &[42; 3 * 4096])
+        .reply_data(b"done")
+        .build();
+    let result = env.execute_ingress(canister_id, "update", payload).unwrap();
+    assert_eq!(result, WasmResult::Reply(b"done".to_vec()));
+    let result = env.execute_ingress(canister_id, "unknown", vec![]);
+    assert!(result.is_err());
+    let payload = wasm().push_bytes(b"error").reject().build();
+    let result = env.execute_ingress(canister_id, "update", payload).unwrap();
+    assert_eq!(result, WasmResult::Reject("error".to_string()));
+
+    let report = env.profiling_report().unwrap();
+    assert_eq!(report.messages.len(), num_setup_messages + 3);
+
+    let update = &report.messages[num_setup_messages];
+    assert_eq!(update.canister_id, canister_id);
+    assert_eq!(update.method, "update");
+    assert!(update.completed);
+    assert!(update.instructions > 0);
+    assert!(update.dirty_pages >= 3);
+    let cycles = update.cycles.get(&canister_id).unwrap();
+    assert!(cycles.get(&CyclesUseCase::Instructions).unwrap() > &0);
+    assert!(cycles.get(&CyclesUseCase::IngressInduction).unwrap() > &0);
+    assert_eq!(update.total_cycles(), cycles.values().sum::<u128>());
+
+    let unknown = &report.messages[num_setup_messages + 1];
+    assert_eq!(unknown.method, "unknown");
+    assert!(!unknown.completed);
+
+    let reject = &report.messages[num_setup_messages + 2];
+    assert_eq!(reject.method, "update");
+    assert!(reject.completed);
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(
+        json["messages"].as_array().unwrap().len(),
+        num_setup_messages + 3
+    );
+    assert!(report
+        .to_folded_stacks()
+        .contains(&format!("{};update {}\n", canister_id, update.instructions)));
+}
@@ -4,6 +4,7 @@ pub mod chash;
 pub mod convert_ids;
 pub mod copy;
 pub mod decode;
+pub mod diff_canister;
 pub mod import_state;
 pub mod list;
 pub mod manifest;
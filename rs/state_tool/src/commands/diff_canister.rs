@@ -0,0 +1,545 @@
+//! Computes a structured diff of a single canister between two checkpoints.
+
+use ic_registry_subnet_type::SubnetType;
+use ic_replicated_state::{
+    canister_state::execution_state::Memory, page_map::TestPageAllocatorFileDescriptorImpl,
+    CanisterState, PageIndex, PageMap, ReplicatedState,
+};
+use ic_state_layout::CompleteCheckpointLayout;
+use ic_state_manager::{checkpoint::load_checkpoint, CheckpointMetrics};
+use ic_types::{CanisterId, Height, PrincipalId};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Placeholder for values of a canister (or of a part of a canister) that does
+/// not exist in one of the checkpoints.
+const ABSENT: &str = "<none>";
+
+/// A change of a single field between the two checkpoints.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// Changes to a canister memory (Wasm heap or stable memory).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct MemoryDiff {
+    /// Memory size in Wasm pages.
+    pub size_before: u64,
+    pub size_after: u64,
+    /// Indices of the OS pages whose contents differ.
+    pub changed_pages: Vec<u64>,
+}
+
+impl MemoryDiff {
+    fn is_empty(&self) -> bool {
+        self.size_before == self.size_after && self.changed_pages.is_empty()
+    }
+}
+
+/// Changes to the snapshots of a canister, by snapshot ID.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SnapshotsDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl SnapshotsDiff {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Structured diff of a single canister between two checkpoints.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CanisterDiff {
+    pub canister_id: String,
+    pub system_state: Vec<FieldChange>,
+    pub settings: Vec<FieldChange>,
+    pub queues: Vec<FieldChange>,
+    pub execution_state: Vec<FieldChange>,
+    pub wasm_memory: MemoryDiff,
+    pub stable_memory: MemoryDiff,
+    pub snapshots: SnapshotsDiff,
+}
+
+impl CanisterDiff {
+    pub fn is_empty(&self) -> bool {
+        self.system_state.is_empty()
+            && self.settings.is_empty()
+            && self.queues.is_empty()
+            && self.execution_state.is_empty()
+            && self.wasm_memory.is_empty()
+            && self.stable_memory.is_empty()
+            && self.snapshots.is_empty()
+    }
+}
+
+/// Output format of the `diff_canister` command.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Collects the values of the given fields for both canister versions and
+/// records the ones that differ.
+fn diff_fields(
+    a: Option<&CanisterState>,
+    b: Option<&CanisterState>,
+    fields: &[(&str, &dyn Fn(&CanisterState) -> String)],
+) -> Vec<FieldChange> {
+    fields
+        .iter()
+        .filter_map(|(field, value)| {
+            let before = a.map_or_else(|| ABSENT.to_string(), value);
+            let after = b.map_or_else(|| ABSENT.to_string(), value);
+            (before != after).then(|| FieldChange {
+                field: field.to_string(),
+                before,
+                after,
+            })
+        })
+        .collect()
+}
+
+fn format_controllers(canister: &CanisterState) -> String {
+    let controllers: Vec<String> = canister
+        .system_state
+        .controllers
+        .iter()
+        .map(PrincipalId::to_string)
+        .collect();
+    format!("[{}]", controllers.join(", "))
+}
+
+fn format_optional<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(|| ABSENT.to_string(), |v| v.to_string())
+}
+
+/// Computes the diff of two memories, page by page.
+fn diff_memory(a: Option<&Memory>, b: Option<&Memory>) -> MemoryDiff {
+    let empty = PageMap::new(Arc::new(TestPageAllocatorFileDescriptorImpl::new()));
+    let page_map_a = a.map_or(&empty, |m| &m.page_map);
+    let page_map_b = b.map_or(&empty, |m| &m.page_map);
+    MemoryDiff {
+        size_before: a.map_or(0, |m| m.size.get() as u64),
+        size_after: b.map_or(0, |m| m.size.get() as u64),
+        changed_pages: diff_page_maps(page_map_a, page_map_b),
+    }
+}
+
+/// Returns the indices of the pages that differ between the two page maps.
+fn diff_page_maps(a: &PageMap, b: &PageMap) -> Vec<u64> {
+    let num_pages = a.num_host_pages().max(b.num_host_pages()) as u64;
+    (0..num_pages)
+        .filter(|i| {
+            let index = PageIndex::new(*i);
+            a.get_page(index) != b.get_page(index)
+        })
+        .collect()
+}
+
+/// Computes the diff of the snapshots of the canister.
+fn diff_snapshots(
+    canister_id: CanisterId,
+    state_a: &ReplicatedState,
+    state_b: &ReplicatedState,
+) -> SnapshotsDiff {
+    let snapshots_a: BTreeMap<_, _> = state_a
+        .canister_snapshots
+        .list_snapshots(canister_id)
+        .into_iter()
+        .collect();
+    let snapshots_b: BTreeMap<_, _> = state_b
+        .canister_snapshots
+        .list_snapshots(canister_id)
+        .into_iter()
+        .collect();
+
+    let mut diff = SnapshotsDiff::default();
+    for (snapshot_id, snapshot_a) in snapshots_a.iter() {
+        match snapshots_b.get(snapshot_id) {
+            None => diff.removed.push(snapshot_id.to_string()),
+            Some(snapshot_b) => {
+                if snapshot_a.canister_version() != snapshot_b.canister_version()
+                    || snapshot_a.taken_at_timestamp() != snapshot_b.taken_at_timestamp()
+                    || snapshot_a.size() != snapshot_b.size()
+                {
+                    diff.changed.push(snapshot_id.to_string())
+                }
+            }
+        }
+    }
+    for snapshot_id in snapshots_b.keys() {
+        if !snapshots_a.contains_key(snapshot_id) {
+            diff.added.push(snapshot_id.to_string());
+        }
+    }
+    diff
+}
+
+/// Computes the diff of canister `canister_id` between the two states.
+pub fn diff_canister_states(
+    canister_id: CanisterId,
+    state_a: &ReplicatedState,
+    state_b: &ReplicatedState,
+) -> Result<CanisterDiff, String> {
+    let a = state_a.canister_state(&canister_id);
+    let b = state_b.canister_state(&canister_id);
+    if a.is_none() && b.is_none() {
+        return Err(format!(
+            "Canister {} does not exist in either checkpoint",
+            canister_id
+        ));
+    }
+
+    let system_state = diff_fields(
+        a,
+        b,
+        &[
+            ("exists", &|_| true.to_string()),
+            ("status", &|c| c.status().to_string()),
+            ("cycles_balance", &|c| c.system_state.balance().to_string()),
+            ("reserved_cycles", &|c| {
+                c.system_state.reserved_balance().to_string()
+            }),
+            ("certified_data", &|c| {
+                hex::encode(&c.system_state.certified_data)
+            }),
+            ("canister_version", &|c| {
+                c.system_state.canister_version.to_string()
+            }),
+            ("global_timer", &|c| {
+                format!("{:?}", c.system_state.global_timer)
+            }),
+            ("canister_log_next_idx", &|c| {
+                c.system_state.canister_log.next_idx().to_string()
+            }),
+        ],
+    );
+
+    let settings = diff_fields(
+        a,
+        b,
+        &[
+            ("controllers", &format_controllers),
+            ("compute_allocation", &|c| {
+                c.compute_allocation().to_string()
+            }),
+            ("memory_allocation", &|c| {
+                c.system_state.memory_allocation.to_string()
+            }),
+            ("freezing_threshold", &|c| {
+                c.system_state.freeze_threshold.to_string()
+            }),
+            ("reserved_cycles_limit", &|c| {
+                format_optional(c.system_state.reserved_balance_limit())
+            }),
+            ("log_visibility", &|c| {
+                format!("{:?}", c.system_state.log_visibility)
+            }),
+            ("wasm_memory_limit", &|c| {
+                format_optional(c.system_state.wasm_memory_limit)
+            }),
+            ("wasm_memory_threshold", &|c| {
+                c.system_state.wasm_memory_threshold.to_string()
+            }),
+        ],
+    );
+
+    let queues = diff_fields(
+        a,
+        b,
+        &[
+            ("ingress_queue_messages", &|c| {
+                c.system_state
+                    .queues()
+                    .ingress_queue_message_count()
+                    .to_string()
+            }),
+            ("input_queues_messages", &|c| {
+                c.system_state
+                    .queues()
+                    .input_queues_message_count()
+                    .to_string()
+            }),
+            ("input_queues_size_bytes", &|c| {
+                c.system_state
+                    .queues()
+                    .input_queues_size_bytes()
+                    .to_string()
+            }),
+            ("output_queues_messages", &|c| {
+                c.system_state
+                    .queues()
+                    .output_queues_message_count()
+                    .to_string()
+            }),
+            ("call_contexts", &|c| {
+                c.system_state
+                    .call_context_manager()
+                    .map_or(0, |ccm| ccm.call_contexts().len())
+                    .to_string()
+            }),
+        ],
+    );
+
+    let execution_state = diff_fields(
+        a,
+        b,
+        &[
+            ("module_hash", &|c| {
+                format_optional(
+                    c.execution_state
+                        .as_ref()
+                        .map(|es| hex::encode(es.wasm_binary.binary.module_hash())),
+                )
+            }),
+            ("exported_globals", &|c| {
+                format_optional(
+                    c.execution_state
+                        .as_ref()
+                        .map(|es| format!("{:?}", es.exported_globals)),
+                )
+            }),
+            ("wasm_execution_mode", &|c| {
+                format_optional(
+                    c.execution_state
+                        .as_ref()
+                        .map(|es| format!("{:?}", es.wasm_execution_mode)),
+                )
+            }),
+        ],
+    );
+
+    let execution_state_a = a.and_then(|c| c.execution_state.as_ref());
+    let execution_state_b = b.and_then(|c| c.execution_state.as_ref());
+    Ok(CanisterDiff {
+        canister_id: canister_id.to_string(),
+        system_state,
+        settings,
+        queues,
+        execution_state,
+        wasm_memory: diff_memory(
+            execution_state_a.map(|es| &es.wasm_memory),
+            execution_state_b.map(|es| &es.wasm_memory),
+        ),
+        stable_memory: diff_memory(
+            execution_state_a.map(|es| &es.stable_memory),
+            execution_state_b.map(|es| &es.stable_memory),
+        ),
+        snapshots: diff_snapshots(canister_id, state_a, state_b),
+    })
+}
+
+/// Loads the checkpoint at `path`.
+fn load_state(path: PathBuf) -> Result<ReplicatedState, String> {
+    let cp_layout = CompleteCheckpointLayout::new_untracked(path.clone(), Height::new(0))
+        .map_err(|e| format!("failed to create checkpoint layout: {}", e))?;
+
+    let dummy_metrics_registry = ic_metrics::MetricsRegistry::new();
+    let dummy_metrics = CheckpointMetrics::new(&dummy_metrics_registry, crate::commands::logger());
+
+    load_checkpoint(
+        &cp_layout,
+        SubnetType::Application,
+        &dummy_metrics,
+        None,
+        Arc::new(TestPageAllocatorFileDescriptorImpl::new()),
+    )
+    .map_err(|e| format!("failed to load checkpoint at {}: {}", path.display(), e))
+}
+
+fn print_field_changes(title: &str, changes: &[FieldChange]) {
+    if changes.is_empty() {
+        return;
+    }
+    println!("{}:", title);
+    for change in changes {
+        println!("  {}: {} -> {}", change.field, change.before, change.after);
+    }
+}
+
+fn print_memory_diff(title: &str, diff: &MemoryDiff) {
+    if diff.is_empty() {
+        return;
+    }
+    println!("{}:", title);
+    if diff.size_before != diff.size_after {
+        println!(
+            "  size (Wasm pages): {} -> {}",
+            diff.size_before, diff.size_after
+        );
+    }
+    if !diff.changed_pages.is_empty() {
+        let pages: Vec<String> = diff.changed_pages.iter().map(u64::to_string).collect();
+        println!(
+            "  {} changed page(s): {}",
+            diff.changed_pages.len(),
+            pages.join(", ")
+        );
+    }
+}
+
+fn print_snapshots_diff(diff: &SnapshotsDiff) {
+    if diff.is_empty() {
+        return;
+    }
+    println!("Snapshots:");
+    for (label, ids) in [
+        ("added", &diff.added),
+        ("removed", &diff.removed),
+        ("changed", &diff.changed),
+    ] {
+        for id in ids {
+            println!("  {}: {}", label, id);
+        }
+    }
+}
+
+/// `diff_canister` command entry point.
+pub fn do_diff_canister(
+    canister_id: PrincipalId,
+    path_a: PathBuf,
+    path_b: PathBuf,
+    format: OutputFormat,
+) -> Result<(), String> {
+    let canister_id = CanisterId::try_from(canister_id)
+        .map_err(|e| format!("invalid canister ID {}: {}", canister_id, e))?;
+    let state_a = load_state(path_a)?;
+    let state_b = load_state(path_b)?;
+    let diff = diff_canister_states(canister_id, &state_a, &state_b)?;
+
+    match format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&diff)
+                .map_err(|e| format!("failed to serialize diff: {}", e))?;
+            println!("{}", json);
+        }
+        OutputFormat::Text => {
+            if diff.is_empty() {
+                println!("✓ Canister {} is identical", diff.canister_id);
+                return Ok(());
+            }
+            println!("Canister {}", diff.canister_id);
+            print_field_changes("System state", &diff.system_state);
+            print_field_changes("Settings", &diff.settings);
+            print_field_changes("Queues", &diff.queues);
+            print_field_changes("Execution state", &diff.execution_state);
+            print_memory_diff("Wasm memory", &diff.wasm_memory);
+            print_memory_diff("Stable memory", &diff.stable_memory);
+            print_snapshots_diff(&diff.snapshots);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_replicated_state::page_map::PAGE_SIZE;
+    use ic_state_machine_tests::StateMachineBuilder;
+    use ic_types::Cycles;
+
+    fn load_checkpoint_at(
+        env: &ic_state_machine_tests::StateMachine,
+        height: u64,
+    ) -> ReplicatedState {
+        let layout = env
+            .state_manager
+            .state_layout()
+            .checkpoint_verified(Height::new(height))
+            .unwrap();
+        load_state(layout.raw_path().to_path_buf()).unwrap()
+    }
+
+    #[test]
+    fn diff_page_maps_reports_changed_pages() {
+        let a = PageMap::new_for_testing();
+        let mut b = PageMap::new_for_testing();
+        b.update(&[
+            (PageIndex::new(1), &[1; PAGE_SIZE]),
+            (PageIndex::new(3), &[0; PAGE_SIZE]),
+            (PageIndex::new(4), &[2; PAGE_SIZE]),
+        ]);
+
+        // Page 3 is written with zeros, so its contents did not change.
+        assert_eq!(diff_page_maps(&a, &b), vec![1, 4]);
+        assert_eq!(diff_page_maps(&b, &a), vec![1, 4]);
+        assert!(diff_page_maps(&b, &b).is_empty());
+    }
+
+    #[test]
+    fn diff_canister_between_checkpoints() {
+        let env = StateMachineBuilder::new()
+            .with_remove_old_states(false)
+            .build();
+        let canister_id = env.install_canister_wat(r#"(module (memory 1))"#, vec![], None);
+        env.checkpointed_tick();
+
+        env.add_cycles(canister_id, 1_000);
+        env.set_stable_memory(canister_id, &[7; 10]);
+        env.checkpointed_tick();
+        env.state_manager.flush_tip_channel();
+
+        let state_1 = load_checkpoint_at(&env, 1);
+        let state_2 = load_checkpoint_at(&env, 2);
+
+        let diff = diff_canister_states(canister_id, &state_1, &state_1).unwrap();
+        assert!(diff.is_empty());
+
+        let diff = diff_canister_states(canister_id, &state_1, &state_2).unwrap();
+        assert_eq!(
+            diff.system_state
+                .iter()
+                .map(|change| change.field.as_str())
+                .collect::<Vec<_>>(),
+            vec!["cycles_balance"]
+        );
+        assert_eq!(
+            diff.system_state[0].after,
+            (Cycles::new(env.cycle_balance(canister_id))).to_string()
+        );
+        assert!(diff.settings.is_empty());
+        assert!(diff.execution_state.is_empty());
+        assert!(diff.wasm_memory.is_empty());
+        assert_eq!(diff.stable_memory.size_before, 0);
+        assert_eq!(diff.stable_memory.size_after, 1);
+        assert_eq!(diff.stable_memory.changed_pages, vec![0]);
+        assert!(diff.snapshots.is_empty());
+    }
+
+    #[test]
+    fn diff_canister_created_between_checkpoints() {
+        let env = StateMachineBuilder::new()
+            .with_remove_old_states(false)
+            .build();
+        env.checkpointed_tick();
+        let canister_id = env.create_canister_with_cycles(None, Cycles::new(1_000_000), None);
+        env.checkpointed_tick();
+        env.state_manager.flush_tip_channel();
+
+        let state_1 = load_checkpoint_at(&env, 1);
+        let state_2 = load_checkpoint_at(&env, 2);
+
+        let diff = diff_canister_states(canister_id, &state_1, &state_2).unwrap();
+        assert_eq!(
+            diff.system_state[0],
+            FieldChange {
+                field: "exists".to_string(),
+                before: ABSENT.to_string(),
+                after: "true".to_string(),
+            }
+        );
+
+        let unknown_canister_id = CanisterId::from_u64(1_000_000);
+        assert!(diff_canister_states(unknown_canister_id, &state_1, &state_2).is_err());
+    }
+}
@@ -19,6 +19,21 @@ enum Opt {
     #[clap(name = "cdiff")]
     CDiff { path_a: PathBuf, path_b: PathBuf },
 
+    /// Computes a structured diff of a single canister between checkpoints.
+    #[clap(name = "diff_canister", visible_alias = "diff-canister")]
+    DiffCanister {
+        /// ID of the canister to diff.
+        #[clap(long = "canister_id")]
+        canister_id: PrincipalId,
+        /// Path to the first checkpoint.
+        path_a: PathBuf,
+        /// Path to the second checkpoint.
+        path_b: PathBuf,
+        /// Output format.
+        #[clap(long = "format", value_enum, default_value = "text")]
+        format: commands::diff_canister::OutputFormat,
+    },
+
     /// Computes partial state hash that is used for certification.
     #[clap(name = "chash")]
     CHash {
@@ -219,6 +234,12 @@ pub(crate) fn main_inner(args: Vec<String>) {
     let opt = Parser::parse_from(args);
     let result = match opt {
         Opt::CDiff { path_a, path_b } => commands::cdiff::do_diff(path_a, path_b),
+        Opt::DiffCanister {
+            canister_id,
+            path_a,
+            path_b,
+            format,
+        } => commands::diff_canister::do_diff_canister(canister_id, path_a, path_b, format),
         Opt::CHash { path } => commands::chash::do_hash(path),
         Opt::ImportState {
             state,
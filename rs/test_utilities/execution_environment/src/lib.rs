@@ -18,8 +18,8 @@ use ic_error_types::{ErrorCode, RejectCode, UserError};
 pub use ic_execution_environment::ExecutionResponse;
 use ic_execution_environment::{
     execute_canister, CompilationCostHandling, ExecuteMessageResult, ExecutionEnvironment,
-    Hypervisor, IngressFilterMetrics, IngressHistoryWriterImpl, InternalHttpQueryHandler,
-    RoundInstructions, RoundLimits,
+    ExecutionTracer, Hypervisor, IngressFilterMetrics, IngressHistoryWriterImpl,
+    InternalHttpQueryHandler, RoundInstructions, RoundLimits,
 };
 use ic_interfaces::execution_environment::{
     ChainKeyData, ChainKeySettings, ExecutionMode, IngressHistoryWriter, RegistryExecutionSettings,
@@ -1728,6 +1728,7 @@ pub struct ExecutionTestBuilder {
     replica_version: ReplicaVersion,
     precompiled_universal_canister: bool,
     cycles_account_manager_config: Option<CyclesAccountManagerConfig>,
+    execution_tracer: Option<Arc<dyn ExecutionTracer>>,
 }
 
 impl Default for ExecutionTestBuilder {
@@ -1776,6 +1777,7 @@ impl Default for ExecutionTestBuilder {
             replica_version: ReplicaVersion::default(),
             precompiled_universal_canister: true,
             cycles_account_manager_config: None,
+            execution_tracer: None,
         }
     }
 }
@@ -1974,6 +1976,13 @@ impl ExecutionTestBuilder {
         }
     }
 
+    pub fn with_execution_tracer(self, execution_tracer: Arc<dyn ExecutionTracer>) -> Self {
+        Self {
+            execution_tracer: Some(execution_tracer),
+            ..self
+        }
+    }
+
     pub fn with_manual_execution(self) -> Self {
         Self {
             manual_execution: true,
@@ -2391,6 +2400,10 @@ impl ExecutionTestBuilder {
             self.canister_snapshot_baseline_instructions,
             self.canister_snapshot_data_baseline_instructions,
         );
+        let exec_env = match self.execution_tracer {
+            Some(execution_tracer) => exec_env.with_execution_tracer(execution_tracer),
+            None => exec_env,
+        };
         let (query_stats_collector, _) =
             ic_query_stats::init_query_stats(self.log.clone(), &config, &metrics_registry);
 
@@ -338,6 +338,10 @@ pub async fn install_minter(
         kyt_principal: None,
         kyt_fee: None,
         get_utxos_cache_expiration_seconds: None,
+        utxo_consolidation_threshold: None,
+        utxo_consolidation_max_fee_per_vbyte: None,
+        utxo_consolidation_max_fee: None,
+        utxo_selection_strategy: None,
     };
 
     let minter_arg = MinterArg::Init(args);
@@ -370,6 +374,7 @@ pub async fn install_btc_checker(
         btc_network: BtcNetwork::Regtest { json_rpc_url },
         check_mode: CheckMode::Normal,
         num_subnet_nodes: 1,
+        use_bitcoin_canister: None,
     });
 
     install_rust_canister_from_path(
@@ -230,6 +230,8 @@ pub fn test(env: TestEnv) {
             Value::entry("icrc1:max_memo_length", 32u64),
             Value::entry("icrc103:public_allowances", "true"),
             Value::entry("icrc103:max_take_value", 500u64),
+            Value::entry("icrc4:max_transfers", 100u64),
+            Value::entry("icrc4:max_balances", 1_000u64),
         ];
         assert_eq!(
             expected_metadata,
@@ -121,6 +121,9 @@ fn main() -> Result<()> {
                 .add_test(systest!(test_http_calls_to_ic_fails))
                 .add_test(systest!(test_get_hello_world_call))
                 .add_test(systest!(test_post_call))
+                .add_test(systest!(test_put_call))
+                .add_test(systest!(test_patch_call))
+                .add_test(systest!(test_delete_call))
                 .add_test(systest!(test_head_call))
                 .add_test(systest!(test_max_possible_request_size))
                 .add_test(systest!(test_max_possible_request_size_exceeded))
@@ -207,6 +210,7 @@ fn test_enforce_https(env: TestEnv) {
                 url: format!("http://[{webserver_ipv6}]:20443"),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -243,6 +247,7 @@ fn test_transform_function_is_executed(env: TestEnv) {
                 url: format!("https://[{webserver_ipv6}]:20443"),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -285,6 +290,7 @@ fn test_non_existent_transform_function(env: TestEnv) {
                 url: format!("https://[{webserver_ipv6}]:20443"),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -323,6 +329,7 @@ fn test_composite_transform_function_is_executed(env: TestEnv) {
                 url: format!("https://[{webserver_ipv6}]:20443"),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -357,6 +364,7 @@ fn test_no_cycles_attached(env: TestEnv) {
                 url: format!("http://[{webserver_ipv6}]:20443"),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -407,6 +415,7 @@ fn test_max_possible_request_size(env: TestEnv) {
                 url: format!("https://[{webserver_ipv6}]:20443/request_size"),
                 headers,
                 method: HttpMethod::POST,
+                is_replicated: None,
                 body: Some(body),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -451,6 +460,7 @@ fn test_max_possible_request_size_exceeded(env: TestEnv) {
                 url: format!("https://[{webserver_ipv6}]:20443/request_size"),
                 headers,
                 method: HttpMethod::POST,
+                is_replicated: None,
                 body: Some(body),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -486,6 +496,7 @@ fn test_2mb_response_cycle_for_rejection_path(env: TestEnv) {
         url: format!("https://[{webserver_ipv6}]:20443"),
         headers: vec![],
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: Some(TransformContext {
             function: TransformFunc(candid::Func {
@@ -529,6 +540,7 @@ fn test_4096_max_response_cycle_case_1(env: TestEnv) {
         url: format!("https://[{webserver_ipv6}]:20443"),
         headers: vec![],
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: Some(TransformContext {
             function: TransformFunc(candid::Func {
@@ -566,6 +578,7 @@ fn test_4096_max_response_cycle_case_2(env: TestEnv) {
         url: format!("https://[{webserver_ipv6}]:20443"),
         headers: vec![],
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: Some(TransformContext {
             function: TransformFunc(candid::Func {
@@ -611,6 +624,7 @@ fn test_max_response_bytes_2_mb_returns_ok(env: TestEnv) {
                 url: format!("https://[{webserver_ipv6}]:20443"),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: None,
                 max_response_bytes: Some((MAX_MAX_RESPONSE_BYTES) as u64),
@@ -633,6 +647,7 @@ fn test_max_response_bytes_too_large(env: TestEnv) {
                 url: format!("https://[{webserver_ipv6}]:20443"),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: None,
                 max_response_bytes: Some((MAX_MAX_RESPONSE_BYTES + 1) as u64),
@@ -665,6 +680,7 @@ fn test_transform_that_bloats_on_the_2mb_limit(env: TestEnv) {
                 url: format!("https://[{webserver_ipv6}]:20443"),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -695,6 +711,7 @@ fn test_transform_that_bloats_on_the_2mb_limit_with_custom_max_response_bytes(en
                 url: format!("https://[{webserver_ipv6}]:20443"),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -733,6 +750,7 @@ fn test_transform_that_bloats_response_above_2mb_limit(env: TestEnv) {
                 url: format!("https://[{webserver_ipv6}]:20443"),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -774,6 +792,7 @@ fn test_post_request(env: TestEnv) {
                     value: "application/x-www-form-urlencoded".to_string(),
                 }],
                 method: HttpMethod::POST,
+                is_replicated: None,
                 body: Some("satoshi".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -814,6 +833,7 @@ fn test_http_endpoint_response_is_within_limits_with_custom_max_response_bytes(e
                 url: format!("https://[{webserver_ipv6}]:20443/bytes/{}", n),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: None,
                 max_response_bytes: Some(max_response_bytes),
@@ -858,6 +878,7 @@ fn test_http_endpoint_response_is_too_large_with_custom_max_response_bytes(env:
                     url: format!("https://[{webserver_ipv6}]:20443/bytes/{}", n + 1),
                     headers: vec![],
                     method: HttpMethod::GET,
+                    is_replicated: None,
                     body: Some("".as_bytes().to_vec()),
                     transform,
                     max_response_bytes: Some(max_response_bytes),
@@ -897,6 +918,7 @@ fn test_http_endpoint_response_is_within_limits_with_default_max_response_bytes(
                 url: format!("https://[{webserver_ipv6}]:20443/bytes/{}", n),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: None,
                 max_response_bytes: None,
@@ -939,6 +961,7 @@ fn test_http_endpoint_response_is_too_large_with_default_max_response_bytes(env:
                     url: format!("https://[{webserver_ipv6}]:20443/bytes/{}", n + 1),
                     headers: vec![],
                     method: HttpMethod::GET,
+                    is_replicated: None,
                     body: Some("".as_bytes().to_vec()),
                     transform,
                     max_response_bytes: None,
@@ -968,6 +991,7 @@ fn test_http_endpoint_with_delayed_response_is_rejected(env: TestEnv) {
                 url: format!("https://[{webserver_ipv6}]:20443/delay/40"),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -1003,6 +1027,7 @@ fn test_that_redirects_are_not_followed(env: TestEnv) {
                 url: format!("https://[{webserver_ipv6}]:20443/redirect/10"),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -1032,6 +1057,7 @@ fn test_http_calls_to_ic_fails(env: TestEnv) {
                 url: format!("https://[{}]:9090", webserver_ipv6),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -1069,6 +1095,7 @@ fn test_invalid_domain_name(env: TestEnv) {
                 url: "https://xwWPqqbNqxxHmLXdguF4DN9xGq22nczV.com".to_string(),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -1106,6 +1133,7 @@ fn test_invalid_ip(env: TestEnv) {
                 url: "https://240.0.0.0".to_string(),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -1150,6 +1178,7 @@ fn test_get_hello_world_call(env: TestEnv) {
         url,
         headers: vec![],
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: None,
         max_response_bytes: Some(max_response_bytes),
@@ -1192,6 +1221,7 @@ fn test_request_header_total_size_within_the_48_kib_limit(env: TestEnv) {
         url: format!("https://[{}]:20443", webserver_ipv6),
         headers,
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: None,
         max_response_bytes: None,
@@ -1238,6 +1268,7 @@ fn test_request_header_total_size_over_the_48_kib_limit(env: TestEnv) {
         url: format!("https://[{}]:20443", webserver_ipv6),
         headers,
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: None,
         max_response_bytes: None,
@@ -1283,6 +1314,7 @@ fn test_response_header_total_size_within_the_48_kib_limit(env: TestEnv) {
                 url,
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: None,
                 transform: None,
                 max_response_bytes: Some(DEFAULT_MAX_RESPONSE_BYTES),
@@ -1334,6 +1366,7 @@ fn test_response_header_total_size_over_the_48_kib_limit(env: TestEnv) {
                 url,
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: None,
                 transform: None,
                 max_response_bytes: Some(DEFAULT_MAX_RESPONSE_BYTES),
@@ -1368,6 +1401,7 @@ fn test_request_header_name_and_value_within_limits(env: TestEnv) {
         url: format!("https://[{}]:20443", webserver_ipv6),
         headers,
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: None,
         max_response_bytes: None,
@@ -1398,6 +1432,7 @@ fn test_request_header_name_too_long(env: TestEnv) {
         url: format!("https://[{}]:20443", webserver_ipv6),
         headers,
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: None,
         max_response_bytes: None,
@@ -1437,6 +1472,7 @@ fn test_request_header_value_too_long(env: TestEnv) {
         url: format!("https://[{}]:20443", webserver_ipv6),
         headers,
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: None,
         max_response_bytes: None,
@@ -1479,6 +1515,7 @@ fn test_response_header_name_within_limit(env: TestEnv) {
                 url,
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: None,
                 max_response_bytes: None,
@@ -1507,6 +1544,7 @@ fn test_response_header_name_over_limit(env: TestEnv) {
                 url,
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: None,
                 max_response_bytes: None,
@@ -1542,6 +1580,7 @@ fn test_response_header_value_within_limit(env: TestEnv) {
         url,
         headers: vec![],
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: None,
         max_response_bytes: None,
@@ -1572,6 +1611,7 @@ fn test_response_header_value_over_limit(env: TestEnv) {
         url,
         headers: vec![],
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: None,
         max_response_bytes: None,
@@ -1599,9 +1639,26 @@ fn test_response_header_value_over_limit(env: TestEnv) {
 }
 
 fn test_post_call(env: TestEnv) {
-    let handlers = Handlers::new(&env);
-    let webserver_ipv6 = get_universal_vm_address(&env);
-    let expected_body = "POST";
+    test_call_with_body(&env, HttpMethod::POST, "POST");
+}
+
+fn test_put_call(env: TestEnv) {
+    test_call_with_body(&env, HttpMethod::PUT, "PUT");
+}
+
+fn test_patch_call(env: TestEnv) {
+    test_call_with_body(&env, HttpMethod::PATCH, "PATCH");
+}
+
+fn test_delete_call(env: TestEnv) {
+    test_call_with_body(&env, HttpMethod::DELETE, "DELETE");
+}
+
+/// Send a request with a body and the given method to the /anything endpoint,
+/// which echoes the method, headers and body of the request.
+fn test_call_with_body(env: &TestEnv, method: HttpMethod, expected_body: &str) {
+    let handlers = Handlers::new(env);
+    let webserver_ipv6 = get_universal_vm_address(env);
 
     let url = format!("https://[{}]:20443/{}", webserver_ipv6, "anything");
     let body = Some("hello_world".as_bytes().to_vec());
@@ -1620,7 +1677,8 @@ fn test_post_call(env: TestEnv) {
     let request = UnvalidatedCanisterHttpRequestArgs {
         url,
         headers,
-        method: HttpMethod::POST,
+        method,
+        is_replicated: None,
         body,
         transform: None,
         max_response_bytes,
@@ -1669,6 +1727,7 @@ fn test_head_call(env: TestEnv) {
         url,
         headers,
         method: HttpMethod::HEAD,
+        is_replicated: None,
         body,
         transform: None,
         max_response_bytes,
@@ -1722,6 +1781,7 @@ fn test_only_headers_with_custom_max_response_bytes(env: TestEnv) {
                 url,
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: None,
                 transform: None,
                 max_response_bytes,
@@ -1760,6 +1820,7 @@ fn test_only_headers_with_custom_max_response_bytes_exceeded(env: TestEnv) {
                 url,
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: None,
                 transform: None,
                 max_response_bytes,
@@ -1797,6 +1858,7 @@ fn test_non_ascii_url_is_accepted(env: TestEnv) {
         url,
         headers: vec![],
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: None,
         max_response_bytes: Some(max_response_bytes),
@@ -1833,6 +1895,7 @@ fn test_max_url_length(env: TestEnv) {
         url,
         headers: vec![],
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: None,
         max_response_bytes: None,
@@ -1866,6 +1929,7 @@ fn test_max_url_length_exceeded(env: TestEnv) {
         url,
         headers: vec![],
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         transform: None,
         max_response_bytes: None,
@@ -1918,6 +1982,7 @@ fn reference_transform_function_exposed_by_different_canister(env: TestEnv) {
         url,
         headers: vec![],
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         max_response_bytes: None,
         transform: Some(TransformContext {
@@ -1963,6 +2028,7 @@ fn test_max_number_of_response_headers(env: TestEnv) {
                 url,
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: None,
                 transform: None,
                 max_response_bytes: None,
@@ -2000,6 +2066,7 @@ fn test_max_number_of_response_headers_exceeded(env: TestEnv) {
                 url,
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: None,
                 transform: None,
                 max_response_bytes: None,
@@ -2036,6 +2103,7 @@ fn test_max_number_of_request_headers(env: TestEnv) {
             url: format!("https://[{webserver_ipv6}]:20443/anything"),
             headers,
             method: HttpMethod::POST,
+            is_replicated: None,
             body: None,
             transform: None,
             max_response_bytes: None,
@@ -2112,6 +2180,7 @@ fn check_caller_id_on_transform_function(env: TestEnv) {
         url,
         headers: vec![],
         method: HttpMethod::GET,
+        is_replicated: None,
         body: Some("".as_bytes().to_vec()),
         max_response_bytes: None,
         transform: Some(TransformContext {
@@ -2261,6 +2330,9 @@ fn assert_http_json_response(
         HttpMethod::GET => "GET",
         HttpMethod::POST => "POST",
         HttpMethod::HEAD => "HEAD",
+        HttpMethod::PUT => "PUT",
+        HttpMethod::PATCH => "PATCH",
+        HttpMethod::DELETE => "DELETE",
     };
 
     assert_eq!(
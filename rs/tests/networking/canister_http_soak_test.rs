@@ -115,6 +115,7 @@ async fn leave_proxy_canister_running(proxy_canister: &Canister<'_>, url: String
                                 context: context.as_bytes().to_vec(),
                             }),
                             method: HttpMethod::GET,
+                            is_replicated: None,
                             max_response_bytes: None,
                         },
                         cycles: 500_000_000_000,
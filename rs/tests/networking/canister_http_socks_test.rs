@@ -167,6 +167,7 @@ pub fn test(env: TestEnv) {
                                 context: context.as_bytes().to_vec(),
                             }),
                             method: HttpMethod::GET,
+                            is_replicated: None,
                             max_response_bytes: None,
                         },
                         cycles: 500_000_000_000,
@@ -220,6 +221,7 @@ pub fn test(env: TestEnv) {
                                 context: context.as_bytes().to_vec(),
                             }),
                             method: HttpMethod::GET,
+                            is_replicated: None,
                             max_response_bytes: None,
                         },
                         cycles: 500_000_000_000,
@@ -174,6 +174,7 @@ async fn do_request(
                             context: context.as_bytes().to_vec(),
                         }),
                         method: HttpMethod::GET,
+                        is_replicated: None,
                         max_response_bytes: None,
                     },
                     cycles: 500_000_000_000,
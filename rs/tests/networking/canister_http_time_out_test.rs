@@ -55,6 +55,7 @@ pub fn test(env: TestEnv) {
                 url: url_to_succeed.clone(),
                 headers: vec![],
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -64,12 +64,13 @@ pub type BoundedHttpHeaders = BoundedVec<
 //     url : text;
 //     max_response_bytes: opt nat64;
 //     headers : vec http_header;
-//     method : variant { get; head; post };
+//     method : variant { get; head; post; put; patch; delete };
 //     body : opt blob;
 //     transform : opt record {
 //       function : func (record {response : http_response; context : blob}) -> (http_response) query;
 //       context : blob;
 //     };
+//     is_replicated : opt bool;
 //   })`
 #[derive(Clone, PartialEq, Debug, CandidType, Deserialize)]
 pub struct CanisterHttpRequestArgs {
@@ -80,6 +81,9 @@ pub struct CanisterHttpRequestArgs {
     pub body: Option<Vec<u8>>,
     pub method: HttpMethod,
     pub transform: Option<TransformContext>,
+    /// If set to `false`, the request is made by a single node and its
+    /// response is not agreed upon by the other nodes. Defaults to `true`.
+    pub is_replicated: Option<bool>,
 }
 
 impl Payload<'_> for CanisterHttpRequestArgs {}
@@ -92,6 +96,11 @@ impl CanisterHttpRequestArgs {
             .as_ref()
             .map(|transform_context| PrincipalId::from(transform_context.function.0.principal))
     }
+
+    /// Returns whether the request should be made by every node of the subnet.
+    pub fn is_replicated(&self) -> bool {
+        self.is_replicated.unwrap_or(true)
+    }
 }
 
 #[test]
@@ -113,6 +122,7 @@ fn test_http_headers_max_number() {
             headers,
             body: None,
             method: HttpMethod::GET,
+            is_replicated: None,
             transform: None,
         };
 
@@ -166,6 +176,7 @@ fn test_http_headers_max_total_size() {
             headers,
             body: None,
             method: HttpMethod::GET,
+            is_replicated: None,
             transform: None,
         };
 
@@ -213,6 +224,7 @@ fn test_http_headers_max_element_size() {
             headers,
             body: None,
             method: HttpMethod::GET,
+            is_replicated: None,
             transform: None,
         };
 
@@ -290,6 +302,12 @@ pub enum HttpMethod {
     POST,
     #[serde(rename = "head")]
     HEAD,
+    #[serde(rename = "put")]
+    PUT,
+    #[serde(rename = "patch")]
+    PATCH,
+    #[serde(rename = "delete")]
+    DELETE,
 }
 
 /// Represents the response for a canister http request.
@@ -104,6 +104,7 @@ pub enum Method {
     BitcoinGetBlockHeaders,
     BitcoinSendTransaction,
     BitcoinGetCurrentFeePercentiles,
+    BitcoinGetBlockFilter,
     // Private APIs used exclusively by the bitcoin canisters.
     BitcoinSendTransactionInternal, // API for sending transactions to the network.
     BitcoinGetSuccessors,           // API for fetching blocks from the network.
@@ -2990,6 +2991,36 @@ impl Payload<'_> for BitcoinGetSuccessorsArgs {}
 impl Payload<'_> for BitcoinGetSuccessorsResponse {}
 impl Payload<'_> for BitcoinSendTransactionInternalArgs {}
 
+/// Argument of the bitcoin_get_block_filter API.
+/// ```text
+/// record {
+///     network : network;
+///     block_hash : blob;
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct BitcoinGetBlockFilterArgs {
+    pub network: ic_btc_interface::NetworkInRequest,
+    #[serde(with = "serde_bytes")]
+    pub block_hash: Vec<u8>,
+}
+
+impl Payload<'_> for BitcoinGetBlockFilterArgs {}
+
+/// Response of the bitcoin_get_block_filter API. The filter is the BIP-158 basic
+/// filter of the block, or `None` if the filter of the block is not known.
+/// ```text
+/// record {
+///     filter : opt blob;
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct BitcoinGetBlockFilterResponse {
+    pub filter: Option<Vec<u8>>,
+}
+
+impl Payload<'_> for BitcoinGetBlockFilterResponse {}
+
 /// Query methods exported by the management canister.
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Display, EnumIter, EnumString)]
 #[strum(serialize_all = "snake_case")]
@@ -3070,15 +3101,84 @@ pub struct NodeMetricsHistoryResponse {
 
 impl Payload<'_> for NodeMetricsHistoryResponse {}
 
+/// `CandidType` for `CanisterLogRange`
+///
+/// A half-open range `[start, end)` of log record indices or timestamps.
+/// ```text
+/// record {
+///     start: nat64;
+///     end: nat64;
+/// }
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
+pub struct CanisterLogRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl CanisterLogRange {
+    pub fn new(start: u64, end: u64) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns a range that includes all values starting from `start`.
+    pub fn starting_at(start: u64) -> Self {
+        Self {
+            start,
+            end: u64::MAX,
+        }
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        self.start <= value && value < self.end
+    }
+}
+
+/// `CandidType` for `CanisterLogContentFilter`
+/// ```text
+/// variant {
+///     substring: text;
+///     regex: text;
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub enum CanisterLogContentFilter {
+    #[serde(rename = "substring")]
+    Substring(String),
+    #[serde(rename = "regex")]
+    Regex(String),
+}
+
+/// `CandidType` for `CanisterLogFilter`
+///
+/// A record is returned only if it matches all the specified conditions.
+/// ```text
+/// record {
+///     idx_range: opt canister_log_range;
+///     timestamp_nanos_range: opt canister_log_range;
+///     content: opt canister_log_content_filter;
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
+pub struct CanisterLogFilter {
+    pub idx_range: Option<CanisterLogRange>,
+    pub timestamp_nanos_range: Option<CanisterLogRange>,
+    pub content: Option<CanisterLogContentFilter>,
+}
+
 /// `CandidType` for `FetchCanisterLogsRequest`
 /// ```text
 /// record {
 ///     canister_id: principal;
+///     filter: opt canister_log_filter;
+///     max_records: opt nat64;
 /// }
 /// ```
 #[derive(Clone, Debug, Default, CandidType, Deserialize)]
 pub struct FetchCanisterLogsRequest {
     pub canister_id: PrincipalId,
+    pub filter: Option<CanisterLogFilter>,
+    pub max_records: Option<u64>,
 }
 
 impl Payload<'_> for FetchCanisterLogsRequest {}
@@ -3087,9 +3187,21 @@ impl FetchCanisterLogsRequest {
     pub fn new(canister_id: CanisterId) -> Self {
         Self {
             canister_id: canister_id.into(),
+            filter: None,
+            max_records: None,
         }
     }
 
+    pub fn with_filter(mut self, filter: CanisterLogFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn with_max_records(mut self, max_records: u64) -> Self {
+        self.max_records = Some(max_records);
+        self
+    }
+
     pub fn get_canister_id(&self) -> CanisterId {
         CanisterId::unchecked_from_principal(self.canister_id)
     }
@@ -3150,14 +3262,21 @@ impl From<pb_canister_state_bits::CanisterLogRecord> for CanisterLogRecord {
 }
 
 /// `CandidType` for `FetchCanisterLogsResponse`
+///
+/// `next_idx` is the cursor for incremental tailing: all records with a
+/// smaller index have been examined, so passing it as the start of the
+/// `idx_range` in the next request only returns new records.
+/// It is optional as replicas that do not support filtering do not set it.
 /// ```text
 /// record {
 ///     canister_log_records: vec canister_log_record;
+///     next_idx: opt nat64;
 /// }
 /// ```
 #[derive(Clone, PartialEq, Debug, Default, CandidType, Deserialize)]
 pub struct FetchCanisterLogsResponse {
     pub canister_log_records: Vec<CanisterLogRecord>,
+    pub next_idx: Option<u64>,
 }
 
 impl Payload<'_> for FetchCanisterLogsResponse {}
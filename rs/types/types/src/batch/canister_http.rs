@@ -1,8 +1,8 @@
 use crate::{
     canister_http::{
         CanisterHttpReject, CanisterHttpRequestId, CanisterHttpResponse,
-        CanisterHttpResponseContent, CanisterHttpResponseDivergence, CanisterHttpResponseMetadata,
-        CanisterHttpResponseShare, CanisterHttpResponseWithConsensus,
+        CanisterHttpResponseArtifact, CanisterHttpResponseContent, CanisterHttpResponseDivergence,
+        CanisterHttpResponseMetadata, CanisterHttpResponseShare, CanisterHttpResponseWithConsensus,
     },
     crypto::{BasicSig, BasicSigOf, CryptoHash, CryptoHashOf, Signed},
     messages::CallbackId,
@@ -148,6 +148,46 @@ impl TryFrom<pb::CanisterHttpResponseDivergence> for CanisterHttpResponseDiverge
     }
 }
 
+impl From<CanisterHttpResponseArtifact> for pb::CanisterHttpArtifact {
+    fn from(artifact: CanisterHttpResponseArtifact) -> Self {
+        pb::CanisterHttpArtifact {
+            share: Some(artifact.share.into()),
+            response: artifact.response.map(|response| pb::CanisterHttpResponse {
+                id: response.id.get(),
+                timeout: response.timeout.as_nanos_since_unix_epoch(),
+                canister_id: Some(pb::CanisterId::from(response.canister_id)),
+                content: Some(pb::CanisterHttpResponseContent::from(&response.content)),
+            }),
+        }
+    }
+}
+
+impl TryFrom<pb::CanisterHttpArtifact> for CanisterHttpResponseArtifact {
+    type Error = ProxyDecodeError;
+
+    fn try_from(artifact: pb::CanisterHttpArtifact) -> Result<Self, Self::Error> {
+        let share = try_from_option_field(artifact.share, "CanisterHttpArtifact::share")?;
+        let response = artifact
+            .response
+            .map(|response| {
+                Ok::<_, ProxyDecodeError>(CanisterHttpResponse {
+                    id: CanisterHttpRequestId::new(response.id),
+                    timeout: Time::from_nanos_since_unix_epoch(response.timeout),
+                    canister_id: try_from_option_field(
+                        response.canister_id,
+                        "CanisterHttpArtifact::response::canister_id",
+                    )?,
+                    content: try_from_option_field(
+                        response.content,
+                        "CanisterHttpArtifact::response::content",
+                    )?,
+                })
+            })
+            .transpose()?;
+        Ok(CanisterHttpResponseArtifact { share, response })
+    }
+}
+
 impl From<&CanisterHttpResponseContent> for pb::CanisterHttpResponseContent {
     fn from(content: &CanisterHttpResponseContent) -> Self {
         let inner = match content {
@@ -22,6 +22,8 @@
 //! 3b. We gossip [`CanisterHttpResponseShare`]s, until we have enough shares to aggregate them into a
 //!     [`CanisterHttpResponseProof`]. Together with the content, this artifact forms the [`CanisterHttpResponseWithConsensus`],
 //!     which is the artifact we can include into the block to prove consensus on the response.
+//!     The share of a non-replicated request is gossiped together with the content as a
+//!     [`CanisterHttpResponseArtifact`], such that any block maker can include the response.
 //!
 //! 4a. Once the [`CanisterHttpResponseWithConsensus`] has made it into a finalized block, the response is delivered
 //!     to execution to resume the initial call.
@@ -45,8 +47,9 @@ use crate::{
     artifact::{CanisterHttpResponseId, IdentifiableArtifact, PbArtifact},
     crypto::{CryptoHashOf, Signed},
     messages::{CallbackId, RejectContext, Request},
+    node_id_into_protobuf, node_id_try_from_option,
     signature::*,
-    CanisterId, CountBytes, RegistryVersion, Time,
+    CanisterId, CountBytes, NodeId, RegistryVersion, Time,
 };
 use ic_base_types::{NumBytes, PrincipalId};
 use ic_error_types::{ErrorCode, RejectCode, UserError};
@@ -125,6 +128,20 @@ pub struct CanisterHttpRequestContext {
     pub http_method: CanisterHttpMethod,
     pub transform: Option<Transform>,
     pub time: Time,
+    pub replication: Replication,
+}
+
+/// Specifies how many nodes make a [`CanisterHttpRequest`] and hence how
+/// consensus is reached on its response.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default, Deserialize, Serialize)]
+pub enum Replication {
+    /// Every node of the canister http committee makes the request, and a
+    /// threshold of matching response shares is needed to include the response.
+    #[default]
+    FullyReplicated,
+    /// Only the given node makes the request, and its response is included
+    /// with that node's signature alone.
+    NonReplicated(NodeId),
 }
 
 impl From<&CanisterHttpRequestContext> for pb_metadata::CanisterHttpRequestContext {
@@ -155,6 +172,10 @@ impl From<&CanisterHttpRequestContext> for pb_metadata::CanisterHttpRequestConte
                 .map(|transform| transform.context.clone()),
             http_method: pb_metadata::HttpMethod::from(&context.http_method).into(),
             time: context.time.as_nanos_since_unix_epoch(),
+            non_replicated_node_id: match context.replication {
+                Replication::FullyReplicated => None,
+                Replication::NonReplicated(node_id) => Some(node_id_into_protobuf(node_id)),
+            },
         }
     }
 }
@@ -189,6 +210,11 @@ impl TryFrom<pb_metadata::CanisterHttpRequestContext> for CanisterHttpRequestCon
             (None, None) => None,
         };
 
+        let replication = match context.non_replicated_node_id {
+            Some(node_id) => Replication::NonReplicated(node_id_try_from_option(Some(node_id))?),
+            None => Replication::FullyReplicated,
+        };
+
         Ok(CanisterHttpRequestContext {
             request,
             url: context.url,
@@ -213,6 +239,7 @@ impl TryFrom<pb_metadata::CanisterHttpRequestContext> for CanisterHttpRequestCon
                 .try_into()?,
             transform,
             time: Time::from_nanos_since_unix_epoch(context.time),
+            replication,
         })
     }
 }
@@ -332,9 +359,13 @@ impl TryFrom<(Time, &Request, CanisterHttpRequestArgs)> for CanisterHttpRequestC
                 HttpMethod::GET => CanisterHttpMethod::GET,
                 HttpMethod::POST => CanisterHttpMethod::POST,
                 HttpMethod::HEAD => CanisterHttpMethod::HEAD,
+                HttpMethod::PUT => CanisterHttpMethod::PUT,
+                HttpMethod::PATCH => CanisterHttpMethod::PATCH,
+                HttpMethod::DELETE => CanisterHttpMethod::DELETE,
             },
             transform: args.transform.map(From::from),
             time,
+            replication: Replication::FullyReplicated,
         })
     }
 }
@@ -548,6 +579,9 @@ pub enum CanisterHttpMethod {
     GET = 1,
     POST = 2,
     HEAD = 3,
+    PUT = 4,
+    PATCH = 5,
+    DELETE = 6,
 }
 
 impl CanisterHttpMethod {
@@ -556,6 +590,9 @@ impl CanisterHttpMethod {
             CanisterHttpMethod::GET => "GET",
             CanisterHttpMethod::POST => "POST",
             CanisterHttpMethod::HEAD => "HEAD",
+            CanisterHttpMethod::PUT => "PUT",
+            CanisterHttpMethod::PATCH => "PATCH",
+            CanisterHttpMethod::DELETE => "DELETE",
         }
     }
 }
@@ -566,6 +603,9 @@ impl From<&CanisterHttpMethod> for pb_metadata::HttpMethod {
             CanisterHttpMethod::GET => pb_metadata::HttpMethod::Get,
             CanisterHttpMethod::POST => pb_metadata::HttpMethod::Post,
             CanisterHttpMethod::HEAD => pb_metadata::HttpMethod::Head,
+            CanisterHttpMethod::PUT => pb_metadata::HttpMethod::Put,
+            CanisterHttpMethod::PATCH => pb_metadata::HttpMethod::Patch,
+            CanisterHttpMethod::DELETE => pb_metadata::HttpMethod::Delete,
         }
     }
 }
@@ -578,6 +618,9 @@ impl TryFrom<pb_metadata::HttpMethod> for CanisterHttpMethod {
             pb_metadata::HttpMethod::Get => Ok(CanisterHttpMethod::GET),
             pb_metadata::HttpMethod::Post => Ok(CanisterHttpMethod::POST),
             pb_metadata::HttpMethod::Head => Ok(CanisterHttpMethod::HEAD),
+            pb_metadata::HttpMethod::Put => Ok(CanisterHttpMethod::PUT),
+            pb_metadata::HttpMethod::Patch => Ok(CanisterHttpMethod::PATCH),
+            pb_metadata::HttpMethod::Delete => Ok(CanisterHttpMethod::DELETE),
             pb_metadata::HttpMethod::Unspecified => Err(ProxyDecodeError::ValueOutOfRange {
                 typ: "ic_protobuf::state::system_metadata::v1::HttpMethod",
                 err: "Unspecified HttpMethod".to_string(),
@@ -641,23 +684,31 @@ impl crate::crypto::SignedBytesWithoutDomainSeparator for CanisterHttpResponseMe
 }
 
 /// A signature share of of [`CanisterHttpResponseMetadata`].
-///
-/// This is the artifact that will actually be gossiped.
 pub type CanisterHttpResponseShare =
     Signed<CanisterHttpResponseMetadata, BasicSignature<CanisterHttpResponseMetadata>>;
 
-impl IdentifiableArtifact for CanisterHttpResponseShare {
+/// The artifact that will actually be gossiped.
+///
+/// It consists of a [`CanisterHttpResponseShare`] and, if the share belongs to a
+/// non-replicated request, the full [`CanisterHttpResponse`] made by the delegated node.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
+pub struct CanisterHttpResponseArtifact {
+    pub share: CanisterHttpResponseShare,
+    pub response: Option<CanisterHttpResponse>,
+}
+
+impl IdentifiableArtifact for CanisterHttpResponseArtifact {
     const NAME: &'static str = "canisterhttp";
     type Id = CanisterHttpResponseId;
     fn id(&self) -> Self::Id {
-        self.clone()
+        self.share.clone()
     }
 }
 
-impl PbArtifact for CanisterHttpResponseShare {
+impl PbArtifact for CanisterHttpResponseArtifact {
     type PbId = ic_protobuf::types::v1::CanisterHttpShare;
     type PbIdError = ProxyDecodeError;
-    type PbMessage = ic_protobuf::types::v1::CanisterHttpShare;
+    type PbMessage = ic_protobuf::types::v1::CanisterHttpArtifact;
     type PbMessageError = ProxyDecodeError;
 }
 
@@ -705,6 +756,7 @@ mod tests {
                 deadline: NO_DEADLINE,
             },
             time: UNIX_EPOCH,
+            replication: Replication::FullyReplicated,
         };
 
         let expected_size = context.url.len()
@@ -747,6 +799,7 @@ mod tests {
                 deadline: NO_DEADLINE,
             },
             time: UNIX_EPOCH,
+            replication: Replication::FullyReplicated,
         };
 
         let expected_size = context.url.len()
@@ -777,7 +830,7 @@ mod tests {
             CanisterHttpMethod::iter()
                 .map(|x| x as i32)
                 .collect::<Vec<i32>>(),
-            [1, 2, 3]
+            [1, 2, 3, 4, 5, 6]
         );
     }
 }
@@ -597,6 +597,7 @@ pub fn extract_effective_canister_id(
         | Ok(Method::BitcoinSendTransactionInternal)
         | Ok(Method::BitcoinGetSuccessors)
         | Ok(Method::BitcoinGetCurrentFeePercentiles)
+        | Ok(Method::BitcoinGetBlockFilter)
         | Ok(Method::NodeMetricsHistory)
         | Ok(Method::SubnetInfo)
         | Ok(Method::FetchCanisterLogs) => {
@@ -263,6 +263,7 @@ impl Request {
             | Ok(Method::BitcoinSendTransactionInternal)
             | Ok(Method::BitcoinGetSuccessors)
             | Ok(Method::BitcoinGetCurrentFeePercentiles)
+            | Ok(Method::BitcoinGetBlockFilter)
             | Ok(Method::NodeMetricsHistory)
             | Ok(Method::SubnetInfo) => {
                 // No effective canister id.